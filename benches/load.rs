@@ -1,8 +1,11 @@
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{Cursor, Read};
+use std::sync::Arc;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use lopdf::Document;
+use lopdf::encryption::crypt_filters::{Aes256CryptFilter, CryptFilter};
+use lopdf::{dictionary, Document, EncryptionState, EncryptionVersion, Object, Permissions, Stream};
 
 fn bench_load_large(c: &mut Criterion) {
     let mut buffer = Vec::new();
@@ -32,5 +35,52 @@ fn bench_load_encrypted(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_load_large, bench_load_encrypted);
+/// A document of many small dictionaries and streams, saved as is and encrypted with AES-256.
+fn many_objects(count: usize) -> (Vec<u8>, Vec<u8>) {
+    let mut doc = Document::with_version("1.7");
+    for index in 0..count {
+        let object: Object = if index % 2 == 0 {
+            dictionary! { "Index" => index as i64, "Name" => Object::string_literal(format!("object {index}")) }.into()
+        } else {
+            Stream::new(dictionary! {}, format!("BT ({index}) Tj ET").into_bytes()).into()
+        };
+        doc.add_object(object);
+    }
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog" });
+    doc.trailer.set("Root", catalog_id);
+    let mut plain = Vec::new();
+    doc.save_to(&mut plain).unwrap();
+
+    let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+    let version = EncryptionVersion::V5 {
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), crypt_filter)]),
+        file_encryption_key: &[7; 32],
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: "owner",
+        user_password: "",
+        permissions: Permissions::all(),
+    };
+    doc.encrypt(&EncryptionState::try_from(version).unwrap()).unwrap();
+    let mut encrypted = Vec::new();
+    doc.save_to(&mut encrypted).unwrap();
+    (plain, encrypted)
+}
+
+fn bench_load_encrypted_many_objects(c: &mut Criterion) {
+    let (plain, encrypted) = many_objects(40_000);
+    assert_eq!(
+        Document::load_mem(&encrypted).unwrap().objects.len(),
+        Document::load_mem(&plain).unwrap().objects.len()
+    );
+
+    let mut group = c.benchmark_group("load_40k_objects");
+    group.sample_size(10);
+    group.bench_function("plain", |b| b.iter(|| Document::load_mem(&plain).unwrap()));
+    group.bench_function("aes256", |b| b.iter(|| Document::load_mem(&encrypted).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_load_large, bench_load_encrypted, bench_load_encrypted_many_objects);
 criterion_main!(benches);
@@ -0,0 +1,17 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use lopdf::Document;
+
+fn bench_load_mmap(c: &mut Criterion) {
+    let path = "assets/AnnotationDemo.pdf";
+
+    let mut group = c.benchmark_group("load_file");
+    group.bench_function("load", |b| b.iter(|| Document::load(path).unwrap()));
+    // Safety: the asset isn't written to while the benchmark runs.
+    group.bench_function("load_mmap", |b| {
+        b.iter(|| unsafe { Document::load_mmap(path) }.unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_load_mmap);
+criterion_main!(benches);
@@ -3,7 +3,7 @@
 use std::io::{Cursor, Read};
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use lopdf::{dictionary, Document, Object, ObjectStream};
+use lopdf::{dictionary, Document, MetadataOptions, Object, ObjectStream};
 
 fn bench_object_stream_compress(c: &mut Criterion) {
     // Create 100 simple dictionary objects
@@ -41,9 +41,51 @@ fn bench_object_stream_parse(c: &mut Criterion) {
     });
 }
 
+/// A document of `count` pages, all of them compressed into object streams.
+fn compressed_pages(count: usize) -> Vec<u8> {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let kids: Vec<Object> = (0..count)
+        .map(|_| {
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            })
+            .into()
+        })
+        .collect();
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => count as i64 }),
+    );
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+    let mut buffer = Vec::new();
+    doc.save_modern(&mut buffer).unwrap();
+    buffer
+}
+
+fn bench_metadata_compressed_objects(c: &mut Criterion) {
+    let buffer = compressed_pages(3000);
+    let options = MetadataOptions {
+        page_dimensions: true,
+        ..MetadataOptions::default()
+    };
+    assert_eq!(Document::load_metadata_mem_with_options(&buffer, &options).unwrap().page_dimensions.len(), 3000);
+
+    let mut group = c.benchmark_group("metadata_3000_compressed_pages");
+    group.sample_size(10);
+    group.bench_function("page_dimensions", |b| {
+        b.iter(|| Document::load_metadata_mem_with_options(&buffer, &options).unwrap())
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_object_stream_compress,
-    bench_object_stream_parse
+    bench_object_stream_parse,
+    bench_metadata_compressed_objects
 );
 criterion_main!(benches);
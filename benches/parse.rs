@@ -32,5 +32,31 @@ fn bench_load_incremental_pdf(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_load, bench_load_incremental_pdf);
+/// A small document before and after 50 MB of garbage: the search for the `%PDF-` header crosses
+/// the garbage before it, and the garbage after it hides the `%%EOF` marker, so that the
+/// cross-reference table is rebuilt by scanning the whole file.
+fn bench_load_with_garbage(c: &mut Criterion) {
+    let mut document = Vec::new();
+    File::open("assets/example.pdf")
+        .unwrap()
+        .read_to_end(&mut document)
+        .unwrap();
+    let garbage = b"x".repeat(50 * 1024 * 1024);
+
+    let leading = [&garbage[..], &document].concat();
+    c.bench_function("load_with_leading_garbage", |b| {
+        b.iter(|| {
+            Document::load_mem(&leading).unwrap();
+        })
+    });
+
+    let trailing = [&document[..], &garbage].concat();
+    c.bench_function("load_with_trailing_garbage", |b| {
+        b.iter(|| {
+            Document::load_mem(&trailing).unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, bench_load, bench_load_incremental_pdf, bench_load_with_garbage);
 criterion_main!(benches);
@@ -37,7 +37,8 @@ fn bench_save_modern(c: &mut Criterion) {
             let options = SaveOptions::builder()
                 .use_xref_streams(true)
                 .use_object_streams(true)
-                .build();
+                .build()
+                .unwrap();
             let mut doc_clone = doc.clone();
             doc_clone.save_with_options(&mut output, options).unwrap();
         })
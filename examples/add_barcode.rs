@@ -1,7 +1,7 @@
 use lopdf::xobject;
 use lopdf::Document;
 use std::fmt::Write;
-use std::io::{Error, ErrorKind};
+use std::io::Error;
 use std::path::Path;
 use std::str::FromStr;
 
@@ -70,7 +70,7 @@ fn generate_operations(rects: Vec<(f64, f64, f64, f64, u8)>) -> String {
 
 #[cfg(not(feature = "async"))]
 fn load_pdf<P: AsRef<Path>>(path: P) -> Result<Document, Error> {
-    Document::load(path).map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
+    Document::load(path).map_err(|e| Error::other(e.to_string()))
 }
 
 #[cfg(feature = "async")]
@@ -78,7 +78,7 @@ fn load_pdf<P: AsRef<Path>>(path: P) -> Result<Document, Error> {
     Ok(Builder::new_current_thread().build().unwrap().block_on(async move {
         Document::load(path)
             .await
-            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
+            .map_err(|e| Error::other(e.to_string()))
     })?)
 }
 
@@ -164,11 +164,9 @@ fn analyze_document(doc: &Document) {
                     }
                     
                     // Check resources
-                    if let Ok(resources) = page_dict.get(b"Resources") {
-                        if let Object::Reference(ref_id) = resources {
-                            if compressed_objects.contains_key(&ref_id.0) {
-                                println!("  ⚠️  Resources {} {} R is compressed!", ref_id.0, ref_id.1);
-                            }
+                    if let Ok(Object::Reference(ref_id)) = page_dict.get(b"Resources") {
+                        if compressed_objects.contains_key(&ref_id.0) {
+                            println!("  ⚠️  Resources {} {} R is compressed!", ref_id.0, ref_id.1);
                         }
                     }
                 }
@@ -191,13 +189,11 @@ fn analyze_document(doc: &Document) {
     println!("\n{}", "=".repeat(80));
     println!("Critical Objects Check:");
     
-    if let Ok(root) = doc.trailer.get(b"Root") {
-        if let Object::Reference(root_id) = root {
-            if compressed_objects.contains_key(&root_id.0) {
-                println!("⚠️  WARNING: Catalog (Root) object {} is compressed!", root_id.0);
-            } else {
-                println!("✓ Catalog (Root) object {} is not compressed", root_id.0);
-            }
+    if let Ok(Object::Reference(root_id)) = doc.trailer.get(b"Root") {
+        if compressed_objects.contains_key(&root_id.0) {
+            println!("⚠️  WARNING: Catalog (Root) object {} is compressed!", root_id.0);
+        } else {
+            println!("✓ Catalog (Root) object {} is not compressed", root_id.0);
         }
     }
 }
\ No newline at end of file
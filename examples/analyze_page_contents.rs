@@ -80,14 +80,14 @@ fn analyze_pages(doc: &Document) {
                 match page_dict.get(b"Contents") {
                     Ok(Object::Reference(content_id)) => {
                         println!("  Contents: {} {} R", content_id.0, content_id.1);
-                        check_content_stream(&doc, *content_id);
+                        check_content_stream(doc, *content_id);
                     }
                     Ok(Object::Array(contents)) => {
                         println!("  Contents array with {} elements:", contents.len());
                         for (i, content_ref) in contents.iter().enumerate() {
                             if let Object::Reference(content_id) = content_ref {
                                 println!("    [{}] {} {} R", i, content_id.0, content_id.1);
-                                check_content_stream(&doc, *content_id);
+                                check_content_stream(doc, *content_id);
                             }
                         }
                     }
@@ -103,7 +103,7 @@ fn analyze_pages(doc: &Document) {
                 match page_dict.get(b"Resources") {
                     Ok(Object::Reference(res_id)) => {
                         println!("  Resources: {} {} R", res_id.0, res_id.1);
-                        check_object_location(&doc, *res_id);
+                        check_object_location(doc, *res_id);
                     }
                     Ok(Object::Dictionary(_)) => {
                         println!("  Resources: Inline dictionary");
@@ -121,7 +121,7 @@ fn analyze_pages(doc: &Document) {
             }
             Err(e) => {
                 println!("  ERROR: Cannot get page object: {}", e);
-                check_object_location(&doc, page_id);
+                check_object_location(doc, page_id);
             }
         }
     }
@@ -45,11 +45,7 @@ fn main() {
     doc.trailer.set("Root", catalog_id);
     
     // Save with object streams
-    let options = SaveOptions {
-        use_object_streams: true,
-        use_xref_streams: true,
-        ..Default::default()
-    };
+    let options = SaveOptions::builder().use_object_streams(true).use_xref_streams(true).build().unwrap();
     
     let filename = "test_raw_objstream.pdf";
     let mut buffer = Vec::new();
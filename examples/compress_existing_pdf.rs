@@ -71,7 +71,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .use_xref_streams(true)
         .max_objects_per_stream(200)  // More objects per stream
         .compression_level(9)         // Maximum compression
-        .build();
+        .build()?;
     doc.save_with_options(&mut custom_file, options)?;
     drop(custom_file);
     let custom_size = std::fs::metadata(&output_custom)?.len();
@@ -47,15 +47,13 @@ fn main() {
     
     // Save with object streams
     println!("\nSaving with object streams (compression level 6)...");
-    let options = SaveOptions {
-        use_object_streams: true,
-        use_xref_streams: true,  // MUST use xref streams with object streams!
-        object_stream_config: lopdf::ObjectStreamConfig {
-            max_objects_per_stream: 100,
-            compression_level: 6,
-        },
-        ..Default::default()
-    };
+    let options = SaveOptions::builder()
+        .use_object_streams(true)
+        .use_xref_streams(true)
+        .max_objects_per_stream(100)
+        .compression_level(6)
+        .build()
+        .unwrap();
     
     let mut buffer = Vec::new();
     doc.save_with_options(&mut buffer, options).unwrap();
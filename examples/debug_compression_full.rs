@@ -52,11 +52,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Save with object streams - with detailed logging
     println!("\n3. Saving with object streams (with detailed logging)...");
-    let options = SaveOptions {
-        use_object_streams: true,
-        use_xref_streams: true,
-        ..Default::default()
-    };
+    let options = SaveOptions::builder().use_object_streams(true).use_xref_streams(true).build().unwrap();
     
     // Simulate the compression process to log what would happen
     let mut compressible_objects = Vec::new();
@@ -176,7 +172,7 @@ fn analyze_document(doc: &Document) -> DocumentAnalysis {
     };
     
     // Count compressed objects
-    for (_id, xref_entry) in &doc.reference_table.entries {
+    for xref_entry in doc.reference_table.entries.values() {
         if let lopdf::xref::XrefEntry::Compressed { .. } = xref_entry {
             analysis.compressed_objects += 1;
         }
@@ -214,21 +210,19 @@ fn analyze_document(doc: &Document) -> DocumentAnalysis {
     
     // Find content streams
     for &page_id in &analysis.page_objects {
-        if let Ok(page_obj) = doc.get_object(page_id) {
-            if let Object::Dictionary(page_dict) = page_obj {
-                match page_dict.get(b"Contents") {
-                    Ok(Object::Reference(content_id)) => {
-                        analysis.content_streams.push(*content_id);
-                    }
-                    Ok(Object::Array(contents)) => {
-                        for content_ref in contents {
-                            if let Object::Reference(content_id) = content_ref {
-                                analysis.content_streams.push(*content_id);
-                            }
+        if let Ok(Object::Dictionary(page_dict)) = doc.get_object(page_id) {
+            match page_dict.get(b"Contents") {
+                Ok(Object::Reference(content_id)) => {
+                    analysis.content_streams.push(*content_id);
+                }
+                Ok(Object::Array(contents)) => {
+                    for content_ref in contents {
+                        if let Object::Reference(content_id) = content_ref {
+                            analysis.content_streams.push(*content_id);
                         }
                     }
-                    _ => {}
                 }
+                _ => {}
             }
         }
     }
@@ -306,10 +300,8 @@ fn verify_critical_objects(doc: &Document, log: &mut File) -> std::io::Result<()
                 writeln!(log, "Page {} ({} {} R): OK - {}", num, page_id.0, page_id.1, describe_object(obj))?;
                 
                 // Check if it's compressed
-                if let Some(xref) = doc.reference_table.get(page_id.0) {
-                    if let lopdf::xref::XrefEntry::Compressed { container, index } = xref {
-                        writeln!(log, "  ERROR: Page is compressed in stream {} at index {}!", container, index)?;
-                    }
+                if let Some(lopdf::xref::XrefEntry::Compressed { container, index }) = doc.reference_table.get(page_id.0) {
+                    writeln!(log, "  ERROR: Page is compressed in stream {} at index {}!", container, index)?;
                 }
                 
                 // Check page contents
@@ -368,14 +360,12 @@ fn check_orphaned_references(doc: &Document, log: &mut File) -> std::io::Result<
         match doc.get_object(*ref_id) {
             Ok(_) => {
                 // Check if it's in a compressed stream
-                if let Some(xref) = doc.reference_table.get(ref_id.0) {
-                    if let lopdf::xref::XrefEntry::Compressed { container, .. } = xref {
-                        // Make sure the container exists
-                        if doc.get_object((*container, 0)).is_err() {
-                            writeln!(log, "ERROR: Reference {} {} R from {} ({} {} R) points to compressed object in non-existent stream {}!", 
-                                ref_id.0, ref_id.1, location, from_id.0, from_id.1, container)?;
-                            orphaned_count += 1;
-                        }
+                if let Some(lopdf::xref::XrefEntry::Compressed { container, .. }) = doc.reference_table.get(ref_id.0) {
+                    // Make sure the container exists
+                    if doc.get_object((*container, 0)).is_err() {
+                        writeln!(log, "ERROR: Reference {} {} R from {} ({} {} R) points to compressed object in non-existent stream {}!", 
+                            ref_id.0, ref_id.1, location, from_id.0, from_id.1, container)?;
+                        orphaned_count += 1;
                     }
                 }
             }
@@ -393,7 +383,9 @@ fn check_orphaned_references(doc: &Document, log: &mut File) -> std::io::Result<
     Ok(())
 }
 
-fn collect_references(obj: &Object, refs: &mut HashMap<(u32, u16), (String, (u32, u16))>, from_id: (u32, u16)) {
+type ReferenceMap = HashMap<(u32, u16), (String, (u32, u16))>;
+
+fn collect_references(obj: &Object, refs: &mut ReferenceMap, from_id: (u32, u16)) {
     match obj {
         Object::Reference(ref_id) => {
             refs.insert(*ref_id, ("direct".to_string(), from_id));
@@ -449,7 +441,7 @@ fn validate_with_external_tools(pdf_path: &str) {
     // Try to use qpdf if available
     println!("\nTrying qpdf --check...");
     match std::process::Command::new("qpdf")
-        .args(&["--check", pdf_path])
+        .args(["--check", pdf_path])
         .output() 
     {
         Ok(output) => {
@@ -468,7 +460,7 @@ fn validate_with_external_tools(pdf_path: &str) {
     // Try to use mutool if available
     println!("\nTrying mutool info...");
     match std::process::Command::new("mutool")
-        .args(&["info", pdf_path])
+        .args(["info", pdf_path])
         .output() 
     {
         Ok(output) => {
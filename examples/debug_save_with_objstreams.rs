@@ -37,10 +37,7 @@ fn main() {
     println!("\nSaving with object streams...");
     
     // Save with object streams
-    let options = SaveOptions {
-        use_object_streams: true,
-        ..Default::default()
-    };
+    let options = SaveOptions::builder().use_object_streams(true).build().unwrap();
     
     let mut buffer = Vec::new();
     doc.save_with_options(&mut buffer, options).unwrap();
@@ -69,7 +66,7 @@ fn main() {
                         println!("  Content size: {} bytes", stream.content.len());
                         
                         // Check if it looks compressed
-                        let looks_compressed = stream.content.iter().take(10).any(|&b| b > 127 || b < 32);
+                        let looks_compressed = stream.content.iter().take(10).any(|&b| !(32..=127).contains(&b));
                         println!("  Content looks compressed: {}", looks_compressed);
                         
                         if !looks_compressed {
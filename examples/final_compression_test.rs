@@ -65,11 +65,7 @@ fn main() {
     println!("  - 10 Annotation objects (should be compressed)");
     
     // Save with object streams
-    let options = SaveOptions {
-        use_object_streams: true,
-        use_xref_streams: true,
-        ..Default::default()
-    };
+    let options = SaveOptions::builder().use_object_streams(true).use_xref_streams(true).build().unwrap();
     
     let mut buffer = Vec::new();
     doc.save_with_options(&mut buffer, options).unwrap();
@@ -85,7 +81,7 @@ fn main() {
     let mut obj_stream_count = 0;
     let mut compressed_count = 0;
     
-    for (_id, obj) in &loaded.objects {
+    for obj in loaded.objects.values() {
         if let Object::Stream(stream) = obj {
             if let Ok(type_obj) = stream.dict.get(b"Type") {
                 if let Ok(type_name) = type_obj.as_name() {
@@ -109,18 +105,14 @@ fn main() {
     println!("\nVerifying critical objects:");
     
     // Check catalog
-    if let Ok(root_ref) = loaded.trailer.get(b"Root") {
-        if let Object::Reference(cat_id) = root_ref {
-            check_not_compressed(&loaded, *cat_id, "Catalog");
-        }
+    if let Ok(Object::Reference(cat_id)) = loaded.trailer.get(b"Root") {
+        check_not_compressed(&loaded, *cat_id, "Catalog");
     }
     
     // Check pages tree
     if let Ok(catalog) = loaded.catalog() {
-        if let Ok(pages_ref) = catalog.get(b"Pages") {
-            if let Object::Reference(pages_id) = pages_ref {
-                check_not_compressed(&loaded, *pages_id, "Pages tree");
-            }
+        if let Ok(Object::Reference(pages_id)) = catalog.get(b"Pages") {
+            check_not_compressed(&loaded, *pages_id, "Pages tree");
         }
     }
     
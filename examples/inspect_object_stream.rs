@@ -39,9 +39,7 @@ fn main() {
                         
                         // List first 20 objects
                         println!("\nFirst 20 objects in stream:");
-                        let mut count = 0;
-                        for ((id, generation), obj) in &obj_stream.objects {
-                            if count >= 20 { break; }
+                        for ((id, generation), obj) in obj_stream.objects.iter().take(20) {
                             println!("  {} {} R: {:?}", id, generation, obj.type_name().unwrap_or(b"Unknown"));
                             
                             // If it's a dictionary, show some keys
@@ -49,7 +47,6 @@ fn main() {
                                 let key_count = dict.len();
                                 println!("    Dictionary with {} keys", key_count);
                             }
-                            count += 1;
                         }
                         
                         // Check if any page-related objects are in there
@@ -83,18 +80,14 @@ fn main() {
             println!("\n\nChecking critical object locations:");
             
             // Check catalog
-            if let Ok(root_ref) = doc.trailer.get(b"Root") {
-                if let Object::Reference(root_id) = root_ref {
-                    check_object_status(&doc, *root_id, "Catalog (Root)");
-                }
+            if let Ok(Object::Reference(root_id)) = doc.trailer.get(b"Root") {
+                check_object_status(&doc, *root_id, "Catalog (Root)");
             }
             
             // Check pages tree
             if let Ok(catalog) = doc.catalog() {
-                if let Ok(pages_ref) = catalog.get(b"Pages") {
-                    if let Object::Reference(pages_id) = pages_ref {
-                        check_object_status(&doc, *pages_id, "Pages tree root");
-                    }
+                if let Ok(Object::Reference(pages_id)) = catalog.get(b"Pages") {
+                    check_object_status(&doc, *pages_id, "Pages tree root");
                 }
             }
             
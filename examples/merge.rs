@@ -248,7 +248,7 @@ fn main() {
         // Set new "Kids" list (collected from documents pages) for "Pages"
         dictionary.set(
             "Kids",
-            documents_pages.into_keys().map(|object_id| Object::Reference(object_id))
+            documents_pages.into_keys().map(Object::Reference)
                 .collect::<Vec<_>>(),
         );
 
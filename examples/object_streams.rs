@@ -109,7 +109,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .use_xref_streams(true)
         .max_objects_per_stream(10)
         .compression_level(9)
-        .build();
+        .build()?;
     doc.save_with_options(&mut custom_buffer, options)?;
     let custom_size = custom_buffer.len();
     
@@ -54,7 +54,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Simulate compression
     let mut would_compress = Vec::new();
-    for (&id, _obj) in &doc.objects {
+    for &id in doc.objects.keys() {
         if !non_compressible.contains(&id) {
             would_compress.push(id);
         }
@@ -73,11 +73,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Save with object streams
     println!("Saving with object streams...");
-    let options = SaveOptions {
-        use_object_streams: true,
-        use_xref_streams: true,
-        ..Default::default()
-    };
+    let options = SaveOptions::builder().use_object_streams(true).use_xref_streams(true).build().unwrap();
     doc.save_with_options(&mut std::fs::File::create("test_with_objstm.pdf")?, options)?;
     
     // Check file sizes
@@ -96,7 +92,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut objstm_count = 0;
     let mut compressed_count = 0;
     
-    for (_id, obj) in &compressed_doc.objects {
+    for obj in compressed_doc.objects.values() {
         if let Object::Stream(stream) = obj {
             if let Ok(type_obj) = stream.dict.get(b"Type") {
                 if let Ok(type_name) = type_obj.as_name() {
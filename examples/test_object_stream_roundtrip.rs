@@ -69,10 +69,7 @@ fn main() {
     
     // Save with object streams
     println!("Saving original PDF with object streams...");
-    let options = SaveOptions {
-        use_object_streams: true,
-        ..Default::default()
-    };
+    let options = SaveOptions::builder().use_object_streams(true).build().unwrap();
     
     let mut original_bytes = Vec::new();
     doc.save_with_options(&mut original_bytes, options.clone()).unwrap();
@@ -54,7 +54,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .use_xref_streams(true)
         .max_objects_per_stream(100)
         .compression_level(6)
-        .build();
+        .build()?;
     doc.save_with_options(&mut with_objstm, options)?;
     
     // Analyze results
@@ -110,7 +110,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut objstm_found = 0;
     let mut compressed_objects = 0;
     
-    for (_, obj) in &compressed_doc.objects {
+    for obj in compressed_doc.objects.values() {
         if let Object::Stream(stream) = obj {
             if let Ok(type_obj) = stream.dict.get(b"Type") {
                 if let Ok(type_name) = type_obj.as_name() {
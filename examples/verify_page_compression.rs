@@ -59,7 +59,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut pages_count = 0;
     let mut catalog_count = 0;
     
-    for (_id, obj) in &doc.objects {
+    for obj in doc.objects.values() {
         if let Object::Dictionary(dict) = obj {
             if let Ok(type_obj) = dict.get(b"Type") {
                 if let Ok(type_name) = type_obj.as_name() {
@@ -42,7 +42,7 @@ fn main() {
             
             // Check for object streams
             let mut obj_stream_count = 0;
-            for (_id, obj) in &doc.objects {
+            for obj in doc.objects.values() {
                 if let Object::Stream(stream) = obj {
                     if let Ok(type_obj) = stream.dict.get(b"Type") {
                         if let Ok(type_name) = type_obj.as_name() {
@@ -54,7 +54,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let options = SaveOptions::builder()
         .use_object_streams(true)
         .use_xref_streams(true)
-        .build();
+        .build()?;
     
     let mut compressed_output = Vec::new();
     doc.save_with_options(&mut compressed_output, options)?;
@@ -0,0 +1,281 @@
+use crate::common_data_structures::{decode_text_string, text_string};
+use crate::sanitize::rectangle;
+use crate::signature::append_to_array;
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result, Stream};
+
+/// The icon a viewer draws for a file attachment annotation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileAttachmentIcon {
+    Graph,
+    #[default]
+    PushPin,
+    Paperclip,
+    Tag,
+}
+
+impl FileAttachmentIcon {
+    fn name(self) -> &'static str {
+        match self {
+            FileAttachmentIcon::Graph => "Graph",
+            FileAttachmentIcon::PushPin => "PushPin",
+            FileAttachmentIcon::Paperclip => "Paperclip",
+            FileAttachmentIcon::Tag => "Tag",
+        }
+    }
+}
+
+/// A file embedded in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachedFile {
+    /// The file name of the file specification
+    pub name: String,
+    /// The MIME type of the file, the /Subtype of its embedded file stream
+    pub mime_type: Option<String>,
+    /// The decoded content of the file
+    pub data: Vec<u8>,
+}
+
+/// An annotation of a page.
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    /// The annotation's object, `None` for annotations not yet added to a document
+    pub id: Option<ObjectId>,
+    pub dict: Dictionary,
+    /// The file name and embedded file stream of a file attachment annotation
+    file: Option<(String, Stream)>,
+}
+
+impl Annotation {
+    /// Create a file attachment annotation at `rect` on its page, embedding `data` as a file
+    /// named `name`. Add it to a page with [`Document::add_annotation`].
+    pub fn file_attachment(
+        rect: [f32; 4], name: &str, data: Vec<u8>, mime_type: Option<&str>, icon: FileAttachmentIcon,
+    ) -> Annotation {
+        let mut stream_dict = dictionary! {
+            "Type" => "EmbeddedFile",
+            "Params" => dictionary! { "Size" => data.len() as i64 },
+        };
+        if let Some(mime_type) = mime_type {
+            stream_dict.set("Subtype", Object::Name(mime_type.as_bytes().to_vec()));
+        }
+        let dict = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "FileAttachment",
+            "Rect" => rect.iter().map(|&value| Object::Real(value)).collect::<Vec<_>>(),
+            "Contents" => text_string(name),
+            "Name" => icon.name(),
+        };
+        Annotation {
+            id: None,
+            dict,
+            file: Some((name.to_string(), Stream::new(stream_dict, data))),
+        }
+    }
+
+    /// Read the annotation `dict` of `doc`, loading the embedded file of file attachment
+    /// annotations.
+    fn read(id: Option<ObjectId>, dict: &Dictionary, doc: &Document) -> Annotation {
+        let file = if dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"FileAttachment") {
+            dict.get_deref(b"FS", doc)
+                .and_then(Object::as_dict)
+                .ok()
+                .and_then(|file_spec| embedded_file(file_spec, doc))
+        } else {
+            None
+        };
+        let mut dict = dict.clone();
+        // The rectangle and each of its coordinates may be indirect.
+        if let Ok(rect) = dict.get(b"Rect").and_then(|rect| doc.resolve_deep(rect, 2)) {
+            dict.set("Rect", rect);
+        }
+        Annotation { id, dict, file }
+    }
+
+    /// The /Subtype of the annotation, e.g. `Text`, `Link` or `FileAttachment`.
+    pub fn subtype(&self) -> Option<&[u8]> {
+        self.dict.get(b"Subtype").and_then(Object::as_name).ok()
+    }
+
+    /// The location of the annotation on its page, normalized to lower left and upper right
+    /// corners.
+    pub fn rect(&self) -> Option<[f32; 4]> {
+        rectangle(self.dict.get(b"Rect").ok()?)
+    }
+
+    /// The file of a file attachment annotation, with its content decompressed. Returns `None`
+    /// for other annotations, files that aren't embedded and streams that can't be decoded.
+    pub fn file(&self) -> Option<AttachedFile> {
+        let (name, stream) = self.file.as_ref()?;
+        let data = if stream.is_compressed() {
+            stream.decompressed_content().ok()?
+        } else {
+            stream.content.clone()
+        };
+        Some(AttachedFile {
+            name: name.clone(),
+            mime_type: stream
+                .dict
+                .get(b"Subtype")
+                .and_then(Object::as_name)
+                .ok()
+                .map(|mime_type| String::from_utf8_lossy(mime_type).into_owned()),
+            data,
+        })
+    }
+}
+
+/// Get the file name and embedded file stream of a file specification dictionary.
+fn embedded_file(file_spec: &Dictionary, doc: &Document) -> Option<(String, Stream)> {
+    let name = [b"UF".as_slice(), b"F"]
+        .iter()
+        .find_map(|key| file_spec.get_deref(key, doc).and_then(decode_text_string).ok())
+        .unwrap_or_default();
+    let streams = file_spec.get_deref(b"EF", doc).and_then(Object::as_dict).ok()?;
+    let stream = [b"UF".as_slice(), b"F"]
+        .iter()
+        .find_map(|key| streams.get_deref(key, doc).and_then(Object::as_stream).ok())?;
+    Some((name, stream.clone()))
+}
+
+impl Document {
+    /// Get the annotations of a page, see [`Document::get_page_annotations`].
+    pub fn get_annotations(&self, page_id: ObjectId) -> Result<Vec<Annotation>> {
+        let page = self.get_dictionary(page_id)?;
+        let Ok(annots) = page.get_deref(b"Annots", self).and_then(Object::as_array) else {
+            return Ok(Vec::new());
+        };
+        Ok(annots
+            .iter()
+            .filter_map(|annot| {
+                let id = annot.as_reference().ok();
+                let dict = self.dereference(annot).ok()?.1.as_dict().ok()?;
+                Some(Annotation::read(id, dict, self))
+            })
+            .collect())
+    }
+
+    /// Add an annotation to a page, with the file of a file attachment annotation compressed in
+    /// an embedded file stream. Returns the id of the annotation.
+    pub fn add_annotation(&mut self, page_id: ObjectId, annotation: Annotation) -> Result<ObjectId> {
+        let Annotation { mut dict, file, .. } = annotation;
+        if let Some((name, mut stream)) = file {
+            stream.compress()?;
+            let stream_id = self.add_object(stream);
+            let file_spec_id = self.add_object(dictionary! {
+                "Type" => "Filespec",
+                "F" => text_string(&name),
+                "UF" => text_string(&name),
+                "EF" => dictionary! { "F" => stream_id, "UF" => stream_id },
+            });
+            dict.set("FS", file_spec_id);
+        }
+        dict.set("P", page_id);
+        let annot_id = self.add_object(dict);
+        append_to_array(self, page_id, b"Annots", annot_id)?;
+        Ok(annot_id)
+    }
+
+    /// Remove an annotation from a page, with the replies, grouped annotations and review states
+    /// of its comment thread and their popups. Returns the number of annotations removed.
+    pub fn remove_annotation(&mut self, page_id: ObjectId, annot_id: ObjectId) -> Result<usize> {
+        if !self.page_annotation_ids(page_id).contains(&annot_id) {
+            return Err(Error::ObjectNotFound(annot_id));
+        }
+        let mut removed = vec![annot_id];
+        removed.extend(self.annotation_replies(page_id, annot_id));
+        let popups: Vec<ObjectId> = removed
+            .iter()
+            .filter_map(|&id| self.get_dictionary(id).ok()?.get(b"Popup").and_then(Object::as_reference).ok())
+            .collect();
+        removed.extend(popups);
+
+        let annots = match self.get_dictionary(page_id)?.get(b"Annots") {
+            Ok(Object::Reference(id)) => {
+                let id = *id;
+                self.get_object_mut(id)?
+            }
+            _ => self.get_dictionary_mut(page_id)?.get_mut(b"Annots")?,
+        };
+        annots
+            .as_array_mut()?
+            .retain(|annot| annot.as_reference().map_or(true, |id| !removed.contains(&id)));
+        let count = removed.len() - removed.iter().filter(|id| self.objects.remove(id).is_none()).count();
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+
+    const CSV: &[u8] = b"date,amount\n2024-01-31,12.50\n2024-02-29,7.25\n";
+
+    fn attach_csv(doc: &mut Document) -> ObjectId {
+        let page_id = doc.page_iter().next().unwrap();
+        let annotation = Annotation::file_attachment(
+            [400.0, 700.0, 420.0, 720.0],
+            "expenses.csv",
+            CSV.repeat(20),
+            Some("text/csv"),
+            FileAttachmentIcon::Paperclip,
+        );
+        doc.add_annotation(page_id, annotation).unwrap()
+    }
+
+    #[test]
+    fn file_attachment_round_trip() {
+        let mut doc = create_document();
+        let annot_id = attach_csv(&mut doc);
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+
+        let doc = Document::load_mem(&bytes).unwrap();
+        let page_id = doc.page_iter().next().unwrap();
+        let annotations = doc.get_annotations(page_id).unwrap();
+        assert_eq!(annotations.len(), 1);
+        let annotation = &annotations[0];
+        assert_eq!(annotation.id, Some(annot_id));
+        assert_eq!(annotation.subtype(), Some(b"FileAttachment".as_slice()));
+        assert_eq!(annotation.rect(), Some([400.0, 700.0, 420.0, 720.0]));
+        assert_eq!(annotation.dict.get(b"Name").and_then(Object::as_name).unwrap(), b"Paperclip");
+        let file = annotation.file().unwrap();
+        assert_eq!(file.name, "expenses.csv");
+        assert_eq!(file.mime_type.as_deref(), Some("text/csv"));
+        assert_eq!(file.data, CSV.repeat(20));
+    }
+
+    #[test]
+    fn other_annotations_have_no_file() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let link_id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+        });
+        doc.get_dictionary_mut(page_id).unwrap().set("Annots", vec![link_id.into()]);
+        let annotations = doc.get_annotations(page_id).unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert!(annotations[0].file().is_none());
+    }
+
+    #[test]
+    fn remove_attachments_covers_annotation_files() {
+        let mut doc = create_document();
+        let annot_id = attach_csv(&mut doc);
+        // Embedded file streams don't need a /Type.
+        let file_spec_id = doc.get_dictionary(annot_id).unwrap().get(b"FS").and_then(Object::as_reference).unwrap();
+        let file_spec = doc.get_dictionary(file_spec_id).unwrap();
+        let stream_id = file_spec.get_deref(b"EF", &doc).and_then(Object::as_dict).unwrap();
+        let stream_id = stream_id.get(b"F").and_then(Object::as_reference).unwrap();
+        doc.get_object_mut(stream_id).and_then(Object::as_stream_mut).unwrap().dict.remove(b"Type");
+
+        assert_eq!(doc.remove_attachments(), 1);
+        let page_id = doc.page_iter().next().unwrap();
+        assert!(doc.get_annotations(page_id).unwrap().is_empty());
+        for id in [annot_id, file_spec_id, stream_id] {
+            assert!(doc.get_object(id).is_err());
+        }
+    }
+}
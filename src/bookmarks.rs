@@ -1,4 +1,5 @@
 use super::{Dictionary, Document, Object, ObjectId};
+use crate::encodings::encode_utf16_be;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -12,6 +13,8 @@ pub struct Bookmark {
     pub color: [f32; 3],
     pub page: ObjectId,
     pub id: u32,
+    /// Whether the children are shown when the document is opened. True by default.
+    pub open: bool,
 }
 
 impl Bookmark {
@@ -23,6 +26,7 @@ impl Bookmark {
             color,
             page,
             id: 0,
+            open: true,
         }
     }
 }
@@ -46,12 +50,14 @@ pub fn add_bookmark(&mut self, mut bookmark: Bookmark, parent: Option<u32>) -> u
         id
     }
 
+    /// Write the outline items of `parent.1` below `parent.0`, returning the first and last of
+    /// them and the number of items shown below the parent when it is open.
     fn outline_child(
         &self, maxid: &mut u32, parent: (ObjectId, &[u32]), processed: &mut HashMap<ObjectId, Dictionary>,
     ) -> (Option<ObjectId>, Option<ObjectId>, i64) {
         let mut first: Option<ObjectId> = None;
         let mut last: Option<ObjectId> = None;
-        let count = parent.1.len();
+        let mut count = 0;
         for i in parent.1 {
             let mut child = Dictionary::new();
             *maxid += 1;
@@ -59,6 +65,7 @@ fn outline_child(
             *maxid += 1;
             let info_id: ObjectId = (*maxid, 0);
             let Some(bookmark) = self.bookmark_table.get(i) else { continue };
+            count += 1;
 
             let info = dictionary! {
                 "D" =>  vec![bookmark.page.into(), Object::Name("Fit".into())],
@@ -68,13 +75,8 @@ fn outline_child(
             let title_bytes = if bookmark.title.is_ascii() {
                 bookmark.title.as_bytes().to_vec()
             } else {
-                // If the title contains non-ASCII characters:
-                // Create a new vector with the UTF-16 Byte Order Mark (BOM) for UTF-16BE.
-                let mut bom = vec![0xFE, 0xFF];
-                let utf16_title = bookmark.title.encode_utf16();
-                // Append the UTF-16BE encoded bytes of the title to the BOM.
-                bom.extend(utf16_title.flat_map(u16::to_be_bytes));
-                bom
+                // If the title contains non-ASCII characters, encode it as UTF-16BE with a BOM.
+                encode_utf16_be(&bookmark.title)
             };
 
             child.set("Parent", parent.0);
@@ -112,14 +114,18 @@ fn outline_child(
                     child.set("Last", n);
                 }
 
-                child.set("Count", c_count);
+                // The number of items shown when open, negated if the item is closed.
+                child.set("Count", if bookmark.open { c_count } else { -c_count });
+                if bookmark.open {
+                    count += c_count;
+                }
             }
 
             processed.insert(id, child);
             processed.insert(info_id, info);
         }
 
-        (first, last, count as i64)
+        (first, last, count)
     }
 
     pub fn build_outline(&mut self) -> Option<ObjectId> {
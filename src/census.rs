@@ -0,0 +1,211 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::xref::XrefEntry;
+use crate::{Document, Object};
+
+/// Counts of the constructs found while loading a document, for learning what files in the wild
+/// contain. Available with the `census` feature, see [`Document::census`].
+///
+/// Censuses of many documents add up with [`Census::merge`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Census {
+    /// Streams by the names of their filters, counted once per filter of a chain
+    pub filters: BTreeMap<String, u64>,
+    /// Documents by the revision of their standard security handler, /R of the encryption
+    /// dictionary
+    pub encryption_revisions: BTreeMap<i64, u64>,
+    /// Strings written as `(...)`
+    pub literal_strings: u64,
+    /// Strings written as `<...>`
+    pub hexadecimal_strings: u64,
+    /// The deepest nesting of arrays and dictionaries parsed
+    pub max_depth: usize,
+    /// Cross-reference sections of hybrid-reference files, whose trailer has an /XRefStm
+    pub hybrid_xrefs: u64,
+    /// Object streams
+    pub object_streams: u64,
+    /// Objects the cross-reference table lists as stored in object streams
+    pub compressed_objects: u64,
+    /// Comments between tokens, the header with its binary marker and `%%EOF` markers excluded
+    pub comments: u64,
+    /// Names with bytes outside ASCII, written as is or with `#` escapes
+    pub non_ascii_names: u64,
+    /// Reals written with more than 6 decimal places
+    pub long_reals: u64,
+    /// Load warnings and repairs by kind, such as `StreamLengthMismatch`
+    pub diagnostics: BTreeMap<String, u64>,
+}
+
+impl Census {
+    /// Add the counts of `other`, as of another document.
+    pub fn merge(&mut self, other: &Census) {
+        add_counts(&mut self.filters, &other.filters);
+        add_counts(&mut self.encryption_revisions, &other.encryption_revisions);
+        self.literal_strings += other.literal_strings;
+        self.hexadecimal_strings += other.hexadecimal_strings;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.hybrid_xrefs += other.hybrid_xrefs;
+        self.object_streams += other.object_streams;
+        self.compressed_objects += other.compressed_objects;
+        self.comments += other.comments;
+        self.non_ascii_names += other.non_ascii_names;
+        self.long_reals += other.long_reals;
+        add_counts(&mut self.diagnostics, &other.diagnostics);
+    }
+
+    pub(crate) fn add_parse_counts(&mut self, counts: ParseCounts) {
+        self.literal_strings += counts.literal_strings;
+        self.hexadecimal_strings += counts.hexadecimal_strings;
+        self.max_depth = self.max_depth.max(counts.max_depth);
+        self.comments += counts.comments;
+        self.non_ascii_names += counts.non_ascii_names;
+        self.long_reals += counts.long_reals;
+    }
+
+    /// Count what the loaded document shows of the file: the filters and object streams of its
+    /// objects, its encryption and the diagnostics of loading it.
+    pub(crate) fn survey(doc: &Document) -> Census {
+        let mut census = Census::default();
+        for stream in doc.objects.values().filter_map(|object| object.as_stream().ok()) {
+            match stream.dict.get(b"Filter") {
+                Ok(Object::Name(filter)) => *census.filters.entry(name(filter)).or_default() += 1,
+                Ok(Object::Array(filters)) => {
+                    for filter in filters.iter().filter_map(|filter| filter.as_name().ok()) {
+                        *census.filters.entry(name(filter)).or_default() += 1;
+                    }
+                }
+                _ => {}
+            }
+            if stream.dict.has_type(b"ObjStm") {
+                census.object_streams += 1;
+            }
+        }
+        census.compressed_objects += doc
+            .reference_table
+            .entries
+            .values()
+            .filter(|entry| matches!(entry, XrefEntry::Compressed { .. }))
+            .count() as u64;
+
+        let revision = doc.encryption_state.as_ref().map(|state| state.revision).or_else(|| {
+            let encrypt = doc.trailer.get_deref(b"Encrypt", doc).and_then(Object::as_dict).ok()?;
+            encrypt.get(b"R").and_then(Object::as_i64).ok()
+        });
+        if let Some(revision) = revision {
+            *census.encryption_revisions.entry(revision).or_default() += 1;
+        }
+
+        let warnings = doc.load_warnings.iter().map(|warning| format!("{warning:?}"));
+        let repairs = doc.repairs.iter().map(|repair| format!("{repair:?}"));
+        for debug in warnings.chain(repairs) {
+            // The variant name, as the debug output starts with it.
+            let kind = debug.split([' ', '(', '{']).next().unwrap_or_default();
+            *census.diagnostics.entry(kind.to_string()).or_default() += 1;
+        }
+        census
+    }
+}
+
+fn add_counts<K: Ord + Clone>(counts: &mut BTreeMap<K, u64>, other: &BTreeMap<K, u64>) {
+    for (key, count) in other {
+        *counts.entry(key.clone()).or_default() += count;
+    }
+}
+
+fn name(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// What the parser counts as it reads tokens.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ParseCounts {
+    pub literal_strings: u64,
+    pub hexadecimal_strings: u64,
+    /// Arrays and dictionaries the parser is inside of
+    pub depth: usize,
+    pub max_depth: usize,
+    pub comments: u64,
+    pub non_ascii_names: u64,
+    pub long_reals: u64,
+}
+
+impl ParseCounts {
+    pub(crate) fn add(&mut self, other: ParseCounts) {
+        self.literal_strings += other.literal_strings;
+        self.hexadecimal_strings += other.hexadecimal_strings;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.comments += other.comments;
+        self.non_ascii_names += other.non_ascii_names;
+        self.long_reals += other.long_reals;
+    }
+}
+
+thread_local! {
+    /// The counts of the parser on this thread, while a [`Counting`] is alive.
+    static COUNTS: Cell<Option<ParseCounts>> = const { Cell::new(None) };
+}
+
+/// Counts what the parser reads on the current thread while alive. Nested in another on the same
+/// thread, it leaves the counts to the outer one.
+pub(crate) struct Counting {
+    nested: bool,
+}
+
+impl Counting {
+    pub(crate) fn start() -> Self {
+        let nested = COUNTS.get().is_some();
+        if !nested {
+            COUNTS.set(Some(ParseCounts::default()));
+        }
+        Counting { nested }
+    }
+
+    pub(crate) fn finish(self) -> ParseCounts {
+        if self.nested {
+            ParseCounts::default()
+        } else {
+            COUNTS.take().unwrap_or_default()
+        }
+    }
+}
+
+impl Drop for Counting {
+    fn drop(&mut self) {
+        if !self.nested {
+            COUNTS.set(None);
+        }
+    }
+}
+
+/// Update the counts of the parser, if it's counting.
+#[inline]
+pub(crate) fn count(update: impl FnOnce(&mut ParseCounts)) {
+    if let Some(mut counts) = COUNTS.get() {
+        update(&mut counts);
+        COUNTS.set(Some(counts));
+    }
+}
+
+/// The counts of the parser, to restore when it backtracks over what it counted.
+#[inline]
+pub(crate) fn snapshot() -> Option<ParseCounts> {
+    COUNTS.get()
+}
+
+#[inline]
+pub(crate) fn restore(snapshot: Option<ParseCounts>) {
+    if snapshot.is_some() {
+        COUNTS.set(snapshot);
+    }
+}
+
+impl Document {
+    /// The census of the constructs found while loading the document, empty for documents that
+    /// weren't loaded in full from a file.
+    pub fn census(&self) -> &Census {
+        &self.census
+    }
+}
@@ -0,0 +1,469 @@
+use std::collections::{HashMap, HashSet};
+use std::time::SystemTime;
+
+use crate::common_data_structures::{decode_text_string, text_string};
+use crate::sanitize::{rectangle, MARKUP_ANNOTATION_SUBTYPES};
+use crate::signature::append_to_array;
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result};
+
+/// A markup annotation of a comment thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub id: ObjectId,
+    /// The /Subtype of the annotation, e.g. `Text` or `Highlight`
+    pub subtype: Vec<u8>,
+    /// The author, from /T
+    pub author: Option<String>,
+    /// The text of /Contents
+    pub contents: Option<String>,
+    /// The rich text of /RC, with its XHTML markup stripped to plain text
+    pub rich_text: Option<String>,
+    /// The modification date, the /M string as written
+    pub modified: Option<String>,
+}
+
+impl Comment {
+    fn read(id: ObjectId, annot: &Dictionary, doc: &Document) -> Comment {
+        Comment {
+            id,
+            subtype: annot.get(b"Subtype").and_then(Object::as_name).unwrap_or_default().to_vec(),
+            author: text_entry(annot, b"T", doc),
+            contents: text_entry(annot, b"Contents", doc),
+            rich_text: annot.get_deref(b"RC", doc).ok().and_then(|rich_text| {
+                let markup = match rich_text {
+                    Object::Stream(stream) => {
+                        String::from_utf8_lossy(&stream.decompressed_content().unwrap_or(stream.content.clone()))
+                            .into_owned()
+                    }
+                    object => decode_text_string(object).ok()?,
+                };
+                Some(strip_markup(&markup))
+            }),
+            modified: text_entry(annot, b"M", doc),
+        }
+    }
+
+    /// The text of the comment: the plain text of its rich text, or else its /Contents.
+    pub fn text(&self) -> Option<&str> {
+        self.rich_text.as_deref().or(self.contents.as_deref())
+    }
+
+    /// The modification date parsed into a date and time, or `None` if missing or malformed.
+    #[cfg(feature = "chrono")]
+    pub fn modified_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.modified.as_deref().and_then(crate::datetime::parse_pdf_datetime)
+    }
+}
+
+/// A review state set on a comment by a Text annotation with a /State, e.g. `Accepted` in the
+/// `Review` state model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewState {
+    /// The state annotation
+    pub id: ObjectId,
+    /// The annotation the state is set on
+    pub target: ObjectId,
+    /// Who set the state, from /T
+    pub author: Option<String>,
+    /// The /StateModel, `Marked` or `Review`
+    pub model: String,
+    /// The /State, e.g. `Marked`, `Accepted`, `Rejected`, `Cancelled`, `Completed` or `None`
+    pub state: String,
+    /// The modification date, the /M string as written
+    pub modified: Option<String>,
+}
+
+/// A markup annotation with the annotations that refer to it through /IRT, directly or through
+/// other replies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentThread {
+    /// The annotation the thread starts with
+    pub root: Comment,
+    /// The replies (/RT /R) to the root and to other replies, ordered by modification date
+    pub replies: Vec<Comment>,
+    /// The annotations grouped with the root (/RT /Group), in page order
+    pub grouped: Vec<Comment>,
+    /// The review states set on the root or on replies, ordered by modification date, so the last
+    /// state of an author is their current one
+    pub states: Vec<ReviewState>,
+}
+
+impl Document {
+    /// Group the markup annotations of a page into comment threads, in the order of the roots on
+    /// the page. Popups, annotations that aren't indirect objects and other subtypes are left out.
+    pub fn get_comment_threads(&self, page_id: ObjectId) -> Result<Vec<CommentThread>> {
+        let markup = self.page_markup_annotations(page_id)?;
+        let parents: HashMap<ObjectId, ObjectId> = markup
+            .iter()
+            .filter_map(|&(id, annot)| Some((id, in_reply_to(annot)?)))
+            .filter(|(_, parent)| markup.iter().any(|(id, _)| id == parent))
+            .collect();
+        let root_of = |mut id: ObjectId| {
+            let mut visited = HashSet::new();
+            while let Some(&parent) = parents.get(&id) {
+                if !visited.insert(id) {
+                    // A cycle of replies belongs to the thread of its lowest annotation.
+                    return visited.into_iter().min().unwrap_or(id);
+                }
+                id = parent;
+            }
+            id
+        };
+
+        let mut threads: Vec<CommentThread> = Vec::new();
+        let mut thread_of_root = HashMap::new();
+        for &(id, annot) in &markup {
+            if root_of(id) == id {
+                thread_of_root.insert(id, threads.len());
+                threads.push(CommentThread {
+                    root: Comment::read(id, annot, self),
+                    replies: Vec::new(),
+                    grouped: Vec::new(),
+                    states: Vec::new(),
+                });
+            }
+        }
+        for &(id, annot) in &markup {
+            let (Some(&parent), Some(&index)) = (parents.get(&id), thread_of_root.get(&root_of(id))) else {
+                continue;
+            };
+            let thread = &mut threads[index];
+            if let Ok(state) = annot.get(b"State").and_then(Object::as_name) {
+                let state = String::from_utf8_lossy(state).into_owned();
+                let model = match annot.get(b"StateModel").and_then(Object::as_name) {
+                    Ok(model) => String::from_utf8_lossy(model).into_owned(),
+                    Err(_) if matches!(state.as_str(), "Marked" | "Unmarked") => "Marked".to_string(),
+                    Err(_) => "Review".to_string(),
+                };
+                thread.states.push(ReviewState {
+                    id,
+                    target: parent,
+                    author: text_entry(annot, b"T", self),
+                    model,
+                    state,
+                    modified: text_entry(annot, b"M", self),
+                });
+            } else if annot.get(b"RT").and_then(Object::as_name).ok() == Some(b"Group") && parent == thread.root.id {
+                thread.grouped.push(Comment::read(id, annot, self));
+            } else {
+                thread.replies.push(Comment::read(id, annot, self));
+            }
+        }
+        for thread in &mut threads {
+            thread.replies.sort_by(|a, b| compare_dates(a.modified.as_deref(), b.modified.as_deref()));
+            thread.states.sort_by(|a, b| compare_dates(a.modified.as_deref(), b.modified.as_deref()));
+        }
+        Ok(threads)
+    }
+
+    /// Reply to the annotation `parent` with a Text annotation by `author`, dated now and placed
+    /// over the parent on its page. Returns the id of the reply.
+    pub fn add_reply(&mut self, parent: ObjectId, author: &str, text: &str) -> Result<ObjectId> {
+        self.add_reply_at(parent, author, text, SystemTime::now())
+    }
+
+    /// Reply to the annotation `parent` like [`Document::add_reply`], dated `at`. The reply has
+    /// no /M date when no date and time crate feature is enabled.
+    pub fn add_reply_at(&mut self, parent: ObjectId, author: &str, text: &str, at: SystemTime) -> Result<ObjectId> {
+        let parent_annot = self.get_dictionary(parent)?;
+        let rect = parent_annot
+            .get_deref(b"Rect", self)
+            .ok()
+            .and_then(rectangle)
+            .unwrap_or_default();
+        let page_id = match parent_annot.get(b"P").and_then(Object::as_reference) {
+            Ok(page_id) => page_id,
+            Err(_) => self
+                .page_iter()
+                .find(|&page_id| self.page_annotation_ids(page_id).contains(&parent))
+                .ok_or_else(|| Error::DictKey("P".to_string()))?,
+        };
+        let mut reply = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Text",
+            "Rect" => rect.iter().map(|&value| Object::Real(value)).collect::<Vec<_>>(),
+            "P" => page_id,
+            "IRT" => parent,
+            "RT" => "R",
+            "T" => text_string(author),
+            "Contents" => text_string(text),
+            "Name" => "Comment",
+        };
+        if let Some(modified) = crate::datetime::pdf_date_utc(at) {
+            reply.set("M", modified);
+        }
+        let reply_id = self.add_object(reply);
+        append_to_array(self, page_id, b"Annots", reply_id)?;
+        Ok(reply_id)
+    }
+
+    /// The markup annotations of a page that are indirect objects, without popups.
+    fn page_markup_annotations(&self, page_id: ObjectId) -> Result<Vec<(ObjectId, &Dictionary)>> {
+        let page = self.get_dictionary(page_id)?;
+        let Ok(annots) = page.get_deref(b"Annots", self).and_then(Object::as_array) else {
+            return Ok(Vec::new());
+        };
+        Ok(annots
+            .iter()
+            .filter_map(|annot| Some((annot.as_reference().ok()?, self.dereference(annot).ok()?.1.as_dict().ok()?)))
+            .filter(|(_, annot)| {
+                annot
+                    .get(b"Subtype")
+                    .and_then(Object::as_name)
+                    .is_ok_and(|subtype| subtype != b"Popup" && MARKUP_ANNOTATION_SUBTYPES.contains(&subtype))
+            })
+            .collect())
+    }
+
+    /// The ids of the annotations of a page that are indirect objects.
+    pub(crate) fn page_annotation_ids(&self, page_id: ObjectId) -> Vec<ObjectId> {
+        self.get_dictionary(page_id)
+            .and_then(|page| page.get_deref(b"Annots", self))
+            .and_then(Object::as_array)
+            .map(|annots| annots.iter().filter_map(|annot| annot.as_reference().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// The annotations of a page that refer to `id` through /IRT, directly or through each
+    /// other: replies, grouped annotations and review states.
+    pub(crate) fn annotation_replies(&self, page_id: ObjectId, id: ObjectId) -> Vec<ObjectId> {
+        let annots: Vec<(ObjectId, Option<ObjectId>)> = self
+            .page_annotation_ids(page_id)
+            .into_iter()
+            .map(|annot_id| (annot_id, self.get_dictionary(annot_id).ok().and_then(in_reply_to)))
+            .collect();
+        let mut replies = vec![];
+        let mut thread = HashSet::from([id]);
+        let mut pending = vec![id];
+        while let Some(parent) = pending.pop() {
+            for &(annot_id, _) in annots.iter().filter(|(_, irt)| *irt == Some(parent)) {
+                if thread.insert(annot_id) {
+                    replies.push(annot_id);
+                    pending.push(annot_id);
+                }
+            }
+        }
+        replies
+    }
+}
+
+/// The annotation an annotation is in reply to, its /IRT.
+fn in_reply_to(annot: &Dictionary) -> Option<ObjectId> {
+    annot.get(b"IRT").and_then(Object::as_reference).ok()
+}
+
+fn text_entry(annot: &Dictionary, key: &[u8], doc: &Document) -> Option<String> {
+    annot.get_deref(key, doc).and_then(decode_text_string).ok()
+}
+
+/// Order PDF date strings chronologically when both parse, and as text otherwise. Missing dates
+/// sort first.
+fn compare_dates(a: Option<&str>, b: Option<&str>) -> std::cmp::Ordering {
+    #[cfg(feature = "chrono")]
+    if let (Some(a), Some(b)) = (
+        a.and_then(crate::datetime::parse_pdf_datetime),
+        b.and_then(crate::datetime::parse_pdf_datetime),
+    ) {
+        return a.cmp(&b);
+    }
+    let strip = |date: Option<&str>| date.map(|date| date.trim().trim_start_matches("D:").to_string());
+    strip(a).cmp(&strip(b))
+}
+
+/// The plain text of the XHTML of a rich text string: tags are removed, paragraphs and line
+/// breaks become newlines and character references are decoded.
+fn strip_markup(markup: &str) -> String {
+    let mut text = String::new();
+    let mut rest = markup;
+    while let Some(start) = rest.find(['<', '&']) {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with('<') {
+            let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+            let tag = rest[1..end].trim_end_matches('>').trim_end_matches('/').trim();
+            let name = tag.split(|c: char| c.is_whitespace()).next().unwrap_or_default();
+            let block_end = matches!(name, "/p" | "/div" | "br") && !text.is_empty() && !text.ends_with('\n');
+            if block_end {
+                text.push('\n');
+            }
+            rest = &rest[end..];
+        } else {
+            let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]).filter(|entity| entity.len() <= 10);
+            let decoded = entity.and_then(|entity| match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => {
+                    let code = entity.strip_prefix('#')?;
+                    let code = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(code)
+                }
+            });
+            match (entity, decoded) {
+                (Some(entity), Some(c)) => {
+                    text.push(c);
+                    rest = &rest[entity.len() + 2..];
+                }
+                _ => {
+                    text.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    text.push_str(rest);
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+
+    fn comment(doc: &mut Document, page_id: ObjectId, author: &str, text: &str, modified: &str) -> ObjectId {
+        let id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Text",
+            "Rect" => vec![100.into(), 100.into(), 120.into(), 120.into()],
+            "T" => text_string(author),
+            "Contents" => text_string(text),
+            "M" => Object::string_literal(modified),
+        });
+        append_to_array(doc, page_id, b"Annots", id).unwrap();
+        id
+    }
+
+    fn reply(doc: &mut Document, page_id: ObjectId, parent: ObjectId, entries: Dictionary) -> ObjectId {
+        let id = comment(doc, page_id, "Bob", "", "D:20240101000000Z");
+        let dict = doc.get_dictionary_mut(id).unwrap();
+        dict.set("IRT", parent);
+        for (key, value) in entries {
+            dict.set(key, value);
+        }
+        id
+    }
+
+    #[test]
+    fn replies_are_grouped_and_ordered_by_date() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let root = comment(&mut doc, page_id, "Alice", "Typo in the title", "D:20240301090000Z");
+        let other = comment(&mut doc, page_id, "Carol", "Unrelated", "D:20240301090500Z");
+        // The later reply comes first on the page, and is dated in another time zone.
+        let second = reply(
+            &mut doc,
+            page_id,
+            root,
+            dictionary! {
+                "T" => text_string("Alice"),
+                "Contents" => text_string("Thanks"),
+                "RC" => Object::string_literal(
+                    "<?xml version=\"1.0\"?><body xmlns=\"http://www.w3.org/1999/xhtml\"><p>Thanks &amp; \
+                     <b>done</b></p><p>Closing</p></body>"
+                ),
+                "M" => Object::string_literal("D:20240301110000+01'00'"),
+            },
+        );
+        let first = reply(
+            &mut doc,
+            page_id,
+            root,
+            dictionary! {
+                "RT" => "R",
+                "T" => text_string("Bob"),
+                "Contents" => text_string("Fixed, see r2"),
+                "M" => Object::string_literal("D:20240301093000Z"),
+            },
+        );
+        let state = reply(
+            &mut doc,
+            page_id,
+            first,
+            dictionary! { "State" => "Accepted", "StateModel" => "Review", "T" => text_string("Alice") },
+        );
+
+        let threads = doc.get_comment_threads(page_id).unwrap();
+        assert_eq!(threads.len(), 2);
+        let thread = &threads[0];
+        assert_eq!(thread.root.id, root);
+        assert_eq!(thread.root.author.as_deref(), Some("Alice"));
+        assert_eq!(thread.root.text(), Some("Typo in the title"));
+        let replies: Vec<_> = thread.replies.iter().map(|reply| (reply.id, reply.text().unwrap())).collect();
+        assert_eq!(replies, [(first, "Fixed, see r2"), (second, "Thanks & done\nClosing")]);
+        assert_eq!(thread.replies[1].contents.as_deref(), Some("Thanks"));
+        assert!(thread.grouped.is_empty());
+        assert_eq!(thread.states.len(), 1);
+        assert_eq!(thread.states[0].id, state);
+        assert_eq!(thread.states[0].target, first);
+        assert_eq!((thread.states[0].model.as_str(), thread.states[0].state.as_str()), ("Review", "Accepted"));
+        assert_eq!(threads[1].root.id, other);
+        assert!(threads[1].replies.is_empty());
+    }
+
+    #[test]
+    fn add_reply_links_to_parent() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let root = comment(&mut doc, page_id, "Alice", "Check this", "D:20240301090000Z");
+        let first = doc.add_reply(root, "Bob", "Looks right").unwrap();
+        let second = doc.add_reply(first, "Ünal", "Agreed").unwrap();
+        let reply = doc.get_dictionary(second).unwrap();
+        assert_eq!(reply.get(b"IRT").and_then(Object::as_reference).unwrap(), first);
+        assert_eq!(reply.get(b"RT").and_then(Object::as_name).unwrap(), b"R");
+        assert_eq!(reply.get(b"P").and_then(Object::as_reference).unwrap(), page_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        let doc = Document::load_mem(&bytes).unwrap();
+        let threads = doc.get_comment_threads(page_id).unwrap();
+        assert_eq!(threads.len(), 1);
+        let replies: Vec<_> = threads[0]
+            .replies
+            .iter()
+            .map(|reply| (reply.author.as_deref().unwrap(), reply.text().unwrap()))
+            .collect();
+        assert_eq!(replies, [("Bob", "Looks right"), ("Ünal", "Agreed")]);
+    }
+
+    #[cfg(any(feature = "chrono", feature = "jiff"))]
+    #[test]
+    fn replies_are_dated_when_added() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let root = comment(&mut doc, page_id, "Alice", "Check this", "D:20240301090000Z");
+        let at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_709_284_500);
+        let reply_id = doc.add_reply_at(root, "Bob", "Looks right", at).unwrap();
+        let threads = doc.get_comment_threads(page_id).unwrap();
+        assert_eq!(threads[0].replies[0].id, reply_id);
+        assert_eq!(threads[0].replies[0].modified.as_deref(), Some("D:20240301091500Z"));
+    }
+
+    #[test]
+    fn removing_root_removes_its_thread() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let root = comment(&mut doc, page_id, "Alice", "Check this", "D:20240301090000Z");
+        let other = comment(&mut doc, page_id, "Carol", "Unrelated", "D:20240301090500Z");
+        let first = doc.add_reply(root, "Bob", "Looks right").unwrap();
+        let second = doc.add_reply(first, "Alice", "Agreed").unwrap();
+        let state = reply(&mut doc, page_id, root, dictionary! { "State" => "Completed" });
+
+        assert_eq!(doc.remove_annotation(page_id, root).unwrap(), 4);
+        assert_eq!(doc.page_annotation_ids(page_id), [other]);
+        for id in [root, first, second, state] {
+            assert!(doc.get_object(id).is_err());
+        }
+        assert!(doc.remove_annotation(page_id, root).is_err());
+    }
+
+    #[test]
+    fn markup_is_stripped_from_rich_text() {
+        assert_eq!(strip_markup("<p>a &lt;b&gt;<br/>c &#233;&#x20AC; &unknown; & d</p>"), "a <b>\nc é€ &unknown; & d");
+    }
+}
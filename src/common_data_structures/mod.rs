@@ -16,24 +16,12 @@ pub fn text_string(text: &str) -> Object {
 /// Decodes a text string.
 /// Depending on the BOM at the start of the string, a different encoding is chosen.
 /// All encodings specified in PDF2.0 are supported (PDFDocEncoding, UTF-16BE,
-/// and UTF-8).
+/// and UTF-8). Lone UTF-16 surrogates are replaced by U+FFFD.
 pub fn decode_text_string(obj: &Object) -> Result<String> {
     let s = obj.as_str()?;
-    if s.starts_with(b"\xFE\xFF") {
+    if let Some(utf16) = s.strip_prefix(b"\xFE\xFF") {
         // Detected UTF-16BE BOM
-        String::from_utf16(
-            &s[2..]
-                .chunks(2)
-                .map(|c| {
-                    if c.len() == 1 {
-                        u16::from_be_bytes([c[0], 0])
-                    } else {
-                        u16::from_be_bytes(c.try_into().unwrap())
-                    }
-                })
-                .collect::<Vec<u16>>(),
-        )
-        .map_err(|_| Error::TextStringDecode)
+        Ok(encodings::decode_utf16_be(utf16).into_text("decoding text string"))
     } else if s.starts_with(b"\xEF\xBB\xBF") {
         // Detected UTF-8 BOM
         String::from_utf8(s.to_vec()).map_err(|_| Error::TextStringDecode)
@@ -46,7 +34,7 @@ pub fn decode_text_string(obj: &Object) -> Result<String> {
 #[cfg(test)]
 mod test {
     use crate::{
-        common_data_structures::decode_text_string, encodings, parser::ParserInput, text_string, writer::Writer,
+        common_data_structures::decode_text_string, encodings, text_string, writer::Writer,
         Object, StringFormat,
     };
 
@@ -69,7 +57,7 @@ fn spec_example1_encode() {
     #[test]
     fn spec_example1_decode() {
         let input = b"<</Key(text\\213)>>";
-        let dict = crate::parser::direct_object(ParserInput::new_extra(input, "")).unwrap();
+        let dict = crate::parse::object(input).unwrap();
         let dict = dict.as_dict().unwrap();
         let actual = decode_text_string(dict.get(b"Key").unwrap()).unwrap();
         let expected = "text‰";
@@ -93,7 +81,7 @@ fn spec_example2_encode() {
     #[test]
     fn spec_example2_decode() {
         let input = b"<</Key<FEFF0442043504410442>>>";
-        let dict = crate::parser::direct_object(ParserInput::new_extra(input, "")).unwrap();
+        let dict = crate::parse::object(input).unwrap();
         let dict = dict.as_dict().unwrap();
         let actual = decode_text_string(dict.get(b"Key").unwrap()).unwrap();
         // Russian for "test"
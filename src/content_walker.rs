@@ -0,0 +1,312 @@
+use log::warn;
+
+use crate::content::{Content, Operation};
+use crate::{Dictionary, Document, Object, ObjectId, Result};
+
+/// Default limit of nested Form XObjects a [`ContentWalker`] descends into.
+pub const DEFAULT_MAX_FORM_DEPTH: usize = 16;
+
+const IDENTITY: [f32; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Walks the content operations of a page, descending into the Form XObjects it draws.
+///
+/// Each Form XObject is walked with its own /Resources taking precedence over those of the page
+/// or the enclosing form; a form without /Resources inherits them. Forms that draw themselves,
+/// directly or indirectly, and forms nested deeper than the configured limit are skipped with a
+/// warning. So are forms whose content can't be decoded, unless the walker is strict.
+#[derive(Debug, Clone)]
+pub struct ContentWalker<'a> {
+    doc: &'a Document,
+    max_depth: usize,
+    strict_forms: bool,
+}
+
+/// State of a [`ContentWalker`] at the operation being visited.
+#[derive(Debug, Clone)]
+pub struct WalkContext<'a> {
+    doc: &'a Document,
+    /// Resource dictionaries in scope, innermost last.
+    resources: Vec<&'a Dictionary>,
+    /// Current transformation matrix from `cm` operators and form /Matrix entries, relative to
+    /// the page's default coordinate system.
+    pub ctm: [f32; 6],
+    /// Number of Form XObjects entered to reach the operation; 0 for page content.
+    pub depth: usize,
+    /// The Form XObject the operation belongs to, if any.
+    pub form_id: Option<ObjectId>,
+}
+
+impl<'a> WalkContext<'a> {
+    /// Look up a named resource of the given category (e.g. `Font`, `XObject`) in the innermost
+    /// resource dictionary that defines it. Returns the object id when the resource is indirect.
+    pub fn resource(&self, category: &[u8], name: &[u8]) -> Option<(Option<ObjectId>, &'a Object)> {
+        let value = self
+            .resources_defining(category, name)?
+            .get_deref(category, self.doc)
+            .and_then(Object::as_dict)
+            .and_then(|dict| dict.get(name))
+            .ok()?;
+        match value {
+            Object::Reference(id) => self.doc.get_object(*id).ok().map(|object| (Some(*id), object)),
+            object => Some((None, object)),
+        }
+    }
+
+    /// Get the innermost resource dictionary in scope that defines the named resource.
+    pub fn resources_defining(&self, category: &[u8], name: &[u8]) -> Option<&'a Dictionary> {
+        self.resources.iter().rev().copied().find(|resources| {
+            resources
+                .get_deref(category, self.doc)
+                .and_then(Object::as_dict)
+                .is_ok_and(|dict| dict.has(name))
+        })
+    }
+
+    /// Look up a font resource by name.
+    pub fn font(&self, name: &[u8]) -> Option<(Option<ObjectId>, &'a Dictionary)> {
+        let (id, font) = self.resource(b"Font", name)?;
+        font.as_dict().ok().map(|font| (id, font))
+    }
+}
+
+impl<'a> ContentWalker<'a> {
+    pub fn new(doc: &'a Document) -> Self {
+        ContentWalker {
+            doc,
+            max_depth: DEFAULT_MAX_FORM_DEPTH,
+            strict_forms: false,
+        }
+    }
+
+    /// Set how many levels of nested Form XObjects are descended into.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set whether walking a page fails when the content of a Form XObject it draws can't be
+    /// decoded, rather than skipping the form.
+    pub fn with_strict_forms(mut self, strict_forms: bool) -> Self {
+        self.strict_forms = strict_forms;
+        self
+    }
+
+    /// Call `visitor` for every operation of the page, including those of the Form XObjects it
+    /// draws. A `Do` operation is visited before the form it draws.
+    pub fn walk_page<F>(&self, page_id: ObjectId, mut visitor: F) -> Result<()>
+    where
+        F: FnMut(&Operation, &WalkContext<'a>),
+    {
+        let content = self.doc.get_and_decode_page_content(page_id)?;
+        let (page_resources, inherited) = self.doc.get_page_resources(page_id)?;
+
+        // `inherited` lists the page's own resources first, followed by those of its ancestors.
+        let mut resources: Vec<&Dictionary> = inherited
+            .iter()
+            .rev()
+            .filter_map(|id| self.doc.get_dictionary(*id).ok())
+            .collect();
+        resources.extend(page_resources);
+
+        let mut context = WalkContext {
+            doc: self.doc,
+            resources,
+            ctm: IDENTITY,
+            depth: 0,
+            form_id: None,
+        };
+        self.walk_operations(&content.operations, &mut context, &mut Vec::new(), &mut visitor)
+    }
+
+    fn walk_operations<F>(
+        &self, operations: &[Operation], context: &mut WalkContext<'a>, path: &mut Vec<ObjectId>, visitor: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(&Operation, &WalkContext<'a>),
+    {
+        let mut saved_ctm = vec![];
+        for operation in operations {
+            visitor(operation, context);
+            match operation.operator.as_str() {
+                "q" => saved_ctm.push(context.ctm),
+                "Q" => {
+                    if let Some(ctm) = saved_ctm.pop() {
+                        context.ctm = ctm;
+                    }
+                }
+                "cm" => {
+                    if let Some(matrix) = to_matrix(&operation.operands) {
+                        context.ctm = multiply(&matrix, &context.ctm);
+                    }
+                }
+                "Do" => {
+                    if let Some(Ok(name)) = operation.operands.first().map(Object::as_name) {
+                        self.walk_form(name, context, path, visitor)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn walk_form<F>(
+        &self, name: &[u8], context: &mut WalkContext<'a>, path: &mut Vec<ObjectId>, visitor: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(&Operation, &WalkContext<'a>),
+    {
+        let Some((Some(form_id), Object::Stream(form))) = context.resource(b"XObject", name) else {
+            return Ok(());
+        };
+        if !form.dict.get(b"Subtype").and_then(Object::as_name).is_ok_and(|subtype| subtype == b"Form") {
+            return Ok(());
+        }
+        if path.contains(&form_id) {
+            warn!("Form XObject {} {} draws itself, not descending into it again", form_id.0, form_id.1);
+            return Ok(());
+        }
+        if context.depth >= self.max_depth {
+            warn!(
+                "Form XObject {} {} is nested deeper than {} levels, skipping it",
+                form_id.0, form_id.1, self.max_depth
+            );
+            return Ok(());
+        }
+        let content = match form.get_plain_content().and_then(|data| Content::decode(&data)) {
+            Ok(content) => content,
+            Err(err) if self.strict_forms => return Err(err),
+            Err(err) => {
+                warn!("cannot decode Form XObject {} {}: {err}", form_id.0, form_id.1);
+                return Ok(());
+            }
+        };
+
+        let outer_ctm = context.ctm;
+        let outer_form_id = context.form_id;
+        let form_resources = form
+            .dict
+            .get_deref(b"Resources", self.doc)
+            .and_then(Object::as_dict)
+            .ok();
+        if let Some(resources) = form_resources {
+            context.resources.push(resources);
+        }
+        if let Some(matrix) = form.dict.get(b"Matrix").and_then(Object::as_array).ok().and_then(|m| to_matrix(m)) {
+            context.ctm = multiply(&matrix, &context.ctm);
+        }
+        context.depth += 1;
+        context.form_id = Some(form_id);
+        path.push(form_id);
+
+        let walked = self.walk_operations(&content.operations, context, path, visitor);
+
+        path.pop();
+        context.form_id = outer_form_id;
+        context.depth -= 1;
+        context.ctm = outer_ctm;
+        if form_resources.is_some() {
+            context.resources.pop();
+        }
+        walked
+    }
+}
+
+fn to_matrix(operands: &[Object]) -> Option<[f32; 6]> {
+    let values: Vec<f32> = operands.iter().map(|v| v.as_float().ok()).collect::<Option<_>>()?;
+    values.try_into().ok()
+}
+
+/// Concatenate two transformation matrices, applying `m` before `n`.
+pub(crate) fn multiply(m: &[f32; 6], n: &[f32; 6]) -> [f32; 6] {
+    [
+        m[0] * n[0] + m[1] * n[2],
+        m[0] * n[1] + m[1] * n[3],
+        m[2] * n[0] + m[3] * n[2],
+        m[2] * n[1] + m[3] * n[3],
+        m[4] * n[0] + m[5] * n[2] + n[4],
+        m[4] * n[1] + m[5] * n[3] + n[5],
+    ]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+    use crate::Stream;
+
+    /// Add a Form XObject drawn by the page under `name` and return its id.
+    pub fn add_page_form(doc: &mut Document, name: &str, form: Stream) -> ObjectId {
+        let form_id = doc.add_object(form);
+        let page_id = doc.page_iter().next().unwrap();
+        let resources_id = doc.get_page_resources(page_id).unwrap().1[0];
+        let resources = doc.get_dictionary_mut(resources_id).unwrap();
+        resources.set("XObject", dictionary! { name => form_id });
+        let content = format!("\nq 2 0 0 2 0 0 cm /{name} Do Q");
+        doc.add_page_contents(page_id, content.into_bytes()).unwrap();
+        form_id
+    }
+
+    pub fn form(content: &str, resources: Option<Dictionary>) -> Stream {
+        let mut dict = dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "Matrix" => vec![1.into(), 0.into(), 0.into(), 1.into(), 10.into(), 20.into()],
+        };
+        if let Some(resources) = resources {
+            dict.set("Resources", resources);
+        }
+        Stream::new(dict, content.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn walks_nested_forms_with_their_resources() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let inner_id = doc.add_object(form("BT /F1 12 Tf (inner) Tj ET", None));
+        let outer = form("/Inner Do", Some(dictionary! { "XObject" => dictionary! { "Inner" => inner_id } }));
+        let outer_id = add_page_form(&mut doc, "Outer", outer);
+        let page_id = doc.page_iter().next().unwrap();
+
+        let mut visited = vec![];
+        ContentWalker::new(&doc)
+            .walk_page(page_id, |operation, context| {
+                if operation.operator == "Tj" {
+                    let font = context.font(b"F1").map(|(id, _)| id);
+                    visited.push((context.depth, context.form_id, context.ctm, font.is_some()));
+                }
+            })
+            .unwrap();
+
+        assert_eq!(visited.len(), 2);
+        assert_eq!(visited[0], (0, None, IDENTITY, true));
+        // Both form matrices translate by (10, 20) inside the page's 2x scale.
+        assert_eq!(visited[1], (2, Some(inner_id), [2.0, 0.0, 0.0, 2.0, 40.0, 80.0], true));
+        assert_ne!(inner_id, outer_id);
+    }
+
+    #[test]
+    fn stops_at_self_reference_and_depth_limit() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let form_id = doc.new_object_id();
+        let looping = form(
+            "BT /F1 12 Tf (loop) Tj ET /Self Do",
+            Some(dictionary! { "XObject" => dictionary! { "Self" => form_id } }),
+        );
+        doc.objects.insert(form_id, Object::Stream(looping.clone()));
+        add_page_form(&mut doc, "Loop", looping);
+        let page_id = doc.page_iter().next().unwrap();
+
+        let count_texts = |walker: ContentWalker| {
+            let mut texts = 0;
+            walker
+                .walk_page(page_id, |operation, _| texts += usize::from(operation.operator == "Tj"))
+                .unwrap();
+            texts
+        };
+        // Page text, the form drawn by the page, and the form object it references once.
+        assert_eq!(count_texts(ContentWalker::new(&doc)), 3);
+        assert_eq!(count_texts(ContentWalker::new(&doc).with_max_depth(1)), 2);
+        assert_eq!(count_texts(ContentWalker::new(&doc).with_max_depth(0)), 1);
+    }
+}
@@ -35,8 +35,57 @@ fn try_from(value: super::DateTime) -> Result<DateTime<Local>, Self::Error> {
                 .map(|date| date.with_timezone(&Local))
         }
     }
+
+    /// Parse a PDF date string, as written by the producers found in the wild.
+    ///
+    /// The leading `D:` and everything after the year are optional (PDF 32000-1:2008, 7.9.4).
+    /// Missing fields take their lowest value and a missing offset means UT. The offset may be
+    /// `Z`, possibly followed by a zero `00'00'`, or signed hours and minutes, with or without
+    /// the apostrophes around them. Malformed dates give `None`.
+    pub(crate) fn parse_pdf_datetime(text: &str) -> Option<DateTime<FixedOffset>> {
+        let text = text.trim();
+        let text = text.strip_prefix("D:").unwrap_or(text);
+        let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+        if !(4..=14).contains(&digits) || digits % 2 != 0 {
+            return None;
+        }
+        let (fields, offset) = text.split_at(digits);
+        let field = |start: usize, len: usize, default: u32| {
+            fields.get(start..start + len).map_or(Some(default), |field| field.parse().ok())
+        };
+        let date = NaiveDate::from_ymd_opt(fields[..4].parse().ok()?, field(4, 2, 1)?, field(6, 2, 1)?)?;
+        let time = NaiveTime::from_hms_opt(field(8, 2, 0)?, field(10, 2, 0)?, field(12, 2, 0)?)?;
+        date.and_time(time).and_local_timezone(parse_utc_offset(offset)?).single()
+    }
+
+    fn parse_utc_offset(offset: &str) -> Option<FixedOffset> {
+        let (sign, rest) = match offset.chars().next() {
+            None => return FixedOffset::east_opt(0),
+            Some('Z') if matches!(&offset[1..], "" | "00'00'" | "00'00") => return FixedOffset::east_opt(0),
+            Some('+') => (1, &offset[1..]),
+            Some('-') => (-1, &offset[1..]),
+            Some(_) => return None,
+        };
+        let hours = rest.get(..2)?;
+        let minutes = match rest[2..].strip_prefix('\'').unwrap_or(&rest[2..]) {
+            "" => "00",
+            minutes => minutes.strip_suffix('\'').unwrap_or(minutes),
+        };
+        let two_digits = |field: &str| field.len() == 2 && field.bytes().all(|b| b.is_ascii_digit());
+        if !two_digits(hours) || !two_digits(minutes) {
+            return None;
+        }
+        let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+    }
 }
 
+#[cfg(feature = "chrono")]
+pub(crate) use chrono_impl::parse_pdf_datetime;
+
 #[cfg(feature = "jiff")]
 mod jiff_impl {
     use crate::{datetime::convert_utc_offset, Object};
@@ -153,6 +202,29 @@ fn convert_utc_offset(bytes: &mut [u8]) {
     }
 }
 
+/// `at` as a PDF date string in UT, e.g. `D:20240131120000Z`, converted by whichever date and
+/// time crate is enabled.
+#[cfg(feature = "chrono")]
+pub(crate) fn pdf_date_utc(at: std::time::SystemTime) -> Option<Object> {
+    Some(chrono::DateTime::<chrono::Utc>::from(at).into())
+}
+
+#[cfg(all(feature = "jiff", not(feature = "chrono")))]
+pub(crate) fn pdf_date_utc(at: std::time::SystemTime) -> Option<Object> {
+    jiff::Timestamp::try_from(at).ok().map(Object::from)
+}
+
+#[cfg(all(feature = "time", not(any(feature = "chrono", feature = "jiff"))))]
+pub(crate) fn pdf_date_utc(at: std::time::SystemTime) -> Option<Object> {
+    Some(time::OffsetDateTime::from(at).into())
+}
+
+/// Without a date and time crate, times can't be written as PDF dates.
+#[cfg(not(any(feature = "chrono", feature = "jiff", feature = "time")))]
+pub(crate) fn pdf_date_utc(_at: std::time::SystemTime) -> Option<Object> {
+    None
+}
+
 #[derive(Clone, Debug)]
 pub struct DateTime(String);
 
@@ -282,3 +354,61 @@ fn parse_datetime() {
     assert_eq!(time2.time().minute(), time.time().minute());
     assert_eq!(time2.time().second(), time.time().second());
 }
+
+#[cfg(feature = "chrono")]
+#[test]
+fn parse_pdf_datetime_producer_variants() {
+    use chrono::prelude::*;
+
+    let at = |offset: i32, y, mo, d, h, mi, s| {
+        FixedOffset::east_opt(offset).unwrap().with_ymd_and_hms(y, mo, d, h, mi, s).unwrap()
+    };
+    let cases = [
+        // Microsoft Word
+        ("D:20230115103000+01'00'", at(3600, 2023, 1, 15, 10, 30, 0)),
+        // LibreOffice
+        ("D:20230115103000-05'00'", at(-5 * 3600, 2023, 1, 15, 10, 30, 0)),
+        // Ghostscript
+        ("D:20230115103000Z00'00'", at(0, 2023, 1, 15, 10, 30, 0)),
+        // Quartz PDFContext
+        ("D:20230115103000Z", at(0, 2023, 1, 15, 10, 30, 0)),
+        // Acrobat Distiller, without the closing apostrophe
+        ("D:20230115103000+05'30", at(5 * 3600 + 1800, 2023, 1, 15, 10, 30, 0)),
+        // No leading D: and no offset
+        ("20230115103000", at(0, 2023, 1, 15, 10, 30, 0)),
+        // The example from the PDF reference, without seconds
+        ("D:199812231952-08'00'", at(-8 * 3600, 1998, 12, 23, 19, 52, 0)),
+        // Offset without apostrophes
+        ("D:20230115103000+0100", at(3600, 2023, 1, 15, 10, 30, 0)),
+        // Only the year
+        ("D:2004", at(0, 2004, 1, 1, 0, 0, 0)),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse_pdf_datetime(text), Some(expected), "{text}");
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn parse_pdf_datetime_malformed() {
+    for text in [
+        "",
+        "D:",
+        "D:2023-01-15",
+        "D:20231315103000Z",
+        "D:20230115253000Z",
+        "D:202301151030005",
+        "D:20230115103000+1'00'",
+        "D:20230115103000Y",
+        "Monday, January 15, 2023",
+    ] {
+        assert_eq!(parse_pdf_datetime(text), None, "{text}");
+    }
+}
+
+#[cfg(any(feature = "chrono", feature = "jiff"))]
+#[test]
+fn pdf_date_utc_is_in_ut() {
+    let at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_706_702_400);
+    assert_eq!(pdf_date_utc(at), Some(Object::string_literal("D:20240131120000Z")));
+}
@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Soft hyphen, a discretionary hyphenation point.
+const SOFT_HYPHEN: char = '\u{AD}';
+
+/// A word split across a line break with a hyphen and joined by
+/// [`ExtractionOptions::dehyphenate`](crate::ExtractionOptions::dehyphenate).
+///
+/// The fragments are given as byte ranges of the text the raw chunks of
+/// [`Document::extract_text_chunks`](crate::Document::extract_text_chunks) concatenate to, so that
+/// consumers of their positions can map the word back to both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyphenJoin {
+    /// The joined word in the extracted text
+    pub word: Range<usize>,
+    /// The fragment ending the first line, hyphen included
+    pub first: Range<usize>,
+    /// The fragment starting the next line
+    pub second: Range<usize>,
+}
+
+/// How a line-ending hyphen is resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Join {
+    /// A hyphenation point, the hyphen is dropped.
+    Drop,
+    /// A hyphen of a compound like `state-of-the-art`, which is kept.
+    Keep,
+}
+
+/// A hyphenated line break found in the text.
+struct Break {
+    join: Join,
+    /// Where the word ending the line starts
+    word_start: usize,
+    /// Where the hyphen ending the line starts
+    hyphen: usize,
+    /// The fragment starting the next line
+    second: Range<usize>,
+}
+
+/// Append `text`, the text of one page, to `out` with the words hyphenated across line breaks
+/// joined. The joined word ends its first line, the rest of the next line stays on its own. Joins
+/// are recorded in `joins`, with the fragments offset by `offset`.
+///
+/// A line-ending soft hyphen is always a hyphenation point. A hyphen-minus is kept, and the
+/// compound it's part of joined, if either fragment has a hyphen of its own or the page has the
+/// hyphenated word elsewhere. Otherwise it's dropped if the page has the joined word elsewhere, or
+/// if it's between lowercase letters, and left with its line break if not.
+pub(crate) fn dehyphenate(text: &str, offset: usize, out: &mut String, joins: &mut Vec<HyphenJoin>) {
+    let tokens: HashSet<String> = text.split_whitespace().filter_map(token).collect();
+    let mut start = 0;
+    while start < text.len() {
+        let end = text[start..].find('\n').map_or(text.len(), |index| start + index);
+        let Some(line_break) = find_break(text, start, end, &tokens) else {
+            out.push_str(&text[start..(end + 1).min(text.len())]);
+            start = end + 1;
+            continue;
+        };
+        let hyphen_end = if line_break.join == Join::Keep {
+            text[line_break.hyphen..].chars().next().map_or(0, char::len_utf8) + line_break.hyphen
+        } else {
+            line_break.hyphen
+        };
+        out.push_str(&text[start..line_break.word_start]);
+        let word_start = out.len();
+        out.push_str(&text[line_break.word_start..hyphen_end]);
+        out.push_str(&text[line_break.second.clone()]);
+        joins.push(HyphenJoin {
+            word: word_start..out.len(),
+            first: offset + line_break.word_start..offset + end,
+            second: offset + line_break.second.start..offset + line_break.second.end,
+        });
+        out.push('\n');
+        start = line_break.second.end + leading_blanks(&text[line_break.second.end..]);
+        if text[start..].starts_with('\n') {
+            start += 1;
+        }
+    }
+}
+
+/// The hyphenated line break ending the line `text[start..end]`, if it's to be joined.
+fn find_break(text: &str, start: usize, end: usize, tokens: &HashSet<String>) -> Option<Break> {
+    let line = &text[start..end];
+    let hyphen_char = line.chars().next_back().filter(|&c| c == '-' || c == SOFT_HYPHEN)?;
+    let hyphen = end - hyphen_char.len_utf8();
+    let word_start = line[..hyphen - start]
+        .rfind(char::is_whitespace)
+        .map_or(start, |index| start + index + 1);
+    let prefix = text[word_start..hyphen].trim_start_matches(|c: char| !c.is_alphanumeric());
+    let next = end + 1;
+    let second_start = next + leading_blanks(text.get(next..)?);
+    let second_end = text[second_start..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |index| second_start + index);
+    let suffix = text[second_start..second_end].trim_end_matches(|c: char| !c.is_alphanumeric());
+    let (before, after) = (prefix.chars().next_back()?, suffix.chars().next()?);
+    if !before.is_alphabetic() || !after.is_alphabetic() {
+        return None;
+    }
+    let join = if hyphen_char == SOFT_HYPHEN {
+        Join::Drop
+    } else if prefix.contains('-')
+        || suffix.contains('-')
+        || tokens.contains(&format!("{prefix}-{suffix}").to_lowercase())
+    {
+        Join::Keep
+    } else if tokens.contains(&format!("{prefix}{suffix}").to_lowercase())
+        || (before.is_lowercase() && after.is_lowercase())
+    {
+        Join::Drop
+    } else {
+        return None;
+    };
+    Some(Break {
+        join,
+        word_start,
+        hyphen,
+        second: second_start..second_end,
+    })
+}
+
+/// A word as it's looked up, without surrounding punctuation and lowercased.
+fn token(word: &str) -> Option<String> {
+    let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+    (!word.is_empty()).then(|| word.to_lowercase())
+}
+
+/// The length of the spaces and tabs `text` starts with.
+fn leading_blanks(text: &str) -> usize {
+    text.len() - text.trim_start_matches([' ', '\t']).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExtractionOptions;
+    use crate::creator::tests::create_document;
+
+    fn joined(text: &str) -> (String, Vec<HyphenJoin>) {
+        let mut out = String::new();
+        let mut joins = Vec::new();
+        dehyphenate(text, 0, &mut out, &mut joins);
+        (out, joins)
+    }
+
+    #[test]
+    fn joins_hyphenated_words() {
+        let text = "we need more infor-\nmation, about it\n";
+        let (out, joins) = joined(text);
+        assert_eq!(out, "we need more information,\nabout it\n");
+        assert_eq!(joins.len(), 1);
+        assert_eq!(&out[joins[0].word.clone()], "information,");
+        assert_eq!(&text[joins[0].first.clone()], "infor-");
+        assert_eq!(&text[joins[0].second.clone()], "mation,");
+
+        // A capitalized word, unless the page has it joined elsewhere.
+        assert_eq!(joined("Jean-\nPaul Sartre\n").0, "Jean-\nPaul Sartre\n");
+        assert_eq!(joined("McDonald, not Mc-\nDonald\n").0, "McDonald, not McDonald\n");
+        assert_eq!(joined("Mc\u{AD}\nDonald\n").0, "McDonald\n");
+        assert_eq!(joined("a dash -\nthere\n").0, "a dash -\nthere\n");
+    }
+
+    #[test]
+    fn keeps_hyphens_of_compounds() {
+        assert_eq!(
+            joined("a state-of-\nthe-art design\n").0,
+            "a state-of-the-art\ndesign\n"
+        );
+        assert_eq!(joined("e-mail or e-\nmail\n").0, "e-mail or e-mail\n");
+    }
+
+    #[test]
+    fn extract_dehyphenated_text() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let lines = [
+            "Accurate infor-",
+            "mation retrieval de-",
+            "pends on joining frag-",
+            "mented words, while state-of-the-",
+            "art compounds keep their hyphens.",
+        ];
+        let content: String = lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| format!("BT /F1 12 Tf 72 {} Td ({line}) Tj ET\n", 700 - 14 * index))
+            .collect();
+        doc.change_page_content(page_id, content.into_bytes()).unwrap();
+
+        let raw = doc.extract_text(&[1]).unwrap();
+        assert_eq!(raw, lines.join("\n") + "\n");
+        let options = ExtractionOptions {
+            dehyphenate: true,
+            ..Default::default()
+        };
+        let (text, joins) = doc.extract_text_with_hyphen_joins(&[1], &options).unwrap();
+        assert_eq!(
+            text,
+            "Accurate information\nretrieval depends\non joining fragmented\nwords, while state-of-the-art\n\
+             compounds keep their hyphens.\n"
+        );
+        assert_eq!(doc.extract_text_with_options(&[1], &options).unwrap(), text);
+        let words: Vec<&str> = joins.iter().map(|join| &text[join.word.clone()]).collect();
+        assert_eq!(words, ["information", "depends", "fragmented", "state-of-the-art"]);
+        let fragments: Vec<(&str, &str)> = joins
+            .iter()
+            .map(|join| (&raw[join.first.clone()], &raw[join.second.clone()]))
+            .collect();
+        assert_eq!(fragments[3], ("state-of-the-", "art"));
+        let chunks = doc.extract_text_chunks_with_options(&[1], &options);
+        assert_eq!(chunks.into_iter().collect::<Result<String, _>>().unwrap(), raw);
+    }
+}
@@ -33,45 +33,42 @@ impl Document {
     pub fn get_named_destinations(
         &self, tree: &Dictionary, named_destinations: &mut IndexMap<Vec<u8>, Destination>,
     ) -> Result<()> {
-        if let Ok(kids) = tree.get(b"Kids") {
+        if let Ok(kids) = tree.get_deref(b"Kids", self) {
             for kid in kids.as_array()? {
                 if let Ok(kid) = kid.as_reference().and_then(move |id| self.get_dictionary(id)) {
                     self.get_named_destinations(kid, named_destinations)?;
                 }
             }
         }
-        if let Ok(names) = tree.get(b"Names") {
+        if let Ok(names) = tree.get_deref(b"Names", self) {
             let mut names = names.as_array()?.iter();
             while let (Some(key), Some(val)) = (names.next(), names.next()) {
                 let key_bytes = match key.as_str() {
                     Ok(s) => s.to_vec(),
                     Err(_) => continue,
                 };
-                if let Ok(obj_ref) = val.as_reference() {
-                    if let Ok(dict) = self.get_dictionary(obj_ref) {
-                        if let Ok(arr) = dict.get(b"D").and_then(|d| d.as_array()) {
-                            if arr.len() >= 2 {
-                                let dest = Destination::new(key.clone(), arr[0].clone(), arr[1].clone());
-                                named_destinations.insert(key_bytes, dest);
-                            }
-                        }
-                    } else if let Ok(Object::Array(val)) = self.get_object(obj_ref) {
-                        if val.len() >= 2 {
-                            let dest = Destination::new(key.clone(), val[0].clone(), val[1].clone());
-                            named_destinations.insert(key_bytes, dest);
-                        }
-                    }
-                } else if let Ok(dict) = val.as_dict() {
-                    if let Ok(arr) = dict.get(b"D").and_then(|d| d.as_array()) {
-                        if arr.len() >= 2 {
-                            let dest = Destination::new(key.clone(), arr[0].clone(), arr[1].clone());
-                            named_destinations.insert(key_bytes, dest);
-                        }
+                // Silently skip unexpected node types
+                if let Some(arr) = self.destination_array(val) {
+                    if arr.len() >= 2 {
+                        let dest = Destination::new(key.clone(), arr[0].clone(), arr[1].clone());
+                        named_destinations.insert(key_bytes, dest);
                     }
                 }
-                // Silently skip unexpected node types
             }
         }
         Ok(())
     }
+
+    /// The array of a destination, given as an array or a dictionary with a /D entry. References
+    /// in it are resolved except the one to the page, which identifies the page.
+    fn destination_array(&self, value: &Object) -> Option<Vec<Object>> {
+        let array = match self.dereference(value).ok()?.1 {
+            Object::Dictionary(dict) => dict.get_deref(b"D", self).ok()?,
+            value => value,
+        };
+        let mut items = array.as_array().ok()?.iter();
+        let page = items.next()?.clone();
+        let rest = items.map(|item| self.resolve_deep(item, 1)).collect::<Result<Vec<_>>>().ok()?;
+        Some(std::iter::once(page).chain(rest).collect())
+    }
 }
@@ -0,0 +1,396 @@
+//! Differences between the text of two versions of a document.
+//!
+//! [`text_diff`] extracts the text of each page of both documents, pairs up the pages, and
+//! compares the text of each pair word by word:
+//!
+//! ```no_run
+//! use lopdf::Document;
+//! use lopdf::diff::{TextDiffOptions, text_diff};
+//!
+//! let old = Document::load_mem(&std::fs::read("contract-v1.pdf")?)?;
+//! let new = Document::load_mem(&std::fs::read("contract-v2.pdf")?)?;
+//! let report = text_diff(&old, &new, &TextDiffOptions::default());
+//! print!("{report}");
+//! # Ok::<(), lopdf::Error>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use log::warn;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::Document;
+
+/// How the pages of the two documents are paired up.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PageAlignment {
+    /// By index if both documents have as many pages, by similarity otherwise
+    #[default]
+    Auto,
+    /// The nth page of one document with the nth page of the other
+    ByIndex,
+    /// The pages in order whose text is most alike, see [`TextDiffOptions::min_similarity`]. Pages
+    /// inserted or removed in the middle are reported as such instead of shifting the pairs.
+    BySimilarity,
+}
+
+/// Options of [`text_diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDiffOptions {
+    pub alignment: PageAlignment,
+    /// The similarity from 0 to 1 below which pages aren't paired when aligning by similarity.
+    /// The similarity of two pages is the Jaccard index of their sets of shingles.
+    pub min_similarity: f64,
+    /// Number of consecutive words in a shingle
+    pub shingle_size: usize,
+}
+
+impl Default for TextDiffOptions {
+    fn default() -> Self {
+        TextDiffOptions {
+            alignment: PageAlignment::Auto,
+            min_similarity: 0.3,
+            shingle_size: 3,
+        }
+    }
+}
+
+/// What a hunk does to the text of the old page.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkKind {
+    Insert,
+    Delete,
+    Change,
+}
+
+/// A run of words that differ between a pair of pages.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub kind: HunkKind,
+    /// Line of the old page the hunk is at, starting from 1. Lines are those of the extracted text,
+    /// so only approximate the lines as laid out.
+    pub old_line: usize,
+    /// Line of the new page the hunk is at, starting from 1
+    pub new_line: usize,
+    /// The words of the old page replaced or deleted, separated by spaces
+    pub removed: String,
+    /// The words of the new page inserted in their place, separated by spaces
+    pub added: String,
+}
+
+/// The differences between a page of the old document and the page of the new document it is
+/// paired with. A page only in the new document is inserted, one only in the old is deleted.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageDiff {
+    /// Page number in the old document, starting from 1
+    pub old_page: Option<u32>,
+    /// Page number in the new document, starting from 1
+    pub new_page: Option<u32>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Counts of the differences of a [`TextDiffReport`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffSummary {
+    pub pages_inserted: usize,
+    pub pages_deleted: usize,
+    /// Number of paired pages whose text differs
+    pub pages_changed: usize,
+    pub hunks: usize,
+    pub words_inserted: usize,
+    pub words_deleted: usize,
+}
+
+/// The differences between the text of two documents, by [`text_diff`]. Displays as plain text.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextDiffReport {
+    /// The pages that differ, in the order of the new document
+    pub pages: Vec<PageDiff>,
+    pub summary: DiffSummary,
+}
+
+/// Compare the text of the pages of `old` and `new`, word by word. Pages whose text can't be
+/// extracted are compared as empty.
+pub fn text_diff(old: &Document, new: &Document, options: &TextDiffOptions) -> TextDiffReport {
+    let old_pages = PageText::all(old);
+    let new_pages = PageText::all(new);
+    let by_index = match options.alignment {
+        PageAlignment::Auto => old_pages.len() == new_pages.len(),
+        PageAlignment::ByIndex => true,
+        PageAlignment::BySimilarity => false,
+    };
+    let pairs = if by_index {
+        align_by_index(old_pages.len(), new_pages.len())
+    } else {
+        align_by_similarity(&old_pages, &new_pages, options)
+    };
+
+    let mut report = TextDiffReport::default();
+    for (old_index, new_index) in pairs {
+        let empty = PageText::default();
+        let old_page = old_index.map_or(&empty, |index| &old_pages[index]);
+        let new_page = new_index.map_or(&empty, |index| &new_pages[index]);
+        let hunks = diff_words(old_page, new_page);
+        if hunks.is_empty() && old_index.is_some() && new_index.is_some() {
+            continue;
+        }
+        let summary = &mut report.summary;
+        match (old_index, new_index) {
+            (None, _) => summary.pages_inserted += 1,
+            (_, None) => summary.pages_deleted += 1,
+            _ => summary.pages_changed += 1,
+        }
+        summary.hunks += hunks.len();
+        for hunk in &hunks {
+            summary.words_inserted += hunk.added.split(' ').filter(|word| !word.is_empty()).count();
+            summary.words_deleted += hunk.removed.split(' ').filter(|word| !word.is_empty()).count();
+        }
+        report.pages.push(PageDiff {
+            old_page: old_index.map(|index| index as u32 + 1),
+            new_page: new_index.map(|index| index as u32 + 1),
+            hunks,
+        });
+    }
+    report
+}
+
+impl fmt::Display for TextDiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let page = |number: Option<u32>| number.map_or("-".to_string(), |number| number.to_string());
+        for page_diff in &self.pages {
+            writeln!(f, "page {} -> {}", page(page_diff.old_page), page(page_diff.new_page))?;
+            for hunk in &page_diff.hunks {
+                writeln!(f, "@@ line {} -> {} @@", hunk.old_line, hunk.new_line)?;
+                if !hunk.removed.is_empty() {
+                    writeln!(f, "- {}", hunk.removed)?;
+                }
+                if !hunk.added.is_empty() {
+                    writeln!(f, "+ {}", hunk.added)?;
+                }
+            }
+        }
+        let summary = &self.summary;
+        writeln!(
+            f,
+            "{} pages changed, {} inserted, {} deleted; {} hunks, {} words inserted, {} deleted",
+            summary.pages_changed,
+            summary.pages_inserted,
+            summary.pages_deleted,
+            summary.hunks,
+            summary.words_inserted,
+            summary.words_deleted
+        )
+    }
+}
+
+/// The words of the text of a page, with the lines they are on, starting from 1.
+#[derive(Debug, Default)]
+struct PageText {
+    words: Vec<String>,
+    lines: Vec<usize>,
+}
+
+impl PageText {
+    fn all(document: &Document) -> Vec<PageText> {
+        document
+            .get_pages()
+            .into_keys()
+            .map(|page_number| {
+                let text = document.extract_text(&[page_number]).unwrap_or_else(|err| {
+                    warn!("text of page {page_number} can't be extracted: {err}");
+                    String::new()
+                });
+                PageText::new(&text)
+            })
+            .collect()
+    }
+
+    fn new(text: &str) -> Self {
+        let mut page = PageText::default();
+        for (index, line) in text.lines().enumerate() {
+            for word in line.split_whitespace() {
+                page.words.push(word.to_string());
+                page.lines.push(index + 1);
+            }
+        }
+        page
+    }
+
+    /// The line of the word at `index`, or of the last word if there is none.
+    fn line(&self, index: usize) -> usize {
+        self.lines.get(index).or(self.lines.last()).copied().unwrap_or(1)
+    }
+
+    fn shingles(&self, size: usize) -> HashSet<&[String]> {
+        let size = size.clamp(1, self.words.len().max(1));
+        self.words.windows(size).collect()
+    }
+}
+
+fn align_by_index(old: usize, new: usize) -> Vec<(Option<usize>, Option<usize>)> {
+    (0..old.max(new))
+        .map(|index| ((index < old).then_some(index), (index < new).then_some(index)))
+        .collect()
+}
+
+/// Pair the pages with the longest common subsequence of pages, scored by similarity, so that
+/// pages inserted or removed in the middle don't shift the pairs that follow.
+fn align_by_similarity(
+    old: &[PageText], new: &[PageText], options: &TextDiffOptions,
+) -> Vec<(Option<usize>, Option<usize>)> {
+    let old_shingles: Vec<_> = old.iter().map(|page| page.shingles(options.shingle_size)).collect();
+    let new_shingles: Vec<_> = new.iter().map(|page| page.shingles(options.shingle_size)).collect();
+    let similarity = |i: usize, j: usize| {
+        let (a, b) = (&old_shingles[i], &new_shingles[j]);
+        if a.is_empty() && b.is_empty() {
+            return 1.0;
+        }
+        a.intersection(b).count() as f64 / a.union(b).count() as f64
+    };
+
+    // score[i][j] is the best score aligning the first i old pages with the first j new ones.
+    let mut score = vec![vec![0.0f64; new.len() + 1]; old.len() + 1];
+    let mut matched = vec![vec![false; new.len() + 1]; old.len() + 1];
+    for i in 1..=old.len() {
+        for j in 1..=new.len() {
+            score[i][j] = score[i - 1][j].max(score[i][j - 1]);
+            let pair = similarity(i - 1, j - 1);
+            if pair >= options.min_similarity && score[i - 1][j - 1] + pair > score[i][j] {
+                score[i][j] = score[i - 1][j - 1] + pair;
+                matched[i][j] = true;
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (old.len(), new.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && matched[i][j] {
+            pairs.push((Some(i - 1), Some(j - 1)));
+            (i, j) = (i - 1, j - 1);
+        } else if i > 0 && (j == 0 || score[i - 1][j] >= score[i][j - 1]) {
+            pairs.push((Some(i - 1), None));
+            i -= 1;
+        } else {
+            pairs.push((None, Some(j - 1)));
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+/// The hunks turning the words of `old` into those of `new`, from their longest common
+/// subsequence.
+fn diff_words(old: &PageText, new: &PageText) -> Vec<Hunk> {
+    let (a, b) = (&old.words, &new.words);
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    // common[i][j] is the length of the longest common subsequence of a_mid[i..] and b_mid[j..].
+    let mut common = vec![vec![0u32; b_mid.len() + 1]; a_mid.len() + 1];
+    for i in (0..a_mid.len()).rev() {
+        for j in (0..b_mid.len()).rev() {
+            common[i][j] = if a_mid[i] == b_mid[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a_mid.len() || j < b_mid.len() {
+        if i < a_mid.len() && j < b_mid.len() && a_mid[i] == b_mid[j] {
+            (i, j) = (i + 1, j + 1);
+            continue;
+        }
+        let (start_i, start_j) = (i, j);
+        while (i < a_mid.len() || j < b_mid.len()) && !(i < a_mid.len() && j < b_mid.len() && a_mid[i] == b_mid[j]) {
+            if j == b_mid.len() || (i < a_mid.len() && common[i + 1][j] >= common[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        let kind = match (i > start_i, j > start_j) {
+            (true, true) => HunkKind::Change,
+            (true, false) => HunkKind::Delete,
+            _ => HunkKind::Insert,
+        };
+        hunks.push(Hunk {
+            kind,
+            old_line: old.line(prefix + start_i),
+            new_line: new.line(prefix + start_j),
+            removed: a_mid[start_i..i].join(" "),
+            added: b_mid[start_j..j].join(" "),
+        });
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+
+    const PAGES: [&str; 3] = [
+        "The parties agree to the terms below.",
+        "The tenant pays the rent on the first day of each month.",
+        "This agreement ends on the last day of the year.",
+    ];
+
+    #[test]
+    fn one_edited_sentence_is_one_hunk() {
+        let old = create_document_with_texts(&PAGES);
+        let new = create_document_with_texts(&[
+            PAGES[0],
+            "The tenant pays the rent on the fifth day of each month.",
+            PAGES[2],
+        ]);
+        let report = text_diff(&old, &new, &TextDiffOptions::default());
+        let [page] = &report.pages[..] else { panic!("{report:?}") };
+        assert_eq!((page.old_page, page.new_page), (Some(2), Some(2)));
+        let expected = Hunk {
+            kind: HunkKind::Change,
+            old_line: 1,
+            new_line: 1,
+            removed: "first".to_string(),
+            added: "fifth".to_string(),
+        };
+        assert_eq!(page.hunks, [expected]);
+        assert_eq!((report.summary.pages_changed, report.summary.hunks), (1, 1));
+        assert!(report.to_string().contains("- first\n+ fifth\n"), "{report}");
+
+        assert_eq!(text_diff(&old, &old, &TextDiffOptions::default()), TextDiffReport::default());
+    }
+
+    #[test]
+    fn page_inserted_in_the_middle() {
+        let old = create_document_with_texts(&PAGES);
+        let new = create_document_with_texts(&[PAGES[0], "A new clause about pets.", PAGES[1], PAGES[2]]);
+        let report = text_diff(&old, &new, &TextDiffOptions::default());
+        let [page] = &report.pages[..] else { panic!("{report:?}") };
+        assert_eq!((page.old_page, page.new_page), (None, Some(2)));
+        assert_eq!(page.hunks[0].kind, HunkKind::Insert);
+        assert_eq!(page.hunks[0].added, "A new clause about pets.");
+        assert_eq!(report.summary.pages_inserted, 1);
+        assert_eq!(report.summary.words_inserted, 5);
+
+        let options = TextDiffOptions {
+            alignment: PageAlignment::ByIndex,
+            ..TextDiffOptions::default()
+        };
+        assert_eq!(text_diff(&old, &new, &options).pages.len(), 3);
+    }
+}
@@ -1,10 +1,13 @@
 use super::encodings::Encoding;
-use super::{Bookmark, Dictionary, Object, ObjectId};
+use super::{Bookmark, Dictionary, Object, ObjectId, StringFormat};
 use crate::encryption::crypt_filters::*;
-use crate::encryption::{self, EncryptionState, PasswordAlgorithm};
+use crate::encryption::{
+    self, CryptoProvider, DefaultCryptoProvider, EncryptionState, FileIdPolicy, Password, PasswordAlgorithm,
+    PasswordKind, Permissions,
+};
 use crate::xobject::PdfImage;
 use crate::xref::{Xref, XrefType};
-use crate::{Error, ObjectStream, Result, Stream};
+use crate::{Error, KeyAliases, LoadWarning, ObjectStream, RepairAction, Result, Revision, Stream};
 use log::debug;
 use std::cmp::max;
 use std::collections::{BTreeMap, HashMap, HashSet};
@@ -16,6 +19,15 @@
 ///
 /// This can both be a combination of multiple incremental updates
 /// or just one (the last) incremental update in a PDF file.
+///
+/// Only the `census` feature gives documents a census:
+///
+#[cfg_attr(feature = "census", doc = "```")]
+#[cfg_attr(not(feature = "census"), doc = "```compile_fail")]
+/// let doc = lopdf::Document::new();
+/// let census: &lopdf::Census = doc.census();
+/// assert_eq!(census.comments, 0);
+/// ```
 #[derive(Debug, Clone)]
 pub struct Document {
     /// The version of the PDF specification to which the file conforms.
@@ -56,6 +68,24 @@ pub struct Document {
     /// The encryption state stores the parameters that were used to decrypt this document if the
     /// document has been decrypted.
     pub encryption_state: Option<EncryptionState>,
+
+    /// The revisions of the file the document was loaded from, oldest first: its original content
+    /// and each incremental update. Empty for documents that weren't loaded from a file.
+    pub revisions: Vec<Revision>,
+
+    /// The repairs made to the document when it was loaded from a damaged file.
+    pub repairs: Vec<RepairAction>,
+
+    /// Misspelled keys accepted by the features reading the document when the correct key is
+    /// absent. Starts with the curated aliases of [`KeyAliases::default`].
+    pub key_aliases: KeyAliases,
+
+    /// The problems found while loading the document, see [`Document::load_warnings`].
+    pub(crate) load_warnings: Vec<LoadWarning>,
+
+    /// The constructs found while loading the document, see [`Document::census`].
+    #[cfg(feature = "census")]
+    pub(crate) census: crate::Census,
 }
 
 impl Document {
@@ -73,6 +103,12 @@ pub fn new() -> Self {
             bookmark_table: HashMap::new(),
             xref_start: 0,
             encryption_state: None,
+            revisions: Vec::new(),
+            repairs: Vec::new(),
+            key_aliases: KeyAliases::default(),
+            load_warnings: Vec::new(),
+            #[cfg(feature = "census")]
+            census: Default::default(),
         }
     }
 
@@ -92,6 +128,12 @@ pub fn new_from_prev(prev: &Document) -> Self {
             bookmark_table: HashMap::new(),
             xref_start: 0,
             encryption_state: None,
+            revisions: Vec::new(),
+            repairs: Vec::new(),
+            key_aliases: KeyAliases::default(),
+            load_warnings: Vec::new(),
+            #[cfg(feature = "census")]
+            census: Default::default(),
         }
     }
 
@@ -159,6 +201,52 @@ pub fn dereference<'a>(&'a self, mut object: &'a Object) -> Result<(Option<Objec
         Ok((id, object))
     }
 
+    /// Copy `object` with the references in its arrays and dictionaries replaced by the objects
+    /// they refer to. At most `max_depth` references are followed along any path, deeper ones are
+    /// kept. References to streams are kept too, as streams can't be direct objects, and a
+    /// reference to a missing object becomes null.
+    ///
+    /// This is meant for small values that some producers split into indirect objects, like
+    /// /DecodeParms or /Widths. Resolving a page or the catalog would copy much of the document.
+    pub fn resolve_deep(&self, object: &Object, max_depth: usize) -> Result<Object> {
+        self.resolve_deep_in(object, max_depth, &mut Vec::new())
+    }
+
+    fn resolve_deep_in(&self, object: &Object, depth_left: usize, chain: &mut Vec<ObjectId>) -> Result<Object> {
+        let resolved = match object {
+            Object::Reference(id) if depth_left > 0 => {
+                if chain.contains(id) {
+                    return Err(Error::ReferenceCycle(*id));
+                }
+                match self.objects.get(id) {
+                    None => Object::Null,
+                    Some(Object::Stream(_)) => object.clone(),
+                    Some(target) => {
+                        chain.push(*id);
+                        let resolved = self.resolve_deep_in(target, depth_left - 1, chain);
+                        chain.pop();
+                        resolved?
+                    }
+                }
+            }
+            Object::Array(items) => Object::Array(
+                items
+                    .iter()
+                    .map(|item| self.resolve_deep_in(item, depth_left, chain))
+                    .collect::<Result<_>>()?,
+            ),
+            Object::Dictionary(dict) => {
+                let mut resolved = Dictionary::new();
+                for (key, value) in dict.iter() {
+                    resolved.set(key.clone(), self.resolve_deep_in(value, depth_left, chain)?);
+                }
+                Object::Dictionary(resolved)
+            }
+            object => object.clone(),
+        };
+        Ok(resolved)
+    }
+
     /// Get object by object id, will iteratively dereference a referenced object.
     pub fn get_object(&self, id: ObjectId) -> Result<&Object> {
         let object = self.objects.get(&id).ok_or(Error::ObjectNotFound(id))?;
@@ -185,7 +273,9 @@ pub fn get_object_mut(&mut self, id: ObjectId) -> Result<&mut Object> {
     pub fn get_object_page(&self, id: ObjectId) -> Result<ObjectId> {
         for (_, object_id) in self.get_pages() {
             let page = self.get_object(object_id)?.as_dict()?;
-            let annots = page.get(b"Annots")?.as_array()?;
+            let Ok(annots) = page.get_deref(b"Annots", self).and_then(Object::as_array) else {
+                continue;
+            };
             let mut objects_ids = annots.iter().map(Object::as_reference);
 
             let contains = objects_ids.any(|object_id| Some(id) == object_id.ok());
@@ -237,10 +327,8 @@ fn traverse_object<A: Fn(&mut Object)>(object: &mut Object, action: &A, refs: &m
                 Object::Array(array) => traverse_array(array, action, refs),
                 Object::Dictionary(dict) => traverse_dictionary(dict, action, refs),
                 Object::Stream(stream) => traverse_dictionary(&mut stream.dict, action, refs),
-                Object::Reference(id) => {
-                    if !refs.contains(id) {
-                        refs.push(*id);
-                    }
+                Object::Reference(id) if !refs.contains(id) => {
+                    refs.push(*id);
                 }
                 _ => {}
             }
@@ -275,6 +363,19 @@ pub fn was_encrypted(&self) -> bool {
         self.encryption_state.is_some()
     }
 
+    /// What a user opening the document with the user password may do, from the /P entry of the
+    /// encryption dictionary it was loaded with, or that it still has if it couldn't be decrypted.
+    /// Bits the security handler revision doesn't define are derived as it specifies, see
+    /// [`Permissions::for_revision`]. Returns `None` for documents that weren't encrypted.
+    pub fn permissions(&self) -> Option<Permissions> {
+        if let Some(state) = &self.encryption_state {
+            return Some(state.permissions().for_revision(state.revision));
+        }
+        let encrypt = self.get_encrypted().ok()?;
+        let integer = |key: &[u8]| encrypt.get(key).and_then(Object::as_i64).ok();
+        Some(Permissions::from_p(integer(b"P")?).for_revision(integer(b"R").unwrap_or(2)))
+    }
+
     /// Authenticate the provided owner password directly as bytes without sanitization
     pub fn authenticate_raw_owner_password<P>(&self, password: P) -> Result<()>
     where
@@ -312,58 +413,97 @@ pub fn authenticate_raw_password<P>(&self, password: P) -> Result<()>
     where
         P: AsRef<[u8]>,
     {
-        if !self.is_encrypted() {
-            return Err(Error::NotEncrypted);
-        }
-
-        let password = password.as_ref();
-        let algorithm = PasswordAlgorithm::try_from(self)?;
-        algorithm
-            .authenticate_owner_password(self, password)
-            .or(algorithm.authenticate_user_password(self, password))?;
+        self.authenticate_raw_password_kind(password)?;
 
         Ok(())
     }
 
-    /// Authenticate the provided owner password
-    pub fn authenticate_owner_password(&self, password: &str) -> Result<()> {
+    /// Authenticate the password as bytes without sanitization, as the owner password first and
+    /// then as the user password, as the standard security handler does.
+    pub(crate) fn authenticate_raw_password_kind<P>(&self, password: P) -> Result<PasswordKind>
+    where
+        P: AsRef<[u8]>,
+    {
         if !self.is_encrypted() {
             return Err(Error::NotEncrypted);
         }
 
+        let password = password.as_ref();
         let algorithm = PasswordAlgorithm::try_from(self)?;
-        let password = algorithm.sanitize_password(password)?;
-        algorithm.authenticate_owner_password(self, &password)?;
+        if algorithm.authenticate_owner_password(self, password).is_ok() {
+            return Ok(PasswordKind::Owner);
+        }
+        algorithm.authenticate_user_password(self, password)?;
 
-        Ok(())
+        Ok(if password.is_empty() { PasswordKind::Empty } else { PasswordKind::User })
     }
 
-    /// Authenticate the provided user password
+    /// Authenticate the provided owner password, see [`Document::authenticate_password`]
+    pub fn authenticate_owner_password(&self, password: &str) -> Result<()> {
+        self.authenticate_encoded(password, |password| self.authenticate_raw_owner_password(password))
+    }
+
+    /// Authenticate the provided user password, see [`Document::authenticate_password`]
     pub fn authenticate_user_password(&self, password: &str) -> Result<()> {
-        if !self.is_encrypted() {
-            return Err(Error::NotEncrypted);
-        }
+        self.authenticate_encoded(password, |password| self.authenticate_raw_user_password(password))
+    }
 
-        let algorithm = PasswordAlgorithm::try_from(self)?;
-        let password = algorithm.sanitize_password(password)?;
-        algorithm.authenticate_user_password(self, &password)?;
+    /// Authenticate the provided owner/user password.
+    ///
+    /// The password is encoded as the security handler of the document specifies: to
+    /// PDFDocEncoding for revision 4 and earlier, with Latin-1 and UTF-8 tried after it, and
+    /// processed with SASLprep as UTF-8 for later revisions. Passwords in other encodings can be
+    /// given as bytes to [`Document::authenticate_raw_password`].
+    pub fn authenticate_password(&self, password: &str) -> Result<()> {
+        self.authenticate_password_kind(password)?;
 
         Ok(())
     }
 
-    /// Authenticate the provided owner/user password
-    pub fn authenticate_password(&self, password: &str) -> Result<()> {
+    /// Authenticate the password as the owner password first and then as the user password,
+    /// returning the bytes it was encoded to and which one it is.
+    pub(crate) fn authenticate_password_kind(&self, password: &str) -> Result<(Vec<u8>, PasswordKind)> {
+        self.authenticate_encoded(password, |password| {
+            let kind = self.authenticate_raw_password_kind(password)?;
+            Ok((password.to_vec(), kind))
+        })
+    }
+
+    /// Authenticate a password given as text or as bytes, see
+    /// [`Document::authenticate_password_kind`].
+    pub(crate) fn authenticate_any_password(&self, password: &Password) -> Result<(Vec<u8>, PasswordKind)> {
+        match password {
+            Password::Text(password) => self.authenticate_password_kind(password),
+            Password::Bytes(password) => Ok((password.clone(), self.authenticate_raw_password_kind(password)?)),
+        }
+    }
+
+    /// Call `authenticate` with each encoding of the password the security handler may have
+    /// hashed, until one is accepted, or return the error of the first.
+    fn authenticate_encoded<T>(&self, password: &str, authenticate: impl Fn(&[u8]) -> Result<T>) -> Result<T> {
         if !self.is_encrypted() {
             return Err(Error::NotEncrypted);
         }
 
         let algorithm = PasswordAlgorithm::try_from(self)?;
-        let password = algorithm.sanitize_password(password)?;
-        algorithm
-            .authenticate_owner_password(self, &password)
-            .or(algorithm.authenticate_user_password(self, &password))?;
+        let mut first_error = None;
+        for candidate in algorithm.password_candidates(password)? {
+            match authenticate(&candidate) {
+                Ok(authenticated) => return Ok(authenticated),
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
 
-        Ok(())
+        Err(first_error.unwrap_or(Error::InvalidPassword))
+    }
+
+    /// The password the document was opened or decrypted with: the owner password, with which
+    /// its permissions may be ignored, a user password, or none because the user password is
+    /// empty. Returns `None` for documents that weren't decrypted.
+    pub fn authentication_result(&self) -> Option<PasswordKind> {
+        self.encryption_state.as_ref()?.password_kind
     }
 
     /// Returns a `BTreeMap` of the crypt filters available in the PDF document if any.
@@ -417,7 +557,23 @@ pub fn get_crypt_filters(&self) -> BTreeMap<Vec<u8>, Arc<dyn CryptFilter>> {
         crypt_filters
     }
 
-    /// Replaces all encrypted Strings and Streams with their encrypted contents
+    /// Give the document a file identifier, the /ID of the trailer, if it has none: twice the same
+    /// 16 random bytes, as for a new file. The keys of the standard security handler are derived
+    /// from it, so a document needs one before its [`EncryptionState`] is computed.
+    pub fn ensure_file_id(&mut self) {
+        if self.trailer.has(b"ID") {
+            return;
+        }
+        let mut id = vec![0; 16];
+        DefaultCryptoProvider.random_bytes(&mut id);
+        let id = Object::String(id, StringFormat::Hexadecimal);
+        self.trailer.set("ID", vec![id.clone(), id]);
+    }
+
+    /// Replaces all encrypted Strings and Streams with their encrypted contents. The state is
+    /// computed for the document, see [`Document::ensure_file_id`] for documents without a file
+    /// identifier. Saving with [`SaveOptionsBuilder::encryption`](crate::SaveOptionsBuilder::encryption)
+    /// encrypts the saved file instead of the document.
     pub fn encrypt(&mut self, state: &EncryptionState) -> Result<()> {
         if self.is_encrypted() {
             return Err(Error::AlreadyEncrypted);
@@ -438,13 +594,8 @@ pub fn encrypt(&mut self, state: &EncryptionState) -> Result<()> {
 
     /// Replaces all encrypted Strings and Streams with their decrypted contents
     pub fn decrypt(&mut self, password: &str) -> Result<()> {
-        if !self.is_encrypted() {
-            return Err(Error::NotEncrypted);
-        }
-
-        let algorithm = PasswordAlgorithm::try_from(&*self)?;
-        let password = algorithm.sanitize_password(password)?;
-        self.decrypt_raw(&password)
+        let (password, _) = self.authenticate_password_kind(password)?;
+        self.decrypt_raw(password)
     }
 
     /// Replaces all encrypted Strings and Streams with their decrypted contents with the password
@@ -457,12 +608,13 @@ pub fn decrypt_raw<P>(&mut self, password: P) -> Result<()>
             return Err(Error::NotEncrypted);
         }
 
-        self.authenticate_raw_password(&password)?;
+        let password_kind = self.authenticate_raw_password_kind(&password)?;
 
         // Find the ID of the encryption dict; we'll want to skip it when decrypting
         let encryption_obj_id = self.trailer.get(b"Encrypt").and_then(Object::as_reference)?;
 
-        let state = EncryptionState::decode(&*self, password)?;
+        let mut state = EncryptionState::decode(&*self, password)?;
+        state.password_kind = Some(password_kind);
 
         for (&id, obj) in self.objects.iter_mut() {
             // The encryption dictionary is not encrypted, leave it alone
@@ -508,6 +660,28 @@ pub fn decrypt_raw<P>(&mut self, password: P) -> Result<()>
         Ok(())
     }
 
+    /// Remove the encryption of the document for good, so that saving it writes a file anyone
+    /// can open without a password. A document still encrypted, as when it was loaded without
+    /// the password it needs, is decrypted with the empty user password, failing if that isn't
+    /// it. A document that was never encrypted is left as it is, but for its file identifier,
+    /// kept or regenerated as `file_id` says.
+    ///
+    /// Unlike [`Document::decrypt`], the [`EncryptionState`] is dropped too, so the
+    /// [`Document::permissions`] and [`Document::authentication_result`] of the document no
+    /// longer reflect the encryption it had.
+    pub fn remove_encryption(&mut self, file_id: FileIdPolicy) -> Result<()> {
+        if self.is_encrypted() {
+            self.decrypt("")?;
+        }
+        self.trailer.remove(b"Encrypt");
+        self.encryption_state = None;
+        if file_id == FileIdPolicy::Regenerate {
+            self.trailer.remove(b"ID");
+            self.ensure_file_id();
+        }
+        Ok(())
+    }
+
     /// Return the PDF document catalog, which is the root of the document's object graph.
     pub fn catalog(&self) -> Result<&Dictionary> {
         self.trailer
@@ -534,6 +708,18 @@ pub fn page_iter(&self) -> impl Iterator<Item = ObjectId> + '_ {
         PageTreeIter::new(self)
     }
 
+    /// Count the `/Type /Page` dictionaries among the loaded objects, regardless of whether
+    /// they are reachable from the page tree.
+    ///
+    /// This is a heuristic for damaged documents where `get_pages` returns nothing because
+    /// the catalog or page tree root cannot be resolved.
+    pub fn count_page_objects(&self) -> u32 {
+        self.objects
+            .values()
+            .filter(|object| matches!(object, Object::Dictionary(dict) if dict.has_type(b"Page")))
+            .count() as u32
+    }
+
     /// Get content stream object ids of a page.
     pub fn get_page_contents(&self, page_id: ObjectId) -> Vec<ObjectId> {
         let mut streams = vec![];
@@ -591,12 +777,19 @@ pub fn add_page_contents(&mut self, page_id: ObjectId, content: Vec<u8>) -> Resu
     }
 
     /// Get content of a page.
+    ///
+    /// The streams of a page are joined with a space. An operation may span streams, so the
+    /// result should be parsed as a whole rather than stream by stream.
     pub fn get_page_content(&self, page_id: ObjectId) -> Result<Vec<u8>> {
         let mut content = Vec::new();
         let content_streams = self.get_page_contents(page_id);
         for object_id in content_streams {
             if let Ok(content_stream) = self.get_object(object_id).and_then(Object::as_stream) {
-                match content_stream.decompressed_content() {
+                // Streams are split at token boundaries, keep the tokens at the seams apart.
+                if !content.is_empty() {
+                    content.write_all(b" ")?;
+                }
+                match self.decompressed_stream_content(content_stream) {
                     Ok(data) => content.write_all(&data)?,
                     Err(_) => content.write_all(&content_stream.content)?,
                 };
@@ -605,6 +798,19 @@ pub fn get_page_content(&self, page_id: ObjectId) -> Result<Vec<u8>> {
         Ok(content)
     }
 
+    /// Decode the content of `stream`, resolving references in its /Filter and /DecodeParms. Some
+    /// producers make the parameters, or their values, indirect objects.
+    pub fn decompressed_stream_content(&self, stream: &Stream) -> Result<Vec<u8>> {
+        // The parameters array, the parameters of a filter and their values may each be indirect.
+        const FILTER_DEPTH: usize = 3;
+        let filter = self.resolve_deep(stream.dict.get(b"Filter")?, FILTER_DEPTH)?;
+        let params = match stream.dict.get(b"DecodeParms") {
+            Ok(params) => Some(self.resolve_deep(params, FILTER_DEPTH)?),
+            Err(_) => None,
+        };
+        stream.decode_with(&filter, params.as_ref())
+    }
+
     /// Get resources used by a page.
     pub fn get_page_resources(&self, page_id: ObjectId) -> Result<(Option<&Dictionary>, Vec<ObjectId>)> {
         fn collect_resources(
@@ -785,13 +991,24 @@ struct PageTreeIter<'a> {
     stack: Vec<&'a [Object]>,
     kids: Option<&'a [Object]>,
     iter_limit: usize,
+    /// The page the catalog's /Pages names in place of a page tree node, see
+    /// [`Document::normalize_page_tree`].
+    single_page: Option<ObjectId>,
 }
 
 impl<'a> PageTreeIter<'a> {
     const PAGE_TREE_DEPTH_LIMIT: usize = 256;
 
     fn new(doc: &'a Document) -> Self {
-        if let Ok(page_tree_id) = doc
+        if let Some(page_id) = doc.single_page_root() {
+            Self {
+                doc,
+                kids: None,
+                stack: Vec::new(),
+                iter_limit: doc.objects.len(),
+                single_page: Some(page_id),
+            }
+        } else if let Ok(page_tree_id) = doc
             .catalog()
             .and_then(|cat| cat.get(b"Pages"))
             .and_then(Object::as_reference)
@@ -801,6 +1018,7 @@ fn new(doc: &'a Document) -> Self {
                 kids: Self::kids(doc, page_tree_id),
                 stack: Vec::with_capacity(32),
                 iter_limit: doc.objects.len(),
+                single_page: None,
             }
         } else {
             Self {
@@ -808,6 +1026,7 @@ fn new(doc: &'a Document) -> Self {
                 kids: None,
                 stack: Vec::new(),
                 iter_limit: doc.objects.len(),
+                single_page: None,
             }
         }
     }
@@ -825,6 +1044,9 @@ impl Iterator for PageTreeIter<'_> {
     type Item = ObjectId;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(page_id) = self.single_page.take() {
+            return Some(page_id);
+        }
         loop {
             while let Some((kid, new_kids)) = self.kids.and_then(|k| k.split_first()) {
                 if self.iter_limit == 0 {
@@ -840,15 +1062,13 @@ fn next(&mut self) -> Option<Self::Item> {
                             b"Page" => {
                                 return Some(kid_id);
                             }
-                            b"Pages" => {
-                                if self.stack.len() < Self::PAGE_TREE_DEPTH_LIMIT {
-                                    if let Some(kids) = self.kids.take() {
-                                        if !kids.is_empty() {
-                                            self.stack.push(kids);
-                                        }
+                            b"Pages" if self.stack.len() < Self::PAGE_TREE_DEPTH_LIMIT => {
+                                if let Some(kids) = self.kids.take() {
+                                    if !kids.is_empty() {
+                                        self.stack.push(kids);
                                     }
-                                    self.kids = Self::kids(self.doc, kid_id);
                                 }
+                                self.kids = Self::kids(self.doc, kid_id);
                             }
                             _ => {}
                         }
@@ -875,8 +1095,9 @@ fn size_hint(&self) -> (usize, Option<usize>) {
                 if let Ok(dict) = kid.as_reference().and_then(|id| self.doc.get_dictionary(id)) {
                     if let Ok(b"Pages") = dict.get_type() {
                         let count = dict.get_deref(b"Count", self.doc).and_then(Object::as_i64).unwrap_or(0);
-                        // Don't let page count go backwards in case of an invalid document.
-                        max(0, count) as usize
+                        // Don't let page count go backwards in case of an invalid document, nor
+                        // past the kids that can be visited, in case of a malicious one.
+                        (max(0, count) as usize).min(self.iter_limit)
                     } else {
                         1
                     }
@@ -884,7 +1105,8 @@ fn size_hint(&self) -> (usize, Option<usize>) {
                     1
                 }
             })
-            .sum();
+            .fold(0, usize::saturating_add)
+            .min(self.iter_limit);
 
         (nb_pages, Some(nb_pages))
     }
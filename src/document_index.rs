@@ -0,0 +1,191 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::parser;
+use crate::reader::Reader;
+use crate::writer::Writer;
+use crate::xref::Xref;
+use crate::{Dictionary, Document, LoadWarning, Object, ReaderOptions, Result};
+
+/// Number of bytes at the start and at the end of a file hashed in its [`DocumentIndex`].
+const FINGERPRINT_SPAN: usize = 64 * 1024;
+
+/// The cross-reference sections and trailer of a PDF file, read once with
+/// [`Document::build_index`] and given to [`Document::load_with_index`] or
+/// [`ReaderOptions::index`] to open the same file again without reading them.
+///
+/// Loading with an index checks that the file still has the length and the first and last 64 KB
+/// of the file it was built from, and reads the cross-reference sections as usual if it doesn't.
+/// The merged cross-reference table gives the offset of each object and the object stream each
+/// compressed object belongs to. Objects are still parsed from the file, which lazy loading with
+/// [`LazyDocument::load_mem_with_options`](crate::LazyDocument::load_mem_with_options) defers to
+/// their first access.
+///
+/// Under the `serde` feature, the index can be serialized to keep it next to the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DocumentIndex {
+    fingerprint: FileFingerprint,
+    /// Offset of the last cross-reference section
+    pub(crate) xref_start: usize,
+    /// The merged cross-reference table
+    pub(crate) xref: Xref,
+    /// The trailer of the last section, without /Prev and /XRefStm, written as a PDF dictionary
+    trailer: Vec<u8>,
+    /// Each cross-reference section, last first, with its offset and trailer
+    sections: Vec<(usize, Xref, Vec<u8>)>,
+    /// The warnings of reading the cross-reference sections, recorded again on loading
+    pub(crate) load_warnings: Vec<LoadWarning>,
+}
+
+/// What identifies the file a [`DocumentIndex`] was built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct FileFingerprint {
+    length: usize,
+    /// SHA-256 of the first 64 KB
+    head: [u8; 32],
+    /// SHA-256 of the last 64 KB
+    tail: [u8; 32],
+}
+
+impl FileFingerprint {
+    fn of(buffer: &[u8]) -> Self {
+        let span = FINGERPRINT_SPAN.min(buffer.len());
+        FileFingerprint {
+            length: buffer.len(),
+            head: Sha256::digest(&buffer[..span]).into(),
+            tail: Sha256::digest(&buffer[buffer.len() - span..]).into(),
+        }
+    }
+}
+
+impl DocumentIndex {
+    pub(crate) fn new(
+        buffer: &[u8], xref_start: usize, xref: Xref, trailer: &Dictionary, sections: Vec<(usize, Xref, Dictionary)>,
+        load_warnings: Vec<LoadWarning>,
+    ) -> Result<Self> {
+        Ok(DocumentIndex {
+            fingerprint: FileFingerprint::of(buffer),
+            xref_start,
+            xref,
+            trailer: encode_dictionary(trailer)?,
+            sections: sections
+                .into_iter()
+                .map(|(start, xref, trailer)| Ok((start, xref, encode_dictionary(&trailer)?)))
+                .collect::<Result<_>>()?,
+            load_warnings,
+        })
+    }
+
+    /// Whether the index was built from a file with the content of `buffer`, from its PDF header on.
+    pub(crate) fn matches(&self, buffer: &[u8]) -> bool {
+        buffer.len() == self.fingerprint.length && FileFingerprint::of(buffer) == self.fingerprint
+    }
+
+    /// The trailer of the last cross-reference section.
+    pub(crate) fn trailer(&self) -> Result<Dictionary> {
+        decode_dictionary(&self.trailer)
+    }
+
+    /// The cross-reference sections of the file, last first, with their offsets and trailers.
+    pub(crate) fn sections(&self) -> Result<Vec<(usize, Xref, Dictionary)>> {
+        self.sections
+            .iter()
+            .map(|(start, xref, trailer)| Ok((*start, xref.clone(), decode_dictionary(trailer)?)))
+            .collect()
+    }
+}
+
+fn encode_dictionary(dictionary: &Dictionary) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    Writer::write_object(&mut bytes, &Object::Dictionary(dictionary.clone()))?;
+    Ok(bytes)
+}
+
+fn decode_dictionary(bytes: &[u8]) -> Result<Dictionary> {
+    parser::whole_dictionary(parser::new_input(bytes))
+}
+
+impl Document {
+    /// Read the cross-reference sections and trailer of the PDF file in `buffer` into an index,
+    /// to load the same file again faster with [`Document::load_mem_with_index`]. Objects aren't
+    /// parsed.
+    pub fn build_index(buffer: &[u8]) -> Result<DocumentIndex> {
+        Reader::new(buffer, ReaderOptions::default()).read_index()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PARSED_BYTES;
+    use crate::LazyDocument;
+
+    /// The result of `load` and the number of bytes it parsed.
+    fn parsed_bytes<T>(load: impl FnOnce() -> T) -> (T, usize) {
+        PARSED_BYTES.set(0);
+        let loaded = load();
+        (loaded, PARSED_BYTES.get())
+    }
+
+    #[test]
+    fn warm_open_skips_the_cross_reference_sections() {
+        let buffer = std::fs::read("assets/Incremental.pdf").unwrap();
+        let index = Document::build_index(&buffer).unwrap();
+
+        let (cold, cold_bytes) = parsed_bytes(|| Document::load_mem(&buffer).unwrap());
+        let (warm, warm_bytes) = parsed_bytes(|| Document::load_mem_with_index(&buffer, &index).unwrap());
+        assert!(warm_bytes < cold_bytes, "{warm_bytes} bytes parsed warm, {cold_bytes} cold");
+        assert_eq!(warm.objects, cold.objects);
+        assert_eq!(warm.trailer, cold.trailer);
+        assert_eq!(warm.reference_table, cold.reference_table);
+        assert_eq!(warm.xref_start, cold.xref_start);
+        assert_eq!(warm.revisions.len(), cold.revisions.len());
+
+        // Opened lazily, the objects aren't parsed either.
+        let options = || ReaderOptions::builder().index(index.clone()).build();
+        let (cold_lazy, cold_bytes) = parsed_bytes(|| LazyDocument::load_mem(&buffer).unwrap());
+        let (warm_lazy, warm_bytes) = parsed_bytes(|| LazyDocument::load_mem_with_options(&buffer, options()).unwrap());
+        assert!(warm_bytes * 10 < cold_bytes, "{warm_bytes} bytes parsed warm, {cold_bytes} cold");
+        assert_eq!(warm_lazy.get_pages(), cold_lazy.get_pages());
+        assert_eq!(warm_lazy.get_pages(), cold.get_pages());
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn load_a_file_with_its_index() {
+        let index = Document::build_index(&std::fs::read("assets/Incremental.pdf").unwrap()).unwrap();
+        let (warm, warm_bytes) = parsed_bytes(|| Document::load_with_index("assets/Incremental.pdf", &index).unwrap());
+        let (cold, cold_bytes) = parsed_bytes(|| Document::load("assets/Incremental.pdf").unwrap());
+        assert!(warm_bytes < cold_bytes);
+        assert_eq!(warm.objects, cold.objects);
+    }
+
+    #[test]
+    fn index_of_another_file_is_ignored() {
+        let buffer = std::fs::read("assets/Incremental.pdf").unwrap();
+        let index = Document::build_index(&buffer).unwrap();
+        let mut changed = buffer.clone();
+        changed.extend_from_slice(b"\n");
+        assert!(!index.matches(&changed));
+
+        let (cold, cold_bytes) = parsed_bytes(|| Document::load_mem(&changed).unwrap());
+        let (doc, bytes) = parsed_bytes(|| Document::load_mem_with_index(&changed, &index).unwrap());
+        assert_eq!(bytes, cold_bytes);
+        assert_eq!(doc.objects, cold.objects);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn index_round_trips_through_serde() {
+        let buffer = std::fs::read("assets/Incremental.pdf").unwrap();
+        let index = Document::build_index(&buffer).unwrap();
+        let json = serde_json::to_vec(&index).unwrap();
+        let index: DocumentIndex = serde_json::from_slice(&json).unwrap();
+        assert!(index.matches(&buffer));
+        let warm = Document::load_mem_with_index(&buffer, &index).unwrap();
+        assert_eq!(warm.objects, Document::load_mem(&buffer).unwrap().objects);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result};
+
+/// How much of a page [`Document::duplicate_page`] copies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateMode {
+    /// Share content streams and resources with the source page, copy only the annotations
+    ShareContent,
+    /// Copy every object the page refers to, except other pages and parents
+    DeepCopy,
+}
+
+/// Keys whose values must not be shared between a page and its copy.
+const STRUCTURE_KEYS: [&[u8]; 2] = [b"StructParents", b"StructParent"];
+
+impl Document {
+    /// Insert a copy of a page right after it and return the id of the copy.
+    ///
+    /// The annotations of the copy are new objects pointing back at the copy, so editing them
+    /// leaves the source page alone. Widgets of form fields become additional widgets of the same
+    /// fields. /StructParents and /StructParent entries are removed from the copies, as the copies
+    /// are not part of the structure tree.
+    pub fn duplicate_page(&mut self, page_number: u32, mode: DuplicateMode) -> Result<ObjectId> {
+        let page_id = *self
+            .get_pages()
+            .get(&page_number)
+            .ok_or(Error::PageNumberNotFound(page_number))?;
+        let page = self.get_dictionary(page_id)?.clone();
+        let copy_id = self.new_object_id();
+        let mut copies = HashMap::from([(page_id, copy_id)]);
+
+        let annots: Vec<Object> = page
+            .get_deref(b"Annots", self)
+            .and_then(Object::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let annot_copies = match mode {
+            DuplicateMode::ShareContent => {
+                for annot_id in annots.iter().filter_map(|annot| annot.as_reference().ok()) {
+                    copies.insert(annot_id, self.new_object_id());
+                }
+                let mut annot_copies = Vec::with_capacity(annots.len());
+                for annot in &annots {
+                    let (copy_id, original) = match annot {
+                        Object::Reference(id) => (Some(copies[id]), self.get_object(*id)?.clone()),
+                        annot => (None, annot.clone()),
+                    };
+                    let copy = remap_references(&original, &copies);
+                    match copy_id {
+                        Some(id) => {
+                            self.objects.insert(id, copy);
+                            annot_copies.push(Object::Reference(id));
+                        }
+                        None => annot_copies.push(copy),
+                    }
+                }
+                annot_copies
+            }
+            DuplicateMode::DeepCopy => annots.iter().map(|annot| self.copy_closure(annot, &mut copies)).collect(),
+        };
+
+        let mut copy = match mode {
+            DuplicateMode::ShareContent => page,
+            DuplicateMode::DeepCopy => match self.copy_closure(&Object::Dictionary(page), &mut copies) {
+                Object::Dictionary(copy) => copy,
+                _ => unreachable!("copies of dictionaries are dictionaries"),
+            },
+        };
+        for key in STRUCTURE_KEYS {
+            copy.remove(key);
+        }
+        if !annot_copies.is_empty() {
+            copy.set("Annots", annot_copies.clone());
+        }
+        self.objects.insert(copy_id, Object::Dictionary(copy));
+
+        for annot in &annot_copies {
+            self.attach_copied_annotation(annot, copy_id)?;
+        }
+        self.insert_page_after(page_id, copy_id)?;
+        Ok(copy_id)
+    }
+
+    /// Point a copied annotation at its page and add copied widgets to their fields.
+    fn attach_copied_annotation(&mut self, annot: &Object, page_id: ObjectId) -> Result<()> {
+        let Ok(annot_id) = annot.as_reference() else {
+            return Ok(());
+        };
+        let annot = self.get_dictionary_mut(annot_id)?;
+        annot.set("P", page_id);
+        for key in STRUCTURE_KEYS {
+            annot.remove(key);
+        }
+        let Ok(field_id) = annot.get(b"Parent").and_then(Object::as_reference) else {
+            return Ok(());
+        };
+        let field = self.get_dictionary_mut(field_id)?;
+        match field.get_mut(b"Kids").and_then(Object::as_array_mut) {
+            Ok(kids) => kids.push(annot_id.into()),
+            Err(_) => field.set("Kids", vec![annot_id.into()]),
+        }
+        Ok(())
+    }
+
+    /// Copy `object` and every object it refers to, recording copies in `copies`. Parents, pages
+    /// and page tree nodes are not copied.
+    fn copy_closure(&mut self, object: &Object, copies: &mut HashMap<ObjectId, ObjectId>) -> Object {
+        match object {
+            Object::Reference(id) => {
+                if let Some(copy_id) = copies.get(id) {
+                    return Object::Reference(*copy_id);
+                }
+                let Ok(original) = self.get_object(*id).cloned() else {
+                    return object.clone();
+                };
+                let is_page_node = original
+                    .as_dict()
+                    .is_ok_and(|dict| dict.has_type(b"Page") || dict.has_type(b"Pages"));
+                if is_page_node {
+                    return object.clone();
+                }
+                let copy_id = self.new_object_id();
+                copies.insert(*id, copy_id);
+                let copy = self.copy_closure(&original, copies);
+                self.objects.insert(copy_id, copy);
+                Object::Reference(copy_id)
+            }
+            Object::Array(array) => Object::Array(array.iter().map(|item| self.copy_closure(item, copies)).collect()),
+            Object::Dictionary(dict) => Object::Dictionary(self.copy_dictionary(dict, copies)),
+            Object::Stream(stream) => {
+                let mut copy = stream.clone();
+                copy.dict = self.copy_dictionary(&stream.dict, copies);
+                Object::Stream(copy)
+            }
+            object => object.clone(),
+        }
+    }
+
+    fn copy_dictionary(&mut self, dict: &Dictionary, copies: &mut HashMap<ObjectId, ObjectId>) -> Dictionary {
+        let mut copy = Dictionary::new();
+        for (key, value) in dict {
+            if STRUCTURE_KEYS.contains(&key.as_slice()) {
+                continue;
+            }
+            let value = match key.as_slice() {
+                b"Parent" => value.clone(),
+                _ => self.copy_closure(value, copies),
+            };
+            copy.set(key.clone(), value);
+        }
+        copy
+    }
+
+    /// Insert `page_id` into the page tree right after `after_id`.
+    fn insert_page_after(&mut self, after_id: ObjectId, page_id: ObjectId) -> Result<()> {
+        let parent_id = self.get_dictionary(after_id)?.get(b"Parent")?.as_reference()?;
+        let kids = self.get_dictionary_mut(parent_id)?.get_mut(b"Kids")?.as_array_mut()?;
+        let position = kids
+            .iter()
+            .position(|kid| kid.as_reference().is_ok_and(|id| id == after_id))
+            .map_or(kids.len(), |position| position + 1);
+        kids.insert(position, page_id.into());
+        self.get_dictionary_mut(page_id)?.set("Parent", parent_id);
+
+        let mut node_id = Some(parent_id);
+        let mut depth = 0;
+        while let Some(id) = node_id {
+            depth += 1;
+            if depth > 64 {
+                return Err(Error::ReferenceCycle(id));
+            }
+            let node = self.get_dictionary_mut(id)?;
+            let count = node.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+            node.set("Count", count + 1);
+            node_id = node.get(b"Parent").and_then(Object::as_reference).ok();
+        }
+        Ok(())
+    }
+}
+
+/// Replace references to objects in `copies` by references to their copies.
+fn remap_references(object: &Object, copies: &HashMap<ObjectId, ObjectId>) -> Object {
+    match object {
+        Object::Reference(id) => Object::Reference(*copies.get(id).unwrap_or(id)),
+        Object::Array(array) => Object::Array(array.iter().map(|item| remap_references(item, copies)).collect()),
+        Object::Dictionary(dict) => {
+            let mut copy = Dictionary::new();
+            for (key, value) in dict {
+                copy.set(key.clone(), remap_references(value, copies));
+            }
+            Object::Dictionary(copy)
+        }
+        object => object.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+
+    /// Give the first page a link to the second one and make it part of the structure tree.
+    fn add_link(doc: &mut Document) -> (ObjectId, ObjectId) {
+        let pages = doc.get_pages();
+        let (first, second) = (pages[&1], pages[&2]);
+        let link_id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![10.into(), 10.into(), 50.into(), 30.into()],
+            "Dest" => vec![second.into(), "Fit".into()],
+            "P" => first,
+            "StructParent" => 1,
+        });
+        let page = doc.get_dictionary_mut(first).unwrap();
+        page.set("Annots", vec![link_id.into()]);
+        page.set("StructParents", 0);
+        (link_id, second)
+    }
+
+    fn check_copy(doc: &mut Document, copy_id: ObjectId, link_id: ObjectId, second: ObjectId) -> ObjectId {
+        let page_ids: Vec<ObjectId> = doc.page_iter().collect();
+        assert_eq!(page_ids.len(), 3);
+        assert_eq!(page_ids[1], copy_id);
+        assert_eq!(doc.get_pages().len(), 3);
+
+        let copy = doc.get_dictionary(copy_id).unwrap();
+        assert!(!copy.has(b"StructParents"));
+        let annots = copy.get(b"Annots").and_then(Object::as_array).unwrap();
+        let copied_link = annots[0].as_reference().unwrap();
+        assert_ne!(copied_link, link_id);
+        let link = doc.get_dictionary(copied_link).unwrap();
+        assert_eq!(link.get(b"P").and_then(Object::as_reference).unwrap(), copy_id);
+        assert!(!link.has(b"StructParent"));
+        let dest = link.get(b"Dest").and_then(Object::as_array).unwrap();
+        assert_eq!(dest[0].as_reference().unwrap(), second);
+
+        // Editing the copy leaves the original annotation alone.
+        doc.get_dictionary_mut(copied_link)
+            .unwrap()
+            .set("Rect", vec![0.into(), 0.into(), 1.into(), 1.into()]);
+        let original = doc.get_dictionary(link_id).unwrap();
+        assert_eq!(original.get(b"Rect").and_then(Object::as_array).unwrap()[2], 50.into());
+        assert_eq!(original.get(b"StructParent").and_then(Object::as_i64).unwrap(), 1);
+        copied_link
+    }
+
+    #[test]
+    fn duplicate_sharing_content() {
+        let mut doc = create_document_with_texts(&["first", "second"]);
+        let (link_id, second) = add_link(&mut doc);
+        let first = doc.get_pages()[&1];
+
+        let copy_id = doc.duplicate_page(1, DuplicateMode::ShareContent).unwrap();
+        check_copy(&mut doc, copy_id, link_id, second);
+        assert_eq!(doc.get_page_contents(copy_id), doc.get_page_contents(first));
+        assert_eq!(doc.extract_text(&[2]).unwrap(), "first\n");
+    }
+
+    #[test]
+    fn duplicate_deep_copy() {
+        let mut doc = create_document_with_texts(&["first", "second"]);
+        let (link_id, second) = add_link(&mut doc);
+        let first = doc.get_pages()[&1];
+
+        let copy_id = doc.duplicate_page(1, DuplicateMode::DeepCopy).unwrap();
+        check_copy(&mut doc, copy_id, link_id, second);
+        let (contents, copied_contents) = (doc.get_page_contents(first), doc.get_page_contents(copy_id));
+        assert_ne!(contents, copied_contents);
+        doc.change_page_content(copy_id, b"BT /F1 48 Tf 100 600 Td (changed) Tj ET".to_vec())
+            .unwrap();
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "first\n");
+        assert_eq!(doc.extract_text(&[2]).unwrap(), "changed\n");
+    }
+
+    #[test]
+    fn duplicate_missing_page() {
+        let mut doc = create_document_with_texts(&["first"]);
+        assert!(matches!(
+            doc.duplicate_page(5, DuplicateMode::ShareContent),
+            Err(Error::PageNumberNotFound(5))
+        ));
+    }
+}
@@ -1,6 +1,5 @@
 use crate::cmap_section::{CMapParseError, CMapSection, CodeLen, SourceCode};
 use crate::parser::cmap_parser::parse;
-use crate::parser::ParserInput;
 
 use log::error;
 use rangemap::RangeInclusiveMap;
@@ -26,6 +25,14 @@ pub struct ReverseCMapEntry {
     // pub priority: u8,
 }
 
+impl ReverseCMapEntry {
+    /// The source code as written in a string, big-endian in `code_len` bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.code_len.clamp(1, 4) as usize;
+        self.source_code.to_be_bytes()[4 - len..].to_vec()
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum UnicodeCMapError {
     #[error("could not parse ToUnicode CMap: {0:#?}")]
@@ -41,7 +48,7 @@ fn from(err: CMapParseError) -> Self {
 }
 
 impl ToUnicodeCMap {
-    const REPLACEMENT_CHAR: u16 = 0xfffd;
+    pub(crate) const REPLACEMENT_CHAR: u16 = 0xfffd;
 
     pub fn new() -> ToUnicodeCMap {
         ToUnicodeCMap {
@@ -51,7 +58,7 @@ pub fn new() -> ToUnicodeCMap {
     }
 
     pub(crate) fn parse(stream_content: Vec<u8>) -> Result<ToUnicodeCMap, UnicodeCMapError> {
-        let cmap_sections = parse(ParserInput::new_extra(&stream_content[..], "cmap"))?;
+        let cmap_sections = parse(crate::parser::new_input(&stream_content[..]))?;
         Self::from_sections(cmap_sections)
     }
 
@@ -141,9 +148,9 @@ fn from_sections(cmap_sections: Vec<CMapSection>) -> Result<ToUnicodeCMap, Unico
                 }
             }
         }
-        // Sort entries by code_len so shorter byte sequences are preferred
+        // Sort entries so shorter byte sequences, then lower codes are preferred
         for entries in rev_map.values_mut() {
-            entries.sort_by_key(|e| e.code_len);
+            entries.sort_by_key(|e| (e.code_len, e.source_code));
         }
         cmap.reverse_map = Some(rev_map);
 
@@ -195,7 +202,7 @@ pub fn put_char(&mut self, code: SourceCode, code_len: CodeLen, dst: Vec<u16>) {
     }
 
     /// Gets the source code(s) for a given Unicode sequence.
-    /// Entries are sorted by code_len ascending, so shorter byte sequences are preferred.
+    /// Entries are sorted by code_len, then source_code ascending, so shorter byte sequences are preferred.
     pub fn get_source_codes_for_unicode(&self, unicode_sequence: &[u16]) -> Option<&[ReverseCMapEntry]> {
         if let Some(map) = &self.reverse_map {
             map.get(unicode_sequence).map(|v| v.as_slice())
@@ -0,0 +1,256 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
+
+use log::warn;
+use ttf_parser::{Face, GlyphId, cff};
+
+use super::glyphnames::glyph_code_point;
+use super::{CodedCharacterSet, DecodedText, decode_utf16_units};
+use crate::font_program::FontProgram;
+use crate::{Dictionary, Document, Object};
+
+/// A simple font encoding: a base encoding modified by the /Differences array of an encoding
+/// dictionary.
+///
+/// Glyph names outside the Adobe Glyph List, such as the `g36` or `cid45` names of font subsets,
+/// are looked up in the embedded font program. Names that can't be resolved keep the code's
+/// character in the base encoding and are counted in [`unresolved_names`](Self::unresolved_names).
+#[derive(Debug, Clone)]
+pub struct DifferencesEncoding {
+    codes: Vec<Option<Vec<u16>>>,
+    /// Number of glyph names of the /Differences array that couldn't be mapped to Unicode
+    pub unresolved_names: usize,
+    /// Number of glyph names outside the Adobe Glyph List mapped through the embedded font program
+    pub font_program_names: usize,
+}
+
+impl DifferencesEncoding {
+    /// Build the encoding of `font` from its encoding dictionary.
+    pub(crate) fn new(font: &Dictionary, encoding: &Dictionary, base: &CodedCharacterSet, doc: &Document) -> Self {
+        let mut codes: Vec<Option<Vec<u16>>> = base.iter().map(|code| code.map(|code| vec![code])).collect();
+        let mut unresolved_names = 0;
+        let mut font_program_names = 0;
+        // The font program is only loaded once a name isn't in the Adobe Glyph List.
+        let font_program = OnceCell::new();
+        let mut parsed_font = None;
+
+        let differences = encoding
+            .get(b"Differences")
+            .and_then(|differences| doc.dereference(differences))
+            .and_then(|(_, differences)| differences.as_array());
+        let mut code = 0;
+        for item in differences.map(Vec::as_slice).unwrap_or_default() {
+            match item {
+                Object::Integer(start) => code = *start,
+                Object::Name(name) => {
+                    let index = usize::try_from(code).ok().filter(|&index| index < codes.len());
+                    code += 1;
+                    let Some(index) = index else {
+                        continue;
+                    };
+                    let name = String::from_utf8_lossy(name);
+                    let resolved = glyph_name_to_unicode(&name).or_else(|| {
+                        let unicode = parsed_font
+                            .get_or_insert_with(|| {
+                                font_program
+                                    .get_or_init(|| FontProgram::load(font, doc))
+                                    .as_ref()
+                                    .and_then(parse_font)
+                            })
+                            .as_mut()?
+                            .unicode(&name);
+                        font_program_names += usize::from(unicode.is_some());
+                        unicode
+                    });
+                    match resolved {
+                        Some(unicode) => codes[index] = Some(unicode),
+                        None => unresolved_names += 1,
+                    }
+                }
+                _ => {}
+            }
+        }
+        if unresolved_names > 0 {
+            warn!("{unresolved_names} glyph names of a /Differences array couldn't be mapped to Unicode");
+        }
+        DifferencesEncoding {
+            codes,
+            unresolved_names,
+            font_program_names,
+        }
+    }
+
+    /// Decode text, leaving out codes without a character.
+    pub fn decode(&self, bytes: &[u8]) -> DecodedText {
+        decode_utf16_units(
+            bytes
+                .iter()
+                .filter_map(|&byte| self.codes[byte as usize].as_deref())
+                .flatten()
+                .copied(),
+        )
+    }
+
+    /// Count the codes of `bytes` without a character.
+    pub(crate) fn unmapped_codes(&self, bytes: &[u8]) -> usize {
+        bytes.iter().filter(|&&byte| self.codes[byte as usize].is_none()).count()
+    }
+
+    /// Encode text, leaving out characters without a code.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        text.chars().filter_map(|ch| self.code(ch)).collect()
+    }
+
+    /// Get the code of a character.
+    pub fn code(&self, ch: char) -> Option<u8> {
+        self.codes_of(ch).next()
+    }
+
+    /// Get all codes of a character, lowest first.
+    pub fn codes_of(&self, ch: char) -> impl Iterator<Item = u8> + '_ {
+        let mut units = [0; 2];
+        let units = ch.encode_utf16(&mut units).to_vec();
+        self.codes
+            .iter()
+            .enumerate()
+            .filter(move |(_, code)| code.as_deref() == Some(units.as_slice()))
+            .map(|(code, _)| code as u8)
+    }
+}
+
+/// Map a glyph name to Unicode following the Adobe Glyph List specification: suffixes after a
+/// period are dropped, underscores separate the components of ligatures, and components are
+/// either in the Adobe Glyph List or `uniXXXX` or `uXXXX[XX]` names.
+pub(crate) fn glyph_name_to_unicode(name: &str) -> Option<Vec<u16>> {
+    let name = name.split('.').next().unwrap_or_default();
+    if name.is_empty() {
+        return None;
+    }
+    let mut unicode = Vec::new();
+    for component in name.split('_') {
+        if let Some(code) = glyph_code_point(component) {
+            unicode.push(code);
+        } else if let Some(hex) = component.strip_prefix("uni") {
+            if hex.is_empty() || hex.len() % 4 != 0 {
+                return None;
+            }
+            for index in (0..hex.len()).step_by(4) {
+                let code = u16::from_str_radix(hex.get(index..index + 4)?, 16).ok()?;
+                if (0xD800..0xE000).contains(&code) {
+                    return None;
+                }
+                unicode.push(code);
+            }
+        } else if let Some(hex) = component.strip_prefix('u') {
+            if !(4..=6).contains(&hex.len()) {
+                return None;
+            }
+            let ch = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+            unicode.extend(ch.encode_utf16(&mut [0; 2]).iter());
+        } else {
+            return None;
+        }
+    }
+    Some(unicode)
+}
+
+/// Get the glyph index of a name made of a glyph index, like `g36`, `glyph36`, `index36` or `cid36`.
+fn glyph_index_name(name: &str) -> Option<GlyphId> {
+    let digits = ["glyph", "index", "cid", "g"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))?;
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().map(GlyphId)
+}
+
+/// Parse a font program to look glyphs up in.
+fn parse_font(program: &FontProgram) -> Option<ParsedFont<'_>> {
+    let parsed = match program {
+        FontProgram::OpenType(data) => ParsedFont::OpenType(Box::new(Face::parse(data, 0).ok()?), None),
+        FontProgram::Cff(data) => ParsedFont::Cff(Box::new(cff::Table::parse(data)?)),
+    };
+    Some(parsed)
+}
+
+/// A parsed font program.
+enum ParsedFont<'a> {
+    /// A TrueType or OpenType font, with the inverse of its Unicode cmap once built
+    OpenType(Box<Face<'a>>, Option<HashMap<GlyphId, u32>>),
+    Cff(Box<cff::Table<'a>>),
+}
+
+impl ParsedFont<'_> {
+    /// Map the glyph `name` to Unicode through the glyph it names in the font.
+    fn unicode(&mut self, name: &str) -> Option<Vec<u16>> {
+        let glyph = glyph_index_name(name).or_else(|| match self {
+            ParsedFont::OpenType(face, _) => face.glyph_index_by_name(name),
+            ParsedFont::Cff(table) => table.glyph_index_by_name(name),
+        })?;
+        if let ParsedFont::OpenType(face, inverse_cmap) = self {
+            let inverse_cmap = inverse_cmap.get_or_insert_with(|| inverse_unicode_cmap(face));
+            if let Some(ch) = inverse_cmap.get(&glyph).and_then(|&code| char::from_u32(code)) {
+                return Some(ch.encode_utf16(&mut [0; 2]).to_vec());
+            }
+        }
+        let glyph_name = match self {
+            ParsedFont::OpenType(face, _) => face.glyph_name(glyph),
+            ParsedFont::Cff(table) => table.glyph_name(glyph),
+        }?;
+        if glyph_name == name {
+            return None;
+        }
+        glyph_name_to_unicode(glyph_name)
+    }
+}
+
+/// Map the glyphs of the Unicode cmap subtables of `face` to their lowest code point.
+fn inverse_unicode_cmap(face: &Face) -> HashMap<GlyphId, u32> {
+    let mut inverse = HashMap::new();
+    let Some(cmap) = face.tables().cmap else {
+        return inverse;
+    };
+    for subtable in cmap.subtables.into_iter().filter(|subtable| subtable.is_unicode()) {
+        subtable.codepoints(|code| {
+            if let Some(glyph) = subtable.glyph_index(code) {
+                inverse
+                    .entry(glyph)
+                    .and_modify(|lowest: &mut u32| *lowest = (*lowest).min(code))
+                    .or_insert(code);
+            }
+        });
+    }
+    inverse.remove(&GlyphId(0));
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_names_follow_the_adobe_glyph_list() {
+        let unicode = |name| glyph_name_to_unicode(name).map(|units| String::from_utf16(&units).unwrap());
+        assert_eq!(unicode("A").as_deref(), Some("A"));
+        assert_eq!(unicode("Euro").as_deref(), Some("€"));
+        assert_eq!(unicode("a.sc").as_deref(), Some("a"));
+        assert_eq!(unicode("f_f_i").as_deref(), Some("ffi"));
+        assert_eq!(unicode("uni00410042").as_deref(), Some("AB"));
+        assert_eq!(unicode("u1F600").as_deref(), Some("😀"));
+        assert_eq!(unicode("uniD800"), None);
+        assert_eq!(unicode("g36"), None);
+        assert_eq!(unicode(".notdef"), None);
+    }
+
+    #[test]
+    fn glyph_index_names() {
+        assert_eq!(glyph_index_name("g36"), Some(GlyphId(36)));
+        assert_eq!(glyph_index_name("glyph7"), Some(GlyphId(7)));
+        assert_eq!(glyph_index_name("index0012"), Some(GlyphId(12)));
+        assert_eq!(glyph_index_name("cid045"), Some(GlyphId(45)));
+        assert_eq!(glyph_index_name("g"), None);
+        assert_eq!(glyph_index_name("gamma"), None);
+        assert_eq!(glyph_index_name("g1x"), None);
+    }
+}
@@ -1,8 +1,28 @@
-pub enum Glyph {}
+/// Define the glyph name constants, and a table of them to look glyph names up by.
+macro_rules! glyph_names {
+    ($(pub const $name:ident: u16 = $code:literal;)*) => {
+        pub enum Glyph {}
 
-#[allow(dead_code)]
-#[allow(non_upper_case_globals)]
-impl Glyph {
+        #[allow(dead_code)]
+        #[allow(non_upper_case_globals)]
+        impl Glyph {
+            $(pub const $name: u16 = $code;)*
+        }
+
+        /// Glyph names of the Adobe Glyph List with their code points, sorted by name.
+        const GLYPH_NAMES: &[(&str, u16)] = &[$((stringify!($name), $code)),*];
+    };
+}
+
+/// Get the code point of a glyph name of the Adobe Glyph List.
+pub(crate) fn glyph_code_point(name: &str) -> Option<u16> {
+    GLYPH_NAMES
+        .binary_search_by(|(glyph, _)| (*glyph).cmp(name))
+        .ok()
+        .map(|index| GLYPH_NAMES[index].1)
+}
+
+glyph_names! {
     pub const A: u16 = 0x0041;
     pub const AE: u16 = 0x00c6;
     pub const AEacute: u16 = 0x01fc;
@@ -1,14 +1,19 @@
 pub mod cmap;
+mod differences;
 mod glyphnames;
 mod mappings;
+mod reverse;
+mod utf16;
 
 use crate::Error;
 use crate::Result;
 use cmap::ToUnicodeCMap;
-use encoding_rs::UTF_16BE;
 use log::debug;
 use crate::parser_aux::substr;
+pub use self::differences::DifferencesEncoding;
 pub use self::mappings::*;
+pub use self::reverse::ReverseMapping;
+pub use self::utf16::{DecodedText, decode_utf16_be, decode_utf16_units, encode_utf16_be};
 
 pub fn bytes_to_string(encoding: &CodedCharacterSet, bytes: &[u8]) -> String {
     let code_points = bytes
@@ -29,6 +34,7 @@ pub enum Encoding<'a> {
     OneByteEncoding(&'a CodedCharacterSet),
     SimpleEncoding(&'a [u8]),
     UnicodeMapEncoding(ToUnicodeCMap),
+    DifferencesEncoding(DifferencesEncoding),
 }
 
 impl std::fmt::Debug for Encoding<'_> {
@@ -38,51 +44,55 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             Self::OneByteEncoding(_arg0) => f.debug_tuple("OneByteEncoding").finish(),
             Self::SimpleEncoding(arg0) => f.debug_tuple("SimpleEncoding").field(arg0).finish(),
             Self::UnicodeMapEncoding(_arg0) => f.debug_tuple("UnicodeMapEncoding").finish(),
+            Self::DifferencesEncoding(arg0) => f
+                .debug_tuple("DifferencesEncoding")
+                .field(&arg0.unresolved_names)
+                .finish(),
         }
     }
 }
 
 impl Encoding<'_> {
     pub fn bytes_to_string(&self, bytes: &[u8]) -> Result<String> {
+        self.decode(bytes).map(|decoded| decoded.into_text("decoding text"))
+    }
+
+    /// Decode text, reporting how many lone UTF-16 surrogates were replaced by U+FFFD.
+    pub fn decode(&self, bytes: &[u8]) -> Result<DecodedText> {
+        match self {
+            Self::OneByteEncoding(map) => Ok(DecodedText {
+                text: bytes_to_string(map, bytes),
+                replacements: 0,
+            }),
+            Self::SimpleEncoding(b"UniGB-UCS2-H") | Self::SimpleEncoding(b"UniGB-UTF16-H") => Ok(decode_utf16_be(bytes)),
+            Self::UnicodeMapEncoding(unicode_map) => Ok(decode_utf16_units(
+                unicode_map_codes(unicode_map, bytes)
+                    .into_iter()
+                    .flat_map(|code| code.unwrap_or(vec![ToUnicodeCMap::REPLACEMENT_CHAR])),
+            )),
+            Self::DifferencesEncoding(encoding) => Ok(encoding.decode(bytes)),
+            Self::SimpleEncoding(b"WinAnsiEncoding") => Ok(DecodedText {
+                text: bytes_to_string(&WIN_ANSI_ENCODING, bytes),
+                replacements: 0,
+            }),
+            Self::SimpleEncoding(_) => Err(Error::CharacterEncoding),
+        }
+    }
+
+    /// Count the codes of `bytes` the encoding has no character for.
+    pub(crate) fn unmapped_codes(&self, bytes: &[u8]) -> usize {
         match self {
-            Self::OneByteEncoding(map) => Ok(bytes_to_string(map, bytes)),
+            Self::OneByteEncoding(map) => bytes.iter().filter(|&&byte| map[byte as usize].is_none()).count(),
             Self::SimpleEncoding(b"UniGB-UCS2-H") | Self::SimpleEncoding(b"UniGB-UTF16-H") => {
-                Ok(UTF_16BE.decode(bytes).0.to_string())
+                decode_utf16_be(bytes).replacements
             }
-            Self::UnicodeMapEncoding(unicode_map) => {
-                let mut output_bytes = Vec::new();
-
-                // source codes can have a variadic length from 1 to 4 bytes
-                let mut bytes_in_considered_code = 0u8;
-                let mut considered_source_code = 0u32;
-                for byte in bytes {
-                    if bytes_in_considered_code == 4 {
-                        let mut value = unicode_map.get_or_replacement_char(considered_source_code, 4);
-                        considered_source_code = 0;
-                        bytes_in_considered_code = 0;
-                        output_bytes.append(&mut value);
-                    }
-                    bytes_in_considered_code += 1;
-                    considered_source_code = considered_source_code * 256 + *byte as u32;
-                    if let Some(mut value) = unicode_map.get(considered_source_code, bytes_in_considered_code) {
-                        considered_source_code = 0;
-                        bytes_in_considered_code = 0;
-                        output_bytes.append(&mut value);
-                    }
-                }
-                if bytes_in_considered_code > 0 {
-                    let mut value =
-                        unicode_map.get_or_replacement_char(considered_source_code, bytes_in_considered_code);
-                    output_bytes.append(&mut value);
-                }
-                let utf16_str: Vec<u8> = output_bytes
-                    .iter()
-                    .flat_map(|it| [(it / 256) as u8, (it % 256) as u8])
-                    .collect();
-                Ok(UTF_16BE.decode(&utf16_str).0.to_string())
-            }
-            Self::SimpleEncoding(b"WinAnsiEncoding") => Ok(bytes_to_string(&WIN_ANSI_ENCODING, bytes)),
-            Self::SimpleEncoding(_) => Err(Error::CharacterEncoding),
+            Self::UnicodeMapEncoding(unicode_map) => unicode_map_codes(unicode_map, bytes)
+                .iter()
+                .filter(|code| code.is_none())
+                .count(),
+            Self::DifferencesEncoding(encoding) => encoding.unmapped_codes(bytes),
+            Self::SimpleEncoding(b"WinAnsiEncoding") => Self::OneByteEncoding(&WIN_ANSI_ENCODING).unmapped_codes(bytes),
+            Self::SimpleEncoding(_) => bytes.len(),
         }
     }
 
@@ -91,6 +101,7 @@ pub fn string_to_bytes(&self, text: &str) -> Vec<u8> {
             Self::OneByteEncoding(map) => string_to_bytes(map, text),
             Self::SimpleEncoding(b"UniGB-UCS2-H") | Self::SimpleEncoding(b"UniGB-UTF16-H") => encode_utf16_be(text),
             Self::SimpleEncoding(b"WinAnsiEncoding") => string_to_bytes(&WIN_ANSI_ENCODING, text),
+            Self::DifferencesEncoding(encoding) => encoding.encode(text),
             Self::UnicodeMapEncoding(unicode_map) => {
                 let mut result_bytes = Vec::new();
 
@@ -100,20 +111,7 @@ pub fn string_to_bytes(&self, text: &str) -> Vec<u8> {
 
                     if let Some(entries) = unicode_map.get_source_codes_for_unicode(&current_unicode_seq) {
                         if let Some(entry) = entries.first() {
-                            let mut bytes_for_code = Vec::new();
-                            let val = entry.source_code;
-                            match entry.code_len {
-                                1 => bytes_for_code.push(val as u8),
-                                2 => bytes_for_code.extend_from_slice(&(val as u16).to_be_bytes()),
-                                3 => {
-                                    bytes_for_code.push((val >> 16) as u8);
-                                    bytes_for_code.push((val >> 8) as u8);
-                                    bytes_for_code.push(val as u8);
-                                }
-                                4 => bytes_for_code.extend_from_slice(&val.to_be_bytes()),
-                                _ => { /* Should not happen */ }
-                            }
-                            result_bytes.extend(bytes_for_code);
+                            result_bytes.extend(entry.to_bytes());
                         } else {
                             // No specific entry, handle as unmappable
                             log::warn!(
@@ -138,16 +136,31 @@ pub fn string_to_bytes(&self, text: &str) -> Vec<u8> {
     }
 }
 
-/// Encodes the given `str` to UTF-16BE.
-/// The recommended way to encode text strings, as it supports all of
-/// unicode and all major PDF readers support it.
-pub fn encode_utf16_be(text: &str) -> Vec<u8> {
-    // Prepend BOM to the mark string as UTF-16BE encoded.
-    let bom: u16 = 0xFEFF;
-    let mut bytes = vec![];
-    bytes.extend([bom].iter().flat_map(|b| b.to_be_bytes()));
-    bytes.extend(text.encode_utf16().flat_map(|b| b.to_be_bytes()));
-    bytes
+/// Split `bytes` into the codes of `unicode_map`, each with its UTF-16 text if the map has one.
+fn unicode_map_codes(unicode_map: &ToUnicodeCMap, bytes: &[u8]) -> Vec<Option<Vec<u16>>> {
+    let mut codes = Vec::new();
+
+    // source codes can have a variadic length from 1 to 4 bytes
+    let mut bytes_in_considered_code = 0u8;
+    let mut considered_source_code = 0u32;
+    for byte in bytes {
+        if bytes_in_considered_code == 4 {
+            codes.push(unicode_map.get(considered_source_code, 4));
+            considered_source_code = 0;
+            bytes_in_considered_code = 0;
+        }
+        bytes_in_considered_code += 1;
+        considered_source_code = considered_source_code * 256 + *byte as u32;
+        if let Some(value) = unicode_map.get(considered_source_code, bytes_in_considered_code) {
+            considered_source_code = 0;
+            bytes_in_considered_code = 0;
+            codes.push(Some(value));
+        }
+    }
+    if bytes_in_considered_code > 0 {
+        codes.push(unicode_map.get(considered_source_code, bytes_in_considered_code));
+    }
+    codes
 }
 
 /// Encodes the given `str` to UTF-8. This method of encoding text strings
@@ -0,0 +1,130 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{CodedCharacterSet, Encoding, WIN_ANSI_ENCODING};
+use crate::{Error, Result};
+
+/// Maps characters back to the codes of a font, for writing text into the font's strings.
+///
+/// A ToUnicode CMap may map several codes to the same character, as when a subset has two
+/// glyphs for it. Codes found in the strings given to [`prefer`](Self::prefer) win, otherwise
+/// the shortest and then lowest code is written.
+pub struct ReverseMapping<'a> {
+    encoding: &'a Encoding<'a>,
+    nearby: HashSet<Vec<u8>>,
+}
+
+impl<'a> ReverseMapping<'a> {
+    pub fn new(encoding: &'a Encoding<'a>) -> Self {
+        ReverseMapping {
+            encoding,
+            nearby: HashSet::new(),
+        }
+    }
+
+    /// Prefer the codes of `bytes`, a string in this encoding near the text being written.
+    pub fn prefer(&mut self, bytes: &[u8]) {
+        let codes: Vec<Vec<u8>> = self.split_codes(bytes).map(<[u8]>::to_vec).collect();
+        self.nearby.extend(codes);
+    }
+
+    /// All codes of `ch`, shortest and then lowest first.
+    pub fn codes(&self, ch: char) -> Vec<Vec<u8>> {
+        let mut units = [0; 2];
+        let units: &[u16] = ch.encode_utf16(&mut units);
+        let utf16 = || vec![units.iter().flat_map(|unit| unit.to_be_bytes()).collect()];
+        match self.encoding {
+            Encoding::OneByteEncoding(map) => one_byte_codes(map, units),
+            Encoding::SimpleEncoding(b"WinAnsiEncoding") => one_byte_codes(&WIN_ANSI_ENCODING, units),
+            Encoding::SimpleEncoding(b"UniGB-UCS2-H") if units.len() == 1 => utf16(),
+            Encoding::SimpleEncoding(b"UniGB-UTF16-H") => utf16(),
+            Encoding::UnicodeMapEncoding(cmap) => cmap
+                .get_source_codes_for_unicode(units)
+                .unwrap_or_default()
+                .iter()
+                .map(|entry| entry.to_bytes())
+                .collect(),
+            Encoding::DifferencesEncoding(encoding) => encoding.codes_of(ch).map(|code| vec![code]).collect(),
+            // Text in unknown encodings is written as is, which only works out for ASCII.
+            Encoding::SimpleEncoding(_) if ch.is_ascii() => vec![vec![ch as u8]],
+            Encoding::SimpleEncoding(_) => Vec::new(),
+        }
+    }
+
+    /// The code to write for `ch`, if the font has one.
+    pub fn code(&self, ch: char) -> Option<Vec<u8>> {
+        let codes = self.codes(ch);
+        let nearby = codes.iter().position(|code| self.nearby.contains(code)).unwrap_or(0);
+        codes.into_iter().nth(nearby)
+    }
+
+    /// Encode `text`, or `None` if a character of it has no code.
+    fn encode_exact(&self, text: &str) -> Option<Vec<u8>> {
+        text.chars().try_fold(Vec::new(), |mut bytes, ch| {
+            bytes.extend(self.code(ch)?);
+            Some(bytes)
+        })
+    }
+
+    /// Encode `text`.
+    ///
+    /// A character without a code is written as its entry in `transliterations`, or else as
+    /// `default`, if the font can represent those. The characters left over fail the encoding
+    /// with [`Error::UnsupportedCharacters`].
+    pub fn encode(
+        &self, text: &str, transliterations: &HashMap<char, String>, default: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut unsupported = Vec::new();
+        for ch in text.chars() {
+            let code = self.code(ch).or_else(|| {
+                let transliteration = transliterations.get(&ch).and_then(|text| self.encode_exact(text));
+                transliteration.or_else(|| default.and_then(|text| self.encode_exact(text)))
+            });
+            match code {
+                Some(code) => bytes.extend(code),
+                None if !unsupported.contains(&ch) => unsupported.push(ch),
+                None => {}
+            }
+        }
+        if unsupported.is_empty() {
+            Ok(bytes)
+        } else {
+            Err(Error::UnsupportedCharacters(unsupported))
+        }
+    }
+
+    /// Split a string in this encoding into its codes.
+    fn split_codes<'b>(&self, bytes: &'b [u8]) -> Box<dyn Iterator<Item = &'b [u8]> + 'b> {
+        match self.encoding {
+            Encoding::UnicodeMapEncoding(cmap) => {
+                // Codes are 1 to 4 bytes long, the shortest one the CMap knows is taken.
+                let mut codes = Vec::new();
+                let mut start = 0;
+                for end in 1..=bytes.len() {
+                    let code = bytes[start..end].iter().fold(0, |code, &byte| code * 256 + byte as u32);
+                    let len = (end - start) as u8;
+                    if len == 4 || cmap.get(code, len).is_some() {
+                        codes.push(&bytes[start..end]);
+                        start = end;
+                    }
+                }
+                Box::new(codes.into_iter())
+            }
+            Encoding::SimpleEncoding(b"UniGB-UCS2-H") | Encoding::SimpleEncoding(b"UniGB-UTF16-H") => {
+                Box::new(bytes.chunks(2))
+            }
+            _ => Box::new(bytes.chunks(1)),
+        }
+    }
+}
+
+fn one_byte_codes(map: &CodedCharacterSet, units: &[u16]) -> Vec<Vec<u8>> {
+    if units.len() != 1 {
+        return Vec::new();
+    }
+    map.iter()
+        .enumerate()
+        .filter(|(_, code)| **code == Some(units[0]))
+        .map(|(byte, _)| vec![byte as u8])
+        .collect()
+}
@@ -0,0 +1,97 @@
+use log::warn;
+
+/// Text decoded from UTF-16, with the number of code units that couldn't be decoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    /// Number of lone surrogates, and trailing odd bytes, replaced by U+FFFD
+    pub replacements: usize,
+}
+
+impl DecodedText {
+    /// Get the text, logging a warning if anything had to be replaced.
+    pub(crate) fn into_text(self, context: &str) -> String {
+        if self.replacements > 0 {
+            warn!(
+                "{context}: replaced {} lone UTF-16 surrogates with U+FFFD",
+                self.replacements
+            );
+        }
+        self.text
+    }
+}
+
+/// Decode UTF-16 code units, pairing surrogates and replacing lone surrogates with U+FFFD.
+pub fn decode_utf16_units<I: IntoIterator<Item = u16>>(units: I) -> DecodedText {
+    let mut decoded = DecodedText::default();
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok(c) => decoded.text.push(c),
+            Err(_) => {
+                decoded.text.push(char::REPLACEMENT_CHARACTER);
+                decoded.replacements += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Decode UTF-16BE bytes without byte order mark. A trailing odd byte is replaced by U+FFFD.
+pub fn decode_utf16_be(bytes: &[u8]) -> DecodedText {
+    let units = bytes.chunks_exact(2).map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+    let mut decoded = decode_utf16_units(units);
+    if bytes.len() % 2 != 0 {
+        decoded.text.push(char::REPLACEMENT_CHARACTER);
+        decoded.replacements += 1;
+    }
+    decoded
+}
+
+/// Encodes the given `str` to UTF-16BE.
+/// The recommended way to encode text strings, as it supports all of
+/// unicode and all major PDF readers support it.
+pub fn encode_utf16_be(text: &str) -> Vec<u8> {
+    // Prepend BOM to the mark string as UTF-16BE encoded.
+    let bom: u16 = 0xFEFF;
+    let mut bytes = vec![];
+    bytes.extend([bom].iter().flat_map(|b| b.to_be_bytes()));
+    bytes.extend(text.encode_utf16().flat_map(|b| b.to_be_bytes()));
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Object, StringFormat, decode_text_string};
+
+    #[test]
+    fn astral_characters_are_encoded_as_surrogate_pairs() {
+        let object = Object::string_utf16("a😀🦀");
+        let Object::String(bytes, StringFormat::Hexadecimal) = &object else {
+            panic!("{object:?}");
+        };
+        assert_eq!(bytes, b"\xFE\xFF\x00a\xD8\x3D\xDE\x00\xD8\x3E\xDD\x80");
+        assert_eq!(decode_text_string(&object).unwrap(), "a😀🦀");
+    }
+
+    #[test]
+    fn lone_surrogates_are_replaced_and_counted() {
+        let decoded = decode_utf16_be(b"\xD8\x3D\xDE\x00\xD8\x00\x00A\xDC\x00");
+        assert_eq!(decoded.text, "😀\u{FFFD}A\u{FFFD}");
+        assert_eq!(decoded.replacements, 2);
+
+        let decoded = decode_utf16_be(b"\x00A\x00");
+        assert_eq!(decoded.text, "A\u{FFFD}");
+        assert_eq!(decoded.replacements, 1);
+
+        let swapped = decode_utf16_units([0xDE00, 0xD83D]);
+        assert_eq!(swapped.text, "\u{FFFD}\u{FFFD}");
+        assert_eq!(swapped.replacements, 2);
+    }
+
+    #[test]
+    fn text_strings_with_lone_surrogates_decode_lossily() {
+        let object = Object::String(b"\xFE\xFF\x00O\xD8\x00\x00K".to_vec(), StringFormat::Literal);
+        assert_eq!(decode_text_string(&object).unwrap(), "O\u{FFFD}K");
+    }
+}
@@ -1,16 +1,20 @@
 mod algorithms;
 pub mod crypt_filters;
+mod crypto_provider;
+mod options;
 mod pkcs5;
 mod rc4;
 
 use bitflags::bitflags;
-use crate::{Dictionary, Document, Error, Object, ObjectId};
+use crate::{Dictionary, Document, Error, Object, ObjectId, Stream};
 use crypt_filters::*;
 use std::collections::BTreeMap;
 use std::sync::Arc;
 use thiserror::Error;
 
 pub use algorithms::PasswordAlgorithm;
+pub use crypto_provider::{CryptoProvider, DefaultCryptoProvider, SeededCryptoProvider};
+pub use options::{EncryptionAlgorithm, EncryptionOptions};
 
 #[derive(Error, Debug)]
 pub enum DecryptionError {
@@ -49,6 +53,8 @@ pub enum DecryptionError {
     NotDecryptable,
     #[error("the supplied password is incorrect")]
     IncorrectPassword,
+    #[error("the encrypted permissions (/Perms) don't match /P and /EncryptMetadata")]
+    PermissionsMismatch,
 
     #[error("the document uses an encryption scheme that is not implemented in lopdf")]
     UnsupportedEncryption,
@@ -118,6 +124,44 @@ fn default() -> Self {
 }
 
 impl Permissions {
+    /// The permissions of a /P value. /P holds 32 bits, written as a signed integer but by some
+    /// producers as an unsigned one, so only its low 32 bits are read and sign-extended.
+    pub fn from_p(value: i64) -> Self {
+        Permissions::from_bits_retain(value as i32 as i64 as u64)
+    }
+
+    /// The /P value of the permissions, the 32 bits as a signed integer.
+    pub fn p_value(self) -> i64 {
+        i64::from(self.bits() as u32 as i32)
+    }
+
+    /// What the permissions allow with a security handler of `revision`.
+    ///
+    /// Revision 2 only defines the printing, modifying, copying and annotating bits, and the
+    /// reserved bits 9 to 12 are set. There printing is at full quality, annotating includes
+    /// filling in forms, modifying includes assembling and copying includes copying for
+    /// accessibility, so those bits are derived from the first four.
+    pub fn for_revision(self, revision: i64) -> Self {
+        if revision >= 3 {
+            return self;
+        }
+        let mut permissions = self.difference(
+            Permissions::FILLABLE
+                | Permissions::COPYABLE_FOR_ACCESSIBILITY
+                | Permissions::ASSEMBLABLE
+                | Permissions::PRINTABLE_IN_HIGH_QUALITY,
+        );
+        for (defined, derived) in [
+            (Permissions::PRINTABLE, Permissions::PRINTABLE_IN_HIGH_QUALITY),
+            (Permissions::MODIFIABLE, Permissions::ASSEMBLABLE),
+            (Permissions::COPYABLE, Permissions::COPYABLE_FOR_ACCESSIBILITY),
+            (Permissions::ANNOTABLE, Permissions::FILLABLE),
+        ] {
+            permissions.set(derived, self.contains(defined));
+        }
+        permissions
+    }
+
     fn correct_bits(self) -> Self {
         let mut bits = self.bits();
 
@@ -135,6 +179,62 @@ fn correct_bits(self) -> Self {
     }
 }
 
+/// The password a document was opened with, see [`Document::authentication_result`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PasswordKind {
+    /// The owner password, which grants every permission
+    Owner,
+    /// A user password, which grants the permissions of the /P entry
+    User,
+    /// No password, the user password being empty
+    Empty,
+}
+
+/// A password to open an encrypted document with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Password {
+    /// Text, encoded as the security handler of the document specifies, see
+    /// [`Document::authenticate_password`]
+    Text(String),
+    /// The bytes the security handler hashes, used as they are
+    Bytes(Vec<u8>),
+}
+
+impl From<&str> for Password {
+    fn from(password: &str) -> Self {
+        Password::Text(password.to_string())
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Password::Text(password)
+    }
+}
+
+impl From<&[u8]> for Password {
+    fn from(password: &[u8]) -> Self {
+        Password::Bytes(password.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Password {
+    fn from(password: Vec<u8>) -> Self {
+        Password::Bytes(password)
+    }
+}
+
+/// What becomes of the file identifier, the /ID of the trailer, when the encryption of a document
+/// is removed with [`Document::remove_encryption`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FileIdPolicy {
+    /// Keep the identifier, so the decrypted file is still known as the same file
+    #[default]
+    Preserve,
+    /// Replace the identifier with new random bytes, as for a new file
+    Regenerate,
+}
+
 #[derive(Clone, Debug)]
 pub enum EncryptionVersion<'a> {
     /// (PDF 1.4; deprecated in PDF 2.0) Indicates the use of encryption of data using the RC4 or
@@ -214,13 +314,32 @@ pub struct EncryptionState {
     pub(crate) user_encrypted: Vec<u8>,
     pub(crate) permissions: Permissions,
     pub(crate) permission_encrypted: Vec<u8>,
+    /// `None` for [`DefaultCryptoProvider`]
+    pub(crate) crypto_provider: Option<Arc<dyn CryptoProvider>>,
+    /// The password the state was decoded with, `None` for states computed to encrypt
+    pub(crate) password_kind: Option<PasswordKind>,
+    /// The first element of the file identifier the key of a revision 2 to 4 state was derived
+    /// from, `None` for other revisions and states computed to encrypt
+    pub(crate) file_id: Option<Vec<u8>>,
 }
 
 impl TryFrom<EncryptionVersion<'_>> for EncryptionState {
     type Error = Error;
 
     fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error> {
-        match version {
+        EncryptionState::with_crypto_provider(version, DefaultCryptoProvider)
+    }
+}
+
+impl EncryptionState {
+    /// Like [`EncryptionState::try_from`], drawing the random bytes of the state and of the objects
+    /// it encrypts from `provider`.
+    pub fn with_crypto_provider(
+        version: EncryptionVersion, provider: impl CryptoProvider + 'static,
+    ) -> Result<EncryptionState, Error> {
+        let provider: Arc<dyn CryptoProvider> = Arc::new(provider);
+        let random = provider.as_ref();
+        let state: Result<Self, Error> = match version {
             EncryptionVersion::V1 {
                 document,
                 owner_password,
@@ -238,8 +357,8 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                     ..Default::default()
                 };
 
-                let owner_password = algorithm.sanitize_password_r4(owner_password)?;
-                let user_password = algorithm.sanitize_password_r4(user_password)?;
+                let owner_password = algorithm.encode_password(owner_password)?;
+                let user_password = algorithm.encode_password(user_password)?;
 
                 algorithm.owner_value = algorithm.compute_hashed_owner_password_r4(
                     Some(&owner_password),
@@ -286,8 +405,8 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                     ..Default::default()
                 };
 
-                let owner_password = algorithm.sanitize_password_r4(owner_password)?;
-                let user_password = algorithm.sanitize_password_r4(user_password)?;
+                let owner_password = algorithm.encode_password(owner_password)?;
+                let user_password = algorithm.encode_password(user_password)?;
 
                 algorithm.owner_value = algorithm.compute_hashed_owner_password_r4(
                     Some(&owner_password),
@@ -297,6 +416,7 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                 algorithm.user_value = algorithm.compute_hashed_user_password_r3_r4(
                     document,
                     &user_password,
+                    random,
                 )?;
 
                 let file_encryption_key = algorithm.compute_file_encryption_key_r4(
@@ -337,8 +457,8 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                     ..Default::default()
                 };
 
-                let owner_password = algorithm.sanitize_password_r4(owner_password)?;
-                let user_password = algorithm.sanitize_password_r4(user_password)?;
+                let owner_password = algorithm.encode_password(owner_password)?;
+                let user_password = algorithm.encode_password(user_password)?;
 
                 algorithm.owner_value = algorithm.compute_hashed_owner_password_r4(
                     Some(&owner_password),
@@ -348,6 +468,7 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                 algorithm.user_value = algorithm.compute_hashed_user_password_r3_r4(
                     document,
                     &user_password,
+                    random,
                 )?;
 
                 let file_encryption_key = algorithm.compute_file_encryption_key_r4(
@@ -395,12 +516,13 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                     ..Default::default()
                 };
 
-                let owner_password = algorithm.sanitize_password_r6(owner_password)?;
-                let user_password = algorithm.sanitize_password_r6(user_password)?;
+                let owner_password = algorithm.encode_password(owner_password)?;
+                let user_password = algorithm.encode_password(user_password)?;
 
                 let (user_value, user_encrypted) = algorithm.compute_hashed_user_password_r6(
                     file_encryption_key,
                     user_password,
+                    random,
                 )?;
 
                 algorithm.user_value = user_value;
@@ -409,6 +531,7 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                 let (owner_value, owner_encrypted) = algorithm.compute_hashed_owner_password_r6(
                     file_encryption_key,
                     owner_password,
+                    random,
                 )?;
 
                 algorithm.owner_value = owner_value;
@@ -416,6 +539,7 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
 
                 algorithm.permission_encrypted = algorithm.compute_permissions(
                     file_encryption_key,
+                    random,
                 )?;
 
                 Ok(Self {
@@ -433,6 +557,9 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                     user_encrypted: algorithm.user_encrypted,
                     permissions: algorithm.permissions,
                     permission_encrypted: algorithm.permission_encrypted,
+                    crypto_provider: None,
+                    password_kind: None,
+                    file_id: None,
                 })
             }
             EncryptionVersion::V5 {
@@ -459,12 +586,13 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                     ..Default::default()
                 };
 
-                let owner_password = algorithm.sanitize_password_r6(owner_password)?;
-                let user_password = algorithm.sanitize_password_r6(user_password)?;
+                let owner_password = algorithm.encode_password(owner_password)?;
+                let user_password = algorithm.encode_password(user_password)?;
 
                 let (user_value, user_encrypted) = algorithm.compute_hashed_user_password_r6(
                     file_encryption_key,
                     user_password,
+                    random,
                 )?;
 
                 algorithm.user_value = user_value;
@@ -473,6 +601,7 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                 let (owner_value, owner_encrypted) = algorithm.compute_hashed_owner_password_r6(
                     file_encryption_key,
                     owner_password,
+                    random,
                 )?;
 
                 algorithm.owner_value = owner_value;
@@ -480,6 +609,7 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
 
                 algorithm.permission_encrypted = algorithm.compute_permissions(
                     file_encryption_key,
+                    random,
                 )?;
 
                 Ok(Self {
@@ -497,13 +627,18 @@ fn try_from(version: EncryptionVersion) -> Result<EncryptionState, Self::Error>
                     user_encrypted: algorithm.user_encrypted,
                     permissions: algorithm.permissions,
                     permission_encrypted: algorithm.permission_encrypted,
+                    crypto_provider: None,
+                    password_kind: None,
+                    file_id: None,
                 })
             }
-        }
+        };
+        Ok(EncryptionState {
+            crypto_provider: Some(provider),
+            ..state?
+        })
     }
-}
 
-impl EncryptionState {
     pub fn version(&self) -> i64 {
         self.version
     }
@@ -610,6 +745,16 @@ pub fn decode<P>(
             ..Default::default()
         };
 
+        // The keys of revisions 5 and 6 don't depend on the file identifier.
+        if algorithm.revision < 5 {
+            let file_id = document.trailer.get(b"ID").and_then(|id| document.resolve_deep(id, 2)).ok();
+            state.file_id = file_id.as_ref()
+                .and_then(|id| id.as_array().ok())
+                .and_then(|id| id.first())
+                .and_then(|id| id.as_str().ok())
+                .map(<[u8]>::to_vec);
+        }
+
         // StmF and StrF are meaningful only when the value of V is 4 (PDF 1.5) or 5 (PDF 2.0).
         if algorithm.version == 4 || algorithm.version == 5 {
             if let Ok(stream_filter) = document.get_encrypted()
@@ -648,7 +793,7 @@ pub fn encode(&self) -> Result<Dictionary, DecryptionError> {
 
         encrypted.set(b"O", Object::string_literal(self.owner_value.clone()));
         encrypted.set(b"U", Object::string_literal(self.user_value.clone()));
-        encrypted.set(b"P", Object::Integer(self.permissions.bits() as i64));
+        encrypted.set(b"P", Object::Integer(self.permissions.p_value()));
 
         if self.revision >= 4 {
             let mut filters = Dictionary::new();
@@ -658,6 +803,19 @@ pub fn encode(&self) -> Result<Dictionary, DecryptionError> {
 
                 filter.set(b"Type", Object::Name(b"CryptFilter".to_vec()));
                 filter.set(b"CFM", Object::Name(crypt_filter.method().to_vec()));
+                filter.set(b"AuthEvent", Object::Name(b"DocOpen".to_vec()));
+
+                // The length of the file encryption key in bytes, as Acrobat writes it: the
+                // /Length of the encryption dictionary is in bits, this one isn't.
+                let length = match crypt_filter.method() {
+                    b"AESV2" => Some(16),
+                    b"AESV3" => Some(32),
+                    b"V2" => self.key_length.map(|length| length as i64 / 8),
+                    _ => None,
+                };
+                if let Some(length) = length {
+                    filter.set(b"Length", Object::Integer(length));
+                }
 
                 filters.set(name.to_vec(), Object::Dictionary(filter));
             }
@@ -677,11 +835,85 @@ pub fn encode(&self) -> Result<Dictionary, DecryptionError> {
     }
 
     pub fn get_stream_filter(&self) -> Arc<dyn CryptFilter> {
-        self.crypt_filters.get(&self.stream_filter).cloned().unwrap_or(Arc::new(Rc4CryptFilter))
+        self.crypt_filter(&self.stream_filter)
     }
 
     pub fn get_string_filter(&self) -> Arc<dyn CryptFilter> {
-        self.crypt_filters.get(&self.string_filter).cloned().unwrap_or(Arc::new(Rc4CryptFilter))
+        self.crypt_filter(&self.string_filter)
+    }
+
+    /// Check that the state, as decoded from an encrypted document, can encrypt a document the
+    /// same way again: lopdf writes the standard security handler revisions 2 to 6, and only the
+    /// crypt filter methods it implements.
+    pub(crate) fn check_writable(&self) -> Result<(), Error> {
+        if !matches!((self.version, self.revision), (1 | 2, 2 | 3) | (4, 4) | (5, 5 | 6)) {
+            return Err(Error::UnwritableEncryption(format!(
+                "version {} revision {} of the standard security handler",
+                self.version, self.revision
+            )));
+        }
+        if self.version >= 4 {
+            for name in [&self.stream_filter, &self.string_filter] {
+                let known = name.is_empty() || name == b"Identity" || self.crypt_filters.contains_key(name);
+                if !known {
+                    return Err(Error::UnwritableEncryption(format!(
+                        "crypt filter /{} with an unsupported method",
+                        String::from_utf8_lossy(name)
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The crypt filter named by /StmF or /StrF. `Identity`, the default from version 4 on,
+    /// leaves data as it is.
+    fn crypt_filter(&self, name: &[u8]) -> Arc<dyn CryptFilter> {
+        if name == b"Identity" || (self.version >= 4 && name.is_empty()) {
+            return Arc::new(IdentityCryptFilter);
+        }
+        self.crypt_filters.get(name).cloned().unwrap_or(Arc::new(Rc4CryptFilter))
+    }
+}
+
+/// The crypt filter a stream names with a Crypt filter in its /Filter, overriding the default
+/// filter for streams, and the index of the Crypt filter.
+///
+/// The stream's DecodeParms entry shall contain a Crypt filter decode parameters dictionary whose
+/// Name entry specifies the particular crypt filter that shall be used (if missing, Identity is
+/// used). Like the parameters of any filter, it's in an array when the stream has several filters.
+fn stream_crypt_filter(state: &EncryptionState, obj: &Object) -> Option<(usize, Arc<dyn CryptFilter>)> {
+    let stream = obj.as_stream().ok()?;
+    let index = stream.filters().ok()?.iter().position(|filter| *filter == b"Crypt")?;
+    let params = match stream.dict.get(b"DecodeParms") {
+        Ok(Object::Array(params)) => params.get(index),
+        params => params.ok(),
+    };
+    let name = params
+        .and_then(|params| params.as_dict().ok())
+        .and_then(|params| params.get(b"Name").and_then(Object::as_name).ok());
+    let crypt_filter = name
+        .filter(|&name| name != b"Identity")
+        .and_then(|name| state.crypt_filters.get(name).cloned())
+        .unwrap_or(Arc::new(IdentityCryptFilter));
+    Some((index, crypt_filter))
+}
+
+/// Remove the Crypt filter at `index` of the /Filter of a decrypted stream, with its parameters.
+fn remove_crypt_filter(stream: &mut Stream, index: usize) {
+    let filters = match stream.dict.get_mut(b"Filter") {
+        Ok(Object::Array(filters)) if filters.len() > 1 => filters,
+        _ => {
+            stream.dict.remove(b"Filter");
+            stream.dict.remove(b"DecodeParms");
+            return;
+        }
+    };
+    filters.remove(index);
+    if let Ok(Object::Array(params)) = stream.dict.get_mut(b"DecodeParms") {
+        if index < params.len() {
+            params.remove(index);
+        }
     }
 }
 
@@ -703,19 +935,8 @@ pub fn encrypt_object(state: &EncryptionState, obj_id: ObjectId, obj: &mut Objec
     }
 
     // A stream filter type, the Crypt filter can be specified for any stream in the document to
-    // override the default filter for streams. The stream's DecodeParms entry shall contain a
-    // Crypt filter decode parameters dictionary whose Name entry specifies the particular crypt
-    // filter that shell be used (if missing, Identity is used).
-    let override_crypt_filter = obj.as_stream().ok()
-        .filter(|stream| stream.filters().map(|filters| filters.contains(&&b"Crypt"[..])).unwrap_or(false))
-        .and_then(|stream| stream.dict.get(b"DecodeParms").ok())
-        .and_then(|object| object.as_dict().ok())
-        .map(|dict| dict.get(b"Name")
-            .and_then(|object| object.as_name())
-            .ok()
-            .and_then(|name| state.crypt_filters.get(name).cloned())
-            .unwrap_or(Arc::new(IdentityCryptFilter))
-        );
+    // override the default filter for streams.
+    let override_crypt_filter = stream_crypt_filter(state, obj);
 
     // Retrieve the plaintext and the crypt filter to use to decrypt the ciphertext from the given
     // object.
@@ -749,8 +970,8 @@ pub fn encrypt_object(state: &EncryptionState, obj_id: ObjectId, obj: &mut Objec
 
     // If the stream object specifies its own crypt filter, override the default one with the one
     // from this stream object.
-    if let Some(filter) = override_crypt_filter {
-        crypt_filter = filter;
+    if let Some((_, filter)) = &override_crypt_filter {
+        crypt_filter = filter.clone();
     }
 
     // Compute the key from the original file encryption key and the object identifier to use for
@@ -758,7 +979,8 @@ pub fn encrypt_object(state: &EncryptionState, obj_id: ObjectId, obj: &mut Objec
     let key = crypt_filter.compute_key(&state.file_encryption_key, obj_id)?;
 
     // Encrypt the plaintext.
-    let ciphertext = crypt_filter.encrypt(&key, plaintext)?;
+    let random = state.crypto_provider.as_deref().unwrap_or(&DefaultCryptoProvider);
+    let ciphertext = crypt_filter.encrypt_with(&key, plaintext, random)?;
 
     // Store the ciphertext in the object.
     match obj {
@@ -788,19 +1010,8 @@ pub fn decrypt_object(state: &EncryptionState, obj_id: ObjectId, obj: &mut Objec
     }
 
     // A stream filter type, the Crypt filter can be specified for any stream in the document to
-    // override the default filter for streams. The stream's DecodeParms entry shall contain a
-    // Crypt filter decode parameters dictionary whose Name entry specifies the particular crypt
-    // filter that shell be used (if missing, Identity is used).
-    let override_crypt_filter = obj.as_stream().ok()
-        .filter(|stream| stream.filters().map(|filters| filters.contains(&&b"Crypt"[..])).unwrap_or(false))
-        .and_then(|stream| stream.dict.get(b"DecodeParms").ok())
-        .and_then(|object| object.as_dict().ok())
-        .map(|dict| dict.get(b"Name")
-            .and_then(|object| object.as_name())
-            .ok()
-            .and_then(|name| state.crypt_filters.get(name).cloned())
-            .unwrap_or(Arc::new(IdentityCryptFilter))
-        );
+    // override the default filter for streams.
+    let override_crypt_filter = stream_crypt_filter(state, obj);
 
     // Retrieve the ciphertext and the crypt filter to use to decrypt the ciphertext from the given
     // object.
@@ -834,8 +1045,8 @@ pub fn decrypt_object(state: &EncryptionState, obj_id: ObjectId, obj: &mut Objec
 
     // If the stream object specifies its own crypt filter, override the default one with the one
     // from this stream object.
-    if let Some(filter) = override_crypt_filter {
-        crypt_filter = filter;
+    if let Some((_, filter)) = &override_crypt_filter {
+        crypt_filter = filter.clone();
     }
 
     // Compute the key from the original file encryption key and the object identifier to use for
@@ -845,9 +1056,14 @@ pub fn decrypt_object(state: &EncryptionState, obj_id: ObjectId, obj: &mut Objec
     // Decrypt the ciphertext.
     let plaintext = crypt_filter.decrypt(&key, ciphertext)?;
 
-    // Store the plaintext in the object.
+    // Store the plaintext in the object. The Crypt filter of the stream is done with.
     match obj {
-        Object::Stream(stream) => stream.set_content(plaintext),
+        Object::Stream(stream) => {
+            stream.set_content(plaintext);
+            if let Some((index, _)) = override_crypt_filter {
+                remove_crypt_filter(stream, index);
+            }
+        }
         Object::String(content, _) => *content = plaintext,
         _ => (),
     }
@@ -857,13 +1073,15 @@ pub fn decrypt_object(state: &EncryptionState, obj_id: ObjectId, obj: &mut Objec
 
 #[cfg(test)]
 mod tests {
-    use crate::{EncryptionState, EncryptionVersion, Permissions};
+    use crate::{EncryptionState, EncryptionVersion, Object, ObjectId, Permissions};
     use crate::creator::tests::create_document;
     use crate::encryption::{CryptFilter, Aes128CryptFilter, Aes256CryptFilter};
     use rand::Rng as _;
     use super::rc4::Rc4;
+    use super::{CryptoProvider, SeededCryptoProvider};
     use std::collections::BTreeMap;
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicU8, Ordering};
 
     #[test]
     fn rc4_works() {
@@ -1005,4 +1223,213 @@ fn encrypt_v5() {
         assert!(document.encrypt(&state).is_ok());
         assert!(document.decrypt("user").is_ok());
     }
+
+    /// Hands out the bytes 0, 1, 2 and so on, wrapping around.
+    #[derive(Debug, Default)]
+    struct CountingProvider(AtomicU8);
+
+    impl CryptoProvider for CountingProvider {
+        fn random_bytes(&self, bytes: &mut [u8]) {
+            for byte in bytes {
+                *byte = self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn aes256_version() -> EncryptionVersion<'static> {
+        let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+        EncryptionVersion::V5 {
+            encrypt_metadata: true,
+            crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), crypt_filter)]),
+            file_encryption_key: &[7; 32],
+            stream_filter: b"StdCF".to_vec(),
+            string_filter: b"StdCF".to_vec(),
+            owner_password: "owner",
+            user_password: "user",
+            permissions: Permissions::all(),
+        }
+    }
+
+    /// The first 16 bytes of the strings and streams in `object`, in the order they're encrypted.
+    fn leading_blocks(object: &Object, blocks: &mut Vec<Vec<u8>>) {
+        match object {
+            Object::Array(objects) => objects.iter().for_each(|object| leading_blocks(object, blocks)),
+            Object::Dictionary(dict) => dict.iter().for_each(|(_, object)| leading_blocks(object, blocks)),
+            Object::String(content, _) => blocks.push(content[..16].to_vec()),
+            Object::Stream(stream) => blocks.push(stream.content[..16].to_vec()),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn random_bytes_come_from_the_provider() {
+        let mut document = create_document();
+        let state = EncryptionState::with_crypto_provider(aes256_version(), CountingProvider::default())
+            .unwrap();
+        // The validation and key salts of U, then those of O, then 4 bytes of Perms.
+        assert_eq!(state.user_value[32..], (0..16).collect::<Vec<u8>>());
+        assert_eq!(state.owner_value[32..], (16..32).collect::<Vec<u8>>());
+
+        let plain_ids: Vec<ObjectId> = document.objects.keys().copied().collect();
+        document.encrypt(&state).unwrap();
+        let mut ivs = Vec::new();
+        for id in plain_ids {
+            leading_blocks(document.get_object(id).unwrap(), &mut ivs);
+        }
+        assert!(ivs.len() > 2);
+        for (index, iv) in ivs.iter().enumerate() {
+            let expected: Vec<u8> = (0..16).map(|byte| (36 + 16 * index + byte) as u8).collect();
+            assert_eq!(iv, &expected, "IV {index}");
+        }
+        assert!(document.decrypt("user").is_ok());
+    }
+
+    #[test]
+    fn seeded_provider_encrypts_reproducibly() {
+        let document = create_document();
+        let encrypted = || {
+            let mut document = document.clone();
+            let state = EncryptionState::with_crypto_provider(aes256_version(), SeededCryptoProvider::new(1)).unwrap();
+            document.encrypt(&state).unwrap();
+            let mut buffer = Vec::new();
+            document.save_to(&mut buffer).unwrap();
+            buffer
+        };
+        assert_eq!(encrypted(), encrypted());
+    }
+
+    #[test]
+    fn p_values_decode_to_documented_permissions() {
+        // Values written by Acrobat for common security settings, with their meaning in its
+        // Document Properties.
+        let cases = [
+            // No printing, changes or copying.
+            (-3904, Permissions::empty()),
+            // All allowed.
+            (-4, Permissions::all()),
+            // High-resolution printing, commenting, filling in forms and accessibility.
+            (
+                -1052,
+                Permissions::PRINTABLE
+                    | Permissions::ANNOTABLE
+                    | Permissions::FILLABLE
+                    | Permissions::COPYABLE_FOR_ACCESSIBILITY
+                    | Permissions::PRINTABLE_IN_HIGH_QUALITY,
+            ),
+            // Low-resolution printing and accessibility only.
+            (-3388, Permissions::PRINTABLE | Permissions::COPYABLE_FOR_ACCESSIBILITY),
+            // Everything but changes and commenting.
+            (
+                -44,
+                Permissions::all().difference(Permissions::MODIFIABLE | Permissions::ANNOTABLE),
+            ),
+        ];
+        for (p, expected) in cases {
+            let permissions = Permissions::from_p(p);
+            assert_eq!(permissions & Permissions::all(), expected, "/P {p}");
+            assert_eq!(permissions.for_revision(3), permissions, "/P {p}");
+            assert_eq!(permissions.p_value(), p);
+            // The same 32 bits written as an unsigned integer.
+            assert_eq!(Permissions::from_p(p & 0xFFFF_FFFF), permissions, "/P {p} unsigned");
+        }
+    }
+
+    #[test]
+    fn revision_2_derives_the_later_bits() {
+        // Revision 2 sets the reserved bits 9 to 12, which mean nothing there.
+        let permissions = Permissions::from_p(-60);
+        assert!(permissions.contains(Permissions::ASSEMBLABLE | Permissions::FILLABLE));
+        assert_eq!(
+            permissions.for_revision(2) & Permissions::all(),
+            Permissions::PRINTABLE | Permissions::PRINTABLE_IN_HIGH_QUALITY
+        );
+        assert_eq!(
+            Permissions::from_p(-64).for_revision(2) & Permissions::all(),
+            Permissions::empty()
+        );
+        assert_eq!(
+            Permissions::from_p(-44).for_revision(2) & Permissions::all(),
+            Permissions::PRINTABLE
+                | Permissions::COPYABLE
+                | Permissions::COPYABLE_FOR_ACCESSIBILITY
+                | Permissions::PRINTABLE_IN_HIGH_QUALITY
+        );
+    }
+
+    #[test]
+    fn identity_stream_filter_leaves_streams_as_they_are() {
+        let crypt_filters: BTreeMap<Vec<u8>, Arc<dyn CryptFilter>> =
+            BTreeMap::from([(b"StdCF".to_vec(), Arc::new(Aes128CryptFilter) as Arc<dyn CryptFilter>)]);
+        let state = EncryptionState {
+            version: 4,
+            revision: 4,
+            crypt_filters,
+            file_encryption_key: vec![7; 16],
+            stream_filter: b"Identity".to_vec(),
+            string_filter: b"StdCF".to_vec(),
+            ..Default::default()
+        };
+        let mut stream = Object::Stream(crate::Stream::new(crate::Dictionary::new(), b"plain".to_vec()));
+        super::decrypt_object(&state, (1, 0), &mut stream).unwrap();
+        assert_eq!(stream.as_stream().unwrap().content, b"plain");
+        // Without /StmF, streams are left as they are from version 4 on.
+        let state = EncryptionState { stream_filter: Vec::new(), ..state };
+        super::encrypt_object(&state, (1, 0), &mut stream).unwrap();
+        assert_eq!(stream.as_stream().unwrap().content, b"plain");
+    }
+
+    #[test]
+    fn unwritable_encryption_is_refused_on_saving() {
+        let crypt_filters: BTreeMap<Vec<u8>, Arc<dyn CryptFilter>> =
+            BTreeMap::from([(b"StdCF".to_vec(), Arc::new(Aes128CryptFilter) as Arc<dyn CryptFilter>)]);
+        let state = EncryptionState {
+            version: 4,
+            revision: 4,
+            crypt_filters,
+            file_encryption_key: vec![7; 16],
+            stream_filter: b"StdCF".to_vec(),
+            string_filter: b"StdCF".to_vec(),
+            ..Default::default()
+        };
+        assert!(state.check_writable().is_ok());
+        // A crypt filter whose method lopdf doesn't implement isn't read into the state.
+        let unknown = EncryptionState { stream_filter: b"VendorCF".to_vec(), ..state.clone() };
+        assert!(unknown.check_writable().is_err());
+        let version_3 = EncryptionState { version: 3, revision: 3, ..state };
+        assert!(version_3.check_writable().is_err());
+
+        let mut document = create_document();
+        document.encryption_state = Some(unknown);
+        let options = crate::SaveOptions::builder().preserve_encryption(true).build().unwrap();
+        let err = document.save_with_options(&mut Vec::new(), options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the encryption of the document can't be written: crypt filter /VendorCF with an unsupported method"
+        );
+    }
+
+    #[test]
+    fn password_candidates_follow_the_revision() {
+        use super::PasswordAlgorithm;
+
+        let candidates = |revision, password| {
+            PasswordAlgorithm { revision, ..Default::default() }
+                .password_candidates(password)
+                .unwrap()
+        };
+        assert_eq!(candidates(4, "owner"), vec![b"owner".to_vec()]);
+        // PDFDocEncoding and Latin-1 agree on "é", UTF-8 is tried after them.
+        assert_eq!(candidates(3, "cl\u{e9}"), vec![b"cl\xe9".to_vec(), b"cl\xc3\xa9".to_vec()]);
+        // PDFDocEncoding has the euro sign at 0xA0, where Latin-1 has none.
+        assert_eq!(candidates(2, "\u{20ac}"), vec![b"\xa0".to_vec(), "\u{20ac}".as_bytes().to_vec()]);
+        // A password that doesn't fit in a single-byte encoding isn't truncated.
+        assert_eq!(candidates(4, "cl\u{e9}\u{1f511}"), vec!["cl\u{e9}\u{1f511}".as_bytes().to_vec()]);
+
+        assert_eq!(
+            candidates(6, "cle\u{301}"),
+            vec!["cl\u{e9}".as_bytes().to_vec(), "cle\u{301}".as_bytes().to_vec()]
+        );
+        // SASLprep prohibits the emoji, so only its UTF-8 is left.
+        assert_eq!(candidates(6, "\u{1f511}"), vec!["\u{1f511}".as_bytes().to_vec()]);
+    }
 }
@@ -3,9 +3,8 @@
 use crate::{Document, Error, Object};
 use crate::encryption::Permissions;
 use md5::{Digest as _, Md5};
-use rand::Rng as _;
 use sha2::{Sha256, Sha384, Sha512};
-use super::DecryptionError;
+use super::{CryptoProvider, DecryptionError};
 use super::rc4::Rc4;
 
 type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
@@ -205,10 +204,9 @@ fn try_from(value: &Document) -> Result<Self, Self::Error> {
         let permission_value = encrypted.get(b"P")
             .map_err(|_| DecryptionError::MissingPermissions)?
             .as_i64()
-            .map_err(|_| DecryptionError::InvalidType)?
-            as u64;
+            .map_err(|_| DecryptionError::InvalidType)?;
 
-        let permissions = Permissions::from_bits_retain(permission_value);
+        let permissions = Permissions::from_p(permission_value);
 
         let permission_encrypted = encrypted.get(b"Perms")
             .and_then(Object::as_str)
@@ -303,10 +301,12 @@ pub(crate) fn compute_file_encryption_key_r4<P>(
 
         // Pass the first element of the file's file identifier array (the value of the ID entry in the
         // document's trailer dictionary to the MD5 hash function.
-        let file_id_0 = doc
+        let file_id = doc
             .trailer
             .get(b"ID")
-            .map_err(|_| DecryptionError::MissingFileID)?
+            .and_then(|id| doc.resolve_deep(id, 2))
+            .map_err(|_| DecryptionError::MissingFileID)?;
+        let file_id_0 = file_id
             .as_array()
             .map_err(|_| DecryptionError::InvalidType)?
             .first()
@@ -416,6 +416,9 @@ fn compute_file_encryption_key_r6<P>(
                 decryptor.decrypt_block_mut(block.into());
             }
 
+            // Validate the permissions with the file encryption key, as for the user password.
+            self.validate_permissions(&owner_encrypted)?;
+
             return Ok(owner_encrypted);
         }
 
@@ -710,6 +713,28 @@ pub(crate) fn compute_hashed_user_password_r3_r4<U>(
         &self,
         doc: &Document,
         user_password: U,
+        random: &dyn CryptoProvider,
+    ) -> Result<Vec<u8>, DecryptionError>
+    where
+        U: AsRef<[u8]>,
+    {
+        let mut result = self.compute_user_password_hash_r3_r4(doc, user_password)?;
+
+        // Append 16 bytes of arbitrary padding to the output from the final invocation of the RC4
+        // function and store the 32-byte result as the value of the U entry in the encryption
+        // dictionary.
+        result.resize(32, 0);
+        random.random_bytes(&mut result[16..]);
+
+        Ok(result)
+    }
+
+    /// All but the last step of [`PasswordAlgorithm::compute_hashed_user_password_r3_r4`], the
+    /// first 16 bytes of the U entry.
+    fn compute_user_password_hash_r3_r4<U>(
+        &self,
+        doc: &Document,
+        user_password: U,
     ) -> Result<Vec<u8>, DecryptionError>
     where
         U: AsRef<[u8]>,
@@ -724,10 +749,12 @@ pub(crate) fn compute_hashed_user_password_r3_r4<U>(
 
         // Pass the first element of the file's file identifier array (the value of the ID entry in the
         // document's trailer dictionary) to the hash function and finish the hash.
-        let file_id_0 = doc
+        let file_id = doc
             .trailer
             .get(b"ID")
-            .map_err(|_| DecryptionError::MissingFileID)?
+            .and_then(|id| doc.resolve_deep(id, 2))
+            .map_err(|_| DecryptionError::MissingFileID)?;
+        let file_id_0 = file_id
             .as_array()
             .map_err(|_| DecryptionError::InvalidType)?
             .first()
@@ -757,14 +784,6 @@ pub(crate) fn compute_hashed_user_password_r3_r4<U>(
             result = Rc4::new(&key).encrypt(&result);
         }
 
-        // Append 16 bytes of arbitrary padding to the output from the final invocation of the RC4
-        // function and store the 32-byte result as the value of the U entry in the encryption
-        // dictionary.
-        result.resize(32, 0);
-
-        let mut rng = rand::rng();
-        rng.fill(&mut result[16..]);
-
         Ok(result)
     }
 
@@ -786,7 +805,7 @@ fn authenticate_user_password_r4<U>(
         // encryption dictionary's U-entry value.
         let hashed_user_password = match self.revision {
             2 => self.compute_hashed_user_password_r2(doc, &user_password)?,
-            3 | 4 => self.compute_hashed_user_password_r3_r4(doc, &user_password)?,
+            3 | 4 => self.compute_user_password_hash_r3_r4(doc, &user_password)?,
             _ => return Err(DecryptionError::InvalidRevision),
         };
 
@@ -819,6 +838,22 @@ fn authenticate_owner_password_r4<O>(
         doc: &Document,
         owner_password: O,
     ) -> Result<(), DecryptionError>
+    where
+        O: AsRef<[u8]>,
+    {
+        let user_password = self.recover_user_password_r4(owner_password)?;
+
+        // The result of the previous step purports to be the user password. Authenticate this user
+        // password using Algorithm 5. If it is correct, the password supplied is the correct owner
+        // password.
+        self.authenticate_user_password_r4(doc, &user_password)
+    }
+
+    /// The padded user password the encryption dictionary's O-entry value holds encrypted with
+    /// `owner_password` (revision 4 and earlier), right if the owner password is.
+    ///
+    /// This implements the first steps of Algorithm 7 as described in ISO 32000-2:2020 (PDF 2.0).
+    fn recover_user_password_r4<O>(&self, owner_password: O) -> Result<Vec<u8>, DecryptionError>
     where
         O: AsRef<[u8]>,
     {
@@ -896,10 +931,7 @@ fn authenticate_owner_password_r4<O>(
         // encryption key.
         result = Rc4::new(&hash[..n]).decrypt(&result);
 
-        // The result of the previous step purports to be the user password. Authenticate this user
-        // password using Algorithm 5. If it is correct, the password supplied is the correct owner
-        // password.
-        self.authenticate_user_password_r4(doc, &result)
+        Ok(result)
     }
 
     /// Compute the encryption dictionary's U-entry value (revision 6).
@@ -909,6 +941,7 @@ pub(crate) fn compute_hashed_user_password_r6<K, U>(
         &self,
         file_encryption_key: K,
         user_password: U,
+        random: &dyn CryptoProvider,
     ) -> Result<(Vec<u8>, Vec<u8>), DecryptionError>
     where
         K: AsRef<[u8]>,
@@ -924,9 +957,7 @@ pub(crate) fn compute_hashed_user_password_r6<K, U>(
         // the 32-byte hash followed by the user validation salt followed by the user key salt is
         // stored as the U key.
         let mut user_value = [0u8; 48];
-        let mut rng = rand::rng();
-
-        rng.fill(&mut user_value[32..]);
+        random.random_bytes(&mut user_value[32..]);
 
         let user_validation_salt = &user_value[32..][..8];
 
@@ -974,6 +1005,7 @@ pub(crate) fn compute_hashed_owner_password_r6<K, O>(
         &self,
         file_encryption_key: K,
         owner_password: O,
+        random: &dyn CryptoProvider,
     ) -> Result<(Vec<u8>, Vec<u8>), DecryptionError>
     where
         K: AsRef<[u8]>,
@@ -990,9 +1022,7 @@ pub(crate) fn compute_hashed_owner_password_r6<K, O>(
         // 32-byte hash followed by the owner validation salt followed by the owner key salt is
         // stored as the O key.
         let mut owner_value = [0u8; 48];
-        let mut rng = rand::rng();
-
-        rng.fill(&mut owner_value[32..]);
+        random.random_bytes(&mut owner_value[32..]);
 
         let owner_validation_salt = &owner_value[32..][..8];
 
@@ -1029,6 +1059,7 @@ pub(crate) fn compute_hashed_owner_password_r6<K, O>(
     pub(crate) fn compute_permissions<K>(
         &self,
         file_encryption_key: K,
+        random: &dyn CryptoProvider,
     ) -> Result<Vec<u8>, DecryptionError>
     where
         K: AsRef<[u8]>,
@@ -1046,8 +1077,7 @@ pub(crate) fn compute_permissions<K>(
         bytes[9..][..3].copy_from_slice(b"adb");
 
         // Set bytes 12-15 to 4 bytes of random data, which will be ignored.
-        let mut rng = rand::rng();
-        rng.fill(&mut bytes[12..][..4]);
+        random.random_bytes(&mut bytes[12..][..4]);
 
         // Encrypt the 16-byte block using AES-256 in ECB mode with an initialization vector of
         // zero, using the file encryption key as the key.
@@ -1165,19 +1195,19 @@ fn validate_permissions<K>(
 
         // Verify that bytes 9-11 of the result are the characters "a", "d", "b".
         if &bytes[9..][..3] != b"adb" {
-            return Err(DecryptionError::IncorrectPassword);
+            return Err(DecryptionError::PermissionsMismatch);
         }
 
         // Bytes 0-3 of the decrypted Perms entry, treated as a little-endian integer, are the
         // user permissions. They should match the value in the P key.
-        if bytes[..3] != u64::to_le_bytes(self.permissions.bits())[..3] {
-            return Err(DecryptionError::IncorrectPassword);
+        if bytes[..4] != u64::to_le_bytes(self.permissions.bits())[..4] {
+            return Err(DecryptionError::PermissionsMismatch);
         }
 
         // Byte 8 should match the ASCII character "T" or "F" according to the boolean value of the
         // EncryptMetadata key.
         if bytes[8] != if self.encrypt_metadata { b'T' } else { b'F' } {
-            return Err(DecryptionError::IncorrectPassword);
+            return Err(DecryptionError::PermissionsMismatch);
         }
 
         Ok(())
@@ -1195,6 +1225,50 @@ pub fn sanitize_password(
         }
     }
 
+    /// The byte strings the password may have been hashed from, most likely first.
+    ///
+    /// Revision 4 and earlier hash bytes of a host codepage: the password encoded to
+    /// PDFDocEncoding, which readers such as Acrobat use, then to Latin-1, where the two differ,
+    /// and then to UTF-8, which some writers use instead. An encoding the password doesn't fit in
+    /// isn't tried, so that characters are never dropped. Later revisions hash the password
+    /// processed with SASLprep as UTF-8, and the unprocessed UTF-8 is tried after it.
+    pub(crate) fn password_candidates(
+        &self,
+        password: &str,
+    ) -> Result<Vec<Vec<u8>>, DecryptionError> {
+        let mut candidates = Vec::new();
+        match self.revision {
+            2..=4 => {
+                let pdf_doc = self.sanitize_password_r4(password)?;
+                if pdf_doc.len() == password.encode_utf16().count() {
+                    candidates.push(pdf_doc);
+                }
+                let latin_1 = password.chars().map(|ch| u8::try_from(ch).ok()).collect::<Option<Vec<u8>>>();
+                candidates.extend(latin_1);
+            }
+            5..=6 => candidates.extend(self.sanitize_password_r6(password).ok()),
+            _ => return Err(DecryptionError::UnsupportedRevision),
+        }
+        candidates.push(password.as_bytes().to_vec());
+
+        let mut unique = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            if !unique.contains(&candidate) {
+                unique.push(candidate);
+            }
+        }
+        Ok(unique)
+    }
+
+    /// The bytes a new password is hashed from: the first of the
+    /// [`password_candidates`](Self::password_candidates), which readers try first.
+    pub(crate) fn encode_password(
+        &self,
+        password: &str,
+    ) -> Result<Vec<u8>, DecryptionError> {
+        Ok(self.password_candidates(password)?.swap_remove(0))
+    }
+
     /// Compute the file encryption key used to encrypt/decrypt the document.
     pub fn compute_file_encryption_key<P>(
         &self,
@@ -1205,6 +1279,16 @@ pub fn compute_file_encryption_key<P>(
         P: AsRef<[u8]>,
     {
         match self.revision {
+            // The file encryption key is derived from the user password, which the owner password
+            // recovers.
+            2..=4 if self.authenticate_user_password_r4(doc, &password).is_err() => {
+                match self.recover_user_password_r4(&password) {
+                    Ok(user_password) if self.authenticate_user_password_r4(doc, &user_password).is_ok() => {
+                        self.compute_file_encryption_key_r4(doc, user_password)
+                    }
+                    _ => self.compute_file_encryption_key_r4(doc, password),
+                }
+            }
             2..=4 => self.compute_file_encryption_key_r4(doc, password),
             5..=6 => self.compute_file_encryption_key_r6(password),
             _ => Err(DecryptionError::UnsupportedRevision),
@@ -1246,7 +1330,7 @@ pub fn authenticate_owner_password<O>(
 
 #[cfg(test)]
 mod tests {
-    use crate::Permissions;
+    use crate::{DefaultCryptoProvider, Document, Object, Permissions, StringFormat};
     use crate::creator::tests::create_document;
     use crate::encryption::PasswordAlgorithm;
     use rand::Rng as _;
@@ -1320,6 +1404,7 @@ fn authenticate_password_r3() {
         algorithm.user_value = algorithm.compute_hashed_user_password_r3_r4(
             &document,
             &user_password,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         // Assert that the correct passwords authenticate.
@@ -1327,8 +1412,14 @@ fn authenticate_password_r3() {
         assert!(algorithm.authenticate_user_password_r4(&document, &user_password).is_ok());
 
         // Assert that the swapped passwords do not authenticate.
-        assert!(algorithm.authenticate_owner_password_r4(&document, user_password).is_err());
-        assert!(algorithm.authenticate_user_password_r4(&document, owner_password).is_err());
+        assert!(algorithm.authenticate_owner_password_r4(&document, &user_password).is_err());
+        assert!(algorithm.authenticate_user_password_r4(&document, &owner_password).is_err());
+
+        // Assert that the owner password leads to the file encryption key of the user password.
+        assert_eq!(
+            algorithm.compute_file_encryption_key(&document, &owner_password).unwrap(),
+            algorithm.compute_file_encryption_key(&document, &user_password).unwrap(),
+        );
     }
 
     #[test]
@@ -1360,6 +1451,7 @@ fn authenticate_password_r4() {
         algorithm.user_value = algorithm.compute_hashed_user_password_r3_r4(
             &document,
             &user_password,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         // Assert that the correct passwords authenticate.
@@ -1371,6 +1463,42 @@ fn authenticate_password_r4() {
         assert!(algorithm.authenticate_user_password_r4(&document, owner_password).is_err());
     }
 
+    #[test]
+    fn user_value_r4_matches_acrobat() {
+        // The /ID, /O and /P of assets/encrypted.pdf, written by Acrobat with AESV2 and an empty
+        // user password.
+        let mut document = create_document();
+        let file_id = Object::String(hex("68de08cbebeed742812f49e95ef30e10"), StringFormat::Hexadecimal);
+        document.trailer.set("ID", vec![file_id, Object::String(vec![0; 16], StringFormat::Hexadecimal)]);
+
+        let algorithm = PasswordAlgorithm {
+            encrypt_metadata: true,
+            length: Some(128),
+            version: 4,
+            revision: 4,
+            permissions: Permissions::from_bits_retain(-1052i64 as u64),
+            owner_value: hex("734614762e793527db970a3522b3e1d4adbd9b3cb4a5897515b259f168d9e9f4"),
+            ..Default::default()
+        };
+
+        // Only the first 16 bytes of /U are defined, Acrobat pads them with zeros.
+        let user_value = algorithm.compute_hashed_user_password_r3_r4(&document, b"", &DefaultCryptoProvider).unwrap();
+        assert_eq!(user_value[..16], hex("2e3fb1da2115447ccc6eac564dab0bff"));
+
+        // The values above are those of the file.
+        let acrobat = Document::load_mem(include_bytes!("../../assets/encrypted.pdf")).unwrap();
+        let state = acrobat.encryption_state.as_ref().unwrap();
+        assert_eq!(state.owner_value, algorithm.owner_value);
+        assert_eq!(state.user_value[..16], user_value[..16]);
+        assert_eq!(state.permissions, algorithm.permissions);
+        let first_id = |document: &Document| document.trailer.get(b"ID").unwrap().as_array().unwrap()[0].clone();
+        assert_eq!(first_id(&acrobat), first_id(&document));
+    }
+
+    fn hex(digits: &str) -> Vec<u8> {
+        (0..digits.len()).step_by(2).map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap()).collect()
+    }
+
     #[test]
     fn authenticate_password_r5() {
         let mut algorithm = PasswordAlgorithm {
@@ -1397,6 +1525,7 @@ fn authenticate_password_r5() {
         let (user_value, user_encrypted) = algorithm.compute_hashed_user_password_r6(
             file_encryption_key,
             &user_password,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         algorithm.user_value = user_value;
@@ -1405,6 +1534,7 @@ fn authenticate_password_r5() {
         let (owner_value, owner_encrypted) = algorithm.compute_hashed_owner_password_r6(
             file_encryption_key,
             &owner_password,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         algorithm.owner_value = owner_value;
@@ -1412,6 +1542,7 @@ fn authenticate_password_r5() {
 
         algorithm.permission_encrypted = algorithm.compute_permissions(
             file_encryption_key,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         // Assert that the correct passwords authenticate.
@@ -1423,7 +1554,7 @@ fn authenticate_password_r5() {
         assert!(algorithm.authenticate_user_password_r6(&owner_password).is_err());
 
         // Assert that the permissions validate correctly.
-        assert!(algorithm.validate_permissions(&file_encryption_key).is_ok());
+        assert!(algorithm.validate_permissions(file_encryption_key).is_ok());
 
         // Assert that the file encryption key is equal for the owner password.
         let key = algorithm.compute_file_encryption_key_r6(&owner_password).unwrap();
@@ -1460,6 +1591,7 @@ fn authenticate_password_r6() {
         let (user_value, user_encrypted) = algorithm.compute_hashed_user_password_r6(
             file_encryption_key,
             &user_password,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         algorithm.user_value = user_value;
@@ -1468,6 +1600,7 @@ fn authenticate_password_r6() {
         let (owner_value, owner_encrypted) = algorithm.compute_hashed_owner_password_r6(
             file_encryption_key,
             &owner_password,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         algorithm.owner_value = owner_value;
@@ -1475,6 +1608,7 @@ fn authenticate_password_r6() {
 
         algorithm.permission_encrypted = algorithm.compute_permissions(
             file_encryption_key,
+            &DefaultCryptoProvider,
         ).unwrap();
 
         // Assert that the correct passwords authenticate.
@@ -1486,7 +1620,7 @@ fn authenticate_password_r6() {
         assert!(algorithm.authenticate_user_password_r6(&owner_password).is_err());
 
         // Assert that the permissions validate correctly.
-        assert!(algorithm.validate_permissions(&file_encryption_key).is_ok());
+        assert!(algorithm.validate_permissions(file_encryption_key).is_ok());
 
         // Assert that the file encryption key is equal for the owner password.
         let key = algorithm.compute_file_encryption_key_r6(&owner_password).unwrap();
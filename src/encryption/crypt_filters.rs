@@ -1,8 +1,7 @@
 use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
 use crate::ObjectId;
 use md5::{Digest as _, Md5};
-use rand::Rng as _;
-use super::DecryptionError;
+use super::{CryptoProvider, DecryptionError, DefaultCryptoProvider};
 use super::pkcs5::Pkcs5;
 use super::rc4::Rc4;
 
@@ -16,6 +15,13 @@ pub trait CryptFilter: std::fmt::Debug + Send + Sync {
     fn method(&self) -> &[u8];
     fn compute_key(&self, key: &[u8], obj_id: ObjectId) -> Result<Vec<u8>, DecryptionError>;
     fn encrypt(&self, key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, DecryptionError>;
+    /// Like [`CryptFilter::encrypt`], drawing the random bytes it needs from `random`.
+    fn encrypt_with(
+        &self, key: &[u8], plaintext: &[u8], random: &dyn CryptoProvider,
+    ) -> Result<Vec<u8>, DecryptionError> {
+        let _ = random;
+        self.encrypt(key, plaintext)
+    }
     fn decrypt(&self, key: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, DecryptionError>;
 }
 
@@ -116,6 +122,12 @@ fn compute_key(&self, key: &[u8], obj_id: ObjectId) -> Result<Vec<u8>, Decryptio
     }
 
     fn encrypt(&self, key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, DecryptionError> {
+        self.encrypt_with(key, plaintext, &DefaultCryptoProvider)
+    }
+
+    fn encrypt_with(
+        &self, key: &[u8], plaintext: &[u8], random: &dyn CryptoProvider,
+    ) -> Result<Vec<u8>, DecryptionError> {
         // Ensure that the key is 128 bits (i.e., 16 bytes).
         if key.len() != 16 {
             return Err(DecryptionError::InvalidKeyLength);
@@ -129,9 +141,8 @@ fn encrypt(&self, key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, DecryptionErr
         let mut ciphertext = Vec::with_capacity(16 + ciphertext_len);
 
         // Generate random numbers to populate the initialization vector.
-        let mut rng = rand::rng();
         let mut iv = [0u8; 16];
-        rng.fill(&mut iv);
+        random.random_bytes(&mut iv);
 
         // Combine the IV and the plaintext.
         ciphertext.extend_from_slice(&iv);
@@ -200,6 +211,12 @@ fn compute_key(&self, key: &[u8], _obj_id: ObjectId) -> Result<Vec<u8>, Decrypti
     }
 
     fn encrypt(&self, key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, DecryptionError> {
+        self.encrypt_with(key, plaintext, &DefaultCryptoProvider)
+    }
+
+    fn encrypt_with(
+        &self, key: &[u8], plaintext: &[u8], random: &dyn CryptoProvider,
+    ) -> Result<Vec<u8>, DecryptionError> {
         // Ensure that the key is 256 bits (i.e., 32 bytes).
         if key.len() != 32 {
             return Err(DecryptionError::InvalidKeyLength);
@@ -213,9 +230,8 @@ fn encrypt(&self, key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, DecryptionErr
         let mut ciphertext = Vec::with_capacity(16 + ciphertext_len);
 
         // Generate random numbers to populate the initialization vector.
-        let mut rng = rand::rng();
         let mut iv = [0u8; 16];
-        rng.fill(&mut iv);
+        random.random_bytes(&mut iv);
 
         // Combine the IV and the plaintext.
         ciphertext.extend_from_slice(&iv);
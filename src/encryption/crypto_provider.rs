@@ -0,0 +1,40 @@
+use rand::rngs::StdRng;
+use rand::{Rng as _, SeedableRng as _};
+use std::sync::Mutex;
+
+/// Source of the random bytes encryption needs: the initialization vectors of AES, the salts of
+/// security handlers of revision 5 and 6, and the padding of the U entry of revisions 3 and 4.
+///
+/// Give one to [`EncryptionState::with_crypto_provider`](super::EncryptionState::with_crypto_provider)
+/// on platforms without OS randomness, or for reproducible output.
+pub trait CryptoProvider: std::fmt::Debug + Send + Sync {
+    /// Fill `bytes` with random bytes.
+    fn random_bytes(&self, bytes: &mut [u8]);
+}
+
+/// The random bytes of the thread-local generator of the `rand` crate, seeded from the OS.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultCryptoProvider;
+
+impl CryptoProvider for DefaultCryptoProvider {
+    fn random_bytes(&self, bytes: &mut [u8]) {
+        rand::rng().fill(bytes);
+    }
+}
+
+/// Random bytes generated from a seed, the same for every run. Not for protecting documents, as
+/// anyone knowing the seed can predict them.
+#[derive(Debug)]
+pub struct SeededCryptoProvider(Mutex<StdRng>);
+
+impl SeededCryptoProvider {
+    pub fn new(seed: u64) -> Self {
+        SeededCryptoProvider(Mutex::new(StdRng::seed_from_u64(seed)))
+    }
+}
+
+impl CryptoProvider for SeededCryptoProvider {
+    fn random_bytes(&self, bytes: &mut [u8]) {
+        self.0.lock().expect("seeded generator mutex poisoned").fill(bytes);
+    }
+}
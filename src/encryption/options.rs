@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::crypt_filters::{Aes128CryptFilter, Aes256CryptFilter, CryptFilter, Rc4CryptFilter};
+use super::{CryptoProvider, DefaultCryptoProvider, EncryptionState, EncryptionVersion, Permissions};
+use crate::{Document, Result};
+
+/// The name of the crypt filter of the states [`EncryptionOptions`] compute.
+const STANDARD_CRYPT_FILTER: &[u8] = b"StdCF";
+
+/// The cipher of the standard security handler to encrypt a document with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EncryptionAlgorithm {
+    /// RC4 with a 128-bit key (V2, revision 3), or with a V2 crypt filter (V4, revision 4) when
+    /// the metadata is left unencrypted.
+    Rc4_128,
+    /// AES-128 in CBC mode with a random IV per string or stream (V4, revision 4, AESV2 crypt
+    /// filter).
+    #[default]
+    Aes128,
+    /// AES-256 in CBC mode with a random file encryption key (V5, revision 6, AESV3 crypt filter),
+    /// as defined by PDF 2.0. Passwords are prepared with SASLprep.
+    Aes256,
+}
+
+/// Passwords, permissions and cipher to encrypt a document with the standard security handler.
+///
+/// [`EncryptionOptions::state`] computes the [`EncryptionState`] for a document, to pass to
+/// [`Document::encrypt`] or [`SaveOptionsBuilder::encryption`](crate::SaveOptionsBuilder::encryption).
+///
+/// ```
+/// use lopdf::{Document, EncryptionAlgorithm, EncryptionOptions, Permissions, SaveOptions};
+///
+/// let mut doc = Document::new();
+/// doc.ensure_file_id();
+/// let state = EncryptionOptions::new("owner", "user")
+///     .algorithm(EncryptionAlgorithm::Aes128)
+///     .permissions(Permissions::PRINTABLE)
+///     .state(&doc)
+///     .unwrap();
+/// let options = SaveOptions::builder().encryption(state).build().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionOptions {
+    /// Password granting all permissions
+    pub owner_password: String,
+    /// Password to open the document, empty to open it without one
+    pub user_password: String,
+    /// What a user opening the document with the user password may do
+    pub permissions: Permissions,
+    /// The cipher strings and streams are encrypted with
+    pub algorithm: EncryptionAlgorithm,
+    /// Encrypt the document-level metadata stream. Left in plain text otherwise, so indexers can
+    /// read it.
+    pub encrypt_metadata: bool,
+}
+
+impl EncryptionOptions {
+    /// Options with all permissions, [`EncryptionAlgorithm::Aes128`] and encrypted metadata.
+    pub fn new(owner_password: impl Into<String>, user_password: impl Into<String>) -> Self {
+        EncryptionOptions {
+            owner_password: owner_password.into(),
+            user_password: user_password.into(),
+            permissions: Permissions::all(),
+            algorithm: EncryptionAlgorithm::default(),
+            encrypt_metadata: true,
+        }
+    }
+
+    /// Set the permissions of the user password
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Set the cipher
+    pub fn algorithm(mut self, algorithm: EncryptionAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set whether the metadata stream is encrypted
+    pub fn encrypt_metadata(mut self, encrypt_metadata: bool) -> Self {
+        self.encrypt_metadata = encrypt_metadata;
+        self
+    }
+
+    /// The state encrypting `document` with these options. Except with
+    /// [`EncryptionAlgorithm::Aes256`], the keys are derived from the file identifier of the
+    /// document, which has to be set first, see [`Document::ensure_file_id`].
+    pub fn state(&self, document: &Document) -> Result<EncryptionState> {
+        let crypt_filter: Arc<dyn CryptFilter> = match self.algorithm {
+            EncryptionAlgorithm::Rc4_128 if self.encrypt_metadata => {
+                return EncryptionState::try_from(EncryptionVersion::V2 {
+                    document,
+                    owner_password: &self.owner_password,
+                    user_password: &self.user_password,
+                    key_length: 128,
+                    permissions: self.permissions,
+                });
+            }
+            // Only crypt filters can leave the metadata unencrypted.
+            EncryptionAlgorithm::Rc4_128 => Arc::new(Rc4CryptFilter),
+            EncryptionAlgorithm::Aes128 => Arc::new(Aes128CryptFilter),
+            EncryptionAlgorithm::Aes256 => {
+                let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+                let mut file_encryption_key = [0; 32];
+                DefaultCryptoProvider.random_bytes(&mut file_encryption_key);
+                return EncryptionState::try_from(EncryptionVersion::V5 {
+                    encrypt_metadata: self.encrypt_metadata,
+                    crypt_filters: BTreeMap::from([(STANDARD_CRYPT_FILTER.to_vec(), crypt_filter)]),
+                    file_encryption_key: &file_encryption_key,
+                    stream_filter: STANDARD_CRYPT_FILTER.to_vec(),
+                    string_filter: STANDARD_CRYPT_FILTER.to_vec(),
+                    owner_password: &self.owner_password,
+                    user_password: &self.user_password,
+                    permissions: self.permissions,
+                });
+            }
+        };
+        EncryptionState::try_from(EncryptionVersion::V4 {
+            document,
+            encrypt_metadata: self.encrypt_metadata,
+            crypt_filters: BTreeMap::from([(STANDARD_CRYPT_FILTER.to_vec(), crypt_filter)]),
+            stream_filter: STANDARD_CRYPT_FILTER.to_vec(),
+            string_filter: STANDARD_CRYPT_FILTER.to_vec(),
+            owner_password: &self.owner_password,
+            user_password: &self.user_password,
+            permissions: self.permissions,
+        })
+    }
+}
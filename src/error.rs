@@ -1,7 +1,7 @@
 use thiserror::Error;
 
 use crate::encodings::cmap::UnicodeCMapError;
-use crate::{encryption, ObjectId};
+use crate::{encryption, ObjectId, OutputFallback};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -22,6 +22,9 @@ pub enum Error {
     /// PDF document is already encrypted.
     #[error("PDF document is already encrypted")]
     AlreadyEncrypted,
+    /// The [progress](crate::Progress) sink cancelled the operation.
+    #[error("operation cancelled")]
+    Cancelled,
     /// The encountered character encoding is invalid.
     #[error("invalid character encoding")]
     CharacterEncoding,
@@ -49,6 +52,15 @@ pub enum Error {
     /// Invalid object stream.
     #[error("invalid object stream: {0}")]
     InvalidObjectStream(String),
+    /// Save options that can't be applied to the document.
+    #[error("invalid save options: {0}")]
+    InvalidSaveOptions(String),
+    /// Converting between a PDF object and a typed value failed.
+    #[error("object mapping failed: {0}")]
+    ObjectMapping(#[from] crate::MappingError),
+    /// Invalid shaped text run.
+    #[error("invalid shaped run: {0}")]
+    InvalidShapedRun(String),
     /// Byte offset in stream or file is invalid.
     #[error("invalid byte offset")]
     InvalidOffset(usize),
@@ -65,6 +77,9 @@ pub enum Error {
     /// Invalid password provided for encrypted PDF.
     #[error("invalid password for encrypted PDF")]
     InvalidPassword,
+    /// The document has no catalog, as the filters it was loaded with removed it.
+    #[error("the document catalog is missing")]
+    MissingCatalog,
     /// Missing xref entry.
     #[error("missing xref entry")]
     MissingXrefEntry,
@@ -77,6 +92,21 @@ pub enum Error {
     /// Page number was not found in document.
     #[error("page number not found")]
     PageNumberNotFound(u32),
+    /// Text contains characters the font cannot represent.
+    #[error("font cannot represent characters {0:?}")]
+    UnsupportedCharacters(Vec<char>),
+    /// Text contains characters a subset font has no glyphs for.
+    #[error("subset font {font} lacks glyphs for {chars:?}; embed a font that has them")]
+    MissingGlyphs { font: String, chars: Vec<char> },
+    /// A font couldn't be converted by [`Document::upgrade_font_to_type0`](crate::Document::upgrade_font_to_type0).
+    #[error("cannot upgrade font to Type0: {0}")]
+    FontUpgrade(String),
+    /// Form field was not found in document.
+    #[error("form field \"{0}\" not found")]
+    FieldNotFound(String),
+    /// The value of a form field doesn't fit its widget, under [`OverflowPolicy::Error`](crate::OverflowPolicy).
+    #[error("value of form field \"{field}\" overflows its widget after {fitted} characters")]
+    TextOverflow { field: String, fitted: usize },
     /// Numeric type cast failed.
     #[error("numberic type cast failed: {0}")]
     NumericCast(String),
@@ -100,6 +130,17 @@ pub enum Error {
     #[cfg(feature = "embed_image")]
     #[error("image error: {0}")]
     Image(#[from] image::ImageError),
+    /// An operation of the `ops` module failed on a file.
+    #[cfg(feature = "ops")]
+    #[error("{file}: {source}")]
+    Ops { file: String, source: Box<Error> },
+    /// Saving in strict mode would have needed lossy fallbacks.
+    #[error("strict output refused {} lossy fallbacks, the first: {}", .0.len(), .0.first().map(ToString::to_string).unwrap_or_default())]
+    StrictOutput(Vec<OutputFallback>),
+    /// Strict reading found a problem that is otherwise tolerated, see
+    /// [`ReaderOptions::strict`](crate::ReaderOptions::strict).
+    #[error("strict reading rejected the file: {0}")]
+    Strict(crate::LoadWarning),
     /// Syntax error while processing the content stream.
     #[error("syntax error in content stream: {0}")]
     Syntax(String),
@@ -108,9 +149,16 @@ pub enum Error {
     ToUnicodeCMap(#[from] UnicodeCMapError),
     #[error("converting integer: {0}")]
     TryFromInt(#[from] std::num::TryFromIntError),
+    /// A stream uses a filter lopdf can't decode.
+    #[error("unsupported stream filter /{0}")]
+    UnsupportedFilter(String),
     /// Encountered an unsupported security handler.
     #[error("unsupported security handler")]
     UnsupportedSecurityHandler(Vec<u8>),
+    /// The encryption of a loaded document can't be written back, see
+    /// [`SaveOptionsBuilder::preserve_encryption`](crate::SaveOptionsBuilder::preserve_encryption).
+    #[error("the encryption of the document can't be written: {0}")]
+    UnwritableEncryption(String),
 }
 
 #[derive(Error, Debug)]
@@ -131,6 +179,12 @@ pub enum ParseError {
     InvalidTrailer,
     #[error("invalid cross reference table")]
     InvalidXref,
+    #[error("invalid object")]
+    InvalidObject,
+    #[error("invalid indirect object header")]
+    InvalidObjectHeader,
+    #[error("unexpected data at byte offset {0}")]
+    TrailingData(usize),
 }
 
 #[derive(Debug, Error)]
@@ -144,4 +198,7 @@ pub enum XrefError {
     /// The trailer's "XRefStm" field was invalid.
     #[error("invalid start value of XRefStm")]
     StreamStart,
+    /// The chain of cross-reference sections is longer than allowed by the reader options.
+    #[error("more than {0} cross-reference sections")]
+    TooManySections(usize),
 }
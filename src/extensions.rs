@@ -0,0 +1,295 @@
+use std::collections::BTreeMap;
+
+use log::warn;
+
+use crate::{Dictionary, Document, Object, Result};
+
+/// A developer extension declared in the catalog's /Extensions dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeveloperExtension {
+    /// The PDF version the extension builds on, e.g. `1.7`
+    pub base_version: String,
+    /// Extension level within the base version
+    pub extension_level: i64,
+    /// Where the extension is documented (PDF 2.0)
+    pub url: Option<String>,
+}
+
+impl DeveloperExtension {
+    fn from_dict(dict: &Dictionary) -> Option<Self> {
+        let base_version = dict.get(b"BaseVersion").and_then(Object::as_name).ok()?;
+        Some(DeveloperExtension {
+            base_version: String::from_utf8_lossy(base_version).into_owned(),
+            extension_level: dict.get(b"ExtensionLevel").and_then(Object::as_i64).ok()?,
+            url: dict
+                .get(b"URL")
+                .and_then(Object::as_str)
+                .ok()
+                .map(|url| String::from_utf8_lossy(url).into_owned()),
+        })
+    }
+}
+
+/// Minimum ADBE extension level needed by features introduced as Adobe extensions to PDF 1.7.
+const ADBE_EXTENSION_LEVEL_3: i64 = 3;
+
+impl Document {
+    /// Get the developer extensions declared in the catalog, keyed by prefix, e.g. `ADBE`.
+    ///
+    /// PDF 2.0 allows an array of extension dictionaries per prefix; the one with the highest
+    /// extension level is returned.
+    pub fn get_extensions(&self) -> BTreeMap<String, DeveloperExtension> {
+        let mut extensions = BTreeMap::new();
+        let Ok(dict) = self
+            .catalog()
+            .and_then(|catalog| catalog.get_deref(b"Extensions", self))
+            .and_then(Object::as_dict)
+        else {
+            return extensions;
+        };
+
+        for (prefix, value) in dict {
+            if prefix == b"Type" {
+                continue;
+            }
+            let candidates = match self.dereference(value) {
+                Ok((_, Object::Array(array))) => array.iter().collect(),
+                Ok((_, object)) => vec![object],
+                Err(_) => continue,
+            };
+            let extension = candidates
+                .into_iter()
+                .filter_map(|candidate| self.dereference(candidate).ok())
+                .filter_map(|(_, candidate)| candidate.as_dict().ok())
+                .filter_map(DeveloperExtension::from_dict)
+                .max_by_key(|extension| extension.extension_level);
+            if let Some(extension) = extension {
+                extensions.insert(String::from_utf8_lossy(prefix).into_owned(), extension);
+            }
+        }
+
+        extensions
+    }
+
+    /// Declare a developer extension in the catalog's /Extensions dictionary.
+    ///
+    /// An existing declaration for the same prefix with a higher extension level is kept. When the
+    /// prefix has a PDF 2.0 array of declarations, the extension is added to it unless it's listed
+    /// already.
+    pub fn declare_extension(&mut self, prefix: &str, base_version: &str, level: i64) -> Result<()> {
+        let extension = DeveloperExtension {
+            base_version: base_version.to_string(),
+            extension_level: level,
+            url: None,
+        };
+        let declared = self
+            .catalog()
+            .and_then(|catalog| catalog.get_deref(b"Extensions", self))
+            .and_then(Object::as_dict)
+            .and_then(|extensions| extensions.get(prefix.as_bytes()))
+            .ok();
+        let mut array_id = None;
+        match declared.map(|declared| self.dereference(declared)) {
+            Some(Ok((id, Object::Array(array)))) => {
+                let listed = array
+                    .iter()
+                    .filter_map(|candidate| self.dereference(candidate).ok())
+                    .filter_map(|(_, candidate)| candidate.as_dict().ok())
+                    .filter_map(DeveloperExtension::from_dict)
+                    .any(|listed| listed.base_version == extension.base_version && listed.extension_level == level);
+                if listed {
+                    return Ok(());
+                }
+                array_id = id;
+            }
+            _ => {
+                if self
+                    .get_extensions()
+                    .get(prefix)
+                    .is_some_and(|existing| existing.extension_level > level)
+                {
+                    return Ok(());
+                }
+            }
+        }
+
+        let extension = Object::from(Dictionary::from(extension));
+        if let Some(id) = array_id {
+            self.get_object_mut(id)?.as_array_mut()?.push(extension);
+            return Ok(());
+        }
+        let extensions_id = self.catalog()?.get(b"Extensions").and_then(Object::as_reference).ok();
+        let extensions = match extensions_id {
+            Some(id) => self.get_dictionary_mut(id)?,
+            None => {
+                let catalog = self.catalog_mut()?;
+                if !catalog.has(b"Extensions") {
+                    catalog.set("Extensions", dictionary! { "Type" => "Extensions" });
+                }
+                catalog.get_mut(b"Extensions")?.as_dict_mut()?
+            }
+        };
+        match extensions.get_mut(prefix.as_bytes()) {
+            Ok(Object::Array(declared)) => declared.push(extension),
+            _ => extensions.set(prefix, extension),
+        }
+        Ok(())
+    }
+
+    /// List features present in the document that require a developer extension which is not
+    /// declared in the catalog.
+    pub fn undeclared_extension_features(&self) -> Vec<&'static str> {
+        let adbe_level = self
+            .get_extensions()
+            .get("ADBE")
+            .map_or(0, |extension| extension.extension_level);
+        if !crate::version::is_newer("2.0", &self.effective_version()) || adbe_level >= ADBE_EXTENSION_LEVEL_3 {
+            return Vec::new();
+        }
+
+        let mut features = Vec::new();
+        let aes_256 = self
+            .get_encrypted()
+            .and_then(|dict| dict.get(b"V"))
+            .and_then(Object::as_i64)
+            .is_ok_and(|version| version == 5);
+        if aes_256 {
+            features.push("AES-256 encryption");
+        }
+        let rich_media = self
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .any(|dict| dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"RichMedia"));
+        if rich_media {
+            features.push("RichMedia annotations");
+        }
+        features
+    }
+
+    /// Log a warning for every feature that needs an undeclared developer extension.
+    pub(crate) fn warn_undeclared_extensions(&self) {
+        for feature in self.undeclared_extension_features() {
+            warn!(
+                "{feature} require PDF 2.0 or an ADBE extension level of at least {ADBE_EXTENSION_LEVEL_3}, \
+                 declare it with Document::declare_extension"
+            );
+        }
+    }
+}
+
+impl From<DeveloperExtension> for Dictionary {
+    fn from(extension: DeveloperExtension) -> Self {
+        let mut dict = dictionary! {
+            "Type" => "DeveloperExtensions",
+            "BaseVersion" => Object::Name(extension.base_version.into_bytes()),
+            "ExtensionLevel" => extension.extension_level,
+        };
+        if let Some(url) = extension.url {
+            dict.set("URL", Object::string_literal(url));
+        }
+        dict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+
+    fn round_trip(doc: &mut Document) -> Document {
+        let mut buffer = Vec::new();
+        doc.save_to(&mut buffer).unwrap();
+        Document::load_mem(&buffer).unwrap()
+    }
+
+    #[test]
+    fn extensions_round_trip() {
+        let mut doc = create_document();
+        doc.catalog_mut().unwrap().set(
+            "Extensions",
+            dictionary! {
+                "ADBE" => dictionary! {
+                    "BaseVersion" => "1.7",
+                    "ExtensionLevel" => 8,
+                },
+            },
+        );
+
+        let mut loaded = round_trip(&mut doc);
+        let adbe = DeveloperExtension {
+            base_version: "1.7".to_string(),
+            extension_level: 8,
+            url: None,
+        };
+        assert_eq!(loaded.get_extensions().get("ADBE"), Some(&adbe));
+
+        loaded.declare_extension("ADBE", "1.7", 3).unwrap();
+        loaded.declare_extension("XMPL", "2.0", 1).unwrap();
+        let extensions = round_trip(&mut loaded).get_extensions();
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions.get("ADBE"), Some(&adbe));
+        assert_eq!(extensions["XMPL"].base_version, "2.0");
+        assert_eq!(extensions["XMPL"].extension_level, 1);
+    }
+
+    #[test]
+    fn extensions_array_uses_highest_level() {
+        let mut doc = create_document();
+        let iso = DeveloperExtension {
+            base_version: "2.0".to_string(),
+            extension_level: 32002,
+            url: Some("https://www.iso.org/standard/45874.html".to_string()),
+        };
+        doc.catalog_mut().unwrap().set(
+            "Extensions",
+            dictionary! {
+                "ISO_" => vec![
+                    Dictionary::from(DeveloperExtension { extension_level: 32001, ..iso.clone() }).into(),
+                    Dictionary::from(iso.clone()).into(),
+                ],
+            },
+        );
+        assert_eq!(doc.get_extensions().get("ISO_"), Some(&iso));
+    }
+
+    #[test]
+    fn declaring_extends_an_array_of_declarations() {
+        let mut doc = create_document();
+        let extension = |level| {
+            Object::from(Dictionary::from(DeveloperExtension {
+                base_version: "2.0".to_string(),
+                extension_level: level,
+                url: None,
+            }))
+        };
+        doc.catalog_mut().unwrap().set(
+            "Extensions",
+            dictionary! { "ISO_" => vec![extension(32001), extension(32002)] },
+        );
+
+        doc.declare_extension("ISO_", "2.0", 32003).unwrap();
+        doc.declare_extension("ISO_", "2.0", 32001).unwrap();
+        let extensions = doc.catalog().unwrap().get(b"Extensions").and_then(Object::as_dict).unwrap();
+        let declared = extensions.get(b"ISO_").and_then(Object::as_array).unwrap();
+        assert_eq!(declared, &[extension(32001), extension(32002), extension(32003)]);
+
+        // An array kept in an object of its own is extended in place.
+        let array_id = doc.add_object(vec![extension(32001)]);
+        doc.catalog_mut().unwrap().set("Extensions", dictionary! { "ISO_" => array_id });
+        doc.declare_extension("ISO_", "2.0", 32002).unwrap();
+        let declared = doc.get_object(array_id).and_then(Object::as_array).unwrap();
+        assert_eq!(declared, &[extension(32001), extension(32002)]);
+    }
+
+    #[test]
+    fn rich_media_requires_declaration() {
+        let mut doc = create_document();
+        doc.version = "1.7".to_string();
+        doc.add_object(dictionary! { "Type" => "Annot", "Subtype" => "RichMedia" });
+        assert_eq!(doc.undeclared_extension_features(), ["RichMedia annotations"]);
+
+        doc.declare_extension("ADBE", "1.7", 3).unwrap();
+        assert!(doc.undeclared_extension_features().is_empty());
+    }
+}
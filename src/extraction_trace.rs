@@ -0,0 +1,100 @@
+use crate::encodings::Encoding;
+use crate::{Object, ObjectId};
+
+/// Where the Unicode text of a font's codes comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingSource {
+    /// The /ToUnicode CMap of the font
+    ToUnicode,
+    /// The /Differences array of the font's encoding dictionary, its glyph names mapped with the
+    /// Adobe Glyph List
+    Differences,
+    /// The /Differences array, with glyph names outside the Adobe Glyph List looked up in the
+    /// cmap or glyph names of the embedded font program
+    FontProgram,
+    /// The encoding or predefined CMap named by /Encoding, e.g. `WinAnsiEncoding`
+    Named(Vec<u8>),
+    /// The standard encoding, assumed for a font without an encoding or ToUnicode CMap lopdf
+    /// can read
+    Guess,
+}
+
+/// A font text was shown with, see [`ExtractionTrace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontTrace {
+    /// The resource name the font was first selected with by `Tf`
+    pub name: Vec<u8>,
+    /// The font's object, `None` for a direct font dictionary
+    pub font_id: Option<ObjectId>,
+    /// The /BaseFont of the font
+    pub base_font: Option<Vec<u8>>,
+    /// Where the Unicode text of the font's codes comes from
+    pub source: MappingSource,
+    /// Number of codes shown with the font that have no character
+    pub unmapped_glyphs: usize,
+}
+
+/// A content operation whose text wasn't extracted, see [`ExtractionTrace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedOperation {
+    /// The operator, e.g. `Tj`
+    pub operator: String,
+    /// The Form XObject the operation belongs to, `None` for page content
+    pub form_id: Option<ObjectId>,
+    /// Why the operation was skipped
+    pub reason: String,
+}
+
+/// How the text of a page was extracted, recorded by [`Document::extract_text_with_trace`].
+///
+/// [`Document::extract_text_with_trace`]: crate::Document::extract_text_with_trace
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractionTrace {
+    /// The number of the page, as given to [`Document::extract_text_with_trace`]
+    ///
+    /// [`Document::extract_text_with_trace`]: crate::Document::extract_text_with_trace
+    pub page_number: u32,
+    /// The fonts text was shown with, in the order they were first selected
+    pub fonts: Vec<FontTrace>,
+    /// The Form XObjects descended into, in the order they were first entered
+    pub forms: Vec<ObjectId>,
+    /// Text operations that couldn't be decoded, or that came before a usable font was selected
+    pub skipped: Vec<SkippedOperation>,
+}
+
+impl ExtractionTrace {
+    pub(crate) fn new(page_number: u32) -> Self {
+        ExtractionTrace {
+            page_number,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn skip(&mut self, operator: &str, form_id: Option<ObjectId>, reason: impl Into<String>) {
+        self.skipped.push(SkippedOperation {
+            operator: operator.to_string(),
+            form_id,
+            reason: reason.into(),
+        });
+    }
+
+    pub(crate) fn enter_form(&mut self, form_id: Option<ObjectId>) {
+        if let Some(form_id) = form_id {
+            if !self.forms.contains(&form_id) {
+                self.forms.push(form_id);
+            }
+        }
+    }
+}
+
+/// Count the codes of the strings of text operands without a character in `encoding`.
+pub(crate) fn unmapped_glyphs(encoding: &Encoding, operands: &[Object]) -> usize {
+    operands
+        .iter()
+        .map(|operand| match operand {
+            Object::String(bytes, _) => encoding.unmapped_codes(bytes),
+            Object::Array(array) => unmapped_glyphs(encoding, array),
+            _ => 0,
+        })
+        .sum()
+}
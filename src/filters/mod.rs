@@ -1 +1,3 @@
 pub mod png;
+pub mod run_length;
+pub mod tiff;
@@ -1,5 +1,5 @@
 use std::convert::{TryFrom, TryInto};
-use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::io::{Error, ErrorKind, Result, Write};
 use std::mem;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -69,7 +69,8 @@ pub fn decode_row(filter: FilterType, bpp: usize, previous: &[u8], current: &mut
             }
 
             for i in bpp..len {
-                current[i] = current[i].wrapping_add((i16::from(current[i - bpp]) + i16::from(previous[i]) / 2) as u8);
+                let mean = (u16::from(current[i - bpp]) + u16::from(previous[i])) / 2;
+                current[i] = current[i].wrapping_add(mean as u8);
             }
         }
         Paeth => {
@@ -85,7 +86,14 @@ pub fn decode_row(filter: FilterType, bpp: usize, previous: &[u8], current: &mut
 }
 
 pub fn decode_frame(content: &[u8], bytes_per_pixel: usize, pixels_per_row: usize) -> Result<Vec<u8>> {
-    let bytes_per_row = bytes_per_pixel * pixels_per_row;
+    decode_rows(content, bytes_per_pixel, bytes_per_pixel * pixels_per_row)
+}
+
+/// Decode rows of `bytes_per_row` bytes, each preceded by its filter type. Components of less
+/// than 8 bits are packed into the bytes of a row, which then has a `bytes_per_pixel` of 1. A
+/// last row cut short is decoded as far as it goes.
+pub fn decode_rows(content: &[u8], bytes_per_pixel: usize, bytes_per_row: usize) -> Result<Vec<u8>> {
+    let bytes_per_pixel = bytes_per_pixel.max(1);
     let mut previous = Vec::new();
     previous.try_reserve(bytes_per_row)?;
     previous.resize(bytes_per_row, 0_u8);
@@ -97,11 +105,13 @@ pub fn decode_frame(content: &[u8], bytes_per_pixel: usize, pixels_per_row: usiz
     while pos < content.len() {
         if let Ok(filter) = content[pos].try_into() {
             pos += 1;
-            (&content[pos..]).read_exact(current.as_mut_slice())?;
+            let row = &content[pos..content.len().min(pos + bytes_per_row)];
             pos += bytes_per_row;
+            current[..row.len()].copy_from_slice(row);
 
-            decode_row(filter, bytes_per_pixel, previous.as_slice(), current.as_mut_slice());
-            decoded.write_all(current.as_slice())?;
+            let len = row.len();
+            decode_row(filter, bytes_per_pixel, &previous[..len], &mut current[..len]);
+            decoded.write_all(&current[..len])?;
             mem::swap(&mut previous, &mut current);
         } else {
             return Err(Error::new(
@@ -132,7 +142,8 @@ pub fn encode_row(method: FilterType, bpp: usize, previous: &[u8], current: &mut
         }
         Avg => {
             for i in (bpp..len).rev() {
-                current[i] = current[i].wrapping_sub(current[i - bpp].wrapping_add(previous[i]) / 2);
+                let mean = (u16::from(current[i - bpp]) + u16::from(previous[i])) / 2;
+                current[i] = current[i].wrapping_sub(mean as u8);
             }
 
             for i in 0..bpp {
@@ -150,3 +161,83 @@ pub fn encode_row(method: FilterType, bpp: usize, previous: &[u8], current: &mut
         }
     }
 }
+
+/// Encode rows of `bytes_per_pixel * pixels_per_row` bytes, each preceded by its filter type.
+/// Without a `method`, each row uses the filter with the lowest sum of absolute differences,
+/// the heuristic the PNG specification recommends.
+pub fn encode_frame(
+    content: &[u8], bytes_per_pixel: usize, pixels_per_row: usize, method: Option<FilterType>,
+) -> Vec<u8> {
+    use self::FilterType::*;
+    let bytes_per_row = (bytes_per_pixel * pixels_per_row).max(1);
+    let mut encoded = Vec::with_capacity(content.len() + content.len() / bytes_per_row + 1);
+    let mut previous = vec![0_u8; bytes_per_row];
+    for row in content.chunks(bytes_per_row) {
+        let mut current = row.to_vec();
+        current.resize(bytes_per_row, 0);
+        let (filter, filtered) = match method {
+            Some(filter) => {
+                let mut filtered = current.clone();
+                encode_row(filter, bytes_per_pixel, &previous, &mut filtered);
+                (filter, filtered)
+            }
+            Option::None => [None, Sub, Up, Avg, Paeth]
+                .into_iter()
+                .map(|filter| {
+                    let mut filtered = current.clone();
+                    encode_row(filter, bytes_per_pixel, &previous, &mut filtered);
+                    (filter, filtered)
+                })
+                .min_by_key(|(_, filtered)| {
+                    filtered
+                        .iter()
+                        .map(|&byte| u64::from((byte as i8).unsigned_abs()))
+                        .sum::<u64>()
+                })
+                .unwrap_or((None, current.clone())),
+        };
+        encoded.push(filter as u8);
+        encoded.extend_from_slice(&filtered);
+        previous = current;
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoded_frames_decode_to_their_content() {
+        let content: Vec<u8> = (0..60_u32).map(|i| (i * 37 % 251) as u8).collect();
+        for method in [
+            Some(FilterType::None),
+            Some(FilterType::Sub),
+            Some(FilterType::Up),
+            Some(FilterType::Avg),
+            Some(FilterType::Paeth),
+            Option::None,
+        ] {
+            let encoded = encode_frame(&content, 2, 3, method);
+            assert_eq!(encoded.len(), content.len() + 10);
+            assert_eq!(decode_frame(&encoded, 2, 3).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn last_row_cut_short_is_decoded() {
+        let content: Vec<u8> = (0..20_u8).collect();
+        // The third row is padded to 8 bytes, then cut to 2.
+        let mut encoded = encode_frame(&content, 1, 8, Some(FilterType::Up));
+        encoded.truncate(encoded.len() - 6);
+        assert_eq!(decode_rows(&encoded, 1, 8).unwrap(), content[..18]);
+    }
+
+    #[test]
+    fn avg_predicts_the_mean_of_left_and_above() {
+        let mut row = [200, 10];
+        decode_row(FilterType::Avg, 1, &[100, 250], &mut row);
+        // 200 + 100 / 2, then 10 + (250 + 250) / 2 without overflowing the sum.
+        assert_eq!(row, [250, 4]);
+    }
+}
@@ -0,0 +1,126 @@
+use log::warn;
+
+/// End-of-data marker of the RunLengthDecode filter.
+const EOD: u8 = 128;
+/// Longest literal or repeat run a single length byte can describe.
+const MAX_RUN: usize = 128;
+
+/// Decode data compressed with the RunLengthDecode filter.
+///
+/// A length byte of 0 to 127 is followed by that many plus one literal bytes, a length byte of
+/// 129 to 255 is followed by one byte that is repeated 257 minus length times, and 128 marks the
+/// end of data. Anything after the end-of-data marker is ignored; a missing marker is accepted.
+pub fn decode(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() * 2);
+    let mut pos = 0;
+
+    while let Some(&length) = input.get(pos) {
+        pos += 1;
+        match length {
+            0..=127 => {
+                let end = pos + length as usize + 1;
+                if end > input.len() {
+                    warn!("RunLengthDecode: literal run is truncated");
+                    output.extend_from_slice(&input[pos..]);
+                    return output;
+                }
+                output.extend_from_slice(&input[pos..end]);
+                pos = end;
+            }
+            EOD => {
+                if pos < input.len() {
+                    warn!("RunLengthDecode: ignoring {} bytes after EOD marker", input.len() - pos);
+                }
+                return output;
+            }
+            129..=255 => {
+                let Some(&byte) = input.get(pos) else {
+                    warn!("RunLengthDecode: repeat run is missing its byte");
+                    return output;
+                };
+                output.resize(output.len() + 257 - length as usize, byte);
+                pos += 1;
+            }
+        }
+    }
+
+    output
+}
+
+/// Encode data with the RunLengthDecode filter, terminated by the end-of-data marker.
+pub fn encode(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() + input.len() / MAX_RUN + 2);
+    let mut literal_start = 0;
+    let mut pos = 0;
+
+    while pos < input.len() {
+        let byte = input[pos];
+        let repeat = input[pos..].iter().take(MAX_RUN).take_while(|&&b| b == byte).count();
+
+        // Two identical bytes cost the same either way, so only switch to a repeat run
+        // when it actually saves space.
+        if repeat >= 3 {
+            flush_literals(&mut output, &input[literal_start..pos]);
+            output.push((257 - repeat) as u8);
+            output.push(byte);
+            pos += repeat;
+            literal_start = pos;
+        } else {
+            pos += 1;
+        }
+    }
+    flush_literals(&mut output, &input[literal_start..]);
+    output.push(EOD);
+
+    output
+}
+
+fn flush_literals(output: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(MAX_RUN) {
+        output.push((chunk.len() - 1) as u8);
+        output.extend_from_slice(chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn decode_literal_and_repeat_runs() {
+        let input = [2, b'a', b'b', b'c', 254, b'x', 0, b'z', EOD];
+        assert_eq!(decode(&input), b"abcxxxz");
+    }
+
+    #[test]
+    fn decode_ignores_data_after_eod() {
+        assert_eq!(decode(&[1, b'o', b'k', EOD, 255, b'!']), b"ok");
+    }
+
+    #[test]
+    fn decode_accepts_missing_eod() {
+        assert_eq!(decode(&[129, b'-']), vec![b'-'; 128]);
+        assert_eq!(decode(&[4, b'a', b'b']), b"ab");
+        assert_eq!(decode(&[]), b"");
+    }
+
+    #[test]
+    fn encode_uses_repeat_runs() {
+        assert_eq!(encode(b"aaaab"), [253, b'a', 0, b'b', EOD]);
+        assert_eq!(encode(&[7; 300]), [129, 7, 129, 7, 213, 7, EOD]);
+        assert_eq!(encode(b""), [EOD]);
+    }
+
+    #[test]
+    fn round_trip_random_buffers() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let len = rng.random_range(0..2048);
+            // A small alphabet produces both long repeats and literal stretches.
+            let alphabet = rng.random_range(1..=255u8);
+            let data: Vec<u8> = (0..len).map(|_| rng.random_range(0..=alphabet)).collect();
+            assert_eq!(decode(&encode(&data)), data);
+        }
+    }
+}
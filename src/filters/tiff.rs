@@ -0,0 +1,65 @@
+//! TIFF predictor 2 for components of 8 and 16 bits: each component stores its difference from
+//! the corresponding component of the pixel to its left.
+
+/// Undo the predictor on rows of `bytes_per_row` bytes.
+pub fn decode(content: &mut [u8], bytes_per_pixel: usize, bytes_per_row: usize) {
+    for row in content.chunks_mut(bytes_per_row.max(1)) {
+        for i in bytes_per_pixel..row.len() {
+            row[i] = row[i].wrapping_add(row[i - bytes_per_pixel]);
+        }
+    }
+}
+
+/// Undo the predictor on rows of `bytes_per_row` bytes of big-endian 16-bit components, with
+/// `colors` components per pixel.
+pub fn decode_16(content: &mut [u8], colors: usize, bytes_per_row: usize) {
+    let bytes_per_pixel = 2 * colors;
+    for row in content.chunks_mut(bytes_per_row.max(1)) {
+        // A trailing odd byte isn't a component.
+        let len = row.len() & !1;
+        for i in (bytes_per_pixel..len).step_by(2) {
+            let left = u16::from_be_bytes([row[i - bytes_per_pixel], row[i - bytes_per_pixel + 1]]);
+            let sample = u16::from_be_bytes([row[i], row[i + 1]]).wrapping_add(left);
+            row[i..i + 2].copy_from_slice(&sample.to_be_bytes());
+        }
+    }
+}
+
+/// Apply the predictor to rows of `bytes_per_row` bytes.
+pub fn encode(content: &mut [u8], bytes_per_pixel: usize, bytes_per_row: usize) {
+    for row in content.chunks_mut(bytes_per_row.max(1)) {
+        for i in (bytes_per_pixel..row.len()).rev() {
+            row[i] = row[i].wrapping_sub(row[i - bytes_per_pixel]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn encoding_is_undone_by_decoding() {
+        let content: Vec<u8> = (0..24_u32).map(|i| (i * 91 % 256) as u8).collect();
+        let mut encoded = content.clone();
+        super::encode(&mut encoded, 1, 6);
+        assert_eq!(encoded[0], content[0]);
+        assert_eq!(encoded[1], content[1].wrapping_sub(content[0]));
+        assert_eq!(encoded[6], content[6]);
+        super::decode(&mut encoded, 1, 6);
+        assert_eq!(encoded, content);
+    }
+
+    #[test]
+    fn differences_of_16_bit_components_carry() {
+        // Two RGB pixels per row: the second pixel is stored as its difference from the first.
+        let mut content = vec![
+            0x01, 0xFF, 0x00, 0x10, 0xFF, 0xFF, 0x00, 0x01, 0x00, 0x20, 0x00, 0x02, //
+            0x12, 0x34, 0x00, 0x00, 0x80, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x80, 0x00,
+        ];
+        super::decode_16(&mut content, 3, 12);
+        let samples: Vec<u16> = content.chunks(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+        assert_eq!(
+            samples,
+            [0x01FF, 0x0010, 0xFFFF, 0x0200, 0x0030, 0x0001, 0x1234, 0x0000, 0x8000, 0x1233, 0x0000, 0x0000]
+        );
+    }
+}
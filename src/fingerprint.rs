@@ -0,0 +1,317 @@
+use std::collections::{HashMap, HashSet};
+
+use sha2::{Digest, Sha256};
+
+use crate::{Dictionary, Document, Object, ObjectId};
+
+type FingerprintCache = HashMap<ObjectId, [u8; 32]>;
+
+impl Document {
+    /// Compute a SHA-256 fingerprint for every page, keyed by page number.
+    ///
+    /// The fingerprint covers the decoded content operations, with numbers normalized so that
+    /// e.g. `1` and `1.0` hash the same, and the page resources, hashed by value rather than by
+    /// object number, and its media box, crop box and rotation, inherited from the page tree like
+    /// the resources. Pages with equal fingerprints render identically.
+    pub fn page_fingerprints(&self) -> Vec<(u32, [u8; 32])> {
+        let mut cache = FingerprintCache::new();
+        self.get_pages()
+            .into_iter()
+            .map(|(number, page_id)| (number, self.page_fingerprint(page_id, &mut cache)))
+            .collect()
+    }
+
+    /// Make pages with identical fingerprints share the same /Contents and /Resources objects.
+    ///
+    /// The page objects stay distinct, only the streams and resources they reference are collapsed.
+    /// Objects that are no longer referenced after that are removed. Returns the number of pages
+    /// that now share the objects of an earlier page.
+    pub fn dedupe_identical_pages(&mut self) -> usize {
+        let pages = self.get_pages();
+        let mut originals: HashMap<[u8; 32], ObjectId> = HashMap::new();
+        let mut replaced = HashSet::new();
+        let mut deduped = 0;
+
+        for (number, fingerprint) in self.page_fingerprints() {
+            let page_id = pages[&number];
+            let Some(&original_id) = originals.get(&fingerprint) else {
+                originals.insert(fingerprint, page_id);
+                continue;
+            };
+            if original_id == page_id {
+                continue;
+            }
+            let Ok(original) = self.get_dictionary(original_id) else {
+                continue;
+            };
+            let contents = original.get(b"Contents").ok().cloned();
+            let resources = match original.get(b"Resources") {
+                // Inline resources can't be shared, so move them into their own object first.
+                Ok(Object::Dictionary(resources)) => {
+                    let resources_id = self.add_object(resources.clone());
+                    if let Ok(original) = self.get_dictionary_mut(original_id) {
+                        original.set("Resources", resources_id);
+                    }
+                    Some(Object::Reference(resources_id))
+                }
+                Ok(resources) => Some(resources.clone()),
+                Err(_) => None,
+            };
+
+            let Ok(page) = self.get_dictionary(page_id) else {
+                continue;
+            };
+            for key in [&b"Contents"[..], b"Resources"] {
+                if let Ok(value) = page.get(key) {
+                    self.collect_references(value, &mut replaced);
+                }
+            }
+
+            let Ok(page) = self.get_dictionary_mut(page_id) else {
+                continue;
+            };
+            if let Some(contents) = contents {
+                page.set("Contents", contents);
+            }
+            if let Some(resources) = resources {
+                page.set("Resources", resources);
+            }
+            deduped += 1;
+        }
+
+        if !replaced.is_empty() {
+            let referenced: HashSet<ObjectId> = self.traverse_objects(|_| {}).into_iter().collect();
+            for id in replaced.difference(&referenced) {
+                self.objects.remove(id);
+            }
+        }
+
+        deduped
+    }
+
+    fn page_fingerprint(&self, page_id: ObjectId, cache: &mut FingerprintCache) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+
+        match self.get_and_decode_page_content(page_id) {
+            Ok(content) => {
+                for operation in &content.operations {
+                    for operand in &operation.operands {
+                        self.hash_object(operand, &mut hasher, cache, &mut Vec::new());
+                    }
+                    hash_bytes(&mut hasher, b"o", operation.operator.as_bytes());
+                }
+            }
+            Err(_) => hash_bytes(&mut hasher, b"c", &self.get_page_content(page_id).unwrap_or_default()),
+        }
+
+        if let Ok((resources, resource_ids)) = self.get_page_resources(page_id) {
+            if let Some(resources) = resources {
+                self.hash_dictionary(resources, &mut hasher, cache, &mut Vec::new());
+            }
+            for id in resource_ids {
+                hasher.update(self.object_fingerprint(id, cache, &mut Vec::new()));
+            }
+        }
+
+        if let Ok(page) = self.get_dictionary(page_id) {
+            // The crop box defaults to the media box, the rotation to 0.
+            let media_box = self.inherited_page_attribute(page, b"MediaBox");
+            let crop_box = self.inherited_page_attribute(page, b"CropBox").or(media_box);
+            for (tag, page_box) in [(b"M", media_box), (b"C", crop_box)] {
+                hasher.update(tag);
+                match page_box {
+                    Some(page_box) => self.hash_object(page_box, &mut hasher, cache, &mut Vec::new()),
+                    None => hasher.update(b"n"),
+                }
+            }
+            let rotate = self
+                .inherited_page_attribute(page, b"Rotate")
+                .and_then(|rotate| rotate.as_i64().ok())
+                .unwrap_or(0);
+            hash_bytes(&mut hasher, b"O", &rotate.rem_euclid(360).to_be_bytes());
+        }
+
+        hasher.finalize().into()
+    }
+
+    fn object_fingerprint(&self, id: ObjectId, cache: &mut FingerprintCache, stack: &mut Vec<ObjectId>) -> [u8; 32] {
+        if let Some(fingerprint) = cache.get(&id) {
+            return *fingerprint;
+        }
+        let mut hasher = Sha256::new();
+        if stack.contains(&id) {
+            hasher.update(b"cycle");
+            return hasher.finalize().into();
+        }
+
+        stack.push(id);
+        match self.get_object(id) {
+            Ok(object) => self.hash_object(object, &mut hasher, cache, stack),
+            Err(_) => hasher.update(b"missing"),
+        }
+        stack.pop();
+
+        let fingerprint = hasher.finalize().into();
+        cache.insert(id, fingerprint);
+        fingerprint
+    }
+
+    fn hash_object(&self, object: &Object, hasher: &mut Sha256, cache: &mut FingerprintCache, stack: &mut Vec<ObjectId>) {
+        match object {
+            Object::Null => hasher.update(b"n"),
+            Object::Boolean(value) => hasher.update(if *value { b"t" } else { b"f" }),
+            Object::Integer(value) => hash_bytes(hasher, b"i", &value.to_be_bytes()),
+            Object::Real(value) if value.fract() == 0.0 && value.abs() < i64::MAX as f32 => {
+                hash_bytes(hasher, b"i", &(*value as i64).to_be_bytes())
+            }
+            Object::Real(value) => hash_bytes(hasher, b"r", &value.to_bits().to_be_bytes()),
+            Object::Name(name) => hash_bytes(hasher, b"/", name),
+            Object::String(text, _) => hash_bytes(hasher, b"s", text),
+            Object::Array(array) => {
+                hash_bytes(hasher, b"[", &(array.len() as u64).to_be_bytes());
+                for item in array {
+                    self.hash_object(item, hasher, cache, stack);
+                }
+            }
+            Object::Dictionary(dict) => self.hash_dictionary(dict, hasher, cache, stack),
+            Object::Stream(stream) => {
+                self.hash_dictionary(&stream.dict, hasher, cache, stack);
+                hash_bytes(hasher, b"S", &stream.content);
+            }
+            Object::Reference(id) => {
+                let fingerprint = self.object_fingerprint(*id, cache, stack);
+                hash_bytes(hasher, b"R", &fingerprint);
+            }
+        }
+    }
+
+    fn hash_dictionary(
+        &self, dict: &Dictionary, hasher: &mut Sha256, cache: &mut FingerprintCache, stack: &mut Vec<ObjectId>,
+    ) {
+        let mut entries: Vec<_> = dict.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        hash_bytes(hasher, b"<", &(entries.len() as u64).to_be_bytes());
+        for (key, value) in entries {
+            hash_bytes(hasher, b"/", key);
+            self.hash_object(value, hasher, cache, stack);
+        }
+    }
+
+    /// Collect the ids of `object` and all objects reachable from it.
+    fn collect_references(&self, object: &Object, ids: &mut HashSet<ObjectId>) {
+        match object {
+            Object::Reference(id) if ids.insert(*id) => {
+                if let Ok(object) = self.get_object(*id) {
+                    self.collect_references(object, ids);
+                }
+            }
+            Object::Array(array) => array.iter().for_each(|item| self.collect_references(item, ids)),
+            Object::Dictionary(dict) => dict.iter().for_each(|(_, value)| self.collect_references(value, ids)),
+            Object::Stream(stream) => stream.dict.iter().for_each(|(_, value)| self.collect_references(value, ids)),
+            _ => {}
+        }
+    }
+}
+
+/// Feed a tagged, length-prefixed value to the hasher so adjacent values can't run together.
+fn hash_bytes(hasher: &mut Sha256, tag: &[u8], bytes: &[u8]) {
+    hasher.update(tag);
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::creator::tests::create_document_with_texts;
+    use crate::{Document, Object, ObjectId, Stream};
+
+    /// Append `copies` pages whose content and resources are copies of the first page's.
+    fn duplicate_first_page(doc: &mut Document, copies: usize) {
+        let page_id = doc.page_iter().next().unwrap();
+        let pages_id = doc.catalog().unwrap().get(b"Pages").and_then(Object::as_reference).unwrap();
+        let page = doc.get_dictionary(page_id).unwrap().clone();
+        let content_id = page.get(b"Contents").and_then(Object::as_reference).unwrap();
+        let content = doc.get_object(content_id).unwrap().clone();
+
+        for _ in 0..copies {
+            let content_id = doc.add_object(content.clone());
+            let mut copy = page.clone();
+            copy.set("Contents", content_id);
+            let copy_id = doc.add_object(copy);
+            let pages = doc.get_dictionary_mut(pages_id).unwrap();
+            pages.get_mut(b"Kids").and_then(Object::as_array_mut).unwrap().push(copy_id.into());
+            let count = pages.get(b"Count").and_then(Object::as_i64).unwrap();
+            pages.set("Count", count + 1);
+        }
+    }
+
+    fn content_ids(doc: &Document) -> Vec<ObjectId> {
+        doc.page_iter().flat_map(|page_id| doc.get_page_contents(page_id)).collect()
+    }
+
+    #[test]
+    fn identical_pages_share_fingerprints() {
+        let mut doc = create_document_with_texts(&["Terms and conditions", "Dear Alice", "Dear Bob"]);
+        duplicate_first_page(&mut doc, 2);
+
+        let fingerprints = doc.page_fingerprints();
+        assert_eq!(fingerprints.len(), 5);
+        assert_eq!(fingerprints[0].1, fingerprints[3].1);
+        assert_eq!(fingerprints[0].1, fingerprints[4].1);
+        assert_ne!(fingerprints[0].1, fingerprints[1].1);
+        assert_ne!(fingerprints[1].1, fingerprints[2].1);
+    }
+
+    #[test]
+    fn fingerprints_normalize_numbers() {
+        let mut doc = create_document_with_texts(&["same", "same"]);
+        let pages: Vec<ObjectId> = doc.page_iter().collect();
+        doc.change_page_content(pages[0], b"BT /F1 48 Tf 100 600 Td (same) Tj ET".to_vec())
+            .unwrap();
+        doc.change_page_content(pages[1], b"BT /F1 48.0 Tf 100.00 600 Td (same) Tj ET".to_vec())
+            .unwrap();
+
+        let fingerprints = doc.page_fingerprints();
+        assert_eq!(fingerprints[0].1, fingerprints[1].1);
+    }
+
+    #[test]
+    fn fingerprints_cover_page_boxes_and_rotation() {
+        let mut doc = create_document_with_texts(&["Terms and conditions"]);
+        duplicate_first_page(&mut doc, 3);
+        let pages: Vec<ObjectId> = doc.page_iter().collect();
+        let media_box: Vec<Object> = vec![0.into(), 0.into(), 595.into(), 842.into()];
+        doc.get_dictionary_mut(pages[1]).unwrap().set("CropBox", media_box);
+        doc.get_dictionary_mut(pages[2]).unwrap().set("Rotate", 90);
+        doc.get_dictionary_mut(pages[3]).unwrap().set("CropBox", vec![0.into(), 0.into(), 300.into(), 300.into()]);
+
+        let fingerprints = doc.page_fingerprints();
+        // A crop box equal to the inherited media box is the default one.
+        assert_eq!(fingerprints[0].1, fingerprints[1].1);
+        assert_ne!(fingerprints[0].1, fingerprints[2].1);
+        assert_ne!(fingerprints[0].1, fingerprints[3].1);
+    }
+
+    #[test]
+    fn dedupe_identical_pages_shares_contents() {
+        let mut doc = create_document_with_texts(&["Terms and conditions"]);
+        duplicate_first_page(&mut doc, 10);
+        let mut before = Vec::new();
+        doc.save_to(&mut before).unwrap();
+        assert_eq!(content_ids(&doc).len(), 11);
+
+        assert_eq!(doc.dedupe_identical_pages(), 10);
+
+        let mut ids = content_ids(&doc);
+        assert_eq!(ids.len(), 11);
+        ids.dedup();
+        assert_eq!(ids.len(), 1);
+        let streams = doc.objects.values().filter(|o| matches!(o, Object::Stream(Stream { .. }))).count();
+        assert_eq!(streams, 1);
+        assert_eq!(doc.get_pages().len(), 11);
+
+        let mut after = Vec::new();
+        doc.save_to(&mut after).unwrap();
+        assert!(after.len() < before.len());
+    }
+}
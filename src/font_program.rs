@@ -0,0 +1,29 @@
+use crate::{Dictionary, Document, Object};
+
+/// The decoded data of a font program embedded in a font descriptor.
+pub(crate) enum FontProgram {
+    /// A TrueType or OpenType font
+    OpenType(Vec<u8>),
+    /// A bare CFF font
+    Cff(Vec<u8>),
+}
+
+impl FontProgram {
+    /// Load the font program embedded in the descriptor of a simple font, if ttf-parser can read
+    /// it. Type 1 font programs are not loaded.
+    pub(crate) fn load(font: &Dictionary, doc: &Document) -> Option<Self> {
+        let descriptor = font.get_deref(b"FontDescriptor", doc).ok()?.as_dict().ok()?;
+        if let Ok(stream) = descriptor.get_deref(b"FontFile2", doc).and_then(Object::as_stream) {
+            return Some(FontProgram::OpenType(stream.get_plain_content().ok()?));
+        }
+        let stream = descriptor
+            .get_deref(b"FontFile3", doc)
+            .and_then(Object::as_stream)
+            .ok()?;
+        let content = stream.get_plain_content().ok()?;
+        match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"OpenType") => Some(FontProgram::OpenType(content)),
+            _ => Some(FontProgram::Cff(content)),
+        }
+    }
+}
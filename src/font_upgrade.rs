@@ -0,0 +1,659 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use ttf_parser::Face;
+
+use crate::content::{Content, Operation};
+use crate::content_walker::ContentWalker;
+use crate::font_widths::WidthSource;
+use crate::resource_usage::ResourceCategory;
+use crate::standard_fonts::strip_subset_prefix;
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result, Stream, StringFormat};
+
+/// The character code word spacing applies to, in single-byte encodings only.
+const SPACE: u8 = b' ';
+
+/// How the codes of a simple font map to the glyphs of the full font program.
+struct CodeGlyphs {
+    /// The character each code shows, from the font's encoding
+    chars: [Option<char>; 256],
+    /// The glyph of the full font program for the character of each code
+    glyphs: [Option<u16>; 256],
+}
+
+impl CodeGlyphs {
+    fn new(font: &Dictionary, doc: &Document, face: &Face) -> Result<Self> {
+        let encoding = font.get_font_encoding(doc)?;
+        let mut chars = [None; 256];
+        let mut glyphs = [None; 256];
+        for code in 0..=u8::MAX {
+            let text = encoding.bytes_to_string(&[code]).unwrap_or_default();
+            let mut text = text.chars();
+            if let (Some(ch), None) = (text.next(), text.next())
+                && ch != char::REPLACEMENT_CHARACTER
+            {
+                chars[code as usize] = Some(ch);
+                glyphs[code as usize] = face.glyph_index(ch).map(|glyph| glyph.0);
+            }
+        }
+        Ok(CodeGlyphs { chars, glyphs })
+    }
+}
+
+/// Text state of the content being re-encoded, saved and restored by `q` and `Q`.
+#[derive(Debug, Clone, Default)]
+struct TextState {
+    font: Option<Vec<u8>>,
+    font_size: f32,
+    word_spacing: f32,
+}
+
+/// Re-encodes the strings a content stream shows with the font being upgraded.
+struct Reencoder<'a> {
+    /// Names the resources of the content give the font
+    names: &'a FontNames,
+    codes: &'a CodeGlyphs,
+    /// Codes shown that the full font program has no glyph for
+    missing: &'a mut BTreeSet<u8>,
+}
+
+impl Reencoder<'_> {
+    /// The operations with the strings shown in the font re-encoded, or `None` if there are none.
+    fn reencode(&mut self, operations: Vec<Operation>) -> Option<Vec<Operation>> {
+        let mut state = TextState::default();
+        let mut saved_states = vec![];
+        let mut changed = false;
+        let mut reencoded = Vec::with_capacity(operations.len());
+        for mut operation in operations {
+            let operands = &operation.operands;
+            match operation.operator.as_str() {
+                "q" => saved_states.push(state.clone()),
+                "Q" => state = saved_states.pop().unwrap_or_default(),
+                "Tf" => {
+                    state.font = operands
+                        .first()
+                        .and_then(|name| name.as_name().ok())
+                        .map(<[u8]>::to_vec);
+                    state.font_size = operands.get(1).and_then(|size| size.as_float().ok()).unwrap_or(0.0);
+                }
+                "Tw" => state.word_spacing = operands.first().and_then(|tw| tw.as_float().ok()).unwrap_or(0.0),
+                "\"" => {
+                    state.word_spacing = operands.first().and_then(|aw| aw.as_float().ok()).unwrap_or(0.0);
+                }
+                _ => {}
+            }
+            let shows_text = matches!(operation.operator.as_str(), "Tj" | "TJ" | "'" | "\"");
+            if !shows_text || !state.font.as_ref().is_some_and(|font| self.names.contains(font)) {
+                reencoded.push(operation);
+                continue;
+            }
+            // Word spacing only applies to single-byte codes, so it's replaced by adjustments
+            // after each space, in thousandths of the font size.
+            let gap = (state.word_spacing != 0.0 && state.font_size != 0.0)
+                .then(|| -state.word_spacing * 1000.0 / state.font_size);
+            let shows_space = match operation.operands.last() {
+                Some(Object::String(bytes, _)) => bytes.contains(&SPACE),
+                Some(Object::Array(shown)) => shown
+                    .iter()
+                    .any(|item| item.as_str().is_ok_and(|bytes| bytes.contains(&SPACE))),
+                _ => {
+                    reencoded.push(operation);
+                    continue;
+                }
+            };
+            let gap = gap.filter(|_| shows_space);
+            changed = true;
+
+            let mut items = vec![];
+            match operation.operands.pop() {
+                Some(Object::String(bytes, _)) => self.encode(&bytes, gap, &mut items),
+                Some(Object::Array(shown)) => {
+                    for item in shown {
+                        match item {
+                            Object::String(bytes, _) => self.encode(&bytes, gap, &mut items),
+                            adjustment => items.push(adjustment),
+                        }
+                    }
+                }
+                _ => unreachable!("the shown operand is a string or an array"),
+            }
+            match (operation.operator.as_str(), gap) {
+                ("TJ", _) => operation.operands.push(Object::Array(items)),
+                (_, None) => operation
+                    .operands
+                    .push(items.pop().unwrap_or(Object::string_literal(""))),
+                (operator, Some(_)) => {
+                    // Show the string with the adjustments of a TJ, setting what ' and " set first.
+                    if operator == "\"" {
+                        let mut spacings = std::mem::take(&mut operation.operands).into_iter();
+                        reencoded.push(Operation::new("Tw", spacings.next().into_iter().collect()));
+                        reencoded.push(Operation::new("Tc", spacings.next().into_iter().collect()));
+                    }
+                    if operator != "Tj" {
+                        reencoded.push(Operation::new("T*", vec![]));
+                    }
+                    operation = Operation::new("TJ", vec![Object::Array(items)]);
+                }
+            }
+            reencoded.push(operation);
+        }
+        changed.then_some(reencoded)
+    }
+
+    /// Append `bytes` as 2-byte glyph ids to the items of a TJ array, with a `gap` after each
+    /// space.
+    fn encode(&mut self, bytes: &[u8], gap: Option<f32>, items: &mut Vec<Object>) {
+        let mut cids = Vec::with_capacity(bytes.len() * 2);
+        for &code in bytes {
+            let glyph = self.codes.glyphs[code as usize].unwrap_or_else(|| {
+                self.missing.insert(code);
+                0
+            });
+            cids.extend_from_slice(&glyph.to_be_bytes());
+            if let Some(gap) = gap.filter(|_| code == SPACE) {
+                items.push(Object::String(std::mem::take(&mut cids), StringFormat::Hexadecimal));
+                items.push(gap.into());
+            }
+        }
+        if !cids.is_empty() || items.is_empty() {
+            items.push(Object::String(cids, StringFormat::Hexadecimal));
+        }
+    }
+}
+
+/// The names resources give the font being upgraded.
+type FontNames = HashSet<Vec<u8>>;
+
+/// Content showing text in the font being upgraded, with the names its resources give the font.
+enum Target {
+    Page(ObjectId, FontNames),
+    Form(ObjectId, FontNames),
+}
+
+impl Document {
+    /// Replace a simple font with a Type0 font of the full TrueType or OpenType `font_program`,
+    /// so that text can be written with any character the program has a glyph for, rather than
+    /// only those the font's single-byte encoding or subset has. Returns the ID of the new font.
+    ///
+    /// `font_program` should be the font the simple font is a subset of, or one matching it
+    /// visually. The new font keeps the /BaseFont, without subset tag, and the descriptor of the
+    /// old one, and the glyphs of the codes shown so far keep the widths of the old font. The
+    /// resources of the pages, and of the Form XObjects they draw, that refer to the old font
+    /// refer to the new one under the same names, and the strings these show in the font are
+    /// re-encoded from the old codes to 2-byte glyph ids. Word spacing, which doesn't apply to
+    /// 2-byte codes, is kept by adjusting the glyphs after spaces. The old font remains for
+    /// anything else referring to it, such as annotation appearances.
+    ///
+    /// Fails without changing the document, as when the program can't be parsed, the font isn't
+    /// a simple font or the program lacks glyphs for characters the pages show in it.
+    pub fn upgrade_font_to_type0(&mut self, font_id: ObjectId, font_program: &[u8]) -> Result<ObjectId> {
+        let face = Face::parse(font_program, 0)
+            .map_err(|err| Error::FontUpgrade(format!("the font program can't be parsed: {err}")))?;
+        let font = self.get_dictionary(font_id)?;
+        let subtype = font.get(b"Subtype").and_then(Object::as_name).unwrap_or_default();
+        if !font.has_type(b"Font") || !matches!(subtype, b"Type1" | b"TrueType" | b"MMType1") {
+            return Err(Error::FontUpgrade(format!(
+                "{} {} is a {} font rather than a simple font",
+                font_id.0,
+                font_id.1,
+                String::from_utf8_lossy(subtype)
+            )));
+        }
+        let base_font = String::from_utf8_lossy(font.get(b"BaseFont").and_then(Object::as_name)?).into_owned();
+        let name = strip_subset_prefix(&base_font).unwrap_or(&base_font).to_string();
+        let codes = CodeGlyphs::new(font, self, &face)?;
+
+        // Re-encode all content before changing anything, so that missing glyphs fail the upgrade.
+        let (targets, holders) = self.font_upgrade_targets(font_id)?;
+        let mut missing = BTreeSet::new();
+        let mut contents = Vec::new();
+        let mut reencoded_streams = HashSet::new();
+        for target in &targets {
+            let (operations, names) = match target {
+                Target::Page(page_id, names) => {
+                    let page_id = *page_id;
+                    let streams = self.get_page_contents(page_id);
+                    if streams.iter().all(|id| reencoded_streams.contains(id)) {
+                        continue;
+                    }
+                    reencoded_streams.extend(streams);
+                    (self.get_and_decode_page_content(page_id)?.operations, names)
+                }
+                Target::Form(form_id, names) => {
+                    let form = self.get_object(*form_id).and_then(Object::as_stream)?;
+                    (Content::decode(&form.get_plain_content()?)?.operations, names)
+                }
+            };
+            let mut reencoder = Reencoder {
+                names,
+                codes: &codes,
+                missing: &mut missing,
+            };
+            if let Some(operations) = reencoder.reencode(operations) {
+                contents.push((target, Content { operations }.encode()?));
+            }
+        }
+        if !missing.is_empty() {
+            let chars: Option<Vec<char>> = missing.iter().map(|&code| codes.chars[code as usize]).collect();
+            return Err(match chars {
+                Some(chars) => Error::MissingGlyphs { font: name, chars },
+                None => Error::FontUpgrade(format!("character codes {missing:?} have no Unicode value")),
+            });
+        }
+
+        let widths = self.font_widths(font).ok();
+        let descriptor = font
+            .get_deref(b"FontDescriptor", self)
+            .and_then(Object::as_dict)
+            .ok()
+            .cloned();
+        // The objects are put back should replacing the content or the resources fail halfway.
+        let (objects, max_id) = (self.objects.clone(), self.max_id);
+        let type0_id = self.add_type0_font(&name, &face, font_program, &codes, widths, descriptor);
+        if let Err(err) = self.replace_font_uses(font_id, type0_id, contents, holders) {
+            (self.objects, self.max_id) = (objects, max_id);
+            return Err(err);
+        }
+        Ok(type0_id)
+    }
+
+    /// Write the re-encoded `contents` and refer the resources of `holders` to the new font.
+    fn replace_font_uses(
+        &mut self, font_id: ObjectId, type0_id: ObjectId, contents: Vec<(&Target, Vec<u8>)>,
+        holders: BTreeSet<ObjectId>,
+    ) -> Result<()> {
+        for (target, content) in contents {
+            match *target {
+                Target::Page(page_id, _) => self.change_page_content(page_id, content)?,
+                Target::Form(form_id, _) => {
+                    let form = self.get_object_mut(form_id).and_then(Object::as_stream_mut)?;
+                    form.set_plain_content(content);
+                    // Ignore any compression error.
+                    let _ = form.compress();
+                }
+            }
+        }
+        for holder in holders {
+            self.replace_font_resource(holder, font_id, type0_id)?;
+        }
+        Ok(())
+    }
+
+    /// The pages and Form XObjects showing text in the font with the names they give it, and the
+    /// objects whose /Resources refer to the font for them.
+    fn font_upgrade_targets(&self, font_id: ObjectId) -> Result<(Vec<Target>, BTreeSet<ObjectId>)> {
+        let mut targets = Vec::new();
+        let mut holders = BTreeSet::new();
+        let mut forms: BTreeMap<ObjectId, FontNames> = BTreeMap::new();
+        for page_id in self.page_iter() {
+            let names: FontNames = self
+                .page_resource_ids(page_id, ResourceCategory::Font)?
+                .into_iter()
+                .filter(|(_, id)| *id == font_id)
+                .map(|(name, _)| name)
+                .collect();
+            if !names.is_empty() {
+                holders.extend(self.page_resources_holder(page_id)?);
+                targets.push(Target::Page(page_id, names));
+            }
+            ContentWalker::new(self).walk_page(page_id, |operation, context| {
+                let (Some(form_id), "Tf") = (context.form_id, operation.operator.as_str()) else {
+                    return;
+                };
+                let Some(Ok(name)) = operation.operands.first().map(Object::as_name) else {
+                    return;
+                };
+                if context.font(name).and_then(|(id, _)| id) == Some(font_id) {
+                    forms.entry(form_id).or_default().insert(name.to_vec());
+                }
+            })?;
+        }
+        for (form_id, names) in forms {
+            let form = self.get_object(form_id).and_then(Object::as_stream)?;
+            let own_font = form
+                .dict
+                .get_deref(b"Resources", self)
+                .and_then(Object::as_dict)
+                .and_then(|resources| resources.get_deref(b"Font", self))
+                .and_then(Object::as_dict)
+                .is_ok_and(|fonts| fonts.iter().any(|(_, font)| font.as_reference().ok() == Some(font_id)));
+            if own_font {
+                holders.insert(form_id);
+            }
+            targets.push(Target::Form(form_id, names));
+        }
+        Ok((targets, holders))
+    }
+
+    /// Add a Type0 font showing the glyphs of `font_program` by their ids through Identity-H.
+    fn add_type0_font(
+        &mut self, name: &str, face: &Face, font_program: &[u8], codes: &CodeGlyphs, widths: Option<crate::FontWidths>,
+        descriptor: Option<Dictionary>,
+    ) -> ObjectId {
+        let units_per_em = f32::from(face.units_per_em());
+        let mut glyph_widths: Vec<f32> = (0..face.number_of_glyphs())
+            .map(|glyph| {
+                let advance = face.glyph_hor_advance(ttf_parser::GlyphId(glyph)).unwrap_or(0);
+                (f32::from(advance) * 1000.0 / units_per_em).round()
+            })
+            .collect();
+        let mut chars = BTreeMap::new();
+        for code in 0..=u8::MAX {
+            let (Some(glyph), Some(ch)) = (codes.glyphs[code as usize], codes.chars[code as usize]) else {
+                continue;
+            };
+            if chars.insert(glyph, ch).is_none()
+                && let Some(widths) = widths
+                    .as_ref()
+                    .filter(|widths| widths.source(code) != WidthSource::Fallback)
+            {
+                glyph_widths[glyph as usize] = widths.width(code);
+            }
+        }
+        for subtable in face
+            .tables()
+            .cmap
+            .iter()
+            .flat_map(|cmap| cmap.subtables)
+            .filter(|table| table.is_unicode())
+        {
+            subtable.codepoints(|codepoint| {
+                let glyph = char::from_u32(codepoint).and_then(|ch| Some((subtable.glyph_index(codepoint)?, ch)));
+                if let Some((glyph, ch)) = glyph.filter(|(glyph, _)| glyph.0 != 0) {
+                    chars.entry(glyph.0).or_insert(ch);
+                }
+            });
+        }
+
+        let mut font_file = Stream::new(
+            dictionary! { "Length1" => font_program.len() as i64 },
+            font_program.to_vec(),
+        );
+        // Ignore any compression error.
+        let _ = font_file.compress();
+        let font_file_id = self.add_object(font_file);
+        let mut descriptor = descriptor.unwrap_or_else(|| {
+            let bbox = face.global_bounding_box();
+            let italic_angle = face.italic_angle();
+            // Nonsymbolic, and fixed pitch and italic as the font program says.
+            let flags = 32 | i64::from(face.is_monospaced()) | if italic_angle != 0.0 { 64 } else { 0 };
+            dictionary! {
+                "Type" => "FontDescriptor",
+                "Flags" => flags,
+                "FontBBox" => [bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max]
+                    .map(|value| Object::from(i64::from(value)))
+                    .to_vec(),
+                "ItalicAngle" => italic_angle.round() as i64,
+                "Ascent" => i64::from(face.ascender()),
+                "Descent" => i64::from(face.descender()),
+                "CapHeight" => i64::from(face.capital_height().unwrap_or(face.ascender())),
+                "StemV" => (f64::from(bbox.width()) * 0.13).round() as i64,
+            }
+        });
+        for key in [b"FontFile".as_slice(), b"FontFile3", b"CharSet", b"CIDSet"] {
+            descriptor.remove(key);
+        }
+        descriptor.set("FontName", Object::Name(name.as_bytes().to_vec()));
+        descriptor.set("FontFile2", font_file_id);
+        let descriptor_id = self.add_object(descriptor);
+
+        let descendant_id = self.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "CIDFontType2",
+            "BaseFont" => Object::Name(name.as_bytes().to_vec()),
+            "CIDSystemInfo" => dictionary! {
+                "Registry" => Object::string_literal("Adobe"),
+                "Ordering" => Object::string_literal("Identity"),
+                "Supplement" => 0,
+            },
+            "FontDescriptor" => descriptor_id,
+            "CIDToGIDMap" => "Identity",
+            "W" => vec![0.into(), glyph_widths.into_iter().map(Object::from).collect::<Vec<_>>().into()],
+        });
+        let to_unicode_id = self.add_object(Stream::new(dictionary! {}, to_unicode_cmap(&chars)));
+        self.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => Object::Name(name.as_bytes().to_vec()),
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![descendant_id.into()],
+            "ToUnicode" => to_unicode_id,
+        })
+    }
+
+    /// Make the /Font resources of `holder`, a page, page tree node or Form XObject, refer to
+    /// `new` wherever they refer to `old`.
+    fn replace_font_resource(&mut self, holder: ObjectId, old: ObjectId, new: ObjectId) -> Result<()> {
+        let holder_dict = match self.get_object(holder)? {
+            Object::Stream(stream) => &stream.dict,
+            object => object.as_dict()?,
+        };
+        let resources = holder_dict.get(b"Resources")?;
+        let resources_id = resources.as_reference().ok();
+        let fonts = match resources_id {
+            Some(id) => self.get_dictionary(id)?.get(b"Font")?,
+            None => resources.as_dict()?.get(b"Font")?,
+        };
+        let fonts = match (fonts.as_reference().ok(), resources_id) {
+            (Some(fonts_id), _) => self.get_dictionary_mut(fonts_id)?,
+            (None, Some(resources_id)) => self.get_dictionary_mut(resources_id)?.get_mut(b"Font")?.as_dict_mut()?,
+            (None, None) => {
+                let holder = match self.get_object_mut(holder)? {
+                    Object::Stream(stream) => &mut stream.dict,
+                    object => object.as_dict_mut()?,
+                };
+                holder
+                    .get_mut(b"Resources")?
+                    .as_dict_mut()?
+                    .get_mut(b"Font")?
+                    .as_dict_mut()?
+            }
+        };
+        for (_, font) in fonts.iter_mut() {
+            if font.as_reference().ok() == Some(old) {
+                *font = new.into();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A ToUnicode CMap mapping 2-byte glyph ids to their characters.
+fn to_unicode_cmap(chars: &BTreeMap<u16, char>) -> Vec<u8> {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<(&u16, &char)> = chars.iter().collect();
+    // A bfchar section has at most 100 entries.
+    for section in entries.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", section.len()));
+        for (glyph, ch) in section {
+            let unicode: String = ch
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|unit| format!("{unit:04X}"))
+                .collect();
+            cmap.push_str(&format!("<{glyph:04X}> <{unicode}>\n"));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content_walker::tests::{add_page_form, form};
+    use crate::creator::tests::create_document;
+    use crate::encodings::WIN_ANSI_ENCODING;
+    use crate::{FontData, UnsupportedCharPolicy};
+
+    fn montserrat() -> Vec<u8> {
+        std::fs::read("./tests/resources/fonts/Montserrat-Regular.ttf").unwrap()
+    }
+
+    /// A document whose page shows `content` with /F2, a WinAnsi subset of Montserrat in the
+    /// resources the page inherits.
+    fn subset_document(content: &str) -> (Document, ObjectId) {
+        let mut doc = create_document();
+        let program = montserrat();
+        let face = Face::parse(&program, 0).unwrap();
+        let font_id = doc
+            .add_font(FontData::new(&program, "ABCDEF+Montserrat-Regular".to_string()))
+            .unwrap();
+        let widths: Vec<Object> = (32..=255u8)
+            .map(|code| {
+                let glyph = WIN_ANSI_ENCODING[code as usize]
+                    .and_then(|unit| char::from_u32(u32::from(unit)))
+                    .and_then(|ch| face.glyph_index(ch));
+                let advance = glyph.and_then(|glyph| face.glyph_hor_advance(glyph)).unwrap_or(0);
+                Object::from(i64::from(advance) * 1000 / i64::from(face.units_per_em()))
+            })
+            .collect();
+        let font = doc.get_dictionary_mut(font_id).unwrap();
+        font.set("FirstChar", 32);
+        font.set("LastChar", 255);
+        font.set("Widths", widths);
+
+        let page_id = doc.page_iter().next().unwrap();
+        let resources_id = doc.get_page_resources(page_id).unwrap().1[0];
+        let resources = doc.get_dictionary_mut(resources_id).unwrap();
+        resources
+            .get_mut(b"Font")
+            .unwrap()
+            .as_dict_mut()
+            .unwrap()
+            .set("F2", font_id);
+        doc.change_page_content(page_id, content.as_bytes().to_vec()).unwrap();
+        (doc, font_id)
+    }
+
+    fn shown_strings(doc: &Document, page_id: ObjectId) -> Vec<Object> {
+        let content = doc.get_and_decode_page_content(page_id).unwrap();
+        content
+            .operations
+            .into_iter()
+            .filter(|op| op.operator == "Tj" || op.operator == "TJ")
+            .filter_map(|mut op| op.operands.pop())
+            .collect()
+    }
+
+    #[test]
+    fn upgraded_font_keeps_text_and_writes_more_characters() {
+        let (mut doc, font_id) = subset_document(
+            "BT /F1 24 Tf 72 760 Td (Courier) Tj ET\nBT /F2 14 Tf 72 700 Td (Caf\\351 cr\\350me) Tj ET\n",
+        );
+        let page_id = doc.page_iter().next().unwrap();
+        let resources = dictionary! { "Font" => dictionary! { "Fx" => font_id } };
+        let form_id = add_page_form(
+            &mut doc,
+            "Fm1",
+            form("BT /Fx 9 Tf [(For) 20 (m)] TJ ET", Some(resources)),
+        );
+        let result = doc.add_text_to_page(
+            page_id,
+            font_id,
+            14.0,
+            (72.0, 650.0),
+            "Łódź",
+            UnsupportedCharPolicy::Error,
+        );
+        assert!(matches!(result, Err(Error::MissingGlyphs { chars, .. }) if chars == ['Ł', 'ź']));
+        let before = doc.extract_text(&[1]).unwrap();
+        assert!(before.contains("Café crème") && before.contains("Form"), "{before:?}");
+
+        let type0_id = doc.upgrade_font_to_type0(font_id, &montserrat()).unwrap();
+        assert_eq!(doc.extract_text(&[1]).unwrap(), before);
+        let fonts = doc.get_page_fonts(page_id).unwrap();
+        assert_eq!(
+            fonts[b"F2".as_slice()].get(b"Subtype").unwrap().as_name().unwrap(),
+            b"Type0"
+        );
+        assert_eq!(
+            fonts[b"F2".as_slice()].get(b"BaseFont").unwrap().as_name().unwrap(),
+            b"Montserrat-Regular"
+        );
+        assert_eq!(
+            fonts[b"F1".as_slice()].get(b"BaseFont").unwrap().as_name().unwrap(),
+            b"Courier"
+        );
+        let form = doc.get_object(form_id).and_then(Object::as_stream).unwrap();
+        let form_fonts = form.dict.get(b"Resources").and_then(Object::as_dict).unwrap();
+        assert_eq!(
+            form_fonts
+                .get(b"Font")
+                .and_then(Object::as_dict)
+                .unwrap()
+                .get(b"Fx")
+                .unwrap(),
+            &type0_id.into()
+        );
+
+        let shown = shown_strings(&doc, page_id);
+        assert_eq!(shown[0].as_str().unwrap(), b"Courier");
+        let cafe = shown[1].as_str().unwrap();
+        assert_eq!(cafe.len(), 2 * "Café crème".chars().count());
+        // The glyphs keep the widths of the subset.
+        let widths = crate::shaping::cid_widths(doc.get_dictionary(type0_id).unwrap(), &doc).unwrap();
+        let subset_widths = doc.font_widths(doc.get_dictionary(font_id).unwrap()).unwrap();
+        assert_eq!(
+            widths.width(u16::from_be_bytes([cafe[0], cafe[1]])),
+            subset_widths.width(b'C')
+        );
+
+        doc.add_text_to_page(
+            page_id,
+            type0_id,
+            14.0,
+            (72.0, 650.0),
+            "Łódź ő",
+            UnsupportedCharPolicy::Error,
+        )
+        .unwrap();
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        let doc = Document::load_mem(&bytes).unwrap();
+        let text = doc.extract_text(&[1]).unwrap();
+        assert_eq!(text, format!("{before}Łódź ő\n"));
+    }
+
+    #[test]
+    fn word_spacing_becomes_adjustments() {
+        let content = "BT /F2 10 Tf q 2 Tw (a b) Tj Q (c d) Tj 1 0 (e f) \" ET";
+        let (mut doc, font_id) = subset_document(content);
+        let page_id = doc.page_iter().next().unwrap();
+        doc.upgrade_font_to_type0(font_id, &montserrat()).unwrap();
+
+        let content = doc.get_and_decode_page_content(page_id).unwrap();
+        let operators: Vec<&str> = content.operations.iter().map(|op| op.operator.as_str()).collect();
+        assert_eq!(
+            operators,
+            ["BT", "Tf", "q", "Tw", "TJ", "Q", "Tj", "Tw", "Tc", "T*", "TJ", "ET"]
+        );
+        let shown = shown_strings(&doc, page_id);
+        let adjustments = |shown: &Object| -> Vec<f32> {
+            shown
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|item| item.as_float().ok())
+                .collect()
+        };
+        assert_eq!(adjustments(&shown[0]), [-200.0]);
+        assert_eq!(shown[1].as_str().unwrap().len(), 6);
+        assert_eq!(adjustments(&shown[2]), [-100.0]);
+    }
+
+    #[test]
+    fn only_simple_fonts_with_parsable_programs_are_upgraded() {
+        let (mut doc, font_id) = subset_document("BT /F2 10 Tf (abc) Tj ET");
+        let result = doc.upgrade_font_to_type0(font_id, b"not a font");
+        assert!(matches!(result, Err(Error::FontUpgrade(_))));
+        let type0_id = doc.upgrade_font_to_type0(font_id, &montserrat()).unwrap();
+        let result = doc.upgrade_font_to_type0(type0_id, &montserrat());
+        assert!(matches!(result, Err(Error::FontUpgrade(_))));
+    }
+}
@@ -0,0 +1,364 @@
+use log::warn;
+use ttf_parser::{Face, GlyphId, PlatformId, cff};
+
+use crate::font_program::FontProgram;
+use crate::standard_fonts::{is_symbolic_standard_font, standard_font_name, standard_font_width};
+use crate::{Dictionary, Document, Error, Object, Result};
+
+/// Width used for character codes nothing gives a width for, in thousandths of text space units.
+///
+/// The specification says 0, which stacks the glyphs on top of each other, while the average
+/// glyph of a Latin font is about half an em wide.
+pub const FALLBACK_WIDTH: f32 = 500.0;
+
+/// Where the width of a character code of a font comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidthSource {
+    /// The /Widths array of the font
+    Widths,
+    /// The metrics of the embedded font program
+    FontProgram,
+    /// The metrics of the standard 14 font /BaseFont names or is an alias of
+    StandardFont,
+    /// The /MissingWidth of the font descriptor
+    MissingWidth,
+    /// Nothing gives a width, so [`FALLBACK_WIDTH`] is used
+    Fallback,
+}
+
+/// Glyph widths of a simple font by character code, in thousandths of text space units.
+///
+/// Codes missing from /Widths, because the font has none or because the array is shorter than
+/// /FirstChar to /LastChar, get their width from the embedded font program, then from the
+/// standard 14 font the /BaseFont matches, then from /MissingWidth, and finally
+/// [`FALLBACK_WIDTH`].
+#[derive(Debug, Clone)]
+pub struct FontWidths {
+    widths: Vec<(f32, WidthSource)>,
+    /// Number of codes from /FirstChar to /LastChar using [`FALLBACK_WIDTH`]
+    pub fallbacks: usize,
+}
+
+impl FontWidths {
+    pub fn width(&self, code: u8) -> f32 {
+        self.widths[code as usize].0
+    }
+
+    pub fn source(&self, code: u8) -> WidthSource {
+        self.widths[code as usize].1
+    }
+
+    /// Get the width of the text shown by a string, in text space units, without character or
+    /// word spacing and horizontal scaling.
+    pub fn text_width(&self, bytes: &[u8], font_size: f32) -> f32 {
+        bytes.iter().map(|&code| self.width(code)).sum::<f32>() * font_size / 1000.0
+    }
+}
+
+impl Document {
+    /// Get the glyph widths of a simple font, synthesizing the widths the font doesn't declare.
+    pub fn font_widths(&self, font: &Dictionary) -> Result<FontWidths> {
+        if !font.has_type(b"Font") {
+            return Err(Error::DictType {
+                expected: "Font",
+                found: String::from_utf8_lossy(font.get_type().unwrap_or(b"None")).to_string(),
+            });
+        }
+        if font.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0".as_slice()) {
+            return Err(Error::Unimplemented("widths of composite fonts"));
+        }
+
+        let first_char = font.get(b"FirstChar").and_then(Object::as_i64).ok();
+        let last_char = font.get(b"LastChar").and_then(Object::as_i64).ok();
+        // Both the array and each of its widths may be indirect.
+        let declared = self.get_with_aliases(font, b"Widths").and_then(|widths| self.resolve_deep(widths, 2));
+        let declared = declared.as_ref().ok().and_then(|widths| widths.as_array().ok());
+        let missing_width = font
+            .get_deref(b"FontDescriptor", self)
+            .and_then(Object::as_dict)
+            .and_then(|descriptor| descriptor.get_deref(b"MissingWidth", self))
+            .and_then(Object::as_float)
+            .ok();
+
+        let mut synthesized = None;
+        let mut widths = Vec::with_capacity(256);
+        let mut fallbacks = 0;
+        for code in 0..=255u8 {
+            let in_range = first_char.is_none_or(|first| i64::from(code) >= first)
+                && last_char.is_none_or(|last| i64::from(code) <= last);
+            let declared_width = declared.and_then(|declared| {
+                let index = usize::try_from(i64::from(code) - first_char.unwrap_or(0)).ok()?;
+                declared.get(index).filter(|_| in_range)?.as_float().ok()
+            });
+            let width = match declared_width {
+                Some(width) => (width, WidthSource::Widths),
+                // Codes outside the declared range aren't meant to be used.
+                None if declared.is_some() && !in_range => (missing_width.unwrap_or(0.0), WidthSource::MissingWidth),
+                None => {
+                    let synthesized = synthesized.get_or_insert_with(|| SynthesizedWidths::new(font, self));
+                    match (synthesized.width(code), missing_width) {
+                        ((_, WidthSource::Fallback), Some(missing_width)) => (missing_width, WidthSource::MissingWidth),
+                        (width, _) => {
+                            if width.1 == WidthSource::Fallback && first_char.is_some() && in_range {
+                                fallbacks += 1;
+                            }
+                            width
+                        }
+                    }
+                }
+            };
+            widths.push(width);
+        }
+        if fallbacks > 0 {
+            warn!("{fallbacks} character codes of a font have no width, using {FALLBACK_WIDTH}");
+        }
+        Ok(FontWidths { widths, fallbacks })
+    }
+}
+
+/// Widths of a font that don't come from its /Widths.
+struct SynthesizedWidths {
+    /// The single UTF-16 code unit the font encoding maps each code to
+    unicode: Vec<Option<u16>>,
+    program_widths: Option<Vec<Option<f32>>>,
+    standard_font: Option<&'static str>,
+}
+
+impl SynthesizedWidths {
+    fn new(font: &Dictionary, doc: &Document) -> Self {
+        let encoding = font.get_font_encoding(doc).ok();
+        let unicode: Vec<Option<u16>> = (0..=255u8)
+            .map(|code| {
+                let text = encoding.as_ref()?.decode(&[code]).ok()?.text;
+                let mut units = text.encode_utf16();
+                units.next().filter(|_| units.next().is_none())
+            })
+            .collect();
+        let program_widths = FontProgram::load(font, doc).and_then(|program| program_widths(&program, &unicode));
+        let base_font = font.get(b"BaseFont").and_then(Object::as_name).unwrap_or_default();
+        SynthesizedWidths {
+            unicode,
+            program_widths,
+            standard_font: standard_font_name(base_font),
+        }
+    }
+
+    fn width(&self, code: u8) -> (f32, WidthSource) {
+        let program_width = self.program_widths.as_ref().and_then(|widths| widths[code as usize]);
+        if let Some(width) = program_width {
+            return (width, WidthSource::FontProgram);
+        }
+        if let Some(font) = self.standard_font {
+            let key = if is_symbolic_standard_font(font) {
+                Some(u16::from(code))
+            } else {
+                self.unicode[code as usize]
+            };
+            if let Some(width) = key.and_then(|key| standard_font_width(font, key)) {
+                return (f32::from(width), WidthSource::StandardFont);
+            }
+        }
+        (FALLBACK_WIDTH, WidthSource::Fallback)
+    }
+}
+
+/// Get the advance widths of the glyphs the codes select in a font program.
+fn program_widths(program: &FontProgram, unicode: &[Option<u16>]) -> Option<Vec<Option<f32>>> {
+    let widths = match program {
+        FontProgram::OpenType(data) => {
+            let face = Face::parse(data, 0).ok()?;
+            let scale = 1000.0 / f32::from(face.units_per_em());
+            (0..=255u8)
+                .map(|code| {
+                    let glyph = truetype_glyph(&face, code, unicode[code as usize])?;
+                    Some(f32::from(face.glyph_hor_advance(glyph)?) * scale)
+                })
+                .collect()
+        }
+        FontProgram::Cff(data) => {
+            let table = cff::Table::parse(data)?;
+            let scale = table.matrix().sx * 1000.0;
+            (0..=255u8)
+                .map(|code| Some(f32::from(table.glyph_width(table.glyph_index(code)?)?) * scale))
+                .collect()
+        }
+    };
+    Some(widths)
+}
+
+/// Select the glyph of a code in a TrueType font as PDF readers do: through a Unicode cmap by
+/// the character the code encodes, or by the code in a symbol or Macintosh cmap.
+fn truetype_glyph(face: &Face, code: u8, unicode: Option<u16>) -> Option<GlyphId> {
+    let cmap = face.tables().cmap?;
+    let code = u32::from(code);
+    cmap.subtables.into_iter().find_map(|subtable| {
+        let glyph = match (subtable.platform_id, subtable.encoding_id) {
+            _ if subtable.is_unicode() => subtable.glyph_index(u32::from(unicode?)),
+            (PlatformId::Windows, 0) => subtable
+                .glyph_index(0xF000 | code)
+                .or_else(|| subtable.glyph_index(code)),
+            (PlatformId::Macintosh, 0) => subtable.glyph_index(code),
+            _ => None,
+        };
+        glyph.filter(|glyph| glyph.0 != 0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Width of "Hello, World" in Helvetica, summed from its AFM widths.
+    const HELLO_WORLD_WIDTH: f32 = 5445.0;
+
+    fn simple_font(entries: Dictionary) -> Dictionary {
+        let mut font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "TrueType",
+        };
+        font.extend(&entries);
+        font
+    }
+
+    #[test]
+    fn widths_of_aliased_standard_font() {
+        let doc = Document::new();
+        let font = simple_font(dictionary! {
+            "BaseFont" => "ABCDEF+Arial,Bold",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let widths = doc.font_widths(&font).unwrap();
+        // Helvetica-Bold widths
+        assert_eq!(widths.width(b'H'), 722.0);
+        assert_eq!(widths.width(b'i'), 278.0);
+        assert_eq!(widths.source(b'i'), WidthSource::StandardFont);
+
+        let font = simple_font(dictionary! {
+            "BaseFont" => "Arial",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let widths = doc.font_widths(&font).unwrap();
+        let measured = widths.text_width(b"Hello, World", 12.0);
+        let expected = HELLO_WORLD_WIDTH * 12.0 / 1000.0;
+        assert!((measured - expected).abs() / expected < 0.02, "{measured} != {expected}");
+        assert_eq!(widths.fallbacks, 0);
+    }
+
+    #[test]
+    fn short_widths_are_padded() {
+        let mut doc = Document::new();
+        let descriptor_id = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "MissingWidth" => 250,
+        });
+        let widths = vec![Object::Integer(700), Object::Integer(600)];
+        let helvetica = simple_font(dictionary! {
+            "BaseFont" => "Helvetica",
+            "FirstChar" => 72,
+            "LastChar" => 111,
+            "Widths" => widths.clone(),
+        });
+        let helvetica = doc.font_widths(&helvetica).unwrap();
+        assert_eq!(helvetica.width(b'H'), 700.0);
+        assert_eq!(helvetica.source(b'I'), WidthSource::Widths);
+        assert_eq!(helvetica.width(b'o'), 556.0);
+        assert_eq!(helvetica.source(b'o'), WidthSource::StandardFont);
+        assert_eq!(helvetica.width(b'p'), 0.0);
+        assert_eq!(helvetica.source(b'p'), WidthSource::MissingWidth);
+
+        let unknown = simple_font(dictionary! {
+            "BaseFont" => "Unknown",
+            "FirstChar" => 72,
+            "LastChar" => 111,
+            "Widths" => widths.clone(),
+            "FontDescriptor" => descriptor_id,
+        });
+        let unknown = doc.font_widths(&unknown).unwrap();
+        assert_eq!(unknown.width(b'o'), 250.0);
+        assert_eq!(unknown.source(b'o'), WidthSource::MissingWidth);
+        assert_eq!(unknown.fallbacks, 0);
+
+        let unknown = simple_font(dictionary! {
+            "BaseFont" => "Unknown",
+            "FirstChar" => 72,
+            "LastChar" => 111,
+            "Widths" => widths,
+        });
+        let unknown = doc.font_widths(&unknown).unwrap();
+        assert_eq!(unknown.width(b'o'), FALLBACK_WIDTH);
+        assert_eq!(unknown.source(b'o'), WidthSource::Fallback);
+        assert_eq!(unknown.fallbacks, 38);
+    }
+
+    #[test]
+    fn misspelled_widths_key() {
+        let mut doc = Document::new();
+        let font = simple_font(dictionary! {
+            "BaseFont" => "Unknown",
+            "FirstChar" => 65,
+            "LastChar" => 66,
+            "Width" => vec![Object::Integer(640), Object::Integer(580)],
+        });
+        let widths = doc.font_widths(&font).unwrap();
+        assert_eq!(widths.width(b'B'), 580.0);
+        assert_eq!(widths.source(b'B'), WidthSource::Widths);
+
+        doc.key_aliases = crate::KeyAliases::none();
+        assert_eq!(doc.font_widths(&font).unwrap().source(b'B'), WidthSource::Fallback);
+    }
+
+    #[test]
+    fn indirect_widths() {
+        let mut doc = crate::creator::tests::create_document();
+        let widths: Vec<Object> = [640, 580, 700].into_iter().map(|width| doc.add_object(width).into()).collect();
+        let widths_id = doc.add_object(widths);
+        let font_id = doc.add_object(simple_font(dictionary! {
+            "BaseFont" => "Unknown",
+            "FirstChar" => 65,
+            "LastChar" => 67,
+            "Widths" => widths_id,
+        }));
+        let mut buffer = Vec::new();
+        doc.save_to(&mut buffer).unwrap();
+
+        let doc = Document::load_mem(&buffer).unwrap();
+        let widths = doc.font_widths(doc.get_dictionary(font_id).unwrap()).unwrap();
+        assert_eq!(widths.width(b'B'), 580.0);
+        assert_eq!(widths.source(b'C'), WidthSource::Widths);
+        assert_eq!(widths.text_width(b"ABC", 10.0), 19.2);
+        assert_eq!(widths.fallbacks, 0);
+    }
+
+    #[test]
+    fn widths_from_embedded_font_program() {
+        let font_file = std::fs::read("./tests/resources/fonts/Montserrat-Regular.ttf").unwrap();
+        let face = Face::parse(&font_file, 0).unwrap();
+        let expected = |ch| {
+            let advance = face.glyph_hor_advance(face.glyph_index(ch).unwrap()).unwrap();
+            f32::from(advance) * 1000.0 / f32::from(face.units_per_em())
+        };
+
+        let mut doc = Document::new();
+        let font_file_id = doc.add_object(crate::Stream::new(dictionary! {}, font_file.clone()));
+        let descriptor_id = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontFile2" => font_file_id,
+        });
+        let font = simple_font(dictionary! {
+            "BaseFont" => "Montserrat-Regular",
+            "Encoding" => "WinAnsiEncoding",
+            "FontDescriptor" => descriptor_id,
+        });
+        let widths = doc.font_widths(&font).unwrap();
+        assert_eq!(widths.source(b'W'), WidthSource::FontProgram);
+        assert_eq!(widths.width(b'W'), expected('W'));
+        assert_eq!(widths.width(0x80), expected('€'));
+    }
+
+    #[test]
+    fn composite_fonts_are_unsupported() {
+        let doc = Document::new();
+        let font = dictionary! { "Type" => "Font", "Subtype" => "Type0" };
+        assert!(matches!(doc.font_widths(&font), Err(Error::Unimplemented(_))));
+    }
+}
@@ -0,0 +1,990 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use log::warn;
+
+use crate::content::{Content, Operation};
+use crate::encodings::Encoding;
+use crate::{
+    Dictionary, Document, Error, FontWidths, Object, ObjectId, Result, Stream, decode_text_string, text_string,
+};
+
+/// Default appearance used when neither the field nor the AcroForm dictionary provides a /DA.
+const DEFAULT_APPEARANCE: &[u8] = b"/Helv 0 Tf 0 g";
+/// Font size used for auto-sized (size 0) text, unless the widget is too small for it.
+const AUTO_FONT_SIZE: f32 = 12.0;
+/// Padding between the widget border and its text.
+const PADDING: f32 = 2.0;
+
+struct FormField {
+    name: String,
+    id: ObjectId,
+    terminal: bool,
+}
+
+impl Document {
+    /// Get the fully qualified names of the terminal form fields with their object ids.
+    pub fn get_form_fields(&self) -> BTreeMap<String, ObjectId> {
+        self.form_fields()
+            .into_iter()
+            .filter(|field| field.terminal)
+            .map(|field| (field.name, field.id))
+            .collect()
+    }
+
+    /// Get the values of the terminal form fields that have one, keyed by fully qualified name.
+    ///
+    /// Text values are decoded; names (e.g. checkbox states) and numbers are converted to text.
+    pub fn get_form_field_values(&self) -> BTreeMap<String, String> {
+        self.get_form_fields()
+            .into_iter()
+            .filter_map(|(name, id)| {
+                let value = self.get_dictionary(id).ok()?.get_deref(b"V", self).ok()?;
+                let value = match value {
+                    Object::String(..) => decode_text_string(value).ok()?,
+                    Object::Name(name) => String::from_utf8_lossy(name).into_owned(),
+                    Object::Integer(number) => number.to_string(),
+                    Object::Real(number) => number.to_string(),
+                    _ => return None,
+                };
+                Some((name, value))
+            })
+            .collect()
+    }
+
+    /// Set the value of a text form field and regenerate the normal appearance of its widgets,
+    /// clipping text that doesn't fit. See [`Document::set_form_field_with_overflow`].
+    pub fn set_form_field(&mut self, name: &str, value: &str) -> Result<FitResult> {
+        self.set_form_field_with_overflow(name, value, OverflowPolicy::Clip)
+    }
+
+    /// Set the value of a text form field and regenerate the normal appearance of its widgets.
+    ///
+    /// The appearance is left-aligned text in the font and color of the field's default
+    /// appearance (/DA), using the resources of the AcroForm dictionary. The text of multiline
+    /// fields is wrapped at spaces, that of other fields kept on one line. Text that doesn't fit a
+    /// widget is laid out according to `policy`, and the returned [`FitResult`] tells how it fit
+    /// the first widget it didn't fit, if any.
+    ///
+    /// The text is shown in the encoding of the font, and characters the font can't encode are
+    /// shown as `?`. When the encoding of the font can't be determined, the text is shown as is.
+    pub fn set_form_field_with_overflow(
+        &mut self, name: &str, value: &str, policy: OverflowPolicy,
+    ) -> Result<FitResult> {
+        let field_id = *self
+            .get_form_fields()
+            .get(name)
+            .ok_or_else(|| Error::FieldNotFound(name.to_string()))?;
+
+        let default_appearance = self.field_default_appearance(field_id);
+        let resources = self
+            .catalog()
+            .and_then(|catalog| catalog.get_deref(b"AcroForm", self))
+            .and_then(Object::as_dict)
+            .and_then(|acro_form| acro_form.get(b"DR"))
+            .ok()
+            .cloned();
+        let appearance = TextAppearance::new(&default_appearance)?;
+        let (font, widths) = self.field_font(resources.as_ref(), appearance.font_name.as_deref())?;
+        let encoding = font
+            .get_font_encoding(self)
+            .inspect_err(|err| warn!("cannot determine the encoding of the font of form field {name}: {err}"))
+            .ok();
+        let shown = match &encoding {
+            Some(encoding) => encodable_text(encoding, value),
+            None => value.to_string(),
+        };
+        let ellipsis = char_advances(&widths, encoding.as_ref(), ELLIPSIS).iter().sum();
+        let advances = char_advances(&widths, encoding.as_ref(), &shown);
+        let multiline = self.field_flags(field_id) & MULTILINE != 0;
+
+        // Lay out all widgets before changing anything, as the policy may refuse the value.
+        let mut fit = FitResult::Fitted;
+        let mut appearances = Vec::new();
+        for widget_id in self.field_widgets(field_id) {
+            let Some(rect) = self
+                .get_dictionary(widget_id)
+                .ok()
+                .and_then(|widget| widget.get(b"Rect").ok())
+                .and_then(crate::sanitize::rectangle)
+            else {
+                continue;
+            };
+            let (width, height) = (rect[2] - rect[0], rect[3] - rect[1]);
+            let font_size = appearance.font_size(height);
+            let layout = TextLayout::new(&shown, &advances, ellipsis, font_size, (width, height), multiline)
+                .apply(policy)
+                .map_err(|fitted| Error::TextOverflow {
+                    field: name.to_string(),
+                    fitted,
+                })?;
+            if fit == FitResult::Fitted {
+                fit = layout.fit;
+            }
+            let content = appearance.content(&layout, encoding.as_ref(), width, height)?;
+            appearances.push((widget_id, width, height, content));
+        }
+
+        self.get_dictionary_mut(field_id)?.set("V", text_string(value));
+        for (widget_id, width, height, content) in appearances {
+            let mut dict = dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+            };
+            if let Some(resources) = &resources {
+                dict.set("Resources", resources.clone());
+            }
+            let appearance_id = self.add_object(Stream::new(dict, content));
+            // Appearance dictionaries that are indirect objects are updated in place.
+            let shared = match self.get_dictionary(widget_id)?.get(b"AP") {
+                Ok(Object::Reference(id)) => self.get_dictionary_mut(*id).ok(),
+                _ => None,
+            };
+            if let Some(appearances) = shared {
+                appearances.set("N", appearance_id);
+                continue;
+            }
+            let widget = self.get_dictionary_mut(widget_id)?;
+            match widget.get_mut(b"AP").and_then(Object::as_dict_mut) {
+                Ok(appearances) => appearances.set("N", appearance_id),
+                Err(_) => widget.set("AP", dictionary! { "N" => appearance_id }),
+            }
+        }
+        Ok(fit)
+    }
+
+    /// Get the fully qualified names of the fields in the order their values are calculated,
+    /// as listed in the AcroForm /CO array.
+    pub fn get_field_calculation_order(&self) -> Vec<String> {
+        let Ok(order) = self
+            .catalog()
+            .and_then(|catalog| catalog.get_deref(b"AcroForm", self))
+            .and_then(Object::as_dict)
+            .and_then(|acro_form| acro_form.get_deref(b"CO", self))
+            .and_then(Object::as_array)
+        else {
+            return Vec::new();
+        };
+        let names: BTreeMap<ObjectId, String> = self
+            .form_fields()
+            .into_iter()
+            .map(|field| (field.id, field.name))
+            .collect();
+        order
+            .iter()
+            .filter_map(|field| field.as_reference().ok())
+            .filter_map(|id| names.get(&id).cloned())
+            .collect()
+    }
+
+    /// Get the JavaScript source of the calculate action (/AA /C) of a form field.
+    pub fn get_field_calculation_script(&self, name: &str) -> Option<String> {
+        let field_id = *self.get_form_fields().get(name)?;
+        let action = self
+            .get_dictionary(field_id)
+            .and_then(|field| field.get_deref(b"AA", self))
+            .and_then(Object::as_dict)
+            .and_then(|actions| actions.get_deref(b"C", self))
+            .and_then(Object::as_dict)
+            .ok()?;
+        if self.get_with_aliases(action, b"S").and_then(Object::as_name).ok()? != b"JavaScript" {
+            return None;
+        }
+        match action.get_deref(b"JS", self).ok()? {
+            Object::Stream(stream) => {
+                let script = stream.get_plain_content().ok()?;
+                decode_text_string(&Object::string_literal(script)).ok()
+            }
+            script => decode_text_string(script).ok(),
+        }
+    }
+
+    /// Recalculate form fields in calculation order.
+    ///
+    /// For every field with a calculate action, `calculate` is called with the field name, its
+    /// script and the current field values, and returns the new value, if any. New values are
+    /// written with [`Document::set_form_field`] and are visible to the fields calculated after.
+    /// Returns the number of fields updated.
+    pub fn recalculate_fields<F>(&mut self, mut calculate: F) -> Result<usize>
+    where
+        F: FnMut(&str, &str, &BTreeMap<String, String>) -> Option<String>,
+    {
+        let mut values = self.get_form_field_values();
+        let mut updated = 0;
+        for name in self.get_field_calculation_order() {
+            let Some(script) = self.get_field_calculation_script(&name) else {
+                continue;
+            };
+            if let Some(value) = calculate(&name, &script, &values) {
+                self.set_form_field(&name, &value)?;
+                values.insert(name, value);
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Walk the AcroForm field tree, naming each field after its ancestors.
+    fn form_fields(&self) -> Vec<FormField> {
+        let Ok(fields) = self
+            .catalog()
+            .and_then(|catalog| catalog.get_deref(b"AcroForm", self))
+            .and_then(Object::as_dict)
+            .and_then(|acro_form| acro_form.get_deref(b"Fields", self))
+            .and_then(Object::as_array)
+        else {
+            return Vec::new();
+        };
+
+        let mut collected = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<(ObjectId, String)> = fields
+            .iter()
+            .rev()
+            .filter_map(|field| field.as_reference().ok())
+            .map(|id| (id, String::new()))
+            .collect();
+        while let Some((id, parent_name)) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let Ok(field) = self.get_dictionary(id) else {
+                continue;
+            };
+            let name = match field.get(b"T").map(decode_text_string) {
+                Ok(Ok(partial)) if parent_name.is_empty() => partial,
+                Ok(Ok(partial)) => format!("{parent_name}.{partial}"),
+                _ => continue,
+            };
+            let kid_fields: Vec<ObjectId> = field
+                .get(b"Kids")
+                .and_then(Object::as_array)
+                .map(|kids| {
+                    kids.iter()
+                        .filter_map(|kid| kid.as_reference().ok())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|kid| self.get_dictionary(*kid).is_ok_and(|kid| kid.has(b"T")))
+                .collect();
+            stack.extend(kid_fields.iter().rev().map(|kid| (*kid, name.clone())));
+            collected.push(FormField {
+                name,
+                id,
+                terminal: kid_fields.is_empty(),
+            });
+        }
+        collected
+    }
+
+    /// The widget annotations of a terminal field: its kids, or the field itself when field and
+    /// widget are merged.
+    fn field_widgets(&self, field_id: ObjectId) -> Vec<ObjectId> {
+        let Ok(field) = self.get_dictionary(field_id) else {
+            return Vec::new();
+        };
+        match field.get(b"Kids").and_then(Object::as_array) {
+            Ok(kids) => kids.iter().filter_map(|kid| kid.as_reference().ok()).collect(),
+            Err(_) => vec![field_id],
+        }
+    }
+
+    /// The /DA of a field, inherited from its ancestors or the AcroForm dictionary.
+    fn field_default_appearance(&self, field_id: ObjectId) -> Vec<u8> {
+        let mut visited = HashSet::new();
+        let mut current = Some(field_id);
+        while let Some(id) = current.filter(|id| visited.insert(*id)) {
+            let Ok(field) = self.get_dictionary(id) else {
+                break;
+            };
+            if let Ok(appearance) = field.get(b"DA").and_then(Object::as_str) {
+                return appearance.to_vec();
+            }
+            current = field.get(b"Parent").and_then(Object::as_reference).ok();
+        }
+        self.catalog()
+            .and_then(|catalog| catalog.get_deref(b"AcroForm", self))
+            .and_then(Object::as_dict)
+            .and_then(|acro_form| acro_form.get(b"DA"))
+            .and_then(Object::as_str)
+            .map_or_else(|_| DEFAULT_APPEARANCE.to_vec(), <[u8]>::to_vec)
+    }
+
+    /// The /Ff flags of a field, inherited from its ancestors.
+    fn field_flags(&self, field_id: ObjectId) -> i64 {
+        let mut visited = HashSet::new();
+        let mut current = Some(field_id);
+        while let Some(id) = current.filter(|id| visited.insert(*id)) {
+            let Ok(field) = self.get_dictionary(id) else {
+                break;
+            };
+            if let Ok(flags) = field.get(b"Ff").and_then(Object::as_i64) {
+                return flags;
+            }
+            current = field.get(b"Parent").and_then(Object::as_reference).ok();
+        }
+        0
+    }
+
+    /// The font `font_name` of `resources` with its widths, or Helvetica if it can't be found or
+    /// its widths can't be read.
+    fn field_font(&self, resources: Option<&Object>, font_name: Option<&[u8]>) -> Result<(Dictionary, FontWidths)> {
+        let font = resources
+            .zip(font_name)
+            .and_then(|(resources, font_name)| {
+                let resources = self.dereference(resources).and_then(|(_, resources)| resources.as_dict());
+                let fonts = resources.ok()?.get_deref(b"Font", self);
+                let font = fonts.ok()?.as_dict().ok()?.get_deref(font_name, self);
+                font.ok()?.as_dict().ok()
+            })
+            .and_then(|font| Some((font.clone(), self.font_widths(font).ok()?)));
+        match font {
+            Some(font) => Ok(font),
+            None => {
+                let helvetica = dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                    "Encoding" => "WinAnsiEncoding",
+                };
+                let widths = self.font_widths(&helvetica)?;
+                Ok((helvetica, widths))
+            }
+        }
+    }
+}
+
+/// How the text of a form field is laid out when it doesn't fit a widget.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OverflowPolicy {
+    /// Show all of the text, cut off at the edges of the widget
+    #[default]
+    Clip,
+    /// Lower the font size until the text fits, down to `min_size`, and clip it at that size
+    Shrink { min_size: f32 },
+    /// Fail with [`Error::TextOverflow`], leaving the field unchanged
+    Error,
+    /// Show the text that fits followed by `...`
+    Ellipsize,
+}
+
+/// How the text of a form field fit its widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitResult {
+    /// All of the text is shown at the size of the default appearance.
+    Fitted,
+    /// Only the characters before character index `at` are shown whole.
+    Truncated { at: usize },
+    /// All of the text is shown, at the lower font `size`.
+    Shrunk { size: f32 },
+}
+
+/// /Ff flag of text fields whose text can span several lines.
+const MULTILINE: i64 = 1 << 12;
+/// Distance between the baselines of a multiline field, relative to the font size.
+const LEADING: f32 = 1.2;
+/// Step by which [`OverflowPolicy::Shrink`] lowers the font size.
+const SHRINK_STEP: f32 = 0.5;
+/// Shown after the text cut off by [`OverflowPolicy::Ellipsize`].
+const ELLIPSIS: &str = "...";
+/// Shown in place of the characters the font of a field can't encode.
+const REPLACEMENT: char = '?';
+
+/// `text` with the characters `encoding` has no code for replaced by [`REPLACEMENT`].
+fn encodable_text(encoding: &Encoding, text: &str) -> String {
+    let mut bytes = [0; 4];
+    let mut replaced = 0;
+    let text = text
+        .chars()
+        .map(|ch| {
+            if Document::encode_text(encoding, ch.encode_utf8(&mut bytes)).is_empty() {
+                replaced += 1;
+                REPLACEMENT
+            } else {
+                ch
+            }
+        })
+        .collect();
+    if replaced > 0 {
+        warn!("the font of a form field can't encode {replaced} characters, showing {REPLACEMENT:?} instead");
+    }
+    text
+}
+
+/// The advance of each character of `text` encoded with `encoding`, or as UTF-8 when it is
+/// unknown, in thousandths of text space units.
+fn char_advances(widths: &FontWidths, encoding: Option<&Encoding>, text: &str) -> Vec<f32> {
+    let mut bytes = [0; 4];
+    text.chars()
+        .map(|ch| widths.text_width(&encoded_text(encoding, ch.encode_utf8(&mut bytes)), 1000.0))
+        .collect()
+}
+
+/// `text` encoded with `encoding`, or as UTF-8 when it is unknown.
+fn encoded_text(encoding: Option<&Encoding>, text: &str) -> Vec<u8> {
+    match encoding {
+        Some(encoding) => Document::encode_text(encoding, text),
+        None => text.as_bytes().to_vec(),
+    }
+}
+
+/// The default appearance (/DA) of a field, as operations setting its font and color.
+struct TextAppearance {
+    operations: Vec<Operation>,
+    font_name: Option<Vec<u8>>,
+    /// `None` for auto-sized text
+    font_size: Option<f32>,
+}
+
+impl TextAppearance {
+    fn new(default_appearance: &[u8]) -> Result<Self> {
+        let operations = Content::decode(default_appearance)
+            .or_else(|_| Content::decode(DEFAULT_APPEARANCE))?
+            .operations;
+        let font = operations.iter().find(|operation| operation.operator == "Tf");
+        let font_name = font
+            .and_then(|font| font.operands.first())
+            .and_then(|name| name.as_name().ok())
+            .map(<[u8]>::to_vec);
+        let font_size = font
+            .and_then(|font| font.operands.get(1))
+            .and_then(|size| size.as_float().ok())
+            .filter(|size| *size > 0.0);
+        Ok(TextAppearance {
+            operations,
+            font_name,
+            font_size,
+        })
+    }
+
+    /// The font size text starts out with in a widget `height` high.
+    fn font_size(&self, height: f32) -> f32 {
+        self.font_size
+            .unwrap_or_else(|| AUTO_FONT_SIZE.min((height - 2.0 * PADDING) * 0.8).max(1.0))
+    }
+
+    /// Content of the appearance of a widget showing `layout` in a font encoded with `encoding`,
+    /// or as UTF-8 when it is unknown.
+    fn content(&self, layout: &TextLayout, encoding: Option<&Encoding>, width: f32, height: f32) -> Result<Vec<u8>> {
+        let show = |line: &str| Operation::new("Tj", vec![Object::string_literal(encoded_text(encoding, line))]);
+        let mut operations = vec![
+            Operation::new("BMC", vec!["Tx".into()]),
+            Operation::new("q", vec![]),
+            Operation::new(
+                "re",
+                vec![1.into(), 1.into(), (width - 2.0).into(), (height - 2.0).into()],
+            ),
+            Operation::new("W", vec![]),
+            Operation::new("n", vec![]),
+            Operation::new("BT", vec![]),
+        ];
+        let font_size = layout.font_size;
+        operations.extend(self.operations.iter().cloned().map(|mut operation| {
+            if operation.operator == "Tf" {
+                if let Some(size) = operation.operands.get_mut(1) {
+                    *size = font_size.into();
+                }
+            }
+            operation
+        }));
+        if layout.multiline {
+            let top = height - PADDING - font_size;
+            operations.push(Operation::new("TL", vec![(font_size * LEADING).into()]));
+            operations.push(Operation::new("Td", vec![PADDING.into(), top.into()]));
+            for (index, line) in layout.lines.iter().enumerate() {
+                if index > 0 {
+                    operations.push(Operation::new("T*", vec![]));
+                }
+                operations.push(show(line));
+            }
+        } else {
+            let baseline = ((height - font_size) / 2.0 + font_size * 0.2).max(PADDING);
+            let line = layout.lines.first().map_or("", String::as_str);
+            operations.push(Operation::new("Td", vec![PADDING.into(), baseline.into()]));
+            operations.push(show(line));
+        }
+        operations.push(Operation::new("ET", vec![]));
+        operations.push(Operation::new("Q", vec![]));
+        operations.push(Operation::new("EMC", vec![]));
+        Content { operations }.encode()
+    }
+}
+
+/// The lines of text shown in a widget.
+struct TextLayout<'a> {
+    chars: Vec<char>,
+    /// Advance of each character in thousandths of text space units
+    advances: &'a [f32],
+    /// Advance of [`ELLIPSIS`]
+    ellipsis: f32,
+    /// Room for text inside the padding
+    room: (f32, f32),
+    multiline: bool,
+    font_size: f32,
+    lines: Vec<String>,
+    fit: FitResult,
+}
+
+impl<'a> TextLayout<'a> {
+    /// Lay out `value` at `font_size` in a widget of `size`, showing all of it.
+    fn new(value: &str, advances: &'a [f32], ellipsis: f32, font_size: f32, size: (f32, f32), multiline: bool) -> Self {
+        let mut layout = TextLayout {
+            chars: value.chars().collect(),
+            advances,
+            ellipsis,
+            room: ((size.0 - 2.0 * PADDING).max(0.0), (size.1 - 2.0 * PADDING).max(0.0)),
+            multiline,
+            font_size,
+            lines: Vec::new(),
+            fit: FitResult::Fitted,
+        };
+        let (lines, shown) = layout.break_lines(font_size);
+        layout.lines = lines.into_iter().map(|line| layout.text(line)).collect();
+        if let Some(at) = shown {
+            layout.fit = FitResult::Truncated { at };
+        }
+        layout
+    }
+
+    /// Change the layout according to `policy` if the text doesn't fit, returning the number of
+    /// characters that fit if the policy refuses it.
+    fn apply(mut self, policy: OverflowPolicy) -> std::result::Result<Self, usize> {
+        let FitResult::Truncated { at } = self.fit else {
+            return Ok(self);
+        };
+        match policy {
+            OverflowPolicy::Clip => {}
+            OverflowPolicy::Error => return Err(at),
+            OverflowPolicy::Shrink { min_size } => {
+                let mut size = self.font_size;
+                while size > min_size {
+                    size = (size - SHRINK_STEP).max(min_size);
+                    let (lines, shown) = self.break_lines(size);
+                    if shown.is_none() || size == min_size {
+                        self.font_size = size;
+                        self.lines = lines.into_iter().map(|line| self.text(line)).collect();
+                        self.fit = shown.map_or(FitResult::Shrunk { size }, |at| FitResult::Truncated { at });
+                        break;
+                    }
+                }
+            }
+            OverflowPolicy::Ellipsize => {
+                // The last line shown ends with the ellipsis, cut short to make room for it.
+                let (lines, _) = self.break_lines(self.font_size);
+                let shown = lines.iter().take_while(|line| line.start < at).count();
+                let last = shown.checked_sub(1).map_or(0..0, |last| lines[last].clone());
+                let mut width = self.ellipsis;
+                let mut end = last.start;
+                while end < last.end && self.fits_width(width + self.advances[end]) {
+                    width += self.advances[end];
+                    end += 1;
+                }
+                self.lines.truncate(shown.saturating_sub(1));
+                let line = self.text(last.start..end);
+                self.lines.push(line.trim_end().to_string() + ELLIPSIS);
+                self.fit = FitResult::Truncated { at: end };
+            }
+        }
+        Ok(self)
+    }
+
+    /// Break the text into lines at `font_size`, and find the index of the first character not
+    /// shown whole, if any.
+    fn break_lines(&self, font_size: f32) -> (Vec<Range<usize>>, Option<usize>) {
+        let max_width = self.room.0 * 1000.0 / font_size;
+        if !self.multiline {
+            let mut width = 0.0;
+            let shown = self.advances.iter().take_while(|advance| {
+                width += **advance;
+                width <= max_width
+            });
+            let shown = shown.count();
+            let line = 0..self.chars.len();
+            return (vec![line], (shown < self.chars.len()).then_some(shown));
+        }
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        while start < self.chars.len() {
+            let (mut end, mut width, mut after_space) = (start, 0.0, None);
+            while end < self.chars.len() && self.chars[end] != '\n' {
+                if end > start && width + self.advances[end] > max_width {
+                    break;
+                }
+                width += self.advances[end];
+                end += 1;
+                if self.chars[end - 1] == ' ' {
+                    after_space = Some(end);
+                }
+            }
+            if end == self.chars.len() || self.chars[end] == '\n' {
+                lines.push(start..end);
+                start = end + 1;
+                continue;
+            }
+            // Wrap after the last space of the line, or within a word too long for a line.
+            let end = after_space.unwrap_or(end);
+            lines.push(start..end);
+            start = end;
+            while self.chars.get(start) == Some(&' ') {
+                start += 1;
+            }
+        }
+
+        let shown_lines = if self.room.1 < font_size {
+            0
+        } else {
+            ((self.room.1 - font_size) / (font_size * LEADING)) as usize + 1
+        };
+        let shown = lines.get(shown_lines).map(|line| line.start);
+        (lines, shown)
+    }
+
+    fn fits_width(&self, advance: f32) -> bool {
+        advance * self.font_size / 1000.0 <= self.room.0
+    }
+
+    fn text(&self, range: Range<usize>) -> String {
+        self.chars[range].iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+
+    /// Add text fields `a`, `b` and `total` to the first page, with `total` calculated as their sum.
+    fn create_order_form() -> Document {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let mut fields = vec![];
+        for (index, (name, value)) in [("a", "10"), ("b", "20"), ("total", "0")].into_iter().enumerate() {
+            let y = 700 - 30 * index as i64;
+            fields.push(doc.add_object(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Widget",
+                "FT" => "Tx",
+                "T" => Object::string_literal(name),
+                "V" => Object::string_literal(value),
+                "Rect" => vec![100.into(), y.into(), 200.into(), (y + 20).into()],
+                "P" => page_id,
+            }));
+        }
+        let script = r#"AFSimple_Calculate("SUM", new Array ("a", "b"));"#;
+        let action_id = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Stream::new(dictionary! {}, script.as_bytes().to_vec()),
+        });
+        doc.get_dictionary_mut(fields[2])
+            .unwrap()
+            .set("AA", dictionary! { "C" => action_id });
+        doc.get_dictionary_mut(page_id).unwrap().set(
+            "Annots",
+            fields.iter().copied().map(Object::Reference).collect::<Vec<_>>(),
+        );
+        doc.catalog_mut().unwrap().set(
+            "AcroForm",
+            dictionary! {
+                "Fields" => fields.iter().copied().map(Object::Reference).collect::<Vec<_>>(),
+                "CO" => vec![fields[2].into()],
+                "DA" => Object::string_literal("/Helv 0 Tf 0 g"),
+                "DR" => dictionary! { "Font" => dictionary! { "Helv" => dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                } } },
+            },
+        );
+        doc
+    }
+
+    #[test]
+    fn reads_calculation_order_and_script() {
+        let mut doc = create_order_form();
+        assert_eq!(doc.get_field_calculation_order(), ["total"]);
+        let script = doc.get_field_calculation_script("total").unwrap();
+        assert!(script.starts_with("AFSimple_Calculate(\"SUM\""));
+        assert_eq!(doc.get_field_calculation_script("a"), None);
+        assert!(matches!(
+            doc.set_form_field("missing", "1"),
+            Err(Error::FieldNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn calculation_script_with_misspelled_action_type() {
+        let mut doc = create_order_form();
+        let total_id = doc.get_form_fields()["total"];
+        let action_id = doc.get_dictionary(total_id).unwrap().get(b"AA").unwrap().as_dict().unwrap();
+        let action_id = action_id.get(b"C").and_then(Object::as_reference).unwrap();
+        let action = doc.get_dictionary_mut(action_id).unwrap();
+        let action_type = action.remove(b"S").unwrap();
+        action.set("Subtype", action_type);
+        assert!(doc.get_field_calculation_script("total").unwrap().starts_with("AFSimple_Calculate"));
+
+        doc.key_aliases = crate::KeyAliases::none();
+        assert_eq!(doc.get_field_calculation_script("total"), None);
+    }
+
+    #[test]
+    fn recalculate_sum_updates_value_and_appearance() {
+        let mut doc = create_order_form();
+        doc.set_form_field("a", "15").unwrap();
+
+        let updated = doc
+            .recalculate_fields(|_, script, values| {
+                if !script.contains("\"SUM\"") {
+                    return None;
+                }
+                let sum: f64 = ["a", "b"]
+                    .iter()
+                    .filter_map(|name| values.get(*name)?.parse::<f64>().ok())
+                    .sum();
+                Some(sum.to_string())
+            })
+            .unwrap();
+        assert_eq!(updated, 1);
+        assert_eq!(doc.get_form_field_values()["total"], "35");
+
+        let total_id = doc.get_form_fields()["total"];
+        let appearance_id = doc
+            .get_dictionary(total_id)
+            .and_then(|total| total.get(b"AP"))
+            .and_then(Object::as_dict)
+            .and_then(|ap| ap.get(b"N"))
+            .and_then(Object::as_reference)
+            .unwrap();
+        let appearance = doc.get_object(appearance_id).and_then(Object::as_stream).unwrap();
+        let operations = appearance.decode_content().unwrap().operations;
+        let shown: Vec<_> = operations.iter().filter(|op| op.operator == "Tj").collect();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].operands[0].as_str().unwrap(), b"35");
+        let font = operations.iter().find(|op| op.operator == "Tf").unwrap();
+        assert_eq!(font.operands[0].as_name().unwrap(), b"Helv");
+        assert!(font.operands[1].as_float().unwrap() > 0.0);
+        assert!(appearance.dict.get(b"Resources").is_ok());
+    }
+
+    /// The order form with field `a`, 100 by 20, in Helvetica at `font_size` (0 for auto) and with
+    /// the field `flags`.
+    fn create_small_field(font_size: u8, flags: i64) -> Document {
+        let mut doc = create_order_form();
+        let field_id = doc.get_form_fields()["a"];
+        let field = doc.get_dictionary_mut(field_id).unwrap();
+        field.set("DA", Object::string_literal(format!("/Helv {font_size} Tf 0 g")));
+        field.set("Ff", flags);
+        doc
+    }
+
+    fn appearance_operations(doc: &Document, name: &str) -> Vec<Operation> {
+        let field_id = doc.get_form_fields()[name];
+        let appearance_id = doc
+            .get_dictionary(field_id)
+            .and_then(|field| field.get(b"AP"))
+            .and_then(Object::as_dict)
+            .and_then(|ap| ap.get(b"N"))
+            .and_then(Object::as_reference)
+            .unwrap();
+        let appearance = doc.get_object(appearance_id).and_then(Object::as_stream).unwrap();
+        appearance.decode_content().unwrap().operations
+    }
+
+    fn shown_text(operations: &[Operation]) -> Vec<String> {
+        operations
+            .iter()
+            .filter(|op| op.operator == "Tj")
+            .map(|op| String::from_utf8(op.operands[0].as_str().unwrap().to_vec()).unwrap())
+            .collect()
+    }
+
+    fn font_size(operations: &[Operation]) -> f32 {
+        let font = operations.iter().find(|op| op.operator == "Tf").unwrap();
+        font.operands[1].as_float().unwrap()
+    }
+
+    const LONG_VALUE: &str = "The quick brown fox jumps over";
+
+    #[test]
+    fn overflow_is_clipped_by_default() {
+        let mut doc = create_small_field(12, 0);
+        let fit = doc.set_form_field("a", "1234").unwrap();
+        assert_eq!(fit, FitResult::Fitted);
+
+        let fit = doc.set_form_field("a", LONG_VALUE).unwrap();
+        let FitResult::Truncated { at } = fit else {
+            panic!("{fit:?}");
+        };
+        // Helvetica at 12 fits about 96 / 6.7 characters.
+        assert!((10..20).contains(&at), "{at}");
+        let operations = appearance_operations(&doc, "a");
+        assert_eq!(shown_text(&operations), [LONG_VALUE]);
+        assert_eq!(font_size(&operations), 12.0);
+        assert!(operations.iter().any(|op| op.operator == "W"));
+    }
+
+    #[test]
+    fn overflow_shrinks_text() {
+        let mut doc = create_small_field(12, 0);
+        let fit = doc
+            .set_form_field_with_overflow("a", LONG_VALUE, OverflowPolicy::Shrink { min_size: 4.0 })
+            .unwrap();
+        let FitResult::Shrunk { size } = fit else {
+            panic!("{fit:?}");
+        };
+        assert!(size > 4.0 && size < 12.0, "{size}");
+        let operations = appearance_operations(&doc, "a");
+        assert_eq!(font_size(&operations), size);
+        assert_eq!(shown_text(&operations), [LONG_VALUE]);
+
+        // Too long to fit at the smallest size allowed.
+        let fit = doc
+            .set_form_field_with_overflow("a", LONG_VALUE, OverflowPolicy::Shrink { min_size: 10.0 })
+            .unwrap();
+        assert!(matches!(fit, FitResult::Truncated { at } if at < LONG_VALUE.len()), "{fit:?}");
+        assert_eq!(font_size(&appearance_operations(&doc, "a")), 10.0);
+    }
+
+    #[test]
+    fn overflow_error_leaves_field_unchanged() {
+        let mut doc = create_small_field(12, 0);
+        let fitted = doc.set_form_field_with_overflow("a", "12", OverflowPolicy::Error).unwrap();
+        assert_eq!(fitted, FitResult::Fitted);
+        let before = shown_text(&appearance_operations(&doc, "a"));
+
+        let result = doc.set_form_field_with_overflow("a", LONG_VALUE, OverflowPolicy::Error);
+        assert!(
+            matches!(&result, Err(Error::TextOverflow { field, fitted }) if field == "a" && *fitted < LONG_VALUE.len()),
+            "{result:?}"
+        );
+        assert_eq!(doc.get_form_field_values()["a"], "12");
+        assert_eq!(shown_text(&appearance_operations(&doc, "a")), before);
+    }
+
+    #[test]
+    fn overflow_is_ellipsized() {
+        let mut doc = create_small_field(12, 0);
+        let clipped_at = match doc.set_form_field("a", LONG_VALUE).unwrap() {
+            FitResult::Truncated { at } => at,
+            fit => panic!("{fit:?}"),
+        };
+        let fit = doc
+            .set_form_field_with_overflow("a", LONG_VALUE, OverflowPolicy::Ellipsize)
+            .unwrap();
+        let FitResult::Truncated { at } = fit else {
+            panic!("{fit:?}");
+        };
+        assert!(at > 0 && at < clipped_at, "{at} {clipped_at}");
+        let shown = format!("{}...", LONG_VALUE[..at].trim_end());
+        assert_eq!(shown_text(&appearance_operations(&doc, "a")), [shown]);
+        assert_eq!(doc.get_form_field_values()["a"], LONG_VALUE);
+    }
+
+    #[test]
+    fn multiline_text_wraps_before_overflowing() {
+        let mut doc = create_small_field(6, MULTILINE);
+        // A line of 6 point text holds about 30 characters, and two lines fit inside the padding.
+        let fit = doc.set_form_field("a", LONG_VALUE).unwrap();
+        assert_eq!(fit, FitResult::Fitted);
+        let operations = appearance_operations(&doc, "a");
+        assert_eq!(shown_text(&operations), ["The quick brown fox jumps over"]);
+
+        let value = "The quick brown fox jumps over the lazy dog, and the quick brown fox jumps over the dog again";
+        let fit = doc.set_form_field("a", value).unwrap();
+        let FitResult::Truncated { at } = fit else {
+            panic!("{fit:?}");
+        };
+        let operations = appearance_operations(&doc, "a");
+        let lines = shown_text(&operations);
+        assert!(lines.len() > 2, "{lines:?}");
+        assert!(operations.iter().any(|op| op.operator == "T*"));
+        // The text wraps after spaces, and the third line is the first cut off.
+        assert!(lines[0].ends_with(' ') && lines[1].ends_with(' '), "{lines:?}");
+        assert_eq!(value[..at], lines[0].clone() + &lines[1]);
+        assert_eq!(lines.concat(), value);
+
+        let fit = doc
+            .set_form_field_with_overflow("a", value, OverflowPolicy::Ellipsize)
+            .unwrap();
+        assert!(matches!(fit, FitResult::Truncated { at: ellipsized } if ellipsized <= at), "{fit:?}");
+        let shown = shown_text(&appearance_operations(&doc, "a"));
+        assert_eq!(shown.len(), 2);
+        assert!(shown[1].ends_with("..."), "{shown:?}");
+
+        let fit = doc
+            .set_form_field_with_overflow("a", value, OverflowPolicy::Shrink { min_size: 3.0 })
+            .unwrap();
+        assert!(matches!(fit, FitResult::Shrunk { size } if size < 6.0), "{fit:?}");
+    }
+
+    #[test]
+    fn text_is_shown_in_the_font_encoding() {
+        let mut doc = create_small_field(12, 0);
+        let font = doc
+            .catalog_mut()
+            .and_then(|catalog| catalog.get_mut(b"AcroForm"))
+            .and_then(Object::as_dict_mut)
+            .and_then(|acro_form| acro_form.get_mut(b"DR"))
+            .and_then(Object::as_dict_mut)
+            .and_then(|resources| resources.get_mut(b"Font"))
+            .and_then(Object::as_dict_mut)
+            .and_then(|fonts| fonts.get_mut(b"Helv"))
+            .and_then(Object::as_dict_mut)
+            .unwrap();
+        font.set("Encoding", "WinAnsiEncoding");
+
+        let fit = doc.set_form_field("a", "Café über 5€ Ω").unwrap();
+        assert_eq!(fit, FitResult::Fitted);
+        let operations = appearance_operations(&doc, "a");
+        let shown: Vec<_> = operations.iter().filter(|op| op.operator == "Tj").collect();
+        assert_eq!(shown[0].operands[0].as_str().unwrap(), b"Caf\xe9 \xfcber 5\x80 ?");
+        assert_eq!(doc.get_form_field_values()["a"], "Café über 5€ Ω");
+
+        // Accented letters are measured by the width of their single code, as wide as without
+        // the accent in Helvetica.
+        let plain = doc.set_form_field("a", &"e".repeat(40)).unwrap();
+        let accented = doc.set_form_field("a", &"é".repeat(40)).unwrap();
+        assert!(matches!(plain, FitResult::Truncated { .. }), "{plain:?}");
+        assert_eq!(accented, plain);
+    }
+
+    #[test]
+    fn text_is_shown_as_is_when_the_font_encoding_is_unknown() {
+        let mut doc = create_small_field(12, 0);
+        let font = doc
+            .catalog_mut()
+            .and_then(|catalog| catalog.get_mut(b"AcroForm"))
+            .and_then(Object::as_dict_mut)
+            .and_then(|acro_form| acro_form.get_mut(b"DR"))
+            .and_then(Object::as_dict_mut)
+            .and_then(|resources| resources.get_mut(b"Font"))
+            .and_then(Object::as_dict_mut)
+            .and_then(|fonts| fonts.get_mut(b"Helv"))
+            .and_then(Object::as_dict_mut)
+            .unwrap();
+        font.set("Encoding", "Identity-H");
+
+        doc.set_form_field("a", "Café").unwrap();
+        assert_eq!(shown_text(&appearance_operations(&doc, "a")), ["Café"]);
+    }
+
+    #[test]
+    fn indirect_appearance_dictionaries_are_updated_in_place() {
+        let mut doc = create_small_field(12, 0);
+        let field_id = doc.get_form_fields()["a"];
+        let down = doc.add_object(Stream::new(dictionary! {}, vec![]));
+        let appearances_id = doc.add_object(dictionary! { "D" => down });
+        doc.get_dictionary_mut(field_id).unwrap().set("AP", appearances_id);
+
+        doc.set_form_field("a", "Hello").unwrap();
+        let field = doc.get_dictionary(field_id).unwrap();
+        assert_eq!(field.get(b"AP").and_then(Object::as_reference).unwrap(), appearances_id);
+        let appearances = doc.get_dictionary(appearances_id).unwrap();
+        assert_eq!(appearances.get(b"D").and_then(Object::as_reference).unwrap(), down);
+        let normal = appearances.get(b"N").and_then(Object::as_reference).unwrap();
+        let content = doc.get_object(normal).and_then(Object::as_stream).unwrap().decode_content().unwrap();
+        assert_eq!(shown_text(&content.operations), ["Hello"]);
+    }
+}
@@ -0,0 +1,436 @@
+use log::warn;
+
+use crate::content::{Content, Operation};
+use crate::encodings::{self, Encoding, ReverseMapping};
+use crate::font_widths::{FontWidths, WidthSource};
+use crate::resource_usage::ResourceCategory;
+use crate::standard_fonts::{is_subset_font_name, is_symbolic_standard_font, standard_font_name};
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result, StringFormat};
+
+/// Characters of a text that a font's encoding cannot represent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Byte offset in the text and the character at it
+    pub unsupported: Vec<(usize, char)>,
+}
+
+impl CoverageReport {
+    /// Whether every character of the text can be represented.
+    pub fn is_complete(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+}
+
+/// What to do with characters the font cannot represent when writing text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsupportedCharPolicy {
+    /// Fail with [`Error::UnsupportedCharacters`]
+    Error,
+    /// Leave the characters out
+    SkipChars,
+    /// Write this character instead
+    ReplaceWith(char),
+    /// Write the characters with this font, switching fonts mid-line
+    FallbackFont(ObjectId),
+}
+
+/// Whether text can be written with a font the document already has, without embedding one: a
+/// simple font that isn't a subset and either uses WinAnsiEncoding or is a standard 14 font with
+/// a Latin character set.
+pub fn is_reusable_font(font: &Dictionary) -> bool {
+    let simple = matches!(
+        font.get(b"Subtype").and_then(Object::as_name),
+        Ok(b"Type1" | b"TrueType" | b"MMType1")
+    );
+    let Ok(base_font) = font.get(b"BaseFont").and_then(Object::as_name) else {
+        return false;
+    };
+    let win_ansi = font.get(b"Encoding").and_then(Object::as_name).ok() == Some(b"WinAnsiEncoding".as_slice());
+    let standard = standard_font_name(base_font).is_some_and(|name| !is_symbolic_standard_font(name));
+    font.has_type(b"Font") && simple && !is_subset_font_name(base_font) && (win_ansi || standard)
+}
+
+/// The glyphs a subset font kept, going by the codes its /Widths gives a width.
+struct SubsetGlyphs {
+    name: String,
+    /// `None` for composite fonts, whose ToUnicode CMap only lists the kept glyphs anyway
+    widths: Option<FontWidths>,
+}
+
+impl SubsetGlyphs {
+    /// `None` if the font isn't a subset.
+    fn new(font: &Dictionary, doc: &Document) -> Option<Self> {
+        let base_font = font.get(b"BaseFont").and_then(Object::as_name).ok()?;
+        is_subset_font_name(base_font).then(|| SubsetGlyphs {
+            name: String::from_utf8_lossy(base_font).into_owned(),
+            widths: doc.font_widths(font).ok(),
+        })
+    }
+
+    fn has(&self, encoding: &Encoding, ch: char) -> bool {
+        let Some(widths) = &self.widths else {
+            return true;
+        };
+        match ReverseMapping::new(encoding).code(ch).as_deref() {
+            Some(&[code]) => widths.source(code) == WidthSource::Widths && widths.width(code) > 0.0,
+            _ => false,
+        }
+    }
+}
+
+impl Encoding<'_> {
+    /// Whether the encoding has a character code for `ch`.
+    pub fn supports_char(&self, ch: char) -> bool {
+        let mut units = [0; 2];
+        let units: &[u16] = ch.encode_utf16(&mut units);
+        match self {
+            Self::OneByteEncoding(map) => units.len() == 1 && map.contains(&Some(units[0])),
+            Self::SimpleEncoding(b"UniGB-UCS2-H") => units.len() == 1,
+            Self::SimpleEncoding(b"UniGB-UTF16-H") => true,
+            Self::SimpleEncoding(b"WinAnsiEncoding") => {
+                units.len() == 1 && encodings::WIN_ANSI_ENCODING.contains(&Some(units[0]))
+            }
+            Self::UnicodeMapEncoding(cmap) => cmap
+                .get_source_codes_for_unicode(units)
+                .is_some_and(|entries| !entries.is_empty()),
+            Self::DifferencesEncoding(encoding) => encoding.code(ch).is_some(),
+            // Text in unknown encodings is written as is, which only works out for ASCII.
+            Self::SimpleEncoding(_) => ch.is_ascii(),
+        }
+    }
+
+    /// List the characters of `text` the encoding cannot represent.
+    pub fn coverage(&self, text: &str) -> CoverageReport {
+        CoverageReport {
+            unsupported: text.char_indices().filter(|(_, ch)| !self.supports_char(*ch)).collect(),
+        }
+    }
+}
+
+impl Document {
+    /// List the characters of `text` that the font cannot represent, including those a subset
+    /// font has a code but no glyph for.
+    pub fn check_text_support(&self, font_id: ObjectId, text: &str) -> Result<CoverageReport> {
+        let font = self.get_dictionary(font_id)?;
+        let encoding = font.get_font_encoding(self)?;
+        let mut report = encoding.coverage(text);
+        if let Some(subset) = SubsetGlyphs::new(font, self) {
+            report.unsupported = text
+                .char_indices()
+                .filter(|(_, ch)| !encoding.supports_char(*ch) || !subset.has(&encoding, *ch))
+                .collect();
+        }
+        Ok(report)
+    }
+
+    /// Find a font the document already has, to write text without embedding one, such as one
+    /// [`is_reusable_font`] accepts.
+    ///
+    /// With a `page_id`, the fonts in effect for the page are searched, otherwise all fonts of the
+    /// document in object ID order. Only fonts that are indirect objects are found.
+    pub fn find_font<P>(&self, page_id: Option<ObjectId>, mut predicate: P) -> Result<Option<ObjectId>>
+    where
+        P: FnMut(&Dictionary) -> bool,
+    {
+        let candidates: Vec<ObjectId> = match page_id {
+            Some(page_id) => self
+                .page_resource_ids(page_id, ResourceCategory::Font)?
+                .into_iter()
+                .map(|(_, id)| id)
+                .collect(),
+            None => self.objects.keys().copied().collect(),
+        };
+        Ok(candidates.into_iter().find(|id| {
+            self.get_dictionary(*id)
+                .is_ok_and(|font| font.has_type(b"Font") && predicate(font))
+        }))
+    }
+
+    /// Write a line of text on a page at `position`, in user space units.
+    ///
+    /// Characters the font cannot represent are handled according to `policy`. Under
+    /// [`UnsupportedCharPolicy::Error`], a subset font lacking glyphs fails with
+    /// [`Error::MissingGlyphs`]. The fonts used are added to the page resources if the page
+    /// doesn't refer to them yet, otherwise the page's names for them are reused.
+    pub fn add_text_to_page(
+        &mut self, page_id: ObjectId, font_id: ObjectId, font_size: f32, position: (f32, f32), text: &str,
+        policy: UnsupportedCharPolicy,
+    ) -> Result<()> {
+        // Split the text into runs of (uses fallback font, encoded text).
+        let mut runs: Vec<(bool, String)> = Vec::new();
+        let mut unsupported = Vec::new();
+        let subset_name;
+        {
+            let font = self.get_dictionary(font_id)?;
+            let encoding = font.get_font_encoding(self)?;
+            let subset = SubsetGlyphs::new(font, self);
+            let fallback = match policy {
+                UnsupportedCharPolicy::FallbackFont(id) => Some(self.get_dictionary(id)?.get_font_encoding(self)?),
+                _ => None,
+            };
+            let mut push = |fallback: bool, ch: char| match runs.last_mut() {
+                Some((last, run)) if *last == fallback => run.push(ch),
+                _ => runs.push((fallback, ch.to_string())),
+            };
+            for ch in text.chars() {
+                if encoding.supports_char(ch) && subset.as_ref().is_none_or(|subset| subset.has(&encoding, ch)) {
+                    push(false, ch);
+                    continue;
+                }
+                match (policy, &fallback) {
+                    (UnsupportedCharPolicy::Error, _) => unsupported.push(ch),
+                    (UnsupportedCharPolicy::SkipChars, _) => {}
+                    (UnsupportedCharPolicy::ReplaceWith(replacement), _) => push(false, replacement),
+                    (UnsupportedCharPolicy::FallbackFont(_), Some(fallback)) if fallback.supports_char(ch) => {
+                        push(true, ch)
+                    }
+                    (UnsupportedCharPolicy::FallbackFont(_), _) => {
+                        warn!("neither the font nor its fallback can represent {ch:?}, skipping it")
+                    }
+                }
+            }
+            subset_name = subset.map(|subset| subset.name);
+        }
+        match subset_name {
+            Some(font) if !unsupported.is_empty() => return Err(Error::MissingGlyphs { font, chars: unsupported }),
+            _ if !unsupported.is_empty() => return Err(Error::UnsupportedCharacters(unsupported)),
+            _ => {}
+        }
+
+        let font_name = self.page_font_name(page_id, font_id)?;
+        let fallback_name = match policy {
+            UnsupportedCharPolicy::FallbackFont(id) if runs.iter().any(|(fallback, _)| *fallback) => {
+                Some(self.page_font_name(page_id, id)?)
+            }
+            _ => None,
+        };
+
+        let mut operations = vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec![Object::Name(font_name.clone()), font_size.into()]),
+            Operation::new("Td", vec![position.0.into(), position.1.into()]),
+        ];
+        let mut current_fallback = false;
+        for (fallback, run) in runs {
+            let (id, name) = match (&fallback_name, policy) {
+                (Some(name), UnsupportedCharPolicy::FallbackFont(id)) if fallback => (id, name),
+                _ => (font_id, &font_name),
+            };
+            if fallback != current_fallback {
+                operations.push(Operation::new("Tf", vec![Object::Name(name.clone()), font_size.into()]));
+                current_fallback = fallback;
+            }
+            let encoding = self.get_dictionary(id)?.get_font_encoding(self)?;
+            let bytes = encoding.string_to_bytes(&run);
+            operations.push(Operation::new("Tj", vec![Object::String(bytes, StringFormat::Literal)]));
+        }
+        operations.push(Operation::new("ET", vec![]));
+        self.add_to_page_content(page_id, Content { operations })
+    }
+
+    /// Name under which the page resources refer to a font, adding the font if needed.
+    pub(crate) fn page_font_name(&mut self, page_id: ObjectId, font_id: ObjectId) -> Result<Vec<u8>> {
+        if let Some(name) = self.find_resource(page_id, ResourceCategory::Font, font_id)? {
+            return Ok(name);
+        }
+        self.localize_page_resources(page_id)?;
+        let resources = self.get_or_create_resources(page_id)?.as_dict_mut()?;
+        if !resources.has(b"Font") {
+            resources.set("Font", crate::Dictionary::new());
+        }
+        let fonts_id = resources.get(b"Font").and_then(Object::as_reference).ok();
+        let fonts = match fonts_id {
+            Some(id) => self.get_dictionary_mut(id)?,
+            None => self.get_or_create_resources(page_id)?.as_dict_mut()?.get_mut(b"Font")?.as_dict_mut()?,
+        };
+
+        if let Some((name, _)) = fonts
+            .iter()
+            .find(|(_, font)| font.as_reference().is_ok_and(|id| id == font_id))
+        {
+            return Ok(name.clone());
+        }
+        let name = (1..)
+            .map(|n| format!("F{n}").into_bytes())
+            .find(|name| !fonts.has(name))
+            .expect("unbounded range yields an unused name");
+        fonts.set(name.clone(), font_id);
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+    use crate::Stream;
+
+    const EMOJI_CMAP: &[u8] = b"/CIDInit /ProcSet findresource begin
+12 dict begin
+begincmap
+/CMapName /Emoji def
+/CMapType 2 def
+1 begincodespacerange
+<0000> <FFFF>
+endcodespacerange
+1 beginbfchar
+<0001> <D83DDE00>
+endbfchar
+endcmap
+CMapName currentdict /CMap defineresource pop
+end
+end";
+
+    fn add_fonts(doc: &mut Document) -> (ObjectId, ObjectId) {
+        let helvetica = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let to_unicode = doc.add_object(Stream::new(dictionary! {}, EMOJI_CMAP.to_vec()));
+        let emoji = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "NotoEmoji",
+            "Encoding" => "Identity-H",
+            "ToUnicode" => to_unicode,
+        });
+        (helvetica, emoji)
+    }
+
+    #[test]
+    fn coverage_lists_unsupported_characters() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let (helvetica, emoji) = add_fonts(&mut doc);
+
+        let report = doc.check_text_support(helvetica, "Café 😀!").unwrap();
+        assert_eq!(report.unsupported, [(6, '😀')]);
+        assert!(doc.check_text_support(emoji, "😀").unwrap().is_complete());
+        assert_eq!(doc.check_text_support(emoji, "a").unwrap().unsupported, [(0, 'a')]);
+    }
+
+    #[test]
+    fn fallback_font_switches_around_unsupported_span() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let (helvetica, emoji) = add_fonts(&mut doc);
+        let page_id = doc.page_iter().next().unwrap();
+
+        let policy = UnsupportedCharPolicy::FallbackFont(emoji);
+        doc.add_text_to_page(page_id, helvetica, 12.0, (72.0, 700.0), "Hi 😀 there", policy)
+            .unwrap();
+
+        let fonts = doc.get_page_fonts(page_id).unwrap();
+        assert!(fonts.contains_key(b"F1".as_slice()), "inherited fonts stay available");
+        let content = doc.get_and_decode_page_content(page_id).unwrap();
+        let added = content.operations.iter().rposition(|op| op.operator == "BT").unwrap();
+        let shown: Vec<(&str, &[u8])> = content.operations[added..]
+            .iter()
+            .filter_map(|op| match op.operator.as_str() {
+                "Tf" => Some(("Tf", op.operands[0].as_name().unwrap())),
+                "Tj" => Some(("Tj", op.operands[0].as_str().unwrap())),
+                _ => None,
+            })
+            .collect();
+        let (helvetica_name, emoji_name) = (shown[0].1, shown[2].1);
+        assert_eq!(fonts[helvetica_name].get(b"BaseFont").unwrap().as_name().unwrap(), b"Helvetica");
+        assert_eq!(fonts[emoji_name].get(b"BaseFont").unwrap().as_name().unwrap(), b"NotoEmoji");
+        assert_eq!(
+            shown,
+            [
+                ("Tf", helvetica_name),
+                ("Tj", b"Hi ".as_slice()),
+                ("Tf", emoji_name),
+                ("Tj", &[0, 1]),
+                ("Tf", helvetica_name),
+                ("Tj", b" there"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_policies() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let (helvetica, _) = add_fonts(&mut doc);
+        let page_id = doc.page_iter().next().unwrap();
+
+        let result = doc.add_text_to_page(page_id, helvetica, 12.0, (0.0, 0.0), "a😀b", UnsupportedCharPolicy::Error);
+        assert!(matches!(result, Err(Error::UnsupportedCharacters(chars)) if chars == ['😀']));
+
+        let replace = UnsupportedCharPolicy::ReplaceWith('?');
+        doc.add_text_to_page(page_id, helvetica, 12.0, (0.0, 0.0), "a😀b", replace).unwrap();
+        doc.add_text_to_page(page_id, helvetica, 12.0, (0.0, 0.0), "c😀d", UnsupportedCharPolicy::SkipChars)
+            .unwrap();
+        let content = doc.get_and_decode_page_content(page_id).unwrap();
+        let shown: Vec<&[u8]> = content
+            .operations
+            .iter()
+            .filter(|op| op.operator == "Tj")
+            .map(|op| op.operands[0].as_str().unwrap())
+            .collect();
+        assert_eq!(shown[1..], [b"a?b".as_slice(), b"cd"]);
+    }
+
+    #[test]
+    fn bates_numbers_reuse_existing_font() {
+        let mut doc = Document::load_mem(&std::fs::read("assets/example.pdf").unwrap()).unwrap();
+        let count_fonts = |doc: &Document| {
+            doc.objects
+                .values()
+                .filter(|object| object.as_dict().is_ok_and(|dict| dict.has_type(b"Font")))
+                .count()
+        };
+        let fonts = count_fonts(&doc);
+        let page_ids: Vec<ObjectId> = doc.page_iter().collect();
+        let font_id = doc.find_font(None, is_reusable_font).unwrap().unwrap();
+        assert_eq!(doc.find_font(Some(page_ids[0]), is_reusable_font).unwrap(), Some(font_id));
+
+        for (number, page_id) in page_ids.iter().enumerate() {
+            let bates = format!("ABC{:06}", number + 1);
+            doc.add_text_to_page(*page_id, font_id, 10.0, (480.0, 20.0), &bates, UnsupportedCharPolicy::Error)
+                .unwrap();
+        }
+
+        assert_eq!(count_fonts(&doc), fonts);
+        // The font is found under the name the inherited resources give it, so the page keeps
+        // inheriting them.
+        assert!(!doc.get_dictionary(page_ids[0]).unwrap().has(b"Resources"));
+        let content = doc.get_and_decode_page_content(page_ids[0]).unwrap();
+        let font_names: Vec<&[u8]> = content
+            .operations
+            .iter()
+            .filter(|op| op.operator == "Tf")
+            .map(|op| op.operands[0].as_name().unwrap())
+            .collect();
+        assert_eq!(font_names, [b"F1", b"F1"]);
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "Hello World!\nABC000001\n");
+    }
+
+    #[test]
+    fn subset_font_missing_glyphs() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let page_id = doc.page_iter().next().unwrap();
+        // Digits only, without a glyph for 5.
+        let widths: Vec<Object> = (b'0'..=b'9').map(|digit| if digit == b'5' { 0 } else { 556 }.into()).collect();
+        let subset = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "TrueType",
+            "BaseFont" => "ABCDEF+Arial",
+            "Encoding" => "WinAnsiEncoding",
+            "FirstChar" => 48,
+            "LastChar" => 57,
+            "Widths" => widths,
+        });
+        assert!(!is_reusable_font(doc.get_dictionary(subset).unwrap()));
+
+        let report = doc.check_text_support(subset, "0-5").unwrap();
+        assert_eq!(report.unsupported, [(1, '-'), (2, '5')]);
+        doc.add_text_to_page(page_id, subset, 12.0, (0.0, 0.0), "0123", UnsupportedCharPolicy::Error)
+            .unwrap();
+        let result = doc.add_text_to_page(page_id, subset, 12.0, (0.0, 0.0), "0-5", UnsupportedCharPolicy::Error);
+        assert!(matches!(
+            result,
+            Err(Error::MissingGlyphs { font, chars }) if font == "ABCDEF+Arial" && chars == ['-', '5']
+        ));
+    }
+}
@@ -0,0 +1,427 @@
+use crate::xobject::PdfImage;
+use crate::{Document, Error, Object, Result, Stream};
+
+/// The pixels of an image XObject, decoded to 8-bit RGB.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ImagePixels {
+    /// RGB samples, row by row
+    pub rgb: Vec<u8>,
+    /// Opacity of each pixel, `None` for an image without a mask
+    pub alpha: Option<Vec<u8>>,
+}
+
+impl ImagePixels {
+    /// Interleave the RGB samples and the opacity of each pixel.
+    pub fn into_rgba8(self) -> Vec<u8> {
+        let ImagePixels { rgb, alpha } = self;
+        let pixels = rgb.len() / 3;
+        let alpha = alpha.unwrap_or_else(|| vec![255; pixels]);
+        rgb.chunks_exact(3)
+            .zip(alpha)
+            .flat_map(|(color, alpha)| [color[0], color[1], color[2], alpha])
+            .collect()
+    }
+}
+
+impl PdfImage<'_> {
+    /// Decode the image to 8-bit RGBA samples, row by row. /Decode arrays remap the samples, and
+    /// color key masks, stencil masks and image masks set the alpha of the pixels they hide to 0.
+    ///
+    /// Only gray, RGB, CMYK, ICC based and indexed color spaces are supported, and filters
+    /// lopdf can decode, so not JPEG or CCITT encoded images.
+    pub fn to_rgba8(&self, doc: &Document) -> Result<Vec<u8>> {
+        let stream = doc.get_object(self.id).and_then(Object::as_stream)?;
+        Ok(doc.image_pixels(stream)?.into_rgba8())
+    }
+}
+
+/// How the samples of a component map to its color value.
+struct SampleLayout {
+    bits_per_component: u32,
+    components: usize,
+    width: usize,
+    height: usize,
+}
+
+impl SampleLayout {
+    fn max_sample(&self) -> f32 {
+        ((1u32 << self.bits_per_component) - 1) as f32
+    }
+
+    /// Read the samples of each component of each pixel, rows starting on a byte boundary.
+    fn unpack(&self, data: &[u8]) -> Result<Vec<u16>> {
+        let bits = self.bits_per_component as usize;
+        let row_samples = self.width.checked_mul(self.components).ok_or_else(too_large)?;
+        let row_bytes = row_samples.checked_mul(bits).ok_or_else(too_large)?.div_ceil(8);
+        let data_len = row_bytes.checked_mul(self.height).ok_or_else(too_large)?;
+        if row_bytes == 0 || data.len() < data_len {
+            return Err(Error::InvalidStream("image data is shorter than its size".to_string()));
+        }
+        let mut samples = Vec::with_capacity(row_samples.checked_mul(self.height).ok_or_else(too_large)?);
+        for row in data.chunks_exact(row_bytes).take(self.height) {
+            for index in 0..row_samples {
+                let sample = match bits {
+                    16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+                    8 => u16::from(row[index]),
+                    _ => {
+                        let bit = index * bits;
+                        let shift = 8 - bits - bit % 8;
+                        u16::from((row[bit / 8] >> shift) & ((1 << bits) - 1) as u8)
+                    }
+                };
+                samples.push(sample);
+            }
+        }
+        Ok(samples)
+    }
+}
+
+impl Document {
+    /// Decode an image XObject to RGB, with the opacity of its pixels for an image mask or an
+    /// image with a /Mask.
+    pub(crate) fn image_pixels(&self, stream: &Stream) -> Result<ImagePixels> {
+        let dict = &stream.dict;
+        let dimension = |key: &[u8]| -> Result<usize> { Ok(usize::try_from(dict.get(key)?.as_i64()?)?) };
+        let (width, height) = (dimension(b"Width")?, dimension(b"Height")?);
+        if dict.get(b"ImageMask").and_then(Object::as_bool).unwrap_or(false) {
+            // Painted samples take the fill color, which isn't known here.
+            let alpha = self.stencil_alpha(stream, width, height)?;
+            let rgb_len = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(3));
+            return Ok(ImagePixels {
+                rgb: vec![0; rgb_len.ok_or_else(too_large)?],
+                alpha: Some(alpha),
+            });
+        }
+
+        let bits_per_component = dict.get(b"BitsPerComponent").and_then(Object::as_i64).unwrap_or(8);
+        if ![1, 2, 4, 8, 16].contains(&bits_per_component) {
+            return Err(Error::Unimplemented("image bits per component"));
+        }
+        let color_space = dict.get_deref(b"ColorSpace", self)?;
+        let palette = self.indexed_palette(color_space);
+        let components = match &palette {
+            Some(_) => 1,
+            None => self
+                .color_space_components(color_space)
+                .ok_or(Error::Unimplemented("image color space"))?,
+        };
+        let layout = SampleLayout {
+            bits_per_component: bits_per_component as u32,
+            components,
+            width,
+            height,
+        };
+        let samples = layout.unpack(&stream.get_plain_content()?)?;
+
+        let max_sample = layout.max_sample();
+        let default_decode = match palette {
+            Some(_) => [0.0, max_sample],
+            None => [0.0, 1.0],
+        };
+        let decode = decode_array(dict.get_deref(b"Decode", self).ok(), components, default_decode);
+        let decoded = samples.iter().enumerate().map(|(index, &sample)| {
+            let [min, max] = decode[index % components];
+            min + f32::from(sample) * (max - min) / max_sample
+        });
+        let rgb = match palette {
+            Some(palette) => decoded
+                .flat_map(|index| {
+                    let index = index.round().max(0.0) as usize;
+                    palette.get(index).copied().unwrap_or([0; 3])
+                })
+                .collect(),
+            None => decoded
+                .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+                .collect::<Vec<u8>>()
+                .chunks_exact(components)
+                .flat_map(to_rgb)
+                .collect(),
+        };
+
+        let alpha = match dict.get_deref(b"Mask", self) {
+            Ok(Object::Array(ranges)) => Some(color_key_alpha(ranges, &samples, components)),
+            Ok(Object::Stream(mask)) => Some(self.mask_alpha(mask, width, height)?),
+            _ => None,
+        };
+        Ok(ImagePixels { rgb, alpha })
+    }
+
+    /// Opacity of the pixels of an image masked by a stencil mask image, scaled to the size of
+    /// the image.
+    fn mask_alpha(&self, mask: &Stream, width: usize, height: usize) -> Result<Vec<u8>> {
+        let mask_width = usize::try_from(mask.dict.get(b"Width")?.as_i64()?)?;
+        let mask_height = usize::try_from(mask.dict.get(b"Height")?.as_i64()?)?;
+        let mask_alpha = self.stencil_alpha(mask, mask_width, mask_height)?;
+        let mut alpha = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let mask_y = y * mask_height / height;
+            for x in 0..width {
+                alpha.push(mask_alpha[mask_y * mask_width + x * mask_width / width]);
+            }
+        }
+        Ok(alpha)
+    }
+
+    /// Opacity of the pixels of a 1-bit stencil mask: samples decoded to 0 are painted, those
+    /// decoded to 1 are masked out.
+    fn stencil_alpha(&self, mask: &Stream, width: usize, height: usize) -> Result<Vec<u8>> {
+        let layout = SampleLayout {
+            bits_per_component: 1,
+            components: 1,
+            width,
+            height,
+        };
+        let samples = layout.unpack(&mask.get_plain_content()?)?;
+        let [painted, _] = decode_array(mask.dict.get_deref(b"Decode", self).ok(), 1, [0.0, 1.0])[0];
+        let painted = painted as u16;
+        Ok(samples
+            .into_iter()
+            .map(|sample| if sample == painted { 255 } else { 0 })
+            .collect())
+    }
+
+    /// Number of components of a device, calibrated or ICC based color space.
+    pub(crate) fn color_space_components(&self, color_space: &Object) -> Option<usize> {
+        let (family, params) = match color_space {
+            Object::Name(name) => (name.as_slice(), None),
+            Object::Array(array) => (array.first()?.as_name().ok()?, array.get(1)),
+            _ => return None,
+        };
+        match family {
+            b"DeviceGray" | b"CalGray" | b"G" => Some(1),
+            b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(3),
+            b"DeviceCMYK" | b"CMYK" => Some(4),
+            b"ICCBased" => {
+                let profile = self.dereference(params?).ok()?.1.as_stream().ok()?;
+                match profile.dict.get(b"N").and_then(Object::as_i64).ok()? {
+                    n @ (1 | 3 | 4) => Some(n as usize),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// RGB colors of an /Indexed color space, by index.
+    pub(crate) fn indexed_palette(&self, color_space: &Object) -> Option<Vec<[u8; 3]>> {
+        let array = color_space.as_array().ok()?;
+        if array.first()?.as_name().ok()? != b"Indexed" {
+            return None;
+        }
+        let base = self.dereference(array.get(1)?).ok()?.1;
+        let components = self.color_space_components(base)?;
+        let lookup = match self.dereference(array.get(3)?).ok()?.1 {
+            Object::String(bytes, _) => bytes.clone(),
+            Object::Stream(stream) => stream.get_plain_content().ok()?,
+            _ => return None,
+        };
+        Some(
+            lookup
+                .chunks_exact(components)
+                .map(|color| {
+                    let rgb = to_rgb(color);
+                    [rgb[0], rgb[1], rgb[2]]
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The error for an image whose number of samples or bytes doesn't fit a `usize`.
+fn too_large() -> Error {
+    Error::InvalidStream("image is too large".to_string())
+}
+
+/// The [min max] pairs of a /Decode array for each component, or `default` for a missing or
+/// malformed array.
+fn decode_array(decode: Option<&Object>, components: usize, default: [f32; 2]) -> Vec<[f32; 2]> {
+    let values: Vec<f32> = decode
+        .and_then(|decode| decode.as_array().ok())
+        .map(|array| array.iter().filter_map(|value| value.as_float().ok()).collect())
+        .unwrap_or_default();
+    if values.len() == components * 2 {
+        values.chunks_exact(2).map(|pair| [pair[0], pair[1]]).collect()
+    } else {
+        vec![default; components]
+    }
+}
+
+/// Opacity of the pixels of an image with a color key mask: pixels whose samples, before
+/// decoding, fall in the [min max] range of every component are masked out.
+fn color_key_alpha(ranges: &[Object], samples: &[u16], components: usize) -> Vec<u8> {
+    let ranges: Vec<i64> = ranges.iter().filter_map(|value| value.as_i64().ok()).collect();
+    samples
+        .chunks_exact(components)
+        .map(|pixel| {
+            let masked = ranges.len() == components * 2
+                && pixel
+                    .iter()
+                    .zip(ranges.chunks_exact(2))
+                    .all(|(&sample, range)| (range[0]..=range[1]).contains(&i64::from(sample)));
+            if masked { 0 } else { 255 }
+        })
+        .collect()
+}
+
+/// Convert one gray, RGB or CMYK sample to RGB.
+pub(crate) fn to_rgb(sample: &[u8]) -> Vec<u8> {
+    match *sample {
+        [gray] => vec![gray; 3],
+        [c, m, y, k] => [c, m, y]
+            .into_iter()
+            .map(|value| 255 - (u16::from(value) + u16::from(k)).min(255) as u8)
+            .collect(),
+        _ => sample.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dictionary;
+
+    fn image(dict: Dictionary, data: Vec<u8>) -> Stream {
+        let mut dict = dict;
+        dict.set("Type", "XObject");
+        dict.set("Subtype", "Image");
+        Stream::new(dict, data)
+    }
+
+    #[test]
+    fn inverted_scan_is_decoded_with_its_decode_array() {
+        let doc = Document::new();
+        // Rows of 10 pixels padded to 2 bytes, 1 bits being black after the [1 0] inversion, as in
+        // CCITT encoded scans.
+        let scan = image(
+            dictionary! {
+                "Width" => 10,
+                "Height" => 2,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 1,
+                "Decode" => vec![1.into(), 0.into()],
+            },
+            vec![0b1000_0000, 0b0100_0000, 0b0000_0000, 0b0011_1111],
+        );
+        let pixels = doc.image_pixels(&scan).unwrap();
+        assert_eq!(pixels.alpha, None);
+        let gray: Vec<u8> = pixels.rgb.chunks_exact(3).map(|rgb| rgb[0]).collect();
+        let mut expected = vec![255; 20];
+        expected[0] = 0;
+        expected[9] = 0;
+        assert_eq!(gray, expected);
+
+        let inverted_cmyk = image(
+            dictionary! {
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceCMYK",
+                "BitsPerComponent" => 8,
+                "Decode" => [1, 0, 1, 0, 1, 0, 1, 0].into_iter().map(Object::Integer).collect::<Vec<_>>(),
+            },
+            vec![255, 0, 255, 255],
+        );
+        assert_eq!(doc.image_pixels(&inverted_cmyk).unwrap().rgb, [255, 0, 255]);
+    }
+
+    #[test]
+    fn oversized_images_are_errors() {
+        let doc = Document::new();
+        let huge = 1i64 << 40;
+        let rgb = image(
+            dictionary! { "Width" => huge, "Height" => huge, "ColorSpace" => "DeviceRGB", "BitsPerComponent" => 8 },
+            vec![0; 3],
+        );
+        assert!(matches!(doc.image_pixels(&rgb), Err(Error::InvalidStream(_))));
+        let mask = image(dictionary! { "Width" => huge, "Height" => huge, "ImageMask" => true }, vec![0]);
+        assert!(matches!(doc.image_pixels(&mask), Err(Error::InvalidStream(_))));
+        let wide = image(
+            dictionary! { "Width" => i64::MAX, "Height" => 1, "ColorSpace" => "DeviceCMYK", "BitsPerComponent" => 16 },
+            vec![0; 8],
+        );
+        assert!(matches!(doc.image_pixels(&wide), Err(Error::InvalidStream(_))));
+    }
+
+    #[test]
+    fn color_key_masks_pixels_before_decoding() {
+        let doc = Document::new();
+        // A GIF-style 4-bit indexed image with palette entry 2 transparent.
+        let palette = vec![255, 0, 0, 0, 255, 0, 0, 0, 255];
+        let indexed = image(
+            dictionary! {
+                "Width" => 3,
+                "Height" => 2,
+                "ColorSpace" => vec![
+                    "Indexed".into(),
+                    "DeviceRGB".into(),
+                    2.into(),
+                    Object::String(palette, crate::StringFormat::Hexadecimal),
+                ],
+                "BitsPerComponent" => 4,
+                "Mask" => vec![2.into(), 2.into()],
+            },
+            vec![0x01, 0x20, 0x21, 0x00],
+        );
+        let rgba = doc.image_pixels(&indexed).unwrap().into_rgba8();
+        assert_eq!(
+            rgba.chunks_exact(4).collect::<Vec<_>>(),
+            [
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [0, 0, 255, 0],
+                [0, 0, 255, 0],
+                [0, 255, 0, 255],
+                [255, 0, 0, 255],
+            ]
+        );
+
+        // The ranges apply to the samples, not to the inverted values.
+        let gray = image(
+            dictionary! {
+                "Width" => 2,
+                "Height" => 1,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+                "Decode" => vec![1.into(), 0.into()],
+                "Mask" => vec![250.into(), 255.into()],
+            },
+            vec![255, 0],
+        );
+        assert_eq!(doc.image_pixels(&gray).unwrap().into_rgba8(), [0, 0, 0, 0, 255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn stencil_masks_are_composited_into_alpha() {
+        let mut doc = Document::new();
+        let mask_id = doc.add_object(image(
+            dictionary! {
+                "Width" => 2,
+                "Height" => 1,
+                "ImageMask" => true,
+            },
+            vec![0b0100_0000],
+        ));
+        let photo = image(
+            dictionary! {
+                "Width" => 4,
+                "Height" => 2,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+                "Mask" => mask_id,
+            },
+            vec![10, 20, 30, 40, 50, 60, 70, 80],
+        );
+        let pixels = doc.image_pixels(&photo).unwrap();
+        assert_eq!(pixels.alpha.unwrap(), [255, 255, 0, 0, 255, 255, 0, 0]);
+
+        let image_mask = image(
+            dictionary! {
+                "Width" => 2,
+                "Height" => 1,
+                "ImageMask" => true,
+                "Decode" => vec![1.into(), 0.into()],
+            },
+            vec![0b0100_0000],
+        );
+        assert_eq!(doc.image_pixels(&image_mask).unwrap().into_rgba8(), [0, 0, 0, 0, 0, 0, 0, 255]);
+    }
+}
@@ -45,6 +45,11 @@ pub fn get_prev_documents_bytes(&self) -> &[u8] {
         &self.bytes_documents
     }
 
+    /// The bytes of the previous documents and the new document, borrowed together for saving.
+    pub(crate) fn parts_mut(&mut self) -> (&[u8], &mut Document) {
+        (&self.bytes_documents, &mut self.new_document)
+    }
+
     /// Clone Object from previous document to new document.
     /// If the object already exists nothing is done.
     ///
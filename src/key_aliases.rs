@@ -0,0 +1,136 @@
+use log::warn;
+
+use crate::{Dictionary, Document, Object, Result};
+
+/// Misspellings of dictionary keys written by some producers, with the key they stand for.
+const DEFAULT_ALIASES: [(&[u8], &[u8]); 8] = [
+    (b"PageMode", b"Pagemode"),
+    (b"PageLayout", b"Pagelayout"),
+    // Actions naming their type like an annotation.
+    (b"S", b"Subtype"),
+    // A lowercase L instead of an uppercase I.
+    (b"BlackIs1", b"Blackls1"),
+    (b"Widths", b"Width"),
+    (b"MediaBox", b"Mediabox"),
+    (b"CropBox", b"Cropbox"),
+    (b"Rotate", b"rotate"),
+];
+
+/// Misspelled dictionary keys accepted in place of the keys they stand for, see
+/// [`Document::key_aliases`].
+///
+/// Aliases are only consulted when the key itself is absent, by the features reading the
+/// document: font widths, inherited page attributes, the types of actions and the page mode.
+/// The [`Dictionary`] API itself stays strict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyAliases {
+    /// Pairs of key and alias
+    aliases: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Default for KeyAliases {
+    /// The curated aliases of common producer typos, like /Pagemode for /PageMode.
+    fn default() -> Self {
+        KeyAliases {
+            aliases: DEFAULT_ALIASES
+                .iter()
+                .map(|(key, alias)| (key.to_vec(), alias.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyAliases {
+    /// No aliases, so keys are only found under their correct spelling.
+    pub fn none() -> Self {
+        KeyAliases { aliases: Vec::new() }
+    }
+
+    /// Accept `alias` for `key`, e.g. a misspelling found in the files of some producer.
+    pub fn add(&mut self, key: impl Into<Vec<u8>>, alias: impl Into<Vec<u8>>) -> &mut Self {
+        let entry = (key.into(), alias.into());
+        if !self.aliases.contains(&entry) {
+            self.aliases.push(entry);
+        }
+        self
+    }
+
+    /// The aliases accepted for `key`.
+    pub fn aliases_of<'a>(&'a self, key: &'a [u8]) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.aliases
+            .iter()
+            .filter(move |(canonical, _)| canonical == key)
+            .map(|(_, alias)| alias.as_slice())
+    }
+}
+
+impl Document {
+    /// Get the value of `key` in `dict`, or of one of the [aliases](Document::key_aliases) of
+    /// `key` if `dict` doesn't have it. Finding a value under an alias is logged as a warning.
+    pub fn get_with_aliases<'a>(&self, dict: &'a Dictionary, key: &[u8]) -> Result<&'a Object> {
+        let missing = match dict.get(key) {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        for alias in self.key_aliases.aliases_of(key) {
+            if let Ok(value) = dict.get(alias) {
+                warn!(
+                    "using misspelled key /{} as /{}",
+                    String::from_utf8_lossy(alias),
+                    String::from_utf8_lossy(key)
+                );
+                return Ok(value);
+            }
+        }
+        Err(missing)
+    }
+
+    /// Like [`Document::get_with_aliases`], following a reference to the value.
+    pub fn get_deref_with_aliases<'a>(&'a self, dict: &'a Dictionary, key: &[u8]) -> Result<&'a Object> {
+        let value = self.get_with_aliases(dict, key)?;
+        self.dereference(value).map(|(_, value)| value)
+    }
+
+    /// The /PageMode of the catalog, how the document is displayed when opened, e.g.
+    /// `UseOutlines`.
+    pub fn page_mode(&self) -> Result<&[u8]> {
+        let catalog = self.catalog()?;
+        self.get_deref_with_aliases(catalog, b"PageMode").and_then(Object::as_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use crate::Error;
+
+    #[test]
+    fn page_mode_through_alias() {
+        let mut doc = create_document();
+        doc.catalog_mut().unwrap().set("Pagemode", "UseOutlines");
+        assert_eq!(doc.page_mode().unwrap(), b"UseOutlines");
+
+        doc.key_aliases = KeyAliases::none();
+        assert!(matches!(doc.page_mode(), Err(Error::DictKey(_))));
+    }
+
+    #[test]
+    fn canonical_key_takes_precedence() {
+        let doc = Document::new();
+        let params = dictionary! { "Blackls1" => true, "K" => -1 };
+        assert_eq!(doc.get_with_aliases(&params, b"BlackIs1").unwrap(), &Object::Boolean(true));
+        let params = dictionary! { "Blackls1" => true, "BlackIs1" => false };
+        assert_eq!(doc.get_with_aliases(&params, b"BlackIs1").unwrap(), &Object::Boolean(false));
+    }
+
+    #[test]
+    fn user_aliases() {
+        let mut doc = Document::new();
+        let dict = dictionary! { "Colour" => "DeviceRGB" };
+        assert!(doc.get_with_aliases(&dict, b"ColorSpace").is_err());
+        doc.key_aliases.add("ColorSpace", "Colour").add("ColorSpace", "Colour");
+        assert_eq!(doc.key_aliases.aliases_of(b"ColorSpace").count(), 1);
+        assert_eq!(doc.get_with_aliases(&dict, b"ColorSpace").and_then(Object::as_name).unwrap(), b"DeviceRGB");
+    }
+}
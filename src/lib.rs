@@ -1,10 +1,16 @@
 #![doc = include_str!("../README.md")]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+// Mapping a file is unsafe, see `Document::load_mmap`, whose module is the one allowed to.
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 #![deny(clippy::all)]
 
 pub mod content;
+pub mod diff;
 pub mod encryption;
 pub mod filters;
+#[cfg(feature = "ops")]
+pub mod ops;
+pub mod parse;
 pub mod xobject;
 pub mod xref;
 
@@ -13,44 +19,137 @@
 mod document;
 mod incremental_document;
 
+mod annotations;
 mod bookmarks;
+#[cfg(feature = "census")]
+mod census;
 mod cmap_section;
+mod comment_threads;
 mod common_data_structures;
+mod content_walker;
 mod creator;
 mod datetime;
+mod dehyphenate;
+mod document_index;
 mod destinations;
+mod duplicate_page;
 mod encodings;
 mod error;
+mod extensions;
+mod extraction_trace;
+mod fingerprint;
+mod forms;
+mod glyph_coverage;
+mod image_pixels;
+mod key_aliases;
+mod load_warning;
+mod manifest;
 mod outlines;
+mod page_box;
+mod page_class;
+mod piece_info;
+mod preview;
 mod processor;
+mod progress;
+mod read_only;
 mod toc;
 mod writer;
 
+mod object_mapping;
 mod object_stream;
 mod parser;
 mod parser_aux;
 mod reader;
+mod reader_options;
+mod repair;
+mod replace_image;
+mod resource_usage;
+mod revisions;
+mod sanitize;
+mod save_atomic;
 mod save_options;
+mod shaping;
+mod signature;
+mod size_breakdown;
+mod stream_compression;
+mod strict_output;
+mod tables;
+mod version;
+mod xmp;
 
 mod font;
+mod font_program;
+mod font_upgrade;
+mod font_widths;
+mod standard_fonts;
 
 pub use document::Document;
 pub use object::{Dictionary, Object, ObjectId, Stream, StringFormat};
 
+pub use annotations::{Annotation, AttachedFile, FileAttachmentIcon};
 pub use bookmarks::Bookmark;
+#[cfg(feature = "census")]
+pub use census::Census;
+pub use comment_threads::{Comment, CommentThread, ReviewState};
 pub use common_data_structures::{decode_text_string, text_string};
+pub use content_walker::{ContentWalker, WalkContext, DEFAULT_MAX_FORM_DEPTH};
+pub use dehyphenate::HyphenJoin;
 pub use destinations::Destination;
-pub use encodings::{Encoding, encode_utf8, encode_utf16_be};
-pub use encryption::{EncryptionState, EncryptionVersion, Permissions};
+pub use document_index::DocumentIndex;
+pub use duplicate_page::DuplicateMode;
+pub use encodings::{
+    DecodedText, DifferencesEncoding, Encoding, ReverseMapping, decode_utf16_be, encode_utf8, encode_utf16_be,
+};
+pub use encryption::{
+    CryptoProvider, DefaultCryptoProvider, EncryptionAlgorithm, EncryptionOptions, EncryptionState, EncryptionVersion,
+    FileIdPolicy, Password, PasswordKind, Permissions, SeededCryptoProvider,
+};
 pub use error::{Error, Result};
+pub use extensions::DeveloperExtension;
+pub use extraction_trace::{ExtractionTrace, FontTrace, MappingSource, SkippedOperation};
+pub use forms::{FitResult, OverflowPolicy};
+pub use glyph_coverage::{CoverageReport, UnsupportedCharPolicy, is_reusable_font};
 pub use incremental_document::IncrementalDocument;
+pub use key_aliases::KeyAliases;
+pub use load_warning::LoadWarning;
+pub use manifest::{MANIFEST_VERSION, Manifest, Mismatch, PageManifest};
+pub use object_mapping::{
+    FieldReader, FieldWriter, FromPdfObject, KeyCase, MappingError, MappingErrorKind, ToPdfObject,
+};
 pub use object_stream::{ObjectStream, ObjectStreamBuilder, ObjectStreamConfig};
-pub use outlines::Outline;
-pub use reader::{Reader, PdfMetadata};
-pub use save_options::{SaveOptions, SaveOptionsBuilder};
+pub use outlines::{Outline, OutlineIssue, OutlineItem, OutlineTree};
+pub use page_class::{PageClass, PageClassOptions};
+pub use piece_info::{PieceInfoEntry, PieceInfoTarget};
+pub use preview::{PreviewImage, PreviewImageData, PreviewInfo, PreviewText};
+pub use progress::{LoadProgress, Phase, Progress, ProgressSink};
+#[allow(deprecated)]
+pub use reader::FilterFunc;
+pub use reader::{
+    EncryptionInfo, FilterContext, LazyDocument, LoadFilter, LoadFilters, MetadataOptions, ObjectFilter, ObjectOrigin,
+    PageDimensions, PdfMetadata, Reader, TrailerFilter,
+};
+pub use read_only::{ReadOnlyDocument, ReadOnlyView};
+pub use reader_options::{ReaderOptions, ReaderOptionsBuilder};
+pub use repair::RepairAction;
+pub use replace_image::{ImageInfo, ImageReplacement, ImageSource};
+pub use resource_usage::ResourceCategory;
+pub use revisions::{Revision, RevisionCandidate};
+pub use sanitize::{SanitizeOptions, SanitizeReport, MARKUP_ANNOTATION_SUBTYPES};
+pub use save_options::{SaveOptions, SaveOptionsBuilder, SaveOptionsError, SaveReport};
+pub use shaping::{ShapedGlyph, ShapedRun, TextShaper};
+#[cfg(feature = "bidi")]
+pub use shaping::BidiShaper;
+pub use signature::{CertificationLevel, SignaturePlaceholder};
+pub use size_breakdown::{CategorySize, ObjectSize, SizeBreakdown, SizeCategory};
+pub use stream_compression::CompressionSummary;
+pub use strict_output::OutputFallback;
+pub use tables::{Table, TableCell, TableOptions};
 pub use toc::Toc;
+pub use xmp::{PdfAIdentification, XmpMetadata};
 
-pub use parser_aux::substr;
+pub use parser_aux::{ExtractionOptions, ReplaceOptions, substr};
 pub use parser_aux::substring;
 
 pub use font::FontData;
+pub use font_widths::{FALLBACK_WIDTH, FontWidths, WidthSource};
+pub use standard_fonts::{STANDARD_FONT_ALIASES, STANDARD_FONTS, is_subset_font_name, standard_font_name};
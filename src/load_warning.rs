@@ -0,0 +1,140 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{Document, ObjectId};
+
+/// A problem found while loading a document that didn't stop it from loading, see
+/// [`Document::load_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LoadWarning {
+    /// The object at `offset`, listed as `id` in the cross-reference table, couldn't be parsed and
+    /// was left out of the document.
+    SkippedObject { offset: usize, id: ObjectId, reason: String },
+    /// The objects stored in the object stream `id` couldn't be read and were left out of the
+    /// document. The stream itself is kept.
+    SkippedObjectStream { id: ObjectId, reason: String },
+    /// The /Size of the trailer doesn't match the entries of the cross-reference table. The
+    /// `actual` size was used.
+    TrailerSizeMismatch { declared: u32, actual: u32 },
+    /// The cross-reference section at `offset`, which the last `startxref` points at, couldn't be
+    /// read, and the one an earlier `startxref` points at was used instead.
+    UnreadableXref { offset: usize, reason: String },
+    /// The cross-reference stream at `offset`, which the /XRefStm entry of a hybrid-reference
+    /// file's table points at, couldn't be read. Only the table was used.
+    UnreadableXrefStream { offset: i64, reason: String },
+    /// The object couldn't be decrypted and was kept as it was read.
+    DecryptFailed(ObjectId),
+    /// The cross-reference table lists `expected` at `offset`, where the object `found` is.
+    ObjectIdMismatch {
+        offset: usize,
+        expected: ObjectId,
+        found: ObjectId,
+    },
+    /// The /Length of the stream `id` at `offset` doesn't end where `endstream` is, `actual` bytes
+    /// after the start of its data, or there is no `endstream`. The data is read up to the
+    /// `endstream` nearest to where the /Length ends.
+    StreamLengthMismatch {
+        id: ObjectId,
+        offset: usize,
+        declared: i64,
+        actual: Option<usize>,
+    },
+    /// The object `id` at `offset` isn't followed by `endobj`. Only strict reading checks this.
+    MissingEndobj { id: ObjectId, offset: usize },
+    /// The cross-reference table lists `id` at `listed`, where it isn't, as when the offset points
+    /// into the data of another object. It was read at `found`, the nearest header of the object.
+    RelocatedObject { id: ObjectId, listed: usize, found: usize },
+    /// The cross-reference table lists the object `id` at `offset` with another `generation`, as
+    /// files reusing object numbers often do. It was read as `id`.
+    GenerationMismatch { id: ObjectId, offset: usize, generation: u16 },
+    /// Objects follow the end of the revision the document was loaded as, at `revision_end`, but
+    /// no cross-reference section for them, as when the file was cut off in the middle of its
+    /// last incremental update. The `ignored` bytes from `revision_end` to the end of the file
+    /// were left out.
+    UnfinishedUpdate { revision_end: usize, ignored: usize },
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadWarning::SkippedObject { offset, id, reason } => {
+                write!(f, "skipped object {} {} at offset {offset}: {reason}", id.0, id.1)
+            }
+            LoadWarning::SkippedObjectStream { id, reason } => {
+                write!(f, "skipped the objects of object stream {} {}: {reason}", id.0, id.1)
+            }
+            LoadWarning::TrailerSizeMismatch { declared, actual } => {
+                write!(f, "Size entry of trailer dictionary is {declared}, correct value is {actual}")
+            }
+            LoadWarning::UnreadableXref { offset, reason } => {
+                write!(f, "cross-reference section at offset {offset} can't be read: {reason}")
+            }
+            LoadWarning::UnreadableXrefStream { offset, reason } => {
+                write!(f, "cross-reference stream at offset {offset} can't be read: {reason}")
+            }
+            LoadWarning::DecryptFailed(id) => write!(f, "object {} {} can't be decrypted", id.0, id.1),
+            LoadWarning::ObjectIdMismatch { offset, expected, found } => write!(
+                f,
+                "object {} {} is listed at offset {offset}, where object {} {} is",
+                expected.0, expected.1, found.0, found.1
+            ),
+            LoadWarning::StreamLengthMismatch {
+                id,
+                offset,
+                declared,
+                actual: Some(actual),
+            } => write!(
+                f,
+                "stream {} {} at offset {offset} has a /Length of {declared} but {actual} bytes of data",
+                id.0, id.1
+            ),
+            LoadWarning::StreamLengthMismatch {
+                id,
+                offset,
+                declared,
+                actual: None,
+            } => write!(
+                f,
+                "stream {} {} at offset {offset} has a /Length of {declared} but no endstream",
+                id.0, id.1
+            ),
+            LoadWarning::MissingEndobj { id, offset } => {
+                write!(f, "object {} {} at offset {offset} isn't followed by endobj", id.0, id.1)
+            }
+            LoadWarning::RelocatedObject { id, listed, found } => write!(
+                f,
+                "object {} {} is listed at offset {listed} but found at offset {found}",
+                id.0, id.1
+            ),
+            LoadWarning::GenerationMismatch { id, offset, generation } => write!(
+                f,
+                "object {} {} at offset {offset} is listed with generation {generation}",
+                id.0, id.1
+            ),
+            LoadWarning::UnfinishedUpdate { revision_end, ignored } => write!(
+                f,
+                "ignored {ignored} bytes of an unfinished update after the revision ending at offset {revision_end}"
+            ),
+        }
+    }
+}
+
+impl Document {
+    /// The problems found while loading the document that didn't stop it from loading, in the
+    /// order they were found. Empty for a document loaded cleanly or not loaded from a file.
+    pub fn load_warnings(&self) -> &[LoadWarning] {
+        &self.load_warnings
+    }
+
+    /// The number of bytes left out at the end of a file cut off in the middle of an incremental
+    /// update, which was loaded as of its last complete revision, see
+    /// [`LoadWarning::UnfinishedUpdate`]. `None` for a complete file.
+    pub fn truncated_bytes(&self) -> Option<usize> {
+        self.load_warnings.iter().find_map(|warning| match warning {
+            LoadWarning::UnfinishedUpdate { ignored, .. } => Some(*ignored),
+            _ => None,
+        })
+    }
+}
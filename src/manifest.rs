@@ -0,0 +1,410 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Dictionary, Document, Object, ObjectId};
+
+/// Version of the canonicalization rules [`Manifest`] hashes are computed with.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Content hashes of a document, to prove that a later migration of the document, e.g.
+/// recompressing it or renumbering its objects, kept what it shows.
+///
+/// All hashes are lowercase hexadecimal SHA-256 digests, computed following version
+/// [`MANIFEST_VERSION`] of these rules, which don't change between lopdf versions:
+///
+/// - **Page content**: the page content streams are decoded into operations, and each operation
+///   is written on its own line as its operands followed by its operator, separated by single
+///   spaces. Integers and reals with an integral value are written as integers, other reals with
+///   5 decimals and without trailing zeros. Names are written with `/` and with bytes outside `!`
+///   to `~`, delimiters and `#` written as `#XX`. Strings are written as lowercase hexadecimal
+///   strings whatever their written form. Dictionary entries are sorted by key. Inline image data
+///   is written as hexadecimal string after its dictionary. Content that doesn't parse is hashed
+///   as is. Form XObjects are not part of the page content.
+/// - **Page text**: the UTF-8 text [`Document::extract_text`] extracts from the page.
+/// - **Images**: the image XObjects of the page resources, by resource name. Their data is hashed
+///   after applying all its filters, preceded by a `width height bits_per_component` line.
+///   Images with filters lopdf can't decode, like DCTDecode, are hashed in their encoded form.
+/// - **Fonts**: the fonts of the page resources with an embedded font program, by resource name.
+///   The font program is hashed after applying all its filters.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// Version of the canonicalization rules the hashes were computed with
+    pub version: u32,
+    pub pages: Vec<PageManifest>,
+}
+
+/// Content hashes of a page.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageManifest {
+    pub page: u32,
+    /// Hash of the canonicalized content operations
+    pub content: String,
+    /// Hash of the extracted text
+    pub text: String,
+    /// Hash of the decoded data of the image XObjects, by resource name
+    pub images: BTreeMap<String, String>,
+    /// Hash of the embedded font programs, by resource name
+    pub fonts: BTreeMap<String, String>,
+}
+
+/// A difference between a [`Manifest`] and the document it is verified against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The manifest uses other canonicalization rules, so nothing else was compared
+    Version { expected: u32, found: u32 },
+    PageCount { expected: usize, found: usize },
+    Content { page: u32 },
+    Text { page: u32 },
+    /// An image changed, or is only in the manifest or only in the document
+    Image { page: u32, name: String },
+    /// A font program changed, or is only in the manifest or only in the document
+    Font { page: u32, name: String },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::Version { expected, found } => {
+                write!(f, "manifest version {expected} doesn't match version {found}")
+            }
+            Mismatch::PageCount { expected, found } => write!(f, "expected {expected} pages, found {found}"),
+            Mismatch::Content { page } => write!(f, "content of page {page} differs"),
+            Mismatch::Text { page } => write!(f, "text of page {page} differs"),
+            Mismatch::Image { page, name } => write!(f, "image {name} of page {page} differs"),
+            Mismatch::Font { page, name } => write!(f, "font {name} of page {page} differs"),
+        }
+    }
+}
+
+impl Manifest {
+    /// Compute the manifest of `doc` again and list how it differs from this one.
+    pub fn verify(&self, doc: &Document) -> Vec<Mismatch> {
+        if self.version != MANIFEST_VERSION {
+            return vec![Mismatch::Version {
+                expected: self.version,
+                found: MANIFEST_VERSION,
+            }];
+        }
+        let actual = doc.content_manifest();
+        let mut mismatches = Vec::new();
+        if self.pages.len() != actual.pages.len() {
+            mismatches.push(Mismatch::PageCount {
+                expected: self.pages.len(),
+                found: actual.pages.len(),
+            });
+        }
+        for (expected, found) in self.pages.iter().zip(&actual.pages) {
+            let page = expected.page;
+            if expected.content != found.content {
+                mismatches.push(Mismatch::Content { page });
+            }
+            if expected.text != found.text {
+                mismatches.push(Mismatch::Text { page });
+            }
+            for name in differing_keys(&expected.images, &found.images) {
+                mismatches.push(Mismatch::Image { page, name });
+            }
+            for name in differing_keys(&expected.fonts, &found.fonts) {
+                mismatches.push(Mismatch::Font { page, name });
+            }
+        }
+        mismatches
+    }
+}
+
+impl Document {
+    /// Compute the content hashes of every page, see [`Manifest`] for what they cover.
+    pub fn content_manifest(&self) -> Manifest {
+        let pages = self
+            .get_pages()
+            .into_iter()
+            .map(|(page, page_id)| PageManifest {
+                page,
+                content: self.content_hash(page_id),
+                text: hash(self.extract_text(&[page]).unwrap_or_default().as_bytes()),
+                images: self.image_hashes(page_id),
+                fonts: self.font_hashes(page_id),
+            })
+            .collect();
+        Manifest {
+            version: MANIFEST_VERSION,
+            pages,
+        }
+    }
+
+    fn content_hash(&self, page_id: ObjectId) -> String {
+        let Ok(content) = self.get_and_decode_page_content(page_id) else {
+            return hash(&self.get_page_content(page_id).unwrap_or_default());
+        };
+        let mut canonical = Vec::new();
+        for operation in &content.operations {
+            for operand in &operation.operands {
+                write_canonical(operand, &mut canonical);
+                canonical.push(b' ');
+            }
+            canonical.extend(operation.operator.as_bytes());
+            canonical.push(b'\n');
+        }
+        hash(&canonical)
+    }
+
+    fn image_hashes(&self, page_id: ObjectId) -> BTreeMap<String, String> {
+        let mut images = BTreeMap::new();
+        let Ok((resources, resource_ids)) = self.get_page_resources(page_id) else {
+            return images;
+        };
+        let resources = resources
+            .into_iter()
+            .chain(resource_ids.into_iter().filter_map(|id| self.get_dictionary(id).ok()));
+        for resources in resources {
+            let Ok(xobjects) = resources.get_deref(b"XObject", self).and_then(Object::as_dict) else {
+                continue;
+            };
+            for (name, xobject) in xobjects {
+                let name = String::from_utf8_lossy(name).into_owned();
+                let Ok(image) = self.dereference(xobject).and_then(|(_, xobject)| xobject.as_stream()) else {
+                    continue;
+                };
+                if images.contains_key(&name) || image.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Image".as_slice()) {
+                    continue;
+                }
+                let number = |key: &[u8]| image.dict.get(key).and_then(Object::as_i64).unwrap_or(0);
+                let mut canonical = format!(
+                    "{} {} {}\n",
+                    number(b"Width"),
+                    number(b"Height"),
+                    number(b"BitsPerComponent")
+                )
+                .into_bytes();
+                canonical.extend(image.get_plain_content().unwrap_or_else(|_| image.content.clone()));
+                images.insert(name, hash(&canonical));
+            }
+        }
+        images
+    }
+
+    fn font_hashes(&self, page_id: ObjectId) -> BTreeMap<String, String> {
+        let Ok(fonts) = self.get_page_fonts(page_id) else {
+            return BTreeMap::new();
+        };
+        fonts
+            .into_iter()
+            .filter_map(|(name, font)| {
+                let program = self.font_program(font)?;
+                Some((String::from_utf8_lossy(&name).into_owned(), hash(&program)))
+            })
+            .collect()
+    }
+
+    /// Get the decoded embedded font program of a font, or of the descendant font of a Type0 font.
+    fn font_program(&self, font: &Dictionary) -> Option<Vec<u8>> {
+        let font = match font.get_deref(b"DescendantFonts", self).and_then(Object::as_array) {
+            Ok(descendants) => self.dereference(descendants.first()?).ok()?.1.as_dict().ok()?,
+            Err(_) => font,
+        };
+        let descriptor = font.get_deref(b"FontDescriptor", self).and_then(Object::as_dict).ok()?;
+        let program = [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"]
+            .iter()
+            .find_map(|key| descriptor.get_deref(key, self).and_then(Object::as_stream).ok())?;
+        Some(program.get_plain_content().unwrap_or_else(|_| program.content.clone()))
+    }
+}
+
+/// Lowercase hexadecimal SHA-256 digest of `bytes`.
+fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Keys whose values differ between two maps, including keys only in one of them.
+fn differing_keys(expected: &BTreeMap<String, String>, found: &BTreeMap<String, String>) -> Vec<String> {
+    let keys: BTreeSet<&String> = expected
+        .keys()
+        .chain(found.keys())
+        .filter(|key| expected.get(*key) != found.get(*key))
+        .collect();
+    keys.into_iter().cloned().collect()
+}
+
+/// Write a content operand following the canonicalization rules of [`Manifest`].
+fn write_canonical(object: &Object, out: &mut Vec<u8>) {
+    match object {
+        Object::Null => out.extend(b"null"),
+        Object::Boolean(value) => out.extend(if *value { b"true".as_slice() } else { b"false" }),
+        Object::Integer(value) => out.extend(value.to_string().into_bytes()),
+        Object::Real(value) if value.fract() == 0.0 && value.abs() < i64::MAX as f32 => {
+            out.extend((*value as i64).to_string().into_bytes())
+        }
+        Object::Real(value) => {
+            let written = format!("{value:.5}");
+            let written = written.trim_end_matches('0').trim_end_matches('.');
+            out.extend(if written == "-0" { "0" } else { written }.as_bytes());
+        }
+        Object::Name(name) => {
+            out.push(b'/');
+            for &byte in name {
+                if byte.is_ascii_graphic() && !b"()<>[]{}/%#".contains(&byte) {
+                    out.push(byte);
+                } else {
+                    out.extend(format!("#{byte:02X}").into_bytes());
+                }
+            }
+        }
+        Object::String(bytes, _) => write_hex(bytes, out),
+        Object::Array(array) => {
+            out.push(b'[');
+            for (index, item) in array.iter().enumerate() {
+                if index > 0 {
+                    out.push(b' ');
+                }
+                write_canonical(item, out);
+            }
+            out.push(b']');
+        }
+        Object::Dictionary(dict) => write_canonical_dictionary(dict, out),
+        Object::Stream(stream) => {
+            write_canonical_dictionary(&stream.dict, out);
+            out.push(b' ');
+            write_hex(&stream.content, out);
+        }
+        Object::Reference(id) => out.extend(format!("{} {} R", id.0, id.1).into_bytes()),
+    }
+}
+
+fn write_canonical_dictionary(dict: &Dictionary, out: &mut Vec<u8>) {
+    let mut entries: Vec<_> = dict.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    out.extend(b"<<");
+    for (key, value) in entries {
+        write_canonical(&Object::Name(key.clone()), out);
+        out.push(b' ');
+        write_canonical(value, out);
+    }
+    out.extend(b">>");
+}
+
+fn write_hex(bytes: &[u8], out: &mut Vec<u8>) {
+    out.push(b'<');
+    out.extend(bytes.iter().flat_map(|byte| format!("{byte:02x}").into_bytes()));
+    out.push(b'>');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{Content, Operation};
+    use crate::creator::tests::create_document_with_texts;
+    use crate::{Stream, StringFormat};
+
+    /// A three page document whose shared resources have an image and an embedded font.
+    fn archived_document() -> Document {
+        let mut doc = create_document_with_texts(&["Hello world", "Second page", "Third page"]);
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 2,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            (0..12).collect(),
+        ));
+        let font_file_id = doc.add_object(Stream::new(dictionary! {}, b"font program".repeat(10)));
+        let descriptor_id = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => "Courier",
+            "FontFile3" => font_file_id,
+        });
+        let pages_id = doc.catalog().unwrap().get(b"Pages").unwrap().as_reference().unwrap();
+        let resources_id = doc.get_dictionary(pages_id).unwrap().get(b"Resources").unwrap().as_reference().unwrap();
+        let resources = doc.get_dictionary_mut(resources_id).unwrap();
+        resources.set("XObject", dictionary! { "Im1" => image_id });
+        let fonts = resources.get(b"Font").and_then(Object::as_dict).unwrap();
+        let font_id = fonts.get(b"F1").and_then(Object::as_reference).unwrap();
+        doc.get_dictionary_mut(font_id).unwrap().set("FontDescriptor", descriptor_id);
+        doc
+    }
+
+    #[test]
+    fn manifest_survives_recompression() {
+        let mut doc = archived_document();
+        let manifest = doc.content_manifest();
+        assert_eq!(manifest.pages.len(), 3);
+        assert_eq!(manifest.pages[0].images.keys().collect::<Vec<_>>(), ["Im1"]);
+        assert_eq!(manifest.pages[0].fonts.keys().collect::<Vec<_>>(), ["F1"]);
+
+        // Recompress every stream, the image with a different filter chain, and reparse.
+        doc.decompress();
+        for object in doc.objects.values_mut() {
+            if let Object::Stream(stream) = object {
+                if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image".as_slice()) {
+                    let hex = stream.content.iter().flat_map(|byte| format!("{byte:02X}").into_bytes());
+                    stream.set_content(hex.chain(*b">").collect());
+                    stream.dict.set("Filter", "ASCIIHexDecode");
+                } else {
+                    stream.compress().unwrap();
+                }
+            }
+        }
+        let mut saved = Vec::new();
+        doc.save_to(&mut saved).unwrap();
+        let doc = Document::load_mem(&saved).unwrap();
+        assert_eq!(manifest.verify(&doc), []);
+    }
+
+    #[test]
+    fn changed_word_mismatches_one_page() {
+        let mut doc = archived_document();
+        let manifest = doc.content_manifest();
+
+        let page_id = doc.get_pages()[&2];
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 48.into()]),
+                Operation::new("Td", vec![100.into(), 600.into()]),
+                Operation::new("Tj", vec![Object::String(b"Second sheet".to_vec(), StringFormat::Hexadecimal)]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        doc.change_page_content(page_id, content.encode().unwrap()).unwrap();
+
+        let mismatches = manifest.verify(&doc);
+        let texts: Vec<_> = mismatches.iter().filter(|m| matches!(m, Mismatch::Text { .. })).collect();
+        assert_eq!(texts, [&Mismatch::Text { page: 2 }]);
+        assert_eq!(mismatches, [Mismatch::Content { page: 2 }, Mismatch::Text { page: 2 }]);
+    }
+
+    #[test]
+    fn operands_are_canonicalized() {
+        let written = |object: Object| {
+            let mut out = Vec::new();
+            write_canonical(&object, &mut out);
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(written(Object::Real(12.0)), "12");
+        assert_eq!(written(Object::Real(0.5)), "0.5");
+        assert_eq!(written(Object::Real(-0.000001)), "0");
+        assert_eq!(written(Object::Name(b"A B#".to_vec())), "/A#20B#23");
+        assert_eq!(written(Object::string_literal("Hi")), "<4869>");
+        assert_eq!(
+            written(Object::Dictionary(dictionary! { "W" => 2, "H" => 1.5 })),
+            "<</H 1.5/W 2>>"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn manifest_json_round_trip() {
+        let manifest = archived_document().content_manifest();
+        let json = serde_json::to_string(&manifest).unwrap();
+        let parsed: Manifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, manifest);
+    }
+}
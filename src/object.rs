@@ -1,8 +1,8 @@
 use crate::encodings;
 use crate::encodings::cmap::ToUnicodeCMap;
-use crate::encodings::Encoding;
+use crate::encodings::{DifferencesEncoding, Encoding};
 use crate::error::DecompressError;
-use crate::{Document, Error, Result};
+use crate::{Document, Error, MappingSource, Result};
 use indexmap::IndexMap;
 use log::warn;
 use std::cmp::max;
@@ -38,6 +38,9 @@ pub enum Object {
     Null,
     Boolean(bool),
     Integer(i64),
+    /// A real number. PDF has no NaN or infinities: saving writes a non-finite real as 0, or
+    /// fails under [`SaveOptionsBuilder::strict_output`](crate::SaveOptionsBuilder::strict_output), see
+    /// [`OutputFallback::NonFiniteReal`](crate::OutputFallback::NonFiniteReal).
     Real(f32),
     Name(Vec<u8>),
     String(Vec<u8>, StringFormat),
@@ -86,12 +89,13 @@ fn from(number: $Int) -> Self {
 
 impl From<f64> for Object {
     fn from(number: f64) -> Self {
-        Object::Real(number as f32)
+        Object::from(number as f32)
     }
 }
 
 impl From<f32> for Object {
     fn from(number: f32) -> Self {
+        debug_assert!(number.is_finite(), "PDF reals must be finite, got {number}");
         Object::Real(number)
     }
 }
@@ -137,6 +141,17 @@ pub fn string_literal<S: Into<Vec<u8>>>(s: S) -> Self {
         Object::String(s.into(), StringFormat::Literal)
     }
 
+    /// Create a real, or `None` if `value` is NaN or infinite and so can't be written to a PDF.
+    pub fn real(value: f32) -> Option<Self> {
+        value.is_finite().then_some(Object::Real(value))
+    }
+
+    /// Create a text string encoded as UTF-16BE with a byte order mark, characters outside the
+    /// Basic Multilingual Plane becoming surrogate pairs.
+    pub fn string_utf16(text: &str) -> Self {
+        Object::String(crate::encodings::encode_utf16_be(text), StringFormat::Hexadecimal)
+    }
+
     pub fn is_null(&self) -> bool {
         matches!(*self, Object::Null)
     }
@@ -365,6 +380,8 @@ pub fn get_mut(&mut self, key: &[u8]) -> Result<&mut Object> {
             .ok_or(Error::DictKey(String::from_utf8_lossy(key).to_string()))
     }
 
+    /// Set `key` to `value`. Numbers converted to reals must be finite, which is checked in
+    /// debug builds.
     pub fn set<K, V>(&mut self, key: K, value: V)
     where
         K: Into<Vec<u8>>,
@@ -404,6 +421,11 @@ pub fn iter_mut(&'_ mut self) -> indexmap::map::IterMut<'_, Vec<u8>, Object> {
     }
 
     pub fn get_font_encoding(&'_ self, doc: &Document) -> Result<Encoding<'_>> {
+        self.font_encoding_with_source(doc).map(|(encoding, _)| encoding)
+    }
+
+    /// Like [`Dictionary::get_font_encoding`], with where the mapping to Unicode comes from.
+    pub(crate) fn font_encoding_with_source(&'_ self, doc: &Document) -> Result<(Encoding<'_>, MappingSource)> {
         if !self.has_type(b"Font") {
             return Err(Error::DictType {
                 expected: "Font",
@@ -411,37 +433,56 @@ pub fn get_font_encoding(&'_ self, doc: &Document) -> Result<Encoding<'_>> {
             });
         }
 
+        let named =
+            |encoding, name: &[u8]| Ok((Encoding::OneByteEncoding(encoding), MappingSource::Named(name.to_vec())));
         // Note: currently not all encodings are handled, not implemented:
-        // - dictionary differences encoding
-        // - default base encoding in dictionary differences encoding
+        // - built-in base encoding of symbolic fonts in dictionary differences encoding
         // - TrueType cmap tables
         // - DescendantFonts in CID-Keyed fonts
         // - predefined CJK CMAP other than indicated in SimpleEncoding
         match self.get(b"Encoding").and_then(Object::as_name) {
-            Ok(b"StandardEncoding") => Ok(Encoding::OneByteEncoding(&encodings::STANDARD_ENCODING)),
-            Ok(b"MacRomanEncoding") => Ok(Encoding::OneByteEncoding(&encodings::MAC_ROMAN_ENCODING)),
-            Ok(b"MacExpertEncoding") => Ok(Encoding::OneByteEncoding(&encodings::MAC_EXPERT_ENCODING)),
-            Ok(b"WinAnsiEncoding") => Ok(Encoding::OneByteEncoding(&encodings::WIN_ANSI_ENCODING)),
-            Ok(b"PDFDocEncoding") => {
+            Ok(name @ b"StandardEncoding") => named(&encodings::STANDARD_ENCODING, name),
+            Ok(name @ b"MacRomanEncoding") => named(&encodings::MAC_ROMAN_ENCODING, name),
+            Ok(name @ b"MacExpertEncoding") => named(&encodings::MAC_EXPERT_ENCODING, name),
+            Ok(name @ b"WinAnsiEncoding") => named(&encodings::WIN_ANSI_ENCODING, name),
+            Ok(name @ b"PDFDocEncoding") => {
                 log::warn!("PDFDocEncoding is not a valid character encoding for a font");
-                Ok(Encoding::OneByteEncoding(&encodings::PDF_DOC_ENCODING))
+                named(&encodings::PDF_DOC_ENCODING, name)
             }
             Ok(b"Identity-H") | Ok(b"Identity-V") => {
                 let stream = self.get_deref(b"ToUnicode", doc)?.as_stream()?;
-                self.get_encoding_from_to_unicode_cmap(stream)
+                Ok((self.get_encoding_from_to_unicode_cmap(stream)?, MappingSource::ToUnicode))
             }
-            Ok(name) => Ok(Encoding::SimpleEncoding(name)),
+            Ok(name) => Ok((Encoding::SimpleEncoding(name), MappingSource::Named(name.to_vec()))),
             Err(err) => {
-                warn!(
-                    "Could not parse the encoding, error: {err:#?}\nFont: {self:#?}\nTrying to retrieve ToUnicode."
-                );
+                let encoding = self.get_deref(b"Encoding", doc).and_then(Object::as_dict);
+                if encoding.is_err() {
+                    warn!(
+                        "Could not parse the encoding, error: {err:#?}\nFont: {self:#?}\nTrying to retrieve ToUnicode."
+                    );
+                }
                 let stream = self.get_deref(b"ToUnicode", doc).and_then(Object::as_stream);
                 if let Ok(stream) = stream {
-                    return self.get_encoding_from_to_unicode_cmap(stream);
+                    return Ok((self.get_encoding_from_to_unicode_cmap(stream)?, MappingSource::ToUnicode));
+                }
+                if let Ok(encoding) = encoding {
+                    let base = match encoding.get(b"BaseEncoding").and_then(Object::as_name) {
+                        Ok(b"MacRomanEncoding") => &encodings::MAC_ROMAN_ENCODING,
+                        Ok(b"MacExpertEncoding") => &encodings::MAC_EXPERT_ENCODING,
+                        Ok(b"WinAnsiEncoding") => &encodings::WIN_ANSI_ENCODING,
+                        _ => &encodings::STANDARD_ENCODING,
+                    };
+                    let encoding = DifferencesEncoding::new(self, encoding, base, doc);
+                    let source = if encoding.font_program_names > 0 {
+                        MappingSource::FontProgram
+                    } else {
+                        MappingSource::Differences
+                    };
+                    return Ok((Encoding::DifferencesEncoding(encoding), source));
                 }
 
                 warn!("Using standard encoding as a fallback!");
-                Ok(Encoding::OneByteEncoding(&encodings::STANDARD_ENCODING))
+                Ok((Encoding::OneByteEncoding(&encodings::STANDARD_ENCODING), MappingSource::Guess))
             }
         }
     }
@@ -625,8 +666,10 @@ pub fn with_compression(mut self, allows_compression: bool) -> Stream {
     }
 
     pub fn filters(&self) -> Result<Vec<&[u8]>> {
-        let filter = self.dict.get(b"Filter")?;
+        Self::filter_names(self.dict.get(b"Filter")?)
+    }
 
+    fn filter_names(filter: &Object) -> Result<Vec<&[u8]>> {
         if let Ok(name) = filter.as_name() {
             Ok(vec![name])
         } else if let Ok(names) = filter.as_array() {
@@ -676,19 +719,34 @@ pub fn compress(&mut self) -> Result<()> {
     }
 
     pub fn decompressed_content(&self) -> Result<Vec<u8>> {
-        let params = self.dict.get(b"DecodeParms").and_then(Object::as_dict).ok();
-        let filters = self.filters()?;
+        self.decode_with(self.dict.get(b"Filter")?, self.dict.get(b"DecodeParms").ok())
+    }
+
+    /// Decode the content with `filter` and `params` in place of the /Filter and /DecodeParms of
+    /// the stream, e.g. copies with their references resolved.
+    pub(crate) fn decode_with(&self, filter: &Object, params: Option<&Object>) -> Result<Vec<u8>> {
+        let filters = Self::filter_names(filter)?;
 
         let mut input = self.content.as_slice();
         let mut output = vec![];
 
         // Filters are in decoding order.
-        for filter in filters {
+        for (index, filter) in filters.into_iter().enumerate() {
+            // An array of parameters has an entry for each filter.
+            let params = match params {
+                Some(Object::Array(params)) => params.get(index).and_then(|params| params.as_dict().ok()),
+                Some(params) => params.as_dict().ok(),
+                None => None,
+            };
             output = match filter {
                 b"FlateDecode" => Self::decompress_zlib(input, params)?,
                 b"LZWDecode" => Self::decompress_lzw(input, params)?,
                 b"ASCII85Decode" => Self::decode_ascii85(input)?,
-                _ => return Err(Error::Unimplemented("decompression algorithms")),
+                b"ASCIIHexDecode" => Self::decode_ascii_hex(input),
+                b"RunLengthDecode" => crate::filters::run_length::decode(input),
+                // Applied by the security handler when the document is decrypted.
+                b"Crypt" => input.to_vec(),
+                _ => return Err(Error::UnsupportedFilter(String::from_utf8_lossy(filter).into_owned())),
             };
             input = &output;
         }
@@ -797,22 +855,52 @@ fn decode_ascii85(input: &[u8]) -> Result<Vec<u8>> {
         Ok(output)
     }
 
+    fn decode_ascii_hex(input: &[u8]) -> Vec<u8> {
+        let digits: Vec<u8> = input
+            .iter()
+            .take_while(|ch| **ch != b'>')
+            .filter_map(|ch| (*ch as char).to_digit(16))
+            .map(|digit| digit as u8)
+            .collect();
+        // A missing final digit is taken to be 0.
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+            .collect()
+    }
+
     fn decompress_predictor(mut data: Vec<u8>, params: Option<&Dictionary>) -> Result<Vec<u8>> {
-        use crate::filters::png;
-
-        if let Some(params) = params {
-            let predictor = params.get(b"Predictor").and_then(Object::as_i64).unwrap_or(1);
-            if (10..=15).contains(&predictor) {
-                let pixels_per_row = max(1, params.get(b"Columns").and_then(Object::as_i64).unwrap_or(1)) as usize;
-                let colors = max(1, params.get(b"Colors").and_then(Object::as_i64).unwrap_or(1)) as usize;
-                let bits = max(8, params.get(b"BitsPerComponent").and_then(Object::as_i64).unwrap_or(8)) as usize;
-                let bytes_per_pixel = colors * bits / 8;
-                data = png::decode_frame(data.as_slice(), bytes_per_pixel, pixels_per_row)?;
+        use crate::filters::{png, tiff};
+
+        let Some(params) = params else {
+            return Ok(data);
+        };
+        let predictor = params.get(b"Predictor").and_then(Object::as_i64).unwrap_or(1);
+        if predictor != 2 && !(10..=15).contains(&predictor) {
+            return Ok(data);
+        }
+        let columns = max(1, params.get(b"Columns").and_then(Object::as_i64).unwrap_or(1)) as usize;
+        let colors = max(1, params.get(b"Colors").and_then(Object::as_i64).unwrap_or(1)) as usize;
+        let bits = params.get(b"BitsPerComponent").and_then(Object::as_i64).unwrap_or(8);
+        if ![1, 2, 4, 8, 16].contains(&bits) {
+            warn!("ignoring predictor of components of {bits} bits");
+            return Ok(data);
+        }
+        // Components of less than 8 bits are packed into the bytes of a row, with the bytes of
+        // the pixel to the left taken to be the previous byte.
+        let bits_per_pixel = colors * bits as usize;
+        let bytes_per_pixel = max(1, bits_per_pixel / 8);
+        let bytes_per_row = (bits_per_pixel * columns).div_ceil(8);
+        if predictor == 2 {
+            match bits {
+                8 => tiff::decode(&mut data, colors, bytes_per_row),
+                16 => tiff::decode_16(&mut data, colors, bytes_per_row),
+                _ => warn!("TIFF predictor with {bits} bits per component isn't supported"),
             }
-            Ok(data)
         } else {
-            Ok(data)
+            data = png::decode_rows(data.as_slice(), bytes_per_pixel, bytes_per_row)?;
         }
+        Ok(data)
     }
 
     pub fn decompress(&mut self) -> Result<()> {
@@ -854,4 +942,44 @@ fn test_decode_ascii85_overflow() {
         // let expected: Result<Vec<u8>, Error> = Err(Error::ContentDecode);
         assert!(matches!(output, Err(Error::Decompress(DecompressError::Ascii85(_)))));
     }
+
+    #[test]
+    fn test_decode_run_length_image() {
+        let doc = crate::Document::load_mem(include_bytes!("../assets/run_length_image.pdf")).unwrap();
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let images = doc.get_page_images(page_id).unwrap();
+        assert_eq!(images.len(), 1);
+
+        let stream = doc.get_object(images[0].id).unwrap().as_stream().unwrap();
+        let pixels = stream.decompressed_content().unwrap();
+        assert_eq!(pixels.len(), 8 * 4);
+        assert_eq!(&pixels[..16], &[0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255]);
+        assert_eq!(&pixels[16..20], &[0x80, 0x40, 0x20, 0x10]);
+        assert_eq!(&pixels[24..], &[200; 8]);
+    }
+
+    #[test]
+    fn test_decode_16_bit_rgb_png_predictor_image() {
+        let doc = crate::Document::load_mem(include_bytes!("../assets/rgb16_predictor.pdf")).unwrap();
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let images = doc.get_page_images(page_id).unwrap();
+        assert_eq!((images[0].width, images[0].height, images[0].bits_per_component), (5, 4, Some(16)));
+
+        // Rows use the Sub, Up, Average and Paeth filters in turn.
+        let stream = doc.get_object(images[0].id).unwrap().as_stream().unwrap();
+        let pixels = stream.decompressed_content().unwrap();
+        let samples: Vec<u16> = pixels.chunks(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+        let expected: Vec<u16> = (0..4_u32)
+            .flat_map(|y| (0..5_u32).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                [
+                    (x * 13107 + y * 257) % 65536,
+                    (65535 - x * 4099 - y * 1000) % 65536,
+                    (x * y * 3001 + 17) % 65536,
+                ]
+            })
+            .map(|sample| sample as u16)
+            .collect();
+        assert_eq!(samples, expected);
+    }
 }
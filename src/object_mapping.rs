@@ -0,0 +1,679 @@
+//! Typed conversions between PDF objects and application data.
+//!
+//! [`FromPdfObject`] and [`ToPdfObject`] are implemented for primitives, strings, [`Vec`],
+//! [`Option`], [`BTreeMap`] and, with the `chrono` feature, dates. A struct stored as a
+//! dictionary implements them with a [`FieldReader`] and a [`FieldWriter`], which name
+//! dictionary keys after the fields of the struct:
+//!
+//! ```
+//! use lopdf::{Dictionary, FieldReader, FieldWriter, FromPdfObject, KeyCase, MappingError, Object, ToPdfObject};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Order {
+//!     order_number: i64,
+//!     note: Option<String>,
+//! }
+//!
+//! impl FromPdfObject for Order {
+//!     fn from_pdf_object(object: &Object, case: KeyCase) -> Result<Self, MappingError> {
+//!         let fields = FieldReader::new(object, case)?;
+//!         Ok(Order {
+//!             order_number: fields.get("order_number")?,
+//!             note: fields.get("note")?,
+//!         })
+//!     }
+//! }
+//!
+//! impl ToPdfObject for Order {
+//!     fn to_pdf_object(&self, case: KeyCase) -> Result<Object, MappingError> {
+//!         let mut fields = FieldWriter::new(case);
+//!         fields.set("order_number", &self.order_number)?;
+//!         fields.set("note", &self.note)?;
+//!         Ok(fields.into())
+//!     }
+//! }
+//!
+//! let order = Order { order_number: 7, note: None };
+//! let dict = Dictionary::serialize_from(&order)?;
+//! assert_eq!(dict.get(b"OrderNumber")?.as_i64()?, 7);
+//! assert_eq!(dict.deserialize_into::<Order>()?, order);
+//! # Ok::<(), lopdf::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{Dictionary, Object, ObjectId, Result, decode_text_string, text_string};
+
+/// How the snake_case field names given to [`FieldReader`] and [`FieldWriter`] become
+/// dictionary keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `line_items` is the key /LineItems, as in the dictionaries of the PDF specification.
+    #[default]
+    Pascal,
+    /// `line_items` is the key /lineItems.
+    Camel,
+    /// Field names are keys as they are.
+    Exact,
+}
+
+impl KeyCase {
+    /// The dictionary key of `field`.
+    pub fn key(self, field: &str) -> String {
+        if self == KeyCase::Exact {
+            return field.to_string();
+        }
+        let mut key = String::with_capacity(field.len());
+        let mut upper = self == KeyCase::Pascal;
+        for c in field.chars() {
+            if c == '_' {
+                upper |= !key.is_empty();
+            } else if upper {
+                key.extend(c.to_uppercase());
+                upper = false;
+            } else {
+                key.push(c);
+            }
+        }
+        key
+    }
+}
+
+/// Why a value couldn't be converted, see [`MappingError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingErrorKind {
+    /// A required dictionary key is missing.
+    MissingKey,
+    /// The object has the wrong type.
+    WrongType { expected: &'static str, found: &'static str },
+    /// A number doesn't fit the type it's converted to.
+    OutOfRange,
+    /// Any other invalid value, e.g. a string that isn't a date.
+    Invalid(String),
+}
+
+/// A value that couldn't be converted, with the path to it from the converted object, like
+/// `/LineItems[1]/Quantity`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingError {
+    /// The dictionary keys and array indices leading to the value, empty for the converted
+    /// object itself.
+    pub path: String,
+    pub kind: MappingErrorKind,
+}
+
+impl MappingError {
+    pub fn new(kind: MappingErrorKind) -> Self {
+        MappingError {
+            path: String::new(),
+            kind,
+        }
+    }
+
+    /// The error of `object` not having the `expected` type.
+    pub fn wrong_type(expected: &'static str, object: &Object) -> Self {
+        Self::new(MappingErrorKind::WrongType {
+            expected,
+            found: object.enum_variant(),
+        })
+    }
+
+    fn within_key(mut self, key: &str) -> Self {
+        self.path.insert_str(0, &format!("/{key}"));
+        self
+    }
+
+    fn within_index(mut self, index: usize) -> Self {
+        self.path.insert_str(0, &format!("[{index}]"));
+        self
+    }
+}
+
+impl fmt::Display for MappingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = if self.path.is_empty() { "value" } else { &self.path };
+        match &self.kind {
+            MappingErrorKind::MissingKey => write!(f, "{path}: missing required key"),
+            MappingErrorKind::WrongType { expected, found } => {
+                write!(f, "{path}: expected {expected} but found {found}")
+            }
+            MappingErrorKind::OutOfRange => write!(f, "{path}: number out of range"),
+            MappingErrorKind::Invalid(message) => write!(f, "{path}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for MappingError {}
+
+/// Conversion of a PDF object into a typed value.
+pub trait FromPdfObject: Sized {
+    /// Convert `object`, with the keys of dictionaries named in `case`.
+    fn from_pdf_object(object: &Object, case: KeyCase) -> std::result::Result<Self, MappingError>;
+
+    /// The value of a missing dictionary key, `None` if the key is required.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Conversion of a typed value into a PDF object.
+pub trait ToPdfObject {
+    /// Convert the value, with the keys of dictionaries named in `case`.
+    fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError>;
+
+    /// Whether the value is left out of a dictionary instead of being written, as `None` is.
+    fn is_absent(&self) -> bool {
+        false
+    }
+}
+
+/// Typed access to the keys of a dictionary, for implementing [`FromPdfObject`].
+pub struct FieldReader<'a> {
+    dict: &'a Dictionary,
+    case: KeyCase,
+}
+
+impl<'a> FieldReader<'a> {
+    /// Read the fields of a dictionary, or of the dictionary of a stream.
+    pub fn new(object: &'a Object, case: KeyCase) -> std::result::Result<Self, MappingError> {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            _ => return Err(MappingError::wrong_type("Dictionary", object)),
+        };
+        Ok(FieldReader { dict, case })
+    }
+
+    /// Convert the value of the key of `field`. A missing or null value is only accepted for
+    /// types with a [missing value](FromPdfObject::from_missing), like `Option`.
+    pub fn get<T: FromPdfObject>(&self, field: &str) -> std::result::Result<T, MappingError> {
+        let key = self.case.key(field);
+        match self.dict.get(key.as_bytes()) {
+            Ok(Object::Null) | Err(_) => T::from_missing().ok_or(MappingError::new(MappingErrorKind::MissingKey)),
+            Ok(value) => T::from_pdf_object(value, self.case),
+        }
+        .map_err(|err| err.within_key(&key))
+    }
+}
+
+/// Typed construction of a dictionary, for implementing [`ToPdfObject`].
+pub struct FieldWriter {
+    dict: Dictionary,
+    case: KeyCase,
+}
+
+impl FieldWriter {
+    pub fn new(case: KeyCase) -> Self {
+        FieldWriter {
+            dict: Dictionary::new(),
+            case,
+        }
+    }
+
+    /// Set the key of `field` to `value`, unless the value [is absent](ToPdfObject::is_absent).
+    pub fn set<T: ToPdfObject + ?Sized>(&mut self, field: &str, value: &T) -> std::result::Result<(), MappingError> {
+        if value.is_absent() {
+            return Ok(());
+        }
+        let key = self.case.key(field);
+        let value = value.to_pdf_object(self.case).map_err(|err| err.within_key(&key))?;
+        self.dict.set(key, value);
+        Ok(())
+    }
+
+    pub fn into_dictionary(self) -> Dictionary {
+        self.dict
+    }
+}
+
+impl From<FieldWriter> for Object {
+    fn from(fields: FieldWriter) -> Self {
+        Object::Dictionary(fields.dict)
+    }
+}
+
+impl Dictionary {
+    /// Convert the dictionary into `T`, with keys in [PascalCase](KeyCase::Pascal).
+    pub fn deserialize_into<T: FromPdfObject>(&self) -> Result<T> {
+        self.deserialize_into_with(KeyCase::default())
+    }
+
+    /// Convert the dictionary into `T`, with keys named in `case`.
+    pub fn deserialize_into_with<T: FromPdfObject>(&self, case: KeyCase) -> Result<T> {
+        // The conversions take an object, whichever type they expect.
+        let object = Object::Dictionary(self.clone());
+        Ok(T::from_pdf_object(&object, case)?)
+    }
+
+    /// Convert `value` into a dictionary, with keys in [PascalCase](KeyCase::Pascal).
+    pub fn serialize_from<T: ToPdfObject + ?Sized>(value: &T) -> Result<Dictionary> {
+        Self::serialize_from_with(value, KeyCase::default())
+    }
+
+    /// Convert `value` into a dictionary, with keys named in `case`.
+    pub fn serialize_from_with<T: ToPdfObject + ?Sized>(value: &T, case: KeyCase) -> Result<Dictionary> {
+        match value.to_pdf_object(case)? {
+            Object::Dictionary(dict) => Ok(dict),
+            object => Err(MappingError::wrong_type("Dictionary", &object).into()),
+        }
+    }
+}
+
+impl FromPdfObject for Object {
+    fn from_pdf_object(object: &Object, _case: KeyCase) -> std::result::Result<Self, MappingError> {
+        Ok(object.clone())
+    }
+}
+
+impl ToPdfObject for Object {
+    fn to_pdf_object(&self, _case: KeyCase) -> std::result::Result<Object, MappingError> {
+        Ok(self.clone())
+    }
+}
+
+impl FromPdfObject for bool {
+    fn from_pdf_object(object: &Object, _case: KeyCase) -> std::result::Result<Self, MappingError> {
+        object.as_bool().map_err(|_| MappingError::wrong_type("Boolean", object))
+    }
+}
+
+impl ToPdfObject for bool {
+    fn to_pdf_object(&self, _case: KeyCase) -> std::result::Result<Object, MappingError> {
+        Ok(Object::Boolean(*self))
+    }
+}
+
+macro_rules! integer_mapping {
+	($( $Int: ty )+) => {
+		$(
+			impl FromPdfObject for $Int {
+				fn from_pdf_object(object: &Object, _case: KeyCase) -> std::result::Result<Self, MappingError> {
+					let value = object.as_i64().map_err(|_| MappingError::wrong_type("Integer", object))?;
+					<$Int>::try_from(value).map_err(|_| MappingError::new(MappingErrorKind::OutOfRange))
+				}
+			}
+
+			impl ToPdfObject for $Int {
+				fn to_pdf_object(&self, _case: KeyCase) -> std::result::Result<Object, MappingError> {
+					i64::try_from(*self)
+						.map(Object::Integer)
+						.map_err(|_| MappingError::new(MappingErrorKind::OutOfRange))
+				}
+			}
+		)+
+	}
+}
+
+integer_mapping! {
+    i8 i16 i32 i64 isize
+    u8 u16 u32 u64 usize
+}
+
+impl FromPdfObject for f32 {
+    fn from_pdf_object(object: &Object, _case: KeyCase) -> std::result::Result<Self, MappingError> {
+        object.as_float().map_err(|_| MappingError::wrong_type("Integer or Real", object))
+    }
+}
+
+impl ToPdfObject for f32 {
+    fn to_pdf_object(&self, _case: KeyCase) -> std::result::Result<Object, MappingError> {
+        Ok(Object::Real(*self))
+    }
+}
+
+impl FromPdfObject for f64 {
+    fn from_pdf_object(object: &Object, case: KeyCase) -> std::result::Result<Self, MappingError> {
+        f32::from_pdf_object(object, case).map(f64::from)
+    }
+}
+
+impl ToPdfObject for f64 {
+    fn to_pdf_object(&self, _case: KeyCase) -> std::result::Result<Object, MappingError> {
+        Ok(Object::Real(*self as f32))
+    }
+}
+
+/// Strings are text strings, in PDFDocEncoding or UTF-16BE.
+impl FromPdfObject for String {
+    fn from_pdf_object(object: &Object, _case: KeyCase) -> std::result::Result<Self, MappingError> {
+        if !matches!(object, Object::String(..)) {
+            return Err(MappingError::wrong_type("String", object));
+        }
+        decode_text_string(object)
+            .map_err(|err| MappingError::new(MappingErrorKind::Invalid(format!("invalid text string: {err}"))))
+    }
+}
+
+impl ToPdfObject for String {
+    fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+        self.as_str().to_pdf_object(case)
+    }
+}
+
+impl ToPdfObject for str {
+    fn to_pdf_object(&self, _case: KeyCase) -> std::result::Result<Object, MappingError> {
+        Ok(text_string(self))
+    }
+}
+
+/// Object ids are references, which aren't followed.
+impl FromPdfObject for ObjectId {
+    fn from_pdf_object(object: &Object, _case: KeyCase) -> std::result::Result<Self, MappingError> {
+        object.as_reference().map_err(|_| MappingError::wrong_type("Reference", object))
+    }
+}
+
+impl ToPdfObject for ObjectId {
+    fn to_pdf_object(&self, _case: KeyCase) -> std::result::Result<Object, MappingError> {
+        Ok(Object::Reference(*self))
+    }
+}
+
+/// A missing key or a null value is `None`.
+impl<T: FromPdfObject> FromPdfObject for Option<T> {
+    fn from_pdf_object(object: &Object, case: KeyCase) -> std::result::Result<Self, MappingError> {
+        match object {
+            Object::Null => Ok(None),
+            object => T::from_pdf_object(object, case).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// `None` is left out of dictionaries, and is null elsewhere.
+impl<T: ToPdfObject> ToPdfObject for Option<T> {
+    fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+        match self {
+            Some(value) => value.to_pdf_object(case),
+            None => Ok(Object::Null),
+        }
+    }
+
+    fn is_absent(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<T: FromPdfObject> FromPdfObject for Vec<T> {
+    fn from_pdf_object(object: &Object, case: KeyCase) -> std::result::Result<Self, MappingError> {
+        let items = object.as_array().map_err(|_| MappingError::wrong_type("Array", object))?;
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| T::from_pdf_object(item, case).map_err(|err| err.within_index(index)))
+            .collect()
+    }
+}
+
+impl<T: ToPdfObject> ToPdfObject for Vec<T> {
+    fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+        self.as_slice().to_pdf_object(case)
+    }
+}
+
+impl<T: ToPdfObject> ToPdfObject for [T] {
+    fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+        self.iter()
+            .enumerate()
+            .map(|(index, item)| item.to_pdf_object(case).map_err(|err| err.within_index(index)))
+            .collect::<std::result::Result<Vec<Object>, _>>()
+            .map(Object::Array)
+    }
+}
+
+/// Maps are dictionaries keyed by any names, which aren't changed by the [`KeyCase`]. Null
+/// values are left out.
+impl<T: FromPdfObject> FromPdfObject for BTreeMap<String, T> {
+    fn from_pdf_object(object: &Object, case: KeyCase) -> std::result::Result<Self, MappingError> {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            _ => return Err(MappingError::wrong_type("Dictionary", object)),
+        };
+        let mut map = BTreeMap::new();
+        for (key, value) in dict {
+            let key = String::from_utf8_lossy(key).into_owned();
+            if value.is_null() {
+                continue;
+            }
+            let value = T::from_pdf_object(value, case).map_err(|err| err.within_key(&key))?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<T: ToPdfObject> ToPdfObject for BTreeMap<String, T> {
+    fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+        let mut dict = Dictionary::new();
+        for (key, value) in self {
+            if value.is_absent() {
+                continue;
+            }
+            dict.set(key.as_str(), value.to_pdf_object(case).map_err(|err| err.within_key(key))?);
+        }
+        Ok(Object::Dictionary(dict))
+    }
+}
+
+impl<T: ToPdfObject + ?Sized> ToPdfObject for &T {
+    fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+        (**self).to_pdf_object(case)
+    }
+
+    fn is_absent(&self) -> bool {
+        (**self).is_absent()
+    }
+}
+
+/// Dates are date strings like `D:20240131120000Z`.
+#[cfg(feature = "chrono")]
+mod chrono_impl {
+    use chrono::{DateTime, Local, Utc};
+
+    use super::{FromPdfObject, KeyCase, MappingError, MappingErrorKind, ToPdfObject};
+    use crate::Object;
+
+    fn parse_date(object: &Object) -> Result<DateTime<Local>, MappingError> {
+        if !matches!(object, Object::String(..)) {
+            return Err(MappingError::wrong_type("String", object));
+        }
+        object
+            .as_datetime()
+            .and_then(|date| DateTime::<Local>::try_from(date).ok())
+            .ok_or(MappingError::new(MappingErrorKind::Invalid("invalid date".to_string())))
+    }
+
+    impl FromPdfObject for DateTime<Local> {
+        fn from_pdf_object(object: &Object, _case: KeyCase) -> Result<Self, MappingError> {
+            parse_date(object)
+        }
+    }
+
+    impl FromPdfObject for DateTime<Utc> {
+        fn from_pdf_object(object: &Object, _case: KeyCase) -> Result<Self, MappingError> {
+            parse_date(object).map(|date| date.with_timezone(&Utc))
+        }
+    }
+
+    impl ToPdfObject for DateTime<Local> {
+        fn to_pdf_object(&self, _case: KeyCase) -> Result<Object, MappingError> {
+            Ok((*self).into())
+        }
+    }
+
+    impl ToPdfObject for DateTime<Utc> {
+        fn to_pdf_object(&self, _case: KeyCase) -> Result<Object, MappingError> {
+            Ok((*self).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct LineItem {
+        description: String,
+        quantity: u32,
+        unit_price: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Invoice {
+        invoice_number: i64,
+        customer: String,
+        line_items: Vec<LineItem>,
+        #[cfg(feature = "chrono")]
+        due_date: Option<chrono::DateTime<chrono::Utc>>,
+        tags: BTreeMap<String, String>,
+    }
+
+    impl FromPdfObject for LineItem {
+        fn from_pdf_object(object: &Object, case: KeyCase) -> std::result::Result<Self, MappingError> {
+            let fields = FieldReader::new(object, case)?;
+            Ok(LineItem {
+                description: fields.get("description")?,
+                quantity: fields.get("quantity")?,
+                unit_price: fields.get("unit_price")?,
+            })
+        }
+    }
+
+    impl ToPdfObject for LineItem {
+        fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+            let mut fields = FieldWriter::new(case);
+            fields.set("description", &self.description)?;
+            fields.set("quantity", &self.quantity)?;
+            fields.set("unit_price", &self.unit_price)?;
+            Ok(fields.into())
+        }
+    }
+
+    impl FromPdfObject for Invoice {
+        fn from_pdf_object(object: &Object, case: KeyCase) -> std::result::Result<Self, MappingError> {
+            let fields = FieldReader::new(object, case)?;
+            Ok(Invoice {
+                invoice_number: fields.get("invoice_number")?,
+                customer: fields.get("customer")?,
+                line_items: fields.get("line_items")?,
+                #[cfg(feature = "chrono")]
+                due_date: fields.get("due_date")?,
+                tags: fields.get::<Option<_>>("tags")?.unwrap_or_default(),
+            })
+        }
+    }
+
+    impl ToPdfObject for Invoice {
+        fn to_pdf_object(&self, case: KeyCase) -> std::result::Result<Object, MappingError> {
+            let mut fields = FieldWriter::new(case);
+            fields.set("invoice_number", &self.invoice_number)?;
+            fields.set("customer", &self.customer)?;
+            fields.set("line_items", &self.line_items)?;
+            #[cfg(feature = "chrono")]
+            fields.set("due_date", &self.due_date)?;
+            if !self.tags.is_empty() {
+                fields.set("tags", &self.tags)?;
+            }
+            Ok(fields.into())
+        }
+    }
+
+    fn invoice() -> Invoice {
+        Invoice {
+            invoice_number: 1042,
+            customer: "Société Générale".to_string(),
+            line_items: vec![
+                LineItem {
+                    description: "Paper".to_string(),
+                    quantity: 5,
+                    unit_price: 4.5,
+                },
+                LineItem {
+                    description: "Toner".to_string(),
+                    quantity: 1,
+                    unit_price: 80.0,
+                },
+            ],
+            #[cfg(feature = "chrono")]
+            due_date: Some(chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap()),
+            tags: BTreeMap::from([("priority".to_string(), "high".to_string())]),
+        }
+    }
+
+    #[test]
+    fn nested_struct_round_trip() {
+        let invoice = invoice();
+        let dict = Dictionary::serialize_from(&invoice).unwrap();
+        assert_eq!(dict.get(b"InvoiceNumber").unwrap(), &Object::Integer(1042));
+        let items = dict.get(b"LineItems").and_then(Object::as_array).unwrap();
+        assert_eq!(items[1].as_dict().unwrap().get(b"UnitPrice").unwrap(), &Object::Real(80.0));
+        assert_eq!(dict.get(b"Tags").and_then(Object::as_dict).unwrap().get(b"priority").unwrap(), &text_string("high"));
+        assert_eq!(dict.deserialize_into::<Invoice>().unwrap(), invoice);
+
+        let dict = Dictionary::serialize_from_with(&invoice, KeyCase::Camel).unwrap();
+        assert!(dict.has(b"invoiceNumber") && dict.has(b"lineItems"));
+        assert_eq!(dict.deserialize_into_with::<Invoice>(KeyCase::Camel).unwrap(), invoice);
+        assert!(dict.deserialize_into::<Invoice>().is_err());
+    }
+
+    #[test]
+    fn missing_optional_and_required_keys() {
+        let mut invoice = invoice();
+        invoice.tags.clear();
+        #[cfg(feature = "chrono")]
+        {
+            invoice.due_date = None;
+        }
+        let mut dict = Dictionary::serialize_from(&invoice).unwrap();
+        assert!(!dict.has(b"DueDate") && !dict.has(b"Tags"));
+        assert_eq!(dict.deserialize_into::<Invoice>().unwrap(), invoice);
+        // A null value is the same as a missing key.
+        dict.set("DueDate", Object::Null);
+        assert_eq!(dict.deserialize_into::<Invoice>().unwrap(), invoice);
+
+        let items = dict.get_mut(b"LineItems").and_then(Object::as_array_mut).unwrap();
+        items[1].as_dict_mut().unwrap().remove(b"Quantity");
+        let err = dict.deserialize_into::<Invoice>().unwrap_err();
+        let Error::ObjectMapping(err) = err else {
+            panic!("unexpected error {err:?}");
+        };
+        assert_eq!(err.path, "/LineItems[1]/Quantity");
+        assert_eq!(err.kind, MappingErrorKind::MissingKey);
+        assert_eq!(err.to_string(), "/LineItems[1]/Quantity: missing required key");
+    }
+
+    #[test]
+    fn wrong_types_are_located() {
+        let mut dict = Dictionary::serialize_from(&invoice()).unwrap();
+        let items = dict.get_mut(b"LineItems").and_then(Object::as_array_mut).unwrap();
+        items[0].as_dict_mut().unwrap().set("Quantity", -5);
+        let err = dict.deserialize_into::<Invoice>().unwrap_err();
+        assert_eq!(err.to_string(), "object mapping failed: /LineItems[0]/Quantity: number out of range");
+
+        dict.set("Customer", Object::Name(b"ACME".to_vec()));
+        let err = dict.deserialize_into::<Invoice>().unwrap_err();
+        assert_eq!(err.to_string(), "object mapping failed: /Customer: expected String but found Name");
+        assert!(matches!(
+            Dictionary::serialize_from(&5),
+            Err(Error::ObjectMapping(MappingError {
+                kind: MappingErrorKind::WrongType { expected: "Dictionary", found: "Integer" },
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn key_cases() {
+        assert_eq!(KeyCase::Pascal.key("unit_price"), "UnitPrice");
+        assert_eq!(KeyCase::Camel.key("unit_price"), "unitPrice");
+        assert_eq!(KeyCase::Exact.key("unit_price"), "unit_price");
+        assert_eq!(KeyCase::Pascal.key("_private__key"), "PrivateKey");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::parser::{self, ParserInput};
+use crate::parser::{self, ParseLimits, ParserInput};
 use crate::{Document, Error, Object, ObjectId, Result, Stream};
 use std::collections::BTreeMap;
 use std::num::TryFromIntError;
@@ -39,7 +39,14 @@ fn default() -> Self {
 impl ObjectStream {
     /// Parse an existing object stream
     pub fn new(stream: &mut Stream) -> Result<ObjectStream> {
-        let _ = stream.decompress();
+        Self::with_limits(stream, ParseLimits::default())
+    }
+
+    /// Parse an existing object stream, with the limits of the reader options.
+    pub(crate) fn with_limits(stream: &mut Stream, limits: ParseLimits) -> Result<ObjectStream> {
+        if stream.is_compressed() {
+            stream.decompress()?;
+        }
 
         if stream.content.is_empty() {
             return Ok(ObjectStream {
@@ -80,7 +87,7 @@ pub fn new(stream: &mut Stream) -> Result<ObjectStream> {
                 warn!("out-of-bounds offset in object stream");
                 return None;
             }
-            let object = parser::direct_object(ParserInput::new_extra(&stream.content[offset..], "direct object"))?;
+            let object = parser::direct_object(ParserInput::new_extra(&stream.content[offset..], limits))?;
 
             Some(((id, 0), object))
         };
@@ -258,12 +265,7 @@ pub fn can_be_compressed(id: ObjectId, obj: &Object, doc: &Document) -> bool {
                         b"ObjStm" => return false,
                         
                         // Catalog can only be excluded in linearized PDFs
-                        b"Catalog" => {
-                            // Check if PDF is linearized
-                            if Self::is_linearized(doc) {
-                                return false;
-                            }
-                        }
+                        b"Catalog" if Self::is_linearized(doc) => return false,
                         
                         // Page, Pages, and all other types CAN be compressed
                         _ => {}
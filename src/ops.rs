@@ -0,0 +1,379 @@
+//! One-call document operations for command line tools.
+//!
+//! Each operation reads its inputs, runs the library APIs and writes its outputs, returning a
+//! [`Report`] of what was done. Errors are wrapped in [`Error::Ops`] naming the file that failed.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{Dictionary, Document, Error, FileIdPolicy, Object, ObjectId, Result, SaveOptions};
+
+/// Summary of an operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Number of pages processed
+    pub pages: usize,
+    /// Bytes read from all inputs
+    pub bytes_in: u64,
+    /// Bytes written to all outputs
+    pub bytes_out: u64,
+    /// Problems that didn't stop the operation
+    pub warnings: Vec<String>,
+}
+
+/// A PDF to read, from a file or any reader.
+pub enum Input<'a> {
+    Path(PathBuf),
+    Reader { name: String, reader: Box<dyn Read + 'a> },
+}
+
+impl<'a> Input<'a> {
+    /// Read from `reader`, using `name` to refer to the input in reports and errors.
+    pub fn reader<S: Into<String>, R: Read + 'a>(name: S, reader: R) -> Self {
+        Input::Reader {
+            name: name.into(),
+            reader: Box::new(reader),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Input::Path(path) => path.display().to_string(),
+            Input::Reader { name, .. } => name.clone(),
+        }
+    }
+
+    /// Load the document, returning it with its name and size in bytes.
+    fn load(self) -> Result<(Document, String, u64)> {
+        let name = self.name();
+        let result = match self {
+            Input::Path(path) => fs::read(path).map_err(Error::from),
+            Input::Reader { mut reader, .. } => {
+                let mut buffer = Vec::new();
+                reader.read_to_end(&mut buffer).map(|_| buffer).map_err(Error::from)
+            }
+        };
+        let buffer = result.map_err(|err| context(&name, err))?;
+        let doc = Document::load_mem(&buffer).map_err(|err| context(&name, err))?;
+        Ok((doc, name, buffer.len() as u64))
+    }
+
+    /// Load the document, failing if it can't be decrypted without a password.
+    fn load_decrypted(self) -> Result<(Document, String, u64)> {
+        let (doc, name, size) = self.load()?;
+        if doc.is_encrypted() {
+            return Err(context(&name, Error::InvalidPassword));
+        }
+        Ok((doc, name, size))
+    }
+}
+
+impl From<PathBuf> for Input<'_> {
+    fn from(path: PathBuf) -> Self {
+        Input::Path(path)
+    }
+}
+
+impl From<&Path> for Input<'_> {
+    fn from(path: &Path) -> Self {
+        Input::Path(path.to_path_buf())
+    }
+}
+
+impl From<&str> for Input<'_> {
+    fn from(path: &str) -> Self {
+        Input::Path(path.into())
+    }
+}
+
+impl From<String> for Input<'_> {
+    fn from(path: String) -> Self {
+        Input::Path(path.into())
+    }
+}
+
+/// Where to write a PDF, a file or any writer.
+pub enum Output<'a> {
+    Path(PathBuf),
+    Writer(Box<dyn Write + 'a>),
+}
+
+impl<'a> Output<'a> {
+    pub fn writer<W: Write + 'a>(writer: W) -> Self {
+        Output::Writer(Box::new(writer))
+    }
+
+    /// Save the document, returning the number of bytes written.
+    fn save(self, doc: &mut Document, options: Option<SaveOptions>) -> Result<u64> {
+        let name = match &self {
+            Output::Path(path) => path.display().to_string(),
+            Output::Writer(_) => "output".to_string(),
+        };
+        let mut buffer = Vec::new();
+        match options {
+            Some(options) => doc.save_with_options(&mut buffer, options),
+            None => doc.save_to(&mut buffer),
+        }
+        .map_err(|err| context(&name, err.into()))?;
+        let written = match self {
+            Output::Path(path) => File::create(path).and_then(|mut file| file.write_all(&buffer)),
+            Output::Writer(mut writer) => writer.write_all(&buffer).and_then(|_| writer.flush()),
+        };
+        written.map_err(|err| context(&name, err.into()))?;
+        Ok(buffer.len() as u64)
+    }
+}
+
+impl From<PathBuf> for Output<'_> {
+    fn from(path: PathBuf) -> Self {
+        Output::Path(path)
+    }
+}
+
+impl From<&Path> for Output<'_> {
+    fn from(path: &Path) -> Self {
+        Output::Path(path.to_path_buf())
+    }
+}
+
+impl From<&str> for Output<'_> {
+    fn from(path: &str) -> Self {
+        Output::Path(path.into())
+    }
+}
+
+fn context(file: &str, err: Error) -> Error {
+    Error::Ops {
+        file: file.to_string(),
+        source: Box::new(err),
+    }
+}
+
+/// Append the pages of all inputs, in order, into one document.
+///
+/// The pages keep their content and resources, and their page-piece data. Document-level data
+/// other than the pages, like outlines and forms, is only kept from the first input, except for
+/// the page-piece data of the catalog: that of applications the first input has none for is
+/// taken from the others.
+pub fn merge<'a, I, T>(inputs: I, output: impl Into<Output<'a>>) -> Result<Report>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<Input<'a>>,
+{
+    let mut report = Report::default();
+    let mut merged: Option<Document> = None;
+    for input in inputs {
+        let (doc, name, size) = input.into().load_decrypted()?;
+        report.bytes_in += size;
+        report.pages += doc.get_pages().len();
+        match &mut merged {
+            None => merged = Some(doc),
+            Some(merged) => {
+                let catalog = doc.catalog().map_err(|err| context(&name, err))?;
+                let dropped: Vec<String> = [b"Outlines".as_slice(), b"AcroForm", b"Names"]
+                    .into_iter()
+                    .filter(|key| catalog.has(key))
+                    .map(|key| String::from_utf8_lossy(key).into_owned())
+                    .collect();
+                if !dropped.is_empty() {
+                    report.warnings.push(format!("{name}: dropped {}", dropped.join(", ")));
+                }
+                append_pages(merged, doc).map_err(|err| context(&name, err))?;
+            }
+        }
+    }
+    let Some(mut merged) = merged else {
+        return Ok(report);
+    };
+    merged.prune_objects();
+    report.bytes_out = output.into().save(&mut merged, None)?;
+    Ok(report)
+}
+
+/// Write every page of the input to its own file `<name>-<page number>.pdf` in `out_dir`.
+///
+/// The outline is left out of the single page documents.
+pub fn split<'a>(input: impl Into<Input<'a>>, out_dir: impl AsRef<Path>) -> Result<Report> {
+    let out_dir = out_dir.as_ref();
+    let (mut doc, name, size) = input.into().load_decrypted()?;
+    let stem = Path::new(&name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or(name.clone());
+    let page_ids: Vec<ObjectId> = doc.page_iter().collect();
+    for page_id in &page_ids {
+        localize_inherited_attributes(&mut doc, *page_id).map_err(|err| context(&name, err))?;
+    }
+    let pages_id = doc
+        .catalog()
+        .and_then(|catalog| catalog.get(b"Pages"))
+        .and_then(Object::as_reference)
+        .map_err(|err| context(&name, err))?;
+    let mut report = Report {
+        pages: page_ids.len(),
+        bytes_in: size,
+        ..Report::default()
+    };
+    if doc.catalog().is_ok_and(|catalog| catalog.has(b"Outlines")) {
+        report.warnings.push(format!("{name}: dropped Outlines"));
+    }
+    fs::create_dir_all(out_dir).map_err(|err| context(&out_dir.display().to_string(), err.into()))?;
+
+    for (index, page_id) in page_ids.iter().enumerate() {
+        let mut page_doc = doc.clone();
+        keep_single_page(&mut page_doc, pages_id, *page_id).map_err(|err| context(&name, err))?;
+        let path = out_dir.join(format!("{stem}-{}.pdf", index + 1));
+        report.bytes_out += Output::Path(path).save(&mut page_doc, None)?;
+    }
+    Ok(report)
+}
+
+/// Extract the text of all pages, each page's text followed by a line break.
+///
+/// Pages whose text can't be extracted are reported as warnings.
+pub fn extract_text<'a>(input: impl Into<Input<'a>>) -> Result<(String, Report)> {
+    let (doc, name, size) = input.into().load_decrypted()?;
+    let pages = doc.get_pages();
+    let mut report = Report {
+        pages: pages.len(),
+        bytes_in: size,
+        ..Report::default()
+    };
+    let mut text = String::new();
+    for page_number in pages.keys() {
+        match doc.extract_text(&[*page_number]) {
+            Ok(page_text) => text.push_str(&page_text),
+            Err(err) => report.warnings.push(format!("{name}: page {page_number}: {err}")),
+        }
+    }
+    Ok((text, report))
+}
+
+/// Write a decrypted copy of the input.
+pub fn decrypt<'a>(input: impl Into<Input<'a>>, password: &str, output: impl Into<Output<'a>>) -> Result<Report> {
+    let (mut doc, name, size) = input.into().load()?;
+    if doc.is_encrypted() {
+        doc.decrypt(password).map_err(|err| context(&name, err))?;
+    } else if doc.encryption_state.is_none() {
+        return Err(context(&name, Error::NotEncrypted));
+    }
+    doc.remove_encryption(FileIdPolicy::Preserve).map_err(|err| context(&name, err))?;
+    let mut report = Report {
+        pages: doc.get_pages().len(),
+        bytes_in: size,
+        ..Report::default()
+    };
+    report.bytes_out = output.into().save(&mut doc, None)?;
+    Ok(report)
+}
+
+/// Compress the streams of the input and save it with `options`.
+pub fn compress<'a>(input: impl Into<Input<'a>>, output: impl Into<Output<'a>>, options: SaveOptions) -> Result<Report> {
+    let (mut doc, _, size) = input.into().load_decrypted()?;
+    doc.compress();
+    let mut report = Report {
+        pages: doc.get_pages().len(),
+        bytes_in: size,
+        ..Report::default()
+    };
+    report.bytes_out = output.into().save(&mut doc, Some(options))?;
+    if report.bytes_out >= report.bytes_in {
+        report.warnings.push("output is not smaller than the input".to_string());
+    }
+    Ok(report)
+}
+
+/// Move the pages of `doc` to the end of the page tree of `target`.
+fn append_pages(target: &mut Document, mut doc: Document) -> Result<()> {
+    let page_ids: Vec<ObjectId> = doc.page_iter().collect();
+    for page_id in page_ids {
+        localize_inherited_attributes(&mut doc, page_id)?;
+    }
+    doc.renumber_objects_with(target.max_id + 1);
+    let page_ids: Vec<ObjectId> = doc.page_iter().collect();
+    let piece_info = doc
+        .catalog()?
+        .get_deref(b"PieceInfo", &doc)
+        .and_then(Object::as_dict)
+        .ok()
+        .cloned();
+
+    let pages_id = target.catalog()?.get(b"Pages")?.as_reference()?;
+    target.max_id = target.max_id.max(doc.max_id);
+    target.objects.extend(doc.objects);
+    if let Some(piece_info) = piece_info {
+        merge_catalog_piece_info(target, piece_info)?;
+    }
+    for page_id in &page_ids {
+        target.get_dictionary_mut(*page_id)?.set("Parent", pages_id);
+    }
+    let pages = target.get_dictionary_mut(pages_id)?;
+    let count = pages.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+    pages.set("Count", count + page_ids.len() as i64);
+    let kids = pages.get_mut(b"Kids")?.as_array_mut()?;
+    kids.extend(page_ids.into_iter().map(Object::Reference));
+    Ok(())
+}
+
+/// Add the applications of `piece_info` that the /PieceInfo of the catalog of `target` has no
+/// data for.
+fn merge_catalog_piece_info(target: &mut Document, piece_info: Dictionary) -> Result<()> {
+    let existing = target.catalog()?.get(b"PieceInfo").and_then(Object::as_reference).ok();
+    let merged = match existing {
+        Some(id) => target.get_dictionary_mut(id)?,
+        None => {
+            let catalog = target.catalog_mut()?;
+            if !catalog.get(b"PieceInfo").is_ok_and(|piece_info| piece_info.as_dict().is_ok()) {
+                catalog.set("PieceInfo", Dictionary::new());
+            }
+            catalog.get_mut(b"PieceInfo")?.as_dict_mut()?
+        }
+    };
+    for (application, data) in piece_info {
+        if !merged.has(&application) {
+            merged.set(application, data);
+        }
+    }
+    Ok(())
+}
+
+/// Make `page_id` the only page of the document, directly under the page tree root.
+fn keep_single_page(doc: &mut Document, pages_id: ObjectId, page_id: ObjectId) -> Result<()> {
+    doc.catalog_mut()?.remove(b"Outlines");
+    let pages = doc.get_dictionary_mut(pages_id)?;
+    pages.set("Kids", vec![Object::Reference(page_id)]);
+    pages.set("Count", 1);
+    doc.get_dictionary_mut(page_id)?.set("Parent", pages_id);
+    doc.prune_objects();
+    Ok(())
+}
+
+/// Copy the attributes a page inherits from its ancestors onto the page itself, so it can be
+/// moved to another page tree.
+fn localize_inherited_attributes(doc: &mut Document, page_id: ObjectId) -> Result<()> {
+    doc.localize_page_resources(page_id)?;
+    for key in [b"MediaBox".as_slice(), b"CropBox", b"Rotate"] {
+        if doc.get_dictionary(page_id)?.has(key) {
+            continue;
+        }
+        let mut node = doc.get_dictionary(page_id)?;
+        let mut inherited = None;
+        let mut depth = 0;
+        while let Ok(parent_id) = node.get(b"Parent").and_then(Object::as_reference) {
+            depth += 1;
+            if depth > 64 {
+                break;
+            }
+            node = doc.get_dictionary(parent_id)?;
+            if let Ok(value) = node.get(key) {
+                inherited = Some(value.clone());
+                break;
+            }
+        }
+        if let Some(value) = inherited {
+            doc.get_dictionary_mut(page_id)?.set(key, value);
+        }
+    }
+    Ok(())
+}
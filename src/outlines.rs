@@ -1,12 +1,149 @@
 use indexmap::IndexMap;
+use log::warn;
+use std::collections::HashSet;
 
-use super::{Destination, Dictionary, Document, Error, Object, Result};
+use super::{Destination, Dictionary, Document, Error, Object, ObjectId, Result};
+use crate::decode_text_string;
+
+/// Maximum number of /Parent entries followed looking for the outline root.
+const MAX_OUTLINE_DEPTH: usize = 64;
 
 pub enum Outline {
     Destination(Destination),
     SubOutlines(Vec<Outline>),
 }
 
+/// An item of the document outline, with its open state, as read by [`Document::outline_tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineItem {
+    pub id: ObjectId,
+    pub title: String,
+    /// Whether the children are shown, from a positive /Count. A negative /Count collapses them.
+    pub open: bool,
+    /// Whether the item is shown when the document is opened, i.e. all its ancestors are open.
+    pub visible: bool,
+    pub children: Vec<OutlineItem>,
+}
+
+/// A problem found reading the document outline that didn't stop it from being read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutlineIssue {
+    /// A /First or /Next entry leads to the item `id` again. The chain stops there.
+    Cycle(ObjectId),
+    /// The /Next chain skips the item `id`, found walking back from /Last through /Prev. It is
+    /// read in the place /Prev gives it.
+    SkippedByNext(ObjectId),
+    /// The item `id` belongs to the outline through its /Parent entries, but can't be reached
+    /// from the outline root. It is left out.
+    Orphan(ObjectId),
+}
+
+/// The document outline read by [`Document::outline_tree`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutlineTree {
+    /// The top level items, which are always visible.
+    pub items: Vec<OutlineItem>,
+    pub issues: Vec<OutlineIssue>,
+}
+
+/// Reads the items of an outline, each once.
+struct OutlineReader<'a> {
+    document: &'a Document,
+    reached: HashSet<ObjectId>,
+    issues: Vec<OutlineIssue>,
+}
+
+impl OutlineReader<'_> {
+    /// The items below `parent`, shown if `visible` is true.
+    fn children(&mut self, parent: &Dictionary, visible: bool) -> Vec<OutlineItem> {
+        let mut items = Vec::new();
+        for id in self.chain(parent) {
+            let Ok(node) = self.document.get_dictionary(id) else {
+                continue;
+            };
+            let open = node.get(b"Count").and_then(Object::as_i64).is_ok_and(|count| count > 0);
+            let title = node
+                .get(b"Title")
+                .and_then(|title| self.document.dereference(title))
+                .and_then(|(_, title)| decode_text_string(title))
+                .unwrap_or_default();
+            let children = self.children(node, visible && open);
+            items.push(OutlineItem {
+                id,
+                title,
+                open,
+                visible,
+                children,
+            });
+        }
+        items
+    }
+
+    /// The ids of the items below `parent` in order: the /Next chain from /First, with the items
+    /// it skips found walking back from /Last. /Last is optional.
+    fn chain(&mut self, parent: &Dictionary) -> Vec<ObjectId> {
+        let link = |node: &Dictionary, key: &[u8]| node.get(key).and_then(Object::as_reference).ok();
+        let mut ids = Vec::new();
+        let mut next = link(parent, b"First");
+        while let Some(id) = next {
+            if !self.reached.insert(id) {
+                warn!("outline item {} {} is reached again, ignoring the rest of its chain", id.0, id.1);
+                self.issues.push(OutlineIssue::Cycle(id));
+                break;
+            }
+            ids.push(id);
+            next = self.document.get_dictionary(id).ok().and_then(|node| link(node, b"Next"));
+        }
+
+        let mut insert_at = ids.len();
+        let mut walked = HashSet::new();
+        let mut prev = link(parent, b"Last");
+        while let Some(id) = prev.filter(|id| walked.insert(*id)) {
+            if let Some(position) = ids.iter().position(|&chained| chained == id) {
+                insert_at = position;
+            } else if self.reached.insert(id) {
+                warn!("outline item {} {} is skipped by the Next chain", id.0, id.1);
+                self.issues.push(OutlineIssue::SkippedByNext(id));
+                ids.insert(insert_at, id);
+            } else {
+                break;
+            }
+            prev = self.document.get_dictionary(id).ok().and_then(|node| link(node, b"Prev"));
+        }
+        ids
+    }
+
+    /// Report the items whose /Parent entries lead to `root` that weren't reached.
+    fn report_orphans(&mut self, root: ObjectId) {
+        for (&id, object) in &self.document.objects {
+            let Ok(node) = object.as_dict() else {
+                continue;
+            };
+            if self.reached.contains(&id) || !node.has(b"Title") {
+                continue;
+            }
+            let mut parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+            for _ in 0..MAX_OUTLINE_DEPTH {
+                match parent {
+                    Some(parent) if parent == root => {
+                        self.issues.push(OutlineIssue::Orphan(id));
+                        break;
+                    }
+                    Some(id) => {
+                        parent = self
+                            .document
+                            .get_dictionary(id)
+                            .and_then(|node| node.get(b"Parent"))
+                            .and_then(Object::as_reference)
+                            .ok();
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
 impl Document {
     pub fn get_outline(
         &self, node: &Dictionary, named_destinations: &mut IndexMap<Vec<u8>, Destination>,
@@ -17,7 +154,7 @@ pub fn get_outline(
                 return self.build_outline_result(node.get(b"Dest")?, node.get(b"Title")?, named_destinations);
             }
         };
-        let command = action.get(b"S")?.as_name()?;
+        let command = self.get_with_aliases(action, b"S")?.as_name()?;
         if command != b"GoTo" && command != b"GoToR" {
             return Err(Error::InvalidOutline("Expected GoTo or GoToR".to_string()));
         }
@@ -32,9 +169,40 @@ pub fn get_outline(
         self.build_outline_result(action.get(b"D")?, self.get_object(title_ref)?, named_destinations)
     }
 
+    /// Read the document outline with the open state of its items. The items are followed from
+    /// /First through /Next, stopping at cycles, and the items the /Next chain skips are found
+    /// walking back from /Last, if there is one. Problems are listed in [`OutlineTree::issues`].
+    ///
+    /// Returns [`Error::NoOutline`] if the catalog has no /Outlines.
+    pub fn outline_tree(&self) -> Result<OutlineTree> {
+        let root = self.catalog()?.get(b"Outlines").map_err(|_| Error::NoOutline)?;
+        let (root_id, root) = self.dereference(root)?;
+        let root = root.as_dict()?;
+        let mut reader = OutlineReader {
+            document: self,
+            reached: root_id.into_iter().collect(),
+            issues: Vec::new(),
+        };
+        let items = reader.children(root, true);
+        if let Some(root_id) = root_id {
+            reader.report_orphans(root_id);
+        }
+        Ok(OutlineTree {
+            items,
+            issues: reader.issues,
+        })
+    }
+
     pub fn get_outlines(
-        &self, mut node: Option<Object>, mut outlines: Option<Vec<Outline>>,
+        &self, node: Option<Object>, outlines: Option<Vec<Outline>>,
         named_destinations: &mut IndexMap<Vec<u8>, Destination>,
+    ) -> Result<Option<Vec<Outline>>> {
+        self.collect_outlines(node, outlines, named_destinations, &mut HashSet::new())
+    }
+
+    fn collect_outlines(
+        &self, mut node: Option<Object>, mut outlines: Option<Vec<Outline>>,
+        named_destinations: &mut IndexMap<Vec<u8>, Destination>, visited: &mut HashSet<ObjectId>,
     ) -> Result<Option<Vec<Outline>>> {
         if outlines.is_none() {
             outlines = Some(Vec::new());
@@ -73,9 +241,10 @@ pub fn get_outlines(
                     outlines.push(outline);
                 }
             }
-            if let Ok(first) = node.get(b"First") {
+            if let Some(first) = node.get(b"First").ok().filter(|first| !is_revisited(first, visited)) {
                 let sub_outlines = Vec::new();
-                let sub_outlines = self.get_outlines(Some(first.clone()), Some(sub_outlines), named_destinations)?;
+                let sub_outlines =
+                    self.collect_outlines(Some(first.clone()), Some(sub_outlines), named_destinations, visited)?;
                 if let Some(sub_outlines) = sub_outlines {
                     if !sub_outlines.is_empty() {
                         if let Some(ref mut outlines) = outlines {
@@ -84,6 +253,9 @@ pub fn get_outlines(
                     }
                 }
             }
+            if node.get(b"Next").is_ok_and(|next| is_revisited(next, visited)) {
+                break;
+            }
             node = match self.get_dict_in_dict(node, b"Next") {
                 Ok(n) => n,
                 Err(_) => break,
@@ -117,3 +289,115 @@ fn build_outline_result(
         Ok(Some(outline))
     }
 }
+
+/// Whether `link` references an outline item already in `visited`, adding it otherwise.
+fn is_revisited(link: &Object, visited: &mut HashSet<ObjectId>) -> bool {
+    let Ok(id) = link.as_reference() else {
+        return false;
+    };
+    let revisited = !visited.insert(id);
+    if revisited {
+        warn!("outline item {} {} is reached again, ignoring the rest of its chain", id.0, id.1);
+    }
+    revisited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bookmark;
+    use crate::creator::tests::create_document_with_texts;
+
+    fn titles(items: &[OutlineItem]) -> Vec<&str> {
+        items.iter().map(|item| item.title.as_str()).collect()
+    }
+
+    #[test]
+    fn collapsed_subtree_round_trips() {
+        let mut doc = create_document_with_texts(&["one", "two"]);
+        let page = doc.get_pages()[&1];
+        let mut add = |title: &str, open: bool, parent: Option<u32>| {
+            let mut bookmark = Bookmark::new(title.to_string(), [0.0; 3], 0, page);
+            bookmark.open = open;
+            doc.add_bookmark(bookmark, parent)
+        };
+        let a = add("A", true, None);
+        add("A1", true, Some(a));
+        let a2 = add("A2", false, Some(a));
+        add("A2a", true, Some(a2));
+        let b = add("B", false, None);
+        let b1 = add("B1", true, Some(b));
+        add("B1a", true, Some(b1));
+        let outline_id = doc.build_outline().unwrap();
+        doc.catalog_mut().unwrap().set("Outlines", outline_id);
+
+        let count = |doc: &Document, id| doc.get_dictionary(id).unwrap().get(b"Count").unwrap().as_i64().unwrap();
+        assert_eq!(count(&doc, outline_id), 4);
+        let tree = doc.outline_tree().unwrap();
+        let [a, b] = &tree.items[..] else { panic!("{tree:?}") };
+        assert_eq!((count(&doc, a.id), count(&doc, a.children[1].id)), (2, -1));
+        assert_eq!((count(&doc, b.id), count(&doc, b.children[0].id)), (-2, 1));
+
+        // Some producers leave out /Last.
+        doc.get_dictionary_mut(a.id).unwrap().remove(b"Last");
+        let mut buffer = Vec::new();
+        doc.save_to(&mut buffer).unwrap();
+        let doc = Document::load_mem(&buffer).unwrap();
+
+        let tree = doc.outline_tree().unwrap();
+        assert_eq!(tree.issues, []);
+        let [a, b] = &tree.items[..] else { panic!("{tree:?}") };
+        assert_eq!(titles(&tree.items), ["A", "B"]);
+        assert_eq!(titles(&a.children), ["A1", "A2"]);
+        assert_eq!(titles(&a.children[1].children), ["A2a"]);
+        assert_eq!(titles(&b.children[0].children), ["B1a"]);
+        let states = |item: &OutlineItem| (item.open, item.visible);
+        // Items without children have no /Count and read as closed.
+        let expected = [(true, true), (false, true), (false, true)];
+        assert_eq!([states(a), states(&a.children[0]), states(&a.children[1])], expected);
+        assert_eq!(states(&a.children[1].children[0]), (false, false));
+        assert_eq!([states(b), states(&b.children[0])], [(false, true), (true, false)]);
+        assert_eq!(states(&b.children[0].children[0]), (false, false));
+    }
+
+    #[test]
+    fn broken_chains_are_reported() {
+        let mut doc = create_document_with_texts(&["one"]);
+        let page = doc.get_pages()[&1];
+        let root = doc.new_object_id();
+        let ids: Vec<ObjectId> = (0..4).map(|_| doc.new_object_id()).collect();
+        let item = |title: &str| {
+            dictionary! {
+                "Title" => Object::string_literal(title),
+                "Parent" => root,
+                "Dest" => vec![page.into(), "Fit".into()],
+            }
+        };
+        // The /Next chain skips the second item, which only /Prev links, the third item lists the
+        // first as its child, and the fourth isn't linked at all.
+        let mut items = [item("one"), item("two"), item("three"), item("four")];
+        items[0].set("Next", ids[2]);
+        items[1].set("Prev", ids[0]);
+        items[2].set("Prev", ids[1]);
+        items[2].set("First", ids[0]);
+        items[2].set("Count", 1);
+        for (id, item) in ids.iter().zip(items) {
+            doc.objects.insert(*id, item.into());
+        }
+        let outline = dictionary! { "Type" => "Outlines", "First" => ids[0], "Last" => ids[2], "Count" => 3 };
+        doc.objects.insert(root, outline.into());
+        doc.catalog_mut().unwrap().set("Outlines", root);
+
+        let tree = doc.outline_tree().unwrap();
+        assert_eq!(titles(&tree.items), ["one", "two", "three"]);
+        assert!(tree.items.iter().all(|item| item.children.is_empty()));
+        let expected = [
+            OutlineIssue::SkippedByNext(ids[1]),
+            OutlineIssue::Cycle(ids[0]),
+            OutlineIssue::Orphan(ids[3]),
+        ];
+        assert_eq!(tree.issues, expected);
+
+        assert!(doc.get_outlines(None, None, &mut IndexMap::new()).is_ok());
+    }
+}
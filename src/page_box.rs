@@ -0,0 +1,73 @@
+use crate::preview::DEFAULT_PAGE_SIZE;
+use crate::{Dictionary, Document, ObjectId, Result};
+
+impl Document {
+    /// The media box of a page, inherited from the page tree if the page has none, as its
+    /// lower-left and upper-right corners. Boxes given by other corners, as some CAD exports
+    /// write `[0 842 595 0]`, are normalized. Pages without one are US Letter.
+    pub fn page_media_box(&self, page_id: ObjectId) -> Result<[f32; 4]> {
+        let page = self.get_dictionary(page_id)?;
+        let (width, height) = DEFAULT_PAGE_SIZE;
+        Ok(self.page_box(page, b"MediaBox").unwrap_or([0.0, 0.0, width, height]))
+    }
+
+    /// Convert `point` from the user space of a page to coordinates relative to the lower-left
+    /// corner of its normalized media box, where the page's width and height are the upper
+    /// right. The page's /Rotate isn't applied.
+    pub fn user_to_page_coords(&self, page_id: ObjectId, (x, y): (f32, f32)) -> Result<(f32, f32)> {
+        let [llx, lly, ..] = self.page_media_box(page_id)?;
+        Ok((x - llx, y - lly))
+    }
+
+    /// Convert `point` from coordinates relative to the lower-left corner of a page's normalized
+    /// media box to its user space, the inverse of [`Document::user_to_page_coords`].
+    pub fn page_to_user_coords(&self, page_id: ObjectId, (x, y): (f32, f32)) -> Result<(f32, f32)> {
+        let [llx, lly, ..] = self.page_media_box(page_id)?;
+        Ok((x + llx, y + lly))
+    }
+
+    /// The normalized rectangle `key` of `page`, inherited from the page tree.
+    pub(crate) fn page_box(&self, page: &Dictionary, key: &[u8]) -> Option<[f32; 4]> {
+        let rect = self.inherited_page_attribute(page, key)?.as_array().ok()?;
+        let values: Vec<f32> = rect
+            .iter()
+            .filter_map(|value| self.dereference(value).ok()?.1.as_float().ok())
+            .collect();
+        Some(normalized(values.try_into().ok()?))
+    }
+}
+
+/// `rect` given by its lower-left and upper-right corners.
+pub(crate) fn normalized([x1, y1, x2, y2]: [f32; 4]) -> [f32; 4] {
+    [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Object;
+    use crate::creator::tests::create_document;
+
+    #[test]
+    fn reversed_and_negative_media_boxes() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        assert_eq!(doc.page_media_box(page_id).unwrap(), [0.0, 0.0, 595.0, 842.0]);
+
+        let set_media_box = |doc: &mut Document, media_box: [i64; 4]| {
+            let media_box = media_box.into_iter().map(Object::from).collect::<Vec<_>>();
+            doc.get_dictionary_mut(page_id).unwrap().set("MediaBox", media_box);
+        };
+        set_media_box(&mut doc, [0, 842, 595, 0]);
+        assert_eq!(doc.page_media_box(page_id).unwrap(), [0.0, 0.0, 595.0, 842.0]);
+        assert_eq!(doc.user_to_page_coords(page_id, (10.0, 20.0)).unwrap(), (10.0, 20.0));
+
+        set_media_box(&mut doc, [300, 400, -300, -400]);
+        assert_eq!(doc.page_media_box(page_id).unwrap(), [-300.0, -400.0, 300.0, 400.0]);
+        assert_eq!(doc.user_to_page_coords(page_id, (0.0, 0.0)).unwrap(), (300.0, 400.0));
+        assert_eq!(
+            doc.page_to_user_coords(page_id, (600.0, 800.0)).unwrap(),
+            (300.0, 400.0)
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::content_walker::ContentWalker;
+use crate::{Dictionary, Document, Object, ObjectId, Result};
+
+/// Filters that compress photographic or bilevel images, as scanners write them, in full and
+/// abbreviated inline image form.
+const SCAN_FILTERS: [&[u8]; 5] = [b"DCTDecode", b"DCT", b"CCITTFaxDecode", b"CCF", b"JBIG2Decode"];
+
+/// What a page is likely to be, see [`Document::classify_pages`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageClass {
+    /// Text and graphics produced digitally, for text extraction
+    BornDigital,
+    /// An image of a scanned page without text, for OCR
+    ScannedImageOnly,
+    /// An image of a scanned page under invisible text recognized from it
+    ScannedWithOcrText,
+    /// Scanned images next to or under visible text
+    Mixed,
+}
+
+/// Thresholds for [`Document::classify_pages_with_options`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageClassOptions {
+    /// Share of the page area images must cover for the page to be an image of a scanned page
+    pub scan_coverage: f32,
+    /// Share of the page area scanner-compressed images must cover to make a page with less
+    /// image coverage mixed
+    pub mixed_coverage: f32,
+    /// Characters a page must show to have text, fewer are taken for stray marks
+    pub min_text_chars: usize,
+    /// Share of the characters that must be invisible, in text render mode 3, for the text to be
+    /// an OCR layer
+    pub invisible_text_ratio: f32,
+}
+
+impl Default for PageClassOptions {
+    fn default() -> Self {
+        PageClassOptions {
+            scan_coverage: 0.85,
+            mixed_coverage: 0.25,
+            min_text_chars: 16,
+            invisible_text_ratio: 0.5,
+        }
+    }
+}
+
+/// The signals a page is classified by.
+#[derive(Debug, Default)]
+struct PageSignals {
+    /// Area covered by images, summed over their bounding boxes clipped to the page
+    image_area: f32,
+    /// Area covered by images compressed with [`SCAN_FILTERS`]
+    scan_image_area: f32,
+    visible_chars: usize,
+    invisible_chars: usize,
+}
+
+impl Document {
+    /// Classify the pages of the document as born-digital or scanned, with the default
+    /// [`PageClassOptions`]. Returns the pages by page number.
+    pub fn classify_pages(&self) -> Vec<(u32, PageClass)> {
+        self.classify_pages_with_options(&PageClassOptions::default())
+    }
+
+    /// Classify the pages of the document as born-digital or scanned, by cheap signals: the area
+    /// images cover, how much text is shown and how much of it is invisible, and whether images
+    /// are compressed as scanners do. Images are placed by their transformation, their data is
+    /// never decoded. Pages whose content can't be read are born-digital.
+    pub fn classify_pages_with_options(&self, options: &PageClassOptions) -> Vec<(u32, PageClass)> {
+        self.get_pages()
+            .into_iter()
+            .map(|(number, page_id)| {
+                let class = match self.page_signals(page_id) {
+                    Ok(signals) => signals.classify(options),
+                    Err(_) => PageClass::BornDigital,
+                };
+                (number, class)
+            })
+            .collect()
+    }
+
+    fn page_signals(&self, page_id: ObjectId) -> Result<PageSignals> {
+        let page_box = self.page_media_box(page_id)?;
+        let page_area = (page_box[2] - page_box[0]) * (page_box[3] - page_box[1]);
+        let mut signals = PageSignals::default();
+        let mut render_mode = 0;
+        let mut saved_render_modes = vec![];
+        ContentWalker::new(self).walk_page(page_id, |operation, context| {
+            let operands = &operation.operands;
+            match operation.operator.as_str() {
+                "q" => saved_render_modes.push(render_mode),
+                "Q" => render_mode = saved_render_modes.pop().unwrap_or(render_mode),
+                "Tr" => render_mode = operands.first().and_then(|mode| mode.as_i64().ok()).unwrap_or(0),
+                "Tj" | "'" | "\"" | "TJ" => {
+                    let chars = match operands.last() {
+                        Some(Object::String(bytes, _)) => bytes.len(),
+                        Some(Object::Array(items)) => items
+                            .iter()
+                            .filter_map(|item| item.as_str().ok())
+                            .map(<[u8]>::len)
+                            .sum(),
+                        _ => 0,
+                    };
+                    if render_mode == 3 {
+                        signals.invisible_chars += chars;
+                    } else {
+                        signals.visible_chars += chars;
+                    }
+                }
+                "Do" => {
+                    let image = operands
+                        .first()
+                        .and_then(|name| name.as_name().ok())
+                        .and_then(|name| context.resource(b"XObject", name))
+                        .and_then(|(_, object)| object.as_stream().ok())
+                        .filter(|stream| stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image"));
+                    if let Some(image) = image {
+                        signals.add_image(self, &image.dict, &context.ctm, page_box);
+                    }
+                }
+                "BI" => {
+                    if let Some(Object::Stream(image)) = operands.first() {
+                        signals.add_image(self, &image.dict, &context.ctm, page_box);
+                    }
+                }
+                _ => {}
+            }
+        })?;
+        if page_area > 0.0 {
+            signals.image_area /= page_area;
+            signals.scan_image_area /= page_area;
+        }
+        Ok(signals)
+    }
+}
+
+impl PageSignals {
+    /// Add the image drawn with `ctm` by the unit square, clipped to `page_box`.
+    fn add_image(&mut self, doc: &Document, image: &Dictionary, ctm: &[f32; 6], page_box: [f32; 4]) {
+        let [a, b, c, d, e, f] = *ctm;
+        let corners =
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)].map(|(x, y)| (a * x + c * y + e, b * x + d * y + f));
+        let (x0, x1) = min_max(corners.map(|(x, _)| x));
+        let (y0, y1) = min_max(corners.map(|(_, y)| y));
+        let width = (x1.min(page_box[2]) - x0.max(page_box[0])).max(0.0);
+        let height = (y1.min(page_box[3]) - y0.max(page_box[1])).max(0.0);
+        let area = width * height;
+        self.image_area += area;
+        if is_scan_compressed(doc, image) {
+            self.scan_image_area += area;
+        }
+    }
+
+    /// Classify the page, with the image areas as shares of the page area.
+    fn classify(&self, options: &PageClassOptions) -> PageClass {
+        let chars = self.visible_chars + self.invisible_chars;
+        let has_text = chars >= options.min_text_chars;
+        let ocr_layer = has_text && self.invisible_chars as f32 >= options.invisible_text_ratio * chars as f32;
+        if self.image_area >= options.scan_coverage {
+            if !has_text {
+                PageClass::ScannedImageOnly
+            } else if ocr_layer {
+                PageClass::ScannedWithOcrText
+            } else if self.scan_image_area >= options.scan_coverage {
+                PageClass::Mixed
+            } else {
+                // A digital page drawn over a background picture.
+                PageClass::BornDigital
+            }
+        } else if ocr_layer && self.image_area > 0.0 {
+            PageClass::ScannedWithOcrText
+        } else if self.scan_image_area >= options.mixed_coverage {
+            if has_text {
+                PageClass::Mixed
+            } else {
+                PageClass::ScannedImageOnly
+            }
+        } else {
+            PageClass::BornDigital
+        }
+    }
+}
+
+/// Whether `image` is compressed with one of the [`SCAN_FILTERS`].
+fn is_scan_compressed(doc: &Document, image: &Dictionary) -> bool {
+    let Ok(filter) = image.get_deref(b"Filter", doc).or_else(|_| image.get_deref(b"F", doc)) else {
+        return false;
+    };
+    let is_scan_filter = |filter: &Object| filter.as_name().is_ok_and(|name| SCAN_FILTERS.contains(&name));
+    match filter {
+        Object::Array(filters) => filters.iter().any(is_scan_filter),
+        filter => is_scan_filter(filter),
+    }
+}
+
+fn min_max(values: [f32; 4]) -> (f32, f32) {
+    values
+        .into_iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stream;
+    use crate::creator::tests::{create_document, create_document_with_texts};
+
+    /// A document whose single page draws `image` over the whole A4 page, followed by `content`.
+    fn full_page_image(image: Stream, content: &str) -> Document {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let image_id = doc.add_object(image);
+        doc.add_xobject(page_id, "Im1", image_id).unwrap();
+        let content = format!("q 595 0 0 842 0 0 cm /Im1 Do Q {content}");
+        doc.change_page_content(page_id, content.into_bytes()).unwrap();
+        doc
+    }
+
+    fn scan(filter: &str) -> Stream {
+        let dict = dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => 2480,
+            "Height" => 3508,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+            "Filter" => filter,
+        };
+        // Not valid image data, which classification never decodes.
+        Stream::new(dict, vec![0xFF, 0xD8, 0xFF, 0xD9])
+    }
+
+    const TEXT: &str = "BT /F1 12 Tf 72 700 Td (Invoice 2024-017, due in thirty days) Tj ET";
+
+    #[test]
+    fn digital_page_is_born_digital() {
+        let doc = create_document_with_texts(&["Hello World! This page was typeset, not scanned."]);
+        assert_eq!(doc.classify_pages(), [(1, PageClass::BornDigital)]);
+    }
+
+    #[test]
+    fn full_page_image_is_scanned() {
+        let doc = full_page_image(scan("DCTDecode"), "");
+        assert_eq!(doc.classify_pages(), [(1, PageClass::ScannedImageOnly)]);
+
+        let doc = full_page_image(scan("CCITTFaxDecode"), &format!("3 Tr {TEXT}"));
+        assert_eq!(doc.classify_pages(), [(1, PageClass::ScannedWithOcrText)]);
+
+        let doc = full_page_image(scan("JBIG2Decode"), TEXT);
+        assert_eq!(doc.classify_pages(), [(1, PageClass::Mixed)]);
+
+        // A picture behind typeset text.
+        let doc = full_page_image(scan("FlateDecode"), TEXT);
+        assert_eq!(doc.classify_pages(), [(1, PageClass::BornDigital)]);
+    }
+
+    #[test]
+    fn thresholds_are_configurable() {
+        let mut doc = full_page_image(scan("DCTDecode"), TEXT);
+        let page_id = doc.page_iter().next().unwrap();
+        let content = format!("q 595 0 0 421 0 0 cm /Im1 Do Q {TEXT}");
+        doc.change_page_content(page_id, content.into_bytes()).unwrap();
+        assert_eq!(doc.classify_pages(), [(1, PageClass::Mixed)]);
+
+        let options = PageClassOptions {
+            mixed_coverage: 0.6,
+            ..Default::default()
+        };
+        assert_eq!(doc.classify_pages_with_options(&options), [(1, PageClass::BornDigital)]);
+        let options = PageClassOptions {
+            scan_coverage: 0.4,
+            min_text_chars: 100,
+            ..Default::default()
+        };
+        assert_eq!(
+            doc.classify_pages_with_options(&options),
+            [(1, PageClass::ScannedImageOnly)]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Parsing of PDF syntax on its own, outside a document: objects, dictionaries, content streams
+//! and the headers of indirect objects, for syntax stored elsewhere than in a PDF file.
+//!
+//! These parsers need no [`Reader`](crate::Reader) or [`Document`](crate::Document). References
+//! aren't resolved but parse to [`Object::Reference`], and streams, whose data needs a /Length,
+//! aren't objects these parsers read. No input makes them panic: arrays and dictionaries nesting
+//! deeper than [`MAX_NESTING_DEPTH`] and literal strings nesting parentheses more than 100 deep
+//! fail to parse.
+
+use crate::content::{Content, Operation};
+use crate::error::ParseError;
+use crate::parser::{self, ParseLimits, ParserInput};
+use crate::{Dictionary, Object, ObjectId, Result};
+
+/// How deep arrays and dictionaries may nest in what these parsers read, a depth real documents
+/// don't reach, so that parsing fits on threads with small stacks.
+pub const MAX_NESTING_DEPTH: usize = 32;
+
+fn input(bytes: &[u8]) -> ParserInput<'_> {
+    let limits = ParseLimits {
+        depth_left: Some(MAX_NESTING_DEPTH),
+        ..ParseLimits::default()
+    };
+    ParserInput::new_extra(bytes, limits)
+}
+
+/// Parse the direct object making up `bytes`, with white-space and comments allowed around it.
+///
+/// ```
+/// use lopdf::{parse, Object};
+///
+/// let object = parse::object(b"[1 2.5 /Name (text) 12 0 R]")?;
+/// let array = object.as_array()?;
+/// assert_eq!(array[3].as_str()?, b"text");
+/// assert_eq!(array[4], Object::Reference((12, 0)));
+///
+/// assert!(parse::object(b"1 2").is_err());
+/// # Ok::<(), lopdf::Error>(())
+/// ```
+pub fn object(bytes: &[u8]) -> Result<Object> {
+    parser::whole_direct_object(input(bytes))
+}
+
+/// Parse the dictionary making up `bytes`, with white-space and comments allowed around it.
+///
+/// ```
+/// let dict = lopdf::parse::dictionary(b"<< /Type /Annot /Rect [0 0 10 10] % a comment\n >>")?;
+/// assert_eq!(dict.get(b"Type")?.as_name()?, b"Annot");
+/// # Ok::<(), lopdf::Error>(())
+/// ```
+pub fn dictionary(bytes: &[u8]) -> Result<Dictionary> {
+    parser::whole_dictionary(input(bytes))
+}
+
+/// Parse the operations of the content stream `bytes`, such as an appearance stream. Parsing
+/// stops at the first operation that doesn't parse, as [`Content::decode`] does, which uses it.
+///
+/// ```
+/// let content = lopdf::parse::content(b"BT /F1 12 Tf (Hello) Tj ET")?;
+/// let operators: Vec<&str> = content.operations.iter().map(|op| op.operator.as_str()).collect();
+/// assert_eq!(operators, ["BT", "Tf", "Tj", "ET"]);
+/// # Ok::<(), lopdf::Error>(())
+/// ```
+pub fn content(bytes: &[u8]) -> Result<Content<Vec<Operation>>> {
+    parser::content(input(bytes)).ok_or(ParseError::InvalidContentStream.into())
+}
+
+/// Parse the `N G obj` header of the indirect object at the start of `bytes`. Returns the ID of
+/// the object and the offset of the object after the header.
+///
+/// ```
+/// let bytes = b"12 0 obj\n<< /Type /Catalog >>\nendobj";
+/// let (id, start) = lopdf::parse::object_id_header(bytes)?;
+/// assert_eq!(id, (12, 0));
+/// assert!(bytes[start..].starts_with(b"<<"));
+/// # Ok::<(), lopdf::Error>(())
+/// ```
+pub fn object_id_header(bytes: &[u8]) -> Result<(ObjectId, usize)> {
+    parser::object_header(input(bytes))
+}
@@ -220,7 +220,7 @@ mod tests {
     use super::*;
 
     fn test_span(s: &'_ [u8]) -> ParserInput<'_> {
-        ParserInput::new_extra(s, "")
+        crate::parser::new_input(s)
     }
     #[test]
     fn parse_1byte_source_code() {
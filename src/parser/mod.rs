@@ -2,7 +2,9 @@
 use crate::content::*;
 use crate::error;
 use crate::xref::*;
-use crate::Error;
+use crate::{Error, LoadWarning};
+use log::warn;
+use memchr::memmem;
 use std::collections::HashSet;
 use std::str::{self, FromStr};
 
@@ -12,7 +14,7 @@
 use nom::character::complete::{digit0, digit1, one_of};
 use nom::character::complete::{space0, space1};
 use nom::combinator::cut;
-use nom::combinator::{map, map_opt, map_res, opt, verify};
+use nom::combinator::{all_consuming, map, map_opt, map_res, opt, recognize, verify};
 use nom::error::{ErrorKind, ParseError};
 use nom::multi::{fold_many0, fold_many1, many0, many0_count};
 use nom::sequence::{delimited, pair, preceded, separated_pair, terminated};
@@ -21,13 +23,45 @@
 
 pub(crate) mod cmap_parser;
 
-pub(crate) type ParserInput<'a> = LocatedSpan<&'a [u8], &'a str>;
+pub(crate) type ParserInput<'a> = LocatedSpan<&'a [u8], ParseLimits>;
 // Change this to something else that implements ParseError to get a
 // different error type out of nom.
 pub(crate) type NomError<'a> = nom::error::Error<ParserInput<'a>>;
 
 pub(crate) type NomResult<'a, O, E = NomError<'a>> = IResult<ParserInput<'a>, O, E>;
 
+#[cfg(test)]
+thread_local! {
+    /// The bytes of cross-reference tables and indirect objects parsed on this thread.
+    pub(crate) static PARSED_BYTES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Limits of the parser, carried along its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ParseLimits {
+    /// The maximum nesting of parentheses in literal strings.
+    pub max_bracket: usize,
+    /// How many more levels of arrays and dictionaries may nest, unlimited if `None`.
+    pub depth_left: Option<usize>,
+    /// Fail on malformed numbers instead of reading them as 0.
+    pub strict: bool,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_bracket: crate::reader::MAX_BRACKET,
+            depth_left: None,
+            strict: false,
+        }
+    }
+}
+
+/// Parser input starting at the beginning of `buffer`, with the default limits.
+pub(crate) fn new_input(buffer: &[u8]) -> ParserInput<'_> {
+    ParserInput::new_extra(buffer, ParseLimits::default())
+}
+
 #[inline]
 fn strip_nom<O>(r: NomResult<O>) -> Option<O> {
     r.ok().map(|(_, o)| o)
@@ -56,12 +90,15 @@ pub(crate) fn eol(input: ParserInput) -> NomResult<ParserInput> {
 pub(crate) fn comment(input: ParserInput) -> NomResult<()> {
     map(
         (tag(&b"%"[..]), take_while(|c: u8| !b"\r\n".contains(&c)), eol),
-        |_| (),
+        |_| {
+            #[cfg(feature = "census")]
+            crate::census::count(|counts| counts.comments += 1);
+        },
     ).parse(input)
 }
 
 #[inline]
-fn is_whitespace(c: u8) -> bool {
+pub(crate) fn is_whitespace(c: u8) -> bool {
     b" \t\n\r\0\x0C".contains(&c)
 }
 
@@ -71,7 +108,7 @@ fn is_delimiter(c: u8) -> bool {
 }
 
 #[inline]
-fn is_regular(c: u8) -> bool {
+pub(crate) fn is_regular(c: u8) -> bool {
     !is_whitespace(c) && !is_delimiter(c)
 }
 
@@ -112,6 +149,38 @@ fn real(input: ParserInput) -> NomResult<f32> {
     convert_result(f32::from_str(str::from_utf8(float_input).unwrap()), i, ErrorKind::Digit)
 }
 
+/// A number, or a token that looks like one: digits and periods after any signs. A malformed
+/// one, like `1.2.3`, `--5` or a real too large for `f32`, fails the parse in strict mode and is
+/// read as 0 with a warning otherwise. Integers too large for `i64` are read as reals.
+fn number(input: ParserInput) -> NomResult<Object> {
+    let (i, token) = recognize(pair(
+        take_while(|c: u8| b"+-".contains(&c)),
+        take_while1(|c: u8| c.is_ascii_digit() || c == b'.'),
+    )).parse(input)?;
+    if !token.iter().any(u8::is_ascii_digit) {
+        return Err(nom::Err::Error(NomError::from_error_kind(input, ErrorKind::Digit)));
+    }
+    let text = str::from_utf8(&token).unwrap();
+    let number = strip_nom(all_consuming(integer).parse(token))
+        .map(Object::Integer)
+        .or_else(|| {
+            let value = strip_nom(all_consuming(real).parse(token)).or_else(|| f32::from_str(text).ok())?;
+            value.is_finite().then_some(Object::Real(value))
+        });
+    #[cfg(feature = "census")]
+    if matches!(number, Some(Object::Real(_))) && text.split_once('.').is_some_and(|(_, decimals)| decimals.len() > 6) {
+        crate::census::count(|counts| counts.long_reals += 1);
+    }
+    match number {
+        Some(number) => Ok((i, number)),
+        None if input.extra.strict => Err(nom::Err::Failure(NomError::from_error_kind(input, ErrorKind::Float))),
+        None => {
+            warn!("malformed number {text} at offset {} read as 0", input.location_offset());
+            Ok((i, Object::Integer(0)))
+        }
+    }
+}
+
 pub(crate) fn hex_char(input: ParserInput) -> NomResult<u8> {
     map_res(
         verify(take(2usize), |h: &ParserInput| {
@@ -130,7 +199,7 @@ fn oct_char(input: ParserInput) -> NomResult<u8> {
 }
 
 pub(crate) fn name(input: ParserInput) -> NomResult<Vec<u8>> {
-    preceded(
+    let (input, name) = preceded(
         tag(&b"/"[..]),
         many0(alt((
             preceded(tag(&b"#"[..]), hex_char),
@@ -142,9 +211,17 @@ pub(crate) fn name(input: ParserInput) -> NomResult<Vec<u8>> {
                 }
             }),
         ))),
-    ).parse(input)
+    ).parse(input)?;
+    #[cfg(feature = "census")]
+    if !name.is_ascii() {
+        crate::census::count(|counts| counts.non_ascii_names += 1);
+    }
+    Ok((input, name))
 }
 
+/// Parse an escape sequence of a literal string. A backslash before an end-of-line marker (CR, LF
+/// or CRLF) continues the line and produces nothing; octal escapes take one to three digits; the
+/// backslash of an unknown escape is dropped.
 fn escape_sequence(input: ParserInput) -> NomResult<Option<u8>> {
     preceded(
         tag(&b"\\"[..]),
@@ -214,7 +291,11 @@ fn nested_literal_string(depth: usize) -> impl Fn(ParserInput) -> NomResult<Vec<
 }
 
 fn literal_string(input: ParserInput) -> NomResult<Vec<u8>> {
-    delimited(tag(&b"("[..]), inner_literal_string(crate::reader::MAX_BRACKET), tag(&b")"[..])).parse(input)
+    let max_bracket = input.extra.max_bracket;
+    let parsed = delimited(tag(&b"("[..]), inner_literal_string(max_bracket), tag(&b")"[..])).parse(input)?;
+    #[cfg(feature = "census")]
+    crate::census::count(|counts| counts.literal_strings += 1);
+    Ok(parsed)
 }
 
 #[inline]
@@ -247,7 +328,11 @@ fn hexadecimal_string(input: ParserInput) -> NomResult<Object> {
             ),
             tag(&b">"[..]),
         ),
-        |(bytes, _)| Object::String(bytes, StringFormat::Hexadecimal),
+        |(bytes, _)| {
+            #[cfg(feature = "census")]
+            crate::census::count(|counts| counts.hexadecimal_strings += 1);
+            Object::String(bytes, StringFormat::Hexadecimal)
+        },
     ).parse(input)
 }
 
@@ -262,12 +347,36 @@ fn null(input: ParserInput) -> NomResult<Object> {
     map(tag(&b"null"[..]), |_| Object::Null).parse(input)
 }
 
+/// Apply `parser` to the content of an array or dictionary, one level deeper. Fails if the
+/// depth limit is reached.
+fn nested<'a, O>(
+    mut parser: impl Parser<ParserInput<'a>, Output = O, Error = NomError<'a>>,
+) -> impl FnMut(ParserInput<'a>) -> NomResult<'a, O> {
+    move |input: ParserInput<'a>| {
+        let limits = input.extra;
+        let depth_left = match limits.depth_left {
+            Some(0) => return Err(nom::Err::Error(NomError::from_error_kind(input, ErrorKind::TooLarge))),
+            depth_left => depth_left.map(|depth| depth - 1),
+        };
+        #[cfg(feature = "census")]
+        crate::census::count(|counts| {
+            counts.depth += 1;
+            counts.max_depth = counts.max_depth.max(counts.depth);
+        });
+        let parsed = parser.parse(input.map_extra(|limits| ParseLimits { depth_left, ..limits }));
+        #[cfg(feature = "census")]
+        crate::census::count(|counts| counts.depth -= 1);
+        let (rest, output) = parsed?;
+        Ok((rest.map_extra(|_| limits), output))
+    }
+}
+
 fn array(input: ParserInput) -> NomResult<Vec<Object>> {
-    delimited(pair(tag(&b"["[..]), space), many0(_direct_object), tag(&b"]"[..])).parse(input)
+    delimited(pair(tag(&b"["[..]), space), nested(many0(_direct_object)), tag(&b"]"[..])).parse(input)
 }
 
 pub(crate) fn dictionary(input: ParserInput) -> NomResult<Dictionary> {
-    delimited(pair(tag(&b"<<"[..]), space), inner_dictionary, tag(&b">>"[..])).parse(input)
+    delimited(pair(tag(&b"<<"[..]), space), nested(inner_dictionary), tag(&b">>"[..])).parse(input)
 }
 
 fn inner_dictionary(input: ParserInput) -> NomResult<Dictionary> {
@@ -308,7 +417,11 @@ pub(crate) fn dict_dup(input: ParserInput) -> NomResult<Dictionary> {
     ).parse(input)
 }
 
-fn stream<'a>(input: ParserInput<'a>, reader: &Reader, already_seen: &mut HashSet<ObjectId>) -> NomResult<'a, Object> {
+/// A stream, with the /Length it was written with if its data doesn't end there but at the nearest
+/// `endstream`.
+fn stream<'a>(
+    input: ParserInput<'a>, reader: &Reader, already_seen: &mut HashSet<ObjectId>,
+) -> NomResult<'a, (Object, Option<i64>)> {
     let (i, dict) = terminated(dictionary, (space, tag(&b"stream"[..]), space0, eol)).parse(input)?;
 
     if let Ok(length) = dict.get(b"Length").and_then(|value| {
@@ -322,11 +435,22 @@ fn stream<'a>(input: ParserInput<'a>, reader: &Reader, already_seen: &mut HashSe
             // artificial error kind is created to allow descriptive nom errors
             return Err(nom::Err::Failure(NomError::from_error_kind(i, ErrorKind::LengthValue)));
         }
-        let (i, data) = terminated(take(length as usize), pair(opt(eol), tag(&b"endstream"[..]))).parse(i)?;
-        Ok((i, Object::Stream(Stream::new(dict, data.to_vec()))))
+        let end = pair(white_space, tag(&b"endstream"[..]));
+        let (i, data, declared) = match terminated(take(length as usize), end).parse(i) {
+            Ok((i, data)) => (i, data, None),
+            // The data of a stream whose /Length is wrong ends at the nearest `endstream`, which
+            // the reader reports.
+            Err(nom::Err::Error(err)) => {
+                let actual = nearest_stream_end(i.fragment(), length as usize).ok_or(nom::Err::Error(err))?;
+                let (i, data) = terminated(take(actual), pair(white_space, tag(&b"endstream"[..]))).parse(i)?;
+                (i, data, (actual != length as usize).then_some(length))
+            }
+            Err(err) => return Err(err),
+        };
+        Ok((i, (Object::Stream(Stream::new(dict, data.to_vec())), declared)))
     } else {
         // Return position relative to the start of the stream dictionary.
-        Ok((i, Object::Stream(Stream::with_position(dict, input.len() - i.len()))))
+        Ok((i, (Object::Stream(Stream::with_position(dict, input.len() - i.len())), None)))
     }
 }
 
@@ -349,8 +473,7 @@ fn _direct_objects(input: ParserInput) -> NomResult<Object> {
         null,
         boolean,
         reference,
-        map(real, Object::Real),
-        map(integer, Object::Integer),
+        number,
         map(name, Object::Name),
         map(literal_string, Object::string_literal),
         hexadecimal_string,
@@ -367,28 +490,43 @@ pub fn direct_object(input: ParserInput) -> Option<Object> {
     strip_nom(_direct_object.parse(input))
 }
 
-fn object<'a>(input: ParserInput<'a>, reader: &Reader, already_seen: &mut HashSet<ObjectId>) -> NomResult<'a, Object> {
-    terminated(
-        alt((|input| stream(input, reader, already_seen), _direct_objects)),
-        space,
-    ).parse(input)
+/// An object, with the /Length of a stream whose data doesn't end there, see [`stream`].
+fn object<'a>(
+    input: ParserInput<'a>, reader: &Reader, already_seen: &mut HashSet<ObjectId>,
+) -> NomResult<'a, (Object, Option<i64>)> {
+    // A dictionary not followed by `stream` is parsed again as a direct object.
+    #[cfg(feature = "census")]
+    let counts = crate::census::snapshot();
+    let stream_object = |input| {
+        let parsed = stream(input, reader, already_seen);
+        #[cfg(feature = "census")]
+        if parsed.is_err() {
+            crate::census::restore(counts);
+        }
+        parsed
+    };
+    let direct_object = map(_direct_objects, |object| (object, None));
+    terminated(alt((stream_object, direct_object)), space).parse(input)
 }
 
+/// The indirect object at `offset`, with the warning for a stream whose /Length isn't the length
+/// of its data.
 pub fn indirect_object(
     input: ParserInput, offset: usize, expected_id: Option<ObjectId>, reader: &Reader,
     already_seen: &mut HashSet<ObjectId>,
-) -> crate::Result<(ObjectId, Object)> {
-    let (id, mut object) = _indirect_object(input.take_from(offset), offset, expected_id, reader, already_seen)?;
+) -> crate::Result<(ObjectId, Object, Option<LoadWarning>)> {
+    let (id, mut object, length_mismatch) =
+        _indirect_object(input.take_from(offset), offset, expected_id, reader, already_seen)?;
 
     offset_stream(&mut object, offset);
 
-    Ok((id, object))
+    Ok((id, object, length_mismatch))
 }
 
 fn _indirect_object<'a>(
     input: ParserInput<'a>, offset: usize, expected_id: Option<ObjectId>, reader: &Reader,
     already_seen: &mut HashSet<ObjectId>,
-) -> crate::Result<(ObjectId, Object)> {
+) -> crate::Result<(ObjectId, Object, Option<LoadWarning>)> {
     let (i, (_, object_id)) = terminated((space, object_id), pair(tag(&b"obj"[..]), space)).parse(input)
         .map_err(|_| Error::IndirectObject { offset })?;
     if let Some(expected_id) = expected_id {
@@ -398,15 +536,117 @@ fn _indirect_object<'a>(
     }
 
     let object_offset = input.len() - i.len();
-    let (_, mut object) = terminated(
-        |i: ParserInput<'a>| object(i, reader, already_seen),
-        (space, opt(tag(&b"endobj"[..])), space),
-    ).parse(i)
-    .map_err(|_| Error::IndirectObject { offset })?;
+    let (rest, (mut object, declared_length)) =
+        terminated(|i: ParserInput<'a>| object(i, reader, already_seen), space)
+            .parse(i)
+            .map_err(|_| Error::IndirectObject { offset })?;
+    // The data of streams without a /Length to read is checked once it's read.
+    let data_deferred = matches!(&object, Object::Stream(stream) if stream.start_position.is_some());
+    if reader.options.strict && !data_deferred && !rest.fragment().starts_with(b"endobj") {
+        return Err(Error::Strict(missing_endobj(rest, object_id, offset, &object, reader)));
+    }
+
+    let length_mismatch = declared_length.map(|declared| LoadWarning::StreamLengthMismatch {
+        id: object_id,
+        offset,
+        declared,
+        actual: object.as_stream().ok().map(|stream| stream.content.len()),
+    });
 
     offset_stream(&mut object, object_offset);
+    #[cfg(test)]
+    PARSED_BYTES.set(PARSED_BYTES.get() + rest.location_offset() - input.location_offset());
+
+    Ok((object_id, object, length_mismatch))
+}
+
+/// Why the object `id` at `offset` isn't followed by `endobj` but by `rest`: a dictionary followed
+/// by `stream` is a stream whose /Length doesn't end at `endstream`.
+fn missing_endobj(rest: ParserInput, id: ObjectId, offset: usize, object: &Object, reader: &Reader) -> LoadWarning {
+    let data = (tag(&b"stream"[..]), space0, eol).parse(rest);
+    let declared = match object {
+        Object::Dictionary(dict) => dict.get(b"Length").and_then(|length| match length.as_reference() {
+            Ok(length_id) => reader.get_object(length_id, &mut HashSet::new()).and_then(|length| length.as_i64()),
+            Err(_) => length.as_i64(),
+        }),
+        _ => return LoadWarning::MissingEndobj { id, offset },
+    };
+    match (data, declared) {
+        (Ok((data, _)), Ok(declared)) => LoadWarning::StreamLengthMismatch {
+            id,
+            offset,
+            declared,
+            actual: stream_data_length(data.fragment()),
+        },
+        _ => LoadWarning::MissingEndobj { id, offset },
+    }
+}
+
+/// The ID and dictionary of the stream object at the start of `input`, with the offset its data
+/// starts at, without reading the data.
+pub(crate) fn stream_header(input: ParserInput) -> Option<(ObjectId, Dictionary, usize)> {
+    let header = (space, object_id, tag(&b"obj"[..]), space);
+    let stream_start = (space, tag(&b"stream"[..]), space0, eol);
+    let (rest, ((_, id, _, _), dict)) = pair(header, terminated(dictionary, stream_start)).parse(input).ok()?;
+    Some((id, dict, input.len() - rest.len()))
+}
+
+/// The length of the stream data at the start of `data`, up to the end of line before `endstream`.
+/// Returns `None` if there is no `endstream`.
+pub(crate) fn stream_data_length(data: &[u8]) -> Option<usize> {
+    memmem::find(data, b"endstream").map(|end| before_eol(data, end))
+}
+
+/// The length of the stream data at the start of `data` whose /Length of `declared` doesn't end at
+/// `endstream`: up to the end of line before the `endstream` nearest to `declared`, before or
+/// after it. An `endstream` after `declared` is only looked for up to the next `obj` keyword, so
+/// that the end of the next stream isn't taken. Returns `None` if there is no `endstream`.
+pub(crate) fn nearest_stream_end(data: &[u8], declared: usize) -> Option<usize> {
+    const KEYWORD: &[u8] = b"endstream";
+    let after = data.get(declared..).and_then(|after| {
+        let next_object = memmem::find(after, b"obj").map_or(after.len(), |obj| obj + b"obj".len());
+        memmem::find(&after[..next_object], KEYWORD).map(|end| declared + end)
+    });
+    let before = memmem::rfind(&data[..data.len().min(declared + KEYWORD.len() - 1)], KEYWORD);
+    let end = match (before, after) {
+        (Some(before), Some(after)) if declared - before <= after - declared => before,
+        (_, Some(after)) => after,
+        (before, None) => before?,
+    };
+    Some(before_eol(data, end))
+}
+
+/// `end`, less the end of line `data` has before it, if any.
+fn before_eol(data: &[u8], end: usize) -> usize {
+    let data = &data[..end];
+    if data.ends_with(b"\r\n") {
+        end - 2
+    } else {
+        end - usize::from(data.ends_with(b"\n") || data.ends_with(b"\r"))
+    }
+}
 
-    Ok((object_id, object))
+/// Parse an indirect object whose body is nothing but white-space and comments, optionally closed
+/// by `endobj`, up to the end of `input`. Some linearizers leave such objects behind as padding.
+pub(crate) fn padding_object(input: ParserInput) -> Option<ObjectId> {
+    let (rest, (_, id)) = terminated((space, object_id), tag(&b"obj"[..])).parse(input).ok()?;
+    let mut body: &[u8] = rest.fragment();
+    let mut closed = false;
+    loop {
+        body = &body[body.iter().position(|&c| !is_whitespace(c)).unwrap_or(body.len())..];
+        match body.first() {
+            None => return Some(id),
+            Some(b'%') => body = &body[body.iter().position(|c| b"\r\n".contains(c)).unwrap_or(body.len())..],
+            Some(_) if !closed && body.starts_with(b"endobj") => {
+                closed = true;
+                body = &body[6..];
+                if body.first().is_some_and(|&c| is_regular(c)) {
+                    return None;
+                }
+            }
+            Some(_) => return None,
+        }
+    }
 }
 
 pub fn header(input: ParserInput) -> Option<String> {
@@ -435,7 +675,7 @@ pub fn binary_mark(input: ParserInput) -> Option<Vec<u8>> {
 fn xref(input: ParserInput) -> NomResult<Xref> {
     let xref_eol = map(alt((tag(&b" \r"[..]), tag(&b" \n"[..]), tag(&b"\r\n"[..]))), |_| ());
     let xref_entry = pair(
-        separated_pair(unsigned_int, tag(&b" "[..]), unsigned_int::<u32>),
+        separated_pair(unsigned_int::<u64>, tag(&b" "[..]), unsigned_int::<u32>),
         delimited(tag(&b" "[..]), map(one_of("nf"), |k| k == 'n'), xref_eol),
     );
 
@@ -455,11 +695,8 @@ fn xref(input: ParserInput) -> NomResult<Xref> {
                         if let Ok(generation) = generation.try_into() {
                             xref.insert((start + index) as u32, XrefEntry::Normal { offset, generation });
                         }
-                    } else {
-                        let next_free_object = offset;
-                        if let Ok(generation) = generation.try_into() {
-                            xref.insert((start + index) as u32, XrefEntry::Free { next_free_object, generation });
-                        }
+                    } else if let (Ok(next_free_object), Ok(generation)) = (offset.try_into(), generation.try_into()) {
+                        xref.insert((start + index) as u32, XrefEntry::Free { next_free_object, generation });
                     }
                 }
                 xref
@@ -485,7 +722,7 @@ pub fn xref_and_trailer(input: ParserInput, reader: &Reader) -> crate::Result<(X
         xref_trailer,
         (|input| {
             _indirect_object(input, 0, None, reader, &mut HashSet::new())
-                .map(|(_, obj)| {
+                .map(|(_, obj, _)| {
                     let res = match obj {
                         Object::Stream(stream) => decode_xref_stream(stream),
                         _ => Err(crate::error::ParseError::InvalidXref.into()),
@@ -498,7 +735,12 @@ pub fn xref_and_trailer(input: ParserInput, reader: &Reader) -> crate::Result<(X
                 })
         }),
     )).parse(input)
-    .map(|(_, o)| o)
+    .map(|(_rest, o)| {
+        // Cross-reference streams are counted as the indirect objects they are.
+        #[cfg(test)]
+        PARSED_BYTES.set(PARSED_BYTES.get() + _rest.location_offset() - input.location_offset());
+        o
+    })
     .map_err(|_| error::ParseError::InvalidTrailer)?
 }
 
@@ -511,8 +753,8 @@ pub fn xref_start(input: ParserInput) -> Option<i64> {
 }
 
 fn trim_spaces<'a, O>(
-    p: impl Parser<ParserInput<'a>, Output = O, Error = nom::error::Error<LocatedSpan<&'a [u8], &'a str>>>,
-) -> impl Parser<ParserInput<'a>, Output = O, Error = nom::error::Error<LocatedSpan<&'a [u8], &'a str>>> {
+    p: impl Parser<ParserInput<'a>, Output = O, Error = nom::error::Error<ParserInput<'a>>>,
+) -> impl Parser<ParserInput<'a>, Output = O, Error = nom::error::Error<ParserInput<'a>>> {
     delimited(many0(tag(" ")), p, many0(tag(" ")))
 }
 
@@ -534,8 +776,7 @@ fn operand(input: ParserInput) -> NomResult<Object> {
         alt((
             null,
             boolean,
-            map(real, Object::Real),
-            map(integer, Object::Integer),
+            number,
             map(name, Object::Name),
             map(literal_string, Object::string_literal),
             hexadecimal_string,
@@ -568,42 +809,69 @@ fn inline_image_impl(input: ParserInput) -> NomResult<(Vec<Object>, String)> {
     Ok((input, (vec![Object::Stream(stream)], String::from("BI"))))
 }
 
+/// The length of inline image data running up to the first `EI` operator, for images whose size
+/// can't be computed without the page resources.
+fn inline_image_data_length(data: &[u8]) -> crate::Result<usize> {
+    let is_space = |c: &u8| b" \t\r\n".contains(c);
+    (1..data.len())
+        .find(|&i| data[i..].starts_with(b"EI") && is_space(&data[i - 1]) && data.get(i + 2).is_none_or(is_space))
+        .map(|i| i - 1)
+        .ok_or_else(|| crate::error::ParseError::EndOfInput.into())
+}
+
 fn image_data_stream(input: ParserInput, stream_dict: Dictionary) -> crate::Result<(ParserInput, Stream)> {
     let get_abbr = |key_abbr: &[u8], key: &[u8]| stream_dict.get(key_abbr).or_else(|_| stream_dict.get(key));
-    let width = get_abbr(b"W", b"Width")?.as_i64()? as usize;
-    let height = get_abbr(b"H", b"Height")?.as_i64()? as usize;
-    let bpc = get_abbr(b"BPC", b"BitsPerComponent")?.as_i64()? as usize;
+    let get_size = |key_abbr: &[u8], key: &[u8]| -> crate::Result<usize> {
+        Ok(usize::try_from(get_abbr(key_abbr, key)?.as_i64()?)?)
+    };
+    let width = get_size(b"W", b"Width")?;
+    let height = get_size(b"H", b"Height")?;
+    let bpc = get_size(b"BPC", b"BitsPerComponent")?;
     let im = get_abbr(b"IM", b"ImageMask").and_then(|x| x.as_bool());
-    let num_colors = match im {
+    // The number of components is unknown for a color space resource of the page.
+    let num_colors: Option<usize> = match im {
         // If we have an image mask then we don't have a colorspace
-        Ok(true) => 1,
-        _ => {
-            let colorspace = get_abbr(b"CS", b"ColorSpace").unwrap().as_name()?;
-            match colorspace {
-                b"DeviceGray" | b"Gray" => 1,
-                b"DeviceRGB" | b"RGB" => 3,
-                b"DeviceRGBA" | b"RGBA" => 4,
-                b"DeviceCMYK" | b"CMYK" => 4,
+        Ok(true) => Some(1),
+        _ => match get_abbr(b"CS", b"ColorSpace")? {
+            Object::Name(colorspace) => match colorspace.as_slice() {
+                b"DeviceGray" | b"Gray" | b"G" => Some(1),
+                b"DeviceRGB" | b"RGB" => Some(3),
+                b"DeviceRGBA" | b"RGBA" => Some(4),
+                b"DeviceCMYK" | b"CMYK" => Some(4),
                 b"Pattern" => {
                     log::warn!("Pattern colorspace is not allowed in inline images");
                     return Err(Error::InvalidInlineImage(String::from(
                         "Pattern colorspace is not allowed in inline images",
                     )));
                 }
-                _ => {
-                    log::warn!("Colorspace of inline image not recognized / not yet implemented");
-                    return Err(Error::Unimplemented("inline image colorspaces"));
-                }
+                _ => None,
+            },
+            Object::Array(colorspace)
+                if matches!(colorspace.first().map(Object::as_name), Some(Ok(b"I" | b"Indexed"))) =>
+            {
+                Some(1)
             }
-        }
+            Object::Array(_) => None,
+            obj => {
+                return Err(Error::ObjectType {
+                    expected: "Name or Array",
+                    found: obj.enum_variant(),
+                });
+            }
+        },
     };
 
-    let stride = (width * (num_colors * bpc)).div_ceil(8);
-    let length = height * stride;
-
     let (input, content) = match get_abbr(b"F", b"Filter") {
         Err(_) => {
             // no decompression needed as no filter was applied
+            let length = match num_colors {
+                Some(num_colors) => num_colors
+                    .checked_mul(bpc)
+                    .and_then(|bits| bits.checked_mul(width))
+                    .and_then(|bits| bits.div_ceil(8).checked_mul(height))
+                    .ok_or_else(|| Error::InvalidInlineImage(String::from("image data too large")))?,
+                None => inline_image_data_length(input.fragment())?,
+            };
             take(length).parse(input).map_err(|_: nom::Err<()>| crate::error::ParseError::EndOfInput)?
         }
         Ok(Object::Name(_filter)) => {
@@ -636,12 +904,47 @@ pub fn content(input: ParserInput) -> Option<Content<Vec<Operation>>> {
     strip_nom(_content.parse(input))
 }
 
+/// Run `parser` on all of `input`, with white-space and comments allowed around what it parses.
+/// Fails with `error` if it doesn't parse, or with the offset of anything left over.
+fn parse_whole<'a, O>(
+    input: ParserInput<'a>, parser: impl Parser<ParserInput<'a>, Output = O, Error = NomError<'a>>,
+    error: error::ParseError,
+) -> crate::Result<O> {
+    match delimited(space, parser, space).parse(input) {
+        Ok((rest, output)) if rest.is_empty() => Ok(output),
+        Ok((rest, _)) => Err(error::ParseError::TrailingData(rest.location_offset()).into()),
+        Err(_) => Err(error.into()),
+    }
+}
+
+/// The direct object making up `input`, see [`crate::parse::object`].
+pub(crate) fn whole_direct_object(input: ParserInput) -> crate::Result<Object> {
+    parse_whole(input, _direct_objects, error::ParseError::InvalidObject)
+}
+
+/// The dictionary making up `input`, see [`crate::parse::dictionary`].
+pub(crate) fn whole_dictionary(input: ParserInput) -> crate::Result<Dictionary> {
+    parse_whole(input, dictionary, error::ParseError::InvalidObject)
+}
+
+/// The ID of the `N G obj` header at the start of `input`, with the offset the object starts at.
+pub(crate) fn object_header(input: ParserInput) -> crate::Result<(ObjectId, usize)> {
+    let (rest, (_, id)) = terminated((space, object_id), pair(tag(&b"obj"[..]), space))
+        .parse(input)
+        .map_err(|_| error::ParseError::InvalidObjectHeader)?;
+    // `obj` is a keyword of its own, not the start of a longer one.
+    if rest.fragment().first().is_some_and(|&c| is_regular(c)) {
+        return Err(error::ParseError::InvalidObjectHeader.into());
+    }
+    Ok((id, rest.location_offset()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn test_span(s: &'_ [u8]) -> ParserInput<'_> {
-        LocatedSpan::new_extra(s, "test")
+        new_input(s)
     }
 
     fn tstrip<O>(r: NomResult<O>) -> Option<O> {
@@ -657,6 +960,26 @@ fn parse_real_number() {
         assert_eq!(real(test_span(b"10.")), Some(10.0));
     }
 
+    #[test]
+    fn parse_malformed_number() {
+        let number = |i| tstrip(number(i));
+        let strict = |s| ParserInput::new_extra(s, ParseLimits { strict: true, ..Default::default() });
+
+        assert_eq!(number(test_span(b"-42")), Some(Object::Integer(-42)));
+        assert_eq!(number(test_span(b"+.5")), Some(Object::Real(0.5)));
+        assert_eq!(number(test_span(b"99999999999999999999")), Some(Object::Real(1e20)));
+        for malformed in [&b"1.2.3"[..], b"--5", b"+-3", b"1000000000000000000000000000000000000000.0"] {
+            assert_eq!(number(test_span(malformed)), Some(Object::Integer(0)));
+            assert!(matches!(super::number(strict(malformed)), Err(nom::Err::Failure(_))));
+        }
+        assert!(super::number(test_span(b"-.")).is_err());
+        assert_eq!(strip_nom(_direct_object(test_span(b"[1 2.0.0 3]"))), Some(Object::Array(vec![
+            Object::Integer(1),
+            Object::Integer(0),
+            Object::Integer(3)
+        ])));
+    }
+
     #[test]
     fn parse_string() {
         let literal_string = |i| tstrip(literal_string(i));
@@ -680,6 +1003,82 @@ fn parse_string() {
         }
     }
 
+    #[test]
+    fn parse_literal_string_escapes() {
+        let deep = crate::reader::MAX_BRACKET;
+
+        let data: Vec<(Vec<u8>, Option<Vec<u8>>)> = vec![
+            // Examples from the PDF specification, section 7.3.4.2.
+            (b"(This is a string)".to_vec(), Some(b"This is a string".to_vec())),
+            (
+                b"(Strings may contain newlines\nand such.)".to_vec(),
+                Some(b"Strings may contain newlines\nand such.".to_vec()),
+            ),
+            (
+                b"(Strings may contain balanced parentheses ( ) and\nspecial characters (*!&}^% and so on).)".to_vec(),
+                Some(b"Strings may contain balanced parentheses ( ) and\nspecial characters (*!&}^% and so on).".to_vec()),
+            ),
+            (b"(The following is an empty string.)".to_vec(), Some(b"The following is an empty string.".to_vec())),
+            (b"()".to_vec(), Some(b"".to_vec())),
+            (
+                b"(These \\\ntwo strings \\\nare the same.)".to_vec(),
+                Some(b"These two strings are the same.".to_vec()),
+            ),
+            (b"(These two strings are the same.)".to_vec(), Some(b"These two strings are the same.".to_vec())),
+            (
+                b"(This string has an end-of-line at the end of it.\n)".to_vec(),
+                Some(b"This string has an end-of-line at the end of it.\n".to_vec()),
+            ),
+            (b"(So does this one.\\n)".to_vec(), Some(b"So does this one.\n".to_vec())),
+            (b"(This string contains \\245two octal characters\\307.)".to_vec(), Some(b"This string contains \xA5two octal characters\xC7.".to_vec())),
+            (b"(\\0053)".to_vec(), Some(b"\x053".to_vec())),
+            (b"(\\053)".to_vec(), Some(b"+".to_vec())),
+            (b"(\\53)".to_vec(), Some(b"+".to_vec())),
+            // Named escapes.
+            (b"(\\n\\r\\t\\b\\f)".to_vec(), Some(b"\n\r\t\x08\x0C".to_vec())),
+            (b"(\\\\)".to_vec(), Some(b"\\".to_vec())),
+            // Octal escapes take one to three digits and overflow is ignored.
+            (b"(\\0)".to_vec(), Some(b"\0".to_vec())),
+            (b"(\\1a)".to_vec(), Some(b"\x01a".to_vec())),
+            (b"(\\377)".to_vec(), Some(b"\xFF".to_vec())),
+            (b"(\\400)".to_vec(), Some(b"\x00".to_vec())),
+            (b"(\\777)".to_vec(), Some(b"\xFF".to_vec())),
+            (b"(\\18)".to_vec(), Some(b"\x018".to_vec())),
+            // Unknown escapes drop the backslash.
+            (b"(\\x\\q\\8)".to_vec(), Some(b"xq8".to_vec())),
+            // Line continuations with every kind of end-of-line marker, each consuming one marker.
+            (b"(a\\\nb)".to_vec(), Some(b"ab".to_vec())),
+            (b"(a\\\rb)".to_vec(), Some(b"ab".to_vec())),
+            (b"(a\\\r\nb)".to_vec(), Some(b"ab".to_vec())),
+            (b"(a\\\n\nb)".to_vec(), Some(b"a\nb".to_vec())),
+            (b"(a\\\n\rb)".to_vec(), Some(b"a\rb".to_vec())),
+            // Escaped parentheses don't need to be balanced.
+            (b"(a\\(b)".to_vec(), Some(b"a(b".to_vec())),
+            (b"(a\\)b)".to_vec(), Some(b"a)b".to_vec())),
+            (b"(\\((b))".to_vec(), Some(b"((b)".to_vec())),
+            (b"(a(b)".to_vec(), None),
+            // Nesting is limited, escaped parentheses don't count towards the limit.
+            (
+                [b"(".repeat(deep + 1), b")".repeat(deep + 1)].concat(),
+                Some([b"(".repeat(deep), b")".repeat(deep)].concat()),
+            ),
+            ([b"(".repeat(deep + 2), b")".repeat(deep + 2)].concat(), None),
+            (
+                [b"(".to_vec(), b"\\(".repeat(deep * 2), b")".to_vec()].concat(),
+                Some(b"(".repeat(deep * 2)),
+            ),
+        ];
+
+        for (input, expected) in data {
+            assert_eq!(
+                tstrip(literal_string(test_span(&input))),
+                expected,
+                "input: {:?}",
+                String::from_utf8_lossy(&input),
+            );
+        }
+    }
+
     #[test]
     fn parse_name() {
         let (text, expected) = (b"/ABC#5f", b"ABC\x5F");
@@ -810,4 +1209,16 @@ fn inline_image() {
             b"00000z0z00zzz00z0zzz0zzzEI aazazaazzzaazazzzazzz"
         )
     }
+
+    #[test]
+    fn inline_image_with_a_named_color_space() {
+        let input = b"BI /W 2 /H 1 /CS /CS0 /BPC 8 ID zEIz\nEI Q";
+        let (rest, out) = super::inline_image(test_span(input)).unwrap();
+        assert_eq!(&out.0[0].as_stream().unwrap().content, b"zEIz");
+        assert_eq!(*rest.fragment(), b"Q");
+
+        let input = b"BI /W 2 /H 1 /CS [/I /CS0 1 <000000ffffff>] /BPC 8 ID zz EI";
+        let out = super::inline_image(test_span(input)).unwrap().1;
+        assert_eq!(&out.0[0].as_stream().unwrap().content, b"zz");
+    }
 }
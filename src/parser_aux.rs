@@ -2,25 +2,53 @@
 
 use crate::{
     content::{Content, Operation},
+    content_walker::{ContentWalker, DEFAULT_MAX_FORM_DEPTH},
     document::Document,
-    encodings::Encoding,
+    encodings::{Encoding, ReverseMapping},
     error::ParseError,
     object::Object::Name,
-    parser::ParserInput,
     xref::{Xref, XrefEntry, XrefType},
     Error, Result,
 };
-use crate::{parser, Dictionary, Object, ObjectId, Stream};
+use crate::dehyphenate::{HyphenJoin, dehyphenate};
+use crate::extraction_trace::{ExtractionTrace, FontTrace, unmapped_glyphs};
+use crate::progress::{self, Phase, ProgressSink};
+use crate::{decode_text_string, Dictionary, Object, ObjectId, Stream};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     io::{Cursor, Read},
 };
 
+/// Options for extracting text, see [`Document::extract_text_with_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractionOptions {
+    /// Receives the progress of extraction, in the [`ExtractPage`](crate::Phase::ExtractPage) phase
+    pub progress: Option<ProgressSink>,
+    /// Join words hyphenated across line breaks, see
+    /// [`Document::extract_text_with_hyphen_joins`]. The chunks of
+    /// [`Document::extract_text_chunks_with_options`] are left as they are.
+    pub dehyphenate: bool,
+    /// How deep Form XObjects drawn by Form XObjects are descended into, `None` for
+    /// [`DEFAULT_MAX_FORM_DEPTH`](crate::DEFAULT_MAX_FORM_DEPTH). Text of forms nested deeper is
+    /// left out with a warning, as is that of forms drawing themselves.
+    pub max_form_depth: Option<usize>,
+}
+
+/// Options for replacing text, see [`Document::replace_text_with_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplaceOptions {
+    /// Written for characters the font cannot represent; without it they fail the replacement
+    /// with [`Error::UnsupportedCharacters`]
+    pub default_str: Option<String>,
+    /// Substitutes for characters the font cannot represent, tried before `default_str`, like
+    /// `'` for `’`
+    pub transliterations: HashMap<char, String>,
+}
+
 impl Content<Vec<Operation>> {
     /// Decode content operations.
     pub fn decode(data: &[u8]) -> Result<Self> {
-        parser::content(ParserInput::new_extra(data, "content operations"))
-            .ok_or(ParseError::InvalidContentStream.into())
+        crate::parse::content(data)
     }
 }
 
@@ -46,91 +74,223 @@ pub fn add_to_page_content(&mut self, page_id: ObjectId, content: Content<Vec<Op
     }
 
     pub fn extract_text(&self, page_numbers: &[u32]) -> Result<String> {
-        let text_fragments = self.extract_text_chunks(page_numbers);
+        self.extract_text_with_options(page_numbers, &ExtractionOptions::default())
+    }
+
+    /// Like [`Document::extract_text`], with `options`.
+    pub fn extract_text_with_options(&self, page_numbers: &[u32], options: &ExtractionOptions) -> Result<String> {
+        Ok(self.extract_text_with_hyphen_joins(page_numbers, options)?.0)
+    }
+
+    /// Like [`Document::extract_text_with_options`], with the words
+    /// [`dehyphenate`](ExtractionOptions::dehyphenate) joined across line breaks. Words are
+    /// joined within a page only.
+    pub fn extract_text_with_hyphen_joins(
+        &self, page_numbers: &[u32], options: &ExtractionOptions,
+    ) -> Result<(String, Vec<HyphenJoin>)> {
+        let (text, joins, _) = self.extract_text_traced(page_numbers, options, false)?;
+        Ok((text, joins))
+    }
+
+    /// Like [`Document::extract_text_with_options`], with an [`ExtractionTrace`] of each page: the
+    /// fonts of its text and where their mapping to Unicode comes from, the forms descended into
+    /// and the text operations skipped.
+    pub fn extract_text_with_trace(
+        &self, page_numbers: &[u32], options: &ExtractionOptions,
+    ) -> Result<(String, Vec<ExtractionTrace>)> {
+        let (text, _, traces) = self.extract_text_traced(page_numbers, options, true)?;
+        Ok((text, traces))
+    }
+
+    fn extract_text_traced(
+        &self, page_numbers: &[u32], options: &ExtractionOptions, trace: bool,
+    ) -> Result<(String, Vec<HyphenJoin>, Vec<ExtractionTrace>)> {
         let mut text = String::new();
-        for maybe_text_fragment in text_fragments.into_iter() {
-            let text_fragment = maybe_text_fragment?;
-            text.push_str(&text_fragment);
+        let mut joins = Vec::new();
+        let mut traces = Vec::new();
+        let mut raw_len = 0;
+        for (page_chunks, trace) in self.page_text_chunks(page_numbers, options, trace) {
+            let mut page_text = String::new();
+            for maybe_text_fragment in page_chunks {
+                let text_fragment = maybe_text_fragment?;
+                page_text.push_str(&text_fragment);
+            }
+            if options.dehyphenate {
+                dehyphenate(&page_text, raw_len, &mut text, &mut joins);
+            } else {
+                text.push_str(&page_text);
+            }
+            raw_len += page_text.len();
+            traces.extend(trace);
         }
 
-        Ok(text)
+        Ok((text, joins, traces))
     }
 
     pub fn extract_text_chunks(&self, page_numbers: &[u32]) -> Vec<Result<String>> {
-        let pages: BTreeMap<u32, (u32, u16)> = self.get_pages();
-        page_numbers
-            .iter()
-            .flat_map(|page_number| {
-                let result = self.extract_text_chunks_from_page(&pages, *page_number);
-                match result {
-                    Ok(text_chunks) => text_chunks,
-                    Err(err) => vec![Err(err)],
-                }
-            })
+        self.extract_text_chunks_with_options(page_numbers, &ExtractionOptions::default())
+    }
+
+    /// Like [`Document::extract_text_chunks`], with `options`.
+    pub fn extract_text_chunks_with_options(
+        &self, page_numbers: &[u32], options: &ExtractionOptions,
+    ) -> Vec<Result<String>> {
+        self.page_text_chunks(page_numbers, options, false)
+            .flat_map(|(text_chunks, _)| text_chunks)
             .collect()
     }
 
+    /// The text chunks of each page, with its trace if `trace`, reporting progress as pages are
+    /// extracted.
+    fn page_text_chunks<'a>(
+        &'a self, page_numbers: &'a [u32], options: &'a ExtractionOptions, trace: bool,
+    ) -> impl Iterator<Item = (Vec<Result<String>>, Option<ExtractionTrace>)> + 'a {
+        let pages: BTreeMap<u32, (u32, u16)> = self.get_pages();
+        let total = Some(page_numbers.len() as u64);
+        page_numbers.iter().enumerate().map(move |(index, &page_number)| {
+            let mut trace = trace.then(|| ExtractionTrace::new(page_number));
+            let result = self.extract_text_chunks_from_page(&pages, page_number, options, trace.as_mut());
+            progress::report(options.progress.as_ref(), Phase::ExtractPage, index as u64 + 1, total);
+            let text_chunks = match result {
+                Ok(text_chunks) => text_chunks,
+                Err(err) => vec![Err(err)],
+            };
+            (text_chunks, trace)
+        })
+    }
+
     fn extract_text_chunks_from_page(
-        &self, pages: &BTreeMap<u32, (u32, u16)>, page_number: u32,
+        &self, pages: &BTreeMap<u32, (u32, u16)>, page_number: u32, options: &ExtractionOptions,
+        mut trace: Option<&mut ExtractionTrace>,
     ) -> Result<Vec<Result<String>>> {
         let mut collected_chunks_and_errs: Vec<std::result::Result<String, Error>> = Vec::new();
 
         let page_id = *pages.get(&page_number).ok_or(Error::PageNumberNotFound(page_number))?;
-        let fonts = self.get_page_fonts(page_id)?;
-        let encodings: BTreeMap<Vec<u8>, Encoding> = fonts
-            .into_iter()
-            .filter_map(|(name, font)| match font.get_font_encoding(self) {
-                Ok(it) => Some((name, it)),
-                Err(err) => {
-                    collected_chunks_and_errs.push(Err(err));
-                    None
-                }
-            })
-            .collect();
-        let content_data = self.get_page_content(page_id)?;
-        let content = Content::decode(&content_data)?;
+
+        // Fonts are resolved through the resources in scope, so a form's own /F1 takes precedence
+        // over the page's. Encodings of shared font objects are built and reported once.
+        let mut encodings: Vec<Encoding> = Vec::new();
+        let mut encoding_index: HashMap<ObjectId, Option<usize>> = HashMap::new();
 
         // each text with different encoding is extracted as separate chunk
-        let mut current_encoding = None;
+        let mut current_encoding: Option<usize> = None;
         let mut current_text = String::new();
-        for operation in &content.operations {
+        // The /ActualText of marked content replaces the text it shows, e.g. glyphs of shaped
+        // right-to-left text in visual order.
+        let mut marked_content: Vec<Option<String>> = Vec::new();
+        let max_form_depth = options.max_form_depth.unwrap_or(DEFAULT_MAX_FORM_DEPTH);
+        ContentWalker::new(self).with_max_depth(max_form_depth).walk_page(page_id, |operation, context| {
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.enter_form(context.form_id);
+            }
             match operation.operator.as_ref() {
                 "Tf" => {
                     let current_font = operation
                         .operands
                         .first()
-                        .ok_or_else(|| Error::Syntax("missing font operand".to_string()))?
-                        .as_name();
-                    current_encoding = match current_font {
-                        Ok(font) => encodings.get(font),
+                        .ok_or_else(|| Error::Syntax("missing font operand".to_string()))
+                        .and_then(Object::as_name);
+                    let font = match current_font {
+                        Ok(name) => {
+                            let font = context.font(name);
+                            if let (None, Some(trace)) = (font, trace.as_deref_mut()) {
+                                let reason = format!("font /{} not found", String::from_utf8_lossy(name));
+                                trace.skip("Tf", context.form_id, reason);
+                            }
+                            font.map(|font| (name, font))
+                        }
                         Err(err) => {
+                            if let Some(trace) = trace.as_deref_mut() {
+                                trace.skip("Tf", context.form_id, err.to_string());
+                            }
                             collected_chunks_and_errs.push(Err(err));
                             None
                         }
                     };
+                    current_encoding = font.and_then(|(name, (font_id, font))| {
+                        if let Some(index) = font_id.and_then(|id| encoding_index.get(&id)) {
+                            return *index;
+                        }
+                        let index = match font.font_encoding_with_source(self) {
+                            Ok((encoding, source)) => {
+                                encodings.push(encoding);
+                                if let Some(trace) = trace.as_deref_mut() {
+                                    let base_font = font.get(b"BaseFont").and_then(Object::as_name).ok();
+                                    trace.fonts.push(FontTrace {
+                                        name: name.to_vec(),
+                                        font_id,
+                                        base_font: base_font.map(<[u8]>::to_vec),
+                                        source,
+                                        unmapped_glyphs: 0,
+                                    });
+                                }
+                                Some(encodings.len() - 1)
+                            }
+                            Err(err) => {
+                                if let Some(trace) = trace.as_deref_mut() {
+                                    trace.skip("Tf", context.form_id, err.to_string());
+                                }
+                                collected_chunks_and_errs.push(Err(err));
+                                None
+                            }
+                        };
+                        if let Some(font_id) = font_id {
+                            encoding_index.insert(font_id, index);
+                        }
+                        index
+                    });
 
                     if !current_text.is_empty() {
-                        collected_chunks_and_errs.push(Ok(current_text));
-                        current_text = String::new();
+                        collected_chunks_and_errs.push(Ok(std::mem::take(&mut current_text)));
                     }
                 }
+                "BMC" => marked_content.push(None),
+                "BDC" => {
+                    let properties = match operation.operands.get(1) {
+                        Some(Object::Name(name)) => context.resource(b"Properties", name).map(|(_, object)| object),
+                        properties => properties,
+                    };
+                    let actual_text = properties
+                        .and_then(|properties| properties.as_dict().ok())
+                        .and_then(|properties| properties.get_deref(b"ActualText", self).ok())
+                        .and_then(|actual_text| decode_text_string(actual_text).ok());
+                    marked_content.push(actual_text);
+                }
+                "EMC" => {
+                    if let Some(Some(actual_text)) = marked_content.pop() {
+                        if !marked_content.iter().any(Option::is_some) {
+                            current_text.push_str(&actual_text);
+                        }
+                    }
+                }
+                "Tj" | "TJ" if marked_content.iter().any(Option::is_some) => {}
                 "Tj" | "TJ" => match current_encoding {
-                    Some(encoding) => {
-                        let res = collect_text(&mut current_text, encoding, &operation.operands);
+                    Some(index) => {
+                        let res = collect_text(&mut current_text, &encodings[index], &operation.operands);
+                        if let Some(trace) = trace.as_deref_mut() {
+                            match &res {
+                                Ok(()) => {
+                                    trace.fonts[index].unmapped_glyphs +=
+                                        unmapped_glyphs(&encodings[index], &operation.operands)
+                                }
+                                Err(err) => trace.skip(&operation.operator, context.form_id, err.to_string()),
+                            }
+                        }
                         if let Err(err) = res {
                             collected_chunks_and_errs.push(Err(err));
                         }
                     }
-                    None => warn!("Could not decode extracted text"),
-                },
-                "ET" => {
-                    if !current_text.ends_with('\n') {
-                        current_text.push('\n')
+                    None => {
+                        warn!("Could not decode extracted text");
+                        if let Some(trace) = trace.as_deref_mut() {
+                            trace.skip(&operation.operator, context.form_id, "no font with a usable encoding selected");
+                        }
                     }
-                }
+                },
+                "ET" if !current_text.ends_with('\n') => current_text.push('\n'),
                 _ => {}
             }
-        }
+        })?;
         if !current_text.is_empty() {
             collected_chunks_and_errs.push(Ok(current_text));
         }
@@ -140,6 +300,22 @@ fn extract_text_chunks_from_page(
 
     pub fn replace_text(
         &mut self, page_number: u32, text: &str, other_text: &str, default_str: Option<&str>,
+    ) -> Result<()> {
+        let options = ReplaceOptions {
+            default_str: default_str.map(str::to_string),
+            ..ReplaceOptions::default()
+        };
+        self.replace_text_with_options(page_number, text, other_text, &options)
+    }
+
+    /// Replace text strings equal to `text` on a page with `other_text`.
+    ///
+    /// The replacement is written in the encoding of the string's font. Where the font's ToUnicode
+    /// CMap maps several codes to a character, the code the string already uses is kept. Characters
+    /// the font cannot represent are substituted according to `options`, and fail the replacement
+    /// with [`Error::UnsupportedCharacters`] otherwise, leaving the page unchanged.
+    pub fn replace_text_with_options(
+        &mut self, page_number: u32, text: &str, other_text: &str, options: &ReplaceOptions,
     ) -> Result<()> {
         let page = page_number.saturating_sub(1) as usize;
         let page_id = self
@@ -167,7 +343,7 @@ pub fn replace_text(
                 "Tj" | "TJ" => {
                     match current_encoding {
                         Some(encoding) => {
-                            try_to_replace_encoded_text(operation, encoding, text, other_text, default_str.unwrap_or(""))?
+                            try_to_replace_encoded_text(operation, encoding, text, other_text, options)?
                         }
                         None => {
                             warn!("Could not decode extracted text, some of the occurances might not be properly replaced")
@@ -187,6 +363,18 @@ pub fn replace_partial_text(
         search_text: &str,
         replacement_text: &str,
         default_char: Option<&str>,
+    ) -> Result<usize> {
+        let options = ReplaceOptions {
+            default_str: Some(default_char.unwrap_or("?").to_string()),
+            ..ReplaceOptions::default()
+        };
+        self.replace_partial_text_with_options(page_number, search_text, replacement_text, &options)
+    }
+
+    /// Like [`Document::replace_partial_text`], substituting characters the font cannot represent
+    /// according to `options`, see [`Document::replace_text_with_options`].
+    pub fn replace_partial_text_with_options(
+        &mut self, page_number: u32, search_text: &str, replacement_text: &str, options: &ReplaceOptions,
     ) -> Result<usize> {
         let page = page_number.saturating_sub(1) as usize;
         let page_id = self
@@ -222,7 +410,7 @@ pub fn replace_partial_text(
                             encoding,
                             search_text,
                             replacement_text,
-                            default_char.unwrap_or("?"),
+                            options,
                         )?;
                     } else {
                         warn!("No encoding found for text operation");
@@ -285,7 +473,7 @@ pub fn insert_form_object(&mut self, page_id: ObjectId, form_obj: Stream) -> Res
         self.change_page_content(page_id, modified_content)
     }
 }
-fn collect_text(text: &mut String, encoding: &Encoding, operands: &[Object]) -> Result<()> {
+pub(crate) fn collect_text(text: &mut String, encoding: &Encoding, operands: &[Object]) -> Result<()> {
     for operand in operands.iter() {
         match operand {
             Object::String(bytes, _) => {
@@ -295,10 +483,8 @@ fn collect_text(text: &mut String, encoding: &Encoding, operands: &[Object]) ->
                 collect_text(text, encoding, arr)?;
                 text.push(' ');
             }
-            Object::Integer(i) => {
-                if *i < -100 {
-                    text.push(' ');
-                }
+            Object::Integer(i) if *i < -100 => {
+                text.push(' ');
             }
             _ => {}
         }
@@ -329,41 +515,36 @@ pub fn substring(s: &str, start: usize) -> &str {
     s.char_indices().nth(start).map(|(idx, _)| &s[idx..]).unwrap_or("")
 }
 
-fn encode(encoding: &Encoding, txt: &str, default_str: &str) -> Vec<u8> {
-    if txt.chars().count() > 1 {
-        let mut cur = 0;
-        let mut result = Vec::new();
-        while cur < txt.chars().count() {
-            let c = substr(txt, cur, 1);
-            result.extend_from_slice(&encode(encoding, c, default_str));
-            cur += 1;
-        }
-        result
-    } else {
-        let encoded_bytes = Document::encode_text(encoding, txt);
-        if !encoded_bytes.is_empty() {
-            encoded_bytes
-        } else {
-            Document::encode_text(encoding, default_str)
-        }
-    }
+fn encode(mapping: &ReverseMapping, text: &str, options: &ReplaceOptions) -> Result<Vec<u8>> {
+    mapping.encode(text, &options.transliterations, options.default_str.as_deref())
 }
+
 fn try_to_replace_encoded_text(
-    operation: &mut Operation, encoding: &Encoding, text_to_replace: &str, replacement: &str, default_str: &str,
+    operation: &mut Operation, encoding: &Encoding, text_to_replace: &str, replacement: &str,
+    options: &ReplaceOptions,
 ) -> Result<()> {
     for operand in &mut operation.operands {
         match operand {
             Object::String(bytes, _) => {
                 let decoded_text = Document::decode_text(encoding, bytes)?;
                 if decoded_text == text_to_replace {
-                    let encoded_bytes = encode(encoding, replacement, default_str);
-                    *bytes = encoded_bytes;
+                    let mut mapping = ReverseMapping::new(encoding);
+                    mapping.prefer(bytes);
+                    *bytes = encode(&mapping, replacement, options)?;
                 }
             }
             Object::Array(arr) => {
                 let mut str_collected = String::new();
                 collect_text(&mut str_collected, encoding, arr)?;
                 if str_collected == text_to_replace {
+                    let mut mapping = ReverseMapping::new(encoding);
+                    for item in arr.iter() {
+                        if let Object::String(bytes, _) = item {
+                            mapping.prefer(bytes);
+                        }
+                    }
+                    // Encoding all of the replacement first reports every unsupported character.
+                    encode(&mapping, replacement, options)?;
                     let s_len = str_collected.chars().count();
                     let r_len = replacement.chars().count();
                     let mut cur = 0;
@@ -371,15 +552,13 @@ fn try_to_replace_encoded_text(
                         if let Object::String(bytes, _f) = item {
                             if cur == s_len - 1 {
                                 let sub = substring(replacement, cur);
-                                let encoded_bytes = encode(encoding, sub, default_str);
-                                *bytes = encoded_bytes;
+                                *bytes = encode(&mapping, sub, options)?;
                                 break;
                             } else if cur > r_len {
                                 *item = Object::Null;
                             } else {
                                 let sub = substr(replacement, cur, 1);
-                                let encoded_bytes = encode(encoding, sub, default_str);
-                                *bytes = encoded_bytes;
+                                *bytes = encode(&mapping, sub, options)?;
                             }
                             cur += 1;
                         }
@@ -398,7 +577,7 @@ fn replace_partial_in_operation(
     encoding: &Encoding,
     search_text: &str,
     replacement_text: &str,
-    default_char: &str,
+    options: &ReplaceOptions,
 ) -> Result<usize> {
     let mut replacement_count = 0;
     
@@ -408,8 +587,9 @@ fn replace_partial_in_operation(
                 let decoded_text = Document::decode_text(encoding, bytes)?;
                 if decoded_text.contains(search_text) {
                     let new_text = decoded_text.replace(search_text, replacement_text);
-                    let encoded_bytes = encode_with_fallback(encoding, &new_text, default_char);
-                    *bytes = encoded_bytes;
+                    let mut mapping = ReverseMapping::new(encoding);
+                    mapping.prefer(bytes);
+                    *bytes = encode(&mapping, &new_text, options)?;
                     replacement_count += decoded_text.matches(search_text).count();
                 }
             }
@@ -419,7 +599,7 @@ fn replace_partial_in_operation(
                     encoding,
                     search_text,
                     replacement_text,
-                    default_char,
+                    options,
                 )?;
             }
             _ => {}
@@ -434,7 +614,7 @@ fn replace_partial_in_array(
     encoding: &Encoding,
     search_text: &str,
     replacement_text: &str,
-    default_char: &str,
+    options: &ReplaceOptions,
 ) -> Result<usize> {
     let mut replacement_count = 0;
 
@@ -443,8 +623,9 @@ fn replace_partial_in_array(
             let decoded_text = Document::decode_text(encoding, bytes)?;
             if decoded_text.contains(search_text) {
                 let new_text = decoded_text.replace(search_text, replacement_text);
-                let encoded_bytes = encode_with_fallback(encoding, &new_text, default_char);
-                *bytes = encoded_bytes;
+                let mut mapping = ReverseMapping::new(encoding);
+                mapping.prefer(bytes);
+                *bytes = encode(&mapping, &new_text, options)?;
                 replacement_count += decoded_text.matches(search_text).count();
             }
         }
@@ -453,19 +634,6 @@ fn replace_partial_in_array(
     Ok(replacement_count)
 }
 
-fn encode_with_fallback(
-    encoding: &Encoding,
-    text: &str,
-    default_char: &str,
-) -> Vec<u8> {
-    let encoded = Document::encode_text(encoding, text);
-    if !encoded.is_empty() {
-        return encoded;
-    }
-    
-    encode(encoding, text, default_char)
-}
-
 /// Decode CrossReferenceStream
 pub fn decode_xref_stream(mut stream: Stream) -> Result<(Xref, Dictionary)> {
     if stream.is_compressed() {
@@ -513,7 +681,7 @@ pub fn decode_xref_stream(mut stream: Stream) -> Result<(Xref, Dictionary)> {
                 match entry_type {
                     0 => {
                         // free object
-                        let next_free_object = read_big_endian_integer(&mut reader, bytes2.as_mut_slice())?;
+                        let next_free_object = read_big_endian_integer(&mut reader, bytes2.as_mut_slice())? as u32;
                         let generation = read_big_endian_integer(&mut reader, bytes3.as_mut_slice())? as u16;
                         xref.insert((start + j) as u32, XrefEntry::Free { next_free_object, generation });
                     }
@@ -529,7 +697,7 @@ pub fn decode_xref_stream(mut stream: Stream) -> Result<(Xref, Dictionary)> {
                     }
                     2 => {
                         // compressed object
-                        let container = read_big_endian_integer(&mut reader, bytes2.as_mut_slice())?;
+                        let container = read_big_endian_integer(&mut reader, bytes2.as_mut_slice())? as u32;
                         let index = read_big_endian_integer(&mut reader, bytes3.as_mut_slice())? as u16;
                         xref.insert((start + j) as u32, XrefEntry::Compressed { container, index });
                     }
@@ -544,11 +712,11 @@ pub fn decode_xref_stream(mut stream: Stream) -> Result<(Xref, Dictionary)> {
     Ok((xref, dict))
 }
 
-fn read_big_endian_integer(reader: &mut Cursor<Vec<u8>>, buffer: &mut [u8]) -> Result<u32> {
+fn read_big_endian_integer(reader: &mut Cursor<Vec<u8>>, buffer: &mut [u8]) -> Result<u64> {
     reader.read_exact(buffer)?;
     let mut value = 0;
     for &mut byte in buffer {
-        value = (value << 8) + u32::from(byte);
+        value = (value << 8) + u64::from(byte);
     }
     Ok(value)
 }
@@ -622,6 +790,43 @@ fn extract_text_concatenates_text_from_multiple_pages() {
         assert_eq!(extracted_text.unwrap(), format!("{text1}\n{text2}\n"));
     }
 
+    #[test]
+    fn extract_text_from_nested_forms() {
+        use crate::content_walker::tests::{add_page_form, form};
+        use crate::creator::tests::create_document_with_texts;
+
+        let mut doc = create_document_with_texts(&["Page text"]);
+        let inner_id = doc.add_object(form("BT /F1 12 Tf (Inner form text) Tj ET", None));
+        let outer = form(
+            "BT /F1 12 Tf (Outer form text) Tj ET /Inner Do",
+            Some(dictionary! { "XObject" => dictionary! { "Inner" => inner_id } }),
+        );
+        add_page_form(&mut doc, "Outer", outer);
+
+        let extracted_text = doc.extract_text(&[1]).unwrap();
+        assert_eq!(extracted_text, "Page text\nOuter form text\nInner form text\n");
+    }
+
+    #[test]
+    fn extract_text_from_self_referencing_form() {
+        use crate::content_walker::tests::{add_page_form, form};
+        use crate::creator::tests::create_document_with_texts;
+        use crate::Object;
+
+        let mut doc = create_document_with_texts(&["Page text"]);
+        let form_id = doc.new_object_id();
+        let looping = form(
+            "BT /F1 12 Tf (Loop) Tj ET /Self Do",
+            Some(dictionary! { "XObject" => dictionary! { "Self" => form_id } }),
+        );
+        doc.objects.insert(form_id, Object::Stream(looping));
+        let wrapper = form("/Self Do", Some(dictionary! { "XObject" => dictionary! { "Self" => form_id } }));
+        add_page_form(&mut doc, "Wrapper", wrapper);
+
+        let extracted_text = doc.extract_text(&[1]).unwrap();
+        assert_eq!(extracted_text, "Page text\nLoop\n");
+    }
+
     #[test]
     fn test_replace_partial_text() {
         use crate::creator::tests::create_document_with_texts;
@@ -633,4 +838,125 @@ fn test_replace_partial_text() {
         let extracted_text = doc.extract_text(&[1]).unwrap();
         assert!(extracted_text.contains("Hi World! Hi Universe!"));
     }
-}
\ No newline at end of file
+
+    const DUPLICATE_CODES_CMAP: &[u8] = b"/CIDInit /ProcSet findresource begin
+12 dict begin
+begincmap
+/CMapName /Duplicates def
+/CMapType 2 def
+1 begincodespacerange
+<0000> <FFFF>
+endcodespacerange
+1 beginbfrange
+<0020> <007E> <0020>
+endbfrange
+2 beginbfchar
+<0101> <00E9>
+<0102> <00E9>
+endbfchar
+endcmap
+CMapName currentdict /CMap defineresource pop
+end
+end";
+
+    /// A page showing "Hé" with the second of two codes for "é", and "A", in a font whose
+    /// ToUnicode CMap maps both codes to "é".
+    fn create_document_with_duplicate_codes() -> crate::Document {
+        use crate::content::{Content, Operation};
+        use crate::creator::tests::create_document_with_texts;
+        use crate::{Object, Stream, StringFormat};
+
+        let mut doc = create_document_with_texts(&["page"]);
+        let page_id = doc.page_iter().next().unwrap();
+        let to_unicode = doc.add_object(Stream::new(dictionary! {}, DUPLICATE_CODES_CMAP.to_vec()));
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "Subset",
+            "Encoding" => "Identity-H",
+            "ToUnicode" => to_unicode,
+        });
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Resources", dictionary! { "Font" => dictionary! { "F2" => font_id } });
+        let hex = |bytes: &[u8]| Object::String(bytes.to_vec(), StringFormat::Hexadecimal);
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F2".into(), 12.into()]),
+                Operation::new("Tj", vec![hex(&[0x00, 0x48, 0x01, 0x02])]),
+                Operation::new("Tj", vec![hex(&[0x00, 0x41])]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        doc.change_page_content(page_id, content.encode().unwrap()).unwrap();
+        doc
+    }
+
+    fn shown_strings(doc: &crate::Document) -> Vec<Vec<u8>> {
+        let page_id = doc.page_iter().next().unwrap();
+        let content = doc.get_and_decode_page_content(page_id).unwrap();
+        content
+            .operations
+            .iter()
+            .filter(|op| op.operator == "Tj")
+            .map(|op| op.operands[0].as_str().unwrap().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn replace_text_with_duplicate_codes() {
+        let mut doc = create_document_with_duplicate_codes();
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "HéA\n");
+
+        // The string's own code for "é" is kept, elsewhere the lowest code is written.
+        doc.replace_text(1, "Hé", "éH", None).unwrap();
+        doc.replace_text(1, "A", "é", None).unwrap();
+        assert_eq!(shown_strings(&doc), [vec![0x01, 0x02, 0x00, 0x48], vec![0x01, 0x01]]);
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "éHé\n");
+    }
+
+    #[test]
+    fn replace_text_with_unsupported_characters() {
+        use crate::{Error, ReplaceOptions};
+
+        let mut doc = create_document_with_duplicate_codes();
+        let original = shown_strings(&doc);
+
+        let result = doc.replace_text(1, "A", "Ω’s", None);
+        assert!(matches!(result, Err(Error::UnsupportedCharacters(chars)) if chars == ['Ω', '’']));
+        assert_eq!(shown_strings(&doc), original);
+
+        let mut options = ReplaceOptions::default();
+        options.transliterations.insert('’', "'".to_string());
+        let result = doc.replace_text_with_options(1, "A", "Ω’s", &options);
+        assert!(matches!(result, Err(Error::UnsupportedCharacters(chars)) if chars == ['Ω']));
+
+        options.default_str = Some("?".to_string());
+        doc.replace_text_with_options(1, "A", "Ω’s", &options).unwrap();
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "Hé?'s\n");
+    }
+
+    #[test]
+    fn operations_spanning_content_streams() {
+        use crate::creator::tests::create_document;
+        use crate::{Object, Stream};
+
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let first = doc.add_object(Stream::new(dictionary! {}, b"BT /F1 12".to_vec()));
+        let second = doc.add_object(Stream::new(dictionary! {}, b"Tf (Hi) Tj ET".to_vec()));
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Contents", vec![first.into(), second.into()]);
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "Hi\n");
+
+        // Editing writes the content back as a single stream and drops the fragments.
+        assert_eq!(doc.replace_partial_text(1, "Hi", "Yo", None).unwrap(), 1);
+        let contents = doc.get_dictionary(page_id).unwrap().get(b"Contents").unwrap();
+        let content_id = contents.as_reference().unwrap();
+        assert!(doc.get_object(content_id).and_then(Object::as_stream).is_ok());
+        assert!(doc.get_object(first).is_err() && doc.get_object(second).is_err());
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "Yo\n");
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeSet;
+
+use crate::{Dictionary, Document, Object, ObjectId, Result};
+
+/// Where to look for a /PieceInfo dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceInfoTarget {
+    /// The document catalog
+    Catalog,
+    /// A page object
+    Page(ObjectId),
+    /// Any other object that can carry page-piece data, e.g. a Form XObject
+    Object(ObjectId),
+}
+
+/// Private data stored by one application in a page-piece dictionary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceInfoEntry {
+    /// Name of the application, e.g. `Illustrator`
+    pub application: Vec<u8>,
+    /// When the application last modified its data, see [`Object::as_datetime`]
+    pub last_modified: Option<Object>,
+    /// The application's /Private data, usually a reference to a dictionary or stream
+    pub private: Option<Object>,
+}
+
+impl Document {
+    /// Get the per-application entries of the /PieceInfo dictionary of `target`.
+    ///
+    /// Returns an empty list when the target has no /PieceInfo.
+    pub fn get_piece_info(&self, target: PieceInfoTarget) -> Result<Vec<PieceInfoEntry>> {
+        let holder = match target {
+            PieceInfoTarget::Catalog => self.catalog()?,
+            PieceInfoTarget::Page(id) | PieceInfoTarget::Object(id) => match self.get_object(id)? {
+                Object::Stream(stream) => &stream.dict,
+                object => object.as_dict()?,
+            },
+        };
+        let Ok(piece_info) = holder.get_deref(b"PieceInfo", self).and_then(Object::as_dict) else {
+            return Ok(Vec::new());
+        };
+
+        let entries = piece_info
+            .iter()
+            .filter_map(|(application, data)| {
+                let data = self.dereference(data).ok()?.1.as_dict().ok()?;
+                Some(PieceInfoEntry {
+                    application: application.clone(),
+                    last_modified: data.get_deref(b"LastModified", self).ok().cloned(),
+                    private: data.get(b"Private").ok().cloned(),
+                })
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    /// Remove the data of application `app_name` from every /PieceInfo dictionary in the document.
+    ///
+    /// /PieceInfo dictionaries left empty are removed as well. The private data itself stays in
+    /// the document until it is pruned with [`Document::prune_objects`].
+    /// Returns the number of entries removed.
+    pub fn remove_piece_info(&mut self, app_name: &[u8]) -> usize {
+        let mut removed = 0;
+        for (holder_id, piece_info_id) in self.piece_info_holders() {
+            let piece_info = match piece_info_id {
+                Some(id) => self.get_dictionary_mut(id).ok(),
+                None => self
+                    .get_object_mut(holder_id)
+                    .ok()
+                    .and_then(|object| holder_dict_mut(object))
+                    .and_then(|holder| holder.get_mut(b"PieceInfo").and_then(Object::as_dict_mut).ok()),
+            };
+            let Some(piece_info) = piece_info else {
+                continue;
+            };
+            if piece_info.remove(app_name).is_none() {
+                continue;
+            }
+            removed += 1;
+            if piece_info.is_empty() {
+                if let Some(holder) = self.get_object_mut(holder_id).ok().and_then(holder_dict_mut) {
+                    holder.remove(b"PieceInfo");
+                }
+            }
+        }
+        removed
+    }
+
+    /// Names of all applications with data in a /PieceInfo dictionary of the document.
+    pub fn piece_info_applications(&self) -> BTreeSet<Vec<u8>> {
+        self.piece_info_holders()
+            .into_iter()
+            .filter_map(|(holder_id, _)| self.get_object(holder_id).ok())
+            .filter_map(|object| match object {
+                Object::Stream(stream) => Some(&stream.dict),
+                object => object.as_dict().ok(),
+            })
+            .filter_map(|holder| holder.get_deref(b"PieceInfo", self).and_then(Object::as_dict).ok())
+            .flat_map(|piece_info| piece_info.iter().map(|(application, _)| application.clone()))
+            .collect()
+    }
+
+    /// Objects carrying a /PieceInfo entry, with the id of the /PieceInfo dictionary when it is
+    /// an indirect object.
+    fn piece_info_holders(&self) -> Vec<(ObjectId, Option<ObjectId>)> {
+        self.objects
+            .iter()
+            .filter_map(|(id, object)| {
+                let holder = match object {
+                    Object::Stream(stream) => &stream.dict,
+                    object => object.as_dict().ok()?,
+                };
+                let piece_info = holder.get(b"PieceInfo").ok()?;
+                Some((*id, piece_info.as_reference().ok()))
+            })
+            .collect()
+    }
+}
+
+fn holder_dict_mut(object: &mut Object) -> Option<&mut Dictionary> {
+    match object {
+        Object::Stream(stream) => Some(&mut stream.dict),
+        object => object.as_dict_mut().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use crate::Stream;
+
+    /// Attach Illustrator-style private data to the first page and return the ids of the
+    /// private dictionary and the stream it references.
+    fn add_illustrator_data(doc: &mut Document) -> (ObjectId, ObjectId) {
+        let blob_id = doc.add_object(Stream::new(dictionary! {}, b"%AI12_CompressedData".to_vec()));
+        let private_id = doc.add_object(dictionary! {
+            "AIMetaData" => blob_id,
+            "NumBlock" => 1,
+        });
+        let page_id = doc.page_iter().next().unwrap();
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set("LastModified", Object::string_literal("D:20240131120000Z"));
+        page.set(
+            "PieceInfo",
+            dictionary! {
+                "Illustrator" => dictionary! {
+                    "LastModified" => Object::string_literal("D:20240131120000Z"),
+                    "Private" => private_id,
+                },
+            },
+        );
+        (private_id, blob_id)
+    }
+
+    #[test]
+    fn piece_info_survives_round_trip() {
+        let mut doc = create_document();
+        let (private_id, _) = add_illustrator_data(&mut doc);
+        let mut buffer = Vec::new();
+        doc.save_to(&mut buffer).unwrap();
+
+        let loaded = Document::load_mem(&buffer).unwrap();
+        let page_id = loaded.page_iter().next().unwrap();
+        let entries = loaded.get_piece_info(PieceInfoTarget::Page(page_id)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].application, b"Illustrator");
+        assert!(entries[0].last_modified.as_ref().and_then(Object::as_datetime).is_some());
+        assert_eq!(entries[0].private, Some(Object::Reference(private_id)));
+        assert!(loaded.get_piece_info(PieceInfoTarget::Catalog).unwrap().is_empty());
+    }
+
+    #[test]
+    fn deep_copied_pages_keep_their_own_piece_info() {
+        let mut doc = create_document();
+        let (private_id, blob_id) = add_illustrator_data(&mut doc);
+        let copy_id = doc.duplicate_page(1, crate::DuplicateMode::DeepCopy).unwrap();
+
+        let entries = doc.get_piece_info(PieceInfoTarget::Page(copy_id)).unwrap();
+        let copied_private = entries[0].private.as_ref().and_then(|private| private.as_reference().ok()).unwrap();
+        assert_ne!(copied_private, private_id);
+        let copied_blob = doc.get_dictionary(copied_private).unwrap().get(b"AIMetaData").unwrap();
+        let copied_blob = copied_blob.as_reference().unwrap();
+        assert_ne!(copied_blob, blob_id);
+        assert_eq!(doc.get_object(copied_blob).and_then(Object::as_stream).unwrap().content, b"%AI12_CompressedData");
+    }
+
+    #[test]
+    fn removing_piece_info_prunes_private_data() {
+        let mut doc = create_document();
+        let (private_id, blob_id) = add_illustrator_data(&mut doc);
+        doc.catalog_mut().unwrap().set(
+            "PieceInfo",
+            dictionary! { "InDesign" => dictionary! { "Private" => dictionary! {} } },
+        );
+        assert_eq!(doc.piece_info_applications().len(), 2);
+
+        assert_eq!(doc.remove_piece_info(b"Illustrator"), 1);
+        assert_eq!(doc.remove_piece_info(b"Illustrator"), 0);
+        let page_id = doc.page_iter().next().unwrap();
+        assert!(!doc.get_dictionary(page_id).unwrap().has(b"PieceInfo"));
+        assert_eq!(doc.get_piece_info(PieceInfoTarget::Catalog).unwrap().len(), 1);
+
+        let pruned = doc.prune_objects();
+        assert!(pruned.contains(&private_id));
+        assert!(pruned.contains(&blob_id));
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::BTreeSet;
+
+use log::warn;
+
+use crate::content_walker::ContentWalker;
+use crate::encodings::Encoding;
+use crate::parser_aux::collect_text;
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result, Stream};
+
+/// Number of text chunks kept in [`PreviewInfo::largest_text`].
+const LARGEST_TEXT_COUNT: usize = 3;
+
+/// Size of a US Letter page, used when a page has no /MediaBox.
+pub(crate) const DEFAULT_PAGE_SIZE: (f32, f32) = (612.0, 792.0);
+
+/// What is needed to render a rough preview of a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewInfo {
+    /// Width and height of the visible area in points, after applying /Rotate
+    pub page_size: (f32, f32),
+    /// The image XObject covering the most pixels
+    pub largest_image: Option<PreviewImage>,
+    /// Text drawn with the largest effective font sizes, largest first
+    pub largest_text: Vec<PreviewText>,
+}
+
+/// An image XObject drawn on the page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewImage {
+    pub id: ObjectId,
+    pub width: u32,
+    pub height: u32,
+    pub data: PreviewImageData,
+}
+
+/// Pixels of a [`PreviewImage`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreviewImageData {
+    /// JPEG file data, passed through from a DCTDecode stream
+    Jpeg(Vec<u8>),
+    /// Decoded 8-bit RGB samples, row by row
+    Rgb(Vec<u8>),
+    /// Decoded 8-bit RGBA samples, row by row, for an image mask or an image with a /Mask
+    Rgba(Vec<u8>),
+    /// The image uses a filter or color space that can't be decoded to RGB
+    Undecoded,
+}
+
+/// A run of text shown with one font and size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewText {
+    /// Font size after applying the text and transformation matrices
+    pub font_size: f32,
+    pub text: String,
+}
+
+impl Document {
+    /// Collect what is needed to render a preview of a page: its size, its largest image and
+    /// its largest text.
+    ///
+    /// Use [`Document::load_preview`] to get the same information without loading the whole
+    /// document.
+    pub fn preview_info(&self, page_number: u32) -> Result<PreviewInfo> {
+        let page_id = *self
+            .get_pages()
+            .get(&page_number)
+            .ok_or(Error::PageNumberNotFound(page_number))?;
+        self.page_preview_info(page_id)
+    }
+
+    pub(crate) fn page_preview_info(&self, page_id: ObjectId) -> Result<PreviewInfo> {
+        let mut images = BTreeSet::new();
+        let mut chunks: Vec<PreviewText> = Vec::new();
+        let mut encoding: Option<Encoding> = None;
+        let mut font_size = 0.0;
+        let mut text_scale = 1.0;
+        let mut chunk_open = false;
+        ContentWalker::new(self).walk_page(page_id, |operation, context| match operation.operator.as_str() {
+            "Do" => {
+                let name = operation.operands.first().and_then(|name| name.as_name().ok());
+                if let Some((Some(id), Object::Stream(stream))) =
+                    name.and_then(|name| context.resource(b"XObject", name))
+                {
+                    if stream
+                        .dict
+                        .get(b"Subtype")
+                        .and_then(Object::as_name)
+                        .is_ok_and(|s| s == b"Image")
+                    {
+                        images.insert(id);
+                    }
+                }
+            }
+            "BT" => {
+                text_scale = 1.0;
+                chunk_open = false;
+            }
+            "ET" => chunk_open = false,
+            "Tf" => {
+                let font = operation.operands.first().and_then(|name| name.as_name().ok());
+                encoding = font
+                    .and_then(|font| context.font(font))
+                    .and_then(|(_, font)| font.get_font_encoding(self).ok());
+                font_size = operation
+                    .operands
+                    .get(1)
+                    .and_then(|size| size.as_float().ok())
+                    .unwrap_or(0.0);
+                chunk_open = false;
+            }
+            "Tm" => {
+                let matrix: Vec<f32> = operation.operands.iter().filter_map(|n| n.as_float().ok()).collect();
+                if matrix.len() == 6 {
+                    text_scale = matrix[2].hypot(matrix[3]);
+                }
+            }
+            "Tj" | "TJ" | "'" | "\"" => {
+                let Some(encoding) = &encoding else {
+                    return;
+                };
+                let ctm_scale = context.ctm[2].hypot(context.ctm[3]);
+                let size = (font_size * text_scale * ctm_scale).abs();
+                let mut text = String::new();
+                if let Err(err) = collect_text(&mut text, encoding, &operation.operands) {
+                    warn!("Could not decode text for preview: {err}");
+                    return;
+                }
+                match chunks.last_mut() {
+                    Some(chunk) if chunk_open && (chunk.font_size - size).abs() < 0.01 => chunk.text.push_str(&text),
+                    _ => chunks.push(PreviewText { font_size: size, text }),
+                }
+                chunk_open = true;
+            }
+            _ => {}
+        })?;
+
+        for chunk in &mut chunks {
+            chunk.text = chunk.text.trim().to_string();
+        }
+        chunks.retain(|chunk| !chunk.text.is_empty());
+        chunks.sort_by(|a, b| b.font_size.total_cmp(&a.font_size));
+        chunks.truncate(LARGEST_TEXT_COUNT);
+
+        let largest_image = images
+            .into_iter()
+            .filter_map(|id| {
+                let stream = self.get_object(id).and_then(Object::as_stream).ok()?;
+                let width = stream.dict.get(b"Width").and_then(Object::as_i64).ok()?;
+                let height = stream.dict.get(b"Height").and_then(Object::as_i64).ok()?;
+                Some((id, stream, u32::try_from(width).ok()?, u32::try_from(height).ok()?))
+            })
+            .max_by_key(|(_, _, width, height)| u64::from(*width) * u64::from(*height))
+            .map(|(id, stream, width, height)| PreviewImage {
+                id,
+                width,
+                height,
+                data: self.preview_image_data(stream),
+            });
+
+        Ok(PreviewInfo {
+            page_size: self.preview_page_size(page_id),
+            largest_image,
+            largest_text: chunks,
+        })
+    }
+
+    fn preview_image_data(&self, stream: &Stream) -> PreviewImageData {
+        let filters = stream.filters().unwrap_or_default();
+        if filters == [b"DCTDecode".as_slice()] {
+            return PreviewImageData::Jpeg(stream.content.clone());
+        }
+        match self.image_pixels(stream) {
+            Ok(pixels) if pixels.alpha.is_some() => PreviewImageData::Rgba(pixels.into_rgba8()),
+            Ok(pixels) => PreviewImageData::Rgb(pixels.rgb),
+            Err(_) => PreviewImageData::Undecoded,
+        }
+    }
+
+    /// Size of the page's crop box, or media box, after applying /Rotate.
+    fn preview_page_size(&self, page_id: ObjectId) -> (f32, f32) {
+        let Ok(page) = self.get_dictionary(page_id) else {
+            return DEFAULT_PAGE_SIZE;
+        };
+        let rect = [b"CropBox".as_slice(), b"MediaBox"]
+            .into_iter()
+            .find_map(|key| self.page_box(page, key))
+            .map(|[llx, lly, urx, ury]| (urx - llx, ury - lly));
+        let (width, height) = rect.unwrap_or(DEFAULT_PAGE_SIZE);
+        let rotate = self
+            .inherited_page_attribute(page, b"Rotate")
+            .and_then(|rotate| rotate.as_i64().ok())
+            .unwrap_or(0);
+        if rotate.rem_euclid(180) == 90 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Look up an attribute on the page or, failing that, its ancestors in the page tree.
+    pub(crate) fn inherited_page_attribute<'a>(&'a self, page: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
+        let mut node = page;
+        for _ in 0..64 {
+            if let Ok(value) = self.get_deref_with_aliases(node, key) {
+                return Some(value);
+            }
+            node = node.get_deref(b"Parent", self).and_then(Object::as_dict).ok()?;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{Content, Operation};
+
+    /// A page with a title, body text, a large JPEG and a small gray image, and a second page
+    /// with only the gray image.
+    fn create_preview_document() -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let photo_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 80,
+                "Height" => 60,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+                "Filter" => "DCTDecode",
+            },
+            b"\xFF\xD8\xFF\xE0 not really a JPEG \xFF\xD9".to_vec(),
+        ));
+        let icon_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 2,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![0, 64, 128, 255],
+        ));
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+            "XObject" => dictionary! { "Im1" => photo_id, "Im2" => icon_id },
+        });
+
+        let title_page = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                Operation::new(
+                    "Tm",
+                    vec![3.into(), 0.into(), 0.into(), 3.into(), 72.into(), 700.into()],
+                ),
+                Operation::new("Tj", vec![Object::string_literal("Annual ")]),
+                Operation::new("Tj", vec![Object::string_literal("Report")]),
+                Operation::new("ET", vec![]),
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 18.into()]),
+                Operation::new("Td", vec![72.into(), 650.into()]),
+                Operation::new("Tj", vec![Object::string_literal("Summary")]),
+                Operation::new("Tf", vec!["F1".into(), 10.into()]),
+                Operation::new("Tj", vec![Object::string_literal("Body text")]),
+                Operation::new("ET", vec![]),
+                Operation::new("q", vec![]),
+                Operation::new("cm", vec![2.into(), 0.into(), 0.into(), 2.into(), 0.into(), 0.into()]),
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 8.into()]),
+                Operation::new("Tj", vec![Object::string_literal("Caption")]),
+                Operation::new("ET", vec![]),
+                Operation::new("Q", vec![]),
+                Operation::new("Do", vec!["Im2".into()]),
+                Operation::new("Do", vec!["Im1".into()]),
+            ],
+        };
+        let icon_page = Content {
+            operations: vec![Operation::new("Do", vec!["Im2".into()])],
+        };
+        let mut kids = vec![];
+        for content in [title_page, icon_page] {
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            kids.push(
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                })
+                .into(),
+            );
+        }
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => 2,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                "Rotate" => 90,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    #[test]
+    fn preview_of_title_page() {
+        let doc = create_preview_document();
+        let info = doc.preview_info(1).unwrap();
+
+        assert_eq!(info.page_size, (842.0, 595.0));
+        let image = info.largest_image.unwrap();
+        assert_eq!((image.width, image.height), (80, 60));
+        assert!(matches!(image.data, PreviewImageData::Jpeg(data) if data.starts_with(b"\xFF\xD8")));
+
+        let texts: Vec<(f32, &str)> = info
+            .largest_text
+            .iter()
+            .map(|text| (text.font_size, text.text.as_str()))
+            .collect();
+        assert_eq!(texts, [(36.0, "Annual Report"), (18.0, "Summary"), (16.0, "Caption")]);
+    }
+
+    #[test]
+    fn preview_decodes_gray_image_to_rgb() {
+        let doc = create_preview_document();
+        let info = doc.preview_info(2).unwrap();
+        let image = info.largest_image.unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(
+            image.data,
+            PreviewImageData::Rgb(vec![0, 0, 0, 64, 64, 64, 128, 128, 128, 255, 255, 255])
+        );
+        assert!(info.largest_text.is_empty());
+    }
+
+    #[test]
+    fn preview_of_masked_image_has_alpha() {
+        let mut doc = create_preview_document();
+        let icon_id = doc.preview_info(2).unwrap().largest_image.unwrap().id;
+        let icon = doc.get_object_mut(icon_id).and_then(Object::as_stream_mut).unwrap();
+        icon.dict.set("Mask", vec![0.into(), 10.into()]);
+        let info = doc.preview_info(2).unwrap();
+        assert_eq!(
+            info.largest_image.unwrap().data,
+            PreviewImageData::Rgba(vec![0, 0, 0, 0, 64, 64, 64, 255, 128, 128, 128, 255, 255, 255, 255, 255])
+        );
+    }
+
+    #[test]
+    fn preview_loaded_lazily_matches_full_load() {
+        let mut doc = create_preview_document();
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+
+        let loaded = Document::load_mem(&bytes).unwrap();
+        for page_number in [1, 2] {
+            let lazy = Document::load_preview_mem(&bytes, page_number).unwrap();
+            assert_eq!(lazy, loaded.preview_info(page_number).unwrap());
+        }
+        assert_eq!(
+            Document::load_preview_mem(&bytes, 1).unwrap().largest_text[0].text,
+            "Annual Report"
+        );
+        assert!(matches!(
+            Document::load_preview_mem(&bytes, 3),
+            Err(Error::PageNumberNotFound(3))
+        ));
+    }
+}
@@ -18,23 +18,28 @@ pub fn change_producer(&mut self, producer: &str) {
         }
     }
 
-    /// Compress PDF stream objects.
+    /// Compress PDF stream objects, see [`Document::compress_streams`].
     pub fn compress(&mut self) {
-        for object in self.objects.values_mut() {
-            if let Object::Stream(stream) = object {
-                if stream.allows_compression {
-                    // Ignore any error and continue to compress other streams.
-                    let _ = stream.compress();
-                }
-            }
-        }
+        self.compress_streams();
     }
 
     /// Decompress PDF stream objects.
     pub fn decompress(&mut self) {
-        for object in self.objects.values_mut() {
-            if let Object::Stream(stream) = object {
-                let _ = stream.decompress();
+        let stream_ids: Vec<ObjectId> = self
+            .objects
+            .iter()
+            .filter(|(_, object)| matches!(object, Object::Stream(_)))
+            .map(|(&id, _)| id)
+            .collect();
+        for id in stream_ids {
+            let Some(Object::Stream(stream)) = self.objects.get(&id) else {
+                continue;
+            };
+            // Ignore any error and leave the stream as it is.
+            if let Ok(data) = self.decompressed_stream_content(stream) {
+                if let Some(Object::Stream(stream)) = self.objects.get_mut(&id) {
+                    stream.set_plain_content(data);
+                }
             }
         }
     }
@@ -269,6 +274,10 @@ pub fn change_content_stream(&mut self, stream_id: ObjectId, content: Vec<u8>) {
         }
     }
 
+    /// Replace the content of a page.
+    ///
+    /// A page whose content is split over several streams gets a single new stream; the old
+    /// fragments are deleted unless another page uses them as well.
     pub fn change_page_content(&mut self, page_id: ObjectId, content: Vec<u8>) -> Result<()> {
         let contents = self.get_dictionary(page_id).and_then(|page| page.get(b"Contents"))?;
         match contents {
@@ -276,13 +285,30 @@ pub fn change_page_content(&mut self, page_id: ObjectId, content: Vec<u8>) -> Re
             Object::Array(arr) => {
                 if arr.len() == 1 {
                     if let Ok(id) = arr[0].as_reference() {
-                        self.change_content_stream(id, content)
+                        self.change_content_stream(id, content);
+                        if let Ok(Object::Dictionary(dict)) = self.get_object_mut(page_id) {
+                            dict.set("Contents", id);
+                        }
                     }
                 } else {
-                    let new_stream = self.add_object(super::Stream::new(dictionary! {}, content));
+                    let fragments = self.get_page_contents(page_id);
+                    let mut new_stream = super::Stream::new(dictionary! {}, content);
+                    // Ignore any compression error.
+                    let _ = new_stream.compress();
+                    let new_stream = self.add_object(new_stream);
                     if let Ok(Object::Dictionary(dict)) = self.get_object_mut(page_id) {
                         dict.set("Contents", new_stream);
                     }
+                    let shared: Vec<ObjectId> = self
+                        .page_iter()
+                        .filter(|id| *id != page_id)
+                        .flat_map(|id| self.get_page_contents(id))
+                        .collect();
+                    for fragment in fragments {
+                        if !shared.contains(&fragment) {
+                            self.delete_object(fragment);
+                        }
+                    }
                 }
             }
             _ => {}
@@ -0,0 +1,319 @@
+use std::fmt;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "rayon")]
+use std::thread;
+#[cfg(feature = "rayon")]
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// A stage of a long operation, reported to a [`Progress`] sink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Reading the cross-reference sections, counting the sections read
+    ParseXref,
+    /// Parsing the objects listed in the cross-reference table, counting its entries
+    ParseObjects,
+    /// Adding the objects of object streams to the document, counting the object streams
+    ExpandObjectStreams,
+    /// Reading the data of streams whose /Length is only known once all objects are parsed,
+    /// counting the streams
+    ReadStreams,
+    /// Writing the objects of a document, counting them
+    SerializeObjects,
+    /// Writing the cross-reference table or stream, counting its entries
+    WriteXref,
+    /// Extracting the text of pages, counting them
+    ExtractPage,
+}
+
+/// Receives the progress of loading, saving and extracting text, through
+/// [`ReaderOptions::progress`](crate::ReaderOptions::progress),
+/// [`SaveOptionsBuilder::progress`](crate::SaveOptionsBuilder::progress) and
+/// [`ExtractionOptions::progress`](crate::ExtractionOptions::progress).
+///
+/// Reports come at coarse granularity, from the thread that started the operation. `done` never
+/// decreases within a phase, and `total` is given when it is known. Implementations should return
+/// quickly, as the operation waits for them.
+pub trait Progress: Send + Sync {
+    /// `done` items of `total` were processed in `phase`.
+    fn report(&self, phase: Phase, done: u64, total: Option<u64>);
+
+    /// Whether to stop the operation, which fails with [`Error::Cancelled`]. Loading checks this
+    /// between reports; saving and text extraction don't.
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// A report of the progress of loading, given to the callback of [`Document::load_with_progress`].
+///
+/// [`Document::load_with_progress`]: crate::Document::load_with_progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub phase: Phase,
+    /// Number of items processed in the phase
+    pub done: u64,
+    /// Number of items of the phase, if known. For [`Phase::ParseObjects`], the number of entries
+    /// of the cross-reference table.
+    pub total: Option<u64>,
+}
+
+/// A [`Progress`] sink calling a function, which can cancel the operation by returning
+/// [`ControlFlow::Break`].
+pub(crate) struct ProgressFn<F> {
+    callback: F,
+    cancelled: AtomicBool,
+}
+
+impl<F> ProgressFn<F> {
+    pub(crate) fn new(callback: F) -> Self {
+        ProgressFn {
+            callback,
+            cancelled: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<F: Fn(LoadProgress) -> ControlFlow<()> + Send + Sync> Progress for ProgressFn<F> {
+    fn report(&self, phase: Phase, done: u64, total: Option<u64>) {
+        if (self.callback)(LoadProgress { phase, done, total }).is_break() {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A shared [`Progress`] sink, as the options of operations hold it.
+#[derive(Clone)]
+pub struct ProgressSink(Arc<dyn Progress>);
+
+impl ProgressSink {
+    pub fn new(progress: impl Progress + 'static) -> Self {
+        ProgressSink(Arc::new(progress))
+    }
+
+    pub(crate) fn report(&self, phase: Phase, done: u64, total: Option<u64>) {
+        self.0.report(phase, done, total);
+    }
+
+    pub(crate) fn cancelled(&self) -> bool {
+        self.0.cancelled()
+    }
+}
+
+impl fmt::Debug for ProgressSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressSink")
+    }
+}
+
+/// Sinks are equal if they are clones of each other.
+impl PartialEq for ProgressSink {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ProgressSink {}
+
+/// Report to `sink`, if there is one.
+pub(crate) fn report(sink: Option<&ProgressSink>, phase: Phase, done: u64, total: Option<u64>) {
+    if let Some(sink) = sink {
+        sink.report(phase, done, total);
+    }
+}
+
+/// Whether `sink` cancelled the operation.
+pub(crate) fn cancelled(sink: Option<&ProgressSink>) -> bool {
+    sink.is_some_and(ProgressSink::cancelled)
+}
+
+/// Fail with [`Error::Cancelled`] if `sink` cancelled the operation.
+pub(crate) fn check_cancelled(sink: Option<&ProgressSink>) -> Result<()> {
+    if cancelled(sink) {
+        return Err(Error::Cancelled);
+    }
+    Ok(())
+}
+
+/// Number of items counted between reports of a [`ProgressCounter`].
+const REPORT_INTERVAL: u64 = 256;
+
+/// How often [`ProgressCounter::sampled`] reports the count of work done on other threads.
+#[cfg(feature = "rayon")]
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Counts the items of a phase counting many items, reporting every [`REPORT_INTERVAL`] of them.
+pub(crate) struct ProgressCounter<'a> {
+    sink: Option<&'a ProgressSink>,
+    phase: Phase,
+    total: Option<u64>,
+    done: AtomicU64,
+    reported: AtomicU64,
+}
+
+impl<'a> ProgressCounter<'a> {
+    pub(crate) fn new(sink: Option<&'a ProgressSink>, phase: Phase, total: Option<u64>) -> Self {
+        ProgressCounter {
+            sink,
+            phase,
+            total,
+            done: AtomicU64::new(0),
+            reported: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    /// Count an item done on the thread that started the operation, reporting if enough were
+    /// done since the last report.
+    pub(crate) fn tick(&self) {
+        let done = self.count();
+        if done % REPORT_INTERVAL == 0 {
+            self.report(done);
+        }
+    }
+
+    /// Count an item done on any thread, without reporting it. See [`ProgressCounter::sampled`].
+    pub(crate) fn count(&self) -> u64 {
+        if self.sink.is_none() {
+            return 0;
+        }
+        self.done.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Run `work`, which counts items on other threads, reporting the count from this thread
+    /// while it runs.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn sampled<T: Send>(&self, work: impl FnOnce() -> T + Send) -> T {
+        if self.sink.is_none() {
+            return work();
+        }
+        thread::scope(|scope| {
+            let caller = thread::current();
+            let worker = scope.spawn(move || {
+                let result = work();
+                caller.unpark();
+                result
+            });
+            while !worker.is_finished() {
+                self.report(self.done.load(Ordering::Relaxed));
+                thread::park_timeout(SAMPLE_INTERVAL);
+            }
+            match worker.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        })
+    }
+
+    /// Report the final count.
+    pub(crate) fn finish(&self) {
+        self.report(self.done.load(Ordering::Relaxed));
+    }
+
+    fn report(&self, done: u64) {
+        // Only the calling thread reports, so this doesn't race.
+        if let Some(sink) = self.sink {
+            if self.reported.swap(done, Ordering::Relaxed) != done {
+                sink.report(self.phase, done, self.total);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+    use crate::{Document, ExtractionOptions, ReaderOptions, SaveOptions};
+    use std::sync::Mutex;
+
+    type Reports = Arc<Mutex<Vec<(Phase, u64, Option<u64>)>>>;
+
+    struct Recorder(Reports);
+
+    impl Progress for Recorder {
+        fn report(&self, phase: Phase, done: u64, total: Option<u64>) {
+            self.0.lock().unwrap().push((phase, done, total));
+        }
+    }
+
+    /// The reports of `phase`, checking that they count up to their total.
+    fn reports_of(reports: &Reports, phase: Phase) -> Vec<(u64, Option<u64>)> {
+        let reports: Vec<_> = reports
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|report| report.0 == phase)
+            .map(|&(_, done, total)| (done, total))
+            .collect();
+        assert!(reports.windows(2).all(|pair| pair[0].0 <= pair[1].0), "{phase:?}: {reports:?}");
+        assert!(reports.iter().all(|&(done, total)| total.is_none_or(|total| done <= total)));
+        reports
+    }
+
+    #[test]
+    fn load_and_save_report_progress() {
+        let buffer = std::fs::read("assets/example.pdf").unwrap();
+        let reports = Reports::default();
+        let options = ReaderOptions::builder().progress(Recorder(reports.clone())).build();
+        let mut doc = Document::load_mem_with_options(&buffer, options).unwrap();
+        assert_eq!(reports_of(&reports, Phase::ParseXref).first(), Some(&(1, None)));
+        let entries = doc.reference_table.entries.len() as u64;
+        assert_eq!(reports_of(&reports, Phase::ParseObjects).last(), Some(&(entries, Some(entries))));
+
+        let reports = Reports::default();
+        let objects = doc.objects.len() as u64;
+        let options = SaveOptions::builder().progress(Recorder(reports.clone())).build().unwrap();
+        doc.save_with_options(&mut Vec::new(), options).unwrap();
+        let serialized = reports_of(&reports, Phase::SerializeObjects);
+        assert_eq!(serialized.len() as u64, objects);
+        assert_eq!(serialized.last(), Some(&(objects, Some(objects))));
+        assert_eq!(reports_of(&reports, Phase::WriteXref).len(), 1);
+        assert_eq!(reports.lock().unwrap().last().unwrap().0, Phase::WriteXref);
+    }
+
+    #[test]
+    fn load_with_progress_can_cancel() {
+        let buffer = std::fs::read("assets/example.pdf").unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let doc = Document::load_mem_with_progress(&buffer, move |progress: LoadProgress| {
+            recorded.lock().unwrap().push(progress);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        let entries = doc.reference_table.entries.len() as u64;
+        let last_parsed = reports.lock().unwrap().iter().rev().find(|p| p.phase == Phase::ParseObjects).copied();
+        assert_eq!(last_parsed.map(|p| (p.done, p.total)), Some((entries, Some(entries))));
+
+        let calls = Arc::new(AtomicU64::new(0));
+        let counted = calls.clone();
+        let result = Document::load_mem_with_progress(&buffer, move |progress: LoadProgress| {
+            counted.fetch_add(1, Ordering::Relaxed);
+            match progress.phase {
+                Phase::ParseXref => ControlFlow::Continue(()),
+                _ => ControlFlow::Break(()),
+            }
+        });
+        assert!(matches!(result, Err(Error::Cancelled)), "{result:?}");
+        assert!(calls.load(Ordering::Relaxed) < reports.lock().unwrap().len() as u64 + 1);
+    }
+
+    #[test]
+    fn text_extraction_reports_pages() {
+        let doc = create_document_with_texts(&["one", "two", "three"]);
+        let reports = Reports::default();
+        let options = ExtractionOptions {
+            progress: Some(ProgressSink::new(Recorder(reports.clone()))),
+            ..Default::default()
+        };
+        doc.extract_text_with_options(&[1, 2, 3], &options).unwrap();
+        assert_eq!(reports_of(&reports, Phase::ExtractPage), [(1, Some(3)), (2, Some(3)), (3, Some(3))]);
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::{Result, Write};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::{Document, SaveOptions};
+
+/// A document that can't be changed, to share one loaded document between threads or requests.
+///
+/// Only the `&self` API of [`Document`] is reachable, through [`Deref`]: objects can be looked up
+/// and dereferenced, pages listed, text and metadata extracted, but no object can be borrowed
+/// mutably. Cloning only clones a reference to the document, and the type is `Send + Sync`.
+/// [`ReadOnlyDocument::to_mutable`] gives an owned copy to edit.
+///
+/// ```
+/// # #[cfg(not(feature = "async"))]
+/// # {
+/// let doc = lopdf::Document::load("assets/example.pdf").unwrap().into_readonly();
+/// let shared = doc.clone();
+/// std::thread::spawn(move || shared.get_pages().len()).join().unwrap();
+/// assert_eq!(doc.extract_text(&[1]).unwrap(), "Hello World!\n");
+/// # }
+/// ```
+///
+/// Mutation doesn't compile:
+///
+/// ```compile_fail
+/// let doc = lopdf::Document::new().into_readonly();
+/// doc.get_object_mut((1, 0)).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadOnlyDocument {
+    document: Arc<Document>,
+}
+
+/// A borrowed read-only view of a document, see [`Document::as_readonly_view`].
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOnlyView<'a> {
+    document: &'a Document,
+}
+
+impl Document {
+    /// Turn the document into a [`ReadOnlyDocument`], without copying it.
+    pub fn into_readonly(self) -> ReadOnlyDocument {
+        ReadOnlyDocument {
+            document: Arc::new(self),
+        }
+    }
+
+    /// A [`ReadOnlyView`] of the document, to hand out read access without a `&mut` path back.
+    pub fn as_readonly_view(&self) -> ReadOnlyView<'_> {
+        ReadOnlyView { document: self }
+    }
+}
+
+impl ReadOnlyDocument {
+    /// An owned copy of the document to edit. The shared document is left as it is.
+    pub fn to_mutable(&self) -> Document {
+        Document::clone(&self.document)
+    }
+
+    /// Save a copy of the document to `target`, see [`Document::save_to`].
+    pub fn save_to<W: Write>(&self, target: &mut W) -> Result<()> {
+        self.to_mutable().save_to(target)
+    }
+
+    /// Save a copy of the document to `target` with `options`, see
+    /// [`Document::save_with_options`].
+    pub fn save_with_options<W: Write>(&self, target: &mut W, options: SaveOptions) -> Result<()> {
+        self.to_mutable().save_with_options(target, options)
+    }
+}
+
+impl Deref for ReadOnlyDocument {
+    type Target = Document;
+
+    fn deref(&self) -> &Document {
+        &self.document
+    }
+}
+
+impl From<Document> for ReadOnlyDocument {
+    fn from(document: Document) -> Self {
+        document.into_readonly()
+    }
+}
+
+impl ReadOnlyView<'_> {
+    /// An owned copy of the document to edit.
+    pub fn to_mutable(&self) -> Document {
+        Document::clone(self.document)
+    }
+
+    /// Save a copy of the document to `target`, see [`Document::save_to`].
+    pub fn save_to<W: Write>(&self, target: &mut W) -> Result<()> {
+        self.to_mutable().save_to(target)
+    }
+}
+
+impl Deref for ReadOnlyView<'_> {
+    type Target = Document;
+
+    fn deref(&self) -> &Document {
+        self.document
+    }
+}
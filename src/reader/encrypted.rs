@@ -1,13 +1,14 @@
 use log::warn;
 use std::collections::HashSet;
 
-use super::{FilterFunc, Reader};
-use crate::encryption::{self, EncryptionState};
-use crate::object_stream::ObjectStream;
-use crate::{Error, Object, ObjectId, Result};
+use super::{ObjectFilter, Reader};
+use crate::encryption::{EncryptionState, PasswordKind};
+use crate::{Error, Object, Result};
 
 impl Reader<'_> {
-    pub(super) fn load_encrypted_document(&mut self, filter_func: Option<FilterFunc>) -> Result<()> {
+    pub(super) fn load_encrypted_document(
+        &mut self, filter: Option<&ObjectFilter<'_>>, reconstructed: bool,
+    ) -> Result<()> {
         // Step 1: Parse the Encrypt dictionary using the proper parser.
         // Since raw_objects is empty, parse_encryption_dictionary uses read_object().
         self.parse_encryption_dictionary()?;
@@ -17,58 +18,14 @@ pub(super) fn load_encrypted_document(&mut self, filter_func: Option<FilterFunc>
             return Ok(());
         }
 
-        // Step 3: Load all objects using the proper parser.
-        // load_objects_raw already skips ObjStm processing for encrypted documents.
-        self.load_objects_raw(filter_func)?;
+        // Step 3: Load all objects, decrypting them before they're filtered and object streams
+        // are expanded.
+        self.load_objects_raw(filter, reconstructed)?;
 
-        // Step 4: Decrypt all loaded objects and process object streams
         if let Some(ref state) = self.encryption_state {
-            let encrypt_ref = self
-                .document
-                .trailer
-                .get(b"Encrypt")
-                .ok()
-                .and_then(|o| o.as_reference().ok());
-
-            // Decrypt all objects (skip the encryption dictionary itself)
-            for (&obj_id, obj) in self.document.objects.iter_mut() {
-                if Some(obj_id) == encrypt_ref {
-                    continue;
-                }
-                let _ = encryption::decrypt_object(state, obj_id, obj);
-            }
-
-            // Step 5: Process object streams now that they're decrypted
-            let obj_stream_ids: Vec<ObjectId> = self
-                .document
-                .objects
-                .iter()
-                .filter_map(|(&id, obj)| {
-                    if let Object::Stream(stream) = obj {
-                        if stream.dict.has_type(b"ObjStm") {
-                            return Some(id);
-                        }
-                    }
-                    None
-                })
-                .collect();
-
-            for container_id in obj_stream_ids {
-                if let Some(container_obj) = self.document.objects.get_mut(&container_id) {
-                    if let Ok(stream) = container_obj.as_stream_mut() {
-                        if let Ok(object_stream) = ObjectStream::new(stream) {
-                            for (obj_id, obj) in object_stream.objects {
-                                self.document.objects.entry(obj_id).or_insert(obj);
-                            }
-                        }
-                    }
-                }
-            }
-
             self.document.encryption_state = Some(state.clone());
-
-            if let Some(enc_ref) = encrypt_ref {
-                self.document.objects.remove(&enc_ref);
+            if let Ok(encrypt_ref) = self.document.trailer.get(b"Encrypt").and_then(Object::as_reference) {
+                self.document.objects.remove(&encrypt_ref);
             }
             self.document.trailer.remove(b"Encrypt");
         }
@@ -85,33 +42,42 @@ pub(super) fn parse_encryption_dictionary(&mut self) -> Result<()> {
         Ok(())
     }
 
-    pub(super) fn authenticate_and_setup_encryption(&mut self, require_password: bool) -> Result<Option<String>> {
-        let password_to_use: Option<String> = if self.document.authenticate_password("").is_ok() {
-            Some(String::new())
-        } else if let Some(ref pwd) = self.password {
-            if self.document.authenticate_password(pwd).is_ok() {
-                Some(pwd.clone())
-            } else if require_password {
-                return Err(Error::InvalidPassword);
-            } else {
-                warn!("Invalid password provided for encrypted PDF");
+    pub(super) fn authenticate_and_setup_encryption(&mut self, require_password: bool) -> Result<Option<PasswordKind>> {
+        // The password given is tried as the owner password and then as the user password, and
+        // the empty user password after it.
+        let given = self
+            .password
+            .as_ref()
+            .and_then(|password| self.document.authenticate_any_password(password).ok());
+        let authenticated = given.or_else(|| {
+            let kind = self.document.authenticate_raw_password_kind("").ok()?;
+            Some((Vec::new(), kind))
+        });
+
+        let (password, password_kind) = match authenticated {
+            Some(authenticated) => authenticated,
+            None if self.password.is_some() => {
+                if !require_password {
+                    warn!("Invalid password provided for encrypted PDF");
+                }
                 return Err(Error::InvalidPassword);
             }
-        } else if require_password {
-            return Err(Error::Unimplemented(
-                "PDF is encrypted and requires a password. Use Document::load_metadata_with_password() instead.",
-            ));
-        } else {
-            warn!("PDF is encrypted and requires a password");
-            return Ok(None);
+            None if require_password => {
+                return Err(Error::Unimplemented(
+                    "PDF is encrypted and requires a password. Use Document::load_metadata_with_password() instead.",
+                ));
+            }
+            None => {
+                warn!("PDF is encrypted and requires a password");
+                return Ok(None);
+            }
         };
 
-        if let Some(ref password) = password_to_use {
-            let state = EncryptionState::decode(&self.document, password)?;
-            self.encryption_state = Some(state);
-        }
+        let mut state = EncryptionState::decode(&self.document, password)?;
+        state.password_kind = Some(password_kind);
+        self.encryption_state = Some(state);
 
-        Ok(password_to_use)
+        Ok(Some(password_kind))
     }
 
     pub(super) fn setup_encryption_for_metadata(&mut self) -> Result<()> {
@@ -0,0 +1,37 @@
+use log::warn;
+
+use super::{Reader, XrefSection};
+use crate::xref::Xref;
+use crate::{Dictionary, DocumentIndex, Result};
+
+impl Reader<'_> {
+    /// Read the header and the cross-reference sections of the file into a [`DocumentIndex`].
+    pub(crate) fn read_index(mut self) -> Result<DocumentIndex> {
+        self.read_header()?;
+        let (xref, trailer, sections) = self.read_xref_sections()?;
+        DocumentIndex::new(
+            self.buffer,
+            self.document.xref_start,
+            xref,
+            &trailer,
+            sections,
+            self.document.load_warnings,
+        )
+    }
+
+    /// The cross-reference sections of the [`ReaderOptions::index`](crate::ReaderOptions::index)
+    /// if it was built from this file, as [`Reader::read_xref_sections`] returns them.
+    pub(super) fn indexed_xref_sections(&mut self) -> Option<Result<(Xref, Dictionary, Vec<XrefSection>)>> {
+        let index = self.options.index.clone()?;
+        if !index.matches(self.buffer) {
+            warn!("the document index was built from another file, reading the cross-reference sections");
+            return None;
+        }
+        let sections = index.trailer().and_then(|trailer| Ok((trailer, index.sections()?)));
+        Some(sections.map(|(trailer, sections)| {
+            self.document.xref_start = index.xref_start;
+            self.document.load_warnings.extend(index.load_warnings.iter().cloned());
+            (index.xref.clone(), trailer, sections)
+        }))
+    }
+}
@@ -0,0 +1,298 @@
+use log::warn;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::OnceLock;
+
+use super::Reader;
+use crate::object_stream::ObjectStream;
+use crate::repair::is_single_page_root;
+use crate::xref::XrefEntry;
+use crate::{Dictionary, Document, Error, Object, ObjectId, Password, ReaderOptions, Result};
+
+/// A PDF document whose objects are parsed when they are first accessed, for reading a few
+/// objects of a large file without parsing all of them.
+///
+/// Loading reads the header, the cross-reference sections and the trailer, as
+/// [`Document::load_mem`] does. Each object is parsed from the buffer on first access and cached,
+/// and each object stream is decoded once, when one of its objects is first accessed.
+///
+/// The document is read-only. [`LazyDocument::into_document`] loads a [`Document`] from the same
+/// buffer, to modify or save it.
+///
+/// ```no_run
+/// use lopdf::LazyDocument;
+///
+/// let buffer = std::fs::read("large.pdf")?;
+/// let doc = LazyDocument::load_mem(&buffer)?;
+/// let first_page = doc.get_pages()[&1];
+/// let media_box = doc.get_dictionary(first_page)?.get(b"MediaBox")?;
+/// # Ok::<(), lopdf::Error>(())
+/// ```
+pub struct LazyDocument<'a> {
+    reader: Reader<'a>,
+    version: String,
+    /// The objects of the cross-reference table not stored in object streams, parsed on first
+    /// access. `None` if parsing failed.
+    objects: BTreeMap<ObjectId, OnceLock<Option<Object>>>,
+    /// The decoded object streams, by the object number of their container.
+    object_streams: BTreeMap<u32, OnceLock<BTreeMap<ObjectId, Object>>>,
+}
+
+impl<'a> LazyDocument<'a> {
+    /// Read the structure of the PDF document in `buffer`, leaving its objects to be parsed on
+    /// first access.
+    pub fn load_mem(buffer: &'a [u8]) -> Result<Self> {
+        Self::load_internal(buffer, None)
+    }
+
+    /// Like [`LazyDocument::load_mem`], with a password for encrypted PDFs.
+    pub fn load_mem_with_password(buffer: &'a [u8], password: &str) -> Result<Self> {
+        Self::load_internal(buffer, Some(password.into()))
+    }
+
+    /// Like [`LazyDocument::load_mem`], with a password given as the bytes the security handler
+    /// hashes, see [`Document::authenticate_raw_password`].
+    pub fn load_mem_with_password_bytes(buffer: &'a [u8], password: &[u8]) -> Result<Self> {
+        Self::load_internal(buffer, Some(password.into()))
+    }
+
+    /// Like [`LazyDocument::load_mem`], reading the file with `options`, e.g. with the
+    /// [`ReaderOptions::index`] of a file opened before.
+    pub fn load_mem_with_options(buffer: &'a [u8], options: ReaderOptions) -> Result<Self> {
+        Self::load_with(buffer, None, options)
+    }
+
+    fn load_internal(buffer: &'a [u8], password: Option<Password>) -> Result<Self> {
+        Self::load_with(buffer, password, ReaderOptions::default())
+    }
+
+    fn load_with(buffer: &'a [u8], password: Option<Password>, options: ReaderOptions) -> Result<Self> {
+        let mut reader = Reader {
+            password,
+            ..Reader::new(buffer, options)
+        };
+        let version = reader.read_trailer_lazily()?;
+
+        let mut objects = BTreeMap::new();
+        let mut object_streams = BTreeMap::new();
+        for (&id, entry) in &reader.document.reference_table.entries {
+            match *entry {
+                XrefEntry::Normal { generation, .. } => {
+                    objects.insert((id, generation), OnceLock::new());
+                }
+                XrefEntry::Compressed { container, .. } => {
+                    object_streams.insert(container, OnceLock::new());
+                }
+                _ => {}
+            }
+        }
+        Ok(LazyDocument {
+            reader,
+            version,
+            objects,
+            object_streams,
+        })
+    }
+
+    /// The PDF version from the file header.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The trailer dictionary of the last cross-reference section.
+    pub fn trailer(&self) -> &Dictionary {
+        &self.reader.document.trailer
+    }
+
+    /// Get an object by its id, parsing it on first access. Unlike [`Document::get_object`], a
+    /// reference is returned as is.
+    pub fn get_object(&self, id: ObjectId) -> Result<&Object> {
+        let compressed_in = match self.reader.document.reference_table.get(id.0) {
+            Some(XrefEntry::Compressed { container, .. }) if id.1 == 0 => Some(*container),
+            _ => None,
+        };
+        if let Some(container) = compressed_in {
+            return self.object_stream(container).get(&id).ok_or(Error::ObjectNotFound(id));
+        }
+
+        // The cross-reference table may list the object number with another generation.
+        let slot = self
+            .objects
+            .get(&id)
+            .or_else(|| {
+                let mut listed = self.objects.range((id.0, 0)..=(id.0, u16::MAX));
+                listed.next().filter(|_| !self.reader.options.strict).map(|(_, slot)| slot)
+            })
+            .ok_or(Error::ObjectNotFound(id))?;
+        let object = slot.get_or_init(|| match self.reader.get_object(id, &mut HashSet::new()) {
+            Ok(object) => Some(object),
+            Err(err) => {
+                warn!("object {} {} can't be loaded: {err}", id.0, id.1);
+                None
+            }
+        });
+        object.as_ref().ok_or(Error::ObjectNotFound(id))
+    }
+
+    /// Get a dictionary by its id, or the dictionary of a stream, following a reference to it.
+    pub fn get_dictionary(&self, id: ObjectId) -> Result<&Dictionary> {
+        let object = self.get_object(id)?;
+        match self.dereference(object)?.1 {
+            Object::Stream(stream) => Ok(&stream.dict),
+            object => object.as_dict(),
+        }
+    }
+
+    /// Follow references from `object`, returning the id of the last object referenced, if any,
+    /// with the object found.
+    pub fn dereference<'b>(&'b self, mut object: &'b Object) -> Result<(Option<ObjectId>, &'b Object)> {
+        let mut id = None;
+        let mut already_seen = HashSet::new();
+        while let Object::Reference(ref_id) = *object {
+            if !already_seen.insert(ref_id) {
+                return Err(Error::ReferenceCycle(ref_id));
+            }
+            id = Some(ref_id);
+            object = self.get_object(ref_id)?;
+        }
+        Ok((id, object))
+    }
+
+    /// The document catalog.
+    pub fn catalog(&self) -> Result<&Dictionary> {
+        let root = self.trailer().get(b"Root")?;
+        self.dereference(root)?.1.as_dict()
+    }
+
+    /// Collect the page ids by page number, starting from 1, parsing only the page tree.
+    pub fn get_pages(&self) -> BTreeMap<u32, ObjectId> {
+        let mut pages = BTreeMap::new();
+        let Ok(root) = self.catalog().and_then(|catalog| catalog.get(b"Pages")).and_then(Object::as_reference)
+        else {
+            return pages;
+        };
+        let mut visited = HashSet::new();
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                warn!("page tree node {} {} is listed more than once", id.0, id.1);
+                continue;
+            }
+            let Ok(node) = self.get_dictionary(id) else {
+                continue;
+            };
+            if node.has_type(b"Page") || (id == root && is_single_page_root(node)) {
+                pages.insert(pages.len() as u32 + 1, id);
+                continue;
+            }
+            let kids = node
+                .get(b"Kids")
+                .and_then(|kids| self.dereference(kids))
+                .and_then(|(_, kids)| kids.as_array());
+            if let Ok(kids) = kids {
+                stack.extend(kids.iter().rev().filter_map(|kid| kid.as_reference().ok()));
+            }
+        }
+        pages
+    }
+
+    /// Load the whole document from the same buffer, as [`Document::load_mem`] does, to modify
+    /// or save it.
+    pub fn into_document(self) -> Result<Document> {
+        Reader {
+            password: self.reader.password,
+            ..Reader::new(self.reader.buffer, self.reader.options)
+        }
+        .read(None)
+    }
+
+    /// The objects of the object stream `container`, decoded on first access.
+    fn object_stream(&self, container: u32) -> &BTreeMap<ObjectId, Object> {
+        let Some(slot) = self.object_streams.get(&container) else {
+            static EMPTY: BTreeMap<ObjectId, Object> = BTreeMap::new();
+            return &EMPTY;
+        };
+        slot.get_or_init(|| {
+            let objects = self.get_object((container, 0)).and_then(|container| {
+                let mut stream = container.as_stream()?.clone();
+                ObjectStream::with_limits(&mut stream, self.reader.options.parse_limits())
+            });
+            match objects {
+                Ok(object_stream) => object_stream.objects,
+                Err(err) => {
+                    warn!("object stream {container} 0 can't be loaded: {err}");
+                    BTreeMap::new()
+                }
+            }
+        })
+    }
+
+    /// The number of objects parsed so far.
+    #[cfg(test)]
+    fn parsed_count(&self) -> usize {
+        let direct = self.objects.values().filter(|slot| slot.get().is_some()).count();
+        let compressed: usize = self.object_streams.values().filter_map(OnceLock::get).map(BTreeMap::len).sum();
+        direct + compressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+    use crate::xref::XrefType;
+
+    fn saved(xref_type: XrefType, object_streams: bool) -> Vec<u8> {
+        let mut doc = create_document_with_texts(&["one", "two", "three"]);
+        doc.reference_table.cross_reference_type = xref_type;
+        let mut buffer = Vec::new();
+        if object_streams {
+            doc.save_modern(&mut buffer).unwrap();
+        } else {
+            doc.save_to(&mut buffer).unwrap();
+        }
+        buffer
+    }
+
+    #[test]
+    fn objects_are_parsed_on_access() {
+        let buffer = saved(XrefType::CrossReferenceTable, false);
+        let eager = Document::load_mem(&buffer).unwrap();
+        let doc = LazyDocument::load_mem(&buffer).unwrap();
+        assert_eq!(doc.version(), eager.version);
+        assert_eq!(doc.parsed_count(), 0);
+
+        let pages = doc.get_pages();
+        assert_eq!(pages, eager.get_pages());
+        let parsed = doc.parsed_count();
+        assert!(parsed < eager.objects.len(), "{parsed} of {} objects parsed", eager.objects.len());
+
+        let page = doc.get_dictionary(pages[&2]).unwrap();
+        assert_eq!(page, eager.get_dictionary(pages[&2]).unwrap());
+        let contents = page.get(b"Contents").and_then(Object::as_reference).unwrap();
+        let content = doc.get_object(contents).and_then(Object::as_stream).unwrap();
+        assert_eq!(content.content, eager.get_object(contents).and_then(Object::as_stream).unwrap().content);
+        assert!(matches!(doc.get_object((999, 0)), Err(Error::ObjectNotFound((999, 0)))));
+
+        assert_eq!(doc.into_document().unwrap().objects, eager.objects);
+    }
+
+    #[test]
+    fn object_streams_are_decoded_once() {
+        let buffer = saved(XrefType::CrossReferenceStream, true);
+        let eager = Document::load_mem(&buffer).unwrap();
+        let doc = LazyDocument::load_mem(&buffer).unwrap();
+        assert!(!doc.object_streams.is_empty());
+
+        let catalog = doc.catalog().unwrap();
+        assert_eq!(catalog, eager.catalog().unwrap());
+        let decoded = doc.parsed_count();
+        for (id, object) in &eager.objects {
+            if !matches!(object, Object::Stream(stream) if stream.dict.has_type(b"ObjStm")) {
+                assert_eq!(doc.get_object(*id).unwrap(), object);
+            }
+        }
+        assert!(decoded > 1);
+        let first = doc.get_object((1, 0)).unwrap() as *const Object;
+        assert_eq!(doc.get_object((1, 0)).unwrap() as *const Object, first);
+    }
+}
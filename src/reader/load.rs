@@ -3,6 +3,7 @@
 use std::fs::File;
 #[cfg(not(feature = "async"))]
 use std::io::Read;
+use std::ops::ControlFlow;
 use std::path::Path;
 
 #[cfg(feature = "async")]
@@ -12,8 +13,12 @@
 #[cfg(feature = "async")]
 use tokio::pin;
 
-use super::{FilterFunc, PdfMetadata, Reader};
-use crate::{Document, Error, IncrementalDocument, Result};
+use super::{LoadFilter, LoadFilters, MetadataOptions, PdfMetadata, Reader};
+use crate::progress::ProgressFn;
+use crate::{
+    Document, DocumentIndex, Error, IncrementalDocument, LoadProgress, Object, Password, PreviewInfo, ReaderOptions,
+    Result,
+};
 
 #[cfg(not(feature = "async"))]
 impl Document {
@@ -25,19 +30,75 @@ pub fn load<P: AsRef<Path>>(path: P) -> Result<Document> {
         Self::load_internal(file, capacity, None, None)
     }
 
+    /// Load a PDF document from a specified file path, parsing it with `options`.
+    #[inline]
+    pub fn load_with_options<P: AsRef<Path>>(path: P, options: ReaderOptions) -> Result<Document> {
+        let buffer = std::fs::read(path)?;
+        Self::load_mem_with_options(&buffer, options)
+    }
+
+    /// Load a PDF document from a specified file path with the `index` built from the file
+    /// earlier, see [`Document::load_mem_with_index`].
+    #[inline]
+    pub fn load_with_index<P: AsRef<Path>>(path: P, index: &DocumentIndex) -> Result<Document> {
+        let buffer = std::fs::read(path)?;
+        Self::load_mem_with_index(&buffer, index)
+    }
+
+    /// Load a PDF document from a specified file path, calling `progress` with the progress of
+    /// loading, see [`Document::load_mem_with_progress`].
+    #[inline]
+    pub fn load_with_progress<P, F>(path: P, progress: F) -> Result<Document>
+    where
+        P: AsRef<Path>,
+        F: Fn(LoadProgress) -> ControlFlow<()> + Send + Sync + 'static,
+    {
+        let buffer = std::fs::read(path)?;
+        Self::load_mem_with_progress(&buffer, progress)
+    }
+
+    /// Load a PDF document from a specified file path, rebuilding its cross-reference table if it's
+    /// corrupt, see [`Reader::read_with_recovery`].
+    #[inline]
+    pub fn load_with_recovery<P: AsRef<Path>>(path: P) -> Result<Document> {
+        let buffer = std::fs::read(path)?;
+        Self::load_mem_with_recovery(&buffer)
+    }
+
     /// Load a PDF document from a specified file path with a password for encrypted PDFs.
     #[inline]
     pub fn load_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<Document> {
         let file = File::open(path)?;
         let capacity = Some(file.metadata()?.len() as usize);
-        Self::load_internal(file, capacity, None, Some(password.to_string()))
+        Self::load_internal(file, capacity, None, Some(password.into()))
+    }
+
+    /// Load a PDF document from a specified file path with a password given as the bytes the
+    /// security handler hashes, see [`Document::authenticate_raw_password`].
+    #[inline]
+    pub fn load_with_password_bytes<P: AsRef<Path>>(path: P, password: &[u8]) -> Result<Document> {
+        let file = File::open(path)?;
+        let capacity = Some(file.metadata()?.len() as usize);
+        Self::load_internal(file, capacity, None, Some(password.into()))
     }
 
+    /// Load a PDF document from a specified file path, passing each object through
+    /// `filter_func`, see [`LoadFilter`].
     #[inline]
-    pub fn load_filtered<P: AsRef<Path>>(path: P, filter_func: FilterFunc) -> Result<Document> {
+    pub fn load_filtered<P, F>(path: P, filter_func: F) -> Result<Document>
+    where
+        P: AsRef<Path>,
+        F: Fn((u32, u16), &mut Object) -> Option<((u32, u16), Object)> + Sync,
+    {
         let file = File::open(path)?;
         let capacity = Some(file.metadata()?.len() as usize);
-        Self::load_internal(file, capacity, Some(filter_func), None)
+        Self::load_internal(file, capacity, Some(&filter_func), None)
+    }
+
+    /// Load a PDF document from a specified file path, passing its objects and trailer through
+    /// `filters`, see [`Reader::read_with_filters`].
+    pub fn load_with_filters<P: AsRef<Path>>(path: P, filters: LoadFilters<'_>) -> Result<Document> {
+        Self::load_mem_with_filters(&std::fs::read(path)?, filters)
     }
 
     /// Load a PDF document from an arbitrary source.
@@ -49,21 +110,25 @@ pub fn load_from<R: Read>(source: R) -> Result<Document> {
     /// Load a PDF document from an arbitrary source with a password for encrypted PDFs.
     #[inline]
     pub fn load_from_with_password<R: Read>(source: R, password: &str) -> Result<Document> {
-        Self::load_internal(source, None, None, Some(password.to_string()))
+        Self::load_internal(source, None, None, Some(password.into()))
+    }
+
+    /// Load a PDF document from an arbitrary source with a password given as the bytes the
+    /// security handler hashes, see [`Document::authenticate_raw_password`].
+    #[inline]
+    pub fn load_from_with_password_bytes<R: Read>(source: R, password: &[u8]) -> Result<Document> {
+        Self::load_internal(source, None, None, Some(password.into()))
     }
 
     fn load_internal<R: Read>(
-        mut source: R, capacity: Option<usize>, filter_func: Option<FilterFunc>, password: Option<String>,
+        mut source: R, capacity: Option<usize>, filter_func: Option<&LoadFilter<'_>>, password: Option<Password>,
     ) -> Result<Document> {
         let mut buffer = capacity.map(Vec::with_capacity).unwrap_or_default();
         source.read_to_end(&mut buffer)?;
 
         Reader {
-            buffer: &buffer,
-            document: Document::new(),
-            encryption_state: None,
-
             password,
+            ..Reader::new(&buffer, ReaderOptions::default())
         }
         .read(filter_func)
     }
@@ -73,16 +138,53 @@ pub fn load_mem(buffer: &[u8]) -> Result<Document> {
         buffer.try_into()
     }
 
+    /// Load a PDF document from a memory slice, parsing it with `options`.
+    pub fn load_mem_with_options(buffer: &[u8], options: ReaderOptions) -> Result<Document> {
+        Reader::new(buffer, options).read(None)
+    }
+
+    /// Load a PDF document from a memory slice with the `index` built from it earlier by
+    /// [`Document::build_index`], which spares reading the cross-reference sections. The index of
+    /// another file is ignored, see [`ReaderOptions::index`].
+    pub fn load_mem_with_index(buffer: &[u8], index: &DocumentIndex) -> Result<Document> {
+        Self::load_mem_with_options(buffer, ReaderOptions::builder().index(index.clone()).build())
+    }
+
+    /// Load a PDF document from a memory slice, calling `progress` with the progress of loading.
+    /// Returning [`ControlFlow::Break`] cancels loading, which fails with [`Error::Cancelled`]
+    /// soon after. Under the `rayon` feature, objects are parsed on several threads, and their
+    /// count is reported from the calling thread while they are.
+    pub fn load_mem_with_progress<F>(buffer: &[u8], progress: F) -> Result<Document>
+    where
+        F: Fn(LoadProgress) -> ControlFlow<()> + Send + Sync + 'static,
+    {
+        let options = ReaderOptions::builder().progress(ProgressFn::new(progress)).build();
+        Self::load_mem_with_options(buffer, options)
+    }
+
+    /// Load a PDF document from a memory slice, rebuilding its cross-reference table if it's
+    /// corrupt, see [`Reader::read_with_recovery`].
+    pub fn load_mem_with_recovery(buffer: &[u8]) -> Result<Document> {
+        Reader::new(buffer, ReaderOptions::default()).read_with_recovery(None)
+    }
+
+    /// Load a PDF document from a memory slice, passing its objects and trailer through
+    /// `filters`, see [`Reader::read_with_filters`].
+    pub fn load_mem_with_filters(buffer: &[u8], filters: LoadFilters<'_>) -> Result<Document> {
+        Reader::new(buffer, ReaderOptions::default()).read_with_filters(filters)
+    }
+
     /// Load a PDF document from a memory slice with a password for encrypted PDFs.
     pub fn load_mem_with_password(buffer: &[u8], password: &str) -> Result<Document> {
-        Reader {
-            buffer,
-            document: Document::new(),
-            encryption_state: None,
+        Reader::new(buffer, ReaderOptions::default()).with_password(password).read(None)
+    }
 
-            password: Some(password.to_string()),
-        }
-        .read(None)
+    /// Load a PDF document from a memory slice with a password given as the bytes the security
+    /// handler hashes, used as they are. Passwords given as text are encoded as the security
+    /// handler specifies, see [`Document::authenticate_password`]; bytes open documents whose
+    /// password was encoded otherwise, e.g. in the codepage of the system they were written on.
+    pub fn load_mem_with_password_bytes(buffer: &[u8], password: &[u8]) -> Result<Document> {
+        Reader::new(buffer, ReaderOptions::default()).with_password(password).read(None)
     }
 
     /// Load PDF metadata (title and page count) without loading the entire document.
@@ -94,12 +196,20 @@ pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<PdfMetadata> {
         Self::load_metadata_internal(file, capacity, None)
     }
 
+    /// Load PDF metadata from a file path, also reading what `options` ask for, such as the
+    /// dimensions of each page.
+    #[inline]
+    pub fn load_metadata_with_options<P: AsRef<Path>>(path: P, options: &MetadataOptions) -> Result<PdfMetadata> {
+        let buffer = std::fs::read(path)?;
+        Self::load_metadata_mem_with_options(&buffer, options)
+    }
+
     /// Load PDF metadata from a file path with a password for encrypted PDFs.
     #[inline]
     pub fn load_metadata_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<PdfMetadata> {
         let file = File::open(path)?;
         let capacity = Some(file.metadata()?.len() as usize);
-        Self::load_metadata_internal(file, capacity, Some(password.to_string()))
+        Self::load_metadata_internal(file, capacity, Some(password.into()))
     }
 
     /// Load PDF metadata from an arbitrary source without loading the entire document.
@@ -111,47 +221,64 @@ pub fn load_metadata_from<R: Read>(source: R) -> Result<PdfMetadata> {
     /// Load PDF metadata from an arbitrary source with a password for encrypted PDFs.
     #[inline]
     pub fn load_metadata_from_with_password<R: Read>(source: R, password: &str) -> Result<PdfMetadata> {
-        Self::load_metadata_internal(source, None, Some(password.to_string()))
+        Self::load_metadata_internal(source, None, Some(password.into()))
     }
 
     /// Load PDF metadata from a memory slice without loading the entire document.
     #[inline]
     pub fn load_metadata_mem(buffer: &[u8]) -> Result<PdfMetadata> {
-        Reader {
-            buffer,
-            document: Document::new(),
-            encryption_state: None,
+        Reader::new(buffer, ReaderOptions::default()).read_metadata()
+    }
 
-            password: None,
-        }
-        .read_metadata()
+    /// Load PDF metadata from a memory slice, also reading what `options` ask for.
+    pub fn load_metadata_mem_with_options(buffer: &[u8], options: &MetadataOptions) -> Result<PdfMetadata> {
+        Reader::new(buffer, ReaderOptions::default()).read_metadata_with_options(options)
     }
 
     /// Load PDF metadata from a memory slice with a password for encrypted PDFs.
     #[inline]
     pub fn load_metadata_mem_with_password(buffer: &[u8], password: &str) -> Result<PdfMetadata> {
-        Reader {
-            buffer,
-            document: Document::new(),
-            encryption_state: None,
+        Reader::new(buffer, ReaderOptions::default()).with_password(password).read_metadata()
+    }
 
-            password: Some(password.to_string()),
-        }
-        .read_metadata()
+    /// Get the preview information of a page without loading the entire document, see
+    /// [`Document::preview_info`].
+    #[inline]
+    pub fn load_preview<P: AsRef<Path>>(path: P, page_number: u32) -> Result<PreviewInfo> {
+        let buffer = std::fs::read(path)?;
+        Self::load_preview_mem(&buffer, page_number)
+    }
+
+    /// Get the preview information of a page from a memory slice without loading the entire
+    /// document.
+    #[inline]
+    pub fn load_preview_mem(buffer: &[u8], page_number: u32) -> Result<PreviewInfo> {
+        Reader::new(buffer, ReaderOptions::default()).read_preview(page_number)
+    }
+
+    /// Load only the pages numbered `page_numbers`, starting from 1, and the objects they use,
+    /// see [`Reader::read_pages`].
+    #[inline]
+    pub fn load_pages<P: AsRef<Path>>(path: P, page_numbers: &[u32]) -> Result<Document> {
+        let buffer = std::fs::read(path)?;
+        Self::load_pages_mem(&buffer, page_numbers)
+    }
+
+    /// Load only the pages numbered `page_numbers` of the document in a memory slice, and the
+    /// objects they use. Objects not used by these pages aren't parsed.
+    pub fn load_pages_mem(buffer: &[u8], page_numbers: &[u32]) -> Result<Document> {
+        Reader::new(buffer, ReaderOptions::default()).read_pages(page_numbers)
     }
 
     fn load_metadata_internal<R: Read>(
-        mut source: R, capacity: Option<usize>, password: Option<String>,
+        mut source: R, capacity: Option<usize>, password: Option<Password>,
     ) -> Result<PdfMetadata> {
         let mut buffer = capacity.map(Vec::with_capacity).unwrap_or_default();
         source.read_to_end(&mut buffer)?;
 
         Reader {
-            buffer: &buffer,
-            document: Document::new(),
-            encryption_state: None,
-
             password,
+            ..Reader::new(&buffer, ReaderOptions::default())
         }
         .read_metadata()
     }
@@ -166,23 +293,75 @@ pub async fn load<P: AsRef<Path>>(path: P) -> Result<Document> {
         Self::load_internal(file, capacity, None, None).await
     }
 
+    /// Load a PDF document from a specified file path, parsing it with `options`.
+    pub async fn load_with_options<P: AsRef<Path>>(path: P, options: ReaderOptions) -> Result<Document> {
+        let buffer = tokio::fs::read(path).await?;
+        Self::load_mem_with_options(&buffer, options)
+    }
+
+    /// Load a PDF document from a specified file path with the `index` built from the file
+    /// earlier, see [`Document::load_mem_with_index`].
+    pub async fn load_with_index<P: AsRef<Path>>(path: P, index: &DocumentIndex) -> Result<Document> {
+        let buffer = tokio::fs::read(path).await?;
+        Self::load_mem_with_index(&buffer, index)
+    }
+
+    /// Load a PDF document from a specified file path, calling `progress` with the progress of
+    /// loading, see [`Document::load_mem_with_progress`].
+    pub async fn load_with_progress<P, F>(path: P, progress: F) -> Result<Document>
+    where
+        P: AsRef<Path>,
+        F: Fn(LoadProgress) -> ControlFlow<()> + Send + Sync + 'static,
+    {
+        let buffer = tokio::fs::read(path).await?;
+        Self::load_mem_with_progress(&buffer, progress)
+    }
+
+    /// Load a PDF document from a specified file path, rebuilding its cross-reference table if it's
+    /// corrupt, see [`Reader::read_with_recovery`].
+    pub async fn load_with_recovery<P: AsRef<Path>>(path: P) -> Result<Document> {
+        let buffer = tokio::fs::read(path).await?;
+        Self::load_mem_with_recovery(&buffer)
+    }
+
     /// Load a PDF document from a specified file path with a password for encrypted PDFs.
     pub async fn load_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<Document> {
         let file = File::open(path).await?;
         let metadata = file.metadata().await?;
         let capacity = Some(metadata.len() as usize);
-        Self::load_internal(file, capacity, None, Some(password.to_string())).await
+        Self::load_internal(file, capacity, None, Some(password.into())).await
+    }
+
+    /// Load a PDF document from a specified file path with a password given as the bytes the
+    /// security handler hashes, see [`Document::authenticate_raw_password`].
+    pub async fn load_with_password_bytes<P: AsRef<Path>>(path: P, password: &[u8]) -> Result<Document> {
+        let file = File::open(path).await?;
+        let metadata = file.metadata().await?;
+        let capacity = Some(metadata.len() as usize);
+        Self::load_internal(file, capacity, None, Some(password.into())).await
     }
 
-    pub async fn load_filtered<P: AsRef<Path>>(path: P, filter_func: FilterFunc) -> Result<Document> {
+    /// Load a PDF document from a specified file path, passing each object through
+    /// `filter_func`, see [`LoadFilter`].
+    pub async fn load_filtered<P, F>(path: P, filter_func: F) -> Result<Document>
+    where
+        P: AsRef<Path>,
+        F: Fn((u32, u16), &mut Object) -> Option<((u32, u16), Object)> + Sync,
+    {
         let file = File::open(path).await?;
         let metadata = file.metadata().await?;
         let capacity = Some(metadata.len() as usize);
-        Self::load_internal(file, capacity, Some(filter_func), None).await
+        Self::load_internal(file, capacity, Some(&filter_func), None).await
+    }
+
+    /// Load a PDF document from a specified file path, passing its objects and trailer through
+    /// `filters`, see [`Reader::read_with_filters`].
+    pub async fn load_with_filters<P: AsRef<Path>>(path: P, filters: LoadFilters<'_>) -> Result<Document> {
+        Self::load_mem_with_filters(&tokio::fs::read(path).await?, filters)
     }
 
     async fn load_internal<R: AsyncRead>(
-        source: R, capacity: Option<usize>, filter_func: Option<FilterFunc>, password: Option<String>,
+        source: R, capacity: Option<usize>, filter_func: Option<&LoadFilter<'_>>, password: Option<Password>,
     ) -> Result<Document> {
         pin!(source);
 
@@ -190,11 +369,8 @@ async fn load_internal<R: AsyncRead>(
         source.read_to_end(&mut buffer).await?;
 
         Reader {
-            buffer: &buffer,
-            document: Document::new(),
-            encryption_state: None,
-
             password,
+            ..Reader::new(&buffer, ReaderOptions::default())
         }
         .read(filter_func)
     }
@@ -204,6 +380,42 @@ pub fn load_mem(buffer: &[u8]) -> Result<Document> {
         buffer.try_into()
     }
 
+    /// Load a PDF document from a memory slice, parsing it with `options`.
+    pub fn load_mem_with_options(buffer: &[u8], options: ReaderOptions) -> Result<Document> {
+        Reader::new(buffer, options).read(None)
+    }
+
+    /// Load a PDF document from a memory slice with the `index` built from it earlier by
+    /// [`Document::build_index`], which spares reading the cross-reference sections. The index of
+    /// another file is ignored, see [`ReaderOptions::index`].
+    pub fn load_mem_with_index(buffer: &[u8], index: &DocumentIndex) -> Result<Document> {
+        Self::load_mem_with_options(buffer, ReaderOptions::builder().index(index.clone()).build())
+    }
+
+    /// Load a PDF document from a memory slice, calling `progress` with the progress of loading.
+    /// Returning [`ControlFlow::Break`] cancels loading, which fails with [`Error::Cancelled`]
+    /// soon after. Under the `rayon` feature, objects are parsed on several threads, and their
+    /// count is reported from the calling thread while they are.
+    pub fn load_mem_with_progress<F>(buffer: &[u8], progress: F) -> Result<Document>
+    where
+        F: Fn(LoadProgress) -> ControlFlow<()> + Send + Sync + 'static,
+    {
+        let options = ReaderOptions::builder().progress(ProgressFn::new(progress)).build();
+        Self::load_mem_with_options(buffer, options)
+    }
+
+    /// Load a PDF document from a memory slice, rebuilding its cross-reference table if it's
+    /// corrupt, see [`Reader::read_with_recovery`].
+    pub fn load_mem_with_recovery(buffer: &[u8]) -> Result<Document> {
+        Reader::new(buffer, ReaderOptions::default()).read_with_recovery(None)
+    }
+
+    /// Load a PDF document from a memory slice, passing its objects and trailer through
+    /// `filters`, see [`Reader::read_with_filters`].
+    pub fn load_mem_with_filters(buffer: &[u8], filters: LoadFilters<'_>) -> Result<Document> {
+        Reader::new(buffer, ReaderOptions::default()).read_with_filters(filters)
+    }
+
     /// Load PDF metadata (title and page count) without loading the entire document.
     /// This is much faster for large PDFs when you only need basic information.
     #[inline]
@@ -214,13 +426,22 @@ pub async fn load_metadata<P: AsRef<Path>>(path: P) -> Result<PdfMetadata> {
         Self::load_metadata_internal(file, capacity, None).await
     }
 
+    /// Load PDF metadata from a file path, also reading what `options` ask for, such as the
+    /// dimensions of each page.
+    pub async fn load_metadata_with_options<P: AsRef<Path>>(
+        path: P, options: &MetadataOptions,
+    ) -> Result<PdfMetadata> {
+        let buffer = tokio::fs::read(path).await?;
+        Self::load_metadata_mem_with_options(&buffer, options)
+    }
+
     /// Load PDF metadata from a file path with a password for encrypted PDFs.
     #[inline]
     pub async fn load_metadata_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<PdfMetadata> {
         let file = File::open(path).await?;
         let metadata = file.metadata().await?;
         let capacity = Some(metadata.len() as usize);
-        Self::load_metadata_internal(file, capacity, Some(password.to_string())).await
+        Self::load_metadata_internal(file, capacity, Some(password.into())).await
     }
 
     /// Load PDF metadata from an arbitrary source without loading the entire document.
@@ -232,37 +453,56 @@ pub async fn load_metadata_from<R: AsyncRead>(source: R) -> Result<PdfMetadata>
     /// Load PDF metadata from an arbitrary source with a password for encrypted PDFs.
     #[inline]
     pub async fn load_metadata_from_with_password<R: AsyncRead>(source: R, password: &str) -> Result<PdfMetadata> {
-        Self::load_metadata_internal(source, None, Some(password.to_string())).await
+        Self::load_metadata_internal(source, None, Some(password.into())).await
     }
 
     /// Load PDF metadata from a memory slice without loading the entire document.
     #[inline]
     pub fn load_metadata_mem(buffer: &[u8]) -> Result<PdfMetadata> {
-        Reader {
-            buffer,
-            document: Document::new(),
-            encryption_state: None,
+        Reader::new(buffer, ReaderOptions::default()).read_metadata()
+    }
 
-            password: None,
-        }
-        .read_metadata()
+    /// Load PDF metadata from a memory slice, also reading what `options` ask for.
+    pub fn load_metadata_mem_with_options(buffer: &[u8], options: &MetadataOptions) -> Result<PdfMetadata> {
+        Reader::new(buffer, ReaderOptions::default()).read_metadata_with_options(options)
     }
 
     /// Load PDF metadata from a memory slice with a password for encrypted PDFs.
     #[inline]
     pub fn load_metadata_mem_with_password(buffer: &[u8], password: &str) -> Result<PdfMetadata> {
-        Reader {
-            buffer,
-            document: Document::new(),
-            encryption_state: None,
+        Reader::new(buffer, ReaderOptions::default()).with_password(password).read_metadata()
+    }
 
-            password: Some(password.to_string()),
-        }
-        .read_metadata()
+    /// Get the preview information of a page without loading the entire document, see
+    /// [`Document::preview_info`].
+    #[inline]
+    pub async fn load_preview<P: AsRef<Path>>(path: P, page_number: u32) -> Result<PreviewInfo> {
+        let buffer = tokio::fs::read(path).await?;
+        Self::load_preview_mem(&buffer, page_number)
+    }
+
+    /// Get the preview information of a page from a memory slice without loading the entire
+    /// document.
+    #[inline]
+    pub fn load_preview_mem(buffer: &[u8], page_number: u32) -> Result<PreviewInfo> {
+        Reader::new(buffer, ReaderOptions::default()).read_preview(page_number)
+    }
+
+    /// Load only the pages numbered `page_numbers`, starting from 1, and the objects they use,
+    /// see [`Reader::read_pages`].
+    pub async fn load_pages<P: AsRef<Path>>(path: P, page_numbers: &[u32]) -> Result<Document> {
+        let buffer = tokio::fs::read(path).await?;
+        Self::load_pages_mem(&buffer, page_numbers)
+    }
+
+    /// Load only the pages numbered `page_numbers` of the document in a memory slice, and the
+    /// objects they use. Objects not used by these pages aren't parsed.
+    pub fn load_pages_mem(buffer: &[u8], page_numbers: &[u32]) -> Result<Document> {
+        Reader::new(buffer, ReaderOptions::default()).read_pages(page_numbers)
     }
 
     async fn load_metadata_internal<R: AsyncRead>(
-        source: R, capacity: Option<usize>, password: Option<String>,
+        source: R, capacity: Option<usize>, password: Option<Password>,
     ) -> Result<PdfMetadata> {
         pin!(source);
 
@@ -270,11 +510,8 @@ async fn load_metadata_internal<R: AsyncRead>(
         source.read_to_end(&mut buffer).await?;
 
         Reader {
-            buffer: &buffer,
-            document: Document::new(),
-            encryption_state: None,
-
             password,
+            ..Reader::new(&buffer, ReaderOptions::default())
         }
         .read_metadata()
     }
@@ -284,14 +521,7 @@ impl TryInto<Document> for &[u8] {
     type Error = Error;
 
     fn try_into(self) -> Result<Document> {
-        Reader {
-            buffer: self,
-            document: Document::new(),
-            encryption_state: None,
-
-            password: None,
-        }
-        .read(None)
+        Reader::new(self, ReaderOptions::default()).read(None)
     }
 }
 
@@ -315,14 +545,7 @@ fn load_internal<R: Read>(mut source: R, capacity: Option<usize>) -> Result<Self
         let mut buffer = capacity.map(Vec::with_capacity).unwrap_or_default();
         source.read_to_end(&mut buffer)?;
 
-        let document = Reader {
-            buffer: &buffer,
-            document: Document::new(),
-            encryption_state: None,
-
-            password: None,
-        }
-        .read(None)?;
+        let document = Reader::new(&buffer, ReaderOptions::default()).read(None)?;
 
         Ok(IncrementalDocument::create_from(buffer, document))
     }
@@ -356,14 +579,7 @@ async fn load_internal<R: AsyncRead>(source: R, capacity: Option<usize>) -> Resu
         let mut buffer = capacity.map(Vec::with_capacity).unwrap_or_default();
         source.read_to_end(&mut buffer).await?;
 
-        let document = Reader {
-            buffer: &buffer,
-            document: Document::new(),
-            encryption_state: None,
-
-            password: None,
-        }
-        .read(None)?;
+        let document = Reader::new(&buffer, ReaderOptions::default()).read(None)?;
 
         Ok(IncrementalDocument::create_from(buffer, document))
     }
@@ -378,14 +594,7 @@ impl TryInto<IncrementalDocument> for &[u8] {
     type Error = Error;
 
     fn try_into(self) -> Result<IncrementalDocument> {
-        let document = Reader {
-            buffer: self,
-            document: Document::new(),
-            encryption_state: None,
-
-            password: None,
-        }
-        .read(None)?;
+        let document = Reader::new(self, ReaderOptions::default()).read(None)?;
 
         Ok(IncrementalDocument::create_from(self.to_vec(), document))
     }
@@ -1,10 +1,25 @@
 use log::warn;
-use std::collections::HashSet;
+use memchr::memmem;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use super::Reader;
-use crate::error::{ParseError, XrefError};
-use crate::parser::{self, ParserInput};
-use crate::{Dictionary, Error, Object, ObjectId, Result};
+use crate::encodings::decode_utf16_be;
+use crate::encryption::Permissions;
+use crate::error::ParseError;
+use crate::object_stream::ObjectStream;
+use crate::parser;
+use crate::preview::DEFAULT_PAGE_SIZE;
+use crate::repair::is_single_page_root;
+use crate::xref::XrefEntry;
+use crate::xmp::{pdf_date, xmp_packet};
+use crate::{Dictionary, Object, ObjectId, Result, Stream, XmpMetadata};
+
+/// Number of cross-reference entries inspected when estimating the page count of a document
+/// whose page tree cannot be resolved, unless [`MetadataOptions::page_estimate_scan_limit`] is set.
+const PAGE_ESTIMATE_SCAN_LIMIT: usize = 100_000;
+
+/// Depth of page tree nodes beyond which kids aren't counted, as in `Document::get_pages`.
+const PAGE_TREE_DEPTH_LIMIT: usize = 256;
 
 /// PDF metadata extracted without loading the entire document.
 /// This is useful for quickly getting basic information about large PDFs.
@@ -28,10 +43,101 @@ pub struct PdfMetadata {
     pub modification_date: Option<String>,
     /// Number of pages in the document
     pub page_count: u32,
+    /// Whether `page_count` was estimated by scanning for `/Type /Page` objects
+    /// because the page tree could not be read
+    pub page_count_is_estimate: bool,
+    /// Whether the page tree root declares more pages in its /Count than are found walking the
+    /// tree, which `page_count` is the number of
+    pub page_count_mismatch: bool,
     /// PDF version
     pub version: String,
+    /// The dimensions of each page, in page order, if requested with
+    /// [`MetadataOptions::page_dimensions`]
+    pub page_dimensions: Vec<PageDimensions>,
+    /// Whether the document is encrypted. Without the password its Info strings can't be
+    /// decrypted, and the fields read from them are `None`.
+    pub is_encrypted: bool,
+    /// How the document is encrypted, read from its /Encrypt dictionary
+    pub encryption: Option<EncryptionInfo>,
+    /// The properties of the catalog's XMP metadata stream, if requested with
+    /// [`MetadataOptions::xmp`]
+    pub xmp: Option<XmpMetadata>,
 }
 
+#[cfg(feature = "chrono")]
+impl PdfMetadata {
+    /// The creation date parsed into a date and time, or `None` if missing or malformed.
+    pub fn creation_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.creation_date.as_deref().and_then(crate::datetime::parse_pdf_datetime)
+    }
+
+    /// The modification date parsed into a date and time, or `None` if missing or malformed.
+    pub fn modification_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.modification_date.as_deref().and_then(crate::datetime::parse_pdf_datetime)
+    }
+}
+
+/// What [`Reader::read_metadata_with_options`] reads beyond the document information and the
+/// page count.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataOptions {
+    /// Walk the whole page tree to read the dimensions of each page
+    pub page_dimensions: bool,
+    /// Fail with an error when the document is encrypted and the password to decrypt it isn't
+    /// given, rather than leaving out the encrypted fields
+    pub require_decryption: bool,
+    /// Read the XMP metadata stream of the catalog, and prefer its title, authors, producer and
+    /// dates over those of the Info dictionary. Its dates are converted to PDF dates.
+    pub xmp: bool,
+    /// Maximum number of cross-reference entries scanned for page objects when the page tree
+    /// can't be read and the page count is estimated, 100 000 if `None`
+    pub page_estimate_scan_limit: Option<usize>,
+}
+
+/// The encryption of a document, as its /Encrypt dictionary declares it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionInfo {
+    /// Security handler, `Standard` for password-based encryption
+    pub filter: String,
+    /// Algorithm version (/V)
+    pub v: i64,
+    /// Revision of the standard security handler (/R)
+    pub r: i64,
+    /// Key length in bits (/Length), if given
+    pub key_length: Option<i64>,
+    /// What a user may do with the document (/P)
+    pub permissions: Permissions,
+}
+
+impl EncryptionInfo {
+    fn from_dictionary(encrypt: &Dictionary) -> Self {
+        let integer = |key: &[u8]| encrypt.get(key).and_then(Object::as_i64).ok();
+        EncryptionInfo {
+            filter: encrypt
+                .get(b"Filter")
+                .and_then(Object::as_name)
+                .map(|name| String::from_utf8_lossy(name).into_owned())
+                .unwrap_or_default(),
+            v: integer(b"V").unwrap_or(0),
+            r: integer(b"R").unwrap_or(0),
+            key_length: integer(b"Length"),
+            permissions: Permissions::from_p(integer(b"P").unwrap_or(-1)),
+        }
+    }
+}
+
+/// The media box size and rotation of a page, with the attributes it inherits from the page tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageDimensions {
+    /// Width of the media box in points, before rotation
+    pub width: f32,
+    /// Height of the media box in points, before rotation
+    pub height: f32,
+    /// Clockwise rotation of the page when shown, in degrees: 0, 90, 180 or 270
+    pub rotation: i64,
+}
+
+#[derive(Default)]
 pub struct InfoMetadata {
     pub title: Option<String>,
     pub author: Option<String>,
@@ -43,72 +149,73 @@ pub struct InfoMetadata {
     pub modification_date: Option<String>,
 }
 
+impl InfoMetadata {
+    /// Take the values `xmp` has over those of the Info dictionary. Several authors are joined
+    /// with `; `.
+    fn prefer_xmp(&mut self, xmp: &XmpMetadata) {
+        self.title = xmp.title.clone().or(self.title.take());
+        if !xmp.creators.is_empty() {
+            self.author = Some(xmp.creators.join("; "));
+        }
+        self.producer = xmp.producer.clone().or(self.producer.take());
+        self.creation_date = xmp.create_date.as_deref().and_then(pdf_date).or(self.creation_date.take());
+        self.modification_date = xmp.modify_date.as_deref().and_then(pdf_date).or(self.modification_date.take());
+    }
+}
+
 impl Reader<'_> {
     /// Read metadata (title and page count) without loading the entire document.
     /// This is much faster for large PDFs when you only need basic information.
     ///
-    /// For encrypted PDFs, use `Document::load_metadata_with_password()` instead.
-    pub fn read_metadata(mut self) -> Result<PdfMetadata> {
-        let offset = self.buffer.windows(5).position(|w| w == b"%PDF-").unwrap_or(0);
-        self.buffer = &self.buffer[offset..];
-
-        let version =
-            parser::header(ParserInput::new_extra(self.buffer, "header")).ok_or(ParseError::InvalidFileHeader)?;
+    /// Encrypted PDFs are decrypted with the empty password or the one given to the reader. When
+    /// neither works, the Info fields are left out, see [`PdfMetadata::is_encrypted`].
+    pub fn read_metadata(self) -> Result<PdfMetadata> {
+        self.read_metadata_with_options(&MetadataOptions::default())
+    }
 
-        let xref_start = Self::get_xref_start(self.buffer)?;
-        if xref_start > self.buffer.len() {
-            return Err(Error::Xref(XrefError::Start));
+    /// Like [`Reader::read_metadata`], also reading what `options` ask for.
+    pub fn read_metadata_with_options(mut self, options: &MetadataOptions) -> Result<PdfMetadata> {
+        let version = self.read_trailer_unauthenticated()?;
+        let mut encryption = None;
+        let mut decrypted = true;
+        if self.document.trailer.get(b"Encrypt").is_ok() {
+            self.parse_encryption_dictionary()?;
+            encryption = self.document.get_encrypted().ok().map(EncryptionInfo::from_dictionary);
+            decrypted = self
+                .authenticate_and_setup_encryption(options.require_decryption)?
+                .is_some();
         }
 
-        let (mut xref, mut trailer) =
-            parser::xref_and_trailer(ParserInput::new_extra(&self.buffer[xref_start..], "xref"), &self)?;
-
-        let mut already_seen = HashSet::new();
-        let mut prev_xref_start = trailer.remove(b"Prev");
-        while let Some(prev) = prev_xref_start.and_then(|offset| offset.as_i64().ok()) {
-            if already_seen.contains(&prev) {
-                break;
-            }
-            already_seen.insert(prev);
-            if prev < 0 || prev as usize > self.buffer.len() {
-                return Err(Error::Xref(XrefError::PrevStart));
-            }
-
-            let (prev_xref, prev_trailer) =
-                parser::xref_and_trailer(ParserInput::new_extra(&self.buffer[prev as usize..], ""), &self)?;
-            xref.merge(prev_xref);
-
-            let prev_xref_stream_start = trailer.remove(b"XRefStm");
-            if let Some(prev) = prev_xref_stream_start.and_then(|offset| offset.as_i64().ok()) {
-                if prev < 0 || prev as usize > self.buffer.len() {
-                    return Err(Error::Xref(XrefError::StreamStart));
-                }
-
-                let (prev_xref, _) =
-                    parser::xref_and_trailer(ParserInput::new_extra(&self.buffer[prev as usize..], ""), &self)?;
-                xref.merge(prev_xref);
-            }
-
-            prev_xref_start = prev_trailer.get(b"Prev").cloned().ok();
-        }
-        let xref_entry_count = xref.max_id().checked_add(1).ok_or(ParseError::InvalidXref)?;
-        if xref.size != xref_entry_count {
-            warn!(
-                "Size entry of trailer dictionary is {}, correct value is {}.",
-                xref.size, xref_entry_count
-            );
-            xref.size = xref_entry_count;
+        let mut info_metadata = if decrypted {
+            self.extract_info_metadata()?
+        } else {
+            InfoMetadata::default()
+        };
+        let xmp = if options.xmp && decrypted {
+            self.extract_xmp_metadata()
+        } else {
+            None
+        };
+        if let Some(xmp) = &xmp {
+            info_metadata.prefer_xmp(xmp);
         }
-
-        self.document.reference_table = xref;
-        self.document.trailer = trailer.clone();
-
-        if self.document.trailer.get(b"Encrypt").is_ok() {
-            self.setup_encryption_for_metadata()?;
+        let mut page_count = self.extract_page_count()?;
+        let mut page_count_is_estimate = false;
+        if page_count == 0 && !self.document.reference_table.entries.is_empty() {
+            let estimate = self.estimate_page_count(options.page_estimate_scan_limit.unwrap_or(PAGE_ESTIMATE_SCAN_LIMIT));
+            if estimate > 0 {
+                warn!("page tree could not be read, estimated {estimate} pages from page objects");
+                page_count = estimate;
+                page_count_is_estimate = true;
+            }
         }
-
-        let info_metadata = self.extract_info_metadata()?;
-        let page_count = self.extract_page_count()?;
+        let page_count_mismatch = match self.declared_page_count() {
+            Some(declared) if !page_count_is_estimate && declared > i64::from(page_count) => {
+                warn!("page tree declares {declared} pages, but has {page_count}");
+                true
+            }
+            _ => false,
+        };
 
         Ok(PdfMetadata {
             title: info_metadata.title,
@@ -120,10 +227,44 @@ pub fn read_metadata(mut self) -> Result<PdfMetadata> {
             creation_date: info_metadata.creation_date,
             modification_date: info_metadata.modification_date,
             page_count,
+            page_count_is_estimate,
+            page_count_mismatch,
             version,
+            page_dimensions: if options.page_dimensions {
+                self.extract_page_dimensions()
+            } else {
+                Vec::new()
+            },
+            is_encrypted: encryption.is_some(),
+            encryption,
+            xmp,
         })
     }
 
+    /// Read the header, the cross-reference sections and the trailer, and set up decryption,
+    /// without loading any objects. Returns the PDF version.
+    pub(super) fn read_trailer_lazily(&mut self) -> Result<String> {
+        let version = self.read_trailer_unauthenticated()?;
+        if self.document.trailer.get(b"Encrypt").is_ok() {
+            self.setup_encryption_for_metadata()?;
+        }
+        Ok(version)
+    }
+
+    /// Like [`Reader::read_trailer_lazily`], leaving decryption to the caller.
+    fn read_trailer_unauthenticated(&mut self) -> Result<String> {
+        let offset = memmem::find(self.buffer, b"%PDF-").unwrap_or(0);
+        self.buffer = &self.buffer[offset..];
+
+        let version =
+            parser::header(parser::new_input(self.buffer)).ok_or(ParseError::InvalidFileHeader)?;
+
+        let (xref, trailer, _) = self.read_xref_sections()?;
+        self.document.reference_table = xref;
+        self.document.trailer = trailer;
+        Ok(version)
+    }
+
     pub(super) fn extract_info_metadata(&self) -> Result<InfoMetadata> {
         let info_ref = match self.document.trailer.get(b"Info") {
             Ok(obj) => obj.as_reference().ok(),
@@ -202,16 +343,27 @@ pub(super) fn extract_info_metadata(&self) -> Result<InfoMetadata> {
         })
     }
 
+    /// The properties of the XMP metadata stream of the catalog, if it has one that can be read.
+    fn extract_xmp_metadata(&self) -> Option<XmpMetadata> {
+        let catalog = self
+            .document
+            .trailer
+            .get(b"Root")
+            .and_then(Object::as_reference)
+            .and_then(|root| self.get_object(root, &mut HashSet::new()))
+            .ok()?;
+        let metadata_id = catalog.as_dict().ok()?.get(b"Metadata").and_then(Object::as_reference).ok()?;
+        let metadata = self.get_object(metadata_id, &mut HashSet::new()).ok()?;
+        let xml = xmp_packet(metadata.as_stream().ok()?, Stream::decompressed_content).ok()?;
+        Some(XmpMetadata::parse(&xml))
+    }
+
     fn extract_string_field(dict: &Dictionary, key: &[u8]) -> Option<String> {
         match dict.get(key) {
             Ok(obj) => match obj {
                 Object::String(bytes, _) => {
-                    let s = if bytes.len() >= 2 && bytes[0] == 0xFE && bytes[1] == 0xFF {
-                        let utf16_bytes: Vec<u16> = bytes[2..]
-                            .chunks_exact(2)
-                            .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
-                            .collect();
-                        String::from_utf16_lossy(&utf16_bytes)
+                    let s = if let Some(utf16) = bytes.strip_prefix(b"\xFE\xFF") {
+                        decode_utf16_be(utf16).into_text("decoding metadata")
                     } else {
                         String::from_utf8_lossy(bytes).to_string()
                     };
@@ -244,54 +396,205 @@ pub(super) fn extract_page_count(&self) -> Result<u32> {
             Ok(id) => id,
             Err(_) => return Ok(0),
         };
+        // Some producers point /Pages directly at the single page.
+        let pages_obj = self.get_object(pages_ref, &mut HashSet::new());
+        if pages_obj.as_ref().is_ok_and(|pages| pages.as_dict().is_ok_and(is_single_page_root)) {
+            return Ok(1);
+        }
 
-        self.get_pages_tree_count(pages_ref, &mut HashSet::new()).or(Ok(0))
+        Ok(self.get_pages_tree_count(pages_ref, &mut HashSet::new(), &mut HashMap::new()))
     }
 
-    fn get_pages_tree_count(&self, pages_id: ObjectId, seen: &mut HashSet<ObjectId>) -> Result<u32> {
-        if seen.contains(&pages_id) {
-            return Err(Error::ReferenceCycle(pages_id));
+    /// The /Count of the page tree root.
+    fn declared_page_count(&self) -> Option<i64> {
+        let root = self.document.trailer.get(b"Root").and_then(Object::as_reference).ok()?;
+        let catalog = self.get_object(root, &mut HashSet::new()).ok()?;
+        let pages = catalog.as_dict().ok()?.get(b"Pages").and_then(Object::as_reference).ok()?;
+        let pages = self.get_object(pages, &mut HashSet::new()).ok()?;
+        let count = pages.as_dict().ok()?.get(b"Count").ok()?;
+        self.resolve(count).ok()?.as_i64().ok()
+    }
+
+    /// The dimensions of the pages of the page tree, in page order. Pages without a /MediaBox,
+    /// on them or an ancestor, are US Letter.
+    fn extract_page_dimensions(&self) -> Vec<PageDimensions> {
+        let mut dimensions = Vec::new();
+        let pages_id = self
+            .document
+            .trailer
+            .get(b"Root")
+            .and_then(Object::as_reference)
+            .and_then(|root| self.get_object(root, &mut HashSet::new()))
+            .and_then(|catalog| catalog.as_dict()?.get(b"Pages")?.as_reference());
+        if let Ok(pages_id) = pages_id {
+            let inherited = InheritedAttributes::default();
+            self.collect_page_dimensions(pages_id, inherited, &mut HashSet::new(), &mut dimensions);
         }
-        seen.insert(pages_id);
+        dimensions
+    }
 
-        let mut already_seen = HashSet::new();
-        let pages_obj = match self.get_object(pages_id, &mut already_seen) {
-            Ok(obj) => obj,
-            Err(_) => return Ok(0),
+    fn collect_page_dimensions(
+        &self, node_id: ObjectId, mut inherited: InheritedAttributes, ancestors: &mut HashSet<ObjectId>,
+        dimensions: &mut Vec<PageDimensions>,
+    ) {
+        let Ok(node) = self.get_object(node_id, &mut HashSet::new()) else {
+            return;
+        };
+        let Ok(node) = node.as_dict() else {
+            return;
         };
+        if let Some(media_box) = node.get(b"MediaBox").ok().and_then(|media_box| self.rectangle(media_box)) {
+            inherited.media_box = Some(media_box);
+        }
+        if let Ok(rotate) = node.get(b"Rotate").and_then(|rotate| self.resolve(rotate)?.as_i64()) {
+            inherited.rotate = rotate;
+        }
+
+        let kids = node.get(b"Kids").and_then(Object::as_array);
+        match (node.get_type(), kids) {
+            (Ok(b"Pages"), _) if ancestors.len() >= PAGE_TREE_DEPTH_LIMIT => {}
+            (Ok(b"Pages"), Ok(kids)) => {
+                ancestors.insert(node_id);
+                for kid in kids.iter().filter_map(|kid| kid.as_reference().ok()) {
+                    if !ancestors.contains(&kid) {
+                        self.collect_page_dimensions(kid, inherited, ancestors, dimensions);
+                    }
+                }
+                ancestors.remove(&node_id);
+            }
+            (Ok(b"Pages"), Err(_)) if !is_single_page_root(node) => {}
+            _ => {
+                let (width, height) = DEFAULT_PAGE_SIZE;
+                let [llx, lly, urx, ury] = inherited.media_box.unwrap_or([0.0, 0.0, width, height]);
+                dimensions.push(PageDimensions {
+                    width: (urx - llx).abs(),
+                    height: (ury - lly).abs(),
+                    rotation: inherited.rotate.rem_euclid(360) / 90 * 90,
+                });
+            }
+        }
+    }
+
+    /// The rectangle `object` is, following references.
+    fn rectangle(&self, object: &Object) -> Option<[f32; 4]> {
+        let values: Vec<f32> = self
+            .resolve(object)
+            .ok()?
+            .as_array()
+            .ok()?
+            .iter()
+            .filter_map(|value| self.resolve(value).ok()?.as_float().ok())
+            .collect();
+        values.try_into().ok()
+    }
+
+    /// `object`, or the object it references.
+    fn resolve(&self, object: &Object) -> Result<Object> {
+        match object {
+            Object::Reference(id) => self.get_object(*id, &mut HashSet::new()),
+            object => Ok(object.clone()),
+        }
+    }
+
+    /// Count `/Type /Page` dictionaries reachable through the cross-reference table,
+    /// scanning at most `scan_limit` entries.
+    pub(super) fn estimate_page_count(&self, scan_limit: usize) -> u32 {
+        let mut count = 0;
+        let mut containers = BTreeSet::new();
+
+        let entries = self.document.reference_table.entries.iter();
+        for (&id, entry) in entries.take(scan_limit) {
+            match *entry {
+                XrefEntry::Normal { generation, .. } => {
+                    if let Ok(Object::Dictionary(dict)) = self.get_object((id, generation), &mut HashSet::new()) {
+                        if dict.has_type(b"Page") {
+                            count += 1;
+                        }
+                    }
+                }
+                XrefEntry::Compressed { container, .. } => {
+                    containers.insert(container);
+                }
+                _ => {}
+            }
+        }
 
+        // Parse each object stream once instead of once per compressed object.
+        for container in containers {
+            let Ok(Object::Stream(mut stream)) = self.get_object((container, 0), &mut HashSet::new()) else {
+                continue;
+            };
+            let Ok(object_stream) = ObjectStream::with_limits(&mut stream, self.options.parse_limits()) else {
+                continue;
+            };
+            count += object_stream
+                .objects
+                .iter()
+                .filter(|((id, _), object)| {
+                    matches!(
+                        self.document.reference_table.get(*id),
+                        Some(XrefEntry::Compressed { container: c, .. }) if *c == container
+                    ) && matches!(object, Object::Dictionary(dict) if dict.has_type(b"Page"))
+                })
+                .count() as u32;
+        }
+
+        count
+    }
+
+    /// Count the pages of a page tree node. A /Count of at most the number of objects in the file
+    /// is trusted; otherwise the /Page leaves are counted the way `Document::get_pages` finds
+    /// them after the page tree was repaired on load, skipping kids that list an ancestor.
+    fn get_pages_tree_count(
+        &self, pages_id: ObjectId, ancestors: &mut HashSet<ObjectId>, counts: &mut HashMap<ObjectId, u32>,
+    ) -> u32 {
+        if let Some(&count) = counts.get(&pages_id) {
+            return count;
+        }
+        let pages_obj = match self.get_object(pages_id, &mut HashSet::new()) {
+            Ok(obj) => obj,
+            Err(_) => return 0,
+        };
         let pages_dict = match pages_obj.as_dict() {
             Ok(dict) => dict,
-            Err(_) => return Ok(0),
+            Err(_) => return 0,
         };
 
         match pages_dict.get_type() {
-            Ok(type_name) if type_name == b"Page" => Ok(1),
-            Ok(type_name) if type_name == b"Pages" => {
-                if let Ok(count_obj) = pages_dict.get(b"Count") {
-                    if let Ok(count) = count_obj.as_i64() {
-                        if count >= 0 {
-                            return Ok(count as u32);
-                        }
-                    }
+            Ok(b"Pages") if ancestors.len() >= PAGE_TREE_DEPTH_LIMIT => 0,
+            Ok(b"Pages") => {
+                let objects = self.document.reference_table.entries.len() as i64;
+                let count = pages_dict.get(b"Count").and_then(|count| self.resolve(count)?.as_i64());
+                if let Some(count) = count.ok().filter(|count| (0..=objects).contains(count)) {
+                    return u32::try_from(count).unwrap_or(u32::MAX);
                 }
-
                 let kids = match pages_dict.get(b"Kids").and_then(Object::as_array) {
                     Ok(arr) => arr,
-                    Err(_) => return Ok(0),
+                    Err(_) => return 0,
                 };
 
+                ancestors.insert(pages_id);
                 let mut total = 0u32;
                 for kid in kids.iter() {
                     if let Ok(kid_ref) = kid.as_reference() {
-                        if let Ok(count) = self.get_pages_tree_count(kid_ref, seen) {
-                            total += count;
+                        if !ancestors.contains(&kid_ref) {
+                            total = total.saturating_add(self.get_pages_tree_count(kid_ref, ancestors, counts));
                         }
                     }
                 }
-                Ok(total)
+                ancestors.remove(&pages_id);
+                counts.insert(pages_id, total);
+                total
             }
-            _ => Ok(1),
+            Ok(b"Page") => 1,
+            _ => 0,
         }
     }
 }
+
+/// The page attributes a page tree node passes down to its kids.
+#[derive(Clone, Copy, Default)]
+struct InheritedAttributes {
+    media_box: Option<[f32; 4]>,
+    rotate: i64,
+}
@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{Document, Result};
+
+impl Document {
+    /// Load a PDF document from a specified file path, parsing it straight out of a memory map of
+    /// the file instead of reading it into a buffer first. Pages of the file are read as the
+    /// parser gets to them, and the contents of streams are copied into the document, which
+    /// doesn't borrow from the map: it's unmapped before this returns. Available with the `mmap`
+    /// feature.
+    ///
+    /// # Safety
+    ///
+    /// The file must not change while it's loaded, by this process or another. Parsing a map of a
+    /// file that's written to or truncated meanwhile is undefined behavior, and may crash the
+    /// process with `SIGBUS` on Unix when pages of a truncated file are read. Files other
+    /// processes may write to are loaded safely with [`Document::load`].
+    ///
+    /// ```no_run
+    /// // Safety: the file is only ever read.
+    /// let doc = unsafe { lopdf::Document::load_mmap("archive/report.pdf") }?;
+    /// # Ok::<(), lopdf::Error>(())
+    /// ```
+    pub unsafe fn load_mmap<P: AsRef<Path>>(path: P) -> Result<Document> {
+        let file = File::open(path)?;
+        // Safety: the caller makes sure the file doesn't change while it's mapped.
+        let map = unsafe { Mmap::map(&file) }?;
+        Document::load_mem(&map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Document;
+
+    #[test]
+    fn load_mmap_matches_load() {
+        let path = "assets/AnnotationDemo.pdf";
+        let mapped = unsafe { Document::load_mmap(path) }.unwrap();
+        let loaded = Document::load_mem(&std::fs::read(path).unwrap()).unwrap();
+
+        assert_eq!(mapped.objects, loaded.objects);
+        assert_eq!(mapped.trailer, loaded.trailer);
+        assert_eq!(mapped.version, loaded.version);
+    }
+
+    #[test]
+    fn load_mmap_fails_on_missing_and_empty_files() {
+        assert!(unsafe { Document::load_mmap("assets/missing.pdf") }.is_err());
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        assert!(unsafe { Document::load_mmap(empty.path()) }.is_err());
+    }
+}
@@ -1,239 +1,726 @@
 mod encrypted;
+mod index;
+mod lazy;
 mod load;
 mod metadata;
+#[cfg(feature = "mmap")]
+#[allow(unsafe_code)]
+mod mmap;
 mod object_loader;
+mod pages;
+mod preview;
+mod recovery;
+mod revisions;
+mod seekable;
 
 #[cfg(test)]
 mod tests;
 
 use log::{error, warn};
+use memchr::memmem;
 use std::cmp;
 use std::collections::{BTreeMap, HashSet};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, OnceLock};
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
-use crate::encryption::EncryptionState;
+use crate::encryption::{self, EncryptionState, Password};
 use crate::error::{ParseError, XrefError};
 use crate::object_stream::ObjectStream;
 use crate::parser::{self, ParserInput};
-use crate::xref::XrefEntry;
-use crate::{Document, Error, Object, Result};
+use crate::progress::{self, Phase, ProgressCounter};
+use crate::xref::{Xref, XrefEntry};
+use crate::{Dictionary, Document, Error, LoadWarning, Object, ObjectId, ReaderOptions, Result};
+
+pub use lazy::LazyDocument;
+pub use metadata::{EncryptionInfo, MetadataOptions, PageDimensions, PdfMetadata};
+use object_loader::{next_bound, object_bounds};
+
+/// A cross-reference section: its offset, its table and its trailer.
+type XrefSection = (usize, Xref, Dictionary);
+
+/// A filter applied to each object as it's loaded, including the objects of object streams. It
+/// may change the object, and leaves it out of the document by returning `None`. Objects are
+/// loaded in parallel with the `rayon` feature, hence `Sync`.
+pub type LoadFilter<'a> = dyn Fn((u32, u16), &mut Object) -> Option<((u32, u16), Object)> + Sync + 'a;
+
+/// Where an object handed to an [`ObjectFilter`] was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectOrigin {
+    /// An indirect object at the offset the cross-reference table lists.
+    TopLevel,
+    /// A member of the object stream with the given ID.
+    ObjectStream(ObjectId),
+    /// An object found by scanning the file, as the cross-reference table was rebuilt or pointed
+    /// elsewhere.
+    Reconstructed,
+}
+
+/// What an [`ObjectFilter`] is told about the object it's handed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterContext {
+    /// The ID the object was read with.
+    pub id: ObjectId,
+    pub origin: ObjectOrigin,
+    /// The byte offset of the object in the file, or of its object stream for the members of one.
+    pub offset: u64,
+}
+
+/// A filter applied to each object as it's loaded, like [`LoadFilter`] but told where the object
+/// comes from. It runs for every object that ends up in the document: members of object streams
+/// whether or not the filter keeps their stream, and objects of encrypted files once decrypted.
+pub type ObjectFilter<'a> = dyn Fn(&FilterContext, &mut Object) -> Option<(ObjectId, Object)> + Sync + 'a;
+
+/// A filter applied to the trailer of the loaded document.
+pub type TrailerFilter<'a> = dyn Fn(&mut Dictionary) + Sync + 'a;
 
-pub use metadata::PdfMetadata;
+/// The filters to load a document with, see [`Reader::read_with_filters`].
+#[derive(Clone, Copy, Default)]
+pub struct LoadFilters<'a> {
+    pub object: Option<&'a ObjectFilter<'a>>,
+    pub trailer: Option<&'a TrailerFilter<'a>>,
+}
+
+/// Call `read` with the filters of a [`LoadFilter`].
+fn with_load_filter<T>(filter_func: Option<&LoadFilter<'_>>, read: impl FnOnce(LoadFilters<'_>) -> T) -> T {
+    let object = filter_func.map(|filter_func| move |context: &FilterContext, object: &mut Object| {
+        filter_func(context.id, object)
+    });
+    read(LoadFilters {
+        object: object.as_ref().map(|object| object as &ObjectFilter<'_>),
+        trailer: None,
+    })
+}
 
-pub(crate) type FilterFunc = fn((u32, u16), &mut Object) -> Option<((u32, u16), Object)>;
+/// The function pointer load filters used to be, see [`LoadFilter`].
+#[deprecated(note = "use LoadFilter, which closures capturing state can be")]
+pub type FilterFunc = fn((u32, u16), &mut Object) -> Option<((u32, u16), Object)>;
 
 pub struct Reader<'a> {
     pub buffer: &'a [u8],
     pub document: Document,
     pub encryption_state: Option<EncryptionState>,
-    pub password: Option<String>, // Password for encrypted PDFs
+    pub password: Option<Password>, // Password for encrypted PDFs
+    pub options: ReaderOptions,
+    /// The object streams parsed to resolve compressed objects, by container object number, so
+    /// each is decoded once
+    pub(crate) object_streams: Mutex<BTreeMap<u32, Arc<ObjectStream>>>,
+    /// The object headers of the whole file, scanned on the first object the cross-reference
+    /// table lists at a wrong offset
+    pub(crate) object_headers: OnceLock<Xref>,
 }
 
 /// Maximum allowed embedding of literal strings.
 pub const MAX_BRACKET: usize = 100;
 
+impl<'a> Reader<'a> {
+    /// A reader of the PDF file in `buffer`, parsing it with `options`. Give it a password with
+    /// [`Reader::with_password`] to read an encrypted file.
+    pub fn new(buffer: &'a [u8], options: ReaderOptions) -> Self {
+        Reader {
+            buffer,
+            document: Document::new(),
+            encryption_state: None,
+            password: None,
+            options,
+            object_streams: Default::default(),
+            object_headers: OnceLock::new(),
+        }
+    }
+
+    /// Decrypt the file with `password`, a `&str` or `String` as before, or the bytes a
+    /// [`Password`] holds.
+    pub fn with_password(mut self, password: impl Into<Password>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+}
+
 impl Reader<'_> {
     /// Read whole document.
-    pub fn read(mut self, filter_func: Option<FilterFunc>) -> Result<Document> {
-        let offset = self.buffer.windows(5).position(|w| w == b"%PDF-").unwrap_or(0);
+    pub fn read(self, filter_func: Option<&LoadFilter<'_>>) -> Result<Document> {
+        with_load_filter(filter_func, |filters| self.read_internal(filters, false))
+    }
+
+    /// Read whole document, passing its objects and trailer through `filters`. Fails with
+    /// [`Error::MissingCatalog`] if they leave the document without its catalog.
+    pub fn read_with_filters(self, filters: LoadFilters<'_>) -> Result<Document> {
+        self.read_internal(filters, false)
+    }
+
+    /// Read whole document, filtering objects with function pointer `filter_func`.
+    #[deprecated(note = "use Reader::read, which takes closures as well")]
+    #[allow(deprecated)]
+    pub fn read_with_filter_func(self, filter_func: Option<FilterFunc>) -> Result<Document> {
+        self.read(filter_func.as_ref().map(|filter_func| filter_func as &LoadFilter<'_>))
+    }
+
+    /// Read whole document, rebuilding the cross-reference table from the `N G obj` headers in the
+    /// file if it can't be read or doesn't lead to the catalog, e.g. because /startxref points into
+    /// the middle of a stream.
+    pub fn read_with_recovery(self, filter_func: Option<&LoadFilter<'_>>) -> Result<Document> {
+        with_load_filter(filter_func, |filters| self.read_internal(filters, true))
+    }
+
+    fn read_internal(mut self, filters: LoadFilters<'_>, recover: bool) -> Result<Document> {
+        let version = self.read_header()?;
+
+        // Counted from here, past the comments of the header.
+        #[cfg(feature = "census")]
+        let counting = crate::census::Counting::start();
+        let xref_sections = self.read_xref_sections();
+        let usable = |(xref, trailer, _): &(Xref, Dictionary, _)| self.root_is_readable(xref, trailer);
+        // A file cut off before its last `startxref` may have no table to read at all.
+        let missing = !self.options.strict && matches!(xref_sections, Err(Error::Xref(XrefError::Start)));
+        let rebuilt = if missing {
+            warn!("no cross-reference table found, rebuilding it from the object headers");
+            self.rebuild_xref()
+        } else if recover && !xref_sections.as_ref().is_ok_and(usable) {
+            warn!("the cross-reference table is unusable, rebuilding it from the object headers");
+            self.rebuild_xref()
+        } else {
+            None
+        };
+        let reconstructed = rebuilt.is_some();
+        let (xref, trailer, sections) = match rebuilt {
+            Some((xref, trailer)) => {
+                self.document.xref_start = self.buffer.len();
+                let sections = vec![(self.buffer.len(), xref.clone(), trailer.clone())];
+                (xref, trailer, sections)
+            }
+            None => xref_sections?,
+        };
+
+        progress::check_cancelled(self.options.progress.as_ref())?;
+        self.document.version = version;
+        self.document.max_id = xref.size - 1;
+        self.document.trailer = trailer;
+        self.document.reference_table = xref;
+
+        // Check if encrypted
+        let is_encrypted = self.document.trailer.get(b"Encrypt").is_ok();
+
+        if is_encrypted {
+            // For encrypted PDFs, use a special loading strategy
+            self.load_encrypted_document(filters.object, reconstructed)?;
+        } else {
+            // For non-encrypted PDFs, use the normal loading
+            self.load_objects_raw(filters.object, reconstructed)?;
+        }
+        if let Some(trailer_filter) = filters.trailer {
+            trailer_filter(&mut self.document.trailer);
+        }
+        // A document left encrypted has only its encryption dictionary to filter.
+        let locked = self.document.trailer.has(b"Encrypt");
+        if (filters.object.is_some() || filters.trailer.is_some()) && !locked && self.document.catalog().is_err() {
+            return Err(Error::MissingCatalog);
+        }
+        if self.options.strict {
+            if let Some(warning) = self.document.load_warnings.first() {
+                return Err(Error::Strict(warning.clone()));
+            }
+        }
+        self.record_revisions(sections);
+        self.document.repairs = self.document.repair_page_tree();
+
+        #[cfg(feature = "census")]
+        {
+            let survey = crate::Census::survey(&self.document);
+            self.document.census.merge(&survey);
+            self.document.census.add_parse_counts(counting.finish());
+        }
+        Ok(self.document)
+    }
+
+    /// Read the header of the file, skipping any garbage before it, and its binary mark. Returns the
+    /// version.
+    fn read_header(&mut self) -> Result<String> {
+        let offset = memmem::find(self.buffer, b"%PDF-").unwrap_or(0);
         self.buffer = &self.buffer[offset..];
 
         // The document structure can be expressed in PEG as:
         //   document <- header indirect_object* xref trailer xref_start
         let version =
-            parser::header(ParserInput::new_extra(self.buffer, "header")).ok_or(ParseError::InvalidFileHeader)?;
+            parser::header(parser::new_input(self.buffer)).ok_or(ParseError::InvalidFileHeader)?;
 
         //The binary_mark is in line 2 after the pdf version. If at other line number, then will be declared as invalid pdf.
         if let Some(pos) = self.buffer.iter().position(|&byte| byte == b'\n') {
             if let Some(binary_mark) =
-                parser::binary_mark(ParserInput::new_extra(&self.buffer[pos + 1..], "binary_mark"))
+                parser::binary_mark(parser::new_input(&self.buffer[pos + 1..]))
             {
                 if binary_mark.iter().all(|&byte| byte >= 128) {
                     self.document.binary_mark = binary_mark;
                 }
             }
         }
+        Ok(version)
+    }
 
-        let xref_start = Self::get_xref_start(self.buffer)?;
-        if xref_start > self.buffer.len() {
-            return Err(Error::Xref(XrefError::Start));
+    /// Read the cross-reference sections of the file, from the last one back through /Prev, or take
+    /// them from the index of the reader options. Returns the merged table, the trailer of the last
+    /// section and all the sections.
+    fn read_xref_sections(&mut self) -> Result<(Xref, Dictionary, Vec<XrefSection>)> {
+        if let Some(sections) = self.indexed_xref_sections() {
+            return sections;
         }
+        let (xref_start, mut xref, mut trailer) = self.read_last_xref()?;
         self.document.xref_start = xref_start;
-
-        let (mut xref, mut trailer) =
-            parser::xref_and_trailer(ParserInput::new_extra(&self.buffer[xref_start..], "xref"), &self)?;
+        if let Some(stream_start) = trailer.remove(b"XRefStm") {
+            #[cfg(feature = "census")]
+            {
+                self.document.census.hybrid_xrefs += 1;
+            }
+            self.merge_hybrid_xref_stream(&mut xref, &stream_start)?;
+        }
 
         // Read previous Xrefs of linearized or incremental updated document.
+        let mut sections = vec![(xref_start, xref.clone(), trailer.clone())];
+        let progress = self.options.progress.clone();
+        progress::report(progress.as_ref(), Phase::ParseXref, 1, None);
         let mut already_seen = HashSet::new();
         let mut prev_xref_start = trailer.remove(b"Prev");
         while let Some(prev) = prev_xref_start.and_then(|offset| offset.as_i64().ok()) {
             if already_seen.contains(&prev) {
                 break;
             }
+            if let Some(max) = self.options.max_xref_sections {
+                if sections.len() >= max {
+                    return Err(Error::Xref(XrefError::TooManySections(max)));
+                }
+            }
             already_seen.insert(prev);
             if prev < 0 || prev as usize > self.buffer.len() {
                 return Err(Error::Xref(XrefError::PrevStart));
             }
 
-            let (prev_xref, prev_trailer) =
-                parser::xref_and_trailer(ParserInput::new_extra(&self.buffer[prev as usize..], ""), &self)?;
-            xref.merge(prev_xref);
-
-            // Read xref stream in hybrid-reference file
-            let prev_xref_stream_start = trailer.remove(b"XRefStm");
-            if let Some(prev) = prev_xref_stream_start.and_then(|offset| offset.as_i64().ok()) {
-                if prev < 0 || prev as usize > self.buffer.len() {
-                    return Err(Error::Xref(XrefError::StreamStart));
+            let (mut prev_xref, prev_trailer) =
+                parser::xref_and_trailer(self.input(&self.buffer[prev as usize..]), self)?;
+            if let Ok(stream_start) = prev_trailer.get(b"XRefStm") {
+                #[cfg(feature = "census")]
+                {
+                    self.document.census.hybrid_xrefs += 1;
                 }
-
-                let (prev_xref, _) =
-                    parser::xref_and_trailer(ParserInput::new_extra(&self.buffer[prev as usize..], ""), &self)?;
-                xref.merge(prev_xref);
+                self.merge_hybrid_xref_stream(&mut prev_xref, stream_start)?;
             }
+            sections.push((prev as usize, prev_xref.clone(), prev_trailer.clone()));
+            progress::report(progress.as_ref(), Phase::ParseXref, sections.len() as u64, None);
+            xref.merge(prev_xref);
 
             prev_xref_start = prev_trailer.get(b"Prev").cloned().ok();
         }
-        let xref_entry_count = xref.max_id().checked_add(1).ok_or(ParseError::InvalidXref)?;
-        if xref.size != xref_entry_count {
-            warn!(
-                "Size entry of trailer dictionary is {}, correct value is {}.",
-                xref.size, xref_entry_count
-            );
-            xref.size = xref_entry_count;
-        }
-
-        self.document.version = version;
-        self.document.max_id = xref.size - 1;
-        self.document.trailer = trailer;
-        self.document.reference_table = xref;
-
-        // Check if encrypted
-        let is_encrypted = self.document.trailer.get(b"Encrypt").is_ok();
+        self.check_xref_size(&mut xref, xref_start)?;
+        Ok((xref, trailer, sections))
+    }
 
-        if is_encrypted {
-            // For encrypted PDFs, use a special loading strategy
-            self.load_encrypted_document(filter_func)?;
-        } else {
-            // For non-encrypted PDFs, use the normal loading
-            self.load_objects_raw(filter_func)?;
+    /// Merge the cross-reference stream a hybrid-reference file's section points at with its
+    /// /XRefStm entry into the section's table. The table's entries take precedence, except free
+    /// ones: writers mark the objects only the stream lists as free in the table. A stream that
+    /// can't be read is only an error when reading strictly; otherwise the table is used alone.
+    fn merge_hybrid_xref_stream(&mut self, xref: &mut Xref, stream_start: &Object) -> Result<()> {
+        if !self.options.follow_xref_stm {
+            return Ok(());
+        }
+        let Ok(start) = stream_start.as_i64() else {
+            return Ok(());
+        };
+        let stream_xref = match usize::try_from(start).ok().and_then(|start| self.buffer.get(start..)) {
+            Some(section) => parser::xref_and_trailer(self.input(section), self).map(|(stream_xref, _)| stream_xref),
+            None => Err(Error::Xref(XrefError::StreamStart)),
+        };
+        match stream_xref {
+            Ok(stream_xref) => xref.merge_hybrid_stream(stream_xref),
+            Err(error) if self.options.strict => return Err(error),
+            Err(error) => {
+                let warning = LoadWarning::UnreadableXrefStream {
+                    offset: start,
+                    reason: error.to_string(),
+                };
+                warn!("{warning}, using the cross-reference table alone");
+                self.document.load_warnings.push(warning);
+            }
         }
+        Ok(())
+    }
 
-        Ok(self.document)
+    /// Read the cross-reference section the last `startxref` of the file points at. If it can't be
+    /// read, try those the earlier ones point at, as a file cut off before its end may still hold
+    /// the `startxref` of a previous revision. Returns the offset of the section read with it.
+    fn read_last_xref(&mut self) -> Result<(usize, Xref, Dictionary)> {
+        let mut first_error: Option<(usize, Error)> = None;
+        let mut tried = HashSet::new();
+        let candidates = Self::xref_start_candidates(self.buffer).filter(|(_, start)| tried.insert(*start));
+        for (startxref, xref_start) in candidates {
+            let section = match self.buffer.get(xref_start..) {
+                Some(section) => parser::xref_and_trailer(self.input(section), self),
+                None => Err(Error::Xref(XrefError::Start)),
+            };
+            match section {
+                Ok((xref, trailer)) => {
+                    if let Some((offset, error)) = &first_error {
+                        let warning = LoadWarning::UnreadableXref {
+                            offset: *offset,
+                            reason: error.to_string(),
+                        };
+                        warn!("{warning}, using the one at offset {xref_start}");
+                        self.document.load_warnings.push(warning);
+                    }
+                    self.check_unfinished_update(startxref);
+                    return Ok((xref_start, xref, trailer));
+                }
+                Err(error) => {
+                    first_error.get_or_insert((xref_start, error));
+                }
+            }
+        }
+        Err(first_error.map_or(Error::Xref(XrefError::Start), |(_, error)| error))
     }
 
-    fn load_objects_raw(&mut self, filter_func: Option<FilterFunc>) -> Result<()> {
+    /// Load the objects the cross-reference table lists, decrypting them with the encryption state
+    /// if there is one and passing them through `filter`. `reconstructed` tells the table was
+    /// rebuilt by scanning the file.
+    fn load_objects_raw(&mut self, filter: Option<&ObjectFilter<'_>>, reconstructed: bool) -> Result<()> {
         let is_encrypted = self.document.trailer.get(b"Encrypt").is_ok();
+        let encrypt_ref = self.document.trailer.get(b"Encrypt").and_then(Object::as_reference).ok();
+        let origin = if reconstructed {
+            ObjectOrigin::Reconstructed
+        } else {
+            ObjectOrigin::TopLevel
+        };
         let zero_length_streams = Mutex::new(vec![]);
         let object_streams = Mutex::new(vec![]);
-
-        let entries_filter_map = |(_, entry): (&_, &_)| {
-            if let XrefEntry::Normal { offset, .. } = *entry {
-                // read_object now handles decryption internally
-                let result = self.read_object(offset as usize, None, &mut HashSet::new());
-                let (object_id, mut object) = match result {
+        // The unparsable object at the lowest offset, if they aren't skipped.
+        let first_failure: Mutex<Option<(u64, Error)>> = Mutex::new(None);
+        // With the offsets of the objects they're about, to keep them in file order.
+        let warnings: Mutex<Vec<(u64, LoadWarning)>> = Mutex::new(vec![]);
+
+        let bounds = object_bounds(&self.document.reference_table, self.document.xref_start);
+        let entry_count = self.document.reference_table.entries.len() as u64;
+        let parse_objects =
+            ProgressCounter::new(self.options.progress.as_ref(), Phase::ParseObjects, Some(entry_count));
+
+        let entries_filter_map = |(entry_id, entry): (&u32, &_)| {
+            if progress::cancelled(self.options.progress.as_ref()) {
+                return None;
+            }
+            if let XrefEntry::Normal { offset, generation } = *entry {
+                if let Some(object_id) = self.padding_object(offset as usize, next_bound(&bounds, offset as usize)) {
+                    warn!(
+                        "object {} {} at offset {offset} is padding without content, loaded as null",
+                        object_id.0, object_id.1
+                    );
+                    let mut object = Object::Null;
+                    if let Some(filter) = filter {
+                        filter(&FilterContext { id: object_id, origin, offset }, &mut object)?;
+                    }
+                    return Some((*entry_id, object_id, object));
+                }
+                let mut origin = origin;
+                let mut object_offset = offset as usize;
+                let mut result = self.read_object_checked(object_offset, None, &mut HashSet::new());
+                let expected_id = (*entry_id, generation);
+                let misplaced = match &result {
+                    Ok((object_id, _, _)) => *object_id != expected_id,
+                    Err(Error::Strict(_)) => false,
+                    Err(_) => true,
+                };
+                if misplaced {
+                    if let Some((found, object, length_mismatch)) = self.relocate_object(expected_id, object_offset) {
+                        let warning = LoadWarning::RelocatedObject {
+                            id: expected_id,
+                            listed: object_offset,
+                            found,
+                        };
+                        warn!("{warning}");
+                        warnings.lock().expect("warnings mutex poisoned").push((offset, warning));
+                        object_offset = found;
+                        origin = ObjectOrigin::Reconstructed;
+                        result = Ok((expected_id, object, length_mismatch));
+                    }
+                }
+                let (object_id, mut object, length_mismatch) = match result {
                     Ok(obj) => obj,
+                    Err(Error::Strict(warning)) => {
+                        warnings.lock().expect("warnings mutex poisoned").push((offset, warning));
+                        return None;
+                    }
                     Err(e) => {
+                        if !self.options.skip_unparsable_objects && !self.options.strict {
+                            let mut first_failure = first_failure.lock().expect("first_failure mutex poisoned");
+                            if first_failure.as_ref().is_none_or(|(first, _)| offset < *first) {
+                                *first_failure = Some((offset, e));
+                            }
+                            return None;
+                        }
+                        let warning = LoadWarning::SkippedObject {
+                            offset: offset as usize,
+                            id: (*entry_id, generation),
+                            reason: e.to_string(),
+                        };
                         // Log error but continue
                         if is_encrypted {
                             // Expected for some encrypted objects - but log which ones
-                            warn!("Skipping encrypted object at offset {}: {:?}", offset, e);
+                            warn!("{warning}");
                         } else {
-                            error!("Object load error at offset {}: {e:?}", offset);
+                            error!("{warning}");
                         }
+                        warnings.lock().expect("warnings mutex poisoned").push((offset, warning));
                         return None;
                     }
                 };
-                if let Some(filter_func) = filter_func {
-                    filter_func(object_id, &mut object)?;
-                }
-
-                if let Ok(ref mut stream) = object.as_stream_mut() {
-                    if stream.dict.has_type(b"ObjStm") && !is_encrypted {
-                        let obj_stream = ObjectStream::new(stream).ok()?;
-                        let mut object_streams = object_streams.lock().expect("object_streams mutex poisoned");
-                        if let Some(filter_func) = filter_func {
-                            let objects: BTreeMap<(u32, u16), Object> = obj_stream
-                                .objects
-                                .into_iter()
-                                .filter_map(|(object_id, mut object)| filter_func(object_id, &mut object))
-                                .collect();
-                            object_streams.extend(objects);
-                        } else {
-                            object_streams.extend(obj_stream.objects);
+                if object_id != (*entry_id, generation) {
+                    let warning = if object_id.0 == *entry_id {
+                        LoadWarning::GenerationMismatch {
+                            id: object_id,
+                            offset: offset as usize,
+                            generation,
+                        }
+                    } else {
+                        LoadWarning::ObjectIdMismatch {
+                            offset: offset as usize,
+                            expected: (*entry_id, generation),
+                            found: object_id,
+                        }
+                    };
+                    warn!("{warning}");
+                    warnings.lock().expect("warnings mutex poisoned").push((offset, warning));
+                }
+                if let Some(warning) = length_mismatch {
+                    warn!("{warning}");
+                    warnings.lock().expect("warnings mutex poisoned").push((offset, warning));
+                }
+                // The encryption dictionary isn't encrypted, and is left out of the document.
+                if Some(object_id) == encrypt_ref {
+                    return Some((*entry_id, object_id, object));
+                }
+                // Streams whose data is read once all objects are loaded are decrypted then.
+                let deferred = matches!(&object, Object::Stream(stream) if stream.start_position.is_some());
+                if let Some(state) = self.encryption_state.as_ref().filter(|_| !deferred) {
+                    if let Err(err) = encryption::decrypt_object(state, object_id, &mut object) {
+                        warn!("object {} {} can't be decrypted: {err}", object_id.0, object_id.1);
+                        let warning = LoadWarning::DecryptFailed(object_id);
+                        warnings.lock().expect("warnings mutex poisoned").push((offset, warning));
+                    }
+                }
+
+                // The members of an object stream are read before the stream is filtered, to be
+                // filtered on their own whether or not it's kept.
+                let mut members = None;
+                if let Ok(stream) = object.as_stream_mut() {
+                    if stream.dict.has_type(b"ObjStm") {
+                        match ObjectStream::with_limits(stream, self.options.parse_limits()) {
+                            Ok(obj_stream) => members = Some(obj_stream.objects),
+                            Err(err) => {
+                                let warning = LoadWarning::SkippedObjectStream {
+                                    id: object_id,
+                                    reason: err.to_string(),
+                                };
+                                error!("{warning}");
+                                warnings.lock().expect("warnings mutex poisoned").push((offset, warning));
+                            }
                         }
-                    } else if stream.content.is_empty() {
-                        let mut zero_length_streams =
-                            zero_length_streams.lock().expect("zero_length_streams mutex poisoned");
-                        zero_length_streams.push(object_id);
+                    }
+                }
+                let offset = object_offset as u64;
+                let keep = filter.is_none_or(|filter| {
+                    filter(&FilterContext { id: object_id, origin, offset }, &mut object).is_some()
+                });
+                if let Some(objects) = members {
+                    let objects = match filter {
+                        Some(filter) => {
+                            let origin = ObjectOrigin::ObjectStream(object_id);
+                            let filter_member =
+                                |(id, mut object)| filter(&FilterContext { id, origin, offset }, &mut object);
+                            objects.into_iter().filter_map(filter_member).collect()
+                        }
+                        None => objects,
+                    };
+                    object_streams.lock().expect("object_streams mutex poisoned").push((object_id, objects));
+                }
+                if !keep {
+                    return None;
+                }
+                if let Ok(stream) = object.as_stream() {
+                    if !stream.dict.has_type(b"ObjStm") && stream.content.is_empty() {
+                        zero_length_streams.lock().expect("zero_length_streams mutex poisoned").push(object_id);
                     }
                 }
 
-                Some((object_id, object))
+                Some((*entry_id, object_id, object))
             } else {
                 None
             }
         };
 
+        // What the parser counts on the threads parsing objects is added up for the census.
+        #[cfg(feature = "census")]
+        let parse_counts = Mutex::new(crate::census::ParseCounts::default());
+        #[cfg(feature = "census")]
+        let entries_filter_map = |entry| {
+            let counting = crate::census::Counting::start();
+            let parsed = entries_filter_map(entry);
+            parse_counts.lock().expect("parse_counts mutex poisoned").add(counting.finish());
+            parsed
+        };
+
+        // Both branches keep the order of the xref entries, so duplicates resolve the same way
+        // regardless of thread scheduling.
         #[cfg(feature = "rayon")]
-        {
-            self.document.objects = self
-                .document
+        let parsed: Vec<_> = parse_objects.sampled(|| {
+            self.document
                 .reference_table
                 .entries
                 .par_iter()
+                .inspect(|_| {
+                    parse_objects.count();
+                })
                 .filter_map(entries_filter_map)
-                .collect();
-        }
+                .collect()
+        });
         #[cfg(not(feature = "rayon"))]
-        {
-            self.document.objects = self
-                .document
-                .reference_table
-                .entries
-                .iter()
-                .filter_map(entries_filter_map)
-                .collect();
+        let parsed: Vec<_> = self
+            .document
+            .reference_table
+            .entries
+            .iter()
+            .inspect(|_| parse_objects.tick())
+            .filter_map(entries_filter_map)
+            .collect();
+        parse_objects.finish();
+        progress::check_cancelled(self.options.progress.as_ref())?;
+        if let Some((_, err)) = first_failure.into_inner().expect("first_failure mutex poisoned") {
+            return Err(err);
         }
+        #[cfg(feature = "census")]
+        self.document
+            .census
+            .add_parse_counts(parse_counts.into_inner().expect("parse_counts mutex poisoned"));
+        let mut warnings = warnings.into_inner().expect("warnings mutex poisoned");
+        warnings.sort_by_key(|(offset, _)| *offset);
+        self.document.load_warnings.extend(warnings.into_iter().map(|(_, warning)| warning));
+
+        // An object whose header matches the xref entry it was read from is authoritative. One
+        // found at the offset of another entry only fills a gap.
+        let mut objects = BTreeMap::new();
+        for (entry_id, object_id, object) in parsed {
+            if object_id.0 == entry_id {
+                objects.insert(object_id, object);
+            } else {
+                objects.entry(object_id).or_insert(object);
+            }
+        }
+        self.document.objects = objects;
 
         // Per PDF spec, first definition wins for duplicate object IDs.
         // See https://github.com/J-F-Liu/lopdf/issues/160
-        for (id, entry) in object_streams.into_inner().expect("object_streams mutex poisoned") {
-            self.document.objects.entry(id).or_insert(entry);
+        // Among object streams, the container named by the xref table wins, then the one with the
+        // lowest object number; streams are pushed in whatever order the threads finish.
+        let mut object_streams = object_streams.into_inner().expect("object_streams mutex poisoned");
+        object_streams.sort_by_key(|(container_id, _)| *container_id);
+        let expand = ProgressCounter::new(
+            self.options.progress.as_ref(),
+            Phase::ExpandObjectStreams,
+            Some(object_streams.len() as u64),
+        );
+        let entries = &self.document.reference_table.entries;
+        let is_listed_in = |id: &ObjectId, container_id: &ObjectId| {
+            matches!(entries.get(&id.0), Some(XrefEntry::Compressed { container, .. }) if *container == container_id.0)
+        };
+        let (listed, unlisted): (Vec<_>, Vec<_>) = object_streams
+            .into_iter()
+            .inspect(|_| expand.tick())
+            .flat_map(|(container_id, objects)| objects.into_iter().map(move |(id, object)| (container_id, id, object)))
+            .partition(|(container_id, id, _)| is_listed_in(id, container_id));
+        expand.finish();
+        progress::check_cancelled(self.options.progress.as_ref())?;
+        for (_, id, object) in listed.into_iter().chain(unlisted) {
+            self.document.objects.entry(id).or_insert(object);
         }
 
-        for object_id in zero_length_streams.into_inner().expect("zero_length_streams mutex poisoned") {
+        let zero_length_streams = zero_length_streams.into_inner().expect("zero_length_streams mutex poisoned");
+        let progress = self.options.progress.clone();
+        let read_streams =
+            ProgressCounter::new(progress.as_ref(), Phase::ReadStreams, Some(zero_length_streams.len() as u64));
+        for object_id in zero_length_streams {
+            read_streams.tick();
             let _ = self.read_stream_content(object_id);
         }
+        read_streams.finish();
+
+        progress::check_cancelled(progress.as_ref())
+    }
 
+    /// Correct the /Size of `xref` to cover all its entries. Padding objects numbered beyond /Size,
+    /// as some linearizers leave behind, don't make /Size incorrect.
+    fn check_xref_size(&mut self, xref: &mut Xref, xref_start: usize) -> Result<()> {
+        let xref_entry_count = xref.max_id().checked_add(1).ok_or(ParseError::InvalidXref)?;
+        if xref.size != xref_entry_count {
+            let bounds = object_bounds(xref, xref_start);
+            let only_padding_beyond_size = xref.size < xref_entry_count
+                && xref.entries.range(xref.size..).all(|(_, entry)| match *entry {
+                    XrefEntry::Normal { offset, .. } => {
+                        self.padding_object(offset as usize, next_bound(&bounds, offset as usize)).is_some()
+                    }
+                    _ => false,
+                });
+            if !only_padding_beyond_size {
+                let warning = LoadWarning::TrailerSizeMismatch {
+                    declared: xref.size,
+                    actual: xref_entry_count,
+                };
+                warn!("{warning}");
+                self.document.load_warnings.push(warning);
+            }
+            xref.size = xref_entry_count;
+        }
         Ok(())
     }
 
     fn get_xref_start(buffer: &[u8]) -> Result<usize> {
+        Self::last_startxref(buffer)
+            .and_then(|xref_pos| parser::xref_start(parser::new_input(&buffer[xref_pos..])))
+            .map(|startxref| startxref as usize)
+            .ok_or(Error::Xref(XrefError::Start))
+    }
+
+    /// The offset of the `startxref` keyword in front of the `%%EOF` in the last 512 bytes of the
+    /// file.
+    fn last_startxref(buffer: &[u8]) -> Option<usize> {
         let seek_pos = buffer.len() - cmp::min(buffer.len(), 512);
         Self::search_substring(buffer, b"%%EOF", seek_pos)
             .and_then(|eof_pos| if eof_pos > 25 { Some(eof_pos) } else { None })
             .and_then(|eof_pos| Self::search_substring(buffer, b"startxref", eof_pos - 25))
-            .ok_or(Error::Xref(XrefError::Start))
-            .and_then(|xref_pos| {
-                if xref_pos <= buffer.len() {
-                    match parser::xref_start(ParserInput::new_extra(&buffer[xref_pos..], "xref")) {
-                        Some(startxref) => Ok(startxref as usize),
-                        None => Err(Error::Xref(XrefError::Start)),
-                    }
-                } else {
-                    Err(Error::Xref(XrefError::Start))
+    }
+
+    /// The `startxref` keywords of the file with the offsets they point at, from the last one
+    /// back. The last is found as [`Reader::get_xref_start`] does, the others by scanning the whole
+    /// file.
+    fn xref_start_candidates(buffer: &[u8]) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let read = |pos: usize| {
+            let xref_start = parser::xref_start(parser::new_input(&buffer[pos..]))?;
+            Some((pos, usize::try_from(xref_start).ok()?))
+        };
+        let mut end = buffer.len();
+        let earlier = std::iter::from_fn(move || {
+            while let Some(pos) = memmem::rfind(&buffer[..end], b"startxref") {
+                end = pos;
+                if let Some(candidate) = read(pos) {
+                    return Some(candidate);
                 }
-            })
+            }
+            None
+        });
+        Self::last_startxref(buffer).and_then(read).into_iter().chain(earlier)
+    }
+
+    /// Parser input over `buffer`, with the limits of the reader options.
+    pub(crate) fn input<'b>(&self, buffer: &'b [u8]) -> ParserInput<'b> {
+        ParserInput::new_extra(buffer, self.options.parse_limits())
     }
 
+    /// The offset of the last `pattern` in `buffer` starting at or after `start_pos`. Like
+    /// [`str::rfind`], an empty pattern is found at the end.
     pub(crate) fn search_substring(buffer: &[u8], pattern: &[u8], start_pos: usize) -> Option<usize> {
-        buffer
-            .get(start_pos..)?
-            .windows(pattern.len())
-            .rposition(|window| window == pattern)
-            .map(|pos| start_pos + pos)
+        memmem::rfind(buffer.get(start_pos..)?, pattern).map(|pos| start_pos + pos)
     }
 }
@@ -1,12 +1,13 @@
 use log::{error, warn};
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use super::Reader;
 use crate::encryption;
 use crate::object_stream::ObjectStream;
-use crate::parser::{self, ParserInput};
-use crate::xref::XrefEntry;
-use crate::{Error, Object, ObjectId, Result};
+use crate::parser;
+use crate::xref::{Xref, XrefEntry};
+use crate::{Error, LoadWarning, Object, ObjectId, Result};
 
 impl Reader<'_> {
     pub fn get_object(&self, id: ObjectId, already_seen: &mut HashSet<ObjectId>) -> Result<Object> {
@@ -23,7 +24,23 @@ pub fn get_object(&self, id: ObjectId, already_seen: &mut HashSet<ObjectId>) ->
         }
 
         let offset = self.get_offset(id)?;
-        let (_, mut obj) = self.read_object(offset as usize, Some(id), already_seen)?;
+        let mut result = self.read_object(offset as usize, Some(id), already_seen);
+        if matches!(result, Err(Error::ObjectIdMismatch | Error::IndirectObject { .. })) {
+            if let Some((found, object, _)) = self.relocate_object(id, offset as usize) {
+                warn!("{}", LoadWarning::RelocatedObject { id, listed: offset as usize, found });
+                result = Ok((id, object));
+            }
+        }
+        // Padding either fails to parse or, followed by another object, reads as the number of
+        // that object.
+        if matches!(result, Err(_) | Ok((_, Object::Integer(_)))) {
+            let next_offset =
+                next_object_offset(&self.document.reference_table, self.document.xref_start, offset as usize);
+            if self.padding_object(offset as usize, next_offset) == Some(id) {
+                return Ok(Object::Null);
+            }
+        }
+        let (_, mut obj) = result?;
 
         if let Some(ref state) = self.encryption_state {
             let encrypt_ref = self
@@ -42,11 +59,16 @@ pub fn get_object(&self, id: ObjectId, already_seen: &mut HashSet<ObjectId>) ->
         Ok(obj)
     }
 
-    /// Get object offset by object ID.
-    pub(super) fn get_offset(&self, id: ObjectId) -> Result<u32> {
+    /// Get object offset by object ID. Unless reading strictly, an entry listing the object
+    /// number with another generation is used too.
+    pub(super) fn get_offset(&self, id: ObjectId) -> Result<u64> {
         let entry = self.document.reference_table.get(id.0).ok_or(Error::MissingXrefEntry)?;
         match *entry {
             XrefEntry::Normal { offset, generation } if generation == id.1 => Ok(offset),
+            XrefEntry::Normal { offset, generation } if !self.options.strict => {
+                warn!("{}", LoadWarning::GenerationMismatch { id, offset: offset as usize, generation });
+                Ok(offset)
+            }
             _ => Err(Error::MissingXrefEntry),
         }
     }
@@ -60,24 +82,42 @@ pub(super) fn get_compressed_object(&self, id: ObjectId) -> Result<Object> {
             _ => return Err(Error::MissingXrefEntry),
         };
 
-        let container_id = (container_id, 0);
-        let mut already_seen = HashSet::new();
-        let container_obj = self.get_object(container_id, &mut already_seen)?;
-        let mut container_stream = container_obj.as_stream()?.clone();
-        let object_stream = ObjectStream::new(&mut container_stream)?;
+        let object_stream = self.object_stream(container_id)?;
         object_stream.objects.get(&id).cloned().ok_or(Error::MissingXrefEntry)
     }
 
+    /// The object stream `container`, parsed on first use and cached for the lookups after.
+    fn object_stream(&self, container: u32) -> Result<Arc<ObjectStream>> {
+        let cached = self.object_streams.lock().expect("object stream cache poisoned").get(&container).cloned();
+        if let Some(object_stream) = cached {
+            return Ok(object_stream);
+        }
+        // Parsed without holding the lock, a stream parsed twice concurrently is cached once.
+        let mut container_obj = self.get_object((container, 0), &mut HashSet::new())?;
+        let object_stream = ObjectStream::with_limits(container_obj.as_stream_mut()?, self.options.parse_limits())?;
+        let mut object_streams = self.object_streams.lock().expect("object stream cache poisoned");
+        Ok(object_streams.entry(container).or_insert(Arc::new(object_stream)).clone())
+    }
+
     pub(super) fn read_object(
         &self, offset: usize, expected_id: Option<ObjectId>, already_seen: &mut HashSet<ObjectId>,
     ) -> Result<(ObjectId, Object)> {
+        let (id, object, _) = self.read_object_checked(offset, expected_id, already_seen)?;
+        Ok((id, object))
+    }
+
+    /// Like [`Reader::read_object`], with the warning for a stream whose /Length isn't the length
+    /// of its data, which ends where `endstream` is.
+    pub(super) fn read_object_checked(
+        &self, offset: usize, expected_id: Option<ObjectId>, already_seen: &mut HashSet<ObjectId>,
+    ) -> Result<(ObjectId, Object, Option<LoadWarning>)> {
         if offset > self.buffer.len() {
             return Err(Error::InvalidOffset(offset));
         }
 
         // Just parse without decryption - we'll decrypt later
         parser::indirect_object(
-            ParserInput::new_extra(self.buffer, "indirect object"),
+            self.input(self.buffer),
             offset,
             expected_id,
             self,
@@ -85,6 +125,13 @@ pub(super) fn read_object(
         )
     }
 
+    /// Recognize the object at `offset` as padding: an object header followed by nothing but
+    /// white-space and comments up to `end`, the offset of the next object. Returns its id.
+    pub(super) fn padding_object(&self, offset: usize, end: usize) -> Option<ObjectId> {
+        let span = self.buffer.get(offset..end.min(self.buffer.len()))?;
+        parser::padding_object(parser::new_input(span))
+    }
+
     pub(super) fn read_stream_content(&mut self, object_id: ObjectId) -> Result<()> {
         let length = self.get_stream_length(object_id)?;
         let stream = self
@@ -99,14 +146,50 @@ pub(super) fn read_stream_content(&mut self, object_id: ObjectId) -> Result<()>
             return Err(Error::InvalidStream("negative stream length.".to_string()));
         }
 
+        let declared = length;
         let length = usize::try_from(length).map_err(|e| Error::NumericCast(e.to_string()))?;
         let end = start + length;
 
-        if end > self.buffer.len() {
-            return Err(Error::InvalidStream("stream extends after document end.".to_string()));
+        let data_end = self.buffer.get(end..).unwrap_or_default();
+        let after = &data_end[data_end.iter().position(|&c| !parser::is_whitespace(c)).unwrap_or(data_end.len())..];
+        if after.starts_with(b"endstream") {
+            stream.set_content(self.buffer[start..end].to_vec());
+            return self.decrypt_stream_content(object_id);
         }
 
-        stream.set_content(self.buffer[start..end].to_vec());
+        // The /Length is wrong, the data ends where `endstream` is.
+        let actual = parser::nearest_stream_end(self.buffer.get(start..).unwrap_or_default(), length);
+        match actual {
+            Some(actual) => stream.set_content(self.buffer[start..start + actual].to_vec()),
+            None if end <= self.buffer.len() => stream.set_content(self.buffer[start..end].to_vec()),
+            None => {}
+        }
+        let warning = LoadWarning::StreamLengthMismatch {
+            id: object_id,
+            offset: self.get_offset(object_id).map_or(start, |offset| offset as usize),
+            declared,
+            actual,
+        };
+        warn!("{warning}");
+        self.document.load_warnings.push(warning);
+        self.decrypt_stream_content(object_id)
+    }
+
+    /// Decrypt the data of the stream `object_id` read by [`Reader::read_stream_content`], unless
+    /// the file isn't encrypted or the stream is its encryption dictionary.
+    fn decrypt_stream_content(&mut self, object_id: ObjectId) -> Result<()> {
+        let Some(state) = &self.encryption_state else {
+            return Ok(());
+        };
+        let encrypt_ref = self.document.trailer.get(b"Encrypt").and_then(Object::as_reference).ok();
+        if encrypt_ref == Some(object_id) {
+            return Ok(());
+        }
+        let object = self.document.get_object_mut(object_id)?;
+        if let Err(err) = encryption::decrypt_object(state, object_id, object) {
+            warn!("object {} {} can't be decrypted: {err}", object_id.0, object_id.1);
+            self.document.load_warnings.push(LoadWarning::DecryptFailed(object_id));
+        }
         Ok(())
     }
 
@@ -126,3 +209,41 @@ fn get_stream_length(&self, object_id: ObjectId) -> Result<i64> {
             })
     }
 }
+
+/// The sorted offsets objects of `xref` and the cross-reference section at `xref_start` start at.
+pub(super) fn object_bounds(xref: &Xref, xref_start: usize) -> Vec<usize> {
+    let mut bounds: Vec<usize> = xref
+        .entries
+        .values()
+        .filter_map(|entry| match *entry {
+            XrefEntry::Normal { offset, .. } => Some(offset as usize),
+            _ => None,
+        })
+        .chain([xref_start])
+        .collect();
+    bounds.sort_unstable();
+    bounds
+}
+
+/// Get the first of the sorted `bounds` after `offset`, or `usize::MAX` if there is none.
+pub(super) fn next_bound(bounds: &[usize], offset: usize) -> usize {
+    bounds
+        .get(bounds.partition_point(|&bound| bound <= offset))
+        .copied()
+        .unwrap_or(usize::MAX)
+}
+
+/// Get the offset of the object or cross-reference section following `offset`, or `usize::MAX`
+/// if there is none.
+pub(super) fn next_object_offset(xref: &Xref, xref_start: usize, offset: usize) -> usize {
+    xref.entries
+        .values()
+        .filter_map(|entry| match *entry {
+            XrefEntry::Normal { offset, .. } => Some(offset as usize),
+            _ => None,
+        })
+        .chain([xref_start])
+        .filter(|&next| next > offset)
+        .min()
+        .unwrap_or(usize::MAX)
+}
@@ -0,0 +1,84 @@
+use super::Reader;
+use super::preview::{INHERITABLE_KEYS, MAX_PAGE_TREE_DEPTH};
+use crate::xref::Xref;
+use crate::{Dictionary, Document, Object, ObjectId, Result};
+
+/// Trailer entries kept in a document of some of the pages of a file.
+const KEPT_TRAILER_KEYS: [&[u8]; 2] = [b"Root", b"ID"];
+
+impl Reader<'_> {
+    /// Load the pages numbered `page_numbers`, starting from 1, with the objects they use, but
+    /// no other objects of the file. The pages are attached to the root of the page tree in
+    /// document order, with the attributes they inherit copied into them, and the catalog keeps
+    /// only its /Type and /Pages.
+    pub fn read_pages(mut self, page_numbers: &[u32]) -> Result<Document> {
+        self.document.version = self.read_trailer_lazily()?;
+        let xref_type = self.document.reference_table.cross_reference_type;
+        self.document.max_id = self.document.reference_table.size.saturating_sub(1);
+
+        let mut page_numbers = page_numbers.to_vec();
+        page_numbers.sort_unstable();
+        page_numbers.dedup();
+        let mut page_ids = Vec::with_capacity(page_numbers.len());
+        for page_number in page_numbers {
+            page_ids.push(self.load_page_lazily(page_number)?);
+        }
+
+        let catalog_id = self.document.trailer.get(b"Root")?.as_reference()?;
+        let catalog = self.get_object(catalog_id, &mut Default::default())?;
+        let pages_id = catalog.as_dict()?.get(b"Pages")?.as_reference()?;
+        for &page_id in &page_ids {
+            let inherited = self.inherited_attributes(page_id);
+            let page = self.document.get_dictionary_mut(page_id)?;
+            for (key, value) in inherited {
+                if !page.has(&key) {
+                    page.set(key, value);
+                }
+            }
+            if page_id != pages_id {
+                page.set("Parent", pages_id);
+            }
+        }
+
+        // Page tree nodes below the root were only needed to find the pages.
+        self.document.objects.retain(|id, object| {
+            *id == pages_id || !object.as_dict().is_ok_and(|dict| dict.has_type(b"Pages"))
+        });
+        if !page_ids.contains(&pages_id) {
+            let kids: Vec<Object> = page_ids.iter().map(|&id| id.into()).collect();
+            let pages = dictionary! { "Type" => "Pages", "Count" => kids.len() as i64, "Kids" => kids };
+            self.document.objects.insert(pages_id, pages.into());
+        }
+        let catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+        self.document.objects.insert(catalog_id, catalog.into());
+
+        self.document.trailer = KEPT_TRAILER_KEYS
+            .iter()
+            .filter_map(|&key| Some((key.to_vec(), self.document.trailer.get(key).ok()?.clone())))
+            .collect::<Dictionary>();
+        self.document.reference_table = Xref::new(0, xref_type);
+        Ok(self.document)
+    }
+
+    /// The attributes the page `page_id` inherits from its ancestors, nearest first.
+    fn inherited_attributes(&self, page_id: ObjectId) -> Vec<(Vec<u8>, Object)> {
+        let mut inherited = Vec::new();
+        let mut node = self.document.get_dictionary(page_id).ok();
+        for _ in 0..MAX_PAGE_TREE_DEPTH {
+            let Some(parent) = node
+                .and_then(|node| node.get(b"Parent").ok())
+                .and_then(|parent| parent.as_reference().ok())
+                .and_then(|parent| self.document.get_dictionary(parent).ok())
+            else {
+                break;
+            };
+            for key in INHERITABLE_KEYS {
+                if let Ok(value) = parent.get(key) {
+                    inherited.push((key.to_vec(), value.clone()));
+                }
+            }
+            node = Some(parent);
+        }
+        inherited
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use super::Reader;
+use crate::{Error, Object, ObjectId, PreviewInfo, Result, Stream};
+
+/// Page attributes a page inherits from its ancestors in the page tree.
+pub(super) const INHERITABLE_KEYS: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Maximum depth of the page tree descended into.
+pub(super) const MAX_PAGE_TREE_DEPTH: usize = 64;
+
+impl Reader<'_> {
+    /// Read the preview information of a page, loading only the page, its ancestors and the
+    /// objects they use.
+    pub fn read_preview(mut self, page_number: u32) -> Result<PreviewInfo> {
+        self.read_trailer_lazily()?;
+        let page_id = self.load_page_lazily(page_number)?;
+        self.document.page_preview_info(page_id)
+    }
+
+    /// Descend the page tree to the page, using the /Count of page tree nodes to skip subtrees.
+    pub(super) fn load_page_lazily(&mut self, page_number: u32) -> Result<ObjectId> {
+        let catalog_id = self.document.trailer.get(b"Root")?.as_reference()?;
+        let catalog = self.get_object(catalog_id, &mut HashSet::new())?;
+        let mut node_id = catalog.as_dict()?.get(b"Pages")?.as_reference()?;
+        let mut remaining = page_number;
+        if remaining == 0 {
+            return Err(Error::PageNumberNotFound(page_number));
+        }
+
+        for _ in 0..MAX_PAGE_TREE_DEPTH {
+            let node = self.get_object(node_id, &mut HashSet::new())?;
+            let dict = node.as_dict()?.clone();
+            self.document.objects.insert(node_id, node);
+            let Ok(kids) = dict.get(b"Kids").and_then(Object::as_array) else {
+                if remaining != 1 {
+                    return Err(Error::PageNumberNotFound(page_number));
+                }
+                self.load_references(&Object::Dictionary(dict));
+                return Ok(node_id);
+            };
+            for key in INHERITABLE_KEYS {
+                if let Ok(value) = dict.get(key) {
+                    self.load_references(value);
+                }
+            }
+
+            let mut next = None;
+            for kid in kids {
+                let kid_id = kid.as_reference()?;
+                let kid = self.get_object(kid_id, &mut HashSet::new())?;
+                let kid = kid.as_dict()?;
+                let pages = match kid.get(b"Kids") {
+                    Ok(_) => kid.get(b"Count").and_then(Object::as_i64)?.max(0) as u32,
+                    Err(_) => 1,
+                };
+                if remaining <= pages {
+                    next = Some(kid_id);
+                    break;
+                }
+                remaining -= pages;
+            }
+            node_id = next.ok_or(Error::PageNumberNotFound(page_number))?;
+        }
+        Err(Error::ReferenceCycle(node_id))
+    }
+
+    /// Load every object `object` refers to, directly or indirectly, except page tree nodes.
+    /// Objects that fail to load are left out, as they are by a full load.
+    pub(super) fn load_references(&mut self, object: &Object) {
+        let mut pending = Vec::new();
+        collect_references(object, &mut pending);
+        while let Some(id) = pending.pop() {
+            if self.document.objects.contains_key(&id) {
+                continue;
+            }
+            let Ok(object) = self.get_object(id, &mut HashSet::new()) else {
+                continue;
+            };
+            let is_page_node = object
+                .as_dict()
+                .is_ok_and(|dict| dict.has_type(b"Page") || dict.has_type(b"Pages"));
+            if is_page_node {
+                continue;
+            }
+            collect_references(&object, &mut pending);
+            self.document.objects.insert(id, object);
+        }
+    }
+}
+
+/// Push the references in `object` to `references`, leaving out links back up the tree.
+fn collect_references(object: &Object, references: &mut Vec<ObjectId>) {
+    match object {
+        Object::Reference(id) => references.push(*id),
+        Object::Array(array) => array.iter().for_each(|item| collect_references(item, references)),
+        Object::Dictionary(dict) | Object::Stream(Stream { dict, .. }) => dict
+            .iter()
+            .filter(|(key, _)| !matches!(key.as_slice(), b"Parent" | b"P"))
+            .for_each(|(_, value)| collect_references(value, references)),
+        _ => {}
+    }
+}
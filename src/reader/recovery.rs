@@ -0,0 +1,226 @@
+use log::warn;
+use memchr::{memchr2, memmem};
+use std::collections::HashSet;
+
+use super::Reader;
+use crate::parser::{self, is_regular, is_whitespace};
+use crate::xref::{Xref, XrefEntry, XrefType};
+use crate::{Dictionary, LoadWarning, Object, ObjectId};
+
+/// Trailer entries kept from the trailer or cross-reference stream a rebuilt trailer is based on.
+const TRAILER_KEYS: [&[u8]; 4] = [b"Root", b"Info", b"ID", b"Encrypt"];
+
+/// Bytes searched on either side of a wrong cross-reference offset for the header of its object.
+const RELOCATION_WINDOW: usize = 4096;
+
+impl Reader<'_> {
+    /// Whether the catalog the trailer points to can be read through `xref`.
+    pub(super) fn root_is_readable(&self, xref: &Xref, trailer: &Dictionary) -> bool {
+        let Ok(root_id) = trailer.get(b"Root").and_then(Object::as_reference) else {
+            return false;
+        };
+        match xref.get(root_id.0) {
+            Some(XrefEntry::Normal { offset, .. }) => self
+                .read_object(*offset as usize, Some(root_id), &mut HashSet::new())
+                .is_ok_and(|(_, root)| root.as_dict().is_ok()),
+            Some(XrefEntry::Compressed { .. }) => true,
+            _ => false,
+        }
+    }
+
+    /// Find the object `id` the cross-reference table wrongly lists at `listed`: at the nearest
+    /// header for it within [`RELOCATION_WINDOW`] bytes, or else at its header among the object
+    /// headers of the whole file, scanned once per reader. Returns the offset it was read at, and
+    /// the warning for a stream whose /Length isn't the length of its data.
+    pub(super) fn relocate_object(
+        &self, id: ObjectId, listed: usize,
+    ) -> Option<(usize, Object, Option<LoadWarning>)> {
+        let start = listed.saturating_sub(RELOCATION_WINDOW);
+        let end = listed.saturating_add(RELOCATION_WINDOW).min(self.buffer.len());
+        let mut nearby: Vec<usize> = memmem::find_iter(self.buffer.get(start..end)?, b"obj")
+            .filter(|pos| self.buffer.get(start + pos + 3).is_none_or(|&c| !is_regular(c)))
+            .filter_map(|pos| object_header_before(self.buffer, start + pos))
+            .filter(|&(_, number, generation)| (number, generation) == id)
+            .map(|(header, _, _)| header)
+            .collect();
+        nearby.sort_by_key(|header| header.abs_diff(listed));
+
+        let read = |offset: usize| {
+            let (_, object, length_mismatch) = self.read_object_checked(offset, Some(id), &mut HashSet::new()).ok()?;
+            Some((offset, object, length_mismatch))
+        };
+        let indexed = || match self.object_headers.get_or_init(|| scan_object_headers(self.buffer)).get(id.0) {
+            Some(XrefEntry::Normal { offset, generation }) if *generation == id.1 => Some(*offset as usize),
+            _ => None,
+        };
+        nearby
+            .into_iter()
+            .filter(|&header| header != listed)
+            .find_map(read)
+            .or_else(|| indexed().filter(|&header| header != listed).and_then(read))
+    }
+
+    /// Record a [`LoadWarning::UnfinishedUpdate`] if objects follow the `%%EOF` of the revision
+    /// whose `startxref` keyword is at `startxref`, as when a file is cut off in the middle of its
+    /// last incremental update.
+    pub(super) fn check_unfinished_update(&mut self, startxref: usize) {
+        let Some(eof) = memmem::find(&self.buffer[startxref..], b"%%EOF") else {
+            return;
+        };
+        let mut revision_end = startxref + eof + b"%%EOF".len();
+        revision_end += self.buffer[revision_end..].iter().take_while(|&&c| matches!(c, b'\r' | b'\n')).count();
+        if scan_object_headers(&self.buffer[revision_end..]).entries.is_empty() {
+            return;
+        }
+        let warning = LoadWarning::UnfinishedUpdate {
+            revision_end,
+            ignored: self.buffer.len() - revision_end,
+        };
+        warn!("{warning}");
+        self.document.load_warnings.push(warning);
+    }
+
+    /// Rebuild the cross-reference table from the object headers found in the file, and a trailer
+    /// from the last `trailer` dictionary or cross-reference stream. Without either, the last
+    /// catalog found becomes the /Root. Returns `None` if no catalog can be found.
+    pub(super) fn rebuild_xref(&mut self) -> Option<(Xref, Dictionary)> {
+        let mut xref = scan_object_headers(self.buffer);
+        // Stream lengths may be indirect, so objects are read through the rebuilt table.
+        self.document.reference_table = xref.clone();
+
+        // The newest of each, by offset.
+        let mut catalog: Option<(u64, ObjectId)> = None;
+        let mut xref_stream_dict: Option<(u64, Dictionary)> = None;
+        for (&id, entry) in &xref.entries {
+            let XrefEntry::Normal { offset, generation } = *entry else {
+                continue;
+            };
+            let Ok((_, object)) = self.read_object(offset as usize, Some((id, generation)), &mut HashSet::new())
+            else {
+                continue;
+            };
+            match object {
+                Object::Dictionary(dict) if dict.has_type(b"Catalog") && catalog.is_none_or(|(at, _)| offset > at) => {
+                    catalog = Some((offset, (id, generation)));
+                }
+                Object::Stream(stream)
+                    if stream.dict.has_type(b"XRef") && xref_stream_dict.as_ref().is_none_or(|(at, _)| offset > *at) =>
+                {
+                    xref_stream_dict = Some((offset, stream.dict));
+                }
+                _ => {}
+            }
+        }
+
+        let found_trailer = Self::search_substring(self.buffer, b"trailer", 0)
+            .and_then(|start| {
+                let rest = &self.buffer[start + b"trailer".len()..];
+                let rest = &rest[rest.iter().position(|&c| !is_whitespace(c)).unwrap_or(rest.len())..];
+                parser::dictionary(self.input(rest)).ok()
+            })
+            .map(|(_, dict)| dict)
+            .or_else(|| xref_stream_dict.map(|(_, dict)| dict));
+        let mut trailer = Dictionary::new();
+        for key in TRAILER_KEYS {
+            if let Some(value) = found_trailer.as_ref().and_then(|found| found.get(key).ok()) {
+                trailer.set(key, value.clone());
+            }
+        }
+        if !self.root_is_readable(&xref, &trailer) {
+            let (_, root_id) = catalog?;
+            trailer.set("Root", root_id);
+        }
+
+        xref.size = xref.max_id() + 1;
+        trailer.set("Size", i64::from(xref.size));
+        warn!("rebuilt the cross-reference table from {} object headers", xref.entries.len());
+        Some((xref, trailer))
+    }
+}
+
+/// Find the `N G obj` headers in `buffer`, skipping the content of streams which may contain
+/// anything. A later header of an object number replaces an earlier one, as an incremental update
+/// would.
+pub(super) fn scan_object_headers(buffer: &[u8]) -> Xref {
+    let mut xref = Xref::new(0, XrefType::CrossReferenceTable);
+    let follows_token = |pos: usize| pos == 0 || !is_regular(buffer[pos - 1]);
+    let ends_token = |pos: usize| buffer.get(pos).is_none_or(|&c| !is_regular(c));
+    let mut pos = 0;
+    // Only a `stream` or `obj` keyword matters, so skip to the next `s` or `o`.
+    while let Some(skip) = memchr2(b's', b'o', &buffer[pos..]) {
+        pos += skip;
+        let rest = &buffer[pos..];
+        if rest.starts_with(b"stream") && follows_token(pos) && matches!(rest.get(6), Some(b'\r' | b'\n')) {
+            match memmem::find(rest, b"endstream") {
+                Some(end) => pos += end + b"endstream".len(),
+                None => break,
+            }
+            continue;
+        }
+        if rest.starts_with(b"obj") && ends_token(pos + 3) {
+            if let Some((start, id, generation)) = object_header_before(buffer, pos) {
+                xref.insert(
+                    id,
+                    XrefEntry::Normal {
+                        offset: start as u64,
+                        generation,
+                    },
+                );
+            }
+        }
+        pos += 1;
+    }
+    xref
+}
+
+/// Parse the object number and generation in front of the `obj` keyword at `keyword`. Returns
+/// the offset the header starts at, with the numbers.
+fn object_header_before(buffer: &[u8], keyword: usize) -> Option<(usize, u32, u16)> {
+    let skip_back = |mut pos: usize, accept: fn(&u8) -> bool| {
+        while pos > 0 && accept(&buffer[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    };
+    let generation_end = skip_back(keyword, |&c| is_whitespace(c));
+    let generation_start = skip_back(generation_end, u8::is_ascii_digit);
+    let id_end = skip_back(generation_start, |&c| is_whitespace(c));
+    let id_start = skip_back(id_end, u8::is_ascii_digit);
+    if generation_end == keyword
+        || generation_start == generation_end
+        || id_end == generation_start
+        || id_start == id_end
+        || (id_start > 0 && is_regular(buffer[id_start - 1]))
+    {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| std::str::from_utf8(&buffer[range]).ok();
+    let id = number(id_start..id_end)?.parse().ok()?;
+    let generation = number(generation_start..generation_end)?.parse().ok()?;
+    Some((id_start, id, generation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_headers_are_found_outside_streams() {
+        let buffer = b"%PDF-1.7\n1 0 obj\n<< /Length 13 >>\nstream\n9 0 obj fake\nendstream\nendobj\n\
+                       2 0 obj 42 endobj 12 3 obj\n(x)\nendobj\nx3 0 obj 1 0 obj null endobj";
+        let xref = scan_object_headers(buffer);
+        let offsets: Vec<(u32, u64, u16)> = xref
+            .entries
+            .iter()
+            .map(|(&id, entry)| match *entry {
+                XrefEntry::Normal { offset, generation } => (id, offset, generation),
+                _ => unreachable!(),
+            })
+            .collect();
+        let find = |header: &[u8]| buffer.windows(header.len()).rposition(|w| w == header).unwrap() as u64;
+        assert_eq!(
+            offsets,
+            [(1, find(b"1 0 obj"), 0), (2, find(b"2 0 obj"), 0), (12, find(b"12 3 obj"), 3)]
+        );
+    }
+}
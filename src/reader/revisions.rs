@@ -0,0 +1,76 @@
+use std::collections::{BTreeMap, HashSet};
+
+use log::warn;
+
+use super::Reader;
+use crate::encryption;
+use crate::xref::{Xref, XrefEntry};
+use crate::{Error, Object, ObjectId, Result, Revision};
+
+impl Reader<'_> {
+    /// Record the revisions of the file from its cross-reference sections and trailers, oldest
+    /// first, reading the /Root and /Info objects of earlier revisions that a later one replaced.
+    pub(super) fn record_revisions(&mut self, sections: Vec<super::XrefSection>) {
+        let revisions = revisions(sections, &self.document.reference_table, |id, offset| {
+            self.read_replaced_object(id, offset)
+        });
+        self.document.revisions = revisions;
+    }
+
+    /// Read an earlier version of object `id` at `offset`.
+    fn read_replaced_object(&self, id: ObjectId, offset: u64) -> Result<Object> {
+        let (_, mut object) = self.read_object(offset as usize, Some(id), &mut HashSet::new())?;
+        if let Some(state) = &self.encryption_state {
+            encryption::decrypt_object(state, id, &mut object).map_err(Error::Decryption)?;
+        }
+        Ok(object)
+    }
+}
+
+/// The revisions of a file, oldest first, from its cross-reference sections and trailers as read
+/// back through /Prev, with the /Root and /Info objects of earlier revisions that a later one
+/// replaced in `current`, the merged table. `read_replaced` reads such an object at its offset.
+pub(super) fn revisions(
+    sections: Vec<super::XrefSection>, current: &Xref, mut read_replaced: impl FnMut(ObjectId, u64) -> Result<Object>,
+) -> Vec<Revision> {
+    let mut revisions: Vec<Revision> = sections
+        .into_iter()
+        .rev()
+        .map(|(xref_start, xref, trailer)| Revision {
+            xref_start,
+            xref,
+            trailer,
+            replaced_objects: BTreeMap::new(),
+        })
+        .collect();
+
+    for index in 0..revisions.len() {
+        for key in [b"Root".as_slice(), b"Info"] {
+            let Ok(id) = revisions[index].trailer.get(key).and_then(Object::as_reference) else {
+                continue;
+            };
+            // The revision sees the newest entry of the object up to and including its own.
+            let entry = revisions[..=index]
+                .iter()
+                .rev()
+                .find_map(|revision| revision.xref.get(id.0));
+            let Some(&XrefEntry::Normal { offset, generation }) = entry else {
+                continue;
+            };
+            let is_current = matches!(
+                current.get(id.0),
+                Some(XrefEntry::Normal { offset: current, .. }) if *current == offset
+            );
+            if generation != id.1 || is_current {
+                continue;
+            }
+            match read_replaced(id, offset) {
+                Ok(object) => {
+                    revisions[index].replaced_objects.insert(id, object);
+                }
+                Err(err) => warn!("Object {} {} of revision {index} can't be read: {err}", id.0, id.1),
+            }
+        }
+    }
+    revisions
+}
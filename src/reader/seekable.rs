@@ -0,0 +1,411 @@
+use log::{debug, warn};
+use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom};
+
+use memchr::memmem;
+
+use super::object_loader::{next_bound, object_bounds};
+use super::revisions::revisions;
+use super::{Reader, XrefSection};
+use crate::error::{ParseError, XrefError};
+use crate::object_stream::ObjectStream;
+use crate::parser;
+use crate::xref::{Xref, XrefEntry};
+use crate::{Dictionary, Document, Error, Object, ObjectId, ReaderOptions, Result, Stream};
+
+/// How much of the start and of the end of the file is read for the header and `startxref`.
+const HEAD_TAIL: usize = 1024;
+/// How much is read for a cross-reference section at first, doubled until it parses.
+const XREF_WINDOW: usize = 64 * 1024;
+/// Objects spanning more than this are read as streams in two steps: the dictionary, then the data
+/// right into the stream, so the data isn't in memory twice.
+const LARGE_OBJECT: usize = 64 * 1024;
+
+impl Document {
+    /// Load a PDF document from a seekable source, reading each object at the offset the
+    /// cross-reference table lists instead of the whole file at once. Only the parsed objects and
+    /// the bytes of the object being read are in memory, where [`Document::load_from`] holds the
+    /// whole file next to them. The file starts at the current position of `source`.
+    ///
+    /// Files that can't be read object by object are loaded from a buffer of the whole file, as
+    /// [`Document::load_mem`] does: encrypted files, and those with anything to repair or warn
+    /// about in their cross-reference table or objects. Sources that can't seek are loaded with
+    /// [`Document::load_from`].
+    ///
+    /// ```no_run
+    /// let file = std::fs::File::open("upload.pdf")?;
+    /// let doc = lopdf::Document::load_seekable(std::io::BufReader::new(file))?;
+    /// # Ok::<(), lopdf::Error>(())
+    /// ```
+    pub fn load_seekable<R: Read + Seek>(mut source: R) -> Result<Document> {
+        let start = source.stream_position()?;
+        match SeekableReader::new(&mut source, start)?.read() {
+            Err(Error::IO(err)) => Err(Error::IO(err)),
+            Err(err) => {
+                debug!("reading the whole file, as it can't be read object by object: {err}");
+                source.seek(SeekFrom::Start(start))?;
+                let mut buffer = Vec::new();
+                source.read_to_end(&mut buffer)?;
+                Document::load_mem(&buffer)
+            }
+            document => document,
+        }
+    }
+}
+
+/// Reads a document from a seekable source, a piece at a time. Offsets are from the `%PDF-` header
+/// on, as those of [`Reader`] are.
+struct SeekableReader<'s, R> {
+    source: &'s mut R,
+    /// The position of the header in the source
+    start: u64,
+    /// The length of the file from the header on
+    len: usize,
+    document: Document,
+    /// The offsets objects and cross-reference sections start at, which the others end at
+    bounds: Vec<usize>,
+}
+
+impl<'s, R: Read + Seek> SeekableReader<'s, R> {
+    fn new(source: &'s mut R, start: u64) -> Result<Self> {
+        let end = source.seek(SeekFrom::End(0))?;
+        let len = usize::try_from(end.saturating_sub(start))?;
+        Ok(SeekableReader {
+            source,
+            start,
+            len,
+            document: Document::new(),
+            bounds: Vec::new(),
+        })
+    }
+
+    /// Read the bytes from `offset` up to `end` or the end of the file.
+    fn read_range(&mut self, offset: usize, end: usize) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; end.min(self.len).saturating_sub(offset)];
+        self.source.seek(SeekFrom::Start(self.start + offset as u64))?;
+        self.source.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read(mut self) -> Result<Document> {
+        let head = self.read_range(0, HEAD_TAIL)?;
+        let mut reader = reader(&head);
+        if memmem::find(&head, b"%PDF-").is_none() {
+            return Err(ParseError::InvalidFileHeader.into());
+        }
+        let version = reader.read_header()?;
+        let header = head.len() - reader.buffer.len();
+        self.document.binary_mark = reader.document.binary_mark;
+        self.start += header as u64;
+        self.len -= header;
+
+        #[cfg(feature = "census")]
+        let counting = crate::census::Counting::start();
+        let tail_start = self.len.saturating_sub(HEAD_TAIL);
+        let xref_start = Reader::get_xref_start(&self.read_range(tail_start, self.len)?)?;
+        let (xref, trailer, sections) = self.read_xref_sections(xref_start)?;
+        if trailer.has(b"Encrypt") {
+            return Err(Error::Unimplemented("reading encrypted files object by object"));
+        }
+        self.document.version = version;
+        self.document.max_id = xref.size - 1;
+        self.document.trailer = trailer;
+        self.document.reference_table = xref;
+        self.document.xref_start = xref_start;
+
+        self.bounds = object_bounds(&self.document.reference_table, xref_start);
+        self.bounds.extend(sections.iter().map(|(start, _, _)| *start));
+        self.bounds.sort_unstable();
+        self.load_objects()?;
+
+        let current = self.document.reference_table.clone();
+        self.document.revisions = revisions(sections, &current, |id, offset| self.read_object(id, offset as usize));
+        self.document.repairs = self.document.repair_page_tree();
+
+        #[cfg(feature = "census")]
+        {
+            let survey = crate::Census::survey(&self.document);
+            self.document.census.merge(&survey);
+            self.document.census.add_parse_counts(counting.finish());
+        }
+        Ok(self.document)
+    }
+
+    /// Read the cross-reference sections from the last one back through /Prev, as
+    /// [`Reader::read_xref_sections`] does. Returns the merged table, the trailer of the last
+    /// section and all the sections.
+    fn read_xref_sections(&mut self, xref_start: usize) -> Result<(Xref, Dictionary, Vec<XrefSection>)> {
+        let (mut xref, mut trailer) = self.read_xref_section(xref_start)?;
+        if let Some(stream_start) = trailer.remove(b"XRefStm").and_then(|start| start.as_i64().ok()) {
+            self.merge_hybrid_xref_stream(&mut xref, stream_start)?;
+        }
+
+        let mut sections = vec![(xref_start, xref.clone(), trailer.clone())];
+        let mut already_seen = HashSet::new();
+        let mut prev_xref_start = trailer.remove(b"Prev");
+        while let Some(prev) = prev_xref_start.and_then(|offset| offset.as_i64().ok()) {
+            if !already_seen.insert(prev) {
+                break;
+            }
+            let prev = usize::try_from(prev).map_err(|_| Error::Xref(XrefError::PrevStart))?;
+            let (mut prev_xref, prev_trailer) = self.read_xref_section(prev)?;
+            if let Ok(stream_start) = prev_trailer.get(b"XRefStm").and_then(Object::as_i64) {
+                self.merge_hybrid_xref_stream(&mut prev_xref, stream_start)?;
+            }
+            sections.push((prev, prev_xref.clone(), prev_trailer.clone()));
+            xref.merge(prev_xref);
+            prev_xref_start = prev_trailer.get(b"Prev").cloned().ok();
+        }
+        // A /Size to correct is reported by the reader of the whole file.
+        if xref.max_id().checked_add(1) != Some(xref.size) {
+            return Err(ParseError::InvalidXref.into());
+        }
+        Ok((xref, trailer, sections))
+    }
+
+    fn merge_hybrid_xref_stream(&mut self, xref: &mut Xref, start: i64) -> Result<()> {
+        #[cfg(feature = "census")]
+        {
+            self.document.census.hybrid_xrefs += 1;
+        }
+        let start = usize::try_from(start).map_err(|_| Error::Xref(XrefError::StreamStart))?;
+        let (stream_xref, _) = self.read_xref_section(start)?;
+        xref.merge_hybrid_stream(stream_xref);
+        Ok(())
+    }
+
+    /// Read the cross-reference section at `offset`, reading more of the file until it parses.
+    fn read_xref_section(&mut self, offset: usize) -> Result<(Xref, Dictionary)> {
+        if offset > self.len {
+            return Err(Error::Xref(XrefError::Start));
+        }
+        let mut window = XREF_WINDOW;
+        loop {
+            let bytes = self.read_range(offset, offset.saturating_add(window))?;
+            let reader = reader(&bytes);
+            match parser::xref_and_trailer(reader.input(&bytes), &reader) {
+                Err(_) if offset + bytes.len() < self.len => window = window.saturating_mul(2),
+                section => return section,
+            }
+        }
+    }
+
+    /// Load the objects the cross-reference table lists, and those of the object streams among
+    /// them, as [`Reader::load_objects_raw`] does.
+    fn load_objects(&mut self) -> Result<()> {
+        let entries: Vec<(ObjectId, usize)> = self
+            .document
+            .reference_table
+            .entries
+            .iter()
+            .filter_map(|(&number, entry)| match *entry {
+                XrefEntry::Normal { offset, generation } => Some(((number, generation), offset as usize)),
+                _ => None,
+            })
+            .collect();
+        let mut object_streams = Vec::new();
+        for (id, offset) in entries {
+            let mut object = self.read_object(id, offset)?;
+            if let Ok(stream) = object.as_stream_mut() {
+                if stream.dict.has_type(b"ObjStm") {
+                    let object_stream = ObjectStream::with_limits(stream, ReaderOptions::default().parse_limits())?;
+                    object_streams.push((id, object_stream.objects));
+                }
+            }
+            self.document.objects.insert(id, object);
+        }
+
+        // The members of the object stream the table lists them in come first, then those of the
+        // others by object number.
+        let entries = &self.document.reference_table.entries;
+        let is_listed_in = |id: &ObjectId, container_id: &ObjectId| {
+            matches!(entries.get(&id.0), Some(XrefEntry::Compressed { container, .. }) if *container == container_id.0)
+        };
+        let (listed, unlisted): (Vec<_>, Vec<_>) = object_streams
+            .into_iter()
+            .flat_map(|(container_id, objects)| objects.into_iter().map(move |(id, object)| (container_id, id, object)))
+            .partition(|(container_id, id, _)| is_listed_in(id, container_id));
+        for (_, id, object) in listed.into_iter().chain(unlisted) {
+            self.document.objects.entry(id).or_insert(object);
+        }
+        Ok(())
+    }
+
+    /// Read the object `id` at `offset`, up to the offset of the next object.
+    fn read_object(&mut self, id: ObjectId, offset: usize) -> Result<Object> {
+        let end = next_bound(&self.bounds, offset).min(self.len);
+        if end.saturating_sub(offset) > LARGE_OBJECT {
+            let head = self.read_range(offset, offset + LARGE_OBJECT)?;
+            if let Some((found, dict, data_start)) = parser::stream_header(reader(&head).input(&head)) {
+                if found != id {
+                    return Err(Error::ObjectIdMismatch);
+                }
+                let length = self.stream_length(&dict)?;
+                let data_start = offset + data_start;
+                let data = self.read_range(data_start, data_start + length)?;
+                self.check_stream_end(data_start + data.len(), length - data.len())?;
+                return Ok(Object::Stream(Stream::new(dict, data)));
+            }
+        }
+
+        let bytes = self.read_range(offset, end)?;
+        let reader = reader(&bytes);
+        if reader.padding_object(0, bytes.len()) == Some(id) {
+            warn!(
+                "object {} {} at offset {offset} is padding without content, loaded as null",
+                id.0, id.1
+            );
+            return Ok(Object::Null);
+        }
+        let (_, mut object) = reader.read_object(0, Some(id), &mut HashSet::new())?;
+        if let Object::Stream(stream) = &mut object {
+            // The data of a stream whose /Length is indirect is left to read, and that of one whose
+            // /Length is wrong is read up to `endstream`.
+            let (_, dict, data_start) =
+                parser::stream_header(reader.input(&bytes)).ok_or(Error::IndirectObject { offset })?;
+            let length = self.stream_length(&dict)?;
+            if stream.start_position.is_some() {
+                let data = bytes
+                    .get(data_start..data_start + length)
+                    .ok_or(Error::InvalidOffset(offset))?;
+                self.check_stream_end(offset + data_start + length, 0)?;
+                *stream = Stream::new(dict, data.to_vec());
+            } else if stream.content.len() != length {
+                return Err(Error::InvalidStream("wrong /Length".to_string()));
+            }
+        }
+        Ok(object)
+    }
+
+    /// The /Length of the stream with `dict`, read from its object if indirect. That object is
+    /// parsed on its own, so a /Length referring back to a stream fails instead of recursing.
+    fn stream_length(&mut self, dict: &Dictionary) -> Result<usize> {
+        let length = match dict.get(b"Length")? {
+            Object::Reference(id) => {
+                let offset = match self.document.reference_table.get(id.0) {
+                    Some(&XrefEntry::Normal { offset, generation }) if generation == id.1 => offset as usize,
+                    _ => return Err(Error::MissingXrefEntry),
+                };
+                let end = next_bound(&self.bounds, offset).min(offset + LARGE_OBJECT);
+                let bytes = self.read_range(offset, end)?;
+                let (_, length) = reader(&bytes).read_object(0, Some(*id), &mut HashSet::new())?;
+                length.as_i64()?
+            }
+            length => length.as_i64()?,
+        };
+        Ok(usize::try_from(length)?)
+    }
+
+    /// Check the stream data ending at `end`, with `missing` bytes cut off by the end of the file,
+    /// is followed by `endstream`.
+    fn check_stream_end(&mut self, end: usize, missing: usize) -> Result<()> {
+        let after = self.read_range(end, end + 64)?;
+        let keyword = &after[after
+            .iter()
+            .position(|&c| !parser::is_whitespace(c))
+            .unwrap_or(after.len())..];
+        if missing > 0 || !keyword.starts_with(b"endstream") {
+            return Err(Error::InvalidStream("wrong /Length".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// A reader of `buffer`, a piece of the file, with the default options.
+fn reader(buffer: &[u8]) -> Reader<'_> {
+    Reader::new(buffer, ReaderOptions::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::creator::tests::create_document;
+    use crate::{Document, LoadWarning, Object, Stream};
+
+    fn saved(doc: &mut Document) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        doc.save_to(&mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn documents_load_as_from_memory() {
+        for name in [
+            "example",
+            "Incremental",
+            "Hybrid",
+            "AnnotationDemo",
+            "unicode",
+            "EncryptedObjectStreams",
+        ] {
+            let buffer = std::fs::read(format!("assets/{name}.pdf")).unwrap();
+            let seekable = Document::load_seekable(Cursor::new(&buffer)).unwrap();
+            let doc = Document::load_mem(&buffer).unwrap();
+            assert_eq!(seekable.version, doc.version, "{name}");
+            assert_eq!(seekable.trailer, doc.trailer, "{name}");
+            assert_eq!(
+                seekable.reference_table.entries.len(),
+                doc.reference_table.entries.len(),
+                "{name}"
+            );
+            assert_eq!(seekable.objects, doc.objects, "{name}");
+            assert_eq!(seekable.revisions.len(), doc.revisions.len(), "{name}");
+        }
+    }
+
+    #[test]
+    fn large_streams_and_indirect_lengths_are_read() {
+        let mut doc = create_document();
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let large = doc.add_object(Stream::new(dictionary! {}, data.clone()));
+        let length = doc.add_object(Object::Integer(5));
+        let mut indirect = Stream::new(dictionary! {}, b"hello".to_vec());
+        indirect.dict.set("Length", length);
+        let indirect = doc.add_object(indirect);
+        let buffer = saved(&mut doc);
+
+        let seekable = Document::load_seekable(Cursor::new(&buffer)).unwrap();
+        let doc = Document::load_mem(&buffer).unwrap();
+        assert_eq!(seekable.objects, doc.objects);
+        assert_eq!(seekable.get_object(large).unwrap().as_stream().unwrap().content, data);
+        assert_eq!(
+            seekable.get_object(indirect).unwrap().as_stream().unwrap().content,
+            b"hello"
+        );
+        assert!(seekable.load_warnings().is_empty());
+    }
+
+    #[test]
+    fn lengths_referring_to_their_stream_are_read_whole() {
+        let mut doc = create_document();
+        let id = doc.new_object_id();
+        let mut stream = Stream::new(dictionary! {}, vec![b'x'; 100_000]);
+        stream.dict.set("Length", id);
+        doc.objects.insert(id, Object::Stream(stream));
+        let buffer = saved(&mut doc);
+
+        let seekable = Document::load_seekable(Cursor::new(&buffer)).unwrap();
+        assert_eq!(seekable.objects, Document::load_mem(&buffer).unwrap().objects);
+    }
+
+    #[test]
+    fn files_to_repair_are_read_whole() {
+        let mut doc = create_document();
+        let mut stream = Stream::new(dictionary! {}, b"hello".to_vec());
+        stream.dict.set("Length", 8);
+        doc.add_object(stream);
+        let buffer = saved(&mut doc);
+
+        let seekable = Document::load_seekable(Cursor::new(&buffer)).unwrap();
+        assert!(matches!(
+            seekable.load_warnings(),
+            [LoadWarning::StreamLengthMismatch {
+                declared: 8,
+                actual: Some(5),
+                ..
+            }]
+        ));
+        assert_eq!(seekable.objects, Document::load_mem(&buffer).unwrap().objects);
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use crate::Document;
+use crate::{Document, Stream};
 
 #[cfg(not(feature = "async"))]
 #[test]
@@ -42,8 +42,7 @@ fn load_document_with_preceding_bytes() {
 
 #[test]
 fn load_many_shallow_brackets() {
-    let content: String = std::iter::repeat("()")
-        .take(MAX_BRACKET * 10)
+    let content: String = std::iter::repeat_n("()", MAX_BRACKET * 10)
         .flat_map(|x| x.chars())
         .collect();
     const STREAM_CRUFT: usize = 33;
@@ -75,9 +74,8 @@ fn load_many_shallow_brackets() {
 
 #[test]
 fn load_too_deep_brackets() {
-    let content: Vec<u8> = std::iter::repeat(b'(')
-        .take(MAX_BRACKET + 1)
-        .chain(std::iter::repeat(b')').take(MAX_BRACKET + 1))
+    let content: Vec<u8> = std::iter::repeat_n(b'(', MAX_BRACKET + 1)
+        .chain(std::iter::repeat_n(b')', MAX_BRACKET + 1))
         .collect();
     let content = String::from_utf8(content).unwrap();
     const STREAM_CRUFT: usize = 33;
@@ -130,4 +128,1077 @@ fn search_substring_finds_last_occurrence() {
         Reader::search_substring(buffer_with_many_percents, b"%%EOF", 0),
         Some(27)
     );
+
+    // An empty pattern is found at the end, as by `str::rfind`, unless the start is past it.
+    assert_eq!(Reader::search_substring(b"hello", b"", 0), Some(5));
+    assert_eq!(Reader::search_substring(b"hello", b"", 5), Some(5));
+    assert_eq!(Reader::search_substring(b"hello", b"", 6), None);
+    assert_eq!(Reader::search_substring(b"", b"", 0), Some(0));
+
+    // A pattern longer than the buffer, or than what is left of it after the start, isn't found.
+    assert_eq!(Reader::search_substring(b"EOF", b"%%EOF", 0), None);
+    assert_eq!(Reader::search_substring(b"", b"%%EOF", 0), None);
+    assert_eq!(Reader::search_substring(b"%%EOF", b"%%EOF", 1), None);
+}
+
+#[test]
+fn duplicate_objects_in_object_streams_load_deterministically() {
+    use crate::writer::Writer;
+    use crate::ObjectStream;
+    use std::io::Write;
+
+    // Twenty object streams that all define objects 500..540. The writer never saves object
+    // streams as regular objects, so the file is put together by hand.
+    let mut objects: Vec<Object> = vec![
+        dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into(),
+        dictionary! { "Type" => "Pages", "Kids" => vec![], "Count" => 0 }.into(),
+    ];
+    for container in 0..20 {
+        let mut obj_stream = ObjectStream::builder().build();
+        for id in 500..540 {
+            obj_stream.add_object((id, 0), Object::Integer(container)).unwrap();
+        }
+        objects.push(obj_stream.to_stream_object().unwrap().into());
+    }
+    let mut buffer = b"%PDF-1.5\n".to_vec();
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(buffer.len());
+        writeln!(buffer, "{} 0 obj", index + 1).unwrap();
+        Writer::write_object(&mut buffer, object).unwrap();
+        buffer.extend(b"\nendobj\n");
+    }
+    let xref_start = buffer.len();
+    write!(buffer, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).unwrap();
+    for offset in offsets {
+        writeln!(buffer, "{offset:010} 00000 n ").unwrap();
+    }
+    write!(
+        buffer,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_start}\n%%EOF\n",
+        objects.len() + 1
+    )
+    .unwrap();
+
+    let first = Document::load_mem(&buffer).unwrap();
+    // No xref entry names a container, so the stream with the lowest object number wins.
+    for id in 500..540 {
+        assert_eq!(first.get_object((id, 0)).unwrap(), &Object::Integer(0));
+    }
+    for _ in 0..50 {
+        let loaded = Document::load_mem(&buffer).unwrap();
+        assert!(loaded.objects == first.objects);
+    }
+}
+
+/// Put numbered objects in a file, returning it with the offsets of the objects.
+fn assemble_objects(objects: &[(u32, Object)]) -> (Vec<u8>, Vec<usize>) {
+    use crate::writer::Writer;
+    use std::io::Write;
+
+    let mut buffer = b"%PDF-1.5\n".to_vec();
+    let mut offsets = Vec::new();
+    for (id, object) in objects {
+        offsets.push(buffer.len());
+        writeln!(buffer, "{id} 0 obj").unwrap();
+        Writer::write_object(&mut buffer, object).unwrap();
+        buffer.extend(b"\nendobj\n");
+    }
+    (buffer, offsets)
+}
+
+fn with_xref_table(mut buffer: Vec<u8>, offsets: &[usize]) -> Vec<u8> {
+    use std::io::Write;
+
+    let xref_start = buffer.len();
+    write!(buffer, "xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).unwrap();
+    for offset in offsets {
+        writeln!(buffer, "{offset:010} 00000 n ").unwrap();
+    }
+    write!(
+        buffer,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_start}\n%%EOF\n",
+        offsets.len() + 1
+    )
+    .unwrap();
+    buffer
+}
+
+fn pages_object_stream() -> Stream {
+    let mut obj_stream = ObjectStream::builder().build();
+    let pages = dictionary! { "Type" => "Pages", "Kids" => vec![], "Count" => 0 };
+    obj_stream.add_object((2, 0), pages.into()).unwrap();
+    let mut stream = obj_stream.to_stream_object().unwrap();
+    stream.decompress().unwrap();
+    stream
+}
+
+#[test]
+fn load_lzw_object_stream() {
+    use weezl::{encode::Encoder, BitOrder};
+
+    let mut stream = pages_object_stream();
+    let lzw = Encoder::with_tiff_size_switch(BitOrder::Msb, 8)
+        .encode(&stream.content)
+        .unwrap();
+    let hex: Vec<u8> = lzw.iter().flat_map(|byte| format!("{byte:02X}").into_bytes()).chain(*b">").collect();
+    stream.dict.set("Filter", vec!["ASCIIHexDecode".into(), "LZWDecode".into()]);
+    stream.dict.set("DecodeParms", vec![Object::Null, dictionary! { "EarlyChange" => 1 }.into()]);
+    stream.set_content(hex);
+
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (3, Object::Stream(stream)),
+    ];
+    let (buffer, offsets) = assemble_objects(&objects);
+    let doc = Document::load_mem(&with_xref_table(buffer, &offsets)).unwrap();
+    let pages = doc.get_dictionary((2, 0)).unwrap();
+    assert!(pages.has_type(b"Pages"));
+}
+
+#[test]
+fn unsupported_object_stream_filter_is_named() {
+    let mut stream = pages_object_stream();
+    stream.dict.set("Filter", "JBIG2Decode");
+    let err = ObjectStream::new(&mut stream.clone()).unwrap_err();
+    assert!(matches!(&err, Error::UnsupportedFilter(filter) if filter == "JBIG2Decode"), "{err}");
+    assert_eq!(err.to_string(), "unsupported stream filter /JBIG2Decode");
+
+    // The container is kept even though its objects can't be read.
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, Object::Stream(stream)),
+    ];
+    let (buffer, offsets) = assemble_objects(&objects);
+    let doc = Document::load_mem(&with_xref_table(buffer, &offsets)).unwrap();
+    assert!(doc.get_object((2, 0)).and_then(Object::as_stream).is_ok());
+}
+
+#[test]
+fn load_xref_stream_with_png_predictor() {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, dictionary! { "Type" => "Pages", "Kids" => vec![], "Count" => 0 }.into()),
+    ];
+    let (mut buffer, offsets) = assemble_objects(&objects);
+    let xref_start = buffer.len();
+
+    // Rows of /W [1 2 1], each encoded with the PNG Up filter as predictor 12 requires.
+    let mut rows = vec![[0u8, 0, 0, 255]];
+    for offset in offsets.iter().chain([&xref_start]) {
+        let offset = *offset as u16;
+        rows.push([1, (offset >> 8) as u8, offset as u8, 0]);
+    }
+    let mut encoded = Vec::new();
+    let mut previous = [0u8; 4];
+    for row in rows {
+        encoded.push(2);
+        encoded.extend(row.iter().zip(previous).map(|(byte, above)| byte.wrapping_sub(above)));
+        previous = row;
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&encoded).unwrap();
+    let content = encoder.finish().unwrap();
+
+    let xref = Stream::new(
+        dictionary! {
+            "Type" => "XRef",
+            "Size" => 4,
+            "W" => vec![1.into(), 2.into(), 1.into()],
+            "Root" => (1, 0),
+            "Filter" => "FlateDecode",
+            "DecodeParms" => dictionary! { "Predictor" => 12, "Columns" => 4 },
+        },
+        content,
+    );
+    writeln!(buffer, "3 0 obj").unwrap();
+    crate::writer::Writer::write_object(&mut buffer, &Object::Stream(xref)).unwrap();
+    write!(buffer, "\nendobj\nstartxref\n{xref_start}\n%%EOF\n").unwrap();
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert!(matches!(
+        doc.reference_table.cross_reference_type,
+        crate::xref::XrefType::CrossReferenceStream
+    ));
+    assert!(doc.catalog().unwrap().has(b"Pages"));
+    assert!(doc.get_dictionary((2, 0)).unwrap().has_type(b"Pages"));
+    assert!(matches!(
+        doc.reference_table.get(2),
+        Some(XrefEntry::Normal { offset, .. }) if *offset as usize == offsets[1]
+    ));
+}
+
+#[test]
+fn padding_objects_are_classified_by_their_whole_body() {
+    let padding = |body: &[u8]| parser::padding_object(parser::new_input(body));
+    assert_eq!(padding(b"4 0 obj\n              \n"), Some((4, 0)));
+    assert_eq!(padding(b"4 0 obj\n% filler\n%%%%%%\r\n  endobj\n"), Some((4, 0)));
+    assert_eq!(padding(b"4 0 obj\n% filler without end of line"), Some((4, 0)));
+    assert_eq!(padding(b"4 0 obj\n  42 % comment\nendobj\n"), None);
+    assert_eq!(padding(b"4 0 obj\nnull\nendobj\n"), None);
+    assert_eq!(padding(b"4 0 obj\nendobj\nendobj\n"), None);
+    assert_eq!(padding(b"4 0 obj\nendobjx\n"), None);
+    assert_eq!(padding(b"4 0 obj<<>>endobj"), None);
+    assert_eq!(padding(b"        \n"), None);
+}
+
+#[test]
+fn load_linearized_file_with_padding_objects() {
+    use std::io::Write;
+
+    let mut buffer = b"%PDF-1.5\n".to_vec();
+    let mut offsets = Vec::new();
+    let bodies: [&[u8]; 6] = [
+        b"<< /Linearized 1 /L 1000 /N 1 >>\nendobj\n",
+        b"<< /Type /Catalog /Pages 3 0 R >>\nendobj\n",
+        b"<< /Type /Pages /Kids [4 0 R] /Count 1 >>\nendobj\n",
+        b"<< /Type /Page /Parent 3 0 R /MediaBox [0 0 10 10] >>\nendobj\n",
+        // Padding directly followed by the next object, which mustn't be read as its content.
+        b"                                \n",
+        b"% padding\n%%%%%%%%%%%%%%%%%%%%%%%%\n    endobj\n",
+    ];
+    for (id, body) in (1..).zip(bodies) {
+        offsets.push(buffer.len());
+        writeln!(buffer, "{id} 0 obj").unwrap();
+        buffer.extend(body);
+    }
+    let xref_start = buffer.len();
+    write!(buffer, "xref\n0 7\n0000000000 65535 f \n").unwrap();
+    for offset in &offsets {
+        writeln!(buffer, "{offset:010} 00000 n ").unwrap();
+    }
+    // The last padding object is numbered past /Size.
+    write!(buffer, "trailer\n<< /Size 6 /Root 2 0 R >>\nstartxref\n{xref_start}\n%%EOF\n").unwrap();
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(doc.get_pages().len(), 1);
+    assert_eq!(doc.get_object((5, 0)).unwrap(), &Object::Null);
+    assert_eq!(doc.get_object((6, 0)).unwrap(), &Object::Null);
+    assert!(doc.get_dictionary((1, 0)).unwrap().has(b"Linearized"));
+    assert_eq!(doc.max_id, 6);
+
+    let metadata = Document::load_metadata_mem(&buffer).unwrap();
+    assert_eq!(metadata.page_count, 1);
+}
+
+#[test]
+fn load_cyclic_page_tree() {
+    use crate::RepairAction;
+    use std::io::Write;
+
+    let mut buffer = b"%PDF-1.5\n".to_vec();
+    let mut offsets = Vec::new();
+    let bodies: [&[u8]; 6] = [
+        b"<< /Type /Catalog /Pages 2 0 R >>",
+        b"<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 3 >>",
+        b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 10 10] >>",
+        // The intermediate node lists the root as its kid, next to its two pages.
+        b"<< /Type /Pages /Parent 2 0 R /Kids [5 0 R 2 0 R 6 0 R] /Count 2 >>",
+        b"<< /Type /Page /Parent 4 0 R /MediaBox [0 0 10 10] >>",
+        b"<< /Type /Page /Parent 4 0 R /MediaBox [0 0 10 10] >>",
+    ];
+    for (id, body) in (1..).zip(bodies) {
+        offsets.push(buffer.len());
+        writeln!(buffer, "{id} 0 obj").unwrap();
+        buffer.extend(body);
+        buffer.extend(b"\nendobj\n");
+    }
+    let xref_start = buffer.len();
+    write!(buffer, "xref\n0 7\n0000000000 65535 f \n").unwrap();
+    for offset in &offsets {
+        writeln!(buffer, "{offset:010} 00000 n ").unwrap();
+    }
+    write!(buffer, "trailer\n<< /Size 7 /Root 1 0 R >>\nstartxref\n{xref_start}\n%%EOF\n").unwrap();
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(
+        doc.repairs,
+        [RepairAction::RemovedPageTreeCycle {
+            parent: (4, 0),
+            kid: (2, 0)
+        }]
+    );
+    let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+    assert_eq!(pages, [(3, 0), (5, 0), (6, 0)]);
+    assert_eq!(doc.get_dictionary((4, 0)).unwrap().get(b"Kids").unwrap().as_array().unwrap().len(), 2);
+
+    let metadata = Document::load_metadata_mem(&buffer).unwrap();
+    assert_eq!(metadata.page_count, 3);
+    assert!(!metadata.page_count_is_estimate);
+}
+
+#[test]
+fn metadata_page_count_walks_the_tree_only_without_a_valid_count() {
+    let page = || Object::from(dictionary! { "Type" => "Page", "Parent" => (2, 0) });
+    let page_count = |count: Object| {
+        let kids = vec![(3, 0).into(), (4, 0).into(), (5, 0).into()];
+        let objects = [
+            (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+            (2, dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => count }.into()),
+            (3, page()),
+            // A kid that is neither a page nor a page tree node.
+            (4, dictionary! { "Type" => "Annot", "Subtype" => "Text" }.into()),
+            (5, page()),
+        ];
+        let (buffer, offsets) = assemble_objects(&objects);
+        Document::load_metadata_mem(&with_xref_table(buffer, &offsets)).unwrap().page_count
+    };
+
+    assert_eq!(page_count(4.into()), 4);
+    assert_eq!(page_count(Object::Null), 2);
+    assert_eq!(page_count((-1).into()), 2);
+    assert_eq!(page_count(1_000_000.into()), 2);
+}
+
+#[test]
+fn load_with_recovery_rebuilds_corrupt_xref() {
+    use crate::creator::tests::create_document_with_texts;
+    use crate::xref::XrefType;
+
+    let mut doc = create_document_with_texts(&["one", "two"]);
+    doc.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    // /startxref points into the middle of a content stream.
+    let into_stream = buffer.windows(7).position(|w| w == b"stream\n").unwrap() + 9;
+    let mut into_stream_file = buffer.clone();
+    let startxref = Reader::search_substring(&into_stream_file, b"startxref", 0).unwrap();
+    into_stream_file.truncate(startxref);
+    into_stream_file.extend(format!("startxref\n{into_stream}\n%%EOF\n").as_bytes());
+    // Bytes inserted after the header shift all the objects away from their offsets.
+    let mut shifted_file = buffer.clone();
+    let header_end = shifted_file.iter().position(|&c| c == b'\n').unwrap() + 1;
+    shifted_file.splice(header_end..header_end, b"%".iter().chain(&[b' '; 64]).chain(b"\n").copied());
+
+    for file in [into_stream_file, shifted_file] {
+        assert!(Document::load_mem(&file).is_err_and(|err| matches!(err, Error::Xref(_) | Error::Parse(_))));
+        let recovered = Document::load_mem_with_recovery(&file).unwrap();
+        let pages: Vec<u32> = recovered.get_pages().into_keys().collect();
+        assert_eq!(pages, [1, 2]);
+        assert_eq!(recovered.extract_text(&[2]).unwrap(), "two\n");
+        assert_eq!(recovered.trailer.get(b"Root").unwrap(), doc.trailer.get(b"Root").unwrap());
+    }
+
+    // A usable table is read as is.
+    let xref_start = Reader::get_xref_start(&buffer).unwrap();
+    assert_eq!(Document::load_mem_with_recovery(&buffer).unwrap().xref_start, xref_start);
+}
+
+#[test]
+fn load_truncated_file() {
+    let buffer = include_bytes!("../../assets/example.pdf");
+    let doc = Document::load_mem(buffer).unwrap();
+
+    // Cut off after the last object, losing the table, the trailer and startxref.
+    let last_object_end = buffer.windows(6).rposition(|w| w == b"endobj").unwrap() + 6;
+    let truncated = Document::load_mem(&buffer[..last_object_end]).unwrap();
+    assert_eq!(truncated.get_pages().len(), doc.get_pages().len());
+    assert_eq!(truncated.objects, doc.objects);
+
+    // An update cut off before its own table leaves the last startxref far from the end.
+    let mut updated = buffer.to_vec();
+    let padding = " ".repeat(1000);
+    updated.extend(format!("7 0 obj\n<< /Padding ({padding}) >>\nendobj\n").as_bytes());
+    let loaded = Document::load_mem(&updated).unwrap();
+    assert_eq!(loaded.xref_start, Reader::get_xref_start(buffer).unwrap());
+    assert_eq!(loaded.get_pages().len(), 1);
+    let ignored = updated.len() - buffer.len();
+    assert_eq!(
+        loaded.load_warnings(),
+        [LoadWarning::UnfinishedUpdate {
+            revision_end: buffer.len(),
+            ignored
+        }]
+    );
+    assert_eq!(loaded.truncated_bytes(), Some(ignored));
+
+    // The last startxref pointing nowhere, an earlier one is used.
+    updated.extend(b"startxref\n100\n%%EOF\n");
+    let loaded = Document::load_mem(&updated).unwrap();
+    assert_eq!(loaded.get_pages().len(), 1);
+    assert!(
+        matches!(
+            loaded.load_warnings(),
+            [LoadWarning::UnreadableXref { offset: 100, .. }, LoadWarning::UnfinishedUpdate { .. }]
+        ),
+        "{:?}",
+        loaded.load_warnings()
+    );
+}
+
+#[test]
+fn load_file_cut_off_in_last_update() {
+    let buffer = include_bytes!("../../assets/Incremental.pdf");
+    let revision_ends: Vec<usize> = buffer
+        .windows(5)
+        .enumerate()
+        .filter(|(_, w)| w == b"%%EOF")
+        .map(|(pos, _)| pos + 5 + buffer[pos + 5..].iter().take_while(|&&c| matches!(c, b'\r' | b'\n')).count())
+        .collect();
+    let [_, previous_end, _] = revision_ends[..] else {
+        panic!("the fixture has three revisions: {revision_ends:?}");
+    };
+    let previous = Document::load_mem(&buffer[..previous_end]).unwrap();
+    assert!(previous.load_warnings().is_empty());
+    assert_eq!(previous.truncated_bytes(), None);
+    assert_eq!(Document::load_mem(buffer).unwrap().truncated_bytes(), None);
+
+    // Cut off in the middle of the objects of the last update, and in its cross-reference stream.
+    let last_xref = buffer.windows(11).rposition(|w| w == b"/Type /XRef").unwrap();
+    for cut in [previous_end + (last_xref - previous_end) / 2, last_xref + 40] {
+        let truncated = Document::load_mem(&buffer[..cut]).unwrap();
+        assert_eq!(truncated.truncated_bytes(), Some(cut - previous_end));
+        assert_eq!(truncated.objects, previous.objects);
+        assert_eq!(truncated.trailer, previous.trailer);
+        assert_eq!(truncated.get_pages(), previous.get_pages());
+    }
+}
+
+/// A file whose catalog holds a literal string nesting parentheses `depth` levels deep.
+fn deeply_nested_catalog(depth: usize) -> Vec<u8> {
+    use std::io::Write;
+
+    let nested: Vec<u8> = std::iter::repeat_n(b'(', depth).chain(std::iter::repeat_n(b')', depth)).collect();
+    let mut catalog = b"<< /Type /Catalog /Pages 2 0 R /Note ".to_vec();
+    catalog.extend(&nested);
+    catalog.extend(b" >>");
+    let bodies: [&[u8]; 3] = [
+        &catalog,
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 10 10] >>",
+    ];
+    let mut buffer = b"%PDF-1.5\n".to_vec();
+    let mut offsets = Vec::new();
+    for (id, body) in (1..).zip(bodies) {
+        offsets.push(buffer.len());
+        writeln!(buffer, "{id} 0 obj").unwrap();
+        buffer.extend(body);
+        buffer.extend(b"\nendobj\n");
+    }
+    with_xref_table(buffer, &offsets)
+}
+
+#[test]
+fn load_with_raised_bracket_limit() {
+    let buffer = deeply_nested_catalog(MAX_BRACKET + 20);
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert!(doc.catalog().is_err());
+    let strict = ReaderOptions::builder().skip_unparsable_objects(false).build();
+    assert!(matches!(
+        Document::load_mem_with_options(&buffer, strict),
+        Err(Error::IndirectObject { offset: 9 })
+    ));
+
+    let options = ReaderOptions::builder().max_bracket(MAX_BRACKET + 50).build();
+    let doc = Document::load_mem_with_options(&buffer, options).unwrap();
+    let note = doc.catalog().unwrap().get(b"Note").and_then(Object::as_str).unwrap();
+    assert_eq!(note.len(), 2 * (MAX_BRACKET + 19));
+    assert_eq!(doc.get_pages().len(), 1);
+}
+
+#[test]
+fn load_with_nesting_depth_limit() {
+    let buffer = deeply_nested_catalog(1);
+    assert_eq!(ReaderOptions::default(), ReaderOptions::builder().build());
+    // The catalog holds no arrays or dictionaries, the page holds an array.
+    let options = ReaderOptions::builder().max_nesting_depth(Some(1)).build();
+    let doc = Document::load_mem_with_options(&buffer, options).unwrap();
+    assert!(doc.catalog().is_ok());
+    assert!(doc.get_object((3, 0)).is_err());
+    let options = ReaderOptions::builder().max_nesting_depth(Some(2)).build();
+    let doc = Document::load_mem_with_options(&buffer, options).unwrap();
+    assert_eq!(doc.get_pages().len(), 1);
+}
+
+#[test]
+fn load_with_xref_section_limit() {
+    let buffer = deeply_nested_catalog(1);
+    let mut incremental: crate::IncrementalDocument = buffer.as_slice().try_into().unwrap();
+    incremental.new_document.add_object(Object::Null);
+    let mut updated = Vec::new();
+    incremental.save_to(&mut updated).unwrap();
+
+    let options = ReaderOptions::builder().max_xref_sections(Some(1)).build();
+    assert!(matches!(
+        Document::load_mem_with_options(&updated, options),
+        Err(Error::Xref(XrefError::TooManySections(1)))
+    ));
+    let options = ReaderOptions::builder().max_xref_sections(Some(2)).build();
+    assert_eq!(Document::load_mem_with_options(&updated, options).unwrap().revisions.len(), 2);
+}
+
+#[test]
+fn load_warnings_report_skipped_objects() {
+    use std::io::Write;
+
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, dictionary! { "Type" => "Pages", "Kids" => vec![(3, 0).into()], "Count" => 1 }.into()),
+        (3, dictionary! { "Type" => "Page", "Parent" => (2, 0) }.into()),
+    ];
+    let (mut buffer, mut offsets) = assemble_objects(&objects);
+    let doc = Document::load_mem(&with_xref_table(buffer.clone(), &offsets)).unwrap();
+    assert!(doc.load_warnings().is_empty());
+
+    offsets.push(buffer.len());
+    writeln!(buffer, "4 0 obj\n<< /Broken ) >>\nendobj").unwrap();
+    let buffer = with_xref_table(buffer, &offsets);
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(doc.get_pages().len(), 1);
+    assert!(!doc.has_object((4, 0)));
+    assert!(
+        matches!(
+            doc.load_warnings(),
+            [LoadWarning::SkippedObject { offset, id: (4, 0), .. }] if *offset == offsets[3]
+        ),
+        "{:?}",
+        doc.load_warnings()
+    );
+
+    let buffer = String::from_utf8(buffer).unwrap().replace("/Size 5", "/Size 3");
+    let doc = Document::load_mem(buffer.as_bytes()).unwrap();
+    assert_eq!(doc.load_warnings()[0], LoadWarning::TrailerSizeMismatch { declared: 3, actual: 5 });
+    assert_eq!(doc.load_warnings().len(), 2);
+}
+
+#[test]
+fn strict_reading_rejects_truncated_stream() {
+    use std::io::Write;
+
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, dictionary! { "Type" => "Pages", "Kids" => vec![(3, 0).into()], "Count" => 1 }.into()),
+        (3, dictionary! { "Type" => "Page", "Parent" => (2, 0), "Contents" => (4, 0) }.into()),
+    ];
+    let (mut buffer, mut offsets) = assemble_objects(&objects);
+    let strict = || ReaderOptions::builder().strict(true).build();
+    let clean = with_xref_table(buffer.clone(), &offsets);
+    let doc = Document::load_mem_with_options(&clean, strict()).unwrap();
+    assert_eq!(doc.get_pages().len(), 1);
+
+    // The data was cut short of its /Length.
+    offsets.push(buffer.len());
+    writeln!(buffer, "4 0 obj\n<< /Length 50 >>\nstream\nBT ET\nendstream\nendobj").unwrap();
+    let buffer = with_xref_table(buffer, &offsets);
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(doc.get_pages().len(), 1);
+
+    let result = Document::load_mem_with_options(&buffer, strict());
+    assert!(
+        matches!(
+            &result,
+            Err(Error::Strict(LoadWarning::StreamLengthMismatch {
+                id: (4, 0),
+                offset,
+                declared: 50,
+                actual: Some(5),
+            })) if *offset == offsets[3]
+        ),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn load_stream_with_short_length() {
+    use std::io::Write;
+
+    let font = dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica" };
+    let page = |contents: u32| {
+        dictionary! {
+            "Type" => "Page",
+            "Parent" => (2, 0),
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => (3, 0) } },
+            "Contents" => (contents, 0),
+        }
+    };
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, dictionary! { "Type" => "Pages", "Kids" => vec![(4, 0).into(), (5, 0).into()], "Count" => 2 }.into()),
+        (3, font.into()),
+        (4, page(6).into()),
+        (5, page(7).into()),
+    ];
+    let (mut buffer, mut offsets) = assemble_objects(&objects);
+    // Both /Length entries are 10 bytes short, the second through a reference.
+    let content = b"BT /F1 12 Tf 10 10 Td (Hello there) Tj ET";
+    let length = content.len() as i64 - 10;
+    for (id, length) in [(6, length.to_string()), (7, "8 0 R".to_string())] {
+        offsets.push(buffer.len());
+        write!(buffer, "{id} 0 obj\n<< /Length {length} >>\nstream\n").unwrap();
+        buffer.extend_from_slice(content);
+        buffer.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+    offsets.push(buffer.len());
+    writeln!(buffer, "8 0 obj\n{length}\nendobj").unwrap();
+    let buffer = with_xref_table(buffer, &offsets);
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    for id in [(6, 0), (7, 0)] {
+        assert_eq!(doc.get_object(id).and_then(Object::as_stream).unwrap().content, content);
+    }
+    assert_eq!(doc.extract_text(&[1]).unwrap().trim(), "Hello there");
+    assert_eq!(doc.extract_text(&[2]).unwrap().trim(), "Hello there");
+    let mismatches: Vec<_> = doc
+        .load_warnings()
+        .iter()
+        .filter_map(|warning| match *warning {
+            LoadWarning::StreamLengthMismatch { id, declared, actual, .. } => Some((id, declared, actual)),
+            _ => None,
+        })
+        .collect();
+    let actual = Some(content.len());
+    assert_eq!(mismatches, [((6, 0), length, actual), ((7, 0), length, actual)]);
+}
+
+#[test]
+fn load_catalog_pointing_at_single_page() {
+    let font = dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica" };
+    let content = Stream::new(dictionary! {}, b"BT /F1 12 Tf 10 10 Td (Hello there) Tj ET".to_vec());
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        // No /Type, as in some files, and no page tree node above it.
+        (
+            2,
+            dictionary! {
+                "MediaBox" => vec![0.into(), 0.into(), 200.into(), 100.into()],
+                "Resources" => dictionary! { "Font" => dictionary! { "F1" => (4, 0) } },
+                "Contents" => (3, 0),
+            }
+            .into(),
+        ),
+        (3, Object::Stream(content)),
+        (4, font.into()),
+    ];
+    let (buffer, offsets) = assemble_objects(&objects);
+    let buffer = with_xref_table(buffer, &offsets);
+
+    assert_eq!(Document::load_metadata_mem(&buffer).unwrap().page_count, 1);
+    assert_eq!(LazyDocument::load_mem(&buffer).unwrap().get_pages().len(), 1);
+    let mut doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(doc.get_pages(), BTreeMap::from([(1, (2, 0))]));
+    assert_eq!(doc.extract_text(&[1]).unwrap().trim(), "Hello there");
+
+    let pages_id = doc.normalize_page_tree().unwrap();
+    assert_eq!(doc.normalize_page_tree(), None);
+    let mut saved = Vec::new();
+    doc.save_to(&mut saved).unwrap();
+    let doc = Document::load_mem(&saved).unwrap();
+    let pages = doc.get_dictionary(pages_id).unwrap();
+    assert_eq!(pages.get(b"Count").and_then(Object::as_i64).unwrap(), 1);
+    assert_eq!(doc.get_pages(), BTreeMap::from([(1, (2, 0))]));
+    assert!(doc.get_dictionary((2, 0)).unwrap().has_type(b"Page"));
+}
+
+#[test]
+fn load_selected_pages() {
+    let mut doc = crate::creator::tests::create_document_with_texts(&["one", "two", "three"]);
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    let full = Document::load_mem(&buffer).unwrap();
+    let contents = |doc: &Document, page: ObjectId| doc.get_dictionary(page).unwrap().get(b"Contents").unwrap().clone();
+    let full_pages = full.get_pages();
+
+    let mut doc = Document::load_pages_mem(&buffer, &[3, 2, 3]).unwrap();
+    let pages = doc.get_pages();
+    assert_eq!(pages.values().copied().collect::<Vec<_>>(), [full_pages[&2], full_pages[&3]]);
+    assert_eq!(doc.extract_text(&[1, 2]).unwrap(), full.extract_text(&[2, 3]).unwrap());
+    // The content of the first page and the info dictionary weren't loaded.
+    let first_contents = contents(&full, full_pages[&1]).as_reference().unwrap();
+    assert!(!doc.objects.contains_key(&first_contents));
+    assert!(doc.trailer.get(b"Info").is_err());
+    let info = full.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+    let unused = [info, full_pages[&1], first_contents];
+    let expected: Vec<_> = (full.objects.iter())
+        .filter(|(id, object)| !unused.contains(id) && !object.as_stream().is_ok_and(|s| s.dict.has_type(b"XRef")))
+        .map(|(id, _)| *id)
+        .collect();
+    assert_eq!(doc.objects.keys().copied().collect::<Vec<_>>(), expected);
+
+    let mut saved = Vec::new();
+    doc.save_to(&mut saved).unwrap();
+    let reloaded = Document::load_mem(&saved).unwrap();
+    assert_eq!(reloaded.extract_text(&[1, 2]).unwrap(), full.extract_text(&[2, 3]).unwrap());
+    assert_eq!(contents(&reloaded, reloaded.get_pages()[&1]), contents(&full, full_pages[&2]));
+
+    assert!(matches!(Document::load_pages_mem(&buffer, &[4]), Err(Error::PageNumberNotFound(4))));
+}
+
+#[test]
+fn load_objects_listed_at_wrong_offsets() {
+    let mut data = b"<< /Type /Fake >>\n7 0 obj\n".to_vec();
+    data.extend(b"% filler\n".repeat(600));
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, dictionary! { "Type" => "Pages", "Kids" => vec![(3, 0).into()], "Count" => 1 }.into()),
+        (3, dictionary! { "Type" => "Page", "Parent" => (2, 0), "Contents" => (4, 0) }.into()),
+        (4, Stream::new(dictionary! {}, data).into()),
+        (5, dictionary! { "Marker" => "five" }.into()),
+    ];
+    let (buffer, mut offsets) = assemble_objects(&objects);
+    let actual = offsets.clone();
+    let find = |needle: &[u8]| buffer.windows(needle.len()).position(|window| window == needle).unwrap();
+    // Object 3 is listed in the middle of the stream data, where no object header is; object 5 at
+    // an object header inside the stream data, further from its own than the nearby search reaches.
+    offsets[2] = find(b"<< /Type /Fake");
+    offsets[4] = find(b"7 0 obj");
+    let file = with_xref_table(buffer, &offsets);
+    let doc = Document::load_mem(&file).unwrap();
+
+    assert_eq!(doc.get_pages().len(), 1);
+    assert_eq!(doc.get_dictionary((3, 0)).unwrap().get(b"Contents").unwrap(), &Object::Reference((4, 0)));
+    assert_eq!(doc.get_dictionary((5, 0)).unwrap().get(b"Marker").unwrap().as_name().unwrap(), b"five");
+    assert!(!doc.has_object((7, 0)));
+    assert_eq!(
+        doc.load_warnings(),
+        [
+            LoadWarning::RelocatedObject {
+                id: (3, 0),
+                listed: offsets[2],
+                found: actual[2],
+            },
+            LoadWarning::RelocatedObject {
+                id: (5, 0),
+                listed: offsets[4],
+                found: actual[4],
+            },
+        ]
+    );
+    assert!(offsets[4].abs_diff(actual[4]) > 4096);
+
+    // The object headers of the file are scanned once, and kept for other objects to relocate.
+    let mut reader = Reader::new(&file, ReaderOptions::default());
+    reader.document.reference_table = reader.read_xref_sections().unwrap().0;
+    assert!(reader.object_headers.get().is_none());
+    let five = reader.get_object((5, 0), &mut HashSet::new()).unwrap();
+    assert_eq!(five.as_dict().unwrap().get(b"Marker").unwrap().as_name().unwrap(), b"five");
+    assert!(reader.object_headers.get().is_some());
+}
+
+#[test]
+fn malformed_numbers_read_as_zero_unless_strict() {
+    use std::io::Write;
+
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, dictionary! { "Type" => "Pages", "Kids" => vec![(3, 0).into()], "Count" => 1 }.into()),
+    ];
+    let (mut buffer, mut offsets) = assemble_objects(&objects);
+    offsets.push(buffer.len());
+    writeln!(buffer, "3 0 obj\n<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612.0.0 --792] >>\nendobj").unwrap();
+    let buffer = with_xref_table(buffer, &offsets);
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    let page = doc.get_dictionary((3, 0)).unwrap();
+    let media_box = page.get(b"MediaBox").and_then(Object::as_array).unwrap();
+    assert_eq!(media_box, &[0.into(), 0.into(), 0.into(), 0.into()]);
+
+    let strict = ReaderOptions::builder().strict(true).build();
+    let result = Document::load_mem_with_options(&buffer, strict);
+    assert!(
+        matches!(&result, Err(Error::Strict(LoadWarning::SkippedObject { id: (3, 0), .. }))),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn load_filter_closure_strips_images() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut doc = crate::creator::tests::create_document();
+    let image = || Stream::new(dictionary! { "Type" => "XObject", "Subtype" => "Image" }, vec![0; 16]);
+    let image_ids = [doc.add_object(image()), doc.add_object(image())];
+    let form_id = doc.add_object(Stream::new(dictionary! { "Subtype" => "Form" }, b"0 0 m".to_vec()));
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    let removed = AtomicUsize::new(0);
+    let strip: &[&[u8]] = &[b"Image"];
+    let filter = |id, object: &mut Object| {
+        let subtype = object.as_stream().ok().and_then(|stream| stream.dict.get(b"Subtype").ok());
+        if subtype.and_then(|subtype| subtype.as_name().ok()).is_some_and(|name| strip.contains(&name)) {
+            removed.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some((id, object.clone()))
+    };
+    let reader = Reader::new(&buffer, ReaderOptions::default());
+    let loaded = reader.read(Some(&filter)).unwrap();
+    assert_eq!(removed.load(Ordering::Relaxed), 2);
+    assert!(image_ids.iter().all(|id| !loaded.has_object(*id)));
+    assert!(loaded.has_object(form_id));
+    assert_eq!(loaded.get_pages().len(), 1);
+}
+
+#[test]
+fn load_hybrid_file_with_xref_stream_in_last_section() {
+    // Linearized for fast web view: the first-page section read first is a table whose /XRefStm
+    // stream alone lists the font, which the table marks free.
+    let buffer = include_bytes!("../../assets/Hybrid.pdf");
+    let doc = Document::load_mem(buffer).unwrap();
+    assert!(!doc.trailer.has(b"XRefStm"));
+    assert!(doc.load_warnings().is_empty(), "{:?}", doc.load_warnings());
+    assert!(matches!(doc.reference_table.get(15), Some(XrefEntry::Compressed { container: 14, index: 0 })));
+    assert_eq!(doc.get_pages().len(), 1);
+    let font = doc.get_dictionary((15, 0)).unwrap();
+    assert_eq!(font.get(b"BaseFont").unwrap().as_name().unwrap(), b"Helvetica");
+    assert_eq!(doc.extract_text(&[1]).unwrap().trim(), "Fast web view");
+
+    let metadata = Document::load_metadata_mem(buffer).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Hybrid reference"));
+    assert_eq!(metadata.page_count, 1);
+
+    let options = ReaderOptions::builder().follow_xref_stm(false).build();
+    let doc = Document::load_mem_with_options(buffer, options).unwrap();
+    assert!(matches!(doc.reference_table.get(15), Some(XrefEntry::Free { .. })));
+}
+
+#[test]
+fn load_hybrid_file_with_unreadable_xref_stream() {
+    let file = include_bytes!("../../assets/Hybrid.pdf");
+    let at = memchr::memmem::find(file, b"/XRefStm 977").unwrap();
+    let mut buffer = file.to_vec();
+    buffer[at..at + 12].copy_from_slice(b"/XRefStm 970");
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert!(
+        matches!(doc.load_warnings(), [LoadWarning::UnreadableXrefStream { offset: 970, .. }]),
+        "{:?}",
+        doc.load_warnings()
+    );
+    assert!(matches!(doc.reference_table.get(15), Some(XrefEntry::Free { .. })));
+    assert_eq!(doc.get_pages().len(), 1);
+
+    let options = ReaderOptions::builder().strict(true).build();
+    assert!(Document::load_mem_with_options(&buffer, options).is_err());
+}
+
+/// Replace the strings in `object` with nulls.
+fn redact(object: &mut Object) {
+    match object {
+        Object::String(..) => *object = Object::Null,
+        Object::Array(array) => array.iter_mut().for_each(redact),
+        Object::Dictionary(dict) => dict.iter_mut().for_each(|(_, value)| redact(value)),
+        Object::Stream(stream) => stream.dict.iter_mut().for_each(|(_, value)| redact(value)),
+        _ => {}
+    }
+}
+
+fn has_strings(object: &Object) -> bool {
+    match object {
+        Object::String(..) => true,
+        Object::Array(array) => array.iter().any(has_strings),
+        Object::Dictionary(dict) => dict.iter().any(|(_, value)| has_strings(value)),
+        Object::Stream(stream) => stream.dict.iter().any(|(_, value)| has_strings(value)),
+        _ => false,
+    }
+}
+
+/// Load `buffer` through a redacting filter, returning the document and the contexts it was called
+/// with.
+fn load_redacted(buffer: &[u8]) -> (Document, Vec<FilterContext>) {
+    let contexts = Mutex::new(Vec::new());
+    let object_filter = |context: &FilterContext, object: &mut Object| {
+        contexts.lock().unwrap().push(*context);
+        redact(object);
+        Some((context.id, object.clone()))
+    };
+    let trailer_filter = |trailer: &mut Dictionary| trailer.iter_mut().for_each(|(_, value)| redact(value));
+    let filters = LoadFilters {
+        object: Some(&object_filter),
+        trailer: Some(&trailer_filter),
+    };
+    let doc = Document::load_mem_with_filters(buffer, filters).unwrap();
+    (doc, contexts.into_inner().unwrap())
+}
+
+#[test]
+fn redacting_filter_leaves_no_strings() {
+    let mut doc = crate::creator::tests::create_document();
+    let info_id = doc.add_object(dictionary! {
+        "Title" => Object::string_literal("Quarterly salaries"),
+        "Author" => Object::string_literal("J. Doe"),
+    });
+    doc.trailer.set("Info", info_id);
+    doc.trailer.set(
+        "ID",
+        vec![Object::string_literal("0123456789abcdef"), Object::string_literal("0123456789abcdef")],
+    );
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    assert!(!buffer.windows(6).any(|window| window == b"ObjStm"));
+
+    let (doc, contexts) = load_redacted(&buffer);
+    assert!(doc.objects.contains_key(&info_id));
+    assert!(!doc.objects.values().any(has_strings));
+    assert!(!has_strings(&Object::Dictionary(doc.trailer.clone())));
+    assert!(contexts.iter().all(|context| context.origin == ObjectOrigin::TopLevel));
+    for id in doc.objects.keys() {
+        let context = contexts.iter().find(|context| context.id == *id).unwrap();
+        let Some(XrefEntry::Normal { offset, .. }) = doc.reference_table.get(id.0) else {
+            panic!("{id:?} isn't listed");
+        };
+        assert_eq!(*offset, context.offset);
+    }
+}
+
+#[test]
+fn redacting_filter_sees_decrypted_object_stream_members() {
+    let buffer = include_bytes!("../../assets/encrypted.pdf");
+    let unfiltered = Document::load_mem(buffer).unwrap();
+    assert!(unfiltered.objects.values().any(has_strings));
+    let (doc, contexts) = load_redacted(buffer);
+    assert!(!doc.objects.values().any(has_strings));
+    assert!(!has_strings(&Object::Dictionary(doc.trailer.clone())));
+    assert_eq!(doc.objects.len(), unfiltered.objects.len());
+    for id in doc.objects.keys() {
+        assert!(contexts.iter().any(|context| context.id == *id), "{id:?} wasn't filtered");
+    }
+    let (container, member) = unfiltered
+        .reference_table
+        .entries
+        .iter()
+        .find_map(|(&id, entry)| match *entry {
+            XrefEntry::Compressed { container, .. } => Some(((container, 0), id)),
+            _ => None,
+        })
+        .unwrap();
+    let context = contexts.iter().find(|context| context.id.0 == member).unwrap();
+    assert_eq!(context.origin, ObjectOrigin::ObjectStream(container));
+    let container_context = contexts.iter().find(|context| context.id == container).unwrap();
+    assert_eq!(context.offset, container_context.offset);
+}
+
+#[test]
+fn filtering_out_the_catalog_fails() {
+    let buffer = include_bytes!("../../assets/example.pdf");
+    let object_filter = |context: &FilterContext, object: &mut Object| {
+        let is_catalog = object.as_dict().is_ok_and(|dict| dict.has_type(b"Catalog"));
+        (!is_catalog).then(|| (context.id, object.clone()))
+    };
+    let filters = LoadFilters {
+        object: Some(&object_filter),
+        trailer: None,
+    };
+    assert!(matches!(Document::load_mem_with_filters(buffer, filters), Err(Error::MissingCatalog)));
+
+    let trailer_filter = |trailer: &mut Dictionary| {
+        trailer.remove(b"Root");
+    };
+    let filters = LoadFilters {
+        object: None,
+        trailer: Some(&trailer_filter),
+    };
+    assert!(matches!(Document::load_mem_with_filters(buffer, filters), Err(Error::MissingCatalog)));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn filter_drops_fonts_from_encrypted_document() {
+    let is_font = |object: &Object| object.as_dict().is_ok_and(|dict| dict.has_type(b"Font"));
+    let unfiltered = Document::load("assets/encrypted.pdf").unwrap();
+    assert!(unfiltered.objects.values().any(is_font));
+
+    // The filter is given each object once it's decrypted, as it's kept in the document.
+    let seen = Mutex::new(BTreeMap::new());
+    let doc = Document::load_filtered("assets/encrypted.pdf", |id, object| {
+        seen.lock().unwrap().insert(id, object.clone());
+        (!is_font(object)).then(|| (id, object.clone()))
+    })
+    .unwrap();
+    let seen = seen.into_inner().unwrap();
+    assert!(seen.values().any(|object| object.as_stream().is_ok()));
+    for (id, object) in &seen {
+        assert_eq!(unfiltered.objects.get(id), Some(object), "object {id:?} given to the filter");
+    }
+
+    assert!(doc.encryption_state.is_some());
+    assert!(!doc.objects.values().any(is_font));
+    let pages = doc.get_pages();
+    assert_eq!(pages.len(), unfiltered.get_pages().len());
+    for page_id in pages.into_values() {
+        assert!(doc.get_dictionary(page_id).unwrap().has_type(b"Page"));
+    }
+}
+
+#[test]
+fn generation_mismatch_in_xref_is_tolerated() {
+    let content = b"BT /F1 12 Tf 72 712 Td (Hello) Tj ET".to_vec();
+    let objects = [
+        (1, dictionary! { "Type" => "Catalog", "Pages" => (2, 0) }.into()),
+        (2, dictionary! { "Type" => "Pages", "Kids" => vec![(3, 0).into()], "Count" => 1 }.into()),
+        (3, dictionary! { "Type" => "Page", "Parent" => (2, 0), "Contents" => (4, 0) }.into()),
+        (4, Stream::new(dictionary! {}, content.clone()).into()),
+    ];
+    let (buffer, offsets) = assemble_objects(&objects);
+    // The table lists the page contents, `4 0 obj` in the file, with generation 1.
+    let listed = format!("{:010} 00000 n ", offsets[3]);
+    let buffer = String::from_utf8(with_xref_table(buffer, &offsets))
+        .unwrap()
+        .replace(&listed, &format!("{:010} 00001 n ", offsets[3]));
+
+    let lazy = LazyDocument::load_mem(buffer.as_bytes()).unwrap();
+    let page_id = lazy.get_pages()[&1];
+    let contents = lazy.get_dictionary(page_id).unwrap().get(b"Contents").unwrap().as_reference().unwrap();
+    assert_eq!(lazy.get_object(contents).unwrap().as_stream().unwrap().content, content);
+
+    let doc = Document::load_mem(buffer.as_bytes()).unwrap();
+    assert_eq!(doc.get_page_content(page_id).unwrap(), content);
+    assert_eq!(
+        doc.load_warnings(),
+        [LoadWarning::GenerationMismatch {
+            id: (4, 0),
+            offset: offsets[3],
+            generation: 1
+        }]
+    );
+
+    let options = ReaderOptions::builder().strict(true).build();
+    assert!(matches!(
+        Document::load_mem_with_options(buffer.as_bytes(), options),
+        Err(Error::Strict(LoadWarning::GenerationMismatch { id: (4, 0), .. }))
+    ));
+    let strict = Reader::new(buffer.as_bytes(), ReaderOptions::builder().strict(true).build());
+    assert!(matches!(strict.get_offset((4, 0)), Err(Error::MissingXrefEntry)));
+}
+
+#[test]
+fn strings_in_encrypted_object_streams_are_decrypted_once() {
+    // AES-128 encrypted, with the catalog, Info, outlines and the first outline item in an object
+    // stream, and the second item and the page content stored directly.
+    let buffer = include_bytes!("../../assets/EncryptedObjectStreams.pdf");
+    let title = |object: &Object| object.as_dict().unwrap().get(b"Title").unwrap().as_str().unwrap().to_vec();
+
+    let doc = Document::load_mem(buffer).unwrap();
+    assert!(matches!(doc.reference_table.get(9), Some(XrefEntry::Compressed { container: 10, .. })));
+    let info = doc.trailer.get(b"Info").and_then(|info| doc.dereference(info)).unwrap().1;
+    assert_eq!(title(info), b"Strings in object streams");
+    let outlines = doc.catalog().unwrap().get(b"Outlines").and_then(Object::as_reference).unwrap();
+    let first = doc.get_dictionary(outlines).unwrap().get(b"First").unwrap();
+    let (_, first) = doc.dereference(first).unwrap();
+    assert_eq!(title(first), b"Introduction");
+    let next = first.as_dict().unwrap().get(b"Next").unwrap();
+    assert_eq!(title(doc.dereference(next).unwrap().1), b"Conclusions");
+    let destination = first.as_dict().unwrap().get(b"Dest").and_then(Object::as_array).unwrap();
+    assert_eq!(destination[0].as_reference().unwrap(), doc.page_iter().next().unwrap());
+    assert_eq!(doc.extract_text(&[1]).unwrap(), "Encrypted object streams\n");
+
+    let lazy = LazyDocument::load_mem(buffer).unwrap();
+    for id in [(7, 0), (8, 0), (9, 0)] {
+        assert_eq!(lazy.get_object(id).unwrap(), doc.get_object(id).unwrap());
+    }
+    let metadata = Document::load_metadata_mem(buffer).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Strings in object streams"));
+    assert_eq!(metadata.author.as_deref(), Some("lopdf"));
+}
+
+#[test]
+fn readers_take_passwords_as_strings() {
+    let reader = Reader::new(b"", ReaderOptions::default()).with_password(String::from("secret"));
+    assert_eq!(reader.password, Some(Password::Text("secret".to_string())));
+    let reader = Reader::new(b"", ReaderOptions::default()).with_password(&b"secret"[..]);
+    assert_eq!(reader.password, Some(Password::Bytes(b"secret".to_vec())));
 }
@@ -0,0 +1,137 @@
+use crate::parser::ParseLimits;
+use crate::reader::MAX_BRACKET;
+use std::sync::Arc;
+
+use crate::{DocumentIndex, Progress, ProgressSink};
+
+/// Options for reading PDF documents, see
+/// [`Document::load_with_options`](crate::Document::load_with_options). The defaults read
+/// documents as [`Document::load`](crate::Document::load) does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReaderOptions {
+    /// Maximum nesting of parentheses in literal strings. A string nesting deeper fails to parse.
+    pub max_bracket: usize,
+
+    /// Maximum nesting of arrays and dictionaries in an object, unlimited if `None`. An object
+    /// nesting deeper fails to parse.
+    pub max_nesting_depth: Option<usize>,
+
+    /// Maximum number of cross-reference sections read by following /Prev, counting the last
+    /// section of the file, unlimited if `None`. Loading fails if the chain is longer.
+    pub max_xref_sections: Option<usize>,
+
+    /// Read the cross-reference stream named by /XRefStm in the trailer of hybrid-reference files
+    pub follow_xref_stm: bool,
+
+    /// Log objects that fail to parse and load the document without them, instead of failing
+    pub skip_unparsable_objects: bool,
+
+    /// Fail with [`Error::Strict`](crate::Error::Strict) on the first problem that would be
+    /// recorded as a [load warning](crate::Document::load_warnings), and also check that objects
+    /// end with `endobj`, that the /Length of streams ends at `endstream` and that numbers are
+    /// well-formed, where malformed ones are read as 0 otherwise. For validating files rather than
+    /// reading them as well as possible.
+    pub strict: bool,
+
+    /// Receives the progress of loading, in the [`ParseXref`](crate::Phase::ParseXref),
+    /// [`ParseObjects`](crate::Phase::ParseObjects),
+    /// [`ExpandObjectStreams`](crate::Phase::ExpandObjectStreams) and
+    /// [`ReadStreams`](crate::Phase::ReadStreams) phases, and can cancel it
+    pub progress: Option<ProgressSink>,
+
+    /// The cross-reference sections and trailer of the file, read earlier with
+    /// [`Document::build_index`](crate::Document::build_index), so they aren't read again. An index
+    /// built from another file is ignored.
+    pub index: Option<Arc<DocumentIndex>>,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        ReaderOptions {
+            max_bracket: MAX_BRACKET,
+            max_nesting_depth: None,
+            max_xref_sections: None,
+            follow_xref_stm: true,
+            skip_unparsable_objects: true,
+            strict: false,
+            progress: None,
+            index: None,
+        }
+    }
+}
+
+impl ReaderOptions {
+    /// Create a builder for ReaderOptions
+    pub fn builder() -> ReaderOptionsBuilder {
+        ReaderOptionsBuilder::default()
+    }
+
+    pub(crate) fn parse_limits(&self) -> ParseLimits {
+        ParseLimits {
+            max_bracket: self.max_bracket,
+            depth_left: self.max_nesting_depth,
+            strict: self.strict,
+        }
+    }
+}
+
+/// Builder for ReaderOptions
+#[derive(Default)]
+pub struct ReaderOptionsBuilder {
+    options: ReaderOptions,
+}
+
+impl ReaderOptionsBuilder {
+    /// Set the maximum nesting of parentheses in literal strings
+    pub fn max_bracket(mut self, value: usize) -> Self {
+        self.options.max_bracket = value;
+        self
+    }
+
+    /// Set the maximum nesting of arrays and dictionaries
+    pub fn max_nesting_depth(mut self, value: Option<usize>) -> Self {
+        self.options.max_nesting_depth = value;
+        self
+    }
+
+    /// Set the maximum number of cross-reference sections
+    pub fn max_xref_sections(mut self, value: Option<usize>) -> Self {
+        self.options.max_xref_sections = value;
+        self
+    }
+
+    /// Enable or disable reading /XRefStm cross-reference streams
+    pub fn follow_xref_stm(mut self, value: bool) -> Self {
+        self.options.follow_xref_stm = value;
+        self
+    }
+
+    /// Enable or disable skipping objects that fail to parse
+    pub fn skip_unparsable_objects(mut self, value: bool) -> Self {
+        self.options.skip_unparsable_objects = value;
+        self
+    }
+
+    /// Enable or disable strict reading
+    pub fn strict(mut self, value: bool) -> Self {
+        self.options.strict = value;
+        self
+    }
+
+    /// Set the sink receiving the progress of loading
+    pub fn progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.options.progress = Some(ProgressSink::new(progress));
+        self
+    }
+
+    /// Set the index of the file read earlier
+    pub fn index(mut self, index: DocumentIndex) -> Self {
+        self.options.index = Some(Arc::new(index));
+        self
+    }
+
+    /// Build the ReaderOptions
+    pub fn build(self) -> ReaderOptions {
+        self.options
+    }
+}
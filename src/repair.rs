@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+
+use log::warn;
+
+use crate::{Dictionary, Document, Object, ObjectId};
+
+/// A change made to a damaged document when it was loaded, see [`Document::repairs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairAction {
+    /// The page tree node `parent` listed `kid`, itself or one of its ancestors, among its /Kids.
+    /// The kid was removed from the /Kids array and the /Count of the page tree recomputed.
+    RemovedPageTreeCycle { parent: ObjectId, kid: ObjectId },
+    /// The page tree node `node` declared a /Count of `declared`, more than the `found` pages
+    /// below it. Its /Count was set to the pages found.
+    CorrectedPageCount { node: ObjectId, declared: i64, found: i64 },
+}
+
+/// Whether the object the catalog's /Pages names is a page rather than a page tree node, as some
+/// producers write single-page documents: it's typed /Page, or has /Contents and no /Kids.
+pub(crate) fn is_single_page_root(node: &Dictionary) -> bool {
+    node.has_type(b"Page") || (node.has(b"Contents") && !node.has(b"Kids"))
+}
+
+/// A page tree node being walked: its id, its kids and the index of the next kid to visit.
+struct PendingNode {
+    id: ObjectId,
+    kids: Vec<ObjectId>,
+    next: usize,
+    count: i64,
+}
+
+impl Document {
+    /// Break cycles in the page tree, where a /Pages node lists one of its ancestors as a kid, by
+    /// removing the kid. The /Count of every node is recomputed when a cycle was found, so that
+    /// it matches the pages [`Document::get_pages`] finds. Otherwise only a /Count claiming more
+    /// pages than are found is corrected, so that a broken or malicious /Count can't make callers
+    /// allocate for pages that don't exist.
+    ///
+    /// This is done when a document is loaded. Returns the removed kids and corrected counts.
+    pub fn repair_page_tree(&mut self) -> Vec<RepairAction> {
+        let Ok(root_id) = self
+            .catalog()
+            .and_then(|catalog| catalog.get(b"Pages"))
+            .and_then(Object::as_reference)
+        else {
+            return Vec::new();
+        };
+        if !self.is_page_tree_node(root_id) {
+            return Vec::new();
+        }
+
+        let mut repairs = Vec::new();
+        let mut counts: HashMap<ObjectId, i64> = HashMap::new();
+        let mut ancestors = HashSet::from([root_id]);
+        let mut stack = vec![self.pending_node(root_id)];
+        while let Some(node) = stack.last_mut() {
+            let Some(&kid) = node.kids.get(node.next) else {
+                let node = stack.pop().expect("the loop checked the stack isn't empty");
+                ancestors.remove(&node.id);
+                if let Some(parent) = stack.last_mut() {
+                    parent.count += node.count;
+                }
+                counts.insert(node.id, node.count);
+                continue;
+            };
+            node.next += 1;
+            if ancestors.contains(&kid) {
+                warn!(
+                    "page tree node {} {} lists its ancestor {} {} as a kid, removing it",
+                    node.id.0, node.id.1, kid.0, kid.1
+                );
+                repairs.push(RepairAction::RemovedPageTreeCycle { parent: node.id, kid });
+            } else if let Some(&count) = counts.get(&kid) {
+                node.count += count;
+            } else if self.is_page_tree_node(kid) {
+                ancestors.insert(kid);
+                let kid_node = self.pending_node(kid);
+                stack.push(kid_node);
+            } else if self.get_dictionary(kid).is_ok_and(|page| page.has_type(b"Page")) {
+                node.count += 1;
+            }
+        }
+
+        if repairs.is_empty() {
+            return self.correct_page_counts(&counts);
+        }
+        for repair in &repairs {
+            if let RepairAction::RemovedPageTreeCycle { parent, kid } = *repair {
+                if let Some(kids) = self.kids_mut(parent) {
+                    kids.retain(|object| object.as_reference().ok() != Some(kid));
+                }
+            }
+        }
+        for (id, count) in counts {
+            if let Ok(node) = self.get_dictionary_mut(id) {
+                node.set("Count", count);
+            }
+        }
+        repairs
+    }
+
+    /// The page the catalog's /Pages names, if it names a page rather than a page tree node.
+    pub(crate) fn single_page_root(&self) -> Option<ObjectId> {
+        let root_id = self.catalog().ok()?.get(b"Pages").and_then(Object::as_reference).ok()?;
+        self.get_dictionary(root_id)
+            .is_ok_and(is_single_page_root)
+            .then_some(root_id)
+    }
+
+    /// Wrap the page the catalog's /Pages names, as some producers write single-page documents,
+    /// in a page tree node with a /Count of 1. [`Document::get_pages`] finds the page either way,
+    /// but other readers may not. Returns the id of the new node, or `None` if the catalog names
+    /// a page tree node already.
+    pub fn normalize_page_tree(&mut self) -> Option<ObjectId> {
+        let page_id = self.single_page_root()?;
+        let pages_id = self.add_object(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+        });
+        let page = self.get_dictionary_mut(page_id).ok()?;
+        page.set("Type", "Page");
+        page.set("Parent", pages_id);
+        self.catalog_mut().ok()?.set("Pages", pages_id);
+        Some(pages_id)
+    }
+
+    /// Set the /Count of the page tree nodes declaring more pages than `counts`, the pages found
+    /// below them, to the pages found.
+    fn correct_page_counts(&mut self, counts: &HashMap<ObjectId, i64>) -> Vec<RepairAction> {
+        let mut repairs: Vec<RepairAction> = counts
+            .iter()
+            .filter_map(|(&node, &found)| {
+                let declared = self.get_dictionary(node).ok()?.get_deref(b"Count", self).ok()?.as_i64().ok()?;
+                (declared > found).then_some(RepairAction::CorrectedPageCount { node, declared, found })
+            })
+            .collect();
+        repairs.sort_by_key(|repair| match *repair {
+            RepairAction::CorrectedPageCount { node, .. } => node,
+            RepairAction::RemovedPageTreeCycle { parent, .. } => parent,
+        });
+        for repair in &repairs {
+            if let RepairAction::CorrectedPageCount { node, declared, found } = *repair {
+                warn!(
+                    "page tree node {} {} declares {declared} pages, but has {found}, correcting its /Count",
+                    node.0, node.1
+                );
+                if let Ok(node) = self.get_dictionary_mut(node) {
+                    node.set("Count", found);
+                }
+            }
+        }
+        repairs
+    }
+
+    fn is_page_tree_node(&self, id: ObjectId) -> bool {
+        self.get_dictionary(id).is_ok_and(|node| node.has_type(b"Pages"))
+    }
+
+    fn pending_node(&self, id: ObjectId) -> PendingNode {
+        let kids = self
+            .get_dictionary(id)
+            .and_then(|node| node.get_deref(b"Kids", self))
+            .and_then(Object::as_array)
+            .map(|kids| kids.iter().filter_map(|kid| kid.as_reference().ok()).collect())
+            .unwrap_or_default();
+        PendingNode {
+            id,
+            kids,
+            next: 0,
+            count: 0,
+        }
+    }
+
+    /// The /Kids array of a page tree node, whether it's direct or referenced.
+    fn kids_mut(&mut self, id: ObjectId) -> Option<&mut Vec<Object>> {
+        let kids_id = match self.get_dictionary(id).ok()?.get(b"Kids").ok()? {
+            Object::Reference(kids_id) => Some(*kids_id),
+            _ => None,
+        };
+        match kids_id {
+            Some(kids_id) => self.get_object_mut(kids_id).and_then(Object::as_array_mut).ok(),
+            None => self.get_dictionary_mut(id).ok()?.get_mut(b"Kids").and_then(Object::as_array_mut).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+
+    /// Make the second page tree node of a three-level tree list the root and itself as kids.
+    fn add_cycles(doc: &mut Document) -> (ObjectId, ObjectId) {
+        let root_id = doc.catalog().unwrap().get(b"Pages").and_then(Object::as_reference).unwrap();
+        let pages: Vec<Object> = doc.get_pages().values().map(|&id| id.into()).collect();
+        let middle_id = doc.add_object(dictionary! {
+            "Type" => "Pages",
+            "Parent" => root_id,
+            "Count" => pages.len() as i64,
+            "Kids" => pages.clone(),
+        });
+        let root = doc.get_dictionary_mut(root_id).unwrap();
+        root.set("Kids", vec![middle_id.into()]);
+        let kids = doc.get_dictionary_mut(middle_id).unwrap().get_mut(b"Kids").unwrap();
+        let kids = kids.as_array_mut().unwrap();
+        kids.insert(1, root_id.into());
+        kids.push(middle_id.into());
+        (root_id, middle_id)
+    }
+
+    #[test]
+    fn cycles_are_removed_and_counts_recomputed() {
+        let mut doc = create_document_with_texts(&["one", "two", "three"]);
+        let (root_id, middle_id) = add_cycles(&mut doc);
+        doc.get_dictionary_mut(root_id).unwrap().set("Count", 40);
+
+        let repairs = doc.repair_page_tree();
+        assert_eq!(
+            repairs,
+            [
+                RepairAction::RemovedPageTreeCycle { parent: middle_id, kid: root_id },
+                RepairAction::RemovedPageTreeCycle { parent: middle_id, kid: middle_id },
+            ]
+        );
+        assert_eq!(doc.get_pages().len(), 3);
+        for id in [root_id, middle_id] {
+            assert_eq!(doc.get_dictionary(id).unwrap().get(b"Count").unwrap().as_i64().unwrap(), 3);
+        }
+        assert!(doc.repair_page_tree().is_empty());
+    }
+
+    #[test]
+    fn overstated_counts_are_corrected() {
+        let mut doc = create_document_with_texts(&["one", "two", "three"]);
+        let root_id = doc.catalog().unwrap().get(b"Pages").and_then(Object::as_reference).unwrap();
+        let pages: Vec<Object> = doc.get_pages().values().map(|&id| id.into()).collect();
+        let middle_id = doc.add_object(dictionary! {
+            "Type" => "Pages",
+            "Parent" => root_id,
+            "Count" => i64::from(i32::MAX),
+            "Kids" => pages,
+        });
+        doc.get_dictionary_mut(root_id).unwrap().set("Kids", vec![middle_id.into()]);
+        assert_eq!(doc.page_iter().size_hint().0, doc.objects.len());
+
+        let repairs = doc.repair_page_tree();
+        assert_eq!(
+            repairs,
+            [RepairAction::CorrectedPageCount {
+                node: middle_id,
+                declared: i32::MAX.into(),
+                found: 3
+            }]
+        );
+        assert_eq!(doc.get_dictionary(middle_id).unwrap().get(b"Count").unwrap().as_i64().unwrap(), 3);
+        assert_eq!(doc.page_iter().size_hint(), (3, Some(3)));
+        assert_eq!(doc.get_pages().len(), 3);
+        assert!(doc.repair_page_tree().is_empty());
+    }
+
+    #[test]
+    fn acyclic_tree_is_left_alone() {
+        let mut doc = create_document_with_texts(&["one", "two"]);
+        let before = doc.objects.clone();
+        assert!(doc.repair_page_tree().is_empty());
+        assert_eq!(doc.objects, before);
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{BTreeMap, HashSet};
+
+use sha2::{Digest, Sha256};
+
+use crate::{Dictionary, Document, Object, ObjectId, ResourceCategory, Result, Stream};
+
+/// Keys of an image dictionary that describe its data, taken from the new image on replacement.
+const IMAGE_DATA_KEYS: [&[u8]; 12] = [
+    b"Width",
+    b"Height",
+    b"ColorSpace",
+    b"BitsPerComponent",
+    b"Filter",
+    b"DecodeParms",
+    b"Decode",
+    b"ImageMask",
+    b"Mask",
+    b"SMask",
+    b"SMaskInData",
+    b"Length",
+];
+
+/// Largest relative difference of aspect ratios not reported as a mismatch.
+const ASPECT_RATIO_TOLERANCE: f64 = 0.01;
+
+/// An image XObject of a document, as given to the predicate of [`Document::replace_image`].
+#[derive(Debug, Clone)]
+pub struct ImageInfo<'a> {
+    /// The image stream object
+    pub id: ObjectId,
+    /// Width in samples (/Width)
+    pub width: i64,
+    /// Height in samples (/Height)
+    pub height: i64,
+    /// The pages whose resources name the image, with the name, in page order
+    pub names: Vec<(ObjectId, Vec<u8>)>,
+    /// The image stream
+    pub stream: &'a Stream,
+}
+
+impl ImageInfo<'_> {
+    /// The SHA-256 of the decoded image data. Data in a format lopdf can't decode, like
+    /// `DCTDecode`, is hashed as stored.
+    pub fn pixel_hash(&self) -> [u8; 32] {
+        let data = self
+            .stream
+            .get_plain_content()
+            .unwrap_or_else(|_| self.stream.content.clone());
+        Sha256::digest(data).into()
+    }
+
+    /// Whether some page names the image `name`.
+    pub fn is_named(&self, name: &[u8]) -> bool {
+        self.names.iter().any(|(_, found)| found == name)
+    }
+}
+
+/// The image put in place of those [`Document::replace_image`] replaces.
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    /// An image XObject stream, e.g. made with [`xobject::image_from`](crate::xobject::image_from)
+    Stream(Stream),
+    /// Samples in `color_space`, row by row without padding beyond the end of each row
+    Samples {
+        width: i64,
+        height: i64,
+        color_space: Object,
+        bits_per_component: i64,
+        data: Vec<u8>,
+    },
+}
+
+impl ImageSource {
+    fn into_stream(self) -> Stream {
+        match self {
+            ImageSource::Stream(stream) => stream,
+            ImageSource::Samples {
+                width,
+                height,
+                color_space,
+                bits_per_component,
+                data,
+            } => {
+                let mut stream = Stream::new(
+                    dictionary! {
+                        "Type" => "XObject",
+                        "Subtype" => "Image",
+                        "Width" => width,
+                        "Height" => height,
+                        "ColorSpace" => color_space,
+                        "BitsPerComponent" => bits_per_component,
+                    },
+                    data,
+                );
+                // Ignore any compression error.
+                let _ = stream.compress();
+                stream
+            }
+        }
+    }
+}
+
+/// What [`Document::replace_image`] replaced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageReplacement {
+    /// The image objects replaced, each shared by all pages using it
+    pub replaced: Vec<ObjectId>,
+    /// Replaced images whose aspect ratio differs from the new image's, with their old width
+    /// and height. The placement on the page is kept, so the new image is drawn stretched.
+    pub aspect_ratio_mismatches: Vec<(ObjectId, i64, i64)>,
+}
+
+impl ImageReplacement {
+    /// The number of image objects replaced.
+    pub fn count(&self) -> usize {
+        self.replaced.len()
+    }
+}
+
+impl Document {
+    /// Replace every image XObject matching `predicate` with `new_image`, in place.
+    ///
+    /// The stream objects keep their ids, so every page and form drawing an image draws the new
+    /// one without changes to content streams, at the same placement. Entries describing the
+    /// image data, like /Width, /Filter and /SMask, come from the new image; others, like
+    /// /Metadata, stay. Soft masks and masks of other images are not matched. A replaced
+    /// image's old soft mask stays in the document until pruned with
+    /// [`Document::prune_objects`].
+    pub fn replace_image(
+        &mut self, predicate: impl Fn(&ImageInfo) -> bool, new_image: ImageSource,
+    ) -> Result<ImageReplacement> {
+        let new_image = new_image.into_stream();
+        let new_width = new_image.dict.get(b"Width").and_then(Object::as_i64)?;
+        let new_height = new_image.dict.get(b"Height").and_then(Object::as_i64)?;
+
+        let matches: Vec<(ObjectId, i64, i64)> = self
+            .images()
+            .filter(|image| predicate(image))
+            .map(|image| (image.id, image.width, image.height))
+            .collect();
+
+        let mut replacement = ImageReplacement::default();
+        for (id, width, height) in matches {
+            let Ok(Object::Stream(image)) = self.get_object_mut(id) else {
+                continue;
+            };
+            let mut dict: Dictionary = image
+                .dict
+                .iter()
+                .filter(|(key, _)| !IMAGE_DATA_KEYS.contains(&key.as_slice()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            for (key, value) in new_image.dict.iter() {
+                dict.set(key.clone(), value.clone());
+            }
+            *image = Stream::new(dict, new_image.content.clone()).with_compression(new_image.allows_compression);
+
+            replacement.replaced.push(id);
+            let ratio_difference = (width * new_height - new_width * height).abs() as f64;
+            if ratio_difference > ASPECT_RATIO_TOLERANCE * (height * new_height).abs() as f64 {
+                replacement.aspect_ratio_mismatches.push((id, width, height));
+            }
+        }
+        Ok(replacement)
+    }
+
+    /// The image XObjects of the document that aren't masks of other images, in object order.
+    fn images(&self) -> impl Iterator<Item = ImageInfo<'_>> {
+        let mut names: BTreeMap<ObjectId, Vec<(ObjectId, Vec<u8>)>> = BTreeMap::new();
+        for page_id in self.page_iter() {
+            for (name, id) in self.page_resource_ids(page_id, ResourceCategory::XObject).unwrap_or_default() {
+                names.entry(id).or_default().push((page_id, name));
+            }
+        }
+
+        let masks: HashSet<ObjectId> = self
+            .objects
+            .values()
+            .filter(|object| is_image(object))
+            .filter_map(|object| object.as_stream().ok())
+            .flat_map(|image| [image.dict.get(b"SMask"), image.dict.get(b"Mask")])
+            .filter_map(|mask| mask.and_then(Object::as_reference).ok())
+            .collect();
+
+        self.objects
+            .iter()
+            .filter(move |(id, object)| is_image(object) && !masks.contains(id))
+            .filter_map(move |(&id, object)| {
+                let stream = object.as_stream().ok()?;
+                Some(ImageInfo {
+                    id,
+                    width: stream.dict.get(b"Width").and_then(Object::as_i64).ok()?,
+                    height: stream.dict.get(b"Height").and_then(Object::as_i64).ok()?,
+                    names: names.remove(&id).unwrap_or_default(),
+                    stream,
+                })
+            })
+    }
+}
+
+fn is_image(object: &Object) -> bool {
+    let Object::Stream(stream) = object else {
+        return false;
+    };
+    stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+
+    /// A document of two pages naming image `shared` and a third page naming image `other`.
+    fn document_with_images() -> (Document, ObjectId, ObjectId) {
+        let mut doc = create_document_with_texts(&["one", "two", "three"]);
+        let image = |data: &[u8]| {
+            Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Image",
+                    "Width" => 2,
+                    "Height" => 1,
+                    "ColorSpace" => "DeviceGray",
+                    "BitsPerComponent" => 8,
+                    "Metadata" => Object::Null,
+                },
+                data.to_vec(),
+            )
+        };
+        let shared = doc.add_object(image(b"\x00\xFF"));
+        let other = doc.add_object(image(b"\x80\x80"));
+        let pages: Vec<ObjectId> = doc.page_iter().collect();
+        doc.add_xobject(pages[0], "Logo", shared).unwrap();
+        doc.add_xobject(pages[1], "Logo", shared).unwrap();
+        doc.add_xobject(pages[2], "Photo", other).unwrap();
+        (doc, shared, other)
+    }
+
+    #[test]
+    fn replace_shared_image_by_pixel_hash() {
+        let (mut doc, shared, other) = document_with_images();
+        let logo_hash: [u8; 32] = Sha256::digest(b"\x00\xFF").into();
+        let new_logo = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 4,
+                "Height" => 2,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            b"NEW-LOGO-SAMPLES".to_vec(),
+        );
+
+        let replacement = doc
+            .replace_image(|image| image.pixel_hash() == logo_hash, ImageSource::Stream(new_logo))
+            .unwrap();
+        assert_eq!(replacement.count(), 1);
+        assert_eq!(replacement.replaced, [shared]);
+        assert_eq!(replacement.aspect_ratio_mismatches, []);
+
+        let image = doc.get_object(shared).and_then(Object::as_stream).unwrap();
+        assert_eq!(image.dict.get(b"Width").unwrap(), &Object::Integer(4));
+        assert_eq!(image.dict.get(b"ColorSpace").unwrap(), &Object::from("DeviceRGB"));
+        assert!(image.dict.has(b"Metadata"));
+        assert_eq!(doc.get_object(other).and_then(Object::as_stream).unwrap().content, b"\x80\x80");
+
+        let mut saved = Vec::new();
+        doc.save_to(&mut saved).unwrap();
+        let occurrences = saved.windows(16).filter(|window| *window == b"NEW-LOGO-SAMPLES").count();
+        assert_eq!(occurrences, 1);
+        let loaded = Document::load_mem(&saved).unwrap();
+        assert_eq!(loaded.get_pages().len(), 3);
+        for page_id in loaded.page_iter().take(2) {
+            assert_eq!(
+                loaded.find_resource(page_id, ResourceCategory::XObject, shared).unwrap(),
+                Some(b"Logo".to_vec())
+            );
+        }
+    }
+
+    #[test]
+    fn replace_image_by_name_reports_aspect_ratio_mismatch() {
+        let (mut doc, _, other) = document_with_images();
+        let new_photo = ImageSource::Samples {
+            width: 2,
+            height: 2,
+            color_space: "DeviceGray".into(),
+            bits_per_component: 8,
+            data: vec![0; 4],
+        };
+        let replacement = doc.replace_image(|image| image.is_named(b"Photo"), new_photo).unwrap();
+        assert_eq!(replacement.replaced, [other]);
+        assert_eq!(replacement.aspect_ratio_mismatches, [(other, 2, 1)]);
+        assert_eq!(
+            doc.get_object(other).and_then(Object::as_stream).unwrap().dict.get(b"Height").unwrap(),
+            &Object::Integer(2)
+        );
+    }
+}
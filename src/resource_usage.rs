@@ -0,0 +1,353 @@
+use std::collections::HashSet;
+
+use crate::content::Operation;
+use crate::{ContentWalker, Dictionary, Document, Error, Object, ObjectId, Result};
+
+/// Category of a named resource, i.e. a key of a resource dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceCategory {
+    ExtGState,
+    ColorSpace,
+    Pattern,
+    Shading,
+    XObject,
+    Font,
+    Properties,
+}
+
+impl ResourceCategory {
+    pub const ALL: [ResourceCategory; 7] = [
+        ResourceCategory::ExtGState,
+        ResourceCategory::ColorSpace,
+        ResourceCategory::Pattern,
+        ResourceCategory::Shading,
+        ResourceCategory::XObject,
+        ResourceCategory::Font,
+        ResourceCategory::Properties,
+    ];
+
+    /// The key of the category in a resource dictionary.
+    pub fn key(self) -> &'static [u8] {
+        match self {
+            ResourceCategory::ExtGState => b"ExtGState",
+            ResourceCategory::ColorSpace => b"ColorSpace",
+            ResourceCategory::Pattern => b"Pattern",
+            ResourceCategory::Shading => b"Shading",
+            ResourceCategory::XObject => b"XObject",
+            ResourceCategory::Font => b"Font",
+            ResourceCategory::Properties => b"Properties",
+        }
+    }
+}
+
+/// The resource a content operation refers to by name, if any.
+fn referenced_resource(operation: &Operation) -> Option<(ResourceCategory, &[u8])> {
+    let operand = |index: usize| operation.operands.get(index).and_then(|name| name.as_name().ok());
+    match operation.operator.as_str() {
+        "Tf" => Some((ResourceCategory::Font, operand(0)?)),
+        "Do" => Some((ResourceCategory::XObject, operand(0)?)),
+        "gs" => Some((ResourceCategory::ExtGState, operand(0)?)),
+        "cs" | "CS" => Some((ResourceCategory::ColorSpace, operand(0)?)),
+        "scn" | "SCN" => Some((ResourceCategory::Pattern, operation.operands.last()?.as_name().ok()?)),
+        "sh" => Some((ResourceCategory::Shading, operand(0)?)),
+        "BDC" | "DP" => Some((ResourceCategory::Properties, operand(1)?)),
+        // The color space of an inline image, or the base of its indexed color space, may be named.
+        "BI" => {
+            let image = operation.operands.first()?.as_stream().ok()?;
+            let color_space = image.dict.get(b"CS").or_else(|_| image.dict.get(b"ColorSpace")).ok()?;
+            let name = match color_space {
+                Object::Array(indexed) => indexed.get(1)?.as_name().ok()?,
+                color_space => color_space.as_name().ok()?,
+            };
+            Some((ResourceCategory::ColorSpace, name))
+        }
+        _ => None,
+    }
+}
+
+impl Document {
+    /// List the entries of a page's own /Resources that its content never refers to.
+    ///
+    /// The content of the Form XObjects the page draws is taken into account; names resolved by
+    /// a form's own /Resources don't count as uses of the page's. Fails when the content of one
+    /// of those forms can't be decoded, as the names it uses are unknown. When the resources are
+    /// shared with other pages, their content is taken into account as well. Inherited resources
+    /// and entries that are not indirect objects are not reported.
+    pub fn unused_page_resources(&self, page_id: ObjectId) -> Result<Vec<(ResourceCategory, Vec<u8>, ObjectId)>> {
+        let Some(resources) = self.own_page_resources(page_id)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut used = HashSet::new();
+        for sharing_page_id in self.pages_sharing_resources(page_id)? {
+            let walker = ContentWalker::new(self).with_strict_forms(true);
+            walker.walk_page(sharing_page_id, |operation, context| {
+                let Some((category, name)) = referenced_resource(operation) else {
+                    return;
+                };
+                let defined_by = context.resources_defining(category.key(), name);
+                if defined_by.is_some_and(|defined_by| std::ptr::eq(defined_by, resources)) {
+                    used.insert((category, name.to_vec()));
+                }
+            })?;
+        }
+
+        let mut unused = Vec::new();
+        for category in ResourceCategory::ALL {
+            let Ok(entries) = resources.get_deref(category.key(), self).and_then(Object::as_dict) else {
+                continue;
+            };
+            for (name, value) in entries {
+                if let Ok(id) = value.as_reference() {
+                    if !used.contains(&(category, name.clone())) {
+                        unused.push((category, name.clone(), id));
+                    }
+                }
+            }
+        }
+        Ok(unused)
+    }
+
+    /// Remove the entries reported by [`Document::unused_page_resources`] from the page's
+    /// resources, so that [`Document::prune_objects`] can remove the objects.
+    /// Returns the number of entries removed.
+    pub fn strip_unused_resources(&mut self, page_id: ObjectId) -> Result<usize> {
+        let unused = self.unused_page_resources(page_id)?;
+        if unused.is_empty() {
+            return Ok(0);
+        }
+
+        let resources_id = self.get_dictionary(page_id)?.get(b"Resources")?.as_reference().ok();
+        let mut removed = 0;
+        for (category, name, _) in unused {
+            let resources = match resources_id {
+                Some(id) => self.get_dictionary(id)?,
+                None => self.get_dictionary(page_id)?.get(b"Resources")?.as_dict()?,
+            };
+            let entries_id = resources.get(category.key()).and_then(Object::as_reference).ok();
+            let entries = match (entries_id, resources_id) {
+                (Some(id), _) => self.get_dictionary_mut(id)?,
+                (None, Some(id)) => self.get_dictionary_mut(id)?.get_mut(category.key())?.as_dict_mut()?,
+                (None, None) => self
+                    .get_dictionary_mut(page_id)?
+                    .get_mut(b"Resources")?
+                    .as_dict_mut()?
+                    .get_mut(category.key())?
+                    .as_dict_mut()?,
+            };
+            if entries.remove(&name).is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Strip unused resources from all pages. Returns the number of entries removed.
+    ///
+    /// Pages whose content, or that of a form they draw, can't be decoded are left alone.
+    pub fn strip_unused_resources_all(&mut self) -> usize {
+        let page_ids: Vec<ObjectId> = self.page_iter().collect();
+        page_ids
+            .into_iter()
+            .filter_map(|page_id| self.strip_unused_resources(page_id).ok())
+            .sum()
+    }
+
+    /// The resource dictionary set on the page itself, not inherited from its ancestors.
+    fn own_page_resources(&self, page_id: ObjectId) -> Result<Option<&Dictionary>> {
+        match self.get_dictionary(page_id)?.get(b"Resources") {
+            Ok(Object::Reference(id)) => Ok(self.get_dictionary(*id).ok()),
+            Ok(Object::Dictionary(resources)) => Ok(Some(resources)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Find the name under which the resources in effect for a page refer to the object `id`.
+    pub fn find_resource(
+        &self, page_id: ObjectId, category: ResourceCategory, id: ObjectId,
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .page_resource_ids(page_id, category)?
+            .into_iter()
+            .find(|(_, value)| *value == id)
+            .map(|(name, _)| name))
+    }
+
+    /// The named resources of a category in effect for a page that are indirect objects.
+    pub(crate) fn page_resource_ids(
+        &self, page_id: ObjectId, category: ResourceCategory,
+    ) -> Result<Vec<(Vec<u8>, ObjectId)>> {
+        let Some(resources) = self.effective_page_resources(page_id)? else {
+            return Ok(Vec::new());
+        };
+        let Ok(entries) = resources.get_deref(category.key(), self).and_then(Object::as_dict) else {
+            return Ok(Vec::new());
+        };
+        Ok(entries
+            .iter()
+            .filter_map(|(name, value)| Some((name.clone(), value.as_reference().ok()?)))
+            .collect())
+    }
+
+    /// The resources in effect for a page: its own, or else those of its nearest ancestor.
+    fn effective_page_resources(&self, page_id: ObjectId) -> Result<Option<&Dictionary>> {
+        let Some(node_id) = self.page_resources_holder(page_id)? else {
+            return Ok(None);
+        };
+        Ok(self.get_dictionary(node_id)?.get_deref(b"Resources", self).and_then(Object::as_dict).ok())
+    }
+
+    /// The page or page tree node whose /Resources are in effect for a page.
+    pub(crate) fn page_resources_holder(&self, page_id: ObjectId) -> Result<Option<ObjectId>> {
+        let mut node_id = page_id;
+        let mut visited = HashSet::new();
+        while visited.insert(node_id) {
+            let node = self.get_dictionary(node_id)?;
+            if node.get_deref(b"Resources", self).and_then(Object::as_dict).is_ok() {
+                return Ok(Some(node_id));
+            }
+            match node.get(b"Parent").and_then(Object::as_reference) {
+                Ok(parent_id) => node_id = parent_id,
+                Err(_) => return Ok(None),
+            }
+        }
+        Err(Error::ReferenceCycle(node_id))
+    }
+
+    /// The page itself and any other page referencing the same resource dictionary.
+    fn pages_sharing_resources(&self, page_id: ObjectId) -> Result<Vec<ObjectId>> {
+        let Ok(resources_id) = self.get_dictionary(page_id)?.get(b"Resources").and_then(Object::as_reference) else {
+            return Ok(vec![page_id]);
+        };
+        Ok(self
+            .page_iter()
+            .filter(|id| {
+                *id == page_id
+                    || self
+                        .get_dictionary(*id)
+                        .and_then(|page| page.get(b"Resources"))
+                        .and_then(Object::as_reference)
+                        .is_ok_and(|id| id == resources_id)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stream;
+    use crate::creator::tests::create_document_with_texts;
+
+    /// Give the first page its own resources with fonts F1..=F`count` and return their ids.
+    fn add_page_fonts(doc: &mut Document, count: usize) -> (ObjectId, Vec<ObjectId>) {
+        let page_id = doc.page_iter().next().unwrap();
+        let mut fonts = Dictionary::new();
+        let mut font_ids = vec![];
+        for index in 1..=count {
+            let font_id = doc.add_object(dictionary! {
+                "Type" => "Font",
+                "Subtype" => "Type1",
+                "BaseFont" => "Courier",
+            });
+            fonts.set(format!("F{index}"), font_id);
+            font_ids.push(font_id);
+        }
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Resources", dictionary! { "Font" => fonts });
+        (page_id, font_ids)
+    }
+
+    #[test]
+    fn strips_and_prunes_unused_fonts() {
+        let mut doc = create_document_with_texts(&["Hello"]);
+        let (page_id, font_ids) = add_page_fonts(&mut doc, 3);
+
+        let unused = doc.unused_page_resources(page_id).unwrap();
+        assert_eq!(
+            unused,
+            [
+                (ResourceCategory::Font, b"F2".to_vec(), font_ids[1]),
+                (ResourceCategory::Font, b"F3".to_vec(), font_ids[2]),
+            ]
+        );
+
+        assert_eq!(doc.strip_unused_resources_all(), 2);
+        assert!(doc.unused_page_resources(page_id).unwrap().is_empty());
+        let pruned = doc.prune_objects();
+        assert!(pruned.contains(&font_ids[1]) && pruned.contains(&font_ids[2]));
+        assert!(doc.get_object(font_ids[0]).is_ok());
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "Hello\n");
+    }
+
+    #[test]
+    fn names_used_in_forms_resolve_at_their_own_level() {
+        let mut doc = create_document_with_texts(&["Hello"]);
+        let (page_id, font_ids) = add_page_fonts(&mut doc, 3);
+        // A form without resources uses the page's F2; one with its own F3 doesn't use the page's.
+        let own_font = doc.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Times" });
+        let inheriting = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Form" },
+            b"BT /F2 12 Tf (a) Tj ET".to_vec(),
+        ));
+        let self_contained = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "Resources" => dictionary! { "Font" => dictionary! { "F3" => own_font } },
+            },
+            b"BT /F3 12 Tf (b) Tj ET".to_vec(),
+        ));
+        let resources = doc.get_dictionary_mut(page_id).unwrap().get_mut(b"Resources").unwrap();
+        resources
+            .as_dict_mut()
+            .unwrap()
+            .set("XObject", dictionary! { "X1" => inheriting, "X2" => self_contained });
+        doc.add_page_contents(page_id, b"/X1 Do /X2 Do".to_vec()).unwrap();
+
+        let unused = doc.unused_page_resources(page_id).unwrap();
+        assert_eq!(unused, [(ResourceCategory::Font, b"F3".to_vec(), font_ids[2])]);
+    }
+    #[test]
+    fn undecodable_forms_leave_the_resources_alone() {
+        let mut doc = create_document_with_texts(&["Hello"]);
+        let (page_id, font_ids) = add_page_fonts(&mut doc, 2);
+        let broken = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Form" },
+            b"BT /F2 12 Tf (a) Tj ET BI /W 1 ID x EI".to_vec(),
+        ));
+        let resources = doc.get_dictionary_mut(page_id).unwrap().get_mut(b"Resources").unwrap();
+        resources.as_dict_mut().unwrap().set("XObject", dictionary! { "X1" => broken });
+        doc.add_page_contents(page_id, b"/X1 Do".to_vec()).unwrap();
+
+        assert!(doc.unused_page_resources(page_id).is_err());
+        assert_eq!(doc.strip_unused_resources_all(), 0);
+        let found = doc.find_resource(page_id, ResourceCategory::Font, font_ids[1]).unwrap();
+        assert_eq!(found, Some(b"F2".to_vec()));
+    }
+
+    #[test]
+    fn inline_image_color_spaces_are_uses() {
+        let mut doc = create_document_with_texts(&["Hello"]);
+        let (page_id, _) = add_page_fonts(&mut doc, 1);
+        let direct = doc.add_object(Object::Name(b"DeviceRGB".to_vec()));
+        let indexed_base = doc.add_object(Object::Name(b"DeviceGray".to_vec()));
+        let unused = doc.add_object(Object::Name(b"DeviceCMYK".to_vec()));
+        let resources = doc.get_dictionary_mut(page_id).unwrap().get_mut(b"Resources").unwrap();
+        resources.as_dict_mut().unwrap().set(
+            "ColorSpace",
+            dictionary! { "CS0" => direct, "CS1" => indexed_base, "CS2" => unused },
+        );
+        doc.add_page_contents(
+            page_id,
+            b"BI /W 1 /H 1 /CS /CS0 /BPC 8 ID \x00\x00\x00 EI\n\
+              BI /W 1 /H 1 /CS [/I /CS1 1 <0000>] /BPC 8 ID \x00 EI\n"
+                .to_vec(),
+        )
+        .unwrap();
+
+        let unused_resources = doc.unused_page_resources(page_id).unwrap();
+        assert_eq!(unused_resources, [(ResourceCategory::ColorSpace, b"CS2".to_vec(), unused)]);
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::xref::Xref;
+use crate::{Dictionary, Document, Object, ObjectId};
+
+/// A revision of a loaded file: its original content or one of its incremental updates.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    /// The byte the cross-reference section of the revision starts at
+    pub xref_start: usize,
+    /// The cross-reference section of the revision, without those of earlier revisions
+    pub xref: Xref,
+    pub trailer: Dictionary,
+    /// The revision's own version of its /Root and /Info objects, where a later revision replaced
+    /// them under the same object number
+    pub replaced_objects: BTreeMap<ObjectId, Object>,
+}
+
+/// A dictionary found in a revision, with the index of the revision in [`Document::revisions`].
+pub type RevisionCandidate = (usize, ObjectId, Dictionary);
+
+impl Document {
+    /// List every document information dictionary the trailers of the file's revisions point to,
+    /// oldest first. Dictionaries which didn't change between revisions are listed once, with the
+    /// first revision using them.
+    ///
+    /// Only the newest is used as the document's information dictionary, the others show e.g. what
+    /// metadata the file had before it was scrubbed.
+    pub fn all_info_dictionaries(&self) -> Vec<RevisionCandidate> {
+        self.trailer_candidates(b"Info")
+    }
+
+    /// List every catalog the trailers of the file's revisions point to, oldest first, followed by
+    /// catalogs no trailer points to. Catalogs are listed as [`Document::all_info_dictionaries`]
+    /// lists information dictionaries.
+    pub fn all_catalogs(&self) -> Vec<RevisionCandidate> {
+        let mut candidates = self.trailer_candidates(b"Root");
+        let roots: HashSet<ObjectId> = self
+            .trailers()
+            .filter_map(|(_, trailer, _)| trailer.get(b"Root").and_then(Object::as_reference).ok())
+            .collect();
+        for (&id, object) in &self.objects {
+            let Ok(dict) = object.as_dict() else {
+                continue;
+            };
+            if roots.contains(&id) || !dict.has_type(b"Catalog") {
+                continue;
+            }
+            // Attribute the catalog to the newest revision that wrote it.
+            let revision = self
+                .revisions
+                .iter()
+                .rposition(|revision| revision.xref.get(id.0).is_some())
+                .unwrap_or(self.revisions.len().saturating_sub(1));
+            candidates.push((revision, id, dict.clone()));
+        }
+        candidates
+    }
+
+    /// Get the dictionaries the trailers refer to by `key`, skipping repetitions.
+    fn trailer_candidates(&self, key: &[u8]) -> Vec<RevisionCandidate> {
+        let mut candidates: Vec<RevisionCandidate> = Vec::new();
+        for (index, trailer, replaced_objects) in self.trailers() {
+            let Ok(id) = trailer.get(key).and_then(Object::as_reference) else {
+                continue;
+            };
+            let object = replaced_objects
+                .and_then(|objects| objects.get(&id))
+                .or_else(|| self.objects.get(&id));
+            let Some(dict) = object.and_then(|object| object.as_dict().ok()) else {
+                continue;
+            };
+            if !candidates
+                .iter()
+                .any(|(_, found_id, found)| *found_id == id && found == dict)
+            {
+                candidates.push((index, id, dict.clone()));
+            }
+        }
+        candidates
+    }
+
+    /// Iterate over the trailers of all revisions, or over the trailer of a document that wasn't
+    /// loaded from a file.
+    fn trailers(&self) -> impl Iterator<Item = (usize, &Dictionary, Option<&BTreeMap<ObjectId, Object>>)> {
+        let current = self.revisions.is_empty().then_some((0, &self.trailer, None));
+        self.revisions
+            .iter()
+            .enumerate()
+            .map(|(index, revision)| (index, &revision.trailer, Some(&revision.replaced_objects)))
+            .chain(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    #[cfg(not(feature = "async"))]
+    use crate::IncrementalDocument;
+
+    #[cfg(not(feature = "async"))]
+    fn title(dict: &Dictionary) -> &[u8] {
+        dict.get(b"Title").and_then(Object::as_str).unwrap()
+    }
+
+    /// Save `doc`, and update it incrementally with `update`.
+    #[cfg(not(feature = "async"))]
+    fn update_incrementally(doc: &mut Document, update: impl FnOnce(&mut IncrementalDocument)) -> Document {
+        let mut original = Vec::new();
+        doc.save_to(&mut original).unwrap();
+        let mut incremental = IncrementalDocument::load_from(original.as_slice()).unwrap();
+        update(&mut incremental);
+        let mut updated = Vec::new();
+        incremental.save_to(&mut updated).unwrap();
+        Document::load_mem(&updated).unwrap()
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn replaced_info_is_discoverable() {
+        let mut doc = create_document();
+        let info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+        doc.get_dictionary_mut(info_id)
+            .unwrap()
+            .set("Title", Object::string_literal("First draft"));
+
+        let doc = update_incrementally(&mut doc, |incremental| {
+            incremental.opt_clone_object_to_new_document(info_id).unwrap();
+            let info = incremental.new_document.get_dictionary_mut(info_id).unwrap();
+            info.set("Title", Object::string_literal("Final"));
+        });
+
+        assert_eq!(doc.revisions.len(), 2);
+        assert_eq!(title(doc.trailer.get_deref(b"Info", &doc).unwrap().as_dict().unwrap()), b"Final");
+        let infos = doc.all_info_dictionaries();
+        let titles: Vec<_> = infos.iter().map(|(revision, id, dict)| (*revision, *id, title(dict))).collect();
+        assert_eq!(
+            titles,
+            [(0, info_id, b"First draft".as_slice()), (1, info_id, b"Final".as_slice())]
+        );
+        assert_eq!(doc.all_catalogs().len(), 1);
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn revisions_are_recorded_oldest_first() {
+        let mut doc = create_document();
+        let info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+        let doc = update_incrementally(&mut doc, |incremental| {
+            incremental.opt_clone_object_to_new_document(info_id).unwrap();
+            let info = incremental.new_document.get_dictionary_mut(info_id).unwrap();
+            info.set("Title", Object::string_literal("Final"));
+        });
+
+        let [original, update] = doc.revisions.as_slice() else {
+            panic!("expected two revisions, found {}", doc.revisions.len());
+        };
+        assert!(original.xref_start < update.xref_start);
+        assert!(original.trailer.get(b"Prev").is_err());
+        assert_eq!(update.trailer.get(b"Prev").and_then(Object::as_i64).unwrap(), original.xref_start as i64);
+        let replaced = original.replaced_objects[&info_id].as_dict().unwrap();
+        assert_eq!(title(replaced), b"Create PDF document example");
+        assert!(update.replaced_objects.is_empty());
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn scrubbed_info_and_orphaned_catalog_are_discoverable() {
+        let mut doc = create_document();
+        let old_info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+        let root_id = doc.trailer.get(b"Root").and_then(Object::as_reference).unwrap();
+
+        let doc = update_incrementally(&mut doc, |incremental| {
+            let new_document = &mut incremental.new_document;
+            let info_id = new_document.add_object(dictionary! { "Producer" => Object::string_literal("scrubber") });
+            new_document.trailer.set("Info", info_id);
+            new_document.add_object(dictionary! { "Type" => "Catalog", "Pages" => Object::Null });
+        });
+
+        let infos = doc.all_info_dictionaries();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].0, 0);
+        assert_eq!(infos[0].1, old_info_id);
+        assert_eq!(title(&infos[0].2), b"Create PDF document example");
+        assert_eq!(infos[1].0, 1);
+        assert!(infos[1].2.get(b"Title").is_err());
+
+        let catalogs = doc.all_catalogs();
+        assert_eq!(catalogs.len(), 2);
+        assert_eq!((catalogs[0].0, catalogs[0].1), (0, root_id));
+        assert_eq!(catalogs[1].0, 1);
+        assert_ne!(catalogs[1].1, root_id);
+    }
+
+    #[test]
+    fn created_document_has_its_own_candidates() {
+        let doc = create_document();
+        assert!(doc.revisions.is_empty());
+        assert_eq!(doc.all_info_dictionaries().len(), 1);
+        assert_eq!(doc.all_catalogs().len(), 1);
+    }
+}
@@ -0,0 +1,980 @@
+use std::collections::HashSet;
+
+use log::warn;
+
+use crate::content::{Content, Operation};
+use crate::{Dictionary, Document, Object, ObjectId};
+
+/// Annotation subtypes the PDF specification classifies as markup annotations, i.e. comments.
+pub const MARKUP_ANNOTATION_SUBTYPES: &[&[u8]] = &[
+    b"Text",
+    b"FreeText",
+    b"Line",
+    b"Square",
+    b"Circle",
+    b"Polygon",
+    b"PolyLine",
+    b"Highlight",
+    b"Underline",
+    b"Squiggly",
+    b"StrikeOut",
+    b"Caret",
+    b"Ink",
+    b"Popup",
+    b"Stamp",
+    b"Sound",
+    b"Redact",
+];
+
+/// Steps performed by [`Document::sanitize`]. Every step can be toggled individually.
+#[derive(Debug, Clone)]
+pub struct SanitizeOptions {
+    /// Strip the document information dictionary, keeping only the keys in `keep_info_keys`
+    pub remove_info: bool,
+    /// Info dictionary keys that survive `remove_info`, e.g. `b"Title".to_vec()`
+    pub keep_info_keys: Vec<Vec<u8>>,
+    /// Remove XMP metadata streams
+    pub remove_xmp_metadata: bool,
+    /// Remove JavaScript and Launch actions
+    pub remove_javascript: bool,
+    /// Remove embedded files and file attachment annotations
+    pub remove_attachments: bool,
+    /// Remove page content belonging to optional content groups that are hidden by default
+    pub remove_hidden_layers: bool,
+    /// Paint form field appearances into the page content and remove the interactive form
+    pub flatten_forms: bool,
+    /// Annotation subtypes to remove, [`MARKUP_ANNOTATION_SUBTYPES`] by default
+    pub remove_annotation_subtypes: Vec<Vec<u8>>,
+    /// Remove page thumbnail images
+    pub remove_thumbnails: bool,
+    /// Remove /PieceInfo private application data
+    pub remove_private_data: bool,
+    /// Remove objects that are no longer referenced
+    pub prune_objects: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            remove_info: true,
+            keep_info_keys: Vec::new(),
+            remove_xmp_metadata: true,
+            remove_javascript: true,
+            remove_attachments: true,
+            remove_hidden_layers: true,
+            flatten_forms: true,
+            remove_annotation_subtypes: MARKUP_ANNOTATION_SUBTYPES.iter().map(|s| s.to_vec()).collect(),
+            remove_thumbnails: true,
+            remove_private_data: true,
+            prune_objects: true,
+        }
+    }
+}
+
+impl SanitizeOptions {
+    /// Options with every step disabled, to enable steps one by one.
+    pub fn none() -> Self {
+        Self {
+            remove_info: false,
+            keep_info_keys: Vec::new(),
+            remove_xmp_metadata: false,
+            remove_javascript: false,
+            remove_attachments: false,
+            remove_hidden_layers: false,
+            flatten_forms: false,
+            remove_annotation_subtypes: Vec::new(),
+            remove_thumbnails: false,
+            remove_private_data: false,
+            prune_objects: false,
+        }
+    }
+}
+
+/// What [`Document::sanitize`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    /// Keys removed from the document information dictionary
+    pub info_keys_removed: Vec<Vec<u8>>,
+    /// Number of XMP metadata streams removed
+    pub xmp_metadata_removed: usize,
+    /// Number of JavaScript and Launch actions removed
+    pub actions_removed: usize,
+    /// Number of embedded files removed
+    pub attachments_removed: usize,
+    /// Number of hidden optional content blocks removed from pages
+    pub hidden_content_removed: usize,
+    /// Number of form field widgets painted into page content
+    pub fields_flattened: usize,
+    /// Number of annotations removed because of their subtype
+    pub annotations_removed: usize,
+    /// Number of page thumbnails removed
+    pub thumbnails_removed: usize,
+    /// Number of /PieceInfo application entries removed
+    pub private_data_removed: usize,
+    /// Number of unreferenced objects removed
+    pub objects_pruned: usize,
+}
+
+impl Document {
+    /// Prepare the document for external sharing by running the steps enabled in `options`.
+    ///
+    /// This is a thin wrapper over the individual `remove_*` and `flatten_forms` methods.
+    pub fn sanitize(&mut self, options: SanitizeOptions) -> SanitizeReport {
+        let mut report = SanitizeReport::default();
+
+        if options.remove_info {
+            let keep: Vec<&[u8]> = options.keep_info_keys.iter().map(Vec::as_slice).collect();
+            report.info_keys_removed = self.remove_info_metadata(&keep);
+        }
+        if options.remove_xmp_metadata {
+            report.xmp_metadata_removed = self.remove_xmp_metadata();
+        }
+        if options.remove_javascript {
+            report.actions_removed = self.remove_javascript();
+        }
+        if options.remove_attachments {
+            report.attachments_removed = self.remove_attachments();
+        }
+        if options.remove_hidden_layers {
+            report.hidden_content_removed = self.remove_hidden_layer_content();
+        }
+        if options.flatten_forms {
+            report.fields_flattened = self.flatten_forms();
+        }
+        if !options.remove_annotation_subtypes.is_empty() {
+            let subtypes: Vec<&[u8]> = options.remove_annotation_subtypes.iter().map(Vec::as_slice).collect();
+            report.annotations_removed = self.remove_annotations_with_subtypes(&subtypes);
+        }
+        if options.remove_thumbnails {
+            report.thumbnails_removed = self.remove_thumbnails();
+        }
+        if options.remove_private_data {
+            report.private_data_removed = self.remove_private_data();
+        }
+        if options.prune_objects {
+            report.objects_pruned = self.prune_objects().len();
+        }
+
+        report
+    }
+
+    /// Remove entries of the document information dictionary except those in `keep`.
+    ///
+    /// When `keep` is empty the dictionary is removed from the trailer altogether.
+    /// Returns the removed keys.
+    pub fn remove_info_metadata(&mut self, keep: &[&[u8]]) -> Vec<Vec<u8>> {
+        let info_id = self.trailer.get(b"Info").and_then(Object::as_reference).ok();
+        let info = match info_id {
+            Some(id) => self.get_dictionary_mut(id),
+            None => self.trailer.get_mut(b"Info").and_then(Object::as_dict_mut),
+        };
+        let Ok(info) = info else {
+            return Vec::new();
+        };
+
+        let removed: Vec<Vec<u8>> = info
+            .iter()
+            .map(|(key, _)| key.clone())
+            .filter(|key| !keep.contains(&key.as_slice()))
+            .collect();
+        for key in &removed {
+            info.remove(key);
+        }
+
+        if keep.is_empty() {
+            self.trailer.remove(b"Info");
+            if let Some(id) = info_id {
+                self.objects.remove(&id);
+            }
+        }
+
+        removed
+    }
+
+    /// Remove XMP metadata streams and the /Metadata entries pointing at them.
+    /// Returns the number of metadata streams removed.
+    pub fn remove_xmp_metadata(&mut self) -> usize {
+        let metadata_ids: Vec<ObjectId> = self
+            .objects
+            .iter()
+            .filter(|(_, object)| matches!(object, Object::Stream(stream) if stream.dict.has_type(b"Metadata")))
+            .map(|(id, _)| *id)
+            .collect();
+
+        for object in self.objects.values_mut() {
+            if let Ok(dict) = dict_of_mut(object) {
+                dict.remove(b"Metadata");
+            }
+        }
+        for id in &metadata_ids {
+            self.objects.remove(id);
+        }
+
+        metadata_ids.len()
+    }
+
+    /// Remove JavaScript and Launch actions, including the document-level JavaScript name tree.
+    /// Returns the number of actions removed.
+    pub fn remove_javascript(&mut self) -> usize {
+        let mut removed = usize::from(self.remove_name_tree(b"JavaScript"));
+
+        let script_ids: HashSet<ObjectId> = self
+            .objects
+            .iter()
+            .filter(|(_, object)| matches!(object, Object::Dictionary(dict) if is_script_action(dict)))
+            .map(|(id, _)| *id)
+            .collect();
+        for object in self.objects.values_mut() {
+            removed += strip_script_actions(object, &script_ids);
+        }
+        for id in &script_ids {
+            self.objects.remove(id);
+        }
+
+        removed
+    }
+
+    /// Remove embedded files, the /EmbeddedFiles name tree, associated files and file attachment
+    /// annotations. Returns the number of embedded files removed.
+    pub fn remove_attachments(&mut self) -> usize {
+        self.remove_name_tree(b"EmbeddedFiles");
+        if let Ok(catalog) = self.catalog_mut() {
+            catalog.remove(b"AF");
+        }
+        // The /Type of embedded file streams is optional, so the files of file attachment
+        // annotations are found through their file specifications.
+        let (file_spec_ids, mut file_ids) = self.file_attachment_annotation_files();
+        self.remove_annotations_with_subtypes(&[b"FileAttachment"]);
+
+        file_ids.extend(
+            self.objects
+                .iter()
+                .filter(|(_, object)| matches!(object, Object::Stream(stream) if stream.dict.has_type(b"EmbeddedFile")))
+                .map(|(id, _)| *id),
+        );
+        for id in file_spec_ids.iter().chain(&file_ids) {
+            self.objects.remove(id);
+        }
+
+        file_ids.len()
+    }
+
+    /// The file specifications of file attachment annotations and the embedded file streams they
+    /// refer to.
+    fn file_attachment_annotation_files(&self) -> (HashSet<ObjectId>, HashSet<ObjectId>) {
+        let mut file_spec_ids = HashSet::new();
+        let mut file_ids = HashSet::new();
+        for page_id in self.page_iter() {
+            for annot in self.page_annotations(page_id) {
+                let Some(annot) = self.resolve_dict(&annot) else {
+                    continue;
+                };
+                if !annot
+                    .get(b"Subtype")
+                    .and_then(Object::as_name)
+                    .is_ok_and(|subtype| subtype == b"FileAttachment")
+                {
+                    continue;
+                }
+                let Ok(file_spec) = annot.get(b"FS") else {
+                    continue;
+                };
+                if let Ok(id) = file_spec.as_reference() {
+                    file_spec_ids.insert(id);
+                }
+                let streams = self
+                    .resolve_dict(file_spec)
+                    .and_then(|file_spec| file_spec.get(b"EF").ok())
+                    .and_then(|streams| self.resolve_dict(streams));
+                for (_, stream) in streams.into_iter().flatten() {
+                    if let Ok(id) = stream.as_reference() {
+                        file_ids.insert(id);
+                    }
+                }
+            }
+        }
+        (file_spec_ids, file_ids)
+    }
+
+    /// Remove page content, XObjects and annotations that belong to optional content groups listed
+    /// in the /OFF array of the default configuration. Returns the number of items removed.
+    pub fn remove_hidden_layer_content(&mut self) -> usize {
+        let hidden = self.hidden_optional_content_groups();
+        if hidden.is_empty() {
+            return 0;
+        }
+
+        let mut removed = 0;
+        for page_id in self.page_iter().collect::<Vec<_>>() {
+            let (properties, xobjects) = self.hidden_resource_names(page_id, &hidden);
+            if properties.is_empty() && xobjects.is_empty() {
+                continue;
+            }
+            let content = match self.get_and_decode_page_content(page_id) {
+                Ok(content) => content,
+                Err(err) => {
+                    warn!("cannot remove hidden content from page {} {}: {err}", page_id.0, page_id.1);
+                    continue;
+                }
+            };
+
+            let mut operations = Vec::with_capacity(content.operations.len());
+            let mut skip_depth = 0;
+            for operation in content.operations {
+                if skip_depth > 0 {
+                    match operation.operator.as_str() {
+                        "BMC" | "BDC" => skip_depth += 1,
+                        "EMC" => skip_depth -= 1,
+                        _ => {}
+                    }
+                    continue;
+                }
+                let hidden_block = operation.operator == "BDC"
+                    && matches!(operation.operands.as_slice(), [Object::Name(tag), Object::Name(name)]
+                        if tag == b"OC" && properties.contains(name));
+                let hidden_xobject = operation.operator == "Do"
+                    && matches!(operation.operands.as_slice(), [Object::Name(name)] if xobjects.contains(name));
+                if hidden_block {
+                    skip_depth = 1;
+                    removed += 1;
+                } else if hidden_xobject {
+                    removed += 1;
+                } else {
+                    operations.push(operation);
+                }
+            }
+
+            if let Ok(data) = (Content { operations }).encode() {
+                if let Err(err) = self.change_page_content(page_id, data) {
+                    warn!("cannot update content of page {} {}: {err}", page_id.0, page_id.1);
+                }
+            }
+        }
+
+        removed += self.remove_annotations_where(|annot| {
+            annot
+                .get(b"OC")
+                .and_then(Object::as_reference)
+                .is_ok_and(|id| hidden.contains(&id))
+        });
+
+        removed
+    }
+
+    /// Paint the normal appearance of every visible form field widget into its page, then remove
+    /// the widgets and the interactive form. Returns the number of widgets painted.
+    pub fn flatten_forms(&mut self) -> usize {
+        let mut flattened = 0;
+
+        for page_id in self.page_iter().collect::<Vec<_>>() {
+            let annots = self.page_annotations(page_id);
+            let placements: Vec<(ObjectId, [f32; 6])> = annots
+                .iter()
+                .filter_map(|annot| self.resolve_dict(annot))
+                .filter(|annot| annot.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Widget"))
+                .filter_map(|widget| self.widget_placement(widget))
+                .collect();
+            if placements.is_empty() {
+                continue;
+            }
+
+            if let Err(err) = self.localize_page_resources(page_id) {
+                warn!("cannot flatten form fields on page {} {}: {err}", page_id.0, page_id.1);
+                continue;
+            }
+            let mut operations = vec![];
+            for (appearance_id, matrix) in placements {
+                if let Ok(Object::Stream(stream)) = self.get_object_mut(appearance_id) {
+                    if !stream.dict.has(b"Subtype") {
+                        stream.dict.set("Subtype", "Form");
+                    }
+                }
+                let name = format!("FlatField{}_{}", appearance_id.0, appearance_id.1);
+                if self.add_xobject(page_id, name.as_bytes(), appearance_id).is_err() {
+                    continue;
+                }
+                operations.push(Operation::new("q", vec![]));
+                operations.push(Operation::new("cm", matrix.iter().map(|&v| Object::Real(v)).collect()));
+                operations.push(Operation::new("Do", vec![Object::Name(name.into_bytes())]));
+                operations.push(Operation::new("Q", vec![]));
+                flattened += 1;
+            }
+            if let Ok(data) = (Content { operations }).encode() {
+                if let Err(err) = self.append_isolated_page_content(page_id, data) {
+                    warn!("cannot update content of page {} {}: {err}", page_id.0, page_id.1);
+                }
+            }
+        }
+
+        self.remove_annotations_with_subtypes(&[b"Widget"]);
+        if let Ok(catalog) = self.catalog_mut() {
+            catalog.remove(b"AcroForm");
+        }
+
+        flattened
+    }
+
+    /// Remove annotations whose /Subtype is one of `subtypes` from all pages.
+    /// Returns the number of annotations removed.
+    pub fn remove_annotations_with_subtypes(&mut self, subtypes: &[&[u8]]) -> usize {
+        self.remove_annotations_where(|annot| {
+            annot
+                .get(b"Subtype")
+                .and_then(Object::as_name)
+                .is_ok_and(|subtype| subtypes.contains(&subtype))
+        })
+    }
+
+    /// Remove page thumbnail images. Returns the number of thumbnails removed.
+    pub fn remove_thumbnails(&mut self) -> usize {
+        let mut removed = 0;
+        for page_id in self.page_iter().collect::<Vec<_>>() {
+            if let Ok(page) = self.get_dictionary_mut(page_id) {
+                removed += usize::from(page.remove(b"Thumb").is_some());
+            }
+        }
+        removed
+    }
+
+    /// Remove /PieceInfo private application data of every application from all objects.
+    /// Returns the number of application entries removed.
+    pub fn remove_private_data(&mut self) -> usize {
+        self.piece_info_applications()
+            .iter()
+            .map(|application| self.remove_piece_info(application))
+            .sum()
+    }
+
+    /// Remove the annotations matching `predicate` with their pop-up annotations, and the /Popup
+    /// entries of other annotations pointing at removed ones.
+    fn remove_annotations_where<F: Fn(&Dictionary) -> bool>(&mut self, predicate: F) -> usize {
+        let mut removed_ids = HashSet::new();
+        let mut removed = 0;
+
+        for page_id in self.page_iter().collect::<Vec<_>>() {
+            let annots = self.page_annotations(page_id);
+            let mut remove: Vec<bool> = annots
+                .iter()
+                .map(|annot| self.resolve_dict(annot).is_some_and(&predicate))
+                .collect();
+            if !remove.contains(&true) {
+                continue;
+            }
+
+            // A pop-up annotation belongs to its parent, found from either side.
+            let mut parents = HashSet::new();
+            for (annot, _) in annots.iter().zip(&remove).filter(|(_, remove)| **remove) {
+                parents.extend(annot.as_reference().ok());
+                let popup = self.resolve_dict(annot).and_then(|annot| annot.get(b"Popup").ok());
+                removed_ids.extend(popup.and_then(|popup| popup.as_reference().ok()));
+            }
+            for (annot, remove) in annots.iter().zip(remove.iter_mut()) {
+                let is_listed_popup = annot.as_reference().is_ok_and(|id| removed_ids.contains(&id));
+                let parent = self
+                    .resolve_dict(annot)
+                    .and_then(|annot| annot.get(b"Parent").and_then(Object::as_reference).ok());
+                *remove |= is_listed_popup || parent.is_some_and(|parent| parents.contains(&parent));
+            }
+            removed_ids.extend(
+                annots
+                    .iter()
+                    .zip(&remove)
+                    .filter(|(_, remove)| **remove)
+                    .filter_map(|(annot, _)| annot.as_reference().ok()),
+            );
+
+            let annots_id = self
+                .get_dictionary(page_id)
+                .and_then(|page| page.get(b"Annots"))
+                .and_then(Object::as_reference);
+            let annots = match annots_id {
+                Ok(id) => self.get_object_mut(id),
+                Err(_) => self
+                    .get_dictionary_mut(page_id)
+                    .and_then(|page| page.get_mut(b"Annots")),
+            };
+            if let Ok(annots) = annots.and_then(Object::as_array_mut) {
+                let mut index = 0;
+                annots.retain(|_| {
+                    index += 1;
+                    !remove[index - 1]
+                });
+                removed += remove.iter().filter(|remove| **remove).count();
+            }
+        }
+
+        for id in &removed_ids {
+            self.objects.remove(id);
+        }
+        for object in self.objects.values_mut() {
+            if let Object::Dictionary(annot) = object {
+                let popup = annot.get(b"Popup").and_then(Object::as_reference);
+                if popup.is_ok_and(|popup| removed_ids.contains(&popup)) {
+                    annot.remove(b"Popup");
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// The /Annots array of a page, resolving an indirect array.
+    fn page_annotations(&self, page_id: ObjectId) -> Vec<Object> {
+        self.get_dictionary(page_id)
+            .and_then(|page| page.get_deref(b"Annots", self))
+            .and_then(Object::as_array)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn resolve_dict<'a>(&'a self, object: &'a Object) -> Option<&'a Dictionary> {
+        self.dereference(object).ok().and_then(|(_, object)| object.as_dict().ok())
+    }
+
+    /// Remove an entry from the catalog's /Names dictionary. Returns whether it was present.
+    fn remove_name_tree(&mut self, key: &[u8]) -> bool {
+        let names = match self.catalog().and_then(|catalog| catalog.get(b"Names")) {
+            Ok(Object::Reference(id)) => {
+                let id = *id;
+                self.get_dictionary_mut(id)
+            }
+            Ok(_) => self
+                .catalog_mut()
+                .and_then(|catalog| catalog.get_mut(b"Names"))
+                .and_then(Object::as_dict_mut),
+            Err(_) => return false,
+        };
+        names.is_ok_and(|names| names.remove(key).is_some())
+    }
+
+    fn hidden_optional_content_groups(&self) -> HashSet<ObjectId> {
+        self.catalog()
+            .and_then(|catalog| catalog.get_deref(b"OCProperties", self))
+            .and_then(Object::as_dict)
+            .and_then(|properties| properties.get_deref(b"D", self))
+            .and_then(Object::as_dict)
+            .and_then(|config| config.get_deref(b"OFF", self))
+            .and_then(Object::as_array)
+            .map(|off| off.iter().filter_map(|group| group.as_reference().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Names of the page's /Properties and /XObject resources that refer to hidden groups.
+    fn hidden_resource_names(
+        &self, page_id: ObjectId, hidden: &HashSet<ObjectId>,
+    ) -> (HashSet<Vec<u8>>, HashSet<Vec<u8>>) {
+        let mut properties = HashSet::new();
+        let mut xobjects = HashSet::new();
+        let Ok((resources, resource_ids)) = self.get_page_resources(page_id) else {
+            return (properties, xobjects);
+        };
+
+        let resources = resources
+            .into_iter()
+            .chain(resource_ids.iter().filter_map(|id| self.get_dictionary(*id).ok()));
+        for resources in resources {
+            if let Ok(dict) = resources.get_deref(b"Properties", self).and_then(Object::as_dict) {
+                for (name, value) in dict {
+                    if value.as_reference().is_ok_and(|id| hidden.contains(&id)) {
+                        properties.insert(name.clone());
+                    }
+                }
+            }
+            if let Ok(dict) = resources.get_deref(b"XObject", self).and_then(Object::as_dict) {
+                for (name, value) in dict {
+                    let group = self
+                        .dereference(value)
+                        .and_then(|(_, xobject)| xobject.as_stream())
+                        .and_then(|stream| stream.dict.get(b"OC"))
+                        .and_then(Object::as_reference);
+                    if group.is_ok_and(|id| hidden.contains(&id)) {
+                        xobjects.insert(name.clone());
+                    }
+                }
+            }
+        }
+
+        (properties, xobjects)
+    }
+
+    /// The appearance stream of a visible widget and the matrix that maps it onto the widget's /Rect.
+    fn widget_placement(&self, widget: &Dictionary) -> Option<(ObjectId, [f32; 6])> {
+        const HIDDEN: i64 = 1 << 1;
+        if widget.get(b"F").and_then(Object::as_i64).unwrap_or(0) & HIDDEN != 0 {
+            return None;
+        }
+
+        let normal = widget
+            .get_deref(b"AP", self)
+            .and_then(Object::as_dict)
+            .and_then(|ap| ap.get(b"N"))
+            .ok()?;
+        let appearance_id = match self.dereference(normal).ok()? {
+            (Some(id), Object::Stream(_)) => id,
+            (_, Object::Dictionary(states)) => {
+                let state = widget.get(b"AS").and_then(Object::as_name).ok()?;
+                states.get(state).and_then(Object::as_reference).ok()?
+            }
+            _ => return None,
+        };
+        let appearance = self.get_object(appearance_id).and_then(Object::as_stream).ok()?;
+
+        let rect = rectangle(widget.get(b"Rect").ok()?)?;
+        let bbox = rectangle(appearance.dict.get(b"BBox").ok()?)?;
+        let matrix = appearance
+            .dict
+            .get(b"Matrix")
+            .and_then(Object::as_array)
+            .ok()
+            .and_then(|m| m.iter().map(|v| v.as_float().ok()).collect::<Option<Vec<_>>>())
+            .unwrap_or_else(|| vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        if matrix.len() != 6 {
+            return None;
+        }
+
+        // Transform the bounding box by the form matrix, then fit the result into the widget rectangle.
+        let corners = [(bbox[0], bbox[1]), (bbox[0], bbox[3]), (bbox[2], bbox[1]), (bbox[2], bbox[3])]
+            .map(|(x, y)| (matrix[0] * x + matrix[2] * y + matrix[4], matrix[1] * x + matrix[3] * y + matrix[5]));
+        let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min);
+        let max_x = corners.iter().map(|c| c.0).fold(f32::NEG_INFINITY, f32::max);
+        let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min);
+        let max_y = corners.iter().map(|c| c.1).fold(f32::NEG_INFINITY, f32::max);
+        if max_x <= min_x || max_y <= min_y {
+            return None;
+        }
+        let scale_x = (rect[2] - rect[0]) / (max_x - min_x);
+        let scale_y = (rect[3] - rect[1]) / (max_y - min_y);
+
+        Some((
+            appearance_id,
+            [scale_x, 0.0, 0.0, scale_y, rect[0] - min_x * scale_x, rect[1] - min_y * scale_y],
+        ))
+    }
+
+    /// Give the page its own /Resources if it only inherits them, so adding resources to it does
+    /// not hide the inherited ones.
+    pub(crate) fn localize_page_resources(&mut self, page_id: ObjectId) -> crate::Result<()> {
+        if self.get_dictionary(page_id)?.has(b"Resources") {
+            return Ok(());
+        }
+        let inherited = match self.get_page_resources(page_id)? {
+            (Some(resources), _) => Some(resources.clone()),
+            (None, ids) => ids.first().and_then(|id| self.get_dictionary(*id).ok()).cloned(),
+        };
+        if let Some(resources) = inherited {
+            self.get_dictionary_mut(page_id)?.set("Resources", resources);
+        }
+        Ok(())
+    }
+
+    /// Append content to a page, wrapping the existing content in `q`/`Q` so that a graphics state
+    /// it leaves behind does not affect the new content.
+    fn append_isolated_page_content(&mut self, page_id: ObjectId, content: Vec<u8>) -> crate::Result<()> {
+        let mut contents: Vec<Object> = match self.get_dictionary(page_id)?.get(b"Contents") {
+            Ok(Object::Reference(id)) => vec![Object::Reference(*id)],
+            Ok(Object::Array(array)) => array.clone(),
+            _ => vec![],
+        };
+        let save_id = self.add_object(crate::Stream::new(Dictionary::new(), b"q\n".to_vec()));
+        let mut restore = b"\nQ\n".to_vec();
+        restore.extend(content);
+        let restore_id = self.add_object(crate::Stream::new(Dictionary::new(), restore));
+        contents.insert(0, Object::Reference(save_id));
+        contents.push(Object::Reference(restore_id));
+
+        self.get_dictionary_mut(page_id)?.set("Contents", contents);
+        Ok(())
+    }
+}
+
+fn dict_of_mut(object: &mut Object) -> crate::Result<&mut Dictionary> {
+    match object {
+        Object::Stream(stream) => Ok(&mut stream.dict),
+        object => object.as_dict_mut(),
+    }
+}
+
+fn is_script_action(dict: &Dictionary) -> bool {
+    matches!(dict.get(b"S").and_then(Object::as_name), Ok(b"JavaScript" | b"Launch"))
+}
+
+/// Remove references to script actions from `object` and the objects nested in it.
+/// Returns the number of references removed.
+fn strip_script_actions(object: &mut Object, script_ids: &HashSet<ObjectId>) -> usize {
+    let is_script = |value: &Object| match value {
+        Object::Reference(id) => script_ids.contains(id),
+        Object::Dictionary(dict) => is_script_action(dict),
+        _ => false,
+    };
+
+    let mut removed = 0;
+    match object {
+        Object::Array(array) => {
+            let len = array.len();
+            array.retain(|item| !is_script(item));
+            removed += len - array.len();
+            for item in array.iter_mut() {
+                removed += strip_script_actions(item, script_ids);
+            }
+        }
+        Object::Dictionary(_) | Object::Stream(_) => {
+            let Ok(dict) = dict_of_mut(object) else {
+                return 0;
+            };
+            let keys: Vec<Vec<u8>> = dict
+                .iter()
+                .filter(|(_, value)| is_script(value))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in &keys {
+                dict.remove(key);
+            }
+            removed += keys.len();
+            for (_, value) in dict.iter_mut() {
+                removed += strip_script_actions(value, script_ids);
+            }
+        }
+        _ => {}
+    }
+    removed
+}
+
+/// Normalized `[llx lly urx ury]` of a rectangle array.
+pub(crate) fn rectangle(object: &Object) -> Option<[f32; 4]> {
+    let values = object
+        .as_array()
+        .ok()?
+        .iter()
+        .map(|v| v.as_float().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let [x0, y0, x1, y1] = values.as_slice() else {
+        return None;
+    };
+    Some([x0.min(*x1), y0.min(*y1), x0.max(*x1), y0.max(*y1)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use crate::Stream;
+
+    /// A document with an author, document JavaScript, a comment, an attachment and a form field.
+    fn create_shareable_document() -> (Document, ObjectId) {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+
+        let info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+        doc.get_dictionary_mut(info_id)
+            .unwrap()
+            .set("Author", Object::string_literal("Secret Author"));
+
+        let script_id = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert('hidden script')"),
+        });
+        let comment_id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Text",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "Contents" => Object::string_literal("secret comment"),
+        });
+        let appearance_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 50.into(), 10.into()],
+            },
+            b"BT /F1 8 Tf (filled value) Tj ET".to_vec(),
+        ));
+        let widget_id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "FT" => "Tx",
+            "T" => Object::string_literal("name"),
+            "V" => Object::string_literal("filled value"),
+            "Rect" => vec![100.into(), 100.into(), 200.into(), 120.into()],
+            "AP" => dictionary! { "N" => appearance_id },
+            "AA" => dictionary! { "K" => dictionary! {
+                "S" => "JavaScript",
+                "JS" => Object::string_literal("AFNumber_Keystroke()"),
+            }},
+        });
+        let file_id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile" },
+            b"attached secret".to_vec(),
+        ));
+        let thumb_id = doc.add_object(Stream::new(
+            dictionary! { "Width" => 1, "Height" => 1, "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8 },
+            vec![0],
+        ));
+        let filespec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("notes.txt"),
+            "EF" => dictionary! { "F" => file_id },
+        });
+
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set("Annots", vec![comment_id.into(), widget_id.into()]);
+        page.set("PieceInfo", dictionary! { "Editor" => dictionary! { "Private" => 1 } });
+        page.set("Thumb", thumb_id);
+        let catalog = doc.catalog_mut().unwrap();
+        catalog.set("OpenAction", script_id);
+        catalog.set("AcroForm", dictionary! { "Fields" => vec![widget_id.into()] });
+        catalog.set(
+            "Names",
+            dictionary! {
+                "EmbeddedFiles" => dictionary! { "Names" => vec![Object::string_literal("notes.txt"), filespec_id.into()] },
+            },
+        );
+
+        (doc, page_id)
+    }
+
+    #[test]
+    fn sanitize_removes_private_content_from_saved_bytes() {
+        let (mut doc, page_id) = create_shareable_document();
+        let report = doc.sanitize(SanitizeOptions::default());
+
+        assert!(report.info_keys_removed.contains(&b"Author".to_vec()));
+        assert_eq!(report.actions_removed, 2);
+        assert_eq!(report.attachments_removed, 1);
+        assert_eq!(report.fields_flattened, 1);
+        assert_eq!(report.annotations_removed, 1);
+        assert_eq!(report.thumbnails_removed, 1);
+        assert_eq!(report.private_data_removed, 1);
+        assert!(doc.catalog().unwrap().get(b"AcroForm").is_err());
+        assert!(doc.get_page_annotations(page_id).unwrap().is_empty());
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        for needle in [&b"Secret Author"[..], b"app.alert", b"AFNumber", b"secret comment", b"attached secret"] {
+            assert!(!bytes.windows(needle.len()).any(|w| w == needle));
+        }
+
+        // The form field value is still visible as page content.
+        let reloaded = Document::load_mem(&bytes).unwrap();
+        assert_eq!(reloaded.get_pages().len(), 1);
+        let content = reloaded.get_and_decode_page_content(page_id).unwrap();
+        let matrix = content.operations.iter().find(|op| op.operator == "cm").unwrap();
+        let matrix: Vec<f32> = matrix.operands.iter().map(|v| v.as_float().unwrap()).collect();
+        assert_eq!(matrix, [2.0, 0.0, 0.0, 2.0, 100.0, 100.0]);
+    }
+
+    #[test]
+    fn sanitize_runs_only_enabled_steps() {
+        let (mut doc, page_id) = create_shareable_document();
+        let object_count = doc.objects.len();
+        assert_eq!(doc.sanitize(SanitizeOptions::none()), SanitizeReport::default());
+        assert_eq!(doc.objects.len(), object_count);
+
+        let report = doc.sanitize(SanitizeOptions {
+            remove_info: true,
+            keep_info_keys: vec![b"Title".to_vec()],
+            remove_annotation_subtypes: vec![b"Text".to_vec()],
+            ..SanitizeOptions::none()
+        });
+        assert_eq!(report.annotations_removed, 1);
+        assert_eq!(report.actions_removed, 0);
+        assert!(!report.info_keys_removed.contains(&b"Title".to_vec()));
+
+        let info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+        let info = doc.get_dictionary(info_id).unwrap();
+        assert!(info.has(b"Title"));
+        assert!(!info.has(b"Author"));
+        assert!(doc.catalog().unwrap().has(b"OpenAction"));
+        assert_eq!(doc.get_page_annotations(page_id).unwrap().len(), 1);
+    }
+
+    /// Add an annotation of `subtype` with a pop-up annotation to the first page, returning both.
+    fn add_annotation_with_popup(doc: &mut Document, subtype: &str, extra: Dictionary) -> (ObjectId, ObjectId) {
+        let page_id = doc.page_iter().next().unwrap();
+        let annot_id = doc.new_object_id();
+        let popup_id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Popup",
+            "Rect" => vec![20.into(), 0.into(), 120.into(), 50.into()],
+            "Parent" => annot_id,
+        });
+        let mut annot = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => subtype,
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "Popup" => popup_id,
+        };
+        annot.extend(&extra);
+        doc.objects.insert(annot_id, Object::Dictionary(annot));
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        if !page.has(b"Annots") {
+            page.set("Annots", Vec::<Object>::new());
+        }
+        let annots = page.get_mut(b"Annots").and_then(Object::as_array_mut).unwrap();
+        annots.extend([annot_id.into(), popup_id.into()]);
+        (annot_id, popup_id)
+    }
+
+    #[test]
+    fn remove_attachments_drops_file_attachment_annotations_and_their_popups() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let file_id = doc.add_object(Stream::new(dictionary! {}, b"attached secret".to_vec()));
+        let filespec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("notes.txt"),
+            "EF" => dictionary! { "F" => file_id },
+        });
+        let (annot_id, popup_id) =
+            add_annotation_with_popup(&mut doc, "FileAttachment", dictionary! { "FS" => filespec_id });
+
+        assert_eq!(doc.remove_attachments(), 1);
+        assert!(doc.get_page_annotations(page_id).unwrap().is_empty());
+        for id in [annot_id, popup_id, filespec_id, file_id] {
+            assert!(!doc.objects.contains_key(&id), "object {id:?} is left");
+        }
+    }
+
+    #[test]
+    fn removed_annotations_take_their_popups_along() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let (comment_id, comment_popup_id) = add_annotation_with_popup(&mut doc, "Text", dictionary! {});
+        let (link_id, link_popup_id) = add_annotation_with_popup(&mut doc, "Link", dictionary! {});
+
+        assert_eq!(doc.remove_annotations_with_subtypes(&[b"Text"]), 2);
+        assert!(!doc.objects.contains_key(&comment_id));
+        assert!(!doc.objects.contains_key(&comment_popup_id));
+        assert_eq!(doc.get_page_annotations(page_id).unwrap().len(), 2);
+
+        // Removing only the pop-up leaves no dangling /Popup entry on its parent.
+        assert_eq!(doc.remove_annotations_with_subtypes(&[b"Popup"]), 1);
+        assert!(!doc.objects.contains_key(&link_popup_id));
+        assert!(!doc.get_dictionary(link_id).unwrap().has(b"Popup"));
+        assert_eq!(doc.get_page_annotations(page_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn remove_hidden_layer_content_drops_marked_blocks() {
+        let mut doc = create_document();
+        let page_id = doc.page_iter().next().unwrap();
+        let layer_id = doc.add_object(dictionary! { "Type" => "OCG", "Name" => Object::string_literal("Draft") });
+        doc.catalog_mut().unwrap().set(
+            "OCProperties",
+            dictionary! {
+                "OCGs" => vec![layer_id.into()],
+                "D" => dictionary! { "OFF" => vec![layer_id.into()] },
+            },
+        );
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set("Resources", dictionary! { "Properties" => dictionary! { "L0" => layer_id } });
+        doc.change_page_content(
+            page_id,
+            b"/OC /L0 BDC BT (draft watermark) Tj ET /Span BMC EMC EMC BT (final) Tj ET".to_vec(),
+        )
+        .unwrap();
+
+        assert_eq!(doc.remove_hidden_layer_content(), 1);
+        let content = doc.get_page_content(page_id).unwrap();
+        let content = String::from_utf8(content).unwrap();
+        assert!(!content.contains("draft"));
+        assert!(content.contains("(final) Tj"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+use crate::{Document, IncrementalDocument, SaveOptions};
+
+/// Number of temporary file names tried before giving up.
+const TEMP_FILE_ATTEMPTS: u32 = 100;
+
+impl Document {
+    /// Save the document to `path` without ever leaving a partially written file there.
+    ///
+    /// The document is written to a temporary file next to `path` which then replaces `path`, so
+    /// on failure the previous file at `path`, if any, is left untouched. With
+    /// [`SaveOptionsBuilder::fsync`](crate::SaveOptionsBuilder::fsync) the file and its
+    /// directory are synced to disk before returning.
+    pub fn save_atomic<P: AsRef<Path>>(&mut self, path: P, options: SaveOptions) -> Result<()> {
+        let fsync = options.fsync;
+        write_atomic(path.as_ref(), fsync, |target| self.save_with_options(target, options))?;
+        Ok(())
+    }
+}
+
+impl IncrementalDocument {
+    /// Save the document to `path` without ever leaving a partially written file there, see
+    /// [`Document::save_atomic`]. Only [`SaveOptionsBuilder::fsync`](crate::SaveOptionsBuilder::fsync)
+    /// applies to incremental updates, and
+    /// [`SaveOptionsBuilder::compact_ids`](crate::SaveOptionsBuilder::compact_ids) is refused as an
+    /// update keeps the object numbers of the file, as are
+    /// [`SaveOptionsBuilder::encryption`](crate::SaveOptionsBuilder::encryption) as an update is
+    /// encrypted like the file and [`SaveOptionsBuilder::linearize`](crate::SaveOptionsBuilder::linearize).
+    pub fn save_atomic<P: AsRef<Path>>(&mut self, path: P, options: SaveOptions) -> Result<()> {
+        options.validate_update()?;
+        write_atomic(path.as_ref(), options.fsync, |target| self.save_to(target))?;
+        Ok(())
+    }
+}
+
+/// Write a file through a temporary sibling renamed over `path` once `write` succeeded. The
+/// temporary file is removed on failure. Returns the written file.
+pub(crate) fn write_atomic<F>(path: &Path, fsync: bool, write: F) -> Result<File>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let (temp_path, file) = create_temp_file(dir, path)?;
+    let result = (|| {
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        let file = writer.into_inner().map_err(|err| err.into_error())?;
+        if fsync {
+            file.sync_all()?;
+        }
+        // Replaces an existing file on all platforms, using MoveFileEx with
+        // MOVEFILE_REPLACE_EXISTING on Windows.
+        fs::rename(&temp_path, path)?;
+        Ok(file)
+    })();
+    match result {
+        Ok(file) => {
+            if fsync {
+                sync_dir(dir)?;
+            }
+            Ok(file)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(err)
+        }
+    }
+}
+
+/// Create a new file named after `path` in `dir`.
+fn create_temp_file(dir: &Path, path: &Path) -> Result<(PathBuf, File)> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "path has no file name"))?
+        .to_string_lossy();
+    for attempt in 0..TEMP_FILE_ATTEMPTS {
+        let temp_path = dir.join(format!(".{name}.{}-{attempt}.tmp", std::process::id()));
+        match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Err(Error::new(
+        ErrorKind::AlreadyExists,
+        "couldn't create a temporary file to save to",
+    ))
+}
+
+/// Make a rename in `dir` durable. Directories can't be synced on Windows.
+fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use std::io::Write;
+
+    /// A writer failing once `remaining` bytes have been written.
+    struct FailingWrite<W> {
+        inner: W,
+        remaining: usize,
+    }
+
+    impl<W: Write> Write for FailingWrite<W> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            if self.remaining == 0 {
+                return Err(Error::other("injected failure"));
+            }
+            let written = self.inner.write(&buf[..buf.len().min(self.remaining)])?;
+            self.remaining -= written;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    fn dir_entries(dir: &Path) -> Vec<PathBuf> {
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn save_atomic_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.pdf");
+        fs::write(&path, b"previous").unwrap();
+
+        let mut doc = create_document();
+        let options = SaveOptions::builder().fsync(true).build().unwrap();
+        doc.save_atomic(&path, options).unwrap();
+
+        let saved = Document::load_mem(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved.get_pages().len(), 1);
+        assert_eq!(dir_entries(dir.path()), [path]);
+    }
+
+    #[test]
+    fn failed_save_leaves_original_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.pdf");
+        fs::write(&path, b"original content").unwrap();
+
+        let mut doc = create_document();
+        let err = write_atomic(&path, true, |target| {
+            doc.save_to(&mut FailingWrite {
+                inner: target,
+                remaining: 100,
+            })
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "injected failure");
+
+        assert_eq!(fs::read(&path).unwrap(), b"original content");
+        assert_eq!(dir_entries(dir.path()), [path]);
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn incremental_save_over_its_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("incremental.pdf");
+        create_document().save(&path).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let mut doc = IncrementalDocument::load_from(bytes.as_slice()).unwrap();
+        let page_id = doc.get_prev_documents().page_iter().next().unwrap();
+        doc.opt_clone_object_to_new_document(page_id).unwrap();
+        doc.new_document.get_dictionary_mut(page_id).unwrap().set("Rotate", 90);
+        doc.save(&path).unwrap();
+
+        let saved = fs::read(&path).unwrap();
+        assert!(saved.starts_with(&bytes));
+        assert!(saved.len() > bytes.len());
+        assert_eq!(dir_entries(dir.path()), [path]);
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn incremental_save_refuses_compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("incremental.pdf");
+        create_document().save(&path).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let mut doc = IncrementalDocument::load_from(bytes.as_slice()).unwrap();
+        let options = SaveOptions::builder().compact_ids(true).build().unwrap();
+        let err = doc.save_atomic(&path, options).unwrap_err();
+        assert!(matches!(
+            err.get_ref().and_then(|err| err.downcast_ref()),
+            Some(crate::Error::InvalidSaveOptions(_))
+        ));
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+    }
+}
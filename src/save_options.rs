@@ -1,19 +1,95 @@
-use crate::ObjectStreamConfig;
+use crate::{CompressionSummary, EncryptionState, ObjectStreamConfig, Progress, ProgressSink};
 
-/// Options for saving PDF documents
+/// Options for saving PDF documents, made with [`SaveOptions::builder`] or one of the presets.
+/// The builder refuses combinations that can't be saved, so saving doesn't check them again.
 #[derive(Debug, Clone, Default)]
 pub struct SaveOptions {
     /// Enable object streams for compressing non-stream objects
-    pub use_object_streams: bool,
+    pub(crate) use_object_streams: bool,
     
     /// Enable cross-reference streams instead of traditional xref tables
-    pub use_xref_streams: bool,
-    
-    /// Enable linearization (fast web view)
-    pub linearize: bool,
+    pub(crate) use_xref_streams: bool,
     
+    /// Enable linearization (fast web view). Can't be combined with incremental updates.
+    pub(crate) linearize: bool,
+
     /// Configuration for object streams
-    pub object_stream_config: ObjectStreamConfig,
+    pub(crate) object_stream_config: ObjectStreamConfig,
+
+    /// Fail instead of writing anything that doesn't match the document exactly, see
+    /// [`Document::output_fallbacks`](crate::Document::output_fallbacks)
+    pub(crate) strict_output: bool,
+
+    /// Sync the file to disk before [`Document::save_atomic`](crate::Document::save_atomic) returns
+    pub(crate) fsync: bool,
+
+    /// Predictor applied to cross-reference streams before compressing them with Flate: 2 for
+    /// the TIFF predictor, 10 to 15 for the PNG predictors. Without a predictor cross-reference
+    /// streams are written uncompressed.
+    pub(crate) xref_stream_predictor: Option<u8>,
+
+    /// Byte widths of the fields of cross-reference stream entries, the stream's /W, instead of
+    /// `[1 4 2]` with the offset field widened as needed for files larger than 4 GiB. Saving
+    /// fails if a value doesn't fit its field.
+    pub(crate) xref_stream_widths: Option<[usize; 3]>,
+
+    /// List unused object numbers of cross-reference streams as free entries, in a single
+    /// subsection starting at object 0, instead of leaving them out
+    pub(crate) xref_stream_free_entries: bool,
+
+    /// Renumber the objects densely from 1 in the saved file, leaving the document itself
+    /// untouched. Can't be combined with incremental updates.
+    pub(crate) compact_ids: bool,
+
+    /// Compress the streams of the document with Flate before saving it, in the document as well,
+    /// see [`Document::compress_streams`](crate::Document::compress_streams)
+    pub(crate) compress_streams: bool,
+
+    /// Encrypt the saved file with this state, computed for the document, leaving the document
+    /// itself unencrypted, see [`Document::encrypt`](crate::Document::encrypt). Can't be combined
+    /// with object streams.
+    pub(crate) encryption: Option<EncryptionState>,
+
+    /// Encrypt the saved file as the document was when it was loaded, with the
+    /// [`Document::encryption_state`](crate::Document::encryption_state) it was decrypted with:
+    /// the same algorithm, key, passwords and permissions. The first element of the trailer /ID,
+    /// which keys up to revision 4 derive from, must be left as it was loaded. Does nothing for
+    /// documents that weren't encrypted or whose encryption was removed. Can't be combined with
+    /// [`SaveOptions::encryption`] or object streams.
+    pub(crate) preserve_encryption: bool,
+
+    /// Receives the progress of saving, in the [`SerializeObjects`](crate::Phase::SerializeObjects)
+    /// and [`WriteXref`](crate::Phase::WriteXref) phases
+    pub(crate) progress: Option<ProgressSink>,
+}
+
+/// A combination of [`SaveOptions`] that can't be saved, or that would be ignored.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SaveOptionsError {
+    #[error("encrypted files can't be saved with object streams")]
+    EncryptedObjectStreams,
+    #[error("a new encryption is set, but the encryption of the document is preserved")]
+    ConflictingEncryption,
+    #[error("the cross-reference stream {0} is set, but the file is saved with a cross-reference table")]
+    XrefStreamOptionWithoutXrefStreams(&'static str),
+    #[error("unsupported cross-reference stream predictor {0}, expected 2 or 10 to 15")]
+    UnsupportedXrefStreamPredictor(u8),
+    #[error("cross-reference stream field widths {0:?} need an offset field of 1 to 8 bytes and other fields of at most 8 bytes")]
+    InvalidXrefStreamWidths([usize; 3]),
+    #[error("compression level {0} is out of the range 0 to 9")]
+    InvalidCompressionLevel(u32),
+    #[error("object ids can't be compacted in an incremental update")]
+    CompactIdsInUpdate,
+    #[error("an incremental update can't change the encryption of the file")]
+    EncryptionInUpdate,
+    #[error("an incremental update can't be linearized")]
+    LinearizedUpdate,
+}
+
+impl From<SaveOptionsError> for std::io::Error {
+    fn from(err: SaveOptionsError) -> Self {
+        std::io::Error::other(crate::Error::InvalidSaveOptions(err.to_string()))
+    }
 }
 
 impl SaveOptions {
@@ -21,6 +97,108 @@ impl SaveOptions {
     pub fn builder() -> SaveOptionsBuilder {
         SaveOptionsBuilder::default()
     }
+
+    /// The smallest files: compressed streams, object streams in a predicted cross-reference
+    /// stream and densely numbered objects.
+    pub fn smallest() -> Self {
+        SaveOptions {
+            use_object_streams: true,
+            use_xref_streams: true,
+            object_stream_config: ObjectStreamConfig {
+                max_objects_per_stream: 100,
+                compression_level: 9,
+            },
+            xref_stream_predictor: Some(12),
+            compact_ids: true,
+            compress_streams: true,
+            ..Default::default()
+        }
+    }
+
+    /// The quickest to write: the document as it is, with a cross-reference table and nothing
+    /// compressed or renumbered.
+    pub fn fastest() -> Self {
+        SaveOptions::default()
+    }
+
+    /// For long-term storage: a cross-reference table and no object streams, which PDF/A-1
+    /// requires, compressed streams, no lossy fallback and the file synced to disk by
+    /// [`Document::save_atomic`](crate::Document::save_atomic).
+    pub fn archival() -> Self {
+        SaveOptions {
+            strict_output: true,
+            fsync: true,
+            compress_streams: true,
+            ..Default::default()
+        }
+    }
+
+    /// Small files that are quick to download and parse: compressed streams and object streams
+    /// in a cross-reference stream at the default compression level. lopdf doesn't linearize
+    /// files.
+    pub fn web_optimized() -> Self {
+        SaveOptions {
+            use_object_streams: true,
+            use_xref_streams: true,
+            object_stream_config: ObjectStreamConfig {
+                max_objects_per_stream: 100,
+                compression_level: 6,
+            },
+            compress_streams: true,
+            ..Default::default()
+        }
+    }
+
+    /// Check that the options can be combined, as [`SaveOptionsBuilder::build`] does.
+    fn validate(&self) -> Result<(), SaveOptionsError> {
+        if self.encryption.is_some() && self.preserve_encryption {
+            return Err(SaveOptionsError::ConflictingEncryption);
+        }
+        if (self.encryption.is_some() || self.preserve_encryption) && self.use_object_streams {
+            return Err(SaveOptionsError::EncryptedObjectStreams);
+        }
+        if !self.use_xref_streams && !self.use_object_streams {
+            let xref_stream_option = [
+                (self.xref_stream_predictor.is_some(), "predictor"),
+                (self.xref_stream_widths.is_some(), "field widths"),
+                (self.xref_stream_free_entries, "free entries"),
+            ];
+            if let Some((_, option)) = xref_stream_option.into_iter().find(|(set, _)| *set) {
+                return Err(SaveOptionsError::XrefStreamOptionWithoutXrefStreams(option));
+            }
+        }
+        if let Some(predictor) = self.xref_stream_predictor {
+            if predictor != 2 && !(10..=15).contains(&predictor) {
+                return Err(SaveOptionsError::UnsupportedXrefStreamPredictor(predictor));
+            }
+        }
+        if let Some(widths) = self.xref_stream_widths {
+            if widths[1] == 0 || widths.iter().any(|&width| width > 8) {
+                return Err(SaveOptionsError::InvalidXrefStreamWidths(widths));
+            }
+        }
+        if self.object_stream_config.compression_level > 9 {
+            return Err(SaveOptionsError::InvalidCompressionLevel(
+                self.object_stream_config.compression_level,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that the options apply to an incremental update, which keeps the object numbers, the
+    /// encryption and the layout of the file it's appended to.
+    pub(crate) fn validate_update(&self) -> Result<(), SaveOptionsError> {
+        if self.compact_ids {
+            return Err(SaveOptionsError::CompactIdsInUpdate);
+        }
+        if self.encryption.is_some() || self.preserve_encryption {
+            return Err(SaveOptionsError::EncryptionInUpdate);
+        }
+        if self.linearize {
+            return Err(SaveOptionsError::LinearizedUpdate);
+        }
+        Ok(())
+    }
 }
 
 /// Builder for SaveOptions
@@ -31,6 +209,16 @@ pub struct SaveOptionsBuilder {
     linearize: bool,
     max_objects_per_stream: usize,
     compression_level: u32,
+    strict_output: bool,
+    fsync: bool,
+    xref_stream_predictor: Option<u8>,
+    xref_stream_widths: Option<[usize; 3]>,
+    xref_stream_free_entries: bool,
+    compact_ids: bool,
+    compress_streams: bool,
+    encryption: Option<EncryptionState>,
+    preserve_encryption: bool,
+    progress: Option<ProgressSink>,
 }
 
 impl SaveOptionsBuilder {
@@ -64,9 +252,69 @@ pub fn compression_level(mut self, value: u32) -> Self {
         self
     }
     
-    /// Build the SaveOptions
-    pub fn build(self) -> SaveOptions {
-        SaveOptions {
+    /// Enable or disable failing on lossy fallbacks
+    pub fn strict_output(mut self, value: bool) -> Self {
+        self.strict_output = value;
+        self
+    }
+    
+    /// Enable or disable syncing atomically saved files to disk
+    pub fn fsync(mut self, value: bool) -> Self {
+        self.fsync = value;
+        self
+    }
+    
+    /// Set the predictor of cross-reference streams
+    pub fn xref_stream_predictor(mut self, value: Option<u8>) -> Self {
+        self.xref_stream_predictor = value;
+        self
+    }
+    
+    /// Set the field widths of cross-reference stream entries
+    pub fn xref_stream_widths(mut self, value: Option<[usize; 3]>) -> Self {
+        self.xref_stream_widths = value;
+        self
+    }
+    
+    /// Enable or disable listing free entries in cross-reference streams
+    pub fn xref_stream_free_entries(mut self, value: bool) -> Self {
+        self.xref_stream_free_entries = value;
+        self
+    }
+    
+    /// Enable or disable renumbering objects densely in the saved file
+    pub fn compact_ids(mut self, value: bool) -> Self {
+        self.compact_ids = value;
+        self
+    }
+    
+    /// Enable or disable compressing streams before saving
+    pub fn compress_streams(mut self, value: bool) -> Self {
+        self.compress_streams = value;
+        self
+    }
+    
+    /// Set the encryption of the saved file
+    pub fn encryption(mut self, state: EncryptionState) -> Self {
+        self.encryption = Some(state);
+        self
+    }
+    
+    /// Enable or disable encrypting the saved file as the document was when it was loaded
+    pub fn preserve_encryption(mut self, value: bool) -> Self {
+        self.preserve_encryption = value;
+        self
+    }
+    
+    /// Set the sink receiving the progress of saving
+    pub fn progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.progress = Some(ProgressSink::new(progress));
+        self
+    }
+    
+    /// Build the SaveOptions, refusing combinations that can't be saved, see [`SaveOptionsError`]
+    pub fn build(self) -> Result<SaveOptions, SaveOptionsError> {
+        let options = SaveOptions {
             use_object_streams: self.use_object_streams,
             use_xref_streams: self.use_xref_streams,
             linearize: self.linearize,
@@ -74,6 +322,122 @@ pub fn build(self) -> SaveOptions {
                 max_objects_per_stream: if self.max_objects_per_stream == 0 { 100 } else { self.max_objects_per_stream },
                 compression_level: self.compression_level,
             },
+            strict_output: self.strict_output,
+            fsync: self.fsync,
+            xref_stream_predictor: self.xref_stream_predictor,
+            xref_stream_widths: self.xref_stream_widths,
+            xref_stream_free_entries: self.xref_stream_free_entries,
+            compact_ids: self.compact_ids,
+            compress_streams: self.compress_streams,
+            encryption: self.encryption,
+            preserve_encryption: self.preserve_encryption,
+            progress: self.progress,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+/// What saving with [`Document::save_with_report`](crate::Document::save_with_report) did besides
+/// writing the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveReport {
+    /// What compressing streams did, when saving with [`SaveOptionsBuilder::compress_streams`]
+    pub compression: Option<CompressionSummary>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use crate::{EncryptionVersion, Permissions};
+
+    fn encryption_state() -> EncryptionState {
+        let document = create_document();
+        EncryptionState::try_from(EncryptionVersion::V1 {
+            document: &document,
+            owner_password: "owner",
+            user_password: "user",
+            permissions: Permissions::all(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn builder_refuses_conflicting_options() {
+        let cases = [
+            (
+                SaveOptions::builder().use_object_streams(true).encryption(encryption_state()),
+                SaveOptionsError::EncryptedObjectStreams,
+            ),
+            (
+                SaveOptions::builder().use_object_streams(true).preserve_encryption(true),
+                SaveOptionsError::EncryptedObjectStreams,
+            ),
+            (
+                SaveOptions::builder().preserve_encryption(true).encryption(encryption_state()),
+                SaveOptionsError::ConflictingEncryption,
+            ),
+            (
+                SaveOptions::builder().xref_stream_widths(Some([1, 4, 2])),
+                SaveOptionsError::XrefStreamOptionWithoutXrefStreams("field widths"),
+            ),
+            (
+                SaveOptions::builder().xref_stream_free_entries(true),
+                SaveOptionsError::XrefStreamOptionWithoutXrefStreams("free entries"),
+            ),
+            (
+                SaveOptions::builder().use_xref_streams(true).xref_stream_predictor(Some(3)),
+                SaveOptionsError::UnsupportedXrefStreamPredictor(3),
+            ),
+            (
+                SaveOptions::builder().use_xref_streams(true).xref_stream_widths(Some([1, 0, 2])),
+                SaveOptionsError::InvalidXrefStreamWidths([1, 0, 2]),
+            ),
+            (
+                SaveOptions::builder().use_xref_streams(true).xref_stream_widths(Some([1, 9, 2])),
+                SaveOptionsError::InvalidXrefStreamWidths([1, 9, 2]),
+            ),
+            (
+                SaveOptions::builder().compression_level(10),
+                SaveOptionsError::InvalidCompressionLevel(10),
+            ),
+        ];
+        for (builder, expected) in cases {
+            assert_eq!(builder.build().unwrap_err(), expected);
         }
+
+        // Object streams are written with a cross-reference stream.
+        let options = SaveOptions::builder()
+            .use_object_streams(true)
+            .xref_stream_predictor(Some(12))
+            .build()
+            .unwrap();
+        assert_eq!(options.xref_stream_predictor, Some(12));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn updates_refuse_whole_file_options() {
+        let compact_ids = SaveOptions::builder().compact_ids(true).build().unwrap();
+        assert_eq!(compact_ids.validate_update(), Err(SaveOptionsError::CompactIdsInUpdate));
+        let encrypted = SaveOptions::builder().encryption(encryption_state()).build().unwrap();
+        assert_eq!(encrypted.validate_update(), Err(SaveOptionsError::EncryptionInUpdate));
+        let preserved = SaveOptions::builder().preserve_encryption(true).build().unwrap();
+        assert_eq!(preserved.validate_update(), Err(SaveOptionsError::EncryptionInUpdate));
+        let linearized = SaveOptions::builder().linearize(true).build().unwrap();
+        assert_eq!(linearized.validate_update(), Err(SaveOptionsError::LinearizedUpdate));
+        assert_eq!(SaveOptions::default().validate_update(), Ok(()));
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for options in [
+            SaveOptions::smallest(),
+            SaveOptions::fastest(),
+            SaveOptions::archival(),
+            SaveOptions::web_optimized(),
+        ] {
+            assert_eq!(options.validate(), Ok(()));
+        }
+    }
+}
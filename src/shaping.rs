@@ -0,0 +1,386 @@
+use crate::common_data_structures::text_string;
+use crate::content::{Content, Operation};
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result, StringFormat};
+
+/// A glyph placed by a text shaper.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    /// The glyph id in the font program, written as the CID of the glyph
+    pub glyph_id: u16,
+    /// The horizontal advance of the glyph, in thousandths of the font size
+    pub advance: f32,
+    /// The byte offset in the logical text of the cluster the glyph belongs to
+    pub cluster: usize,
+}
+
+/// The glyphs a shaper produced for a text, in visual order from left to right.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShapedRun {
+    pub glyphs: Vec<ShapedGlyph>,
+}
+
+/// Turns text into positioned glyphs of a font.
+///
+/// lopdf doesn't shape text itself: implement this trait on top of a shaping engine such as
+/// HarfBuzz or rustybuzz to write scripts that need reordering, joining or ligatures with
+/// [`Document::add_shaped_text_to_page`]. The shaper receives the text in logical order and
+/// returns the glyphs in visual order, left to right, so right-to-left runs come out reversed.
+///
+/// A shaper working with a font program in font units converts its advances with
+/// `advance * 1000.0 / units_per_em`, and uses the byte offset of the first character of each
+/// cluster as its `cluster`.
+pub trait TextShaper {
+    fn shape(&self, text: &str) -> Result<ShapedRun>;
+}
+
+impl Document {
+    /// Shape `text` with `shaper` and write the glyphs on a page at `position`, in user space
+    /// units. See [`Document::add_shaped_run_to_page`].
+    pub fn add_shaped_text_to_page(
+        &mut self, page_id: ObjectId, font_id: ObjectId, font_size: f32, position: (f32, f32), text: &str,
+        shaper: &dyn TextShaper,
+    ) -> Result<()> {
+        let run = shaper.shape(text)?;
+        self.add_shaped_run_to_page(page_id, font_id, font_size, position, text, &run)
+    }
+
+    /// Write the glyphs of a shaped run of `text` on a page at `position`, in user space units.
+    ///
+    /// The font must be a Type0 font with the Identity-H encoding whose CIDs are glyph ids, as
+    /// with a /CIDToGIDMap of /Identity. Glyphs are moved to the advances of the run where they
+    /// differ from the font's widths. The text is marked with its logical order as /ActualText,
+    /// so it's extracted as written rather than in the visual order of the glyphs.
+    pub fn add_shaped_run_to_page(
+        &mut self, page_id: ObjectId, font_id: ObjectId, font_size: f32, position: (f32, f32), text: &str,
+        run: &ShapedRun,
+    ) -> Result<()> {
+        if let Some(glyph) = run
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.cluster >= text.len() || !text.is_char_boundary(glyph.cluster))
+        {
+            return Err(Error::InvalidShapedRun(format!(
+                "cluster {} of glyph {} is not a character of the text",
+                glyph.cluster, glyph.glyph_id
+            )));
+        }
+        let widths = cid_widths(self.get_dictionary(font_id)?, self)?;
+
+        let mut shown = Vec::new();
+        let mut bytes = Vec::new();
+        for glyph in &run.glyphs {
+            bytes.extend_from_slice(&glyph.glyph_id.to_be_bytes());
+            let adjustment = widths.width(glyph.glyph_id) - glyph.advance;
+            if adjustment.abs() > 0.001 {
+                shown.push(Object::String(std::mem::take(&mut bytes), StringFormat::Hexadecimal));
+                shown.push(adjustment.into());
+            }
+        }
+        if !bytes.is_empty() {
+            shown.push(Object::String(bytes, StringFormat::Hexadecimal));
+        }
+
+        let font_name = self.page_font_name(page_id, font_id)?;
+        let operations = vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec![Object::Name(font_name), font_size.into()]),
+            Operation::new("Td", vec![position.0.into(), position.1.into()]),
+            Operation::new(
+                "BDC",
+                vec![Object::Name(b"Span".to_vec()), dictionary! { "ActualText" => text_string(text) }.into()],
+            ),
+            Operation::new("TJ", vec![Object::Array(shown)]),
+            Operation::new("EMC", vec![]),
+            Operation::new("ET", vec![]),
+        ];
+        self.add_to_page_content(page_id, Content { operations })
+    }
+}
+
+/// The glyph widths of a CIDFont, from its /W array and /DW default width.
+pub(crate) struct CidWidths {
+    default: f32,
+    ranges: Vec<(u32, u32, f32)>,
+}
+
+impl CidWidths {
+    pub(crate) fn width(&self, cid: u16) -> f32 {
+        let cid = u32::from(cid);
+        self.ranges
+            .iter()
+            .find(|(first, last, _)| (*first..=*last).contains(&cid))
+            .map_or(self.default, |(_, _, width)| *width)
+    }
+}
+
+/// Read the widths of the descendant font of a Type0 font, checking that glyph ids can be shown
+/// as CIDs through the Identity-H encoding.
+pub(crate) fn cid_widths(font: &Dictionary, doc: &Document) -> Result<CidWidths> {
+    let unsupported = Error::Unimplemented("shaped text in fonts other than Type0 fonts with Identity-H encoding");
+    if font.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Type0".as_slice())
+        || font.get(b"Encoding").and_then(Object::as_name).ok() != Some(b"Identity-H".as_slice())
+    {
+        return Err(unsupported);
+    }
+    let descendant = font
+        .get_deref(b"DescendantFonts", doc)
+        .and_then(Object::as_array)
+        .ok()
+        .and_then(|fonts| fonts.first())
+        .and_then(|font| doc.dereference(font).ok())
+        .and_then(|(_, font)| font.as_dict().ok());
+    let Some(descendant) = descendant else {
+        return Ok(CidWidths {
+            default: 1000.0,
+            ranges: Vec::new(),
+        });
+    };
+    match descendant.get_deref(b"CIDToGIDMap", doc) {
+        Ok(Object::Name(name)) if name == b"Identity" => {}
+        Ok(_) => return Err(unsupported),
+        // Without a map, CIDs are glyph ids of TrueType fonts.
+        Err(_) => {}
+    }
+
+    let number = |object: &Object| doc.dereference(object).ok().and_then(|(_, value)| value.as_float().ok());
+    let default = descendant.get_deref(b"DW", doc).ok().and_then(number).unwrap_or(1000.0);
+    let mut ranges = Vec::new();
+    let entries = descendant
+        .get_deref(b"W", doc)
+        .and_then(Object::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut index = 0;
+    // Entries are either `first [w1 w2 ...]` or `first last w`.
+    while index + 1 < entries.len() {
+        let Some(first) = number(&entries[index]).map(|first| first as u32) else {
+            break;
+        };
+        if let Ok(widths) = doc.dereference(&entries[index + 1]).and_then(|(_, widths)| widths.as_array()) {
+            for (offset, width) in widths.iter().enumerate() {
+                if let Some(width) = number(width) {
+                    ranges.push((first + offset as u32, first + offset as u32, width));
+                }
+            }
+            index += 2;
+        } else {
+            let (Some(last), Some(width)) = (number(&entries[index + 1]), entries.get(index + 2).and_then(number))
+            else {
+                break;
+            };
+            ranges.push((first, last as u32, width));
+            index += 3;
+        }
+    }
+    Ok(CidWidths { default, ranges })
+}
+
+/// A fallback shaper that reorders text with the Unicode bidirectional algorithm (UAX #9) and
+/// maps each character to a glyph through the cmap of a TrueType or OpenType font program.
+///
+/// Characters aren't joined, substituted or mirrored: it suits scripts like Hebrew, but not
+/// Arabic. Use a shaping engine through [`TextShaper`] for complex scripts.
+#[cfg(feature = "bidi")]
+pub struct BidiShaper<'a> {
+    face: ttf_parser::Face<'a>,
+}
+
+#[cfg(feature = "bidi")]
+impl<'a> BidiShaper<'a> {
+    /// Parse a TrueType or OpenType font program, returning `None` if it can't be parsed.
+    pub fn new(font_program: &'a [u8]) -> Option<Self> {
+        let face = ttf_parser::Face::parse(font_program, 0).ok()?;
+        Some(BidiShaper { face })
+    }
+}
+
+#[cfg(feature = "bidi")]
+impl TextShaper for BidiShaper<'_> {
+    fn shape(&self, text: &str) -> Result<ShapedRun> {
+        let units_per_em = f32::from(self.face.units_per_em());
+        let glyphs = visual_order(text)
+            .into_iter()
+            .filter_map(|(cluster, ch)| {
+                let glyph = self.face.glyph_index(ch)?;
+                let advance = f32::from(self.face.glyph_hor_advance(glyph).unwrap_or(0));
+                Some(ShapedGlyph {
+                    glyph_id: glyph.0,
+                    advance: advance * 1000.0 / units_per_em,
+                    cluster,
+                })
+            })
+            .collect();
+        Ok(ShapedRun { glyphs })
+    }
+}
+
+/// Reorder the characters of each paragraph of `text` into visual order, keeping the byte offset
+/// of each character. Line breaks are left out.
+#[cfg(feature = "bidi")]
+pub(crate) fn visual_order(text: &str) -> Vec<(usize, char)> {
+    let info = unicode_bidi::BidiInfo::new(text, None);
+    let mut ordered = Vec::with_capacity(text.len());
+    for paragraph in &info.paragraphs {
+        let line = paragraph.range.clone();
+        let (levels, runs) = info.visual_runs(paragraph, line);
+        for run in runs {
+            let chars = text[run.clone()]
+                .char_indices()
+                .map(|(offset, ch)| (run.start + offset, ch))
+                .filter(|(_, ch)| *ch != '\n' && *ch != '\r');
+            if levels[run.start].is_rtl() {
+                ordered.extend(chars.rev());
+            } else {
+                ordered.extend(chars);
+            }
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+    use crate::Stream;
+
+    /// "Shalom olam" in Hebrew, spelled שלום עולם.
+    const HEBREW: &str = "שלום עולם";
+
+    /// Maps the Hebrew letters of a text to glyph ids 1 to 27 and spaces to glyph 28, reversing
+    /// the text as every character is right-to-left.
+    struct StubShaper;
+
+    fn stub_glyph(ch: char) -> u16 {
+        match ch {
+            ' ' => 28,
+            _ => (ch as u32 - 0x05D0 + 1) as u16,
+        }
+    }
+
+    impl TextShaper for StubShaper {
+        fn shape(&self, text: &str) -> Result<ShapedRun> {
+            let glyphs = text
+                .char_indices()
+                .rev()
+                .map(|(cluster, ch)| ShapedGlyph {
+                    glyph_id: stub_glyph(ch),
+                    advance: if ch == ' ' { 250.0 } else { 600.0 },
+                    cluster,
+                })
+                .collect();
+            Ok(ShapedRun { glyphs })
+        }
+    }
+
+    /// Add a Type0 font whose ToUnicode CMap maps the stub glyphs back to their letters.
+    fn add_hebrew_font(doc: &mut Document) -> ObjectId {
+        let mut cmap = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n/CMapName /Hebrew def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n28 beginbfchar\n",
+        );
+        for ch in ('\u{05D0}'..='\u{05EA}').chain([' ']) {
+            cmap.push_str(&format!("<{:04X}> <{:04X}>\n", stub_glyph(ch), ch as u32));
+        }
+        cmap.push_str("endbfchar\nendcmap\nCMapName currentdict /CMap defineresource pop\nend\nend");
+        let to_unicode = doc.add_object(Stream::new(dictionary! {}, cmap.into_bytes()));
+        let descendant = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "CIDFontType2",
+            "BaseFont" => "HebrewStub",
+            "CIDToGIDMap" => "Identity",
+            "DW" => 600,
+            "W" => vec![28.into(), vec![250.into()].into()],
+        });
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "HebrewStub",
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![descendant.into()],
+            "ToUnicode" => to_unicode,
+        })
+    }
+
+    #[test]
+    fn hebrew_text_extracts_in_logical_order() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let font_id = add_hebrew_font(&mut doc);
+        let page_id = doc.page_iter().next().unwrap();
+        doc.add_shaped_text_to_page(page_id, font_id, 14.0, (72.0, 600.0), HEBREW, &StubShaper)
+            .unwrap();
+
+        let content = doc.get_and_decode_page_content(page_id).unwrap();
+        let shown = content.operations.iter().find(|op| op.operator == "TJ").unwrap();
+        let shown = shown.operands[0].as_array().unwrap();
+        assert_eq!(shown.len(), 1, "advances match the font's widths");
+        let expected: Vec<u8> = HEBREW.chars().rev().flat_map(|ch| stub_glyph(ch).to_be_bytes()).collect();
+        assert_eq!(shown[0].as_str().unwrap(), expected);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        let doc = Document::load_mem(&bytes).unwrap();
+        let text = doc.extract_text(&[1]).unwrap();
+        assert!(text.contains(HEBREW), "{text:?}");
+        assert!(!text.contains(&HEBREW.chars().rev().collect::<String>()));
+    }
+
+    #[test]
+    fn advances_differing_from_widths_adjust_glyphs() {
+        let mut doc = create_document_with_texts(&["page"]);
+        let font_id = add_hebrew_font(&mut doc);
+        let page_id = doc.page_iter().next().unwrap();
+        let run = ShapedRun {
+            glyphs: vec![
+                ShapedGlyph {
+                    glyph_id: 2,
+                    advance: 550.0,
+                    cluster: 2,
+                },
+                ShapedGlyph {
+                    glyph_id: 1,
+                    advance: 600.0,
+                    cluster: 0,
+                },
+            ],
+        };
+        doc.add_shaped_run_to_page(page_id, font_id, 12.0, (0.0, 0.0), "אב", &run).unwrap();
+        let content = doc.get_and_decode_page_content(page_id).unwrap();
+        let shown = content.operations.iter().find(|op| op.operator == "TJ").unwrap();
+        assert_eq!(
+            shown.operands[0].as_array().unwrap(),
+            &vec![
+                Object::String(vec![0, 2], StringFormat::Hexadecimal),
+                50.into(),
+                Object::String(vec![0, 1], StringFormat::Hexadecimal),
+            ]
+        );
+
+        let bad_run = ShapedRun {
+            glyphs: vec![ShapedGlyph {
+                glyph_id: 1,
+                advance: 600.0,
+                cluster: 1,
+            }],
+        };
+        let result = doc.add_shaped_run_to_page(page_id, font_id, 12.0, (0.0, 0.0), "אב", &bad_run);
+        assert!(matches!(result, Err(Error::InvalidShapedRun(_))));
+        let helvetica = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let result = doc.add_shaped_run_to_page(page_id, helvetica, 12.0, (0.0, 0.0), "אב", &run);
+        assert!(matches!(result, Err(Error::Unimplemented(_))));
+    }
+
+    #[cfg(feature = "bidi")]
+    #[test]
+    fn bidi_fallback_reorders_right_to_left_runs() {
+        let text = "abc שלום def";
+        let visual: String = visual_order(text).into_iter().map(|(_, ch)| ch).collect();
+        assert_eq!(visual, "abc םולש def");
+        let offsets: Vec<usize> = visual_order("אב").into_iter().map(|(offset, _)| offset).collect();
+        assert_eq!(offsets, [2, 0]);
+    }
+}
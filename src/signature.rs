@@ -0,0 +1,364 @@
+use std::collections::HashSet;
+
+use log::warn;
+
+use crate::{Dictionary, Document, IncrementalDocument, Object, ObjectId, Result, StringFormat};
+
+/// Changes a certification signature allows, the /P entry of the DocMDP transform parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CertificationLevel {
+    /// No changes to the document are permitted
+    NoChanges = 1,
+    /// Filling in forms, instantiating page templates and signing
+    FormFilling = 2,
+    /// Form filling and signing, plus creating, deleting and modifying annotations
+    Annotations = 3,
+}
+
+impl CertificationLevel {
+    pub fn from_permissions(p: i64) -> Option<Self> {
+        match p {
+            1 => Some(CertificationLevel::NoChanges),
+            2 => Some(CertificationLevel::FormFilling),
+            3 => Some(CertificationLevel::Annotations),
+            _ => None,
+        }
+    }
+
+    pub fn permissions(self) -> i64 {
+        self as i64
+    }
+}
+
+/// An unsigned signature field to be filled in by an external signer.
+#[derive(Debug, Clone)]
+pub struct SignaturePlaceholder {
+    field_name: String,
+    contents_size: usize,
+    certification: Option<CertificationLevel>,
+    reason: Option<String>,
+}
+
+impl SignaturePlaceholder {
+    /// Default number of bytes reserved for the signature /Contents.
+    pub const DEFAULT_CONTENTS_SIZE: usize = 8192;
+
+    pub fn new<S: Into<String>>(field_name: S) -> Self {
+        SignaturePlaceholder {
+            field_name: field_name.into(),
+            contents_size: Self::DEFAULT_CONTENTS_SIZE,
+            certification: None,
+            reason: None,
+        }
+    }
+
+    /// Make the signature a certification signature allowing the given changes.
+    pub fn certification(mut self, level: CertificationLevel) -> Self {
+        self.certification = Some(level);
+        self
+    }
+
+    /// Number of bytes reserved for the signature /Contents.
+    pub fn contents_size(mut self, size: usize) -> Self {
+        self.contents_size = size;
+        self
+    }
+
+    pub fn reason<S: Into<String>>(mut self, reason: S) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+impl Document {
+    /// Get the certification level declared by the DocMDP signature in the catalog's /Perms.
+    pub fn get_certification_level(&self) -> Option<CertificationLevel> {
+        let signature = self
+            .catalog()
+            .and_then(|catalog| catalog.get_deref(b"Perms", self))
+            .and_then(Object::as_dict)
+            .and_then(|perms| perms.get_deref(b"DocMDP", self))
+            .and_then(Object::as_dict)
+            .ok()?;
+        let references = signature
+            .get_deref(b"Reference", self)
+            .and_then(Object::as_array)
+            .ok()?;
+        let doc_mdp = references
+            .iter()
+            .filter_map(|reference| self.dereference(reference).ok()?.1.as_dict().ok())
+            .find(|reference| reference.get(b"TransformMethod").and_then(Object::as_name).ok() == Some(b"DocMDP"))?;
+        // /P defaults to 2 when the transform parameters leave it out.
+        let permissions = doc_mdp
+            .get_deref(b"TransformParams", self)
+            .and_then(Object::as_dict)
+            .and_then(|params| params.get(b"P"))
+            .and_then(Object::as_i64)
+            .unwrap_or(2);
+        CertificationLevel::from_permissions(permissions)
+    }
+
+    /// Add an invisible signature field on the first page with an unsigned signature dictionary.
+    ///
+    /// The /ByteRange and /Contents of the signature dictionary are placeholders for the signer
+    /// to fill in. For a certification signature, the DocMDP transform is declared in the
+    /// signature's /Reference array and the catalog's /Perms. Returns the id of the signature
+    /// dictionary.
+    pub fn add_signature_placeholder(&mut self, placeholder: &SignaturePlaceholder) -> Result<ObjectId> {
+        let page_id = self.page_iter().next().ok_or(crate::Error::PageNumberNotFound(1))?;
+
+        let mut signature = dictionary! {
+            "Type" => "Sig",
+            "Filter" => "Adobe.PPKLite",
+            "SubFilter" => "adbe.pkcs7.detached",
+            "ByteRange" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+            "Contents" => Object::String(vec![0; placeholder.contents_size], StringFormat::Hexadecimal),
+        };
+        if let Some(reason) = &placeholder.reason {
+            signature.set("Reason", crate::text_string(reason));
+        }
+        if let Some(level) = placeholder.certification {
+            signature.set(
+                "Reference",
+                vec![Object::Dictionary(dictionary! {
+                    "Type" => "SigRef",
+                    "TransformMethod" => "DocMDP",
+                    "TransformParams" => dictionary! {
+                        "Type" => "TransformParams",
+                        "P" => level.permissions(),
+                        "V" => "1.2",
+                    },
+                })],
+            );
+        }
+        let signature_id = self.add_object(signature);
+        if placeholder.certification.is_some() {
+            if self.get_certification_level().is_some() {
+                warn!("replacing the existing certification signature, a document can only have one");
+            }
+            self.catalog_mut()?
+                .set("Perms", dictionary! { "DocMDP" => signature_id });
+        }
+
+        const PRINT: i64 = 1 << 2;
+        const LOCKED: i64 = 1 << 7;
+        let field_id = self.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "FT" => "Sig",
+            "T" => crate::text_string(&placeholder.field_name),
+            "V" => signature_id,
+            "Rect" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+            "F" => PRINT | LOCKED,
+            "P" => page_id,
+        });
+        append_to_array(self, page_id, b"Annots", field_id)?;
+
+        let acro_form_id = self.catalog()?.get(b"AcroForm").and_then(Object::as_reference).ok();
+        let acro_form = match acro_form_id {
+            Some(id) => self.get_dictionary_mut(id)?,
+            None => {
+                let catalog = self.catalog_mut()?;
+                if !catalog.has(b"AcroForm") {
+                    catalog.set("AcroForm", dictionary! { "Fields" => vec![] });
+                }
+                catalog.get_mut(b"AcroForm")?.as_dict_mut()?
+            }
+        };
+        // SignaturesExist | AppendOnly
+        acro_form.set("SigFlags", 3);
+        match acro_form.get(b"Fields").and_then(Object::as_reference) {
+            Ok(fields_id) => {
+                if let Ok(fields) = self.get_object_mut(fields_id).and_then(Object::as_array_mut) {
+                    fields.push(field_id.into());
+                }
+            }
+            Err(_) => match acro_form.get_mut(b"Fields").and_then(Object::as_array_mut) {
+                Ok(fields) => fields.push(field_id.into()),
+                Err(_) => acro_form.set("Fields", vec![field_id.into()]),
+            },
+        }
+
+        Ok(signature_id)
+    }
+}
+
+/// Append a reference to the array stored under `key` in a dictionary, directly or indirectly.
+pub(crate) fn append_to_array(doc: &mut Document, dict_id: ObjectId, key: &[u8], id: ObjectId) -> Result<()> {
+    if let Ok(array_id) = doc.get_dictionary(dict_id)?.get(key).and_then(Object::as_reference) {
+        doc.get_object_mut(array_id)?.as_array_mut()?.push(id.into());
+        return Ok(());
+    }
+    let dict = doc.get_dictionary_mut(dict_id)?;
+    match dict.get_mut(key).and_then(Object::as_array_mut) {
+        Ok(array) => array.push(id.into()),
+        Err(_) => dict.set(key, vec![id.into()]),
+    }
+    Ok(())
+}
+
+impl IncrementalDocument {
+    /// Describe the changes of the incremental update that the certification signature of the
+    /// previous document does not allow.
+    ///
+    /// This is a heuristic: page content, page attributes and, below
+    /// [`CertificationLevel::Annotations`], annotations other than widgets are reported.
+    pub fn certification_violations(&self) -> Vec<String> {
+        let prev = self.get_prev_documents();
+        let Some(level) = prev.get_certification_level() else {
+            return Vec::new();
+        };
+        let page_ids: HashSet<ObjectId> = prev.page_iter().collect();
+        let content_ids: HashSet<ObjectId> = page_ids.iter().flat_map(|id| prev.get_page_contents(*id)).collect();
+
+        let mut violations = Vec::new();
+        for (id, object) in &self.new_document.objects {
+            let previous = prev.get_object(*id).ok();
+            if previous == Some(object) {
+                continue;
+            }
+            if level == CertificationLevel::NoChanges {
+                violations.push(format!(
+                    "object {} {} is modified, but the document is certified without allowing changes",
+                    id.0, id.1
+                ));
+                continue;
+            }
+            if content_ids.contains(id) {
+                violations.push(format!("page content stream {} {} is modified", id.0, id.1));
+                continue;
+            }
+            let Ok(dict) = object.as_dict() else {
+                continue;
+            };
+            if let Some(previous) = previous.and_then(|previous| previous.as_dict().ok()) {
+                if page_ids.contains(id) {
+                    violations.extend(
+                        changed_keys(previous, dict)
+                            .into_iter()
+                            .filter(|key| key != b"Annots")
+                            .map(|key| {
+                                format!(
+                                    "page {} {} entry /{} is modified",
+                                    id.0,
+                                    id.1,
+                                    String::from_utf8_lossy(&key)
+                                )
+                            }),
+                    );
+                }
+            }
+            let subtype = dict.get(b"Subtype").and_then(Object::as_name).ok();
+            let is_annotation = dict.has_type(b"Annot") || (subtype.is_some() && dict.has(b"Rect"));
+            if is_annotation && subtype != Some(b"Widget") && level < CertificationLevel::Annotations {
+                violations.push(format!(
+                    "annotation {} {} is added or modified, which requires certification level 3",
+                    id.0, id.1
+                ));
+            }
+        }
+        violations
+    }
+
+    /// Log a warning for every change the previous document's certification does not allow.
+    pub(crate) fn warn_certification_violations(&self) {
+        for violation in self.certification_violations() {
+            warn!("{violation}");
+        }
+    }
+}
+
+fn changed_keys(before: &Dictionary, after: &Dictionary) -> Vec<Vec<u8>> {
+    let mut keys: Vec<Vec<u8>> = before
+        .iter()
+        .chain(after.iter())
+        .map(|(key, _)| key.clone())
+        .filter(|key| before.get(key).ok() != after.get(key).ok())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+
+    fn certified_bytes(level: CertificationLevel) -> Vec<u8> {
+        let mut doc = create_document();
+        let placeholder = SignaturePlaceholder::new("Certification")
+            .certification(level)
+            .contents_size(64);
+        doc.add_signature_placeholder(&placeholder).unwrap();
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn placeholder_declares_doc_mdp() {
+        let doc = Document::load_mem(&certified_bytes(CertificationLevel::FormFilling)).unwrap();
+        assert_eq!(doc.get_certification_level(), Some(CertificationLevel::FormFilling));
+
+        let signature_id = doc
+            .catalog()
+            .and_then(|catalog| catalog.get(b"Perms"))
+            .and_then(Object::as_dict)
+            .and_then(|perms| perms.get(b"DocMDP"))
+            .and_then(Object::as_reference)
+            .unwrap();
+        let signature = doc.get_dictionary(signature_id).unwrap();
+        assert_eq!(signature.get(b"Contents").and_then(Object::as_str).unwrap(), [0; 64]);
+        let reference = signature.get(b"Reference").and_then(Object::as_array).unwrap()[0]
+            .as_dict()
+            .unwrap();
+        assert_eq!(
+            reference.get(b"TransformMethod").and_then(Object::as_name).unwrap(),
+            b"DocMDP"
+        );
+        let params = reference.get(b"TransformParams").and_then(Object::as_dict).unwrap();
+        assert_eq!(params.get(b"P").and_then(Object::as_i64).unwrap(), 2);
+
+        assert_eq!(doc.get_form_fields().len(), 1);
+        let acro_form = doc
+            .catalog()
+            .unwrap()
+            .get(b"AcroForm")
+            .and_then(Object::as_dict)
+            .unwrap();
+        assert_eq!(acro_form.get(b"SigFlags").and_then(Object::as_i64).unwrap(), 3);
+        assert_eq!(create_document().get_certification_level(), None);
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn incremental_update_reports_changes_beyond_certification() {
+        let mut incremental = IncrementalDocument::load_from(certified_bytes(CertificationLevel::FormFilling).as_slice()).unwrap();
+        let page_id = incremental.get_prev_documents().page_iter().next().unwrap();
+        assert!(incremental.certification_violations().is_empty());
+
+        // Cloning objects without changing them is fine.
+        incremental.opt_clone_object_to_new_document(page_id).unwrap();
+        assert!(incremental.certification_violations().is_empty());
+
+        let content_id = incremental.get_prev_documents().get_page_contents(page_id)[0];
+        incremental.opt_clone_object_to_new_document(content_id).unwrap();
+        let content = incremental
+            .new_document
+            .get_object_mut(content_id)
+            .and_then(Object::as_stream_mut)
+            .unwrap();
+        content.set_plain_content(b"BT /F1 48 Tf 100 600 Td (Changed) Tj ET".to_vec());
+        incremental.new_document.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Text",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+        });
+
+        let violations = incremental.certification_violations();
+        assert_eq!(violations.len(), 2);
+        assert!(violations[0].starts_with("page content stream"));
+        assert!(violations[1].contains("requires certification level 3"));
+    }
+}
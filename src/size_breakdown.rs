@@ -0,0 +1,430 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::writer::Writer;
+use crate::xref::{XrefEntry, XrefType};
+use crate::{Document, Object, ObjectId};
+
+/// What the bytes of a document are spent on.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SizeCategory {
+    /// Image XObjects
+    Images,
+    /// A font with its descriptor, font program and encoding data, by /BaseFont
+    Font(String),
+    /// Page content streams and Form XObjects
+    ContentStreams,
+    /// XMP metadata streams and the document information dictionary
+    Metadata,
+    /// Object stream headers and compression overhead
+    ObjectStreams,
+    /// Cross-reference tables and streams
+    Xref,
+    Other,
+}
+
+impl fmt::Display for SizeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SizeCategory::Images => write!(f, "images"),
+            SizeCategory::Font(name) => write!(f, "font {name}"),
+            SizeCategory::ContentStreams => write!(f, "content streams"),
+            SizeCategory::Metadata => write!(f, "metadata"),
+            SizeCategory::ObjectStreams => write!(f, "object streams"),
+            SizeCategory::Xref => write!(f, "xref"),
+            SizeCategory::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// Bytes spent on one category.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategorySize {
+    pub category: SizeCategory,
+    pub size: usize,
+    /// Number of objects in the category
+    pub objects: usize,
+}
+
+/// Bytes spent on one object.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectSize {
+    pub id: ObjectId,
+    pub size: usize,
+    /// /Subtype or /Type of the object, or the kind of object if it has neither
+    pub type_name: String,
+    pub category: SizeCategory,
+    /// Numbers of the pages using the object
+    pub pages: Vec<u32>,
+}
+
+/// Size of a document attributed to its objects, see [`Document::size_breakdown`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    pub total: usize,
+    /// Whether the sizes were measured in the loaded file rather than by serializing the objects
+    pub from_file: bool,
+    /// Categories, largest first
+    pub categories: Vec<CategorySize>,
+    /// The largest objects, largest first
+    pub largest: Vec<ObjectSize>,
+}
+
+impl fmt::Display for SizeBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let source = if self.from_file { "measured in file" } else { "serialized" };
+        writeln!(f, "Total: {} bytes ({source})", self.total)?;
+        writeln!(f, "{:<40} {:>12} {:>7} {:>8}", "Category", "Bytes", "Share", "Objects")?;
+        for category in &self.categories {
+            writeln!(
+                f,
+                "{:<40} {:>12} {:>6.1}% {:>8}",
+                category.category.to_string(),
+                category.size,
+                share(category.size, self.total),
+                category.objects
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<12} {:<16} {:>12} {:>7}  {:<24} Pages", "Object", "Type", "Bytes", "Share", "Category")?;
+        for object in &self.largest {
+            let pages: Vec<String> = object.pages.iter().map(u32::to_string).collect();
+            writeln!(
+                f,
+                "{:<12} {:<16} {:>12} {:>6.1}%  {:<24} {}",
+                format!("{} {}", object.id.0, object.id.1),
+                object.type_name,
+                object.size,
+                share(object.size, self.total),
+                object.category.to_string(),
+                pages.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn share(size: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { size as f64 * 100.0 / total as f64 }
+}
+
+/// Size of a cross-reference table entry.
+const XREF_ENTRY_SIZE: usize = 20;
+
+impl Document {
+    /// Attribute the size of the document to its objects and roll it up by category, listing the
+    /// `top` largest objects with the pages that use them.
+    ///
+    /// For a loaded document the sizes are the spans of the objects in the file as it was loaded,
+    /// with objects in object streams sharing the stream's content by their serialized size.
+    /// Otherwise the objects are serialized the way [`Document::save`] writes them. Cross-reference
+    /// tables are estimated at 20 bytes per entry.
+    pub fn size_breakdown(&self, top: usize) -> SizeBreakdown {
+        let from_file = self.xref_start > 0;
+        let sizes = if from_file { self.file_object_sizes() } else { self.serialized_object_sizes() };
+        let categories = self.object_categories();
+
+        let mut by_category: BTreeMap<SizeCategory, CategorySize> = BTreeMap::new();
+        let mut add = |category: SizeCategory, size: usize| {
+            let entry = by_category.entry(category.clone()).or_insert(CategorySize {
+                category,
+                size: 0,
+                objects: 0,
+            });
+            entry.size += size;
+            entry.objects += 1;
+        };
+        for (id, size) in &sizes {
+            add(categories.get(id).cloned().unwrap_or(SizeCategory::Other), *size);
+        }
+        let uses_table = matches!(self.reference_table.cross_reference_type, XrefType::CrossReferenceTable);
+        if !from_file || uses_table {
+            let entries = self.objects.keys().map(|id| id.0).max().unwrap_or(0) as usize + 1;
+            add(SizeCategory::Xref, entries * XREF_ENTRY_SIZE);
+        }
+
+        let mut categories_by_size: Vec<CategorySize> = by_category.into_values().collect();
+        categories_by_size.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.category.cmp(&b.category)));
+
+        let mut largest: Vec<(ObjectId, usize)> = sizes.iter().map(|(id, size)| (*id, *size)).collect();
+        largest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        largest.truncate(top);
+        let pages = self.pages_using(largest.iter().map(|(id, _)| *id).collect());
+        let largest = largest
+            .into_iter()
+            .map(|(id, size)| ObjectSize {
+                id,
+                size,
+                type_name: self.objects.get(&id).map(object_type_name).unwrap_or_default(),
+                category: categories.get(&id).cloned().unwrap_or(SizeCategory::Other),
+                pages: pages.get(&id).cloned().unwrap_or_default(),
+            })
+            .collect();
+
+        SizeBreakdown {
+            total: categories_by_size.iter().map(|category| category.size).sum(),
+            from_file,
+            categories: categories_by_size,
+            largest,
+        }
+    }
+
+    /// Sizes of the objects as the writer would write them. Object and cross-reference streams
+    /// are left out, as the writer doesn't write them.
+    fn serialized_object_sizes(&self) -> BTreeMap<ObjectId, usize> {
+        self.objects
+            .iter()
+            .filter(|(_, object)| {
+                !object
+                    .as_stream()
+                    .is_ok_and(|stream| stream.dict.has_type(b"ObjStm") || stream.dict.has_type(b"XRef"))
+            })
+            .map(|(id, object)| (*id, Writer::indirect_object_size(*id, object)))
+            .collect()
+    }
+
+    /// Sizes of the objects from their offsets in the loaded file.
+    fn file_object_sizes(&self) -> BTreeMap<ObjectId, usize> {
+        let mut offsets: Vec<(usize, ObjectId)> = self
+            .reference_table
+            .entries
+            .iter()
+            .filter_map(|(id, entry)| match entry {
+                XrefEntry::Normal { offset, generation } => Some((*offset as usize, (*id, *generation))),
+                _ => None,
+            })
+            .collect();
+        offsets.sort();
+
+        let mut sizes = BTreeMap::new();
+        for (index, (offset, id)) in offsets.iter().enumerate() {
+            let end = offsets.get(index + 1).map_or(self.xref_start, |(next, _)| *next);
+            if end > *offset && self.objects.contains_key(id) {
+                sizes.insert(*id, end - offset);
+            }
+        }
+
+        // Objects in object streams share the stream content by their serialized size, the rest
+        // of the object stream is overhead.
+        let mut members: BTreeMap<u32, Vec<ObjectId>> = BTreeMap::new();
+        for (id, entry) in &self.reference_table.entries {
+            if let XrefEntry::Compressed { container, .. } = entry {
+                if self.objects.contains_key(&(*id, 0)) {
+                    members.entry(*container).or_default().push((*id, 0));
+                }
+            }
+        }
+        for (container, members) in members {
+            let container_id = (container, 0);
+            let (Some(container_size), Ok(stream)) = (
+                sizes.get(&container_id).copied(),
+                self.get_object(container_id).and_then(Object::as_stream),
+            ) else {
+                continue;
+            };
+            let content_size = stream.content.len().min(container_size);
+            let serialized: Vec<usize> = members
+                .iter()
+                .map(|id| Writer::indirect_object_size(*id, &self.objects[id]))
+                .collect();
+            let serialized_total: usize = serialized.iter().sum();
+            if serialized_total == 0 {
+                continue;
+            }
+            for (id, size) in members.iter().zip(serialized) {
+                sizes.insert(*id, content_size * size / serialized_total);
+            }
+            sizes.insert(container_id, container_size - content_size);
+        }
+        sizes
+    }
+
+    /// Category of every object that isn't [`SizeCategory::Other`].
+    fn object_categories(&self) -> HashMap<ObjectId, SizeCategory> {
+        let mut categories = HashMap::new();
+
+        // Fonts claim the objects they reach; composite fonts before their descendants.
+        let mut fonts: Vec<(ObjectId, &crate::Dictionary)> = self
+            .objects
+            .iter()
+            .filter_map(|(id, object)| Some((*id, object.as_dict().ok().filter(|dict| dict.has_type(b"Font"))?)))
+            .collect();
+        fonts.sort_by_key(|(_, font)| {
+            font.get(b"Subtype")
+                .and_then(Object::as_name)
+                .is_ok_and(|subtype| subtype.starts_with(b"CIDFont"))
+        });
+        for (font_id, font) in fonts {
+            let name = font
+                .get(b"BaseFont")
+                .and_then(Object::as_name)
+                .map(|name| String::from_utf8_lossy(name).into_owned())
+                .unwrap_or_else(|_| format!("{} {}", font_id.0, font_id.1));
+            let mut reachable = HashSet::new();
+            self.collect_within_page(&Object::Reference(font_id), None, &mut reachable);
+            for id in reachable {
+                categories.entry(id).or_insert_with(|| SizeCategory::Font(name.clone()));
+            }
+        }
+
+        let page_contents: HashSet<ObjectId> = self.page_iter().flat_map(|id| self.get_page_contents(id)).collect();
+        let info_id = self.trailer.get(b"Info").and_then(Object::as_reference).ok();
+        for (id, object) in &self.objects {
+            if categories.contains_key(id) {
+                continue;
+            }
+            let dict = match object {
+                Object::Stream(stream) => &stream.dict,
+                Object::Dictionary(dict) => dict,
+                _ => continue,
+            };
+            let subtype = dict.get(b"Subtype").and_then(Object::as_name).ok();
+            let category = if dict.has_type(b"ObjStm") {
+                SizeCategory::ObjectStreams
+            } else if dict.has_type(b"XRef") {
+                SizeCategory::Xref
+            } else if subtype == Some(b"Image") {
+                SizeCategory::Images
+            } else if subtype == Some(b"Form") || page_contents.contains(id) {
+                SizeCategory::ContentStreams
+            } else if dict.has_type(b"Metadata") || Some(*id) == info_id {
+                SizeCategory::Metadata
+            } else {
+                continue;
+            };
+            categories.insert(*id, category);
+        }
+        categories
+    }
+
+    /// Numbers of the pages that use each of `ids`, directly or through their resources.
+    fn pages_using(&self, ids: HashSet<ObjectId>) -> HashMap<ObjectId, Vec<u32>> {
+        let mut pages: HashMap<ObjectId, Vec<u32>> = HashMap::new();
+        for (page_number, page_id) in self.get_pages() {
+            let mut reachable = HashSet::new();
+            self.collect_within_page(&Object::Reference(page_id), Some(page_id), &mut reachable);
+            if let Ok((_, inherited)) = self.get_page_resources(page_id) {
+                for resources_id in inherited {
+                    self.collect_within_page(&Object::Reference(resources_id), Some(page_id), &mut reachable);
+                }
+            }
+            for id in reachable.intersection(&ids) {
+                pages.entry(*id).or_default().push(page_number);
+            }
+        }
+        pages
+    }
+
+    /// Collect the ids of objects reachable from `object` without leaving the page: parents,
+    /// other pages and page tree nodes are not followed.
+    fn collect_within_page(&self, object: &Object, page_id: Option<ObjectId>, ids: &mut HashSet<ObjectId>) {
+        match object {
+            Object::Reference(id) if !ids.contains(id) => {
+                let Ok(object) = self.get_object(*id) else {
+                    return;
+                };
+                let is_page_node = object
+                    .as_dict()
+                    .is_ok_and(|dict| dict.has_type(b"Page") || dict.has_type(b"Pages"));
+                if is_page_node && Some(*id) != page_id {
+                    return;
+                }
+                ids.insert(*id);
+                self.collect_within_page(object, page_id, ids);
+            }
+            Object::Array(array) => array.iter().for_each(|item| self.collect_within_page(item, page_id, ids)),
+            Object::Dictionary(dict) => dict
+                .iter()
+                .filter(|(key, _)| key.as_slice() != b"Parent" && key.as_slice() != b"P")
+                .for_each(|(_, value)| self.collect_within_page(value, page_id, ids)),
+            Object::Stream(stream) => self.collect_within_page(&Object::Dictionary(stream.dict.clone()), page_id, ids),
+            _ => {}
+        }
+    }
+}
+
+fn object_type_name(object: &Object) -> String {
+    let dict = match object {
+        Object::Stream(stream) => Some(&stream.dict),
+        Object::Dictionary(dict) => Some(dict),
+        _ => None,
+    };
+    dict.and_then(|dict| {
+        dict.get(b"Subtype")
+            .or_else(|_| dict.get(b"Type"))
+            .and_then(Object::as_name)
+            .ok()
+    })
+    .map(|name| String::from_utf8_lossy(name).into_owned())
+    .unwrap_or_else(|| object.enum_variant().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use crate::Stream;
+
+    /// Embed a font with a large font program in the inherited resources and return the ids of
+    /// the font and its program.
+    fn embed_large_font(doc: &mut Document) -> (ObjectId, ObjectId) {
+        let program: Vec<u8> = (0..60_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+        let program_id = doc.add_object(Stream::new(dictionary! { "Length1" => program.len() as i64 }, program));
+        let descriptor_id = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => "Embedded",
+            "FontFile2" => program_id,
+        });
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "TrueType",
+            "BaseFont" => "Embedded",
+            "FontDescriptor" => descriptor_id,
+        });
+        let page_id = doc.page_iter().next().unwrap();
+        let resources_id = doc.get_page_resources(page_id).unwrap().1[0];
+        let resources = doc.get_dictionary_mut(resources_id).unwrap();
+        resources.get_mut(b"Font").unwrap().as_dict_mut().unwrap().set("F2", font_id);
+        (font_id, program_id)
+    }
+
+    fn assert_font_dominates(breakdown: &SizeBreakdown, program_id: ObjectId) {
+        let font = &breakdown.categories[0];
+        assert_eq!(font.category, SizeCategory::Font("Embedded".to_string()));
+        assert_eq!(font.objects, 3);
+        assert!(font.size > breakdown.total * 9 / 10, "{breakdown}");
+
+        let largest = &breakdown.largest[0];
+        assert_eq!(largest.id, program_id);
+        assert!((60_000..60_200).contains(&largest.size), "{}", largest.size);
+        assert_eq!(largest.category, font.category);
+        assert_eq!(largest.pages, [1]);
+    }
+
+    #[test]
+    fn embedded_font_dominates_breakdown() {
+        let mut doc = create_document();
+        let (_, program_id) = embed_large_font(&mut doc);
+
+        let breakdown = doc.size_breakdown(5);
+        assert!(!breakdown.from_file);
+        assert_font_dominates(&breakdown, program_id);
+        assert_eq!(breakdown.largest.len(), 5);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        let loaded = Document::load_mem(&bytes).unwrap();
+        let breakdown = loaded.size_breakdown(1);
+        assert!(breakdown.from_file);
+        assert_font_dominates(&breakdown, program_id);
+        assert!(breakdown.total <= bytes.len());
+        assert!(breakdown.to_string().contains("font Embedded"));
+    }
+}
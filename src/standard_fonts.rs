@@ -0,0 +1,506 @@
+/// Names of the standard 14 fonts, which PDF readers provide without them being embedded.
+pub const STANDARD_FONTS: [&str; 14] = [
+    "Times-Roman",
+    "Times-Bold",
+    "Times-Italic",
+    "Times-BoldItalic",
+    "Helvetica",
+    "Helvetica-Bold",
+    "Helvetica-Oblique",
+    "Helvetica-BoldOblique",
+    "Courier",
+    "Courier-Bold",
+    "Courier-Oblique",
+    "Courier-BoldOblique",
+    "Symbol",
+    "ZapfDingbats",
+];
+
+/// Names fonts with the metrics of a standard 14 font go by, with that standard font.
+///
+/// Spaces are left out of the names, as they are when [`standard_font_name`] looks them up.
+pub const STANDARD_FONT_ALIASES: &[(&str, &str)] = &[
+    ("Arial", "Helvetica"),
+    ("ArialMT", "Helvetica"),
+    ("Arial,Bold", "Helvetica-Bold"),
+    ("Arial-Bold", "Helvetica-Bold"),
+    ("Arial-BoldMT", "Helvetica-Bold"),
+    ("Arial,Italic", "Helvetica-Oblique"),
+    ("Arial-Italic", "Helvetica-Oblique"),
+    ("Arial-ItalicMT", "Helvetica-Oblique"),
+    ("Arial,BoldItalic", "Helvetica-BoldOblique"),
+    ("Arial-BoldItalic", "Helvetica-BoldOblique"),
+    ("Arial-BoldItalicMT", "Helvetica-BoldOblique"),
+    ("Helvetica,Bold", "Helvetica-Bold"),
+    ("Helvetica,Italic", "Helvetica-Oblique"),
+    ("Helvetica,BoldItalic", "Helvetica-BoldOblique"),
+    ("Helvetica-Italic", "Helvetica-Oblique"),
+    ("Helvetica-BoldItalic", "Helvetica-BoldOblique"),
+    ("Times", "Times-Roman"),
+    ("TimesNewRoman", "Times-Roman"),
+    ("TimesNewRomanPSMT", "Times-Roman"),
+    ("TimesNewRoman,Bold", "Times-Bold"),
+    ("TimesNewRoman-Bold", "Times-Bold"),
+    ("TimesNewRomanPS-BoldMT", "Times-Bold"),
+    ("TimesNewRoman,Italic", "Times-Italic"),
+    ("TimesNewRoman-Italic", "Times-Italic"),
+    ("TimesNewRomanPS-ItalicMT", "Times-Italic"),
+    ("TimesNewRoman,BoldItalic", "Times-BoldItalic"),
+    ("TimesNewRoman-BoldItalic", "Times-BoldItalic"),
+    ("TimesNewRomanPS-BoldItalicMT", "Times-BoldItalic"),
+    ("CourierNew", "Courier"),
+    ("CourierNewPSMT", "Courier"),
+    ("CourierNew,Bold", "Courier-Bold"),
+    ("CourierNew-Bold", "Courier-Bold"),
+    ("CourierNewPS-BoldMT", "Courier-Bold"),
+    ("CourierNew,Italic", "Courier-Oblique"),
+    ("CourierNew-Italic", "Courier-Oblique"),
+    ("CourierNewPS-ItalicMT", "Courier-Oblique"),
+    ("CourierNew,BoldItalic", "Courier-BoldOblique"),
+    ("CourierNew-BoldItalic", "Courier-BoldOblique"),
+    ("CourierNewPS-BoldItalicMT", "Courier-BoldOblique"),
+    ("Courier,Bold", "Courier-Bold"),
+    ("Courier,Italic", "Courier-Oblique"),
+    ("Courier,BoldItalic", "Courier-BoldOblique"),
+    ("SymbolMT", "Symbol"),
+    ("ZapfDingbatsITC", "ZapfDingbats"),
+    ("Dingbats", "ZapfDingbats"),
+];
+
+/// Get the standard 14 font a /BaseFont names, by its own name or an alias from
+/// [`STANDARD_FONT_ALIASES`]. Subset prefixes like `ABCDEF+` and spaces are ignored.
+pub fn standard_font_name(base_font: &[u8]) -> Option<&'static str> {
+    let name: String = String::from_utf8_lossy(base_font).chars().filter(|ch| *ch != ' ').collect();
+    let name = strip_subset_prefix(&name).unwrap_or(&name);
+    STANDARD_FONTS
+        .iter()
+        .find(|font| **font == name)
+        .copied()
+        .or_else(|| {
+            STANDARD_FONT_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == name)
+                .map(|(_, font)| *font)
+        })
+}
+
+/// Whether a /BaseFont names a font subset, i.e. starts with a tag like `ABCDEF+`.
+pub fn is_subset_font_name(base_font: &[u8]) -> bool {
+    strip_subset_prefix(&String::from_utf8_lossy(base_font)).is_some()
+}
+
+pub(crate) fn strip_subset_prefix(name: &str) -> Option<&str> {
+    match name.split_once('+') {
+        Some((prefix, name)) if prefix.len() == 6 && prefix.bytes().all(|byte| byte.is_ascii_uppercase()) => Some(name),
+        _ => None,
+    }
+}
+
+/// Whether a standard 14 font has its own built-in encoding rather than a Latin one.
+pub(crate) fn is_symbolic_standard_font(font: &str) -> bool {
+    matches!(font, "Symbol" | "ZapfDingbats")
+}
+
+/// Get the width of a glyph of a standard 14 font, in thousandths of text space units.
+///
+/// The glyphs of Symbol and ZapfDingbats are looked up by their code in their built-in
+/// encoding, those of other fonts by their UTF-16 code unit.
+pub(crate) fn standard_font_width(font: &str, key: u16) -> Option<u16> {
+    let widths = match font {
+        "Courier" | "Courier-Bold" | "Courier-Oblique" | "Courier-BoldOblique" => return Some(600),
+        "Helvetica" | "Helvetica-Oblique" => HELVETICA,
+        "Helvetica-Bold" | "Helvetica-BoldOblique" => HELVETICA_BOLD,
+        "Times-Roman" => TIMES_ROMAN,
+        "Times-Bold" => TIMES_BOLD,
+        "Times-Italic" => TIMES_ITALIC,
+        "Times-BoldItalic" => TIMES_BOLD_ITALIC,
+        "Symbol" => SYMBOL,
+        "ZapfDingbats" => ZAPF_DINGBATS,
+        _ => return None,
+    };
+    widths
+        .binary_search_by_key(&key, |(key, _)| *key)
+        .ok()
+        .map(|index| widths[index].1)
+}
+
+// Widths from the Adobe Font Metrics files of the standard 14 fonts.
+
+/// Widths of the glyphs of Helvetica, by UTF-16 code unit.
+const HELVETICA: &[(u16, u16)] = &[
+    (0x0020, 278), (0x0021, 278), (0x0022, 355), (0x0023, 556), (0x0024, 556), (0x0025, 889), (0x0026, 667),
+    (0x0027, 191), (0x0028, 333), (0x0029, 333), (0x002A, 389), (0x002B, 584), (0x002C, 278), (0x002D, 333),
+    (0x002E, 278), (0x002F, 278), (0x0030, 556), (0x0031, 556), (0x0032, 556), (0x0033, 556), (0x0034, 556),
+    (0x0035, 556), (0x0036, 556), (0x0037, 556), (0x0038, 556), (0x0039, 556), (0x003A, 278), (0x003B, 278),
+    (0x003C, 584), (0x003D, 584), (0x003E, 584), (0x003F, 556), (0x0040, 1015), (0x0041, 667), (0x0042, 667),
+    (0x0043, 722), (0x0044, 722), (0x0045, 667), (0x0046, 611), (0x0047, 778), (0x0048, 722), (0x0049, 278),
+    (0x004A, 500), (0x004B, 667), (0x004C, 556), (0x004D, 833), (0x004E, 722), (0x004F, 778), (0x0050, 667),
+    (0x0051, 778), (0x0052, 722), (0x0053, 667), (0x0054, 611), (0x0055, 722), (0x0056, 667), (0x0057, 944),
+    (0x0058, 667), (0x0059, 667), (0x005A, 611), (0x005B, 278), (0x005C, 278), (0x005D, 278), (0x005E, 469),
+    (0x005F, 556), (0x0060, 333), (0x0061, 556), (0x0062, 556), (0x0063, 500), (0x0064, 556), (0x0065, 556),
+    (0x0066, 278), (0x0067, 556), (0x0068, 556), (0x0069, 222), (0x006A, 222), (0x006B, 500), (0x006C, 222),
+    (0x006D, 833), (0x006E, 556), (0x006F, 556), (0x0070, 556), (0x0071, 556), (0x0072, 333), (0x0073, 500),
+    (0x0074, 278), (0x0075, 556), (0x0076, 500), (0x0077, 722), (0x0078, 500), (0x0079, 500), (0x007A, 500),
+    (0x007B, 334), (0x007C, 260), (0x007D, 334), (0x007E, 584), (0x00A1, 333), (0x00A2, 556), (0x00A3, 556),
+    (0x00A4, 556), (0x00A5, 556), (0x00A6, 260), (0x00A7, 556), (0x00A8, 333), (0x00A9, 737), (0x00AA, 370),
+    (0x00AB, 556), (0x00AC, 584), (0x00AE, 737), (0x00AF, 333), (0x00B0, 400), (0x00B1, 584), (0x00B2, 333),
+    (0x00B3, 333), (0x00B4, 333), (0x00B5, 556), (0x00B6, 537), (0x00B7, 278), (0x00B8, 333), (0x00B9, 333),
+    (0x00BA, 365), (0x00BB, 556), (0x00BC, 834), (0x00BD, 834), (0x00BE, 834), (0x00BF, 611), (0x00C0, 667),
+    (0x00C1, 667), (0x00C2, 667), (0x00C3, 667), (0x00C4, 667), (0x00C5, 667), (0x00C6, 1000), (0x00C7, 722),
+    (0x00C8, 667), (0x00C9, 667), (0x00CA, 667), (0x00CB, 667), (0x00CC, 278), (0x00CD, 278), (0x00CE, 278),
+    (0x00CF, 278), (0x00D0, 722), (0x00D1, 722), (0x00D2, 778), (0x00D3, 778), (0x00D4, 778), (0x00D5, 778),
+    (0x00D6, 778), (0x00D7, 584), (0x00D8, 778), (0x00D9, 722), (0x00DA, 722), (0x00DB, 722), (0x00DC, 722),
+    (0x00DD, 667), (0x00DE, 667), (0x00DF, 611), (0x00E0, 556), (0x00E1, 556), (0x00E2, 556), (0x00E3, 556),
+    (0x00E4, 556), (0x00E5, 556), (0x00E6, 889), (0x00E7, 500), (0x00E8, 556), (0x00E9, 556), (0x00EA, 556),
+    (0x00EB, 556), (0x00EC, 278), (0x00ED, 278), (0x00EE, 278), (0x00EF, 278), (0x00F0, 556), (0x00F1, 556),
+    (0x00F2, 556), (0x00F3, 556), (0x00F4, 556), (0x00F5, 556), (0x00F6, 556), (0x00F7, 584), (0x00F8, 611),
+    (0x00F9, 556), (0x00FA, 556), (0x00FB, 556), (0x00FC, 556), (0x00FD, 500), (0x00FE, 556), (0x00FF, 500),
+    (0x0100, 667), (0x0101, 556), (0x0102, 667), (0x0103, 556), (0x0104, 667), (0x0105, 556), (0x0106, 722),
+    (0x0107, 500), (0x010C, 722), (0x010D, 500), (0x010E, 722), (0x010F, 643), (0x0110, 722), (0x0111, 556),
+    (0x0112, 667), (0x0113, 556), (0x0116, 667), (0x0117, 556), (0x0118, 667), (0x0119, 556), (0x011A, 667),
+    (0x011B, 556), (0x011E, 778), (0x011F, 556), (0x0122, 778), (0x0123, 556), (0x012A, 278), (0x012B, 278),
+    (0x012E, 278), (0x012F, 222), (0x0130, 278), (0x0131, 278), (0x0136, 667), (0x0137, 500), (0x0139, 556),
+    (0x013A, 222), (0x013B, 556), (0x013C, 222), (0x013D, 556), (0x013E, 299), (0x0141, 556), (0x0142, 222),
+    (0x0143, 722), (0x0144, 556), (0x0145, 722), (0x0146, 556), (0x0147, 722), (0x0148, 556), (0x014C, 778),
+    (0x014D, 556), (0x0150, 778), (0x0151, 556), (0x0152, 1000), (0x0153, 944), (0x0154, 722), (0x0155, 333),
+    (0x0156, 722), (0x0157, 333), (0x0158, 722), (0x0159, 333), (0x015A, 667), (0x015B, 500), (0x015E, 667),
+    (0x015F, 500), (0x0160, 667), (0x0161, 500), (0x0162, 611), (0x0163, 278), (0x0164, 611), (0x0165, 317),
+    (0x016A, 722), (0x016B, 556), (0x016E, 722), (0x016F, 556), (0x0170, 722), (0x0171, 556), (0x0172, 722),
+    (0x0173, 556), (0x0178, 667), (0x0179, 611), (0x017A, 500), (0x017B, 611), (0x017C, 500), (0x017D, 611),
+    (0x017E, 500), (0x0192, 556), (0x0218, 667), (0x0219, 500), (0x02C6, 333), (0x02C7, 333), (0x02D8, 333),
+    (0x02D9, 333), (0x02DA, 333), (0x02DB, 333), (0x02DC, 333), (0x02DD, 333), (0x2013, 556), (0x2014, 1000),
+    (0x2018, 222), (0x2019, 222), (0x201A, 222), (0x201C, 333), (0x201D, 333), (0x201E, 333), (0x2020, 556),
+    (0x2021, 556), (0x2022, 350), (0x2026, 1000), (0x2030, 1000), (0x2039, 333), (0x203A, 333), (0x2044, 167),
+    (0x20AC, 556), (0x2122, 1000), (0x2202, 476), (0x2206, 612), (0x2211, 600), (0x2212, 584), (0x221A, 453),
+    (0x2260, 549), (0x2264, 549), (0x2265, 549), (0x25CA, 471), (0xF6C3, 250), (0xFB01, 500), (0xFB02, 500),
+];
+
+/// Widths of the glyphs of Helvetica-Bold, by UTF-16 code unit.
+const HELVETICA_BOLD: &[(u16, u16)] = &[
+    (0x0020, 278), (0x0021, 333), (0x0022, 474), (0x0023, 556), (0x0024, 556), (0x0025, 889), (0x0026, 722),
+    (0x0027, 238), (0x0028, 333), (0x0029, 333), (0x002A, 389), (0x002B, 584), (0x002C, 278), (0x002D, 333),
+    (0x002E, 278), (0x002F, 278), (0x0030, 556), (0x0031, 556), (0x0032, 556), (0x0033, 556), (0x0034, 556),
+    (0x0035, 556), (0x0036, 556), (0x0037, 556), (0x0038, 556), (0x0039, 556), (0x003A, 333), (0x003B, 333),
+    (0x003C, 584), (0x003D, 584), (0x003E, 584), (0x003F, 611), (0x0040, 975), (0x0041, 722), (0x0042, 722),
+    (0x0043, 722), (0x0044, 722), (0x0045, 667), (0x0046, 611), (0x0047, 778), (0x0048, 722), (0x0049, 278),
+    (0x004A, 556), (0x004B, 722), (0x004C, 611), (0x004D, 833), (0x004E, 722), (0x004F, 778), (0x0050, 667),
+    (0x0051, 778), (0x0052, 722), (0x0053, 667), (0x0054, 611), (0x0055, 722), (0x0056, 667), (0x0057, 944),
+    (0x0058, 667), (0x0059, 667), (0x005A, 611), (0x005B, 333), (0x005C, 278), (0x005D, 333), (0x005E, 584),
+    (0x005F, 556), (0x0060, 333), (0x0061, 556), (0x0062, 611), (0x0063, 556), (0x0064, 611), (0x0065, 556),
+    (0x0066, 333), (0x0067, 611), (0x0068, 611), (0x0069, 278), (0x006A, 278), (0x006B, 556), (0x006C, 278),
+    (0x006D, 889), (0x006E, 611), (0x006F, 611), (0x0070, 611), (0x0071, 611), (0x0072, 389), (0x0073, 556),
+    (0x0074, 333), (0x0075, 611), (0x0076, 556), (0x0077, 778), (0x0078, 556), (0x0079, 556), (0x007A, 500),
+    (0x007B, 389), (0x007C, 280), (0x007D, 389), (0x007E, 584), (0x00A1, 333), (0x00A2, 556), (0x00A3, 556),
+    (0x00A4, 556), (0x00A5, 556), (0x00A6, 280), (0x00A7, 556), (0x00A8, 333), (0x00A9, 737), (0x00AA, 370),
+    (0x00AB, 556), (0x00AC, 584), (0x00AE, 737), (0x00AF, 333), (0x00B0, 400), (0x00B1, 584), (0x00B2, 333),
+    (0x00B3, 333), (0x00B4, 333), (0x00B5, 611), (0x00B6, 556), (0x00B7, 278), (0x00B8, 333), (0x00B9, 333),
+    (0x00BA, 365), (0x00BB, 556), (0x00BC, 834), (0x00BD, 834), (0x00BE, 834), (0x00BF, 611), (0x00C0, 722),
+    (0x00C1, 722), (0x00C2, 722), (0x00C3, 722), (0x00C4, 722), (0x00C5, 722), (0x00C6, 1000), (0x00C7, 722),
+    (0x00C8, 667), (0x00C9, 667), (0x00CA, 667), (0x00CB, 667), (0x00CC, 278), (0x00CD, 278), (0x00CE, 278),
+    (0x00CF, 278), (0x00D0, 722), (0x00D1, 722), (0x00D2, 778), (0x00D3, 778), (0x00D4, 778), (0x00D5, 778),
+    (0x00D6, 778), (0x00D7, 584), (0x00D8, 778), (0x00D9, 722), (0x00DA, 722), (0x00DB, 722), (0x00DC, 722),
+    (0x00DD, 667), (0x00DE, 667), (0x00DF, 611), (0x00E0, 556), (0x00E1, 556), (0x00E2, 556), (0x00E3, 556),
+    (0x00E4, 556), (0x00E5, 556), (0x00E6, 889), (0x00E7, 556), (0x00E8, 556), (0x00E9, 556), (0x00EA, 556),
+    (0x00EB, 556), (0x00EC, 278), (0x00ED, 278), (0x00EE, 278), (0x00EF, 278), (0x00F0, 611), (0x00F1, 611),
+    (0x00F2, 611), (0x00F3, 611), (0x00F4, 611), (0x00F5, 611), (0x00F6, 611), (0x00F7, 584), (0x00F8, 611),
+    (0x00F9, 611), (0x00FA, 611), (0x00FB, 611), (0x00FC, 611), (0x00FD, 556), (0x00FE, 611), (0x00FF, 556),
+    (0x0100, 722), (0x0101, 556), (0x0102, 722), (0x0103, 556), (0x0104, 722), (0x0105, 556), (0x0106, 722),
+    (0x0107, 556), (0x010C, 722), (0x010D, 556), (0x010E, 722), (0x010F, 743), (0x0110, 722), (0x0111, 611),
+    (0x0112, 667), (0x0113, 556), (0x0116, 667), (0x0117, 556), (0x0118, 667), (0x0119, 556), (0x011A, 667),
+    (0x011B, 556), (0x011E, 778), (0x011F, 611), (0x0122, 778), (0x0123, 611), (0x012A, 278), (0x012B, 278),
+    (0x012E, 278), (0x012F, 278), (0x0130, 278), (0x0131, 278), (0x0136, 722), (0x0137, 556), (0x0139, 611),
+    (0x013A, 278), (0x013B, 611), (0x013C, 278), (0x013D, 611), (0x013E, 400), (0x0141, 611), (0x0142, 278),
+    (0x0143, 722), (0x0144, 611), (0x0145, 722), (0x0146, 611), (0x0147, 722), (0x0148, 611), (0x014C, 778),
+    (0x014D, 611), (0x0150, 778), (0x0151, 611), (0x0152, 1000), (0x0153, 944), (0x0154, 722), (0x0155, 389),
+    (0x0156, 722), (0x0157, 389), (0x0158, 722), (0x0159, 389), (0x015A, 667), (0x015B, 556), (0x015E, 667),
+    (0x015F, 556), (0x0160, 667), (0x0161, 556), (0x0162, 611), (0x0163, 333), (0x0164, 611), (0x0165, 389),
+    (0x016A, 722), (0x016B, 611), (0x016E, 722), (0x016F, 611), (0x0170, 722), (0x0171, 611), (0x0172, 722),
+    (0x0173, 611), (0x0178, 667), (0x0179, 611), (0x017A, 500), (0x017B, 611), (0x017C, 500), (0x017D, 611),
+    (0x017E, 500), (0x0192, 556), (0x0218, 667), (0x0219, 556), (0x02C6, 333), (0x02C7, 333), (0x02D8, 333),
+    (0x02D9, 333), (0x02DA, 333), (0x02DB, 333), (0x02DC, 333), (0x02DD, 333), (0x2013, 556), (0x2014, 1000),
+    (0x2018, 278), (0x2019, 278), (0x201A, 278), (0x201C, 500), (0x201D, 500), (0x201E, 500), (0x2020, 556),
+    (0x2021, 556), (0x2022, 350), (0x2026, 1000), (0x2030, 1000), (0x2039, 333), (0x203A, 333), (0x2044, 167),
+    (0x20AC, 556), (0x2122, 1000), (0x2202, 494), (0x2206, 612), (0x2211, 600), (0x2212, 584), (0x221A, 549),
+    (0x2260, 549), (0x2264, 549), (0x2265, 549), (0x25CA, 494), (0xF6C3, 250), (0xFB01, 611), (0xFB02, 611),
+];
+
+/// Widths of the glyphs of Times-Roman, by UTF-16 code unit.
+const TIMES_ROMAN: &[(u16, u16)] = &[
+    (0x0020, 250), (0x0021, 333), (0x0022, 408), (0x0023, 500), (0x0024, 500), (0x0025, 833), (0x0026, 778),
+    (0x0027, 180), (0x0028, 333), (0x0029, 333), (0x002A, 500), (0x002B, 564), (0x002C, 250), (0x002D, 333),
+    (0x002E, 250), (0x002F, 278), (0x0030, 500), (0x0031, 500), (0x0032, 500), (0x0033, 500), (0x0034, 500),
+    (0x0035, 500), (0x0036, 500), (0x0037, 500), (0x0038, 500), (0x0039, 500), (0x003A, 278), (0x003B, 278),
+    (0x003C, 564), (0x003D, 564), (0x003E, 564), (0x003F, 444), (0x0040, 921), (0x0041, 722), (0x0042, 667),
+    (0x0043, 667), (0x0044, 722), (0x0045, 611), (0x0046, 556), (0x0047, 722), (0x0048, 722), (0x0049, 333),
+    (0x004A, 389), (0x004B, 722), (0x004C, 611), (0x004D, 889), (0x004E, 722), (0x004F, 722), (0x0050, 556),
+    (0x0051, 722), (0x0052, 667), (0x0053, 556), (0x0054, 611), (0x0055, 722), (0x0056, 722), (0x0057, 944),
+    (0x0058, 722), (0x0059, 722), (0x005A, 611), (0x005B, 333), (0x005C, 278), (0x005D, 333), (0x005E, 469),
+    (0x005F, 500), (0x0060, 333), (0x0061, 444), (0x0062, 500), (0x0063, 444), (0x0064, 500), (0x0065, 444),
+    (0x0066, 333), (0x0067, 500), (0x0068, 500), (0x0069, 278), (0x006A, 278), (0x006B, 500), (0x006C, 278),
+    (0x006D, 778), (0x006E, 500), (0x006F, 500), (0x0070, 500), (0x0071, 500), (0x0072, 333), (0x0073, 389),
+    (0x0074, 278), (0x0075, 500), (0x0076, 500), (0x0077, 722), (0x0078, 500), (0x0079, 500), (0x007A, 444),
+    (0x007B, 480), (0x007C, 200), (0x007D, 480), (0x007E, 541), (0x00A1, 333), (0x00A2, 500), (0x00A3, 500),
+    (0x00A4, 500), (0x00A5, 500), (0x00A6, 200), (0x00A7, 500), (0x00A8, 333), (0x00A9, 760), (0x00AA, 276),
+    (0x00AB, 500), (0x00AC, 564), (0x00AE, 760), (0x00AF, 333), (0x00B0, 400), (0x00B1, 564), (0x00B2, 300),
+    (0x00B3, 300), (0x00B4, 333), (0x00B5, 500), (0x00B6, 453), (0x00B7, 250), (0x00B8, 333), (0x00B9, 300),
+    (0x00BA, 310), (0x00BB, 500), (0x00BC, 750), (0x00BD, 750), (0x00BE, 750), (0x00BF, 444), (0x00C0, 722),
+    (0x00C1, 722), (0x00C2, 722), (0x00C3, 722), (0x00C4, 722), (0x00C5, 722), (0x00C6, 889), (0x00C7, 667),
+    (0x00C8, 611), (0x00C9, 611), (0x00CA, 611), (0x00CB, 611), (0x00CC, 333), (0x00CD, 333), (0x00CE, 333),
+    (0x00CF, 333), (0x00D0, 722), (0x00D1, 722), (0x00D2, 722), (0x00D3, 722), (0x00D4, 722), (0x00D5, 722),
+    (0x00D6, 722), (0x00D7, 564), (0x00D8, 722), (0x00D9, 722), (0x00DA, 722), (0x00DB, 722), (0x00DC, 722),
+    (0x00DD, 722), (0x00DE, 556), (0x00DF, 500), (0x00E0, 444), (0x00E1, 444), (0x00E2, 444), (0x00E3, 444),
+    (0x00E4, 444), (0x00E5, 444), (0x00E6, 667), (0x00E7, 444), (0x00E8, 444), (0x00E9, 444), (0x00EA, 444),
+    (0x00EB, 444), (0x00EC, 278), (0x00ED, 278), (0x00EE, 278), (0x00EF, 278), (0x00F0, 500), (0x00F1, 500),
+    (0x00F2, 500), (0x00F3, 500), (0x00F4, 500), (0x00F5, 500), (0x00F6, 500), (0x00F7, 564), (0x00F8, 500),
+    (0x00F9, 500), (0x00FA, 500), (0x00FB, 500), (0x00FC, 500), (0x00FD, 500), (0x00FE, 500), (0x00FF, 500),
+    (0x0100, 722), (0x0101, 444), (0x0102, 722), (0x0103, 444), (0x0104, 722), (0x0105, 444), (0x0106, 667),
+    (0x0107, 444), (0x010C, 667), (0x010D, 444), (0x010E, 722), (0x010F, 588), (0x0110, 722), (0x0111, 500),
+    (0x0112, 611), (0x0113, 444), (0x0116, 611), (0x0117, 444), (0x0118, 611), (0x0119, 444), (0x011A, 611),
+    (0x011B, 444), (0x011E, 722), (0x011F, 500), (0x0122, 722), (0x0123, 500), (0x012A, 333), (0x012B, 278),
+    (0x012E, 333), (0x012F, 278), (0x0130, 333), (0x0131, 278), (0x0136, 722), (0x0137, 500), (0x0139, 611),
+    (0x013A, 278), (0x013B, 611), (0x013C, 278), (0x013D, 611), (0x013E, 344), (0x0141, 611), (0x0142, 278),
+    (0x0143, 722), (0x0144, 500), (0x0145, 722), (0x0146, 500), (0x0147, 722), (0x0148, 500), (0x014C, 722),
+    (0x014D, 500), (0x0150, 722), (0x0151, 500), (0x0152, 889), (0x0153, 722), (0x0154, 667), (0x0155, 333),
+    (0x0156, 667), (0x0157, 333), (0x0158, 667), (0x0159, 333), (0x015A, 556), (0x015B, 389), (0x015E, 556),
+    (0x015F, 389), (0x0160, 556), (0x0161, 389), (0x0162, 611), (0x0163, 278), (0x0164, 611), (0x0165, 326),
+    (0x016A, 722), (0x016B, 500), (0x016E, 722), (0x016F, 500), (0x0170, 722), (0x0171, 500), (0x0172, 722),
+    (0x0173, 500), (0x0178, 722), (0x0179, 611), (0x017A, 444), (0x017B, 611), (0x017C, 444), (0x017D, 611),
+    (0x017E, 444), (0x0192, 500), (0x0218, 556), (0x0219, 389), (0x02C6, 333), (0x02C7, 333), (0x02D8, 333),
+    (0x02D9, 333), (0x02DA, 333), (0x02DB, 333), (0x02DC, 333), (0x02DD, 333), (0x2013, 500), (0x2014, 1000),
+    (0x2018, 333), (0x2019, 333), (0x201A, 333), (0x201C, 444), (0x201D, 444), (0x201E, 444), (0x2020, 500),
+    (0x2021, 500), (0x2022, 350), (0x2026, 1000), (0x2030, 1000), (0x2039, 333), (0x203A, 333), (0x2044, 167),
+    (0x20AC, 500), (0x2122, 980), (0x2202, 476), (0x2206, 612), (0x2211, 600), (0x2212, 564), (0x221A, 453),
+    (0x2260, 549), (0x2264, 549), (0x2265, 549), (0x25CA, 471), (0xF6C3, 250), (0xFB01, 556), (0xFB02, 556),
+];
+
+/// Widths of the glyphs of Times-Bold, by UTF-16 code unit.
+const TIMES_BOLD: &[(u16, u16)] = &[
+    (0x0020, 250), (0x0021, 333), (0x0022, 555), (0x0023, 500), (0x0024, 500), (0x0025, 1000), (0x0026, 833),
+    (0x0027, 278), (0x0028, 333), (0x0029, 333), (0x002A, 500), (0x002B, 570), (0x002C, 250), (0x002D, 333),
+    (0x002E, 250), (0x002F, 278), (0x0030, 500), (0x0031, 500), (0x0032, 500), (0x0033, 500), (0x0034, 500),
+    (0x0035, 500), (0x0036, 500), (0x0037, 500), (0x0038, 500), (0x0039, 500), (0x003A, 333), (0x003B, 333),
+    (0x003C, 570), (0x003D, 570), (0x003E, 570), (0x003F, 500), (0x0040, 930), (0x0041, 722), (0x0042, 667),
+    (0x0043, 722), (0x0044, 722), (0x0045, 667), (0x0046, 611), (0x0047, 778), (0x0048, 778), (0x0049, 389),
+    (0x004A, 500), (0x004B, 778), (0x004C, 667), (0x004D, 944), (0x004E, 722), (0x004F, 778), (0x0050, 611),
+    (0x0051, 778), (0x0052, 722), (0x0053, 556), (0x0054, 667), (0x0055, 722), (0x0056, 722), (0x0057, 1000),
+    (0x0058, 722), (0x0059, 722), (0x005A, 667), (0x005B, 333), (0x005C, 278), (0x005D, 333), (0x005E, 581),
+    (0x005F, 500), (0x0060, 333), (0x0061, 500), (0x0062, 556), (0x0063, 444), (0x0064, 556), (0x0065, 444),
+    (0x0066, 333), (0x0067, 500), (0x0068, 556), (0x0069, 278), (0x006A, 333), (0x006B, 556), (0x006C, 278),
+    (0x006D, 833), (0x006E, 556), (0x006F, 500), (0x0070, 556), (0x0071, 556), (0x0072, 444), (0x0073, 389),
+    (0x0074, 333), (0x0075, 556), (0x0076, 500), (0x0077, 722), (0x0078, 500), (0x0079, 500), (0x007A, 444),
+    (0x007B, 394), (0x007C, 220), (0x007D, 394), (0x007E, 520), (0x00A1, 333), (0x00A2, 500), (0x00A3, 500),
+    (0x00A4, 500), (0x00A5, 500), (0x00A6, 220), (0x00A7, 500), (0x00A8, 333), (0x00A9, 747), (0x00AA, 300),
+    (0x00AB, 500), (0x00AC, 570), (0x00AE, 747), (0x00AF, 333), (0x00B0, 400), (0x00B1, 570), (0x00B2, 300),
+    (0x00B3, 300), (0x00B4, 333), (0x00B5, 556), (0x00B6, 540), (0x00B7, 250), (0x00B8, 333), (0x00B9, 300),
+    (0x00BA, 330), (0x00BB, 500), (0x00BC, 750), (0x00BD, 750), (0x00BE, 750), (0x00BF, 500), (0x00C0, 722),
+    (0x00C1, 722), (0x00C2, 722), (0x00C3, 722), (0x00C4, 722), (0x00C5, 722), (0x00C6, 1000), (0x00C7, 722),
+    (0x00C8, 667), (0x00C9, 667), (0x00CA, 667), (0x00CB, 667), (0x00CC, 389), (0x00CD, 389), (0x00CE, 389),
+    (0x00CF, 389), (0x00D0, 722), (0x00D1, 722), (0x00D2, 778), (0x00D3, 778), (0x00D4, 778), (0x00D5, 778),
+    (0x00D6, 778), (0x00D7, 570), (0x00D8, 778), (0x00D9, 722), (0x00DA, 722), (0x00DB, 722), (0x00DC, 722),
+    (0x00DD, 722), (0x00DE, 611), (0x00DF, 556), (0x00E0, 500), (0x00E1, 500), (0x00E2, 500), (0x00E3, 500),
+    (0x00E4, 500), (0x00E5, 500), (0x00E6, 722), (0x00E7, 444), (0x00E8, 444), (0x00E9, 444), (0x00EA, 444),
+    (0x00EB, 444), (0x00EC, 278), (0x00ED, 278), (0x00EE, 278), (0x00EF, 278), (0x00F0, 500), (0x00F1, 556),
+    (0x00F2, 500), (0x00F3, 500), (0x00F4, 500), (0x00F5, 500), (0x00F6, 500), (0x00F7, 570), (0x00F8, 500),
+    (0x00F9, 556), (0x00FA, 556), (0x00FB, 556), (0x00FC, 556), (0x00FD, 500), (0x00FE, 556), (0x00FF, 500),
+    (0x0100, 722), (0x0101, 500), (0x0102, 722), (0x0103, 500), (0x0104, 722), (0x0105, 500), (0x0106, 722),
+    (0x0107, 444), (0x010C, 722), (0x010D, 444), (0x010E, 722), (0x010F, 672), (0x0110, 722), (0x0111, 556),
+    (0x0112, 667), (0x0113, 444), (0x0116, 667), (0x0117, 444), (0x0118, 667), (0x0119, 444), (0x011A, 667),
+    (0x011B, 444), (0x011E, 778), (0x011F, 500), (0x0122, 778), (0x0123, 500), (0x012A, 389), (0x012B, 278),
+    (0x012E, 389), (0x012F, 278), (0x0130, 389), (0x0131, 278), (0x0136, 778), (0x0137, 556), (0x0139, 667),
+    (0x013A, 278), (0x013B, 667), (0x013C, 278), (0x013D, 667), (0x013E, 394), (0x0141, 667), (0x0142, 278),
+    (0x0143, 722), (0x0144, 556), (0x0145, 722), (0x0146, 556), (0x0147, 722), (0x0148, 556), (0x014C, 778),
+    (0x014D, 500), (0x0150, 778), (0x0151, 500), (0x0152, 1000), (0x0153, 722), (0x0154, 722), (0x0155, 444),
+    (0x0156, 722), (0x0157, 444), (0x0158, 722), (0x0159, 444), (0x015A, 556), (0x015B, 389), (0x015E, 556),
+    (0x015F, 389), (0x0160, 556), (0x0161, 389), (0x0162, 667), (0x0163, 333), (0x0164, 667), (0x0165, 416),
+    (0x016A, 722), (0x016B, 556), (0x016E, 722), (0x016F, 556), (0x0170, 722), (0x0171, 556), (0x0172, 722),
+    (0x0173, 556), (0x0178, 722), (0x0179, 667), (0x017A, 444), (0x017B, 667), (0x017C, 444), (0x017D, 667),
+    (0x017E, 444), (0x0192, 500), (0x0218, 556), (0x0219, 389), (0x02C6, 333), (0x02C7, 333), (0x02D8, 333),
+    (0x02D9, 333), (0x02DA, 333), (0x02DB, 333), (0x02DC, 333), (0x02DD, 333), (0x2013, 500), (0x2014, 1000),
+    (0x2018, 333), (0x2019, 333), (0x201A, 333), (0x201C, 500), (0x201D, 500), (0x201E, 500), (0x2020, 500),
+    (0x2021, 500), (0x2022, 350), (0x2026, 1000), (0x2030, 1000), (0x2039, 333), (0x203A, 333), (0x2044, 167),
+    (0x20AC, 500), (0x2122, 1000), (0x2202, 494), (0x2206, 612), (0x2211, 600), (0x2212, 570), (0x221A, 549),
+    (0x2260, 549), (0x2264, 549), (0x2265, 549), (0x25CA, 494), (0xF6C3, 250), (0xFB01, 556), (0xFB02, 556),
+];
+
+/// Widths of the glyphs of Times-Italic, by UTF-16 code unit.
+const TIMES_ITALIC: &[(u16, u16)] = &[
+    (0x0020, 250), (0x0021, 333), (0x0022, 420), (0x0023, 500), (0x0024, 500), (0x0025, 833), (0x0026, 778),
+    (0x0027, 214), (0x0028, 333), (0x0029, 333), (0x002A, 500), (0x002B, 675), (0x002C, 250), (0x002D, 333),
+    (0x002E, 250), (0x002F, 278), (0x0030, 500), (0x0031, 500), (0x0032, 500), (0x0033, 500), (0x0034, 500),
+    (0x0035, 500), (0x0036, 500), (0x0037, 500), (0x0038, 500), (0x0039, 500), (0x003A, 333), (0x003B, 333),
+    (0x003C, 675), (0x003D, 675), (0x003E, 675), (0x003F, 500), (0x0040, 920), (0x0041, 611), (0x0042, 611),
+    (0x0043, 667), (0x0044, 722), (0x0045, 611), (0x0046, 611), (0x0047, 722), (0x0048, 722), (0x0049, 333),
+    (0x004A, 444), (0x004B, 667), (0x004C, 556), (0x004D, 833), (0x004E, 667), (0x004F, 722), (0x0050, 611),
+    (0x0051, 722), (0x0052, 611), (0x0053, 500), (0x0054, 556), (0x0055, 722), (0x0056, 611), (0x0057, 833),
+    (0x0058, 611), (0x0059, 556), (0x005A, 556), (0x005B, 389), (0x005C, 278), (0x005D, 389), (0x005E, 422),
+    (0x005F, 500), (0x0060, 333), (0x0061, 500), (0x0062, 500), (0x0063, 444), (0x0064, 500), (0x0065, 444),
+    (0x0066, 278), (0x0067, 500), (0x0068, 500), (0x0069, 278), (0x006A, 278), (0x006B, 444), (0x006C, 278),
+    (0x006D, 722), (0x006E, 500), (0x006F, 500), (0x0070, 500), (0x0071, 500), (0x0072, 389), (0x0073, 389),
+    (0x0074, 278), (0x0075, 500), (0x0076, 444), (0x0077, 667), (0x0078, 444), (0x0079, 444), (0x007A, 389),
+    (0x007B, 400), (0x007C, 275), (0x007D, 400), (0x007E, 541), (0x00A1, 389), (0x00A2, 500), (0x00A3, 500),
+    (0x00A4, 500), (0x00A5, 500), (0x00A6, 275), (0x00A7, 500), (0x00A8, 333), (0x00A9, 760), (0x00AA, 276),
+    (0x00AB, 500), (0x00AC, 675), (0x00AE, 760), (0x00AF, 333), (0x00B0, 400), (0x00B1, 675), (0x00B2, 300),
+    (0x00B3, 300), (0x00B4, 333), (0x00B5, 500), (0x00B6, 523), (0x00B7, 250), (0x00B8, 333), (0x00B9, 300),
+    (0x00BA, 310), (0x00BB, 500), (0x00BC, 750), (0x00BD, 750), (0x00BE, 750), (0x00BF, 500), (0x00C0, 611),
+    (0x00C1, 611), (0x00C2, 611), (0x00C3, 611), (0x00C4, 611), (0x00C5, 611), (0x00C6, 889), (0x00C7, 667),
+    (0x00C8, 611), (0x00C9, 611), (0x00CA, 611), (0x00CB, 611), (0x00CC, 333), (0x00CD, 333), (0x00CE, 333),
+    (0x00CF, 333), (0x00D0, 722), (0x00D1, 667), (0x00D2, 722), (0x00D3, 722), (0x00D4, 722), (0x00D5, 722),
+    (0x00D6, 722), (0x00D7, 675), (0x00D8, 722), (0x00D9, 722), (0x00DA, 722), (0x00DB, 722), (0x00DC, 722),
+    (0x00DD, 556), (0x00DE, 611), (0x00DF, 500), (0x00E0, 500), (0x00E1, 500), (0x00E2, 500), (0x00E3, 500),
+    (0x00E4, 500), (0x00E5, 500), (0x00E6, 667), (0x00E7, 444), (0x00E8, 444), (0x00E9, 444), (0x00EA, 444),
+    (0x00EB, 444), (0x00EC, 278), (0x00ED, 278), (0x00EE, 278), (0x00EF, 278), (0x00F0, 500), (0x00F1, 500),
+    (0x00F2, 500), (0x00F3, 500), (0x00F4, 500), (0x00F5, 500), (0x00F6, 500), (0x00F7, 675), (0x00F8, 500),
+    (0x00F9, 500), (0x00FA, 500), (0x00FB, 500), (0x00FC, 500), (0x00FD, 444), (0x00FE, 500), (0x00FF, 444),
+    (0x0100, 611), (0x0101, 500), (0x0102, 611), (0x0103, 500), (0x0104, 611), (0x0105, 500), (0x0106, 667),
+    (0x0107, 444), (0x010C, 667), (0x010D, 444), (0x010E, 722), (0x010F, 544), (0x0110, 722), (0x0111, 500),
+    (0x0112, 611), (0x0113, 444), (0x0116, 611), (0x0117, 444), (0x0118, 611), (0x0119, 444), (0x011A, 611),
+    (0x011B, 444), (0x011E, 722), (0x011F, 500), (0x0122, 722), (0x0123, 500), (0x012A, 333), (0x012B, 278),
+    (0x012E, 333), (0x012F, 278), (0x0130, 333), (0x0131, 278), (0x0136, 667), (0x0137, 444), (0x0139, 556),
+    (0x013A, 278), (0x013B, 556), (0x013C, 278), (0x013D, 611), (0x013E, 300), (0x0141, 556), (0x0142, 278),
+    (0x0143, 667), (0x0144, 500), (0x0145, 667), (0x0146, 500), (0x0147, 667), (0x0148, 500), (0x014C, 722),
+    (0x014D, 500), (0x0150, 722), (0x0151, 500), (0x0152, 944), (0x0153, 667), (0x0154, 611), (0x0155, 389),
+    (0x0156, 611), (0x0157, 389), (0x0158, 611), (0x0159, 389), (0x015A, 500), (0x015B, 389), (0x015E, 500),
+    (0x015F, 389), (0x0160, 500), (0x0161, 389), (0x0162, 556), (0x0163, 278), (0x0164, 556), (0x0165, 300),
+    (0x016A, 722), (0x016B, 500), (0x016E, 722), (0x016F, 500), (0x0170, 722), (0x0171, 500), (0x0172, 722),
+    (0x0173, 500), (0x0178, 556), (0x0179, 556), (0x017A, 389), (0x017B, 556), (0x017C, 389), (0x017D, 556),
+    (0x017E, 389), (0x0192, 500), (0x0218, 500), (0x0219, 389), (0x02C6, 333), (0x02C7, 333), (0x02D8, 333),
+    (0x02D9, 333), (0x02DA, 333), (0x02DB, 333), (0x02DC, 333), (0x02DD, 333), (0x2013, 500), (0x2014, 889),
+    (0x2018, 333), (0x2019, 333), (0x201A, 333), (0x201C, 556), (0x201D, 556), (0x201E, 556), (0x2020, 500),
+    (0x2021, 500), (0x2022, 350), (0x2026, 889), (0x2030, 1000), (0x2039, 333), (0x203A, 333), (0x2044, 167),
+    (0x20AC, 500), (0x2122, 980), (0x2202, 476), (0x2206, 612), (0x2211, 600), (0x2212, 675), (0x221A, 453),
+    (0x2260, 549), (0x2264, 549), (0x2265, 549), (0x25CA, 471), (0xF6C3, 250), (0xFB01, 500), (0xFB02, 500),
+];
+
+/// Widths of the glyphs of Times-BoldItalic, by UTF-16 code unit.
+const TIMES_BOLD_ITALIC: &[(u16, u16)] = &[
+    (0x0020, 250), (0x0021, 389), (0x0022, 555), (0x0023, 500), (0x0024, 500), (0x0025, 833), (0x0026, 778),
+    (0x0027, 278), (0x0028, 333), (0x0029, 333), (0x002A, 500), (0x002B, 570), (0x002C, 250), (0x002D, 333),
+    (0x002E, 250), (0x002F, 278), (0x0030, 500), (0x0031, 500), (0x0032, 500), (0x0033, 500), (0x0034, 500),
+    (0x0035, 500), (0x0036, 500), (0x0037, 500), (0x0038, 500), (0x0039, 500), (0x003A, 333), (0x003B, 333),
+    (0x003C, 570), (0x003D, 570), (0x003E, 570), (0x003F, 500), (0x0040, 832), (0x0041, 667), (0x0042, 667),
+    (0x0043, 667), (0x0044, 722), (0x0045, 667), (0x0046, 667), (0x0047, 722), (0x0048, 778), (0x0049, 389),
+    (0x004A, 500), (0x004B, 667), (0x004C, 611), (0x004D, 889), (0x004E, 722), (0x004F, 722), (0x0050, 611),
+    (0x0051, 722), (0x0052, 667), (0x0053, 556), (0x0054, 611), (0x0055, 722), (0x0056, 667), (0x0057, 889),
+    (0x0058, 667), (0x0059, 611), (0x005A, 611), (0x005B, 333), (0x005C, 278), (0x005D, 333), (0x005E, 570),
+    (0x005F, 500), (0x0060, 333), (0x0061, 500), (0x0062, 500), (0x0063, 444), (0x0064, 500), (0x0065, 444),
+    (0x0066, 333), (0x0067, 500), (0x0068, 556), (0x0069, 278), (0x006A, 278), (0x006B, 500), (0x006C, 278),
+    (0x006D, 778), (0x006E, 556), (0x006F, 500), (0x0070, 500), (0x0071, 500), (0x0072, 389), (0x0073, 389),
+    (0x0074, 278), (0x0075, 556), (0x0076, 444), (0x0077, 667), (0x0078, 500), (0x0079, 444), (0x007A, 389),
+    (0x007B, 348), (0x007C, 220), (0x007D, 348), (0x007E, 570), (0x00A1, 389), (0x00A2, 500), (0x00A3, 500),
+    (0x00A4, 500), (0x00A5, 500), (0x00A6, 220), (0x00A7, 500), (0x00A8, 333), (0x00A9, 747), (0x00AA, 266),
+    (0x00AB, 500), (0x00AC, 606), (0x00AE, 747), (0x00AF, 333), (0x00B0, 400), (0x00B1, 570), (0x00B2, 300),
+    (0x00B3, 300), (0x00B4, 333), (0x00B5, 576), (0x00B6, 500), (0x00B7, 250), (0x00B8, 333), (0x00B9, 300),
+    (0x00BA, 300), (0x00BB, 500), (0x00BC, 750), (0x00BD, 750), (0x00BE, 750), (0x00BF, 500), (0x00C0, 667),
+    (0x00C1, 667), (0x00C2, 667), (0x00C3, 667), (0x00C4, 667), (0x00C5, 667), (0x00C6, 944), (0x00C7, 667),
+    (0x00C8, 667), (0x00C9, 667), (0x00CA, 667), (0x00CB, 667), (0x00CC, 389), (0x00CD, 389), (0x00CE, 389),
+    (0x00CF, 389), (0x00D0, 722), (0x00D1, 722), (0x00D2, 722), (0x00D3, 722), (0x00D4, 722), (0x00D5, 722),
+    (0x00D6, 722), (0x00D7, 570), (0x00D8, 722), (0x00D9, 722), (0x00DA, 722), (0x00DB, 722), (0x00DC, 722),
+    (0x00DD, 611), (0x00DE, 611), (0x00DF, 500), (0x00E0, 500), (0x00E1, 500), (0x00E2, 500), (0x00E3, 500),
+    (0x00E4, 500), (0x00E5, 500), (0x00E6, 722), (0x00E7, 444), (0x00E8, 444), (0x00E9, 444), (0x00EA, 444),
+    (0x00EB, 444), (0x00EC, 278), (0x00ED, 278), (0x00EE, 278), (0x00EF, 278), (0x00F0, 500), (0x00F1, 556),
+    (0x00F2, 500), (0x00F3, 500), (0x00F4, 500), (0x00F5, 500), (0x00F6, 500), (0x00F7, 570), (0x00F8, 500),
+    (0x00F9, 556), (0x00FA, 556), (0x00FB, 556), (0x00FC, 556), (0x00FD, 444), (0x00FE, 500), (0x00FF, 444),
+    (0x0100, 667), (0x0101, 500), (0x0102, 667), (0x0103, 500), (0x0104, 667), (0x0105, 500), (0x0106, 667),
+    (0x0107, 444), (0x010C, 667), (0x010D, 444), (0x010E, 722), (0x010F, 608), (0x0110, 722), (0x0111, 500),
+    (0x0112, 667), (0x0113, 444), (0x0116, 667), (0x0117, 444), (0x0118, 667), (0x0119, 444), (0x011A, 667),
+    (0x011B, 444), (0x011E, 722), (0x011F, 500), (0x0122, 722), (0x0123, 500), (0x012A, 389), (0x012B, 278),
+    (0x012E, 389), (0x012F, 278), (0x0130, 389), (0x0131, 278), (0x0136, 667), (0x0137, 500), (0x0139, 611),
+    (0x013A, 278), (0x013B, 611), (0x013C, 278), (0x013D, 611), (0x013E, 382), (0x0141, 611), (0x0142, 278),
+    (0x0143, 722), (0x0144, 556), (0x0145, 722), (0x0146, 556), (0x0147, 722), (0x0148, 556), (0x014C, 722),
+    (0x014D, 500), (0x0150, 722), (0x0151, 500), (0x0152, 944), (0x0153, 722), (0x0154, 667), (0x0155, 389),
+    (0x0156, 667), (0x0157, 389), (0x0158, 667), (0x0159, 389), (0x015A, 556), (0x015B, 389), (0x015E, 556),
+    (0x015F, 389), (0x0160, 556), (0x0161, 389), (0x0162, 611), (0x0163, 278), (0x0164, 611), (0x0165, 366),
+    (0x016A, 722), (0x016B, 556), (0x016E, 722), (0x016F, 556), (0x0170, 722), (0x0171, 556), (0x0172, 722),
+    (0x0173, 556), (0x0178, 611), (0x0179, 611), (0x017A, 389), (0x017B, 611), (0x017C, 389), (0x017D, 611),
+    (0x017E, 389), (0x0192, 500), (0x0218, 556), (0x0219, 389), (0x02C6, 333), (0x02C7, 333), (0x02D8, 333),
+    (0x02D9, 333), (0x02DA, 333), (0x02DB, 333), (0x02DC, 333), (0x02DD, 333), (0x2013, 500), (0x2014, 1000),
+    (0x2018, 333), (0x2019, 333), (0x201A, 333), (0x201C, 500), (0x201D, 500), (0x201E, 500), (0x2020, 500),
+    (0x2021, 500), (0x2022, 350), (0x2026, 1000), (0x2030, 1000), (0x2039, 333), (0x203A, 333), (0x2044, 167),
+    (0x20AC, 500), (0x2122, 1000), (0x2202, 494), (0x2206, 612), (0x2211, 600), (0x2212, 606), (0x221A, 549),
+    (0x2260, 549), (0x2264, 549), (0x2265, 549), (0x25CA, 494), (0xF6C3, 250), (0xFB01, 556), (0xFB02, 556),
+];
+
+/// Widths of the glyphs of Symbol, by code in its built-in encoding.
+const SYMBOL: &[(u16, u16)] = &[
+    (0x0020, 250), (0x0021, 333), (0x0022, 713), (0x0023, 500), (0x0024, 549), (0x0025, 833), (0x0026, 778),
+    (0x0027, 439), (0x0028, 333), (0x0029, 333), (0x002A, 500), (0x002B, 549), (0x002C, 250), (0x002D, 549),
+    (0x002E, 250), (0x002F, 278), (0x0030, 500), (0x0031, 500), (0x0032, 500), (0x0033, 500), (0x0034, 500),
+    (0x0035, 500), (0x0036, 500), (0x0037, 500), (0x0038, 500), (0x0039, 500), (0x003A, 278), (0x003B, 278),
+    (0x003C, 549), (0x003D, 549), (0x003E, 549), (0x003F, 444), (0x0040, 549), (0x0041, 722), (0x0042, 667),
+    (0x0043, 722), (0x0044, 612), (0x0045, 611), (0x0046, 763), (0x0047, 603), (0x0048, 722), (0x0049, 333),
+    (0x004A, 631), (0x004B, 722), (0x004C, 686), (0x004D, 889), (0x004E, 722), (0x004F, 722), (0x0050, 768),
+    (0x0051, 741), (0x0052, 556), (0x0053, 592), (0x0054, 611), (0x0055, 690), (0x0056, 439), (0x0057, 768),
+    (0x0058, 645), (0x0059, 795), (0x005A, 611), (0x005B, 333), (0x005C, 863), (0x005D, 333), (0x005E, 658),
+    (0x005F, 500), (0x0060, 500), (0x0061, 631), (0x0062, 549), (0x0063, 549), (0x0064, 494), (0x0065, 439),
+    (0x0066, 521), (0x0067, 411), (0x0068, 603), (0x0069, 329), (0x006A, 603), (0x006B, 549), (0x006C, 549),
+    (0x006D, 576), (0x006E, 521), (0x006F, 549), (0x0070, 549), (0x0071, 521), (0x0072, 549), (0x0073, 603),
+    (0x0074, 439), (0x0075, 576), (0x0076, 713), (0x0077, 686), (0x0078, 493), (0x0079, 686), (0x007A, 494),
+    (0x007B, 480), (0x007C, 200), (0x007D, 480), (0x007E, 549), (0x00A0, 750), (0x00A1, 620), (0x00A2, 247),
+    (0x00A3, 549), (0x00A4, 167), (0x00A5, 713), (0x00A6, 500), (0x00A7, 753), (0x00A8, 753), (0x00A9, 753),
+    (0x00AA, 753), (0x00AB, 1042), (0x00AC, 987), (0x00AD, 603), (0x00AE, 987), (0x00AF, 603), (0x00B0, 400),
+    (0x00B1, 549), (0x00B2, 411), (0x00B3, 549), (0x00B4, 549), (0x00B5, 713), (0x00B6, 494), (0x00B7, 460),
+    (0x00B8, 549), (0x00B9, 549), (0x00BA, 549), (0x00BB, 549), (0x00BC, 1000), (0x00BD, 603), (0x00BE, 1000),
+    (0x00BF, 658), (0x00C0, 823), (0x00C1, 686), (0x00C2, 795), (0x00C3, 987), (0x00C4, 768), (0x00C5, 768),
+    (0x00C6, 823), (0x00C7, 768), (0x00C8, 768), (0x00C9, 713), (0x00CA, 713), (0x00CB, 713), (0x00CC, 713),
+    (0x00CD, 713), (0x00CE, 713), (0x00CF, 713), (0x00D0, 768), (0x00D1, 713), (0x00D2, 790), (0x00D3, 790),
+    (0x00D4, 890), (0x00D5, 823), (0x00D6, 549), (0x00D7, 250), (0x00D8, 713), (0x00D9, 603), (0x00DA, 603),
+    (0x00DB, 1042), (0x00DC, 987), (0x00DD, 603), (0x00DE, 987), (0x00DF, 603), (0x00E0, 494), (0x00E1, 329),
+    (0x00E2, 790), (0x00E3, 790), (0x00E4, 786), (0x00E5, 713), (0x00E6, 384), (0x00E7, 384), (0x00E8, 384),
+    (0x00E9, 384), (0x00EA, 384), (0x00EB, 384), (0x00EC, 494), (0x00ED, 494), (0x00EE, 494), (0x00EF, 494),
+    (0x00F1, 329), (0x00F2, 274), (0x00F3, 686), (0x00F4, 686), (0x00F5, 686), (0x00F6, 384), (0x00F7, 384),
+    (0x00F8, 384), (0x00F9, 384), (0x00FA, 384), (0x00FB, 384), (0x00FC, 494), (0x00FD, 494), (0x00FE, 494),
+];
+
+/// Widths of the glyphs of ZapfDingbats, by code in its built-in encoding.
+const ZAPF_DINGBATS: &[(u16, u16)] = &[
+    (0x0020, 278), (0x0021, 974), (0x0022, 961), (0x0023, 974), (0x0024, 980), (0x0025, 719), (0x0026, 789),
+    (0x0027, 790), (0x0028, 791), (0x0029, 690), (0x002A, 960), (0x002B, 939), (0x002C, 549), (0x002D, 855),
+    (0x002E, 911), (0x002F, 933), (0x0030, 911), (0x0031, 945), (0x0032, 974), (0x0033, 755), (0x0034, 846),
+    (0x0035, 762), (0x0036, 761), (0x0037, 571), (0x0038, 677), (0x0039, 763), (0x003A, 760), (0x003B, 759),
+    (0x003C, 754), (0x003D, 494), (0x003E, 552), (0x003F, 537), (0x0040, 577), (0x0041, 692), (0x0042, 786),
+    (0x0043, 788), (0x0044, 788), (0x0045, 790), (0x0046, 793), (0x0047, 794), (0x0048, 816), (0x0049, 823),
+    (0x004A, 789), (0x004B, 841), (0x004C, 823), (0x004D, 833), (0x004E, 816), (0x004F, 831), (0x0050, 923),
+    (0x0051, 744), (0x0052, 723), (0x0053, 749), (0x0054, 790), (0x0055, 792), (0x0056, 695), (0x0057, 776),
+    (0x0058, 768), (0x0059, 792), (0x005A, 759), (0x005B, 707), (0x005C, 708), (0x005D, 682), (0x005E, 701),
+    (0x005F, 826), (0x0060, 815), (0x0061, 789), (0x0062, 789), (0x0063, 707), (0x0064, 687), (0x0065, 696),
+    (0x0066, 689), (0x0067, 786), (0x0068, 787), (0x0069, 713), (0x006A, 791), (0x006B, 785), (0x006C, 791),
+    (0x006D, 873), (0x006E, 761), (0x006F, 762), (0x0070, 762), (0x0071, 759), (0x0072, 759), (0x0073, 892),
+    (0x0074, 892), (0x0075, 788), (0x0076, 784), (0x0077, 438), (0x0078, 138), (0x0079, 277), (0x007A, 415),
+    (0x007B, 392), (0x007C, 392), (0x007D, 668), (0x007E, 668), (0x0080, 390), (0x0081, 390), (0x0082, 317),
+    (0x0083, 317), (0x0084, 276), (0x0085, 276), (0x0086, 509), (0x0087, 509), (0x0088, 410), (0x0089, 410),
+    (0x008A, 234), (0x008B, 234), (0x008C, 334), (0x008D, 334), (0x00A1, 732), (0x00A2, 544), (0x00A3, 544),
+    (0x00A4, 910), (0x00A5, 667), (0x00A6, 760), (0x00A7, 760), (0x00A8, 776), (0x00A9, 595), (0x00AA, 694),
+    (0x00AB, 626), (0x00AC, 788), (0x00AD, 788), (0x00AE, 788), (0x00AF, 788), (0x00B0, 788), (0x00B1, 788),
+    (0x00B2, 788), (0x00B3, 788), (0x00B4, 788), (0x00B5, 788), (0x00B6, 788), (0x00B7, 788), (0x00B8, 788),
+    (0x00B9, 788), (0x00BA, 788), (0x00BB, 788), (0x00BC, 788), (0x00BD, 788), (0x00BE, 788), (0x00BF, 788),
+    (0x00C0, 788), (0x00C1, 788), (0x00C2, 788), (0x00C3, 788), (0x00C4, 788), (0x00C5, 788), (0x00C6, 788),
+    (0x00C7, 788), (0x00C8, 788), (0x00C9, 788), (0x00CA, 788), (0x00CB, 788), (0x00CC, 788), (0x00CD, 788),
+    (0x00CE, 788), (0x00CF, 788), (0x00D0, 788), (0x00D1, 788), (0x00D2, 788), (0x00D3, 788), (0x00D4, 894),
+    (0x00D5, 838), (0x00D6, 1016), (0x00D7, 458), (0x00D8, 748), (0x00D9, 924), (0x00DA, 748), (0x00DB, 918),
+    (0x00DC, 927), (0x00DD, 928), (0x00DE, 928), (0x00DF, 834), (0x00E0, 873), (0x00E1, 828), (0x00E2, 924),
+    (0x00E3, 924), (0x00E4, 917), (0x00E5, 930), (0x00E6, 931), (0x00E7, 463), (0x00E8, 883), (0x00E9, 836),
+    (0x00EA, 836), (0x00EB, 867), (0x00EC, 867), (0x00ED, 696), (0x00EE, 696), (0x00EF, 874), (0x00F1, 874),
+    (0x00F2, 760), (0x00F3, 946), (0x00F4, 771), (0x00F5, 865), (0x00F6, 771), (0x00F7, 888), (0x00F8, 967),
+    (0x00F9, 888), (0x00FA, 831), (0x00FB, 873), (0x00FC, 927), (0x00FD, 970), (0x00FE, 918),
+];
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_font_names() {
+        assert_eq!(standard_font_name(b"Helvetica"), Some("Helvetica"));
+        assert_eq!(standard_font_name(b"ArialMT"), Some("Helvetica"));
+        assert_eq!(standard_font_name(b"ABCDEF+TimesNewRomanPS-BoldMT"), Some("Times-Bold"));
+        assert_eq!(standard_font_name(b"Times New Roman,Italic"), Some("Times-Italic"));
+        assert_eq!(standard_font_name(b"abcdef+Arial"), None);
+        assert_eq!(standard_font_name(b"Montserrat"), None);
+        assert!(
+            STANDARD_FONT_ALIASES
+                .iter()
+                .all(|(_, font)| STANDARD_FONTS.contains(font))
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{Document, Object, Stream};
+
+/// How much of a stream is compressed first, to tell whether compressing all of it is worth it.
+const TRIAL_SIZE: usize = 64 * 1024;
+
+/// Streams whose trial compression saves less than this share of the trial, in percent, are left
+/// uncompressed: their data is close to random already, as that of images decoded into streams.
+const MIN_SAVINGS_PERCENT: usize = 2;
+
+/// Bytes Flate has to save for a stream to be worth the `/Filter /FlateDecode` entry.
+const FILTER_ENTRY_SIZE: usize = 19;
+
+/// What [`Document::compress_streams`] did, which saving with
+/// [`SaveOptionsBuilder::compress_streams`](crate::SaveOptionsBuilder::compress_streams) reports in its
+/// [`SaveReport`](crate::SaveReport).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompressionSummary {
+    /// Number of streams compressed with Flate
+    pub compressed: usize,
+    /// Number of streams left as they were because they have filters already, by the filter first
+    /// applied to their data, such as `DCTDecode` for JPEG images
+    pub skipped_by_filter: BTreeMap<String, usize>,
+    /// Number of streams left uncompressed because compressing them saved too little
+    pub skipped_incompressible: usize,
+    /// Bytes the compressed streams shrank by
+    pub bytes_saved: u64,
+}
+
+impl CompressionSummary {
+    /// Number of streams left as they were because they have filters already.
+    pub fn skipped_filtered(&self) -> usize {
+        self.skipped_by_filter.values().sum()
+    }
+}
+
+/// What compressing a stream did.
+enum Outcome {
+    Compressed { saved: usize },
+    Filtered(String),
+    Incompressible,
+    NotAllowed,
+}
+
+impl Document {
+    /// Compress the streams of the document with Flate, leaving those which don't allow it, see
+    /// [`Stream::with_compression`], and those with filters alone. Filtered streams hold data
+    /// compressed already, or entropy coded like the `DCTDecode`, `JPXDecode`, `CCITTFaxDecode` and
+    /// `JBIG2Decode` images which Flate would only make larger. Streams whose first 64 KiB barely
+    /// compress aren't compressed either. Streams are compressed in parallel with the `rayon`
+    /// feature.
+    pub fn compress_streams(&mut self) -> CompressionSummary {
+        let streams: Vec<&mut Stream> = self
+            .objects
+            .values_mut()
+            .filter_map(|object| object.as_stream_mut().ok())
+            .collect();
+        #[cfg(feature = "rayon")]
+        let outcomes: Vec<Outcome> = streams.into_par_iter().map(compress_stream).collect();
+        #[cfg(not(feature = "rayon"))]
+        let outcomes: Vec<Outcome> = streams.into_iter().map(compress_stream).collect();
+
+        let mut summary = CompressionSummary::default();
+        for outcome in outcomes {
+            match outcome {
+                Outcome::Compressed { saved } => {
+                    summary.compressed += 1;
+                    summary.bytes_saved += saved as u64;
+                }
+                Outcome::Filtered(filter) => *summary.skipped_by_filter.entry(filter).or_default() += 1,
+                Outcome::Incompressible => summary.skipped_incompressible += 1,
+                Outcome::NotAllowed => {}
+            }
+        }
+        summary
+    }
+}
+
+fn compress_stream(stream: &mut Stream) -> Outcome {
+    if !stream.allows_compression {
+        return Outcome::NotAllowed;
+    }
+    if stream.dict.has(b"Filter") {
+        // Filters are in decoding order, the last one was applied to the data first.
+        let filters = stream.filters().unwrap_or_default();
+        let filter = filters
+            .last()
+            .map(|filter| String::from_utf8_lossy(filter).into_owned());
+        return Outcome::Filtered(filter.unwrap_or_default());
+    }
+
+    let content = &stream.content;
+    let trial = &content[..content.len().min(TRIAL_SIZE)];
+    let Ok(trial_compressed) = deflate(trial) else {
+        return Outcome::Incompressible;
+    };
+    if trial.len().saturating_sub(trial_compressed.len()) * 100 < trial.len() * MIN_SAVINGS_PERCENT {
+        return Outcome::Incompressible;
+    }
+    let compressed = if trial.len() == content.len() {
+        trial_compressed
+    } else {
+        match deflate(content) {
+            Ok(compressed) => compressed,
+            Err(_) => return Outcome::Incompressible,
+        }
+    };
+    if compressed.len() + FILTER_ENTRY_SIZE >= content.len() {
+        return Outcome::Incompressible;
+    }
+
+    let saved = content.len() - compressed.len();
+    stream.dict.set("Filter", Object::Name(b"FlateDecode".to_vec()));
+    // Parameters without a filter are stray, and would apply to Flate now.
+    stream.dict.remove(b"DecodeParms");
+    stream.set_content(compressed);
+    Outcome::Compressed { saved }
+}
+
+fn deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes which don't compress, as the data of encoded images.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compress_streams_skips_filtered_and_incompressible_streams() {
+        let mut doc = Document::with_version("1.7");
+        let text = doc.add_object(Stream::new(dictionary! {}, b"BT (Hello) Tj ET\n".repeat(100)));
+        let jpeg = doc.add_object(Stream::new(dictionary! { "Filter" => "DCTDecode" }, noise(1000)));
+        let chained = Stream::new(
+            dictionary! { "Filter" => vec!["ASCIIHexDecode".into(), "JPXDecode".into()] },
+            b"00ff".to_vec(),
+        );
+        doc.add_object(chained);
+        let noisy = doc.add_object(Stream::new(dictionary! {}, noise(200 * 1024)));
+        let font = doc.add_object(Stream::new(dictionary! {}, vec![0; 1000]).with_compression(false));
+
+        let summary = doc.compress_streams();
+
+        assert_eq!(summary.compressed, 1);
+        let skipped = BTreeMap::from([("DCTDecode".to_string(), 1), ("JPXDecode".to_string(), 1)]);
+        assert_eq!(summary.skipped_by_filter, skipped);
+        assert_eq!(summary.skipped_filtered(), 2);
+        assert_eq!(summary.skipped_incompressible, 1);
+        let text = doc.get_object(text).unwrap().as_stream().unwrap();
+        assert_eq!(text.filters().unwrap(), [b"FlateDecode"]);
+        assert_eq!(summary.bytes_saved, 1700 - text.content.len() as u64);
+        assert_eq!(text.decompressed_content().unwrap(), b"BT (Hello) Tj ET\n".repeat(100));
+        let jpeg = doc.get_object(jpeg).unwrap().as_stream().unwrap();
+        assert_eq!(jpeg.filters().unwrap(), [b"DCTDecode"]);
+        assert!(doc.get_object(noisy).unwrap().as_stream().unwrap().filters().is_err());
+        assert!(doc.get_object(font).unwrap().as_stream().unwrap().filters().is_err());
+    }
+
+    #[test]
+    fn compress_streams_judges_large_streams_by_their_start() {
+        let mut doc = Document::with_version("1.7");
+        // Noise for the trial, then a long run compressing well.
+        let mut data = noise(TRIAL_SIZE);
+        data.extend(vec![b'x'; 10 * TRIAL_SIZE]);
+        let id = doc.add_object(Stream::new(dictionary! { "DecodeParms" => dictionary! {} }, data));
+
+        assert_eq!(doc.compress_streams().skipped_incompressible, 1);
+
+        // The other way around, compressed as a whole.
+        let mut data = vec![b'x'; TRIAL_SIZE];
+        data.extend(noise(10 * TRIAL_SIZE));
+        doc.get_object_mut(id)
+            .unwrap()
+            .as_stream_mut()
+            .unwrap()
+            .set_content(data.clone());
+
+        let summary = doc.compress_streams();
+        assert_eq!(summary.compressed, 1);
+        let stream = doc.get_object(id).unwrap().as_stream().unwrap();
+        assert!(!stream.dict.has(b"DecodeParms"));
+        assert_eq!(stream.decompressed_content().unwrap(), data);
+    }
+}
@@ -0,0 +1,272 @@
+use std::fmt;
+
+use crate::encodings::decode_utf16_be;
+use crate::writer::Writer;
+use crate::{Document, Error, Object, ObjectId};
+
+/// Largest relative difference between a real and the value read back from its written form.
+const REAL_TOLERANCE: f32 = 1e-6;
+
+/// A place where writing the document would not reproduce what the [`Document`] contains.
+///
+/// The writer falls back to writing these as well as it can, unless
+/// [`SaveOptionsBuilder::strict_output`](crate::SaveOptionsBuilder::strict_output) is set, in which case saving
+/// fails with [`Error::StrictOutput`] listing all of them. [`Document::output_fallbacks`] lists
+/// them without saving.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputFallback {
+    /// A stream nested in another object, which can only be written as an indirect object
+    NestedStream { id: ObjectId },
+    /// A real whose written form reads back as a different number
+    InexactReal { id: ObjectId, value: f32 },
+    /// A NaN or infinite real, which PDF has no syntax for, written as 0
+    NonFiniteReal { id: ObjectId, value: f32 },
+    /// A UTF-16 text string with an unpaired surrogate or an odd number of bytes
+    LoneSurrogate { id: ObjectId },
+    /// A stream whose /Length doesn't match its content, written as it is
+    LengthMismatch {
+        id: ObjectId,
+        declared: Option<i64>,
+        actual: usize,
+    },
+    /// An object numbered at or above the trailer's /Size, left out of the cross-reference table
+    IdAboveSize { id: ObjectId, size: u32 },
+}
+
+impl fmt::Display for OutputFallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputFallback::NestedStream { id } => write!(f, "object {} {} contains a direct stream", id.0, id.1),
+            OutputFallback::InexactReal { id, value } => {
+                write!(
+                    f,
+                    "object {} {} contains real {value} that can't be written exactly",
+                    id.0, id.1
+                )
+            }
+            OutputFallback::NonFiniteReal { id, value } => {
+                write!(f, "object {} {} contains real {value}, written as 0", id.0, id.1)
+            }
+            OutputFallback::LoneSurrogate { id } => {
+                write!(
+                    f,
+                    "object {} {} contains a UTF-16 string with a lone surrogate",
+                    id.0, id.1
+                )
+            }
+            OutputFallback::LengthMismatch { id, declared, actual } => match declared {
+                Some(declared) => write!(
+                    f,
+                    "stream {} {} declares /Length {declared} but has {actual} bytes",
+                    id.0, id.1
+                ),
+                None => write!(f, "stream {} {} has no /Length for its {actual} bytes", id.0, id.1),
+            },
+            OutputFallback::IdAboveSize { id, size } => {
+                write!(f, "object {} {} is not below the trailer /Size {size}", id.0, id.1)
+            }
+        }
+    }
+}
+
+impl Document {
+    /// List the lossy fallbacks writing the document would take.
+    ///
+    /// Object streams, cross-reference streams and linearization dictionaries are not listed,
+    /// as the writer regenerates them.
+    pub fn output_fallbacks(&self) -> Vec<OutputFallback> {
+        let mut fallbacks = Vec::new();
+        let size = self.max_id + 1;
+        for (&id, object) in &self.objects {
+            if id.0 >= size {
+                fallbacks.push(OutputFallback::IdAboveSize { id, size });
+            }
+            if let Object::Stream(stream) = object {
+                let declared = match stream.dict.get(b"Length") {
+                    Ok(length) => self.dereference(length).and_then(|(_, length)| length.as_i64()).ok(),
+                    Err(_) => None,
+                };
+                if declared != Some(stream.content.len() as i64) {
+                    fallbacks.push(OutputFallback::LengthMismatch {
+                        id,
+                        declared,
+                        actual: stream.content.len(),
+                    });
+                }
+                for value in stream.dict.iter().map(|(_, value)| value) {
+                    collect_fallbacks(id, value, &mut fallbacks);
+                }
+            } else {
+                collect_fallbacks(id, object, &mut fallbacks);
+            }
+        }
+        fallbacks
+    }
+
+    /// Refuse the lossy fallbacks of writing the document, for strict saving. Listing them walks
+    /// every object, so other saves don't.
+    pub(crate) fn refuse_output_fallbacks(&self) -> std::io::Result<()> {
+        let fallbacks = self.output_fallbacks();
+        if !fallbacks.is_empty() {
+            return Err(std::io::Error::other(Error::StrictOutput(fallbacks)));
+        }
+        Ok(())
+    }
+}
+
+/// Collect the fallbacks of a direct object of indirect object `id`.
+fn collect_fallbacks(id: ObjectId, object: &Object, fallbacks: &mut Vec<OutputFallback>) {
+    match object {
+        Object::Real(value) if !value.is_finite() => {
+            fallbacks.push(OutputFallback::NonFiniteReal { id, value: *value })
+        }
+        Object::Real(value) if !is_exact(*value) => fallbacks.push(OutputFallback::InexactReal { id, value: *value }),
+        Object::String(bytes, _) if has_lone_surrogate(bytes) => fallbacks.push(OutputFallback::LoneSurrogate { id }),
+        Object::Array(array) => array.iter().for_each(|item| collect_fallbacks(id, item, fallbacks)),
+        Object::Dictionary(dict) => dict
+            .iter()
+            .for_each(|(_, value)| collect_fallbacks(id, value, fallbacks)),
+        Object::Stream(_) => fallbacks.push(OutputFallback::NestedStream { id }),
+        _ => {}
+    }
+}
+
+/// Whether `value` reads back within [`REAL_TOLERANCE`] once written.
+fn is_exact(value: f32) -> bool {
+    let mut written = Vec::new();
+    if Writer::write_object(&mut written, &Object::Real(value)).is_err() {
+        return false;
+    }
+    let read = match crate::parse::object(&written) {
+        Ok(Object::Real(read)) => read,
+        Ok(Object::Integer(read)) => read as f32,
+        _ => return false,
+    };
+    (read - value).abs() <= REAL_TOLERANCE * value.abs().max(1.0)
+}
+
+/// Whether `bytes` is a UTF-16BE text string that doesn't decode.
+fn has_lone_surrogate(bytes: &[u8]) -> bool {
+    bytes
+        .strip_prefix(b"\xFE\xFF")
+        .is_some_and(|text| decode_utf16_be(text).replacements > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use crate::{SaveOptions, Stream};
+
+    /// Save `doc` leniently and strictly, returning the fallbacks the strict save refused.
+    fn save_both_ways(doc: &mut Document) -> Vec<OutputFallback> {
+        let mut lenient = Vec::new();
+        doc.clone().save_to(&mut lenient).unwrap();
+        assert!(!lenient.is_empty());
+
+        let options = SaveOptions::builder().strict_output(true).build().unwrap();
+        let err = doc.save_with_options(&mut Vec::new(), options).unwrap_err();
+        match err.get_ref().and_then(|err| err.downcast_ref::<Error>()) {
+            Some(Error::StrictOutput(fallbacks)) => fallbacks.clone(),
+            _ => panic!("unexpected error {err}"),
+        }
+    }
+
+    #[test]
+    fn clean_document_saves_strictly() {
+        let mut doc = create_document();
+        assert_eq!(doc.output_fallbacks(), []);
+        let options = SaveOptions::builder().strict_output(true).build().unwrap();
+        doc.save_with_options(&mut Vec::new(), options).unwrap();
+    }
+
+    #[test]
+    fn nested_stream_is_refused() {
+        let mut doc = create_document();
+        let id = doc.add_object(dictionary! { "Inline" => Stream::new(dictionary! {}, b"data".to_vec()) });
+        assert_eq!(doc.output_fallbacks(), [OutputFallback::NestedStream { id }]);
+        assert_eq!(save_both_ways(&mut doc), [OutputFallback::NestedStream { id }]);
+    }
+
+    #[test]
+    fn finite_reals_are_exact() {
+        // Large reals are written without an exponent, as digits that read back as a real.
+        let mut doc = create_document();
+        doc.add_object(vec![Object::Real(0.1), Object::Real(1e30), Object::Real(-1e-30)]);
+        assert_eq!(doc.output_fallbacks(), []);
+    }
+
+    #[test]
+    fn non_finite_reals_are_refused() {
+        let mut doc = create_document();
+        let id = doc.add_object(dictionary! {
+            "NaN" => Object::Real(f32::NAN),
+            "Infinity" => Object::Real(f32::NEG_INFINITY),
+        });
+        let fallbacks = save_both_ways(&mut doc);
+        assert_eq!(fallbacks.len(), 2, "{fallbacks:?}");
+        assert!(
+            fallbacks
+                .iter()
+                .all(|fallback| matches!(fallback, OutputFallback::NonFiniteReal { id: found, .. } if *found == id))
+        );
+    }
+
+    #[test]
+    fn non_finite_reals_are_written_as_zero() {
+        let mut doc = create_document();
+        let id = doc.add_object(vec![Object::Real(f32::NAN), Object::Real(f32::INFINITY), Object::Real(1.5)]);
+        let mut saved = Vec::new();
+        doc.save_to(&mut saved).unwrap();
+        let doc = Document::load_mem(&saved).unwrap();
+        let array = doc.get_object(id).unwrap().as_array().unwrap();
+        assert_eq!(array, &[Object::Integer(0), Object::Integer(0), Object::Real(1.5)]);
+    }
+
+    #[test]
+    fn lone_surrogate_is_refused() {
+        let mut doc = create_document();
+        let id = doc.add_object(Object::string_literal(b"\xFE\xFF\xD8\x00\x00A".to_vec()));
+        doc.add_object(Object::string_literal(b"\xFE\xFF\xD8\x3D\xDE\x00".to_vec()));
+        assert_eq!(save_both_ways(&mut doc), [OutputFallback::LoneSurrogate { id }]);
+    }
+
+    #[test]
+    fn length_mismatch_is_refused() {
+        let mut doc = create_document();
+        let mut stream = Stream::new(dictionary! {}, b"content".to_vec());
+        stream.dict.set("Length", 3);
+        let id = doc.add_object(stream);
+        let length_id = doc.add_object(7);
+        doc.add_object(Stream::new(dictionary! { "Length" => length_id }, b"content".to_vec()));
+        assert_eq!(
+            save_both_ways(&mut doc),
+            [OutputFallback::LengthMismatch {
+                id,
+                declared: Some(3),
+                actual: 7
+            }]
+        );
+    }
+
+    #[test]
+    fn id_above_size_is_refused() {
+        let mut doc = create_document();
+        let id = (doc.max_id + 3, 0);
+        doc.objects.insert(id, Object::Null);
+        let size = doc.max_id + 1;
+        assert_eq!(save_both_ways(&mut doc), [OutputFallback::IdAboveSize { id, size }]);
+
+        // Saving with object streams numbers them after the highest id, which is refused first.
+        let options = SaveOptions::builder()
+            .use_object_streams(true)
+            .strict_output(true)
+            .build()
+            .unwrap();
+        let err = doc.save_with_options(&mut Vec::new(), options).unwrap_err();
+        assert!(matches!(
+            err.get_ref().and_then(|err| err.downcast_ref::<Error>()),
+            Some(Error::StrictOutput(fallbacks)) if fallbacks == &[OutputFallback::IdAboveSize { id, size }]
+        ));
+    }
+}
@@ -0,0 +1,873 @@
+use std::collections::HashMap;
+
+use crate::content_walker::{ContentWalker, multiply};
+use crate::encodings::Encoding;
+use crate::font_widths::{FALLBACK_WIDTH, FontWidths};
+use crate::shaping::{CidWidths, cid_widths};
+use crate::{Dictionary, Document, Error, Object, ObjectId, Result};
+
+const IDENTITY: [f32; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Share of the font size a glyph extends below the baseline, and above it the rest of an em.
+const DESCENT: f32 = 0.2;
+
+/// Gap between glyphs, in multiples of the font size, that separates words.
+const WORD_GAP: f32 = 0.15;
+
+/// Vertical gap between lines, in multiples of the font size, that ends a borderless table.
+const MAX_LINE_GAP: f32 = 3.0;
+
+/// Options for [`Document::extract_tables`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableOptions {
+    /// Also look for tables without ruling lines, whose columns are separated by whitespace
+    pub borderless: bool,
+    /// Distance in points within which ruling lines are joined and meet each other
+    pub tolerance: f32,
+    /// Filled rectangles at most this thick, in points, are ruling lines
+    pub max_rule_thickness: f32,
+    /// Gap between words, in multiples of the font size, that separates the columns of borderless
+    /// tables
+    pub min_column_gap: f32,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            borderless: false,
+            tolerance: 2.0,
+            max_rule_thickness: 3.0,
+            min_column_gap: 1.0,
+        }
+    }
+}
+
+/// A table found by [`Document::extract_tables`], a grid of rows from the top and columns from the
+/// left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// The bounding box `[x0, y0, x1, y1]`, in default user space
+    pub bbox: [f32; 4],
+    pub row_count: usize,
+    pub column_count: usize,
+    /// The cells by row and column of their top left grid position. A cell covers
+    /// the grid positions it spans.
+    pub cells: Vec<TableCell>,
+}
+
+/// A cell of a [`Table`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableCell {
+    pub row: usize,
+    pub column: usize,
+    /// Number of rows the cell covers, more than 1 where an interior horizontal ruling is missing
+    pub row_span: usize,
+    /// Number of columns the cell covers, more than 1 where an interior vertical ruling is missing
+    pub column_span: usize,
+    /// The bounding box `[x0, y0, x1, y1]`, in default user space
+    pub bbox: [f32; 4],
+    /// The words inside the cell, separated by spaces and by newlines between lines
+    pub text: String,
+}
+
+impl Table {
+    /// The cell covering the grid position at `row` and `column`.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&TableCell> {
+        self.cells.iter().find(|cell| {
+            (cell.row..cell.row + cell.row_span).contains(&row)
+                && (cell.column..cell.column + cell.column_span).contains(&column)
+        })
+    }
+
+    /// The text of the cells as rows of columns. Grid positions covered by a cell spanning from
+    /// an earlier position are empty.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![vec![String::new(); self.column_count]; self.row_count];
+        for cell in &self.cells {
+            rows[cell.row][cell.column] = cell.text.clone();
+        }
+        rows
+    }
+}
+
+impl Document {
+    /// Find tables on a page and extract the text of their cells.
+    ///
+    /// This is a heuristic. Tables are found from horizontal and vertical ruling lines, stroked
+    /// or drawn as thin filled rectangles, which make a grid of cells where they meet. A cell
+    /// whose interior ruling is missing spans several grid positions. Words are assigned to the
+    /// cell containing their center. With [`TableOptions::borderless`], lines of words with wide
+    /// gaps at the same positions outside ruled tables also make tables.
+    pub fn extract_tables(&self, page_number: u32, options: &TableOptions) -> Result<Vec<Table>> {
+        let page_id = *self
+            .get_pages()
+            .get(&page_number)
+            .ok_or(Error::PageNumberNotFound(page_number))?;
+        let graphics = self.page_graphics(page_id, options)?;
+
+        let mut tables = ruled_tables(&graphics, options.tolerance);
+        if options.borderless {
+            let free: Vec<&Word> = graphics
+                .words
+                .iter()
+                .filter(|word| !tables.iter().any(|table| contains(&table.bbox, word.center())))
+                .collect();
+            tables.extend(borderless_tables(&free, options.min_column_gap));
+            tables.sort_by(|a, b| b.bbox[3].total_cmp(&a.bbox[3]));
+        }
+        for table in &mut tables {
+            for cell in &mut table.cells {
+                let words = graphics.words.iter().filter(|word| contains(&cell.bbox, word.center()));
+                cell.text = lines(words)
+                    .iter()
+                    .map(|line| line.iter().map(|word| word.text.as_str()).collect::<Vec<_>>().join(" "))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            }
+        }
+        Ok(tables)
+    }
+
+    /// Collect the words and ruling lines a page draws.
+    fn page_graphics(&self, page_id: ObjectId, options: &TableOptions) -> Result<PageGraphics> {
+        let mut graphics = PageGraphics::default();
+        let mut fonts: Vec<PageFont> = Vec::new();
+        let mut font_index: HashMap<ObjectId, usize> = HashMap::new();
+        let mut state = TextState::default();
+        let mut saved_states = Vec::new();
+        let mut text_matrix = IDENTITY;
+        let mut line_matrix = IDENTITY;
+        let mut path = Path::default();
+        let mut word: Option<Word> = None;
+
+        ContentWalker::new(self).walk_page(page_id, |operation, context| {
+            let operands = &operation.operands;
+            let number = |index: usize| operands.get(index).and_then(|n| n.as_float().ok()).unwrap_or(0.0);
+            let point = |index: usize| transform(&context.ctm, number(index), number(index + 1));
+            match operation.operator.as_str() {
+                "q" => saved_states.push(state),
+                "Q" => state = saved_states.pop().unwrap_or_default(),
+                "BT" => {
+                    text_matrix = IDENTITY;
+                    line_matrix = IDENTITY;
+                }
+                "Tc" => state.char_spacing = number(0),
+                "Tw" => state.word_spacing = number(0),
+                "Tz" => state.scale = number(0) / 100.0,
+                "TL" => state.leading = number(0),
+                "Ts" => state.rise = number(0),
+                "Tf" => {
+                    state.font_size = number(1);
+                    let font = operands.first().and_then(|name| name.as_name().ok());
+                    state.font = font.and_then(|font| context.font(font)).and_then(|(font_id, font)| {
+                        if let Some(index) = font_id.and_then(|id| font_index.get(&id)) {
+                            return Some(*index);
+                        }
+                        fonts.push(PageFont::new(self, font)?);
+                        if let Some(font_id) = font_id {
+                            font_index.insert(font_id, fonts.len() - 1);
+                        }
+                        Some(fonts.len() - 1)
+                    });
+                }
+                "Td" | "TD" => {
+                    if operation.operator == "TD" {
+                        state.leading = -number(1);
+                    }
+                    line_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, number(0), number(1)], &line_matrix);
+                    text_matrix = line_matrix;
+                }
+                "Tm" => {
+                    let values: Vec<f32> = operands.iter().filter_map(|n| n.as_float().ok()).collect();
+                    if let Ok(matrix) = values.try_into() {
+                        line_matrix = matrix;
+                        text_matrix = matrix;
+                    }
+                }
+                "T*" | "'" | "\"" => {
+                    if operation.operator == "\"" {
+                        state.word_spacing = number(0);
+                        state.char_spacing = number(1);
+                    }
+                    line_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, 0.0, -state.leading], &line_matrix);
+                    text_matrix = line_matrix;
+                    if let Some(Object::String(bytes, _)) = operands.last().filter(|_| operation.operator != "T*") {
+                        let font = state.font.map(|index| &fonts[index]);
+                        let ctm = context.ctm;
+                        show(
+                            bytes,
+                            font,
+                            &state,
+                            &mut text_matrix,
+                            &ctm,
+                            &mut word,
+                            &mut graphics.words,
+                        );
+                    }
+                }
+                "Tj" | "TJ" => {
+                    let font = state.font.map(|index| &fonts[index]);
+                    for operand in operands.iter().flat_map(|operand| match operand {
+                        Object::Array(items) => items.iter().collect(),
+                        operand => vec![operand],
+                    }) {
+                        match operand {
+                            Object::String(bytes, _) => {
+                                let ctm = context.ctm;
+                                show(
+                                    bytes,
+                                    font,
+                                    &state,
+                                    &mut text_matrix,
+                                    &ctm,
+                                    &mut word,
+                                    &mut graphics.words,
+                                );
+                            }
+                            Object::Integer(_) | Object::Real(_) => {
+                                let adjustment = operand.as_float().unwrap_or(0.0);
+                                let offset = -adjustment / 1000.0 * state.font_size * state.scale;
+                                text_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, offset, 0.0], &text_matrix);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                "m" => path.move_to(point(0)),
+                "l" => path.line_to(point(0)),
+                "c" => path.move_to(point(4)),
+                "v" | "y" => path.move_to(point(2)),
+                "re" => {
+                    let (x, y, width, height) = (number(0), number(1), number(2), number(3));
+                    let corners = [(x, y), (x + width, y), (x + width, y + height), (x, y + height), (x, y)];
+                    path.rectangle(corners.map(|(x, y)| transform(&context.ctm, x, y)).to_vec());
+                }
+                "h" => path.close(),
+                "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" | "n" => {
+                    let operator = operation.operator.as_str();
+                    if matches!(operator, "s" | "b" | "b*") {
+                        path.close();
+                    }
+                    let subpaths = path.take();
+                    if matches!(operator, "S" | "s" | "B" | "B*" | "b" | "b*") {
+                        for subpath in &subpaths {
+                            graphics.stroke(subpath, options.tolerance);
+                        }
+                    }
+                    if matches!(operator, "f" | "F" | "f*" | "B" | "B*" | "b" | "b*") {
+                        for subpath in &subpaths {
+                            graphics.fill(subpath, options.max_rule_thickness);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        })?;
+        graphics.words.extend(word);
+        Ok(graphics)
+    }
+}
+
+/// A font shown on the page, with what's needed to position its glyphs.
+struct PageFont<'a> {
+    encoding: Encoding<'a>,
+    widths: GlyphWidths,
+}
+
+enum GlyphWidths {
+    Simple(FontWidths),
+    /// Widths of two-byte codes of a composite font, by CID
+    Composite(Option<CidWidths>),
+}
+
+impl<'a> PageFont<'a> {
+    fn new(doc: &'a Document, font: &'a Dictionary) -> Option<Self> {
+        let encoding = font.get_font_encoding(doc).ok()?;
+        let widths = if font.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0") {
+            GlyphWidths::Composite(cid_widths(font, doc).ok())
+        } else {
+            GlyphWidths::Simple(doc.font_widths(font).ok()?)
+        };
+        Some(PageFont { encoding, widths })
+    }
+
+    /// The character codes of `bytes`.
+    fn codes<'b>(&self, bytes: &'b [u8]) -> std::slice::Chunks<'b, u8> {
+        match self.widths {
+            GlyphWidths::Simple(_) => bytes.chunks(1),
+            GlyphWidths::Composite(_) => bytes.chunks(2),
+        }
+    }
+
+    /// The width of the glyph of `code`, in thousandths of text space units.
+    fn width(&self, code: &[u8]) -> f32 {
+        match &self.widths {
+            GlyphWidths::Simple(widths) => widths.width(code[0]),
+            GlyphWidths::Composite(widths) => {
+                let cid = code.iter().fold(0, |cid, &byte| (cid << 8) | u16::from(byte));
+                widths.as_ref().map_or(FALLBACK_WIDTH, |widths| widths.width(cid))
+            }
+        }
+    }
+}
+
+/// The text state parameters, saved and restored with the graphics state.
+#[derive(Debug, Clone, Copy)]
+struct TextState {
+    char_spacing: f32,
+    word_spacing: f32,
+    scale: f32,
+    leading: f32,
+    rise: f32,
+    font_size: f32,
+    /// Index of the font among those shown on the page
+    font: Option<usize>,
+}
+
+impl Default for TextState {
+    fn default() -> Self {
+        TextState {
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            scale: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+            font_size: 0.0,
+            font: None,
+        }
+    }
+}
+
+/// Show a string, adding its glyphs to `word` and finished words to `words`.
+fn show(
+    bytes: &[u8], font: Option<&PageFont>, state: &TextState, text_matrix: &mut [f32; 6], ctm: &[f32; 6],
+    word: &mut Option<Word>, words: &mut Vec<Word>,
+) {
+    let Some(font) = font else {
+        return;
+    };
+    for code in font.codes(bytes) {
+        let text = font.encoding.bytes_to_string(code).unwrap_or_default();
+        let glyph_width = font.width(code) / 1000.0 * state.font_size * state.scale;
+        let word_spacing = if code == b" " { state.word_spacing } else { 0.0 };
+        let advance = glyph_width + (state.char_spacing + word_spacing) * state.scale;
+
+        let matrix = multiply(text_matrix, ctm);
+        let (bottom, top) = (
+            state.rise - DESCENT * state.font_size,
+            state.rise + (1.0 - DESCENT) * state.font_size,
+        );
+        let corners = [(0.0, bottom), (glyph_width, bottom), (0.0, top), (glyph_width, top)];
+        let bbox = bounding_box(corners.iter().map(|&(x, y)| transform(&matrix, x, y)));
+        let size = state.font_size * matrix[2].hypot(matrix[3]);
+        *text_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, advance, 0.0], text_matrix);
+
+        if text.trim().is_empty() {
+            words.extend(word.take());
+            continue;
+        }
+        match word {
+            Some(current) if current.continues_with(&bbox, size) => {
+                current.text.push_str(&text);
+                current.bbox = bounding_box([current.bbox, bbox].iter().flat_map(|b| [(b[0], b[1]), (b[2], b[3])]));
+            }
+            _ => {
+                words.extend(word.take());
+                *word = Some(Word { text, bbox, size });
+            }
+        }
+    }
+}
+
+/// A word with its bounding box in default user space.
+#[derive(Debug, Clone)]
+struct Word {
+    text: String,
+    bbox: [f32; 4],
+    /// The font size in default user space
+    size: f32,
+}
+
+impl Word {
+    fn center(&self) -> (f32, f32) {
+        ((self.bbox[0] + self.bbox[2]) / 2.0, (self.bbox[1] + self.bbox[3]) / 2.0)
+    }
+
+    /// Whether a glyph at `bbox` continues the word on the same line, without a gap.
+    fn continues_with(&self, bbox: &[f32; 4], size: f32) -> bool {
+        let gap = bbox[0] - self.bbox[2];
+        let center = (bbox[1] + bbox[3]) / 2.0;
+        (-WORD_GAP * size..=WORD_GAP * size).contains(&gap) && (center - self.center().1).abs() < size / 2.0
+    }
+}
+
+/// Group words into lines from the top, each from the left.
+fn lines<'a>(words: impl Iterator<Item = &'a Word>) -> Vec<Vec<&'a Word>> {
+    let mut words: Vec<&Word> = words.collect();
+    words.sort_by(|a, b| b.center().1.total_cmp(&a.center().1));
+    let mut lines: Vec<Vec<&Word>> = Vec::new();
+    for word in words {
+        match lines.last_mut() {
+            Some(line) if (line[0].center().1 - word.center().1).abs() < line[0].size.max(word.size) / 2.0 => {
+                line.push(word);
+            }
+            _ => lines.push(vec![word]),
+        }
+    }
+    for line in &mut lines {
+        line.sort_by(|a, b| a.bbox[0].total_cmp(&b.bbox[0]));
+    }
+    lines
+}
+
+/// A horizontal or vertical ruling line at `position` on the other axis, from `start` to `end`.
+#[derive(Debug, Clone, Copy)]
+struct Ruling {
+    position: f32,
+    start: f32,
+    end: f32,
+}
+
+impl Ruling {
+    fn new(position: f32, a: f32, b: f32) -> Self {
+        Ruling {
+            position,
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    /// Whether the ruling covers `start` to `end`.
+    fn covers(&self, position: f32, start: f32, end: f32, tolerance: f32) -> bool {
+        (self.position - position).abs() <= tolerance && self.start <= start + tolerance && self.end >= end - tolerance
+    }
+}
+
+#[derive(Debug, Default)]
+struct PageGraphics {
+    words: Vec<Word>,
+    horizontal: Vec<Ruling>,
+    vertical: Vec<Ruling>,
+}
+
+impl PageGraphics {
+    /// Add the axis-aligned segments of a stroked subpath as rulings.
+    fn stroke(&mut self, subpath: &[(f32, f32)], tolerance: f32) {
+        for segment in subpath.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            if (y1 - y0).abs() <= tolerance && (x1 - x0).abs() > tolerance {
+                self.horizontal.push(Ruling::new((y0 + y1) / 2.0, x0, x1));
+            } else if (x1 - x0).abs() <= tolerance && (y1 - y0).abs() > tolerance {
+                self.vertical.push(Ruling::new((x0 + x1) / 2.0, y0, y1));
+            }
+        }
+    }
+
+    /// Add a filled subpath as ruling if it's a thin rectangle.
+    fn fill(&mut self, subpath: &[(f32, f32)], max_thickness: f32) {
+        if !(4..=5).contains(&subpath.len()) {
+            return;
+        }
+        let [x0, y0, x1, y1] = bounding_box(subpath.iter().copied());
+        if y1 - y0 <= max_thickness && x1 - x0 > max_thickness {
+            self.horizontal.push(Ruling::new((y0 + y1) / 2.0, x0, x1));
+        } else if x1 - x0 <= max_thickness && y1 - y0 > max_thickness {
+            self.vertical.push(Ruling::new((x0 + x1) / 2.0, y0, y1));
+        }
+    }
+}
+
+/// The current path, as subpaths of points in default user space.
+#[derive(Debug, Default)]
+struct Path {
+    closed: Vec<Vec<(f32, f32)>>,
+    current: Vec<(f32, f32)>,
+}
+
+impl Path {
+    fn move_to(&mut self, point: (f32, f32)) {
+        if self.current.len() > 1 {
+            self.closed.push(std::mem::take(&mut self.current));
+        }
+        self.current = vec![point];
+    }
+
+    /// Add a closed rectangle subpath; a new subpath starts at its first corner.
+    fn rectangle(&mut self, corners: Vec<(f32, f32)>) {
+        self.move_to(corners[0]);
+        self.closed.push(corners);
+    }
+
+    fn line_to(&mut self, point: (f32, f32)) {
+        self.current.push(point);
+    }
+
+    fn close(&mut self) {
+        if let Some(&first) = self.current.first() {
+            self.current.push(first);
+            self.closed.push(std::mem::take(&mut self.current));
+            self.current.push(first);
+        }
+    }
+
+    fn take(&mut self) -> Vec<Vec<(f32, f32)>> {
+        let mut subpaths = std::mem::take(&mut self.closed);
+        if self.current.len() > 1 {
+            subpaths.push(std::mem::take(&mut self.current));
+        }
+        self.current.clear();
+        subpaths
+    }
+}
+
+/// Tables made of the grids of ruling lines meeting each other.
+fn ruled_tables(graphics: &PageGraphics, tolerance: f32) -> Vec<Table> {
+    let horizontal = merge_rulings(&graphics.horizontal, tolerance);
+    let vertical = merge_rulings(&graphics.vertical, tolerance);
+
+    // Group the rulings meeting each other, horizontal ones first.
+    let mut groups = UnionFind::new(horizontal.len() + vertical.len());
+    for (h, across) in horizontal.iter().enumerate() {
+        for (v, down) in vertical.iter().enumerate() {
+            let meets = (across.start - tolerance..=across.end + tolerance).contains(&down.position)
+                && (down.start - tolerance..=down.end + tolerance).contains(&across.position);
+            if meets {
+                groups.union(h, horizontal.len() + v);
+            }
+        }
+    }
+
+    let mut tables = Vec::new();
+    for members in groups.sets() {
+        let (h, v): (Vec<usize>, Vec<usize>) = members.into_iter().partition(|&index| index < horizontal.len());
+        let members_h: Vec<Ruling> = h.into_iter().map(|index| horizontal[index]).collect();
+        let members_v: Vec<Ruling> = v.into_iter().map(|index| vertical[index - horizontal.len()]).collect();
+        if let Some(table) = grid_table(&members_h, &members_v, tolerance) {
+            tables.push(table);
+        }
+    }
+    tables.sort_by(|a, b| b.bbox[3].total_cmp(&a.bbox[3]));
+    tables
+}
+
+/// The table of the grid of `horizontal` and `vertical` rulings meeting each other.
+fn grid_table(horizontal: &[Ruling], vertical: &[Ruling], tolerance: f32) -> Option<Table> {
+    let xs = positions(vertical, tolerance);
+    let mut ys = positions(horizontal, tolerance);
+    ys.reverse();
+    let (rows, columns) = (ys.len().checked_sub(1)?, xs.len().checked_sub(1)?);
+    if rows * columns < 2 {
+        return None;
+    }
+
+    let index = |row: usize, column: usize| row * columns + column;
+    let mut cells = UnionFind::new(rows * columns);
+    for row in 0..rows {
+        for column in 0..columns {
+            let right_open = column + 1 < columns
+                && !vertical
+                    .iter()
+                    .any(|ruling| ruling.covers(xs[column + 1], ys[row + 1], ys[row], tolerance));
+            if right_open {
+                cells.union(index(row, column), index(row, column + 1));
+            }
+            let bottom_open = row + 1 < rows
+                && !horizontal
+                    .iter()
+                    .any(|ruling| ruling.covers(ys[row + 1], xs[column], xs[column + 1], tolerance));
+            if bottom_open {
+                cells.union(index(row, column), index(row + 1, column));
+            }
+        }
+    }
+
+    let mut cells: Vec<TableCell> = cells
+        .sets()
+        .into_iter()
+        .map(|members| {
+            let (first_row, last_row) = min_max(members.iter().map(|index| index / columns));
+            let (first_column, last_column) = min_max(members.iter().map(|index| index % columns));
+            TableCell {
+                row: first_row,
+                column: first_column,
+                row_span: last_row - first_row + 1,
+                column_span: last_column - first_column + 1,
+                bbox: [xs[first_column], ys[last_row + 1], xs[last_column + 1], ys[first_row]],
+                text: String::new(),
+            }
+        })
+        .collect();
+    cells.sort_by_key(|cell| (cell.row, cell.column));
+    Some(Table {
+        bbox: [xs[0], ys[rows], xs[columns], ys[0]],
+        row_count: rows,
+        column_count: columns,
+        cells,
+    })
+}
+
+/// Tables of lines of words whose gaps line up in columns.
+fn borderless_tables(words: &[&Word], min_column_gap: f32) -> Vec<Table> {
+    // Split lines into segments at wide gaps, and find runs of lines with several segments.
+    let mut blocks: Vec<Vec<Vec<[f32; 4]>>> = Vec::new();
+    let mut previous: Option<(f32, f32)> = None;
+    for line in lines(words.iter().copied()) {
+        let size = line.iter().map(|word| word.size).fold(0.0, f32::max);
+        let mut segments: Vec<[f32; 4]> = Vec::new();
+        for word in &line {
+            match segments.last_mut() {
+                Some(segment) if word.bbox[0] - segment[2] <= min_column_gap * size => {
+                    *segment = bounding_box([*segment, word.bbox].iter().flat_map(|b| [(b[0], b[1]), (b[2], b[3])]));
+                }
+                _ => segments.push(word.bbox),
+            }
+        }
+        let center = line[0].center().1;
+        let adjacent = previous.is_some_and(|(above, size)| above - center <= MAX_LINE_GAP * size);
+        previous = Some((center, size));
+        if segments.len() < 2 {
+            blocks.push(Vec::new());
+        } else if adjacent && blocks.last().is_some_and(|block| !block.is_empty()) {
+            blocks.last_mut().unwrap().push(segments);
+        } else {
+            blocks.push(vec![segments]);
+        }
+    }
+
+    let mut tables = Vec::new();
+    for block in blocks.into_iter().filter(|block| block.len() >= 2) {
+        // Columns are where the segments of all lines overlap horizontally.
+        let mut spans: Vec<(f32, f32)> = block.iter().flatten().map(|segment| (segment[0], segment[2])).collect();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut columns: Vec<(f32, f32)> = Vec::new();
+        for (start, end) in spans {
+            match columns.last_mut() {
+                Some(column) if start <= column.1 => column.1 = column.1.max(end),
+                _ => columns.push((start, end)),
+            }
+        }
+        if columns.len() < 2 {
+            continue;
+        }
+        let mut xs = vec![columns[0].0];
+        xs.extend(columns.windows(2).map(|pair| (pair[0].1 + pair[1].0) / 2.0));
+        xs.push(columns[columns.len() - 1].1);
+
+        let bands: Vec<(f32, f32)> = block
+            .iter()
+            .map(|segments| {
+                let bbox = bounding_box(segments.iter().flat_map(|b| [(b[0], b[1]), (b[2], b[3])]));
+                (bbox[1], bbox[3])
+            })
+            .collect();
+        let mut ys = vec![bands[0].1];
+        ys.extend(bands.windows(2).map(|pair| (pair[0].0 + pair[1].1) / 2.0));
+        ys.push(bands[bands.len() - 1].0);
+
+        let (rows, columns) = (bands.len(), columns.len());
+        let cells = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .map(|(row, column)| TableCell {
+                row,
+                column,
+                row_span: 1,
+                column_span: 1,
+                bbox: [xs[column], ys[row + 1], xs[column + 1], ys[row]],
+                text: String::new(),
+            })
+            .collect();
+        tables.push(Table {
+            bbox: [xs[0], ys[rows], xs[columns], ys[0]],
+            row_count: rows,
+            column_count: columns,
+            cells,
+        });
+    }
+    tables
+}
+
+/// Snap rulings at nearly the same position to one position and join those that touch.
+fn merge_rulings(rulings: &[Ruling], tolerance: f32) -> Vec<Ruling> {
+    let mut rulings = rulings.to_vec();
+    rulings.sort_by(|a, b| a.position.total_cmp(&b.position));
+    let mut cluster_start = 0;
+    for index in 1..=rulings.len() {
+        if index == rulings.len() || rulings[index].position - rulings[index - 1].position > tolerance {
+            let cluster = &mut rulings[cluster_start..index];
+            let position = cluster.iter().map(|ruling| ruling.position).sum::<f32>() / cluster.len() as f32;
+            for ruling in cluster {
+                ruling.position = position;
+            }
+            cluster_start = index;
+        }
+    }
+
+    rulings.sort_by(|a, b| a.position.total_cmp(&b.position).then(a.start.total_cmp(&b.start)));
+    let mut merged: Vec<Ruling> = Vec::new();
+    for ruling in rulings {
+        match merged.last_mut() {
+            Some(last) if last.position == ruling.position && ruling.start <= last.end + tolerance => {
+                last.end = last.end.max(ruling.end);
+            }
+            _ => merged.push(ruling),
+        }
+    }
+    merged
+}
+
+/// The distinct positions of `rulings`, ascending.
+fn positions(rulings: &[Ruling], tolerance: f32) -> Vec<f32> {
+    let mut positions: Vec<f32> = rulings.iter().map(|ruling| ruling.position).collect();
+    positions.sort_by(f32::total_cmp);
+    positions.dedup_by(|a, b| *a - *b <= tolerance);
+    positions
+}
+
+fn transform(matrix: &[f32; 6], x: f32, y: f32) -> (f32, f32) {
+    (
+        x * matrix[0] + y * matrix[2] + matrix[4],
+        x * matrix[1] + y * matrix[3] + matrix[5],
+    )
+}
+
+fn bounding_box(points: impl IntoIterator<Item = (f32, f32)>) -> [f32; 4] {
+    points.into_iter().fold(
+        [f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY],
+        |[x0, y0, x1, y1], (x, y)| [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+    )
+}
+
+fn contains(bbox: &[f32; 4], (x, y): (f32, f32)) -> bool {
+    (bbox[0]..=bbox[2]).contains(&x) && (bbox[1]..=bbox[3]).contains(&y)
+}
+
+fn min_max(values: impl Iterator<Item = usize>) -> (usize, usize) {
+    values.fold((usize::MAX, 0), |(min, max), value| (min.min(value), max.max(value)))
+}
+
+/// Disjoint sets of indices.
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a.max(b)] = a.min(b);
+    }
+
+    /// The sets, each in ascending order, by their smallest index.
+    fn sets(mut self) -> Vec<Vec<usize>> {
+        let mut sets: Vec<Vec<usize>> = vec![Vec::new(); self.parents.len()];
+        for index in 0..self.parents.len() {
+            let root = self.find(index);
+            sets[root].push(index);
+        }
+        sets.retain(|set| !set.is_empty());
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document_with_texts;
+
+    const COLUMNS: [f32; 5] = [50.0, 300.0, 360.0, 460.0, 545.0];
+    const ITEMS: [[&str; 4]; 6] = [
+        ["Description", "Qty", "Unit price", "Total"],
+        ["Widget", "2", "5.00", "10.00"],
+        ["Gadget set", "3", "8.50", "25.50"],
+        ["Cable", "1", "7.25", "7.25"],
+        ["Service hours", "4", "25.00", "100.00"],
+        ["Subtotal", "", "", "142.75"],
+    ];
+
+    /// An invoice page with a line-item table of rows 20 points high from y 700 down, in Courier.
+    /// With `ruled` it's drawn with stroked horizontal lines and vertical lines as thin filled
+    /// rectangles, where the subtotal label spans the first three columns.
+    fn invoice(ruled: bool) -> Document {
+        let mut content = String::from("BT /F1 14 Tf 50 760 Td (INVOICE 2024-017) Tj ET\n");
+        for (row, cells) in ITEMS.iter().enumerate() {
+            let baseline = 700.0 - 20.0 * row as f32 - 14.0;
+            for (column, text) in cells.iter().enumerate().filter(|(_, text)| !text.is_empty()) {
+                let x = COLUMNS[column] + 4.0;
+                content += &format!("BT /F1 10 Tf {x} {baseline} Td ({text}) Tj ET\n");
+            }
+        }
+        if ruled {
+            for row in 0..=ITEMS.len() {
+                let y = 700 - 20 * row;
+                content += &format!("{} {y} m {} {y} l S\n", COLUMNS[0], COLUMNS[4]);
+            }
+            for x in COLUMNS {
+                // The subtotal row has no rulings between its first three columns.
+                let bottom = if x == 300.0 || x == 360.0 { 600.0 } else { 580.0 };
+                content += &format!("{} {bottom} 1 {} re f\n", x - 0.5, 700.0 - bottom);
+            }
+        }
+
+        let mut doc = create_document_with_texts(&[""]);
+        let page_id = doc.page_iter().next().unwrap();
+        doc.change_page_content(page_id, content.into_bytes()).unwrap();
+        doc
+    }
+
+    fn totals(table: &Table) -> Vec<String> {
+        table.rows().into_iter().map(|row| row[3].clone()).collect()
+    }
+
+    #[test]
+    fn ruled_invoice_table() {
+        let doc = invoice(true);
+        let tables = doc.extract_tables(1, &TableOptions::default()).unwrap();
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!((table.row_count, table.column_count), (6, 4));
+        assert_eq!(table.bbox, [50.0, 580.0, 545.0, 700.0]);
+        assert_eq!(totals(table), ["Total", "10.00", "25.50", "7.25", "100.00", "142.75"]);
+        assert_eq!(table.rows()[0], ITEMS[0]);
+        assert_eq!(table.rows()[4][0], "Service hours");
+
+        let price = table.cell(2, 2).unwrap();
+        assert_eq!(price.text, "8.50");
+        assert_eq!(price.bbox, [360.0, 640.0, 460.0, 660.0]);
+        let subtotal = table.cell(5, 1).unwrap();
+        assert_eq!((subtotal.row, subtotal.column), (5, 0));
+        assert_eq!((subtotal.row_span, subtotal.column_span), (1, 3));
+        assert_eq!(subtotal.text, "Subtotal");
+        assert_eq!(table.cells.len(), 6 * 4 - 2);
+    }
+
+    #[test]
+    fn borderless_invoice_table() {
+        let doc = invoice(false);
+        assert_eq!(doc.extract_tables(1, &TableOptions::default()).unwrap(), []);
+
+        let options = TableOptions {
+            borderless: true,
+            ..Default::default()
+        };
+        let tables = doc.extract_tables(1, &options).unwrap();
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!((table.row_count, table.column_count), (6, 4));
+        assert_eq!(totals(table), ["Total", "10.00", "25.50", "7.25", "100.00", "142.75"]);
+        assert_eq!(table.rows()[2], ITEMS[2]);
+        assert_eq!(table.rows()[5], ITEMS[5]);
+    }
+}
@@ -4,6 +4,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::{Document, Error, Object, Outline, Result};
+use crate::encodings::{decode_utf16_be, decode_utf16_units};
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone)]
@@ -113,24 +114,15 @@ pub fn get_toc(&self) -> Result<Toc> {
                             .push(format!("Title encoded UTF16_BE {title:?} has invalid length!"));
                         continue;
                     }
-                    let t16: Vec<u16> = title
-                        .chunks(2)
-                        .skip(1)
-                        .map(|x| ((x[0] as u16) << 8) | x[1] as u16)
-                        .collect();
-                    s = String::from_utf16_lossy(&t16);
+                    s = decode_utf16_be(&title[2..]).into_text("decoding outline title");
                 } else if title[0] == 0xff && title[1] == 0xfe {
                     if title.len() & 1 != 0 {
                         toc.errors
                             .push(format!("Title encoded UTF16_LE {title:?} has invalid length!"));
                         continue;
                     }
-                    let t16: Vec<u16> = title
-                        .chunks(2)
-                        .skip(1)
-                        .map(|x| ((x[1] as u16) << 8) | x[0] as u16)
-                        .collect();
-                    s = String::from_utf16_lossy(&t16);
+                    let units = title.chunks(2).skip(1).map(|x| u16::from_le_bytes([x[0], x[1]]));
+                    s = decode_utf16_units(units).into_text("decoding outline title");
                 } else {
                     s = String::from_utf8_lossy(&title).to_string();
                 }
@@ -0,0 +1,123 @@
+use crate::{Document, IncrementalDocument, Object, Result};
+
+impl Document {
+    /// The PDF version the document conforms to: the catalog's /Version if it's newer than the
+    /// version in the file header, which it then overrides, and the header version otherwise.
+    pub fn effective_version(&self) -> String {
+        match self.catalog_version() {
+            Some(version) if is_newer(&version, &self.version) => version,
+            _ => self.version.clone(),
+        }
+    }
+
+    /// The version declared in the catalog's /Version entry, if any.
+    pub fn catalog_version(&self) -> Option<String> {
+        let version = self.catalog().ok()?.get_deref(b"Version", self).ok()?;
+        let version = version.as_name().or_else(|_| version.as_str()).ok()?;
+        Some(String::from_utf8_lossy(version).into_owned())
+    }
+
+    /// Set the PDF version the document conforms to.
+    ///
+    /// A version newer than the header's is declared in the catalog's /Version entry, which
+    /// overrides the header also where it can't be rewritten, as in incremental updates. Otherwise
+    /// the header version is replaced and any /Version removed.
+    pub fn set_version(&mut self, version: &str) -> Result<()> {
+        if is_newer(version, &self.version) {
+            self.catalog_mut()?
+                .set("Version", Object::Name(version.as_bytes().to_vec()));
+        } else {
+            self.version = version.to_string();
+            if let Ok(catalog) = self.catalog_mut() {
+                catalog.remove(b"Version");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl IncrementalDocument {
+    /// Set the PDF version of the updated document, see [`Document::set_version`]. The catalog is
+    /// cloned to the new document version first when it needs a /Version entry.
+    pub fn set_version(&mut self, version: &str) -> Result<()> {
+        if is_newer(version, &self.new_document.version) {
+            let root = self.new_document.trailer.get(b"Root").and_then(Object::as_reference)?;
+            self.opt_clone_object_to_new_document(root)?;
+        }
+        self.new_document.set_version(version)
+    }
+}
+
+/// Whether PDF version `version`, like `1.7`, is newer than `other`.
+pub(crate) fn is_newer(version: &str, other: &str) -> bool {
+    let parse = |version: &str| {
+        let (major, minor) = version.trim().split_once('.')?;
+        Some((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?))
+    };
+    match (parse(version), parse(other)) {
+        (Some(version), Some(other)) => version > other,
+        _ => version > other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+
+    fn round_trip(doc: &mut Document) -> (Document, Vec<u8>) {
+        let mut buffer = Vec::new();
+        doc.save_with_options(&mut buffer, crate::SaveOptions::default())
+            .unwrap();
+        (Document::load_mem(&buffer).unwrap(), buffer)
+    }
+
+    #[test]
+    fn catalog_version_overrides_older_header() {
+        let mut doc = create_document();
+        doc.version = "1.4".to_string();
+        doc.catalog_mut().unwrap().set("Version", Object::Name(b"1.7".to_vec()));
+        let (loaded, _) = round_trip(&mut doc);
+        assert_eq!(loaded.version, "1.4");
+        assert_eq!(loaded.catalog_version().as_deref(), Some("1.7"));
+        assert_eq!(loaded.effective_version(), "1.7");
+
+        doc.catalog_mut().unwrap().set("Version", Object::Name(b"1.3".to_vec()));
+        let (loaded, _) = round_trip(&mut doc);
+        assert_eq!(loaded.effective_version(), "1.4");
+        assert!(is_newer("1.10", "1.9"));
+    }
+
+    #[test]
+    fn set_version_declares_newer_versions_in_catalog() {
+        let mut doc = create_document();
+        doc.version = "1.4".to_string();
+        doc.set_version("1.7").unwrap();
+        let (mut loaded, buffer) = round_trip(&mut doc);
+        assert!(buffer.starts_with(b"%PDF-1.4"));
+        assert_eq!(loaded.effective_version(), "1.7");
+
+        loaded.set_version("1.3").unwrap();
+        let (loaded, buffer) = round_trip(&mut loaded);
+        assert!(buffer.starts_with(b"%PDF-1.3"));
+        assert_eq!(loaded.catalog_version(), None);
+        assert_eq!(loaded.effective_version(), "1.3");
+    }
+
+    #[test]
+    fn set_version_in_incremental_update() {
+        let mut doc = create_document();
+        let mut original = Vec::new();
+        doc.save_to(&mut original).unwrap();
+        let previous = Document::load_mem(&original).unwrap();
+        let mut incremental = IncrementalDocument::create_from(original.clone(), previous);
+        incremental.set_version("2.0").unwrap();
+
+        let mut updated = Vec::new();
+        incremental.save_to(&mut updated).unwrap();
+        assert!(updated.starts_with(&original));
+        let loaded = Document::load_mem(&updated).unwrap();
+        assert_eq!(loaded.version, "1.5");
+        assert_eq!(loaded.effective_version(), "2.0");
+    }
+}
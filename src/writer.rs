@@ -1,34 +1,68 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{BufWriter, Result, Write};
 use std::path::Path;
 use std::vec;
 
+#[cfg(feature = "async")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
 use super::Object::*;
-use super::{Dictionary, Document, Object, Stream, StringFormat};
-use crate::{xref::*, IncrementalDocument};
+use super::{Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+use crate::progress::{self, Phase};
+use crate::{xref::*, EncryptionState, IncrementalDocument};
 
 impl Document {
     /// Save PDF document to specified file path.
     #[inline]
     pub fn save<P: AsRef<Path>>(&mut self, path: P) -> Result<File> {
         let mut file = BufWriter::new(File::create(path)?);
-        self.save_internal(&mut file)?;
+        self.save_internal(&mut file, &crate::SaveOptions::default())?;
         Ok(file.into_inner()?)
     }
 
     /// Save PDF to arbitrary target
     #[inline]
     pub fn save_to<W: Write>(&mut self, target: &mut W) -> Result<()> {
-        self.save_internal(target)
+        self.save_internal(target, &crate::SaveOptions::default())
     }
 
     /// Save PDF with custom options
     pub fn save_with_options<W: Write>(&mut self, target: &mut W, options: crate::SaveOptions) -> Result<()> {
-        if options.use_object_streams {
-            self.save_with_object_streams(target, options)
+        self.save_with_report(target, options).map(|_| ())
+    }
+
+    /// Save PDF with custom options, returning a report of what saving did besides writing, such
+    /// as compressing streams.
+    pub fn save_with_report<W: Write>(
+        &mut self, target: &mut W, options: crate::SaveOptions,
+    ) -> Result<crate::SaveReport> {
+        let report = crate::SaveReport {
+            compression: options.compress_streams.then(|| self.compress_streams()),
+        };
+        if let Some((mut prepared, options)) = self.prepared(&options)? {
+            prepared.save_internal(target, &options)?;
         } else {
-            self.save_internal(target)
+            self.save_internal(target, &options)?;
+        }
+        Ok(report)
+    }
+
+    /// Save PDF with custom options to an async target, writing the same bytes as
+    /// [`Document::save_with_options`]. The task yields to the executor after each object, and
+    /// the target is flushed at the end.
+    #[cfg(feature = "async")]
+    pub async fn save_to_async<W: AsyncWrite + Unpin>(
+        &mut self, mut target: W, options: crate::SaveOptions,
+    ) -> Result<()> {
+        if options.compress_streams {
+            self.compress_streams();
+        }
+        if let Some((mut prepared, options)) = self.prepared(&options)? {
+            return prepared.emitter(&options)?.write_to_async(&mut target).await;
         }
+        self.emitter(&options)?.write_to_async(&mut target).await
     }
 
     /// Save PDF with modern features (object streams and cross-reference streams)
@@ -41,59 +75,128 @@ pub fn save_modern<W: Write>(&mut self, target: &mut W) -> Result<()> {
         self.save_with_options(target, options)
     }
 
-    fn save_internal<W: Write>(&mut self, target: &mut W) -> Result<()> {
-        let mut target = CountingWrite {
-            inner: target,
-            bytes_written: 0,
+    /// The copy of the document to save with `options` in its place, with compacted ids or
+    /// encrypted, and the options for saving the copy. `None` if the document is saved as it is.
+    fn prepared(&self, options: &crate::SaveOptions) -> Result<Option<(Document, crate::SaveOptions)>> {
+        let encryption = self.saved_encryption(options)?;
+        if !options.compact_ids && encryption.is_none() {
+            return Ok(None);
+        }
+        let (mut copy, mut options) = if options.compact_ids {
+            self.compacted(options.clone())
+        } else {
+            (self.clone(), options.clone())
         };
+        options.encryption = None;
+        options.preserve_encryption = false;
+        // Encrypted after compacting, as the keys of objects are derived from their ids.
+        if let Some(state) = encryption {
+            if let Some(file_id) = &state.file_id {
+                copy.restore_file_id(file_id);
+            }
+            copy.encrypt(&state).map_err(std::io::Error::other)?;
+        }
+        Ok(Some((copy, options)))
+    }
 
-        let mut xref = Xref::new(self.max_id + 1, self.reference_table.cross_reference_type);
-        writeln!(target, "%PDF-{}", self.version)?;
-
-        Writer::write_binary_mark(&mut target, &self.binary_mark)?;
+    /// Put `file_id` back as the first element of the trailer /ID, should it have changed since
+    /// the encryption keys were derived from it, so that the saved file can be decrypted again.
+    fn restore_file_id(&mut self, file_id: &[u8]) {
+        let current = self.trailer.get(b"ID").and_then(Object::as_array).ok();
+        if current.and_then(|id| id.first()).and_then(|id| id.as_str().ok()) == Some(file_id) {
+            return;
+        }
+        let first = Object::String(file_id.to_vec(), StringFormat::Hexadecimal);
+        let second = current.and_then(|id| id.get(1)).cloned().unwrap_or_else(|| first.clone());
+        self.trailer.set("ID", vec![first, second]);
+    }
 
-        for (&(id, generation), object) in &self.objects {
-            if object
-                .type_name()
-                .map(|name| [b"ObjStm".as_slice(), b"XRef".as_slice(), b"Linearized".as_slice()].contains(&name))
-                .ok()
-                != Some(true)
-            {
-                Writer::write_indirect_object(&mut target, id, generation, object, &mut xref)?;
-            }
+    /// The encryption of the file saved with `options`: the one they set, or the one the document
+    /// was decrypted with if they preserve it. A document still encrypted is written as it is.
+    fn saved_encryption(&self, options: &crate::SaveOptions) -> Result<Option<EncryptionState>> {
+        if let Some(state) = &options.encryption {
+            return Ok(Some(state.clone()));
+        }
+        if !options.preserve_encryption || self.is_encrypted() {
+            return Ok(None);
         }
+        let Some(state) = &self.encryption_state else {
+            return Ok(None);
+        };
+        state.check_writable().map_err(std::io::Error::other)?;
+        Ok(Some(state.clone()))
+    }
 
-        let xref_start = target.bytes_written;
+    /// A copy of the document with compacted ids, and `options` for saving it.
+    fn compacted(&self, options: crate::SaveOptions) -> (Document, crate::SaveOptions) {
+        let mut compacted = self.clone();
+        compacted.compact_ids();
+        let options = crate::SaveOptions {
+            compact_ids: false,
+            ..options
+        };
+        (compacted, options)
+    }
 
-        // Pick right cross reference stream.
-        match xref.cross_reference_type {
-            XrefType::CrossReferenceTable => {
-                Writer::write_xref(&mut target, &xref)?;
-                self.write_trailer(&mut target)?;
-            }
-            XrefType::CrossReferenceStream => {
-                // Cross Reference Stream instead of XRef and Trailer
-                self.write_cross_reference_stream(&mut target, &mut xref, xref_start as u32)?;
-            }
+    /// Renumber the objects from 1 without gaps, dropping the object streams, cross-reference
+    /// streams and linearization dictionaries which aren't written anyway.
+    fn compact_ids(&mut self) {
+        self.objects.retain(|_, object| !is_regenerated(object));
+        self.renumber_objects();
+    }
+
+    fn save_internal<W: Write>(&mut self, target: &mut W, options: &crate::SaveOptions) -> Result<()> {
+        self.emitter(options)?.write_to(target)
+    }
+
+    /// The emitter of the file saved with `options`, which must not compact ids.
+    fn emitter<'a>(&'a mut self, options: &'a crate::SaveOptions) -> Result<Emitter<'a>> {
+        if options.use_object_streams {
+            return self.object_stream_emitter(options);
+        }
+        // The document keeps the cross-reference type it was loaded with.
+        let xref_type = if options.use_xref_streams {
+            XrefType::CrossReferenceStream
+        } else {
+            self.reference_table.cross_reference_type
+        };
+        self.warn_undeclared_extensions();
+        if options.strict_output {
+            self.refuse_output_fallbacks()?;
         }
-        // Write `startxref` part of trailer
-        write!(target, "\nstartxref\n{xref_start}\n%%EOF")?;
 
-        Ok(())
+        let pending = self
+            .objects
+            .iter()
+            .enumerate()
+            .map(|(index, (&id, object))| {
+                let pending = if is_regenerated(object) {
+                    Pending::Skipped
+                } else {
+                    Pending::Stored(id)
+                };
+                (pending, index as u64 + 1)
+            })
+            .collect();
+        let total = Some(self.objects.len() as u64);
+        let xref = Xref::new(self.max_id + 1, xref_type);
+        Ok(Emitter::new(self, options, &[], xref, pending, total))
     }
 
-    /// Save PDF with object streams enabled
-    fn save_with_object_streams<W: Write>(&mut self, target: &mut W, options: crate::SaveOptions) -> Result<()> {
+    /// The emitter of the file saved with object streams
+    fn object_stream_emitter<'a>(&'a mut self, options: &'a crate::SaveOptions) -> Result<Emitter<'a>> {
         use crate::ObjectStream;
         use std::collections::HashMap;
-        
-        let mut target = CountingWrite {
-            inner: target,
-            bytes_written: 0,
-        };
+
+        self.warn_undeclared_extensions();
+        // Before `max_id` is raised below, so objects above the /Size are refused as when saving
+        // without object streams.
+        if options.strict_output {
+            self.refuse_output_fallbacks()?;
+        }
 
         // Ensure PDF version is at least 1.5 (required for object streams)
-        if self.version.as_str() < "1.5" {
+        if crate::version::is_newer("1.5", &self.effective_version()) {
             self.version = "1.5".to_string();
         }
 
@@ -102,15 +205,19 @@ fn save_with_object_streams<W: Write>(&mut self, target: &mut W, options: crate:
             self.reference_table.cross_reference_type = XrefType::CrossReferenceStream;
         }
 
-        let mut xref = Xref::new(self.max_id + 1, self.reference_table.cross_reference_type);
-        writeln!(target, "%PDF-{}", self.version)?;
-        Writer::write_binary_mark(&mut target, &self.binary_mark)?;
+        // New object streams are numbered after `max_id`, which is stale when objects were inserted
+        // into `objects` directly. Reusing such an id would make the xref entry of e.g. an
+        // appearance stream point at the object stream instead.
+        if let Some(&(highest_id, _)) = self.objects.keys().next_back() {
+            self.max_id = self.max_id.max(highest_id);
+        }
+
+        let xref = Xref::new(self.max_id + 1, self.reference_table.cross_reference_type);
 
         // Organize objects into streams
         let mut object_streams: Vec<crate::ObjectStream> = Vec::new();
         let mut objects_to_write_directly = Vec::new();
         let mut object_to_stream_map = HashMap::new();
-
         // Categorize objects
         for (&(id, generation), object) in &self.objects {
             // Skip existing object streams - we'll create new ones
@@ -144,55 +251,30 @@ fn save_with_object_streams<W: Write>(&mut self, target: &mut W, options: crate:
                 object_to_stream_map.insert((id, generation), stream_index);
             } else {
                 // Object must be written directly
-                objects_to_write_directly.push(((id, generation), object));
+                objects_to_write_directly.push((id, generation));
             }
         }
 
-        // Write direct objects first
-        for ((id, generation), object) in objects_to_write_directly {
-            Writer::write_indirect_object(&mut target, id, generation, object, &mut xref)?;
+        // Write direct objects first, then the object streams
+        let total = Some((objects_to_write_directly.len() + object_to_stream_map.len()) as u64);
+        let mut written = 0;
+        let mut pending = VecDeque::new();
+        for id in objects_to_write_directly {
+            written += 1;
+            pending.push_back((Pending::Stored(id), written));
         }
-
-        // Write object streams
         let mut stream_count = 0;
         for obj_stream in object_streams.into_iter() {
             let stream_id = self.max_id + 1 + stream_count;
-            let stream_obj = obj_stream.to_stream_object().map_err(std::io::Error::other)?;
-            
-            // Record compressed objects in xref
-            // Must use the same sort order as build_stream_content()
-            let mut sorted_objects: Vec<_> = obj_stream.objects.keys().cloned().collect();
-            sorted_objects.sort_by_key(|id| *id);
-            for (index_in_stream, (obj_id, _gen)) in sorted_objects.iter().enumerate() {
-                xref.insert(*obj_id, XrefEntry::Compressed {
-                    container: stream_id,
-                    index: index_in_stream as u16,
-                });
-            }
-            
-            // Write the object stream
-            Writer::write_indirect_object(&mut target, stream_id, 0, &Object::Stream(stream_obj), &mut xref)?;
+            written += obj_stream.object_count() as u64;
+            pending.push_back((Pending::ObjectStream(stream_id, obj_stream), written));
             stream_count += 1;
         }
 
         // Update max_id to account for object streams
         self.max_id += stream_count;
 
-        let xref_start = target.bytes_written;
-
-        // Write cross-reference
-        match xref.cross_reference_type {
-            XrefType::CrossReferenceTable => {
-                Writer::write_xref(&mut target, &xref)?;
-                self.write_trailer(&mut target)?;
-            }
-            XrefType::CrossReferenceStream => {
-                self.write_cross_reference_stream(&mut target, &mut xref, xref_start as u32)?;
-            }
-        }
-
-        write!(target, "\nstartxref\n{xref_start}\n%%EOF")?;
-        Ok(())
+        Ok(Emitter::new(self, options, &[], xref, pending, total))
     }
 
     /// Write the Cross Reference Stream.
@@ -200,7 +282,7 @@ fn save_with_object_streams<W: Write>(&mut self, target: &mut W, options: crate:
     /// Insert an `Object` to the end of the PDF (not visible when inspecting `Document`).
     /// Note: This is different from the "Cross Reference Table".
     fn write_cross_reference_stream<W: Write>(
-        &mut self, file: &mut CountingWrite<&mut W>, xref: &mut Xref, xref_start: u32,
+        &mut self, file: &mut CountingWrite<&mut W>, xref: &mut Xref, xref_start: u64, options: &crate::SaveOptions,
     ) -> Result<()> {
         // Increment max_id to account for CRS.
         self.max_id += 1;
@@ -216,17 +298,31 @@ fn write_cross_reference_stream<W: Write>(
         // Update `max_id` in trailer
         self.trailer.set("Size", i64::from(self.max_id + 1));
         // Set the size of each entry in bytes (default for PDFs is `[1 2 1]`)
-        // In our case we use `[u8, u32, u16]` for each entry
+        // Unless overridden we use `[u8, u32, u16]` for each entry
         // to keep things simple and working at all times.
-        self.trailer.set("W", Array(vec![Integer(1), Integer(4), Integer(2)]));
+        let widths = options
+            .xref_stream_widths
+            .unwrap_or_else(|| Writer::default_xref_stream_widths(xref));
+        self.trailer.set("W", Array(widths.iter().map(|&width| Integer(width as i64)).collect()));
         // Note that `ASCIIHexDecode` does not work correctly,
         // but is still useful for debugging sometimes.
         let filter = XRefStreamFilter::None;
-        let (stream, stream_length, indexes) = Writer::create_xref_steam(xref, filter)?;
+        let (mut stream, mut stream_length, indexes) =
+            Writer::create_xref_steam(xref, filter, widths, options.xref_stream_free_entries)?;
         self.trailer.set("Index", indexes);
+        self.trailer.remove(b"DecodeParms");
 
         if filter == XRefStreamFilter::ASCIIHexDecode {
             self.trailer.set("Filter", Name(b"ASCIIHexDecode".to_vec()));
+        } else if let Some(predictor) = options.xref_stream_predictor {
+            let columns = widths.iter().sum::<usize>();
+            stream = Writer::predict_xref_stream(&stream, predictor, columns)?;
+            stream_length = stream.len();
+            self.trailer.set("Filter", Name(b"FlateDecode".to_vec()));
+            self.trailer.set(
+                "DecodeParms",
+                dictionary! { "Predictor" => i64::from(predictor), "Columns" => columns as i64 },
+            );
         } else {
             self.trailer.remove(b"Filter");
         }
@@ -257,11 +353,12 @@ fn write_trailer(&mut self, file: &mut dyn Write) -> Result<()> {
 
 impl IncrementalDocument {
     /// Save PDF document to specified file path.
+    ///
+    /// The file is written to a temporary file first, so saving over the file the document was
+    /// loaded from leaves it intact when saving fails.
     #[inline]
     pub fn save<P: AsRef<Path>>(&mut self, path: P) -> Result<File> {
-        let mut file = BufWriter::new(File::create(path)?);
-        self.save_internal(&mut file)?;
-        Ok(file.into_inner()?)
+        crate::save_atomic::write_atomic(path.as_ref(), false, |file| self.save_internal(file))
     }
 
     /// Save PDF to arbitrary target
@@ -270,63 +367,229 @@ pub fn save_to<W: Write>(&mut self, target: &mut W) -> Result<()> {
         self.save_internal(target)
     }
 
-    fn save_internal<W: Write>(&mut self, target: &mut W) -> Result<()> {
-        let mut target = CountingWrite {
-            inner: target,
-            bytes_written: 0,
-        };
+    /// Save PDF to an async target, writing the same bytes as [`IncrementalDocument::save_to`].
+    /// The task yields to the executor after each object, and the target is flushed at the end.
+    #[cfg(feature = "async")]
+    pub async fn save_to_async<W: AsyncWrite + Unpin>(&mut self, mut target: W) -> Result<()> {
+        let options = crate::SaveOptions::default();
+        self.emitter(&options).write_to_async(&mut target).await
+    }
 
-        // Write previous document versions.
-        let prev_document_bytes = self.get_prev_documents_bytes();
-        target.inner.write_all(prev_document_bytes)?;
-        target.bytes_written += prev_document_bytes.len();
+    fn save_internal<W: Write>(&mut self, target: &mut W) -> Result<()> {
+        let options = crate::SaveOptions::default();
+        self.emitter(&options).write_to(target)
+    }
 
-        // Write/Append new document version.
-        let mut xref = Xref::new(
+    /// The emitter of the previous document versions followed by the new one.
+    fn emitter<'a>(&'a mut self, options: &'a crate::SaveOptions) -> Emitter<'a> {
+        self.warn_certification_violations();
+        let xref = Xref::new(
             self.new_document.max_id + 1,
             self.get_prev_documents().reference_table.cross_reference_type,
         );
+        let (previous, new_document) = self.parts_mut();
+        let pending = new_document
+            .objects
+            .iter()
+            .map(|(&id, object)| {
+                let pending = if is_regenerated(object) {
+                    Pending::Skipped
+                } else {
+                    Pending::Stored(id)
+                };
+                (pending, 0)
+            })
+            .collect();
+        Emitter::new(new_document, options, previous, xref, pending, None)
+    }
+}
 
-        if let Some(last_byte) = prev_document_bytes.last() {
-            if *last_byte != b'\n' {
-                // Add a newline if it was not already present
-                writeln!(target)?;
-            }
-        }
-        writeln!(target, "%PDF-{}", self.new_document.version)?;
+/// Whether `object` is made anew on saving instead of written as is, like object streams,
+/// cross-reference streams and linearization dictionaries.
+fn is_regenerated(object: &Object) -> bool {
+    object
+        .type_name()
+        .map(|name| [b"ObjStm".as_slice(), b"XRef".as_slice(), b"Linearized".as_slice()].contains(&name))
+        .ok()
+        == Some(true)
+}
 
-        Writer::write_binary_mark(&mut target, &self.new_document.binary_mark)?;
+/// An indirect object for [`Emitter`] to write.
+enum Pending {
+    /// An object of the document
+    Stored(ObjectId),
+    /// An object of the document left out of the file
+    Skipped,
+    /// A new object stream with its object number
+    ObjectStream(u32, crate::ObjectStream),
+}
 
-        for (&(id, generation), object) in &self.new_document.objects {
-            if object
-                .type_name()
-                .map(|name| [b"ObjStm".as_slice(), b"XRef".as_slice(), b"Linearized".as_slice()].contains(&name))
-                .ok()
-                != Some(true)
-            {
-                Writer::write_indirect_object(&mut target, id, generation, object, &mut xref)?;
-            }
-        }
+/// The part of the file [`Emitter`] writes next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Previous,
+    Header,
+    Objects,
+    Tail,
+    Done,
+}
 
-        let xref_start = target.bytes_written;
+/// Serializes a document into the chunks of the saved file without doing any I/O: the previous
+/// versions for incremental updates, the header, one chunk per indirect object, and the
+/// cross-reference section with the trailer. Sync and async saving only write out the chunks.
+pub(crate) struct Emitter<'a> {
+    document: &'a mut Document,
+    options: &'a crate::SaveOptions,
+    /// The previous document versions an incremental update is appended to
+    previous: &'a [u8],
+    xref: Xref,
+    /// The objects to write, each with the progress to report once written
+    pending: VecDeque<(Pending, u64)>,
+    total: Option<u64>,
+    /// The number of bytes emitted so far
+    offset: usize,
+    stage: Stage,
+}
 
-        // Pick right cross reference stream.
-        match xref.cross_reference_type {
-            XrefType::CrossReferenceTable => {
-                Writer::write_xref(&mut target, &xref)?;
-                self.new_document.write_trailer(&mut target)?;
-            }
-            XrefType::CrossReferenceStream => {
-                // Cross Reference Stream instead of XRef and Trailer
-                self.new_document
-                    .write_cross_reference_stream(&mut target, &mut xref, xref_start as u32)?;
+impl<'a> Emitter<'a> {
+    fn new(
+        document: &'a mut Document, options: &'a crate::SaveOptions, previous: &'a [u8], xref: Xref,
+        pending: VecDeque<(Pending, u64)>, total: Option<u64>,
+    ) -> Self {
+        Emitter {
+            document,
+            options,
+            previous,
+            xref,
+            pending,
+            total,
+            offset: 0,
+            stage: Stage::Previous,
+        }
+    }
+
+    /// The next chunk of the file, or `None` when it's complete.
+    pub(crate) fn next_chunk(&mut self) -> Result<Option<Cow<'a, [u8]>>> {
+        // Stages and objects that write nothing are passed over, until one does.
+        loop {
+            let mut chunk = Vec::new();
+            let mut file = CountingWrite {
+                inner: &mut chunk,
+                bytes_written: self.offset,
+            };
+            match self.stage {
+                Stage::Previous => {
+                    self.stage = Stage::Header;
+                    if !self.previous.is_empty() {
+                        self.offset += self.previous.len();
+                        return Ok(Some(Cow::Borrowed(self.previous)));
+                    }
+                    continue;
+                }
+                Stage::Header => {
+                    if self.previous.last().is_some_and(|&last_byte| last_byte != b'\n') {
+                        // Add a newline if it was not already present
+                        writeln!(file)?;
+                    }
+                    writeln!(file, "%PDF-{}", self.document.version)?;
+                    Writer::write_binary_mark(&mut file, &self.document.binary_mark)?;
+                    self.stage = Stage::Objects;
+                }
+                Stage::Objects => {
+                    let Some((pending, done)) = self.pending.pop_front() else {
+                        self.stage = Stage::Tail;
+                        continue;
+                    };
+                    match pending {
+                        Pending::Stored(id) => {
+                            if let Some(object) = self.document.objects.get(&id) {
+                                Writer::write_indirect_object(&mut file, id.0, id.1, object, &mut self.xref)?;
+                            }
+                        }
+                        Pending::Skipped => {}
+                        Pending::ObjectStream(stream_id, obj_stream) => {
+                            let stream_obj = obj_stream.to_stream_object().map_err(std::io::Error::other)?;
+
+                            // Record compressed objects in xref
+                            // Must use the same sort order as build_stream_content()
+                            let mut sorted_objects: Vec<_> = obj_stream.objects.keys().cloned().collect();
+                            sorted_objects.sort_by_key(|id| *id);
+                            for (index_in_stream, (obj_id, _gen)) in sorted_objects.iter().enumerate() {
+                                self.xref.insert(
+                                    *obj_id,
+                                    XrefEntry::Compressed {
+                                        container: stream_id,
+                                        index: index_in_stream as u16,
+                                    },
+                                );
+                            }
+                            let stream_obj = Object::Stream(stream_obj);
+                            Writer::write_indirect_object(&mut file, stream_id, 0, &stream_obj, &mut self.xref)?;
+                        }
+                    }
+                    progress::report(
+                        self.options.progress.as_ref(),
+                        Phase::SerializeObjects,
+                        done,
+                        self.total,
+                    );
+                    if file.bytes_written == self.offset {
+                        continue;
+                    }
+                }
+                Stage::Tail => {
+                    let xref_start = self.offset;
+                    // Pick right cross reference stream.
+                    match self.xref.cross_reference_type {
+                        XrefType::CrossReferenceTable => {
+                            Writer::write_xref(&mut file, &self.xref)?;
+                            self.document.write_trailer(&mut file)?;
+                        }
+                        XrefType::CrossReferenceStream => {
+                            // Cross Reference Stream instead of XRef and Trailer
+                            self.document.write_cross_reference_stream(
+                                &mut file,
+                                &mut self.xref,
+                                xref_start as u64,
+                                self.options,
+                            )?;
+                        }
+                    }
+                    let xref_entries = self.xref.entries.len() as u64;
+                    progress::report(
+                        self.options.progress.as_ref(),
+                        Phase::WriteXref,
+                        xref_entries,
+                        Some(xref_entries),
+                    );
+                    // Write `startxref` part of trailer
+                    write!(file, "\nstartxref\n{xref_start}\n%%EOF")?;
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return Ok(None),
             }
+            self.offset = file.bytes_written;
+            return Ok(Some(Cow::Owned(chunk)));
         }
-        // Write `startxref` part of trailer
-        write!(target, "\nstartxref\n{xref_start}\n%%EOF")?;
+    }
 
+    /// Write the whole file to `target`.
+    fn write_to<W: Write>(mut self, target: &mut W) -> Result<()> {
+        while let Some(chunk) = self.next_chunk()? {
+            target.write_all(&chunk)?;
+        }
         Ok(())
     }
+
+    /// Write the whole file to `target`, yielding after each chunk.
+    #[cfg(feature = "async")]
+    async fn write_to_async<W: AsyncWrite + Unpin>(mut self, target: &mut W) -> Result<()> {
+        while let Some(chunk) = self.next_chunk()? {
+            target.write_all(&chunk).await?;
+            tokio::task::yield_now().await;
+        }
+        target.flush().await
+    }
 }
 
 pub struct Writer;
@@ -396,29 +659,70 @@ fn write_xref(file: &mut dyn Write, xref: &Xref) -> Result<()> {
         Ok(())
     }
 
-    /// Create stream for Cross reference stream.
-    fn create_xref_steam(xref: &Xref, filter: XRefStreamFilter) -> Result<(Vec<u8>, usize, Object)> {
-        let mut xref_sections = Vec::new();
-        let mut xref_section = XrefSection::new(0);
+    /// The field widths of cross-reference stream entries unless set in the save options: `[1 4 2]`,
+    /// with the offset field widened for files larger than 4 GiB.
+    fn default_xref_stream_widths(xref: &Xref) -> [usize; 3] {
+        let largest_offset = xref
+            .entries
+            .values()
+            .filter_map(|entry| match *entry {
+                XrefEntry::Normal { offset, .. } => Some(offset),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let offset_width = (u64::BITS - largest_offset.leading_zeros()).div_ceil(8) as usize;
+        [1, offset_width.max(4), 2]
+    }
 
-        for obj_id in 1..xref.size + 1 {
-            // If section is empty change number of starting id.
-            if xref_section.is_empty() {
-                xref_section = XrefSection::new(obj_id);
+    /// Create stream for Cross reference stream, with entries of `widths` bytes. With
+    /// `free_entries` the stream is a single section from object 0, listing unused object numbers
+    /// as free entries.
+    fn create_xref_steam(
+        xref: &Xref, filter: XRefStreamFilter, widths: [usize; 3], free_entries: bool,
+    ) -> Result<(Vec<u8>, usize, Object)> {
+        let last_id = xref.max_id().max(xref.size);
+        let mut xref_sections = Vec::new();
+        if free_entries {
+            // Object 0 heads the linked list of free objects, the last free object links back to it.
+            let mut next_free = (1..=last_id).filter(|&id| xref.get(id).is_none()).chain([0]);
+            let mut xref_section = XrefSection::new(0);
+            xref_section.add_entry(XrefEntry::Free {
+                next_free_object: next_free.next().unwrap_or(0),
+                generation: 65535,
+            });
+            for obj_id in 1..=last_id {
+                let entry = match xref.get(obj_id) {
+                    Some(entry) => entry.clone(),
+                    None => XrefEntry::Free {
+                        next_free_object: next_free.next().unwrap_or(0),
+                        generation: 0,
+                    },
+                };
+                xref_section.add_entry(entry);
             }
-            if let Some(entry) = xref.get(obj_id) {
-                xref_section.add_entry(entry.clone());
-            } else {
-                // Skip over but finish section if not empty
-                if !xref_section.is_empty() {
-                    xref_sections.push(xref_section);
+            xref_sections.push(xref_section);
+        } else {
+            let mut xref_section = XrefSection::new(0);
+            for obj_id in 1..=last_id {
+                // If section is empty change number of starting id.
+                if xref_section.is_empty() {
                     xref_section = XrefSection::new(obj_id);
                 }
+                if let Some(entry) = xref.get(obj_id) {
+                    xref_section.add_entry(entry.clone());
+                } else {
+                    // Skip over but finish section if not empty
+                    if !xref_section.is_empty() {
+                        xref_sections.push(xref_section);
+                        xref_section = XrefSection::new(obj_id);
+                    }
+                }
+            }
+            // Print last section
+            if !xref_section.is_empty() {
+                xref_sections.push(xref_section);
             }
-        }
-        // Print last section
-        if !xref_section.is_empty() {
-            xref_sections.push(xref_section);
         }
 
         let mut xref_stream = Vec::new();
@@ -429,35 +733,33 @@ fn create_xref_steam(xref: &Xref, filter: XRefStreamFilter) -> Result<(Vec<u8>,
             xref_index.push(Integer(section.starting_id as i64));
             xref_index.push(Integer(section.entries.len() as i64));
             // Add entries to stream
-            let mut obj_id = section.starting_id;
-            for entry in section.entries {
-                match entry {
+            for (obj_id, entry) in (section.starting_id..).zip(section.entries) {
+                let fields: [u64; 3] = match entry {
+                    // Type 0
                     XrefEntry::Free { next_free_object, generation } => {
-                        // Type 0
-                        xref_stream.push(0);
-                        xref_stream.extend(next_free_object.to_be_bytes());
-                        xref_stream.extend(generation.to_be_bytes());
-                    }
-                    XrefEntry::UnusableFree => {
-                        // Type 0
-                        xref_stream.push(0);
-                        xref_stream.extend(obj_id.to_be_bytes());
-                        xref_stream.extend(65535_u16.to_be_bytes());
+                        [0, next_free_object.into(), generation.into()]
                     }
-                    XrefEntry::Normal { offset, generation } => {
-                        // Type 1
-                        xref_stream.push(1);
-                        xref_stream.extend(offset.to_be_bytes());
-                        xref_stream.extend(generation.to_be_bytes());
-                    }
-                    XrefEntry::Compressed { container, index } => {
-                        // Type 2
-                        xref_stream.push(2);
-                        xref_stream.extend(container.to_be_bytes());
-                        xref_stream.extend(index.to_be_bytes());
+                    XrefEntry::UnusableFree => [0, obj_id.into(), 65535],
+                    // Type 1
+                    XrefEntry::Normal { offset, generation } => [1, offset, generation.into()],
+                    // Type 2
+                    XrefEntry::Compressed { container, index } => [2, container.into(), index.into()],
+                };
+                for ((value, width), default) in fields.into_iter().zip(widths).zip([1, 0, 0]) {
+                    // A field of width 0 holds its default value.
+                    let fits = if width == 0 {
+                        value == default
+                    } else {
+                        width >= 8 || value >> (width * 8) == 0
+                    };
+                    if !fits {
+                        return Err(std::io::Error::other(crate::Error::InvalidSaveOptions(format!(
+                            "entry of object {obj_id} doesn't fit cross-reference stream field widths {widths:?}"
+                        ))));
                     }
+                    let bytes = (0..width).rev().map(|byte| value.checked_shr(byte as u32 * 8).unwrap_or(0) as u8);
+                    xref_stream.extend(bytes);
                 }
-                obj_id += 1;
             }
         }
 
@@ -474,10 +776,44 @@ fn create_xref_steam(xref: &Xref, filter: XRefStreamFilter) -> Result<(Vec<u8>,
         Ok((xref_stream, stream_length, Array(xref_index)))
     }
 
+    /// Apply `predictor` to the rows of `columns` bytes of a cross-reference stream and compress
+    /// the result with Flate.
+    fn predict_xref_stream(content: &[u8], predictor: u8, columns: usize) -> Result<Vec<u8>> {
+        use crate::filters::{png, tiff};
+        use flate2::{write::ZlibEncoder, Compression};
+
+        let predicted = match predictor {
+            2 => {
+                let mut predicted = content.to_vec();
+                tiff::encode(&mut predicted, 1, columns);
+                predicted
+            }
+            10..=15 => {
+                let method = match predictor {
+                    10 => Some(png::FilterType::None),
+                    11 => Some(png::FilterType::Sub),
+                    12 => Some(png::FilterType::Up),
+                    13 => Some(png::FilterType::Avg),
+                    14 => Some(png::FilterType::Paeth),
+                    _ => None,
+                };
+                png::encode_frame(content, 1, columns, method)
+            }
+            _ => {
+                return Err(std::io::Error::other(crate::Error::InvalidSaveOptions(format!(
+                    "unsupported cross-reference stream predictor {predictor}"
+                ))));
+            }
+        };
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&predicted)?;
+        encoder.finish()
+    }
+
     fn write_indirect_object<W: Write>(
         file: &mut CountingWrite<&mut W>, id: u32, generation: u16, object: &Object, xref: &mut Xref,
     ) -> Result<()> {
-        let offset = file.bytes_written as u32;
+        let offset = file.bytes_written as u64;
         xref.insert(id, XrefEntry::Normal { offset, generation });
         write!(
             file,
@@ -495,6 +831,19 @@ fn write_indirect_object<W: Write>(
         Ok(())
     }
 
+    /// Number of bytes `object` takes when written as indirect object `id`.
+    pub(crate) fn indirect_object_size(id: crate::ObjectId, object: &Object) -> usize {
+        let mut buffer = Vec::new();
+        let mut file = CountingWrite {
+            inner: &mut buffer,
+            bytes_written: 0,
+        };
+        let mut xref = Xref::new(0, XrefType::CrossReferenceTable);
+        // Writing to memory can't fail.
+        let _ = Writer::write_indirect_object(&mut file, id.0, id.1, object, &mut xref);
+        file.bytes_written
+    }
+
     pub fn write_object(file: &mut dyn Write, object: &Object) -> Result<()> {
         match object {
             Null => file.write_all(b"null"),
@@ -509,6 +858,8 @@ pub fn write_object(file: &mut dyn Write, object: &Object) -> Result<()> {
                 let mut buf = itoa::Buffer::new();
                 file.write_all(buf.format(*value).as_bytes())
             }
+            // PDF has no syntax for NaN or infinities.
+            Real(value) if !value.is_finite() => file.write_all(b"0"),
             Real(value) => write!(file, "{value}"),
             Name(name) => Writer::write_name(file, name),
             String(text, format) => Writer::write_string(file, text, format),
@@ -540,37 +891,36 @@ fn write_string(file: &mut dyn Write, text: &[u8], format: &StringFormat) -> Res
             // whether the string is interpreted as single-byte or multiple-byte character codes.
             // If an end-of-line marker appears within a literal string without a preceding backslash, the result is
             // equivalent to \n. So \r also need be escaped.
+            // Balanced parentheses nested deeper than the reader accepts are escaped as well.
             StringFormat::Literal => {
-                let mut escape_indice = Vec::new();
-                let mut parentheses = Vec::new();
+                let mut escaped = vec![false; text.len()];
+                let mut open_parentheses = Vec::new();
                 for (index, &byte) in text.iter().enumerate() {
                     match byte {
-                        b'(' => parentheses.push(index),
-                        b')' => {
-                            if !parentheses.is_empty() {
-                                parentheses.pop();
-                            } else {
-                                escape_indice.push(index);
-                            }
+                        b'(' => {
+                            escaped[index] = open_parentheses.len() >= crate::reader::MAX_BRACKET;
+                            open_parentheses.push(index);
                         }
-                        b'\\' | b'\r' => escape_indice.push(index),
+                        b')' => match open_parentheses.pop() {
+                            Some(open) => escaped[index] = escaped[open],
+                            None => escaped[index] = true,
+                        },
+                        b'\\' | b'\r' => escaped[index] = true,
                         _ => continue,
                     }
                 }
-                escape_indice.append(&mut parentheses);
+                for index in open_parentheses {
+                    escaped[index] = true;
+                }
 
                 file.write_all(b"(")?;
-                if !escape_indice.is_empty() {
-                    for (index, &byte) in text.iter().enumerate() {
-                        if escape_indice.contains(&index) {
-                            file.write_all(b"\\")?;
-                            file.write_all(&[if byte == b'\r' { b'r' } else { byte }])?;
-                        } else {
-                            file.write_all(&[byte])?;
-                        }
+                for (&byte, escaped) in text.iter().zip(escaped) {
+                    if escaped {
+                        file.write_all(b"\\")?;
+                        file.write_all(&[if byte == b'\r' { b'r' } else { byte }])?;
+                    } else {
+                        file.write_all(&[byte])?;
                     }
-                } else {
-                    file.write_all(text)?;
                 }
                 file.write_all(b")")?;
             }
@@ -670,6 +1020,34 @@ fn flush(&mut self) -> Result<()> {
     }
 }
 
+#[test]
+fn literal_strings_round_trip() {
+    let deep = crate::reader::MAX_BRACKET;
+    let texts: Vec<Vec<u8>> = vec![
+        b"plain".to_vec(),
+        b"balanced (parentheses) stay".to_vec(),
+        b"unbalanced ( and ) and )(".to_vec(),
+        b"back\\slash \\n not a newline".to_vec(),
+        b"line\r\nbreaks\rand\nmore".to_vec(),
+        b"\\\n".to_vec(),
+        b"octal-looking \\053 and bytes \x00\xFF".to_vec(),
+        [b"(".repeat(deep), b")".repeat(deep)].concat(),
+        [b"(".repeat(deep + 5), b")".repeat(deep + 5)].concat(),
+        [b"(".repeat(deep + 5), b")".repeat(2)].concat(),
+    ];
+    for text in texts {
+        let mut bytes = Vec::new();
+        Writer::write_string(&mut bytes, &text, &StringFormat::Literal).unwrap();
+        let parsed = crate::parser::direct_object(crate::parser::new_input(&bytes));
+        assert_eq!(
+            parsed.as_ref().and_then(|object| object.as_str().ok()),
+            Some(text.as_slice()),
+            "{}",
+            std::string::String::from_utf8_lossy(&bytes)
+        );
+    }
+}
+
 #[test]
 fn save_document() {
     let mut doc = Document::with_version("1.5");
@@ -707,3 +1085,175 @@ fn save_document() {
     // Check if the file is above 400 bytes (should be about 610 bytes)
     assert!(file_path.metadata().unwrap().len() > 400);
 }
+
+/// Save the test document with cross-reference stream `options`, returning the reloaded document,
+/// the dictionary of its cross-reference stream and the stream's decoded entries.
+#[cfg(test)]
+fn save_with_xref_stream(options: crate::SaveOptionsBuilder) -> (Document, Dictionary, Vec<u8>) {
+    let mut doc = crate::creator::tests::create_document();
+    let mut buffer = Vec::new();
+    doc.save_with_options(&mut buffer, options.use_xref_streams(true).build().unwrap())
+        .unwrap();
+    let reloaded = Document::load_mem(&buffer).unwrap();
+
+    let xref_start = buffer.windows(9).rposition(|window| window == b"startxref").unwrap();
+    let start = buffer[..xref_start].windows(7).rposition(|window| window == b"stream\n").unwrap() + 7;
+    let end = buffer[..xref_start].windows(10).rposition(|window| window == b"\nendstream").unwrap();
+    let dict_start = buffer[..start].windows(4).rposition(|window| window == b"obj\n").unwrap() + 4;
+    let input = crate::parser::new_input(&buffer[dict_start..start]);
+    let (_, dict) = crate::parser::dictionary(input).unwrap();
+    let stream = Stream::new(dict, buffer[start..end].to_vec());
+    let entries = if stream.is_compressed() {
+        stream.decompressed_content().unwrap()
+    } else {
+        stream.content
+    };
+    (reloaded, stream.dict, entries)
+}
+
+#[test]
+fn predicted_xref_streams_round_trip() {
+    let (_, unpredicted, entries) = save_with_xref_stream(crate::SaveOptions::builder());
+    assert!(unpredicted.get(b"DecodeParms").is_err());
+    for predictor in [2, 10, 11, 12, 13, 14, 15] {
+        let (doc, dict, predicted_entries) =
+            save_with_xref_stream(crate::SaveOptions::builder().xref_stream_predictor(Some(predictor)));
+        assert_eq!(predicted_entries, entries, "predictor {predictor}");
+        let params = dict.get(b"DecodeParms").and_then(Object::as_dict).unwrap();
+        assert_eq!(params.get(b"Predictor").and_then(Object::as_i64).unwrap(), i64::from(predictor));
+        assert_eq!(params.get(b"Columns").and_then(Object::as_i64).unwrap(), 7);
+        assert_eq!(doc.get_pages().len(), 1);
+    }
+}
+
+#[test]
+fn xref_stream_field_widths() {
+    let (doc, dict, entries) = save_with_xref_stream(
+        crate::SaveOptions::builder()
+            .xref_stream_widths(Some([1, 3, 1]))
+            .xref_stream_predictor(Some(12)),
+    );
+    let widths = dict.get(b"W").and_then(Object::as_array).unwrap();
+    assert_eq!(widths, &vec![Integer(1), Integer(3), Integer(1)]);
+    assert_eq!(entries.len() % 5, 0);
+    assert_eq!(doc.get_pages().len(), 1);
+
+    let mut doc = crate::creator::tests::create_document();
+    let options = crate::SaveOptions::builder()
+        .use_xref_streams(true)
+        .xref_stream_widths(Some([1, 1, 0]))
+        .build()
+        .unwrap();
+    let err = doc.save_with_options(&mut Vec::new(), options).unwrap_err();
+    assert!(matches!(
+        err.get_ref().and_then(|err| err.downcast_ref()),
+        Some(crate::Error::InvalidSaveOptions(_))
+    ));
+}
+
+#[test]
+fn xref_stream_free_entries() {
+    let (doc, _, _) = save_with_xref_stream(crate::SaveOptions::builder());
+    assert!(doc.reference_table.get(0).is_none());
+
+    let (doc, dict, entries) = save_with_xref_stream(crate::SaveOptions::builder().xref_stream_free_entries(true));
+    let size = dict.get(b"Size").and_then(Object::as_i64).unwrap();
+    let index = dict.get(b"Index").and_then(Object::as_array).unwrap();
+    assert_eq!(index, &vec![Integer(0), Integer(size)]);
+    assert_eq!(entries.len() as i64, size * 7);
+    assert!(matches!(
+        doc.reference_table.get(0),
+        Some(XrefEntry::Free { generation: 65535, .. })
+    ));
+    assert_eq!(doc.get_pages().len(), 1);
+}
+
+#[test]
+fn saving_with_xref_streams_leaves_the_document_alone() {
+    let mut doc = crate::creator::tests::create_document();
+    doc.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    let mut bytes = Vec::new();
+    let options = crate::SaveOptions::builder().use_xref_streams(true).build().unwrap();
+    doc.save_with_options(&mut bytes, options).unwrap();
+    assert_eq!(doc.reference_table.cross_reference_type, XrefType::CrossReferenceTable);
+    let saved = Document::load_mem(&bytes).unwrap();
+    assert_eq!(saved.reference_table.cross_reference_type, XrefType::CrossReferenceStream);
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).unwrap();
+    assert!(bytes.windows(5).any(|window| window == b"xref\n"));
+}
+
+#[test]
+fn offsets_beyond_4_gib_round_trip() {
+    let mut xref = Xref::new(3, XrefType::CrossReferenceStream);
+    xref.insert(1, XrefEntry::Normal { offset: 17, generation: 0 });
+    xref.insert(
+        2,
+        XrefEntry::Normal {
+            offset: 12_000_000_000,
+            generation: 0,
+        },
+    );
+    let widths = Writer::default_xref_stream_widths(&xref);
+    assert_eq!(widths, [1, 5, 2]);
+
+    let (content, _, index) = Writer::create_xref_steam(&xref, XRefStreamFilter::None, widths, false).unwrap();
+    assert_eq!(content.len(), 2 * 8);
+    let dict = dictionary! {
+        "Type" => "XRef",
+        "Size" => 3,
+        "W" => widths.iter().map(|&width| Integer(width as i64)).collect::<Vec<_>>(),
+        "Index" => index,
+    };
+    let (decoded, _) = crate::xref::decode_xref_stream(Stream::new(dict, content)).unwrap();
+    assert!(matches!(
+        decoded.get(2),
+        Some(XrefEntry::Normal {
+            offset: 12_000_000_000,
+            generation: 0
+        })
+    ));
+
+    // Cross-reference tables only have ten digits for offsets.
+    let err = Writer::write_xref(&mut Vec::new(), &xref).unwrap_err();
+    assert!(matches!(
+        err.get_ref().and_then(|err| err.downcast_ref()),
+        Some(crate::Error::InvalidSaveOptions(_))
+    ));
+}
+
+#[test]
+fn unsupported_xref_stream_predictor() {
+    let builder = || crate::SaveOptions::builder().use_xref_streams(true);
+    assert!(matches!(
+        builder().xref_stream_predictor(Some(5)).build(),
+        Err(crate::SaveOptionsError::UnsupportedXrefStreamPredictor(5))
+    ));
+}
+
+#[test]
+fn compact_ids_renumbers_saved_objects_densely() {
+    let mut doc = crate::creator::tests::create_document_with_texts(&["first page", "second page"]);
+    // Leave gaps as edits would: move every object far up and drop some unused ones.
+    doc.renumber_objects_with(5000);
+    for _ in 0..20 {
+        let id = doc.add_object(Object::Null);
+        doc.objects.remove(&id);
+    }
+    doc.add_object(dictionary! { "Unused" => true });
+    let live_objects = doc.objects.len();
+    // A cross-reference stream would take an object number of its own.
+    doc.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    let before = doc.objects.clone();
+
+    let mut bytes = Vec::new();
+    let options = crate::SaveOptions::builder().compact_ids(true).build().unwrap();
+    doc.save_with_options(&mut bytes, options).unwrap();
+    assert_eq!(doc.objects, before, "the document itself isn't renumbered");
+
+    let saved = Document::load_mem(&bytes).unwrap();
+    assert_eq!(saved.trailer.get(b"Size").and_then(Object::as_i64).unwrap(), live_objects as i64 + 1);
+    assert_eq!(saved.objects.keys().map(|id| id.0).max(), Some(live_objects as u32));
+    assert_eq!(saved.extract_text(&[1, 2]).unwrap(), doc.extract_text(&[1, 2]).unwrap());
+}
@@ -0,0 +1,467 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+
+use crate::{Document, Result, Stream};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const DC: &str = "http://purl.org/dc/elements/1.1/";
+const XMP: &str = "http://ns.adobe.com/xap/1.0/";
+const PDF: &str = "http://ns.adobe.com/pdf/1.3/";
+const PDFAID: &str = "http://www.aiim.org/pdfa/ns/id/";
+
+/// Namespaces of the conventional prefixes, for packets that use them without declaring them.
+const CONVENTIONAL_PREFIXES: [(&str, &str); 5] =
+    [("rdf", RDF), ("dc", DC), ("xmp", XMP), ("pdf", PDF), ("pdfaid", PDFAID)];
+
+/// The properties read, as namespace and local name, in the order of [`Properties`].
+const PROPERTIES: [(&str, &str); 7] = [
+    (DC, "title"),
+    (DC, "creator"),
+    (XMP, "CreateDate"),
+    (XMP, "ModifyDate"),
+    (PDF, "Producer"),
+    (PDFAID, "part"),
+    (PDFAID, "conformance"),
+];
+
+/// The values found for each of [`PROPERTIES`], with their `xml:lang`.
+type Properties = [Vec<(Option<String>, String)>; PROPERTIES.len()];
+
+/// The document properties of an XMP metadata packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmpMetadata {
+    /// Title (`dc:title`), in the default language if there are several
+    pub title: Option<String>,
+    /// Authors (`dc:creator`), in order
+    pub creators: Vec<String>,
+    /// Creation date (`xmp:CreateDate`), as written: an ISO 8601 date like
+    /// `2023-01-15T10:30:00+01:00`
+    pub create_date: Option<String>,
+    /// Modification date (`xmp:ModifyDate`), as written
+    pub modify_date: Option<String>,
+    /// Application that produced the document (`pdf:Producer`)
+    pub producer: Option<String>,
+    /// The PDF/A conformance the document claims, if any
+    pub pdfa: Option<PdfAIdentification>,
+}
+
+/// The PDF/A identification schema of an XMP packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdfAIdentification {
+    /// Part of ISO 19005 (`pdfaid:part`), e.g. 2 for PDF/A-2
+    pub part: u32,
+    /// Conformance level (`pdfaid:conformance`), e.g. `B`
+    pub conformance: Option<String>,
+}
+
+impl XmpMetadata {
+    /// Read the properties of the XMP packet `xml`.
+    ///
+    /// The packet is read leniently: properties may be elements or attributes of
+    /// `rdf:Description`, and whatever can't be made sense of is skipped.
+    pub fn parse(xml: &[u8]) -> XmpMetadata {
+        let xml = String::from_utf8_lossy(xml);
+        let properties = read_properties(xml.trim_start_matches('\u{FEFF}'));
+        let [title, creators, create_date, modify_date, producer, part, conformance] = properties;
+        let first = |values: Vec<(Option<String>, String)>| values.into_iter().next().map(|(_, value)| value);
+        let default_title = title.iter().position(|(lang, _)| lang.as_deref() == Some("x-default"));
+        XmpMetadata {
+            title: title.into_iter().nth(default_title.unwrap_or(0)).map(|(_, title)| title),
+            creators: creators.into_iter().map(|(_, creator)| creator).collect(),
+            create_date: first(create_date),
+            modify_date: first(modify_date),
+            producer: first(producer),
+            pdfa: first(part)
+                .and_then(|part| part.parse().ok())
+                .map(|part| PdfAIdentification {
+                    part,
+                    conformance: first(conformance),
+                }),
+        }
+    }
+}
+
+impl Document {
+    /// Get the XML of the XMP metadata stream the catalog's /Metadata refers to, or `None` if
+    /// the catalog has no /Metadata.
+    pub fn get_xmp_metadata(&self) -> Result<Option<Vec<u8>>> {
+        let Ok(metadata) = self.catalog()?.get(b"Metadata") else {
+            return Ok(None);
+        };
+        let stream = self.dereference(metadata)?.1.as_stream()?;
+        xmp_packet(stream, |stream| self.decompressed_stream_content(stream)).map(Some)
+    }
+
+    /// Get the properties of the XMP metadata stream, see [`Document::get_xmp_metadata`].
+    pub fn xmp_metadata(&self) -> Result<Option<XmpMetadata>> {
+        Ok(self.get_xmp_metadata()?.map(|xml| XmpMetadata::parse(&xml)))
+    }
+}
+
+/// The XML of metadata stream `stream`, decoded with `decode` if it has filters. The spec has
+/// metadata streams unfiltered, but some producers compress them anyway, with or without
+/// declaring /FlateDecode.
+pub(crate) fn xmp_packet(stream: &Stream, decode: impl FnOnce(&Stream) -> Result<Vec<u8>>) -> Result<Vec<u8>> {
+    if stream.dict.has(b"Filter") {
+        return decode(stream);
+    }
+    let content = &stream.content;
+    let zlib = match content.as_slice() {
+        [method, flags, ..] => method & 0x0F == 8 && u16::from_be_bytes([*method, *flags]) % 31 == 0,
+        _ => false,
+    };
+    let mut inflated = Vec::new();
+    if zlib && ZlibDecoder::new(content.as_slice()).read_to_end(&mut inflated).is_ok() {
+        return Ok(inflated);
+    }
+    Ok(content.clone())
+}
+
+/// Convert an XMP date, like `2023-01-15T10:30:00.25+01:00`, to a PDF date, like
+/// `D:20230115103000+01'00'`. Fractions of seconds are dropped.
+pub(crate) fn pdf_date(xmp_date: &str) -> Option<String> {
+    let (date, time) = match xmp_date.trim().split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (xmp_date.trim(), None),
+    };
+    let digits = |field: &str, len: usize| field.len() == len && field.bytes().all(|b| b.is_ascii_digit());
+    let mut pdf_date = String::from("D:");
+    for (index, field) in date.split('-').enumerate() {
+        if index > 2 || !digits(field, if index == 0 { 4 } else { 2 }) {
+            return None;
+        }
+        pdf_date.push_str(field);
+    }
+    let Some(time) = time else {
+        return Some(pdf_date);
+    };
+    let offset_start = time.find(['Z', '+', '-']).unwrap_or(time.len());
+    let (time, offset) = time.split_at(offset_start);
+    let time = time.split_once('.').map_or(time, |(time, _)| time);
+    let fields: Vec<&str> = time.split(':').collect();
+    if !(2..=3).contains(&fields.len()) || !fields.iter().all(|field| digits(field, 2)) {
+        return None;
+    }
+    pdf_date.extend(fields);
+    match offset.split_at_checked(1) {
+        None => {}
+        Some(("Z", "")) => pdf_date.push('Z'),
+        Some((sign @ ("+" | "-"), offset)) => {
+            let (hours, minutes) = offset.split_once(':')?;
+            if !digits(hours, 2) || !digits(minutes, 2) {
+                return None;
+            }
+            pdf_date.push_str(&format!("{sign}{hours}'{minutes}'"));
+        }
+        Some(_) => return None,
+    }
+    Some(pdf_date)
+}
+
+/// Find the values of [`PROPERTIES`] in `xml`.
+fn read_properties(xml: &str) -> Properties {
+    let mut properties = Properties::default();
+    let mut namespaces: HashMap<&str, String> = HashMap::new();
+    // The property being read and the depth of its element.
+    let mut open: Option<(usize, usize)> = None;
+    let mut depth = 0;
+    let mut lang = None;
+    let mut text = String::new();
+    let mut has_items = false;
+    for event in XmlEvents(xml) {
+        match event {
+            XmlEvent::Start { name, attributes, empty } => {
+                for (name, value) in &attributes {
+                    if let Some(("xmlns", prefix)) = name.split_once(':') {
+                        namespaces.insert(prefix, value.to_string());
+                    }
+                }
+                for (name, value) in &attributes {
+                    if let Some(index) = property(&namespaces, name) {
+                        properties[index].push((None, value.trim().to_string()));
+                    }
+                }
+                match open {
+                    None => {
+                        if let Some(index) = property(&namespaces, name).filter(|_| !empty) {
+                            open = Some((index, depth));
+                            text.clear();
+                            has_items = false;
+                        }
+                    }
+                    Some(_) if resolve(&namespaces, name) == (RDF, "li") => {
+                        lang = attributes
+                            .iter()
+                            .find(|(name, _)| *name == "xml:lang")
+                            .map(|(_, lang)| lang.to_string());
+                        text.clear();
+                    }
+                    Some(_) => {}
+                }
+                if !empty {
+                    depth += 1;
+                }
+            }
+            XmlEvent::Text(content) => {
+                if open.is_some() {
+                    text.push_str(&content);
+                }
+            }
+            XmlEvent::End { name } => {
+                depth -= 1;
+                let Some((index, open_depth)) = open else {
+                    continue;
+                };
+                if resolve(&namespaces, name) == (RDF, "li") {
+                    properties[index].push((lang.take(), text.trim().to_string()));
+                    text.clear();
+                    has_items = true;
+                } else if depth == open_depth {
+                    if !has_items && !text.trim().is_empty() {
+                        properties[index].push((None, text.trim().to_string()));
+                    }
+                    open = None;
+                }
+            }
+        }
+    }
+    properties
+}
+
+/// The index in [`PROPERTIES`] of the element or attribute `name`.
+fn property(namespaces: &HashMap<&str, String>, name: &str) -> Option<usize> {
+    let name = resolve(namespaces, name);
+    PROPERTIES.iter().position(|property| *property == name)
+}
+
+/// The namespace and local name of qualified name `name`.
+fn resolve<'a>(namespaces: &'a HashMap<&str, String>, name: &'a str) -> (&'a str, &'a str) {
+    let Some((prefix, local)) = name.split_once(':') else {
+        return ("", name);
+    };
+    let conventional = || CONVENTIONAL_PREFIXES.iter().find(|(known, _)| *known == prefix).map(|(_, ns)| *ns);
+    (namespaces.get(prefix).map(String::as_str).or_else(conventional).unwrap_or(""), local)
+}
+
+enum XmlEvent<'a> {
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, Cow<'a, str>)>,
+        empty: bool,
+    },
+    End {
+        name: &'a str,
+    },
+    Text(Cow<'a, str>),
+}
+
+/// The elements and text of an XML document, skipping declarations, processing instructions
+/// and comments.
+struct XmlEvents<'a>(&'a str);
+
+impl<'a> Iterator for XmlEvents<'a> {
+    type Item = XmlEvent<'a>;
+
+    fn next(&mut self) -> Option<XmlEvent<'a>> {
+        loop {
+            let rest = self.0;
+            if rest.is_empty() {
+                return None;
+            }
+            let Some(start) = rest.find('<') else {
+                self.0 = "";
+                return Some(XmlEvent::Text(unescape(rest)));
+            };
+            if start > 0 {
+                self.0 = &rest[start..];
+                return Some(XmlEvent::Text(unescape(&rest[..start])));
+            }
+            if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let end = cdata.find("]]>").unwrap_or(cdata.len());
+                self.0 = cdata.get(end + 3..).unwrap_or("");
+                return Some(XmlEvent::Text(Cow::Borrowed(&cdata[..end])));
+            }
+            for (open, close) in [("<!--", "-->"), ("<?", "?>"), ("<!", ">")] {
+                if rest.starts_with(open) {
+                    self.0 = rest.find(close).map_or("", |end| &rest[end + close.len()..]);
+                    break;
+                }
+            }
+            if self.0 != rest {
+                continue;
+            }
+            // The end of the tag is the first '>' outside attribute values.
+            let mut quote = None;
+            let end = rest.char_indices().find(|&(_, c)| match quote {
+                Some(open) if c == open => {
+                    quote = None;
+                    false
+                }
+                Some(_) => false,
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    false
+                }
+                None => c == '>',
+            });
+            let Some((end, _)) = end else {
+                self.0 = "";
+                return None;
+            };
+            self.0 = &rest[end + 1..];
+            let tag = &rest[1..end];
+            if let Some(name) = tag.strip_prefix('/') {
+                return Some(XmlEvent::End { name: name.trim() });
+            }
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            return Some(XmlEvent::Start {
+                name: &tag[..name_end],
+                attributes: attributes(&tag[name_end..]),
+                empty,
+            });
+        }
+    }
+}
+
+/// The attributes of a start tag, after its name.
+fn attributes(mut rest: &str) -> Vec<(&str, Cow<'_, str>)> {
+    let mut attributes = Vec::new();
+    while let Some((name, after)) = rest.split_once('=') {
+        let after = after.trim_start();
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some((value, after)) = after[1..].split_once(quote) else {
+            break;
+        };
+        attributes.push((name.trim(), unescape(value)));
+        rest = after;
+    }
+    attributes
+}
+
+/// Replace the character and entity references of `text`.
+fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let ch = reference.and_then(|reference| match reference {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match reference.strip_prefix("#x").or_else(|| reference.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => reference.strip_prefix('#')?.parse().ok().and_then(char::from_u32),
+            },
+        });
+        match (ch, reference) {
+            (Some(ch), Some(reference)) => {
+                unescaped.push(ch);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    Cow::Owned(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creator::tests::create_document;
+    use crate::Object;
+
+    const PACKET: &str = r#"<?xpacket begin="&#xFEFF;" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about=""
+        xmlns:pdf="http://ns.adobe.com/pdf/1.3/"
+        xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/"
+        pdf:Producer="Acme PDF &amp; Co" pdfaid:part="2" pdfaid:conformance="B"/>
+    <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/"
+        xmlns:xap="http://ns.adobe.com/xap/1.0/">
+      <dc:title>
+        <rdf:Alt>
+          <rdf:li xml:lang="de-DE">Jahresbericht</rdf:li>
+          <rdf:li xml:lang="x-default">Annual report</rdf:li>
+        </rdf:Alt>
+      </dc:title>
+      <dc:creator><rdf:Seq><rdf:li>Ada Lovelace</rdf:li><rdf:li>Charles Babbage</rdf:li></rdf:Seq></dc:creator>
+      <!-- <xap:CreateDate>1999-01-01</xap:CreateDate> -->
+      <xap:CreateDate>2023-01-15T10:30:00+01:00</xap:CreateDate>
+      <xap:ModifyDate>2023-02-01T08:00:00.125Z</xap:ModifyDate>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    #[test]
+    fn parse_xmp_packet() {
+        let metadata = XmpMetadata::parse(PACKET.as_bytes());
+        assert_eq!(
+            metadata,
+            XmpMetadata {
+                title: Some("Annual report".into()),
+                creators: vec!["Ada Lovelace".into(), "Charles Babbage".into()],
+                create_date: Some("2023-01-15T10:30:00+01:00".into()),
+                modify_date: Some("2023-02-01T08:00:00.125Z".into()),
+                producer: Some("Acme PDF & Co".into()),
+                pdfa: Some(PdfAIdentification {
+                    part: 2,
+                    conformance: Some("B".into())
+                }),
+            }
+        );
+        assert_eq!(XmpMetadata::parse(b"not xml"), XmpMetadata::default());
+    }
+
+    #[test]
+    fn convert_xmp_dates() {
+        assert_eq!(pdf_date("2023-01-15T10:30:00+01:00").as_deref(), Some("D:20230115103000+01'00'"));
+        assert_eq!(pdf_date("2023-02-01T08:00:00.125Z").as_deref(), Some("D:20230201080000Z"));
+        assert_eq!(pdf_date("2023-02-01T08:00-05:00").as_deref(), Some("D:202302010800-05'00'"));
+        assert_eq!(pdf_date("2023-02").as_deref(), Some("D:202302"));
+        assert_eq!(pdf_date("yesterday"), None);
+        assert_eq!(pdf_date("2023-02-01T8:00"), None);
+    }
+
+    #[test]
+    fn get_plain_and_compressed_xmp_metadata() {
+        let mut doc = create_document();
+        assert_eq!(doc.get_xmp_metadata().unwrap(), None);
+
+        let metadata = Stream::new(dictionary! { "Type" => "Metadata", "Subtype" => "XML" }, PACKET.into());
+        let metadata_id = doc.add_object(metadata.clone());
+        doc.catalog_mut().unwrap().set("Metadata", metadata_id);
+        assert_eq!(doc.get_xmp_metadata().unwrap().as_deref(), Some(PACKET.as_bytes()));
+
+        // Compressed, with and without declaring the filter.
+        let mut compressed = metadata;
+        compressed.compress().unwrap();
+        doc.objects.insert(metadata_id, Object::Stream(compressed.clone()));
+        assert_eq!(doc.get_xmp_metadata().unwrap().as_deref(), Some(PACKET.as_bytes()));
+        compressed.dict.remove(b"Filter");
+        doc.objects.insert(metadata_id, Object::Stream(compressed));
+        assert_eq!(doc.get_xmp_metadata().unwrap().as_deref(), Some(PACKET.as_bytes()));
+        assert_eq!(doc.xmp_metadata().unwrap().unwrap().title.as_deref(), Some("Annual report"));
+    }
+}
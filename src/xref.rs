@@ -1,7 +1,13 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::io::{Result, Write};
 
-#[derive(Debug, Clone)]
+/// Largest offset a cross-reference table entry can hold, in its ten digits.
+const MAX_XREF_TABLE_OFFSET: u64 = 9_999_999_999;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Xref {
     /// Type of Cross-Reference used in the last incremental version.
     /// This method of cross-referencing will also be used when saving the file.
@@ -15,7 +21,8 @@ pub struct Xref {
     pub size: u32,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum XrefType {
     /// Cross-Reference Streams are supported beginning with PDF 1.5.
     CrossReferenceStream,
@@ -23,11 +30,12 @@ pub enum XrefType {
     CrossReferenceTable,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum XrefEntry {
     Free { next_free_object: u32, generation: u16 },
     UnusableFree,
-    Normal { offset: u32, generation: u16 },
+    Normal { offset: u64, generation: u16 },
     Compressed { container: u32, index: u16 },
 }
 
@@ -62,6 +70,19 @@ pub fn merge(&mut self, xref: Xref) {
         }
     }
 
+    /// Combine the entries of a hybrid-reference file's cross-reference stream with those of the
+    /// table of the same section, which take precedence unless they're free.
+    pub(crate) fn merge_hybrid_stream(&mut self, stream: Xref) {
+        for (id, entry) in stream.entries {
+            match self.entries.get(&id) {
+                Some(XrefEntry::Normal { .. } | XrefEntry::Compressed { .. }) => {}
+                _ => {
+                    self.entries.insert(id, entry);
+                }
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         self.entries.clear()
     }
@@ -103,7 +124,7 @@ pub fn is_compressed(&self) -> bool {
             XrefEntry::Normal { offset, generation } => {
                 // Type 1: Uncompressed object
                 encode_field(1, widths[0], &mut result);
-                encode_field(*offset as u64, widths[1], &mut result);
+                encode_field(*offset, widths[1], &mut result);
                 encode_field(*generation as u64, widths[2], &mut result);
             }
             XrefEntry::Compressed { container, index } => {
@@ -121,6 +142,12 @@ pub fn is_compressed(&self) -> bool {
     pub fn write_xref_entry(&self, file: &mut dyn Write) -> Result<()> {
         match self {
             XrefEntry::Normal { offset, generation } => {
+                if *offset > MAX_XREF_TABLE_OFFSET {
+                    return Err(std::io::Error::other(crate::Error::InvalidSaveOptions(format!(
+                        "offset {offset} doesn't fit the ten digits of a cross-reference table entry, \
+                         save with cross-reference streams instead"
+                    ))));
+                }
                 writeln!(file, "{offset:>010} {generation:>05} n ")?;
             }
             XrefEntry::Compressed { container: _, index: _ } => {
@@ -216,7 +243,7 @@ pub fn entries_count(&self) -> usize {
         for (_, entry) in &self.entries {
             match entry {
                 XrefEntry::Normal { offset, generation } => {
-                    max_offset = max_offset.max(*offset as u64);
+                    max_offset = max_offset.max(*offset);
                     max_gen = max_gen.max(*generation);
                 }
                 XrefEntry::Compressed { container, index } => {
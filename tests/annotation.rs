@@ -12,3 +12,36 @@ fn annotation_count() -> Result<()> {
     assert_eq!(doc.get_page_annotations(doc.page_iter().next().unwrap())?.len(), 33);
     Ok(())
 }
+
+#[test]
+fn file_attachment_annotation_round_trip() -> Result<()> {
+    use lopdf::{Annotation, Document, FileAttachmentIcon};
+
+    let mut doc = utils::load_document("assets/AnnotationDemo.pdf")?;
+    let page_id = doc.page_iter().next().unwrap();
+    let csv = b"id,name\n1,first\n2,second\n".to_vec();
+    let annotation = Annotation::file_attachment(
+        [10.0, 10.0, 30.0, 30.0],
+        "rows.csv",
+        csv.clone(),
+        Some("text/csv"),
+        FileAttachmentIcon::PushPin,
+    );
+    doc.add_annotation(page_id, annotation)?;
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+
+    let doc = Document::load_mem(&bytes)?;
+    let annotations = doc.get_annotations(doc.page_iter().next().unwrap())?;
+    assert_eq!(annotations.len(), 34);
+    let files: Vec<_> = annotations.iter().filter_map(Annotation::file).collect();
+    // The demo file comes with an attached sound.
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].name, "test.wav");
+    assert!(!files[0].data.is_empty());
+    let file = &files[1];
+    assert_eq!(file.name, "rows.csv");
+    assert_eq!(file.mime_type.as_deref(), Some("text/csv"));
+    assert_eq!(file.data, csv);
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use lopdf::{Document, IncrementalDocument, Object, SaveOptions};
+use tokio::io::AsyncWrite;
+
+/// An async writer taking at most 4 KiB per write, each after first reporting it's not ready.
+#[derive(Default)]
+struct Throttled {
+    written: Vec<u8>,
+    ready: bool,
+    flushed: bool,
+}
+
+impl AsyncWrite for Throttled {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        let len = buf.len().min(4096);
+        self.written.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.flushed = true;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn assert_send<T: Send>(value: T) -> T {
+    value
+}
+
+#[tokio::test]
+async fn save_to_async_matches_sync_output() {
+    let doc = Document::load("assets/example.pdf").await.unwrap();
+    let option_sets = [
+        SaveOptions::default(),
+        SaveOptions::builder().use_xref_streams(true).build().unwrap(),
+        SaveOptions::builder().use_object_streams(true).use_xref_streams(true).build().unwrap(),
+        SaveOptions::builder().compact_ids(true).build().unwrap(),
+    ];
+    for options in option_sets {
+        let mut expected = Vec::new();
+        doc.clone().save_with_options(&mut expected, options.clone()).unwrap();
+
+        let mut async_doc = doc.clone();
+        let mut target = Throttled::default();
+        assert_send(async_doc.save_to_async(&mut target, options)).await.unwrap();
+        assert!(target.flushed);
+        assert_eq!(target.written, expected);
+        assert_eq!(Document::load_mem(&target.written).unwrap().get_pages().len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn incremental_save_to_async_matches_sync_output() {
+    let mut doc = IncrementalDocument::load("assets/Incremental.pdf").await.unwrap();
+    doc.new_document.add_object(Object::string_literal("appended"));
+
+    let mut expected = Vec::new();
+    doc.clone().save_to(&mut expected).unwrap();
+
+    let mut target = Throttled::default();
+    doc.save_to_async(&mut target).await.unwrap();
+    assert_eq!(target.written, expected);
+    assert!(target.written.starts_with(doc.get_prev_documents_bytes()));
+}
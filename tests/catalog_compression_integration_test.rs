@@ -35,7 +35,8 @@ fn test_catalog_included_in_object_stream_output() {
     let options = SaveOptions::builder()
         .use_object_streams(true)
         .use_xref_streams(true)
-        .build();
+        .build()
+        .unwrap();
     
     let mut output = Vec::new();
     doc.save_with_options(&mut output, options).unwrap();
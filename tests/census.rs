@@ -0,0 +1,93 @@
+use lopdf::{Census, Document};
+
+fn census(path: &str) -> Census {
+    Document::load_mem(&std::fs::read(path).unwrap()).unwrap().census().clone()
+}
+
+#[test]
+fn counts_strings_filters_and_depth() {
+    let census = census("assets/unicode.pdf");
+    assert_eq!(census.literal_strings, 3);
+    assert_eq!(census.hexadecimal_strings, 1);
+    assert_eq!(census.filters.get("FlateDecode"), Some(&3));
+    assert_eq!(census.max_depth, 3);
+    assert_eq!(census.long_reals, 1);
+    assert_eq!(census.object_streams, 0);
+    assert!(census.diagnostics.is_empty());
+}
+
+#[test]
+fn counts_each_filter_of_a_chain() {
+    let census = census("assets/run_length_image.pdf");
+    assert_eq!(census.filters.get("ASCII85Decode"), Some(&1));
+    assert_eq!(census.filters.get("RunLengthDecode"), Some(&1));
+}
+
+#[test]
+fn counts_hybrid_references_and_object_streams() {
+    let census = census("assets/Hybrid.pdf");
+    assert_eq!(census.hybrid_xrefs, 1);
+    assert_eq!(census.object_streams, 1);
+    assert_eq!(census.compressed_objects, 1);
+}
+
+#[test]
+fn counts_encryption_revisions() {
+    let census = census("assets/EncryptedObjectStreams.pdf");
+    assert_eq!(census.encryption_revisions.get(&4), Some(&1));
+    assert_eq!(census.object_streams, 1);
+    assert_eq!(census.compressed_objects, 7);
+}
+
+#[test]
+fn counts_diagnostics_by_kind() {
+    let census = census("assets/encrypted.pdf");
+    assert_eq!(census.diagnostics.get("DecryptFailed"), Some(&1));
+}
+
+#[test]
+fn counts_comments_and_non_ascii_names() {
+    let objects: [&[u8]; 2] = [
+        b"<< /Type /Catalog /Pages 2 0 R /N#C3#A9 (x) % note\n>>",
+        b"<< /Type /Pages /Kids [] /Count 0 >>",
+    ];
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let startxref = pdf.len();
+    pdf.extend_from_slice(b"xref\n0 3\n0000000000 65535 f \n");
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    pdf.extend_from_slice(format!("trailer\n<< /Size 3 /Root 1 0 R >>\nstartxref\n{startxref}\n%%EOF\n").as_bytes());
+
+    let doc = Document::load_mem(&pdf).unwrap();
+    let census = doc.census();
+    assert_eq!(census.non_ascii_names, 1);
+    assert_eq!(census.literal_strings, 1);
+    assert_eq!(census.comments, 1);
+    assert!(doc.load_warnings().is_empty());
+}
+
+#[test]
+fn merges_and_serializes() {
+    let mut total = census("assets/unicode.pdf");
+    total.merge(&census("assets/AnnotationDemo.pdf"));
+    assert_eq!(total.filters.get("FlateDecode"), Some(&15));
+    assert_eq!(total.max_depth, 11);
+    assert_eq!(total.hexadecimal_strings, 5);
+
+    let json = serde_json::to_value(&total).unwrap();
+    assert_eq!(json["filters"]["FlateDecode"], 15);
+    assert_eq!(json["literal_strings"], 142);
+}
+
+#[test]
+fn documents_not_loaded_have_an_empty_census() {
+    assert_eq!(Document::new().census(), &Census::default());
+}
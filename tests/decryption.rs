@@ -1284,7 +1284,7 @@ fn test_load_encrypted_pdf_with_object_streams() {
     let text = doc.extract_text(&page_numbers).unwrap();
     println!("Extracted {} characters of text", text.len());
     
-    assert!(pages.len() > 0, "Should have at least one page");
+    assert!(!pages.is_empty(), "Should have at least one page");
     
     // Now save and reload to verify round-trip
     let temp_dir = tempfile::tempdir().unwrap();
@@ -1504,3 +1504,533 @@ fn test_was_encrypted_method() {
     assert!(loaded_locked.is_encrypted(), "Should still appear encrypted without password");
     assert!(!loaded_locked.was_encrypted(), "encryption_state not set when auth failed");
 }
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_save_encrypted_with_rc4_128_round_trip() {
+    let mut doc = Document::load("assets/example.pdf").unwrap();
+    assert!(doc.trailer.get(b"ID").is_err(), "the asset has no file identifier to start with");
+    let page_numbers: Vec<u32> = doc.get_pages().keys().cloned().collect();
+    let text = doc.extract_text(&page_numbers).unwrap();
+
+    doc.ensure_file_id();
+    let file_id = doc.trailer.get(b"ID").unwrap().clone();
+    let version = lopdf::EncryptionVersion::V2 {
+        document: &doc,
+        owner_password: "owner_secret",
+        user_password: "user_secret",
+        key_length: 128,
+        permissions: lopdf::Permissions::all(),
+    };
+    let state = lopdf::EncryptionState::try_from(version).unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let encrypted_path = temp_dir.path().join("example_rc4_128.pdf");
+    let options = lopdf::SaveOptions::builder().encryption(state).build().unwrap();
+    doc.save_atomic(&encrypted_path, options).unwrap();
+    // The document itself stays as it was.
+    assert!(doc.trailer.get(b"Encrypt").is_err());
+    assert_eq!(doc.extract_text(&page_numbers).unwrap(), text);
+
+    let bytes = std::fs::read(&encrypted_path).unwrap();
+    assert!(!bytes.windows(b"Hello World!".len()).any(|window| window == b"Hello World!"));
+    let raw = Document::load_mem(&bytes).unwrap();
+    assert!(raw.is_encrypted(), "the user password isn't empty");
+    let encrypt = raw.get_encrypted().unwrap();
+    assert_eq!(encrypt.get(b"Filter").unwrap(), &Object::Name(b"Standard".to_vec()));
+    assert_eq!(encrypt.get(b"V").unwrap(), &Object::Integer(2));
+    assert_eq!(encrypt.get(b"R").unwrap(), &Object::Integer(3));
+    assert_eq!(encrypt.get(b"Length").unwrap(), &Object::Integer(128));
+    assert_eq!(raw.trailer.get(b"ID").unwrap(), &file_id);
+
+    for password in ["user_secret", "owner_secret"] {
+        let loaded = Document::load_with_password(&encrypted_path, password).unwrap();
+        assert!(!loaded.is_encrypted());
+        assert_eq!(loaded.extract_text(&page_numbers).unwrap(), text, "opened with {password}");
+    }
+    assert!(matches!(
+        Document::load_with_password(&encrypted_path, "wrong"),
+        Err(Error::InvalidPassword)
+    ));
+}
+
+#[test]
+fn test_load_encrypted_streams_with_indirect_length() {
+    use lopdf::{EncryptionAlgorithm, EncryptionOptions};
+
+    let mut doc = Document::load_mem(&std::fs::read("assets/example.pdf").unwrap()).unwrap();
+    let page_numbers: Vec<u32> = doc.get_pages().keys().cloned().collect();
+    let text = doc.extract_text(&page_numbers).unwrap();
+    let page_id = doc.get_pages()[&1];
+    let content_id = doc.get_page_contents(page_id)[0];
+    let content = doc.get_object(content_id).unwrap().as_stream().unwrap().content.clone();
+
+    doc.ensure_file_id();
+    let state = EncryptionOptions::new("owner_secret", "")
+        .algorithm(EncryptionAlgorithm::Aes128)
+        .state(&doc)
+        .unwrap();
+    doc.encrypt(&state).unwrap();
+    // The /Length refers to an object holding another reference, so the stream data can only be
+    // read once all objects are loaded.
+    let length = doc.get_object(content_id).unwrap().as_stream().unwrap().content.len() as i64;
+    let length_id = doc.add_object(length);
+    let reference_id = doc.add_object(length_id);
+    let stream = doc.get_object_mut(content_id).and_then(Object::as_stream_mut).unwrap();
+    stream.dict.set("Length", reference_id);
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).unwrap();
+
+    let loaded = Document::load_mem(&bytes).unwrap();
+    assert!(!loaded.is_encrypted());
+    let stream = loaded.get_object(content_id).unwrap().as_stream().unwrap();
+    assert_eq!(stream.content, content);
+    assert_eq!(loaded.extract_text(&page_numbers).unwrap(), text);
+}
+
+#[test]
+fn test_save_encrypted_refuses_object_streams() {
+    let mut doc = Document::load_mem(&std::fs::read("assets/example.pdf").unwrap()).unwrap();
+    doc.ensure_file_id();
+    let version = lopdf::EncryptionVersion::V2 {
+        document: &doc,
+        owner_password: "owner",
+        user_password: "",
+        key_length: 128,
+        permissions: lopdf::Permissions::all(),
+    };
+    let state = lopdf::EncryptionState::try_from(version).unwrap();
+    let refused = lopdf::SaveOptions::builder().use_object_streams(true).encryption(state).build();
+    assert_eq!(refused.unwrap_err(), lopdf::SaveOptionsError::EncryptedObjectStreams);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_save_encrypted_with_aes_128_round_trip() {
+    use lopdf::{EncryptionAlgorithm, EncryptionOptions, Stream};
+
+    let mut doc = Document::load("assets/example.pdf").unwrap();
+    let page_numbers: Vec<u32> = doc.get_pages().keys().cloned().collect();
+    let text = doc.extract_text(&page_numbers).unwrap();
+    let xmp = b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>".to_vec();
+    let mut metadata = Stream::new(lopdf::dictionary! { "Type" => "Metadata", "Subtype" => "XML" }, xmp.clone());
+    metadata.allows_compression = false;
+    let metadata_id = doc.add_object(metadata);
+    doc.catalog_mut().unwrap().set("Metadata", metadata_id);
+
+    doc.ensure_file_id();
+    let state = EncryptionOptions::new("owner_secret", "user_secret")
+        .algorithm(EncryptionAlgorithm::Aes128)
+        .encrypt_metadata(false)
+        .state(&doc)
+        .unwrap();
+    let mut bytes = Vec::new();
+    doc.save_with_options(&mut bytes, lopdf::SaveOptions::builder().encryption(state).build().unwrap()).unwrap();
+
+    assert!(!bytes.windows(b"Hello World!".len()).any(|window| window == b"Hello World!"));
+    assert!(bytes.windows(xmp.len()).any(|window| window == xmp), "the metadata is left in plain text");
+    let raw = Document::load_mem(&bytes).unwrap();
+    let encrypt = raw.get_encrypted().unwrap();
+    assert_eq!(encrypt.get(b"V").unwrap(), &Object::Integer(4));
+    assert_eq!(encrypt.get(b"R").unwrap(), &Object::Integer(4));
+    assert_eq!(encrypt.get(b"EncryptMetadata").unwrap(), &Object::Boolean(false));
+    assert_eq!(encrypt.get(b"StmF").unwrap(), &Object::Name(b"StdCF".to_vec()));
+    assert_eq!(encrypt.get(b"StrF").unwrap(), &Object::Name(b"StdCF".to_vec()));
+    let filter = encrypt.get_deref(b"CF", &raw).unwrap().as_dict().unwrap().get(b"StdCF").unwrap();
+    let filter = filter.as_dict().unwrap();
+    assert_eq!(filter.get(b"CFM").unwrap(), &Object::Name(b"AESV2".to_vec()));
+    assert_eq!(filter.get(b"AuthEvent").unwrap(), &Object::Name(b"DocOpen".to_vec()));
+    assert_eq!(filter.get(b"Length").unwrap(), &Object::Integer(16));
+
+    for password in ["user_secret", "owner_secret"] {
+        let loaded = Document::load_mem_with_password(&bytes, password).unwrap();
+        assert_eq!(loaded.extract_text(&page_numbers).unwrap(), text, "opened with {password}");
+        let metadata = loaded.get_object(metadata_id).unwrap().as_stream().unwrap();
+        assert_eq!(metadata.content, xmp);
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_load_aes_256_sample() {
+    use lopdf::Permissions;
+
+    // Written by an independent implementation of revision 6, with the user password
+    // "Päss<U+00A0>wörd" and the owner password "owner<U+2163>", which SASLprep maps to
+    // "Päss wörd" and "ownerIV".
+    let path = "assets/encrypted_aes256.pdf";
+    let raw = Document::load(path).unwrap();
+    assert!(raw.is_encrypted(), "the user password isn't empty");
+    assert_eq!(raw.get_encrypted().unwrap().get(b"R").unwrap(), &Object::Integer(6));
+
+    for password in ["P\u{e4}ss\u{a0}w\u{f6}rd", "Pa\u{308}ss w\u{f6}rd", "owner\u{2163}", "ownerIV"] {
+        let doc = Document::load_with_password(path, password).unwrap();
+        assert_eq!(doc.extract_text(&[1]).unwrap(), "Hello AES-256\n", "opened with {password}");
+        let info = doc.trailer.get_deref(b"Info", &doc).unwrap().as_dict().unwrap();
+        assert_eq!(info.get(b"Title").unwrap().as_str().unwrap(), b"AES-256 sample");
+        let permissions = doc.encryption_state.as_ref().unwrap().permissions();
+        assert!(permissions.contains(Permissions::PRINTABLE | Permissions::COPYABLE_FOR_ACCESSIBILITY));
+        assert!(!permissions.intersects(Permissions::COPYABLE | Permissions::MODIFIABLE));
+    }
+    for password in ["Pass word", "owner"] {
+        assert!(matches!(Document::load_with_password(path, password), Err(Error::InvalidPassword)));
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_load_with_non_ascii_passwords() {
+    use lopdf::{EncryptionAlgorithm, EncryptionOptions, LazyDocument, PasswordKind, SaveOptions};
+
+    let opened = |doc: lopdf::Result<Document>, title: &[u8]| {
+        let doc = doc.unwrap();
+        assert_eq!(doc.authentication_result(), Some(PasswordKind::User));
+        let info = doc.trailer.get_deref(b"Info", &doc).unwrap().as_dict().unwrap();
+        assert_eq!(info.get(b"Title").unwrap().as_str().unwrap(), title);
+    };
+
+    // RC4 (revision 3), with the user password hashed from the UTF-8 of "pässwörd" rather than
+    // from its PDFDocEncoding.
+    let path = "assets/encrypted_rc4_utf8_password.pdf";
+    opened(Document::load_with_password(path, "p\u{e4}ssw\u{f6}rd"), b"RC4 sample");
+    opened(Document::load_with_password_bytes(path, "p\u{e4}ssw\u{f6}rd".as_bytes()), b"RC4 sample");
+    assert!(matches!(
+        Document::load_with_password_bytes(path, b"p\xe4ssw\xf6rd"),
+        Err(Error::InvalidPassword)
+    ));
+
+    // AES-256 (revision 6), with the user password "clé🔑", which SASLprep prohibits, hashed from
+    // its UTF-8.
+    let path = "assets/encrypted_aes256_emoji_password.pdf";
+    let buffer = std::fs::read(path).unwrap();
+    opened(Document::load_with_password(path, "cl\u{e9}\u{1f511}"), b"AES-256 sample");
+    opened(Document::load_mem_with_password_bytes(&buffer, "cl\u{e9}\u{1f511}".as_bytes()), b"AES-256 sample");
+    let lazy = LazyDocument::load_mem_with_password_bytes(&buffer, "cl\u{e9}\u{1f511}".as_bytes()).unwrap();
+    opened(lazy.into_document(), b"AES-256 sample");
+    assert!(matches!(Document::load_with_password(path, "cl\u{e9}"), Err(Error::InvalidPassword)));
+
+    // The bytes of a revision 6 password are used without SASLprep.
+    let path = "assets/encrypted_aes256.pdf";
+    opened(Document::load_with_password_bytes(path, "P\u{e4}ss w\u{f6}rd".as_bytes()), b"AES-256 sample");
+    assert!(matches!(
+        Document::load_with_password_bytes(path, "P\u{e4}ss\u{a0}w\u{f6}rd".as_bytes()),
+        Err(Error::InvalidPassword)
+    ));
+
+    // Passwords lopdf encrypts with are hashed from Latin-1, as readers such as Acrobat expect.
+    let mut doc = Document::load("assets/example.pdf").unwrap();
+    doc.ensure_file_id();
+    let state = EncryptionOptions::new("owner", "p\u{e4}ssw\u{f6}rd")
+        .algorithm(EncryptionAlgorithm::Rc4_128)
+        .state(&doc)
+        .unwrap();
+    let mut bytes = Vec::new();
+    doc.save_with_options(&mut bytes, SaveOptions::builder().encryption(state).build().unwrap()).unwrap();
+    let reloaded = Document::load_mem_with_password_bytes(&bytes, b"p\xe4ssw\xf6rd").unwrap();
+    assert_eq!(reloaded.authentication_result(), Some(PasswordKind::User));
+    assert_eq!(reloaded.extract_text(&[1]).unwrap(), doc.extract_text(&[1]).unwrap());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_save_encrypted_with_aes_256_round_trip() {
+    use lopdf::{EncryptionAlgorithm, EncryptionOptions, Permissions};
+
+    let mut doc = Document::load("assets/example.pdf").unwrap();
+    let text = doc.extract_text(&[1]).unwrap();
+    let permissions = Permissions::PRINTABLE | Permissions::FILLABLE;
+    let state = EncryptionOptions::new("owner", "us\u{e9}r")
+        .algorithm(EncryptionAlgorithm::Aes256)
+        .permissions(permissions)
+        .state(&doc)
+        .unwrap();
+    let mut bytes = Vec::new();
+    doc.save_with_options(&mut bytes, lopdf::SaveOptions::builder().encryption(state).build().unwrap()).unwrap();
+
+    let raw = Document::load_mem(&bytes).unwrap();
+    let encrypt = raw.get_encrypted().unwrap();
+    assert_eq!(encrypt.get(b"V").unwrap(), &Object::Integer(5));
+    assert_eq!(encrypt.get(b"R").unwrap(), &Object::Integer(6));
+    for (key, len) in [(&b"O"[..], 48), (b"U", 48), (b"OE", 32), (b"UE", 32), (b"Perms", 16)] {
+        assert_eq!(encrypt.get(key).unwrap().as_str().unwrap().len(), len);
+    }
+    let filter = encrypt.get_deref(b"CF", &raw).unwrap().as_dict().unwrap().get(b"StdCF").unwrap();
+    assert_eq!(filter.as_dict().unwrap().get(b"CFM").unwrap(), &Object::Name(b"AESV3".to_vec()));
+
+    // The decomposed form of the password is the same after SASLprep.
+    for password in ["us\u{e9}r", "use\u{301}r", "owner"] {
+        let loaded = Document::load_mem_with_password(&bytes, password).unwrap();
+        assert_eq!(loaded.extract_text(&[1]).unwrap(), text, "opened with {password}");
+        let loaded_permissions = loaded.encryption_state.as_ref().unwrap().permissions();
+        assert!(loaded_permissions.contains(permissions));
+        assert!(!loaded_permissions.intersects(Permissions::COPYABLE | Permissions::MODIFIABLE));
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_document_permissions() {
+    use lopdf::{EncryptionOptions, Permissions, SaveOptions};
+
+    assert_eq!(Document::load("assets/example.pdf").unwrap().permissions(), None);
+
+    // /P -1052 in a revision 4 handler.
+    let doc = Document::load("assets/encrypted.pdf").unwrap();
+    assert_eq!(
+        doc.permissions().unwrap() & Permissions::all(),
+        Permissions::PRINTABLE
+            | Permissions::ANNOTABLE
+            | Permissions::FILLABLE
+            | Permissions::COPYABLE_FOR_ACCESSIBILITY
+            | Permissions::PRINTABLE_IN_HIGH_QUALITY
+    );
+
+    // Read from the encryption dictionary of a document that couldn't be decrypted.
+    let low_resolution = Permissions::PRINTABLE | Permissions::COPYABLE_FOR_ACCESSIBILITY;
+    let raw = Document::load("assets/encrypted_aes256.pdf").unwrap();
+    assert!(raw.is_encrypted());
+    assert_eq!(raw.permissions().unwrap() & Permissions::all(), low_resolution);
+    let doc = Document::load_with_password("assets/encrypted_aes256.pdf", "ownerIV").unwrap();
+    assert_eq!(doc.permissions().unwrap() & Permissions::all(), low_resolution);
+
+    let mut doc = Document::load("assets/example.pdf").unwrap();
+    doc.ensure_file_id();
+    let permissions = Permissions::PRINTABLE | Permissions::COPYABLE;
+    let state = EncryptionOptions::new("owner", "").permissions(permissions).state(&doc).unwrap();
+    let mut buffer = Vec::new();
+    doc.save_with_options(&mut buffer, SaveOptions::builder().encryption(state).build().unwrap())
+        .unwrap();
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(doc.permissions().unwrap() & Permissions::all(), permissions);
+    // Written as a negative 32-bit integer, with the reserved bits set.
+    assert!(buffer.windows(8).any(|window| window == b"/P -3884"));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_authentication_result() {
+    use lopdf::{EncryptionAlgorithm, EncryptionOptions, PasswordKind, SaveOptions};
+
+    // The fixture has the user password "Päss wörd" and the owner password "ownerIV".
+    let path = "assets/encrypted_aes256.pdf";
+    let cases = [
+        ("ownerIV", PasswordKind::Owner),
+        ("owner\u{2163}", PasswordKind::Owner),
+        ("P\u{e4}ss\u{a0}w\u{f6}rd", PasswordKind::User),
+    ];
+    for (password, kind) in cases {
+        let doc = Document::load_with_password(path, password).unwrap();
+        assert_eq!(doc.authentication_result(), Some(kind), "opened with {password}");
+    }
+    let raw = Document::load(path).unwrap();
+    assert_eq!(raw.authentication_result(), None);
+    let mut doc = raw.clone();
+    doc.decrypt("ownerIV").unwrap();
+    assert_eq!(doc.authentication_result(), Some(PasswordKind::Owner));
+    let mut doc = raw;
+    doc.decrypt("P\u{e4}ss w\u{f6}rd").unwrap();
+    assert_eq!(doc.authentication_result(), Some(PasswordKind::User));
+
+    // With an empty user password, the owner password is still recognized.
+    let mut doc = Document::load("assets/example.pdf").unwrap();
+    assert_eq!(doc.authentication_result(), None);
+    doc.ensure_file_id();
+    let state = EncryptionOptions::new("owner", "")
+        .algorithm(EncryptionAlgorithm::Aes128)
+        .state(&doc)
+        .unwrap();
+    let mut buffer = Vec::new();
+    doc.save_with_options(&mut buffer, SaveOptions::builder().encryption(state).build().unwrap())
+        .unwrap();
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(doc.authentication_result(), Some(PasswordKind::Empty));
+    let doc = Document::load_mem_with_password(&buffer, "owner").unwrap();
+    assert_eq!(doc.authentication_result(), Some(PasswordKind::Owner));
+    // A wrong password falls back to the empty user password.
+    let doc = Document::load_mem_with_password(&buffer, "wrong").unwrap();
+    assert_eq!(doc.authentication_result(), Some(PasswordKind::Empty));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_decrypt_streams_with_crypt_filters() {
+    use lopdf::encryption::crypt_filters::{Aes128CryptFilter, CryptFilter, Rc4CryptFilter};
+    use lopdf::{EncryptionState, EncryptionVersion, Permissions, Stream, dictionary};
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    let mut doc = Document::load("assets/example.pdf").unwrap();
+    doc.ensure_file_id();
+    // The metadata is routed through the Identity crypt filter, although /EncryptMetadata is true.
+    let xmp = b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>".to_vec();
+    let hex: String = xmp.iter().map(|byte| format!("{byte:02X}")).collect();
+    let metadata = Stream::new(
+        dictionary! {
+            "Type" => "Metadata",
+            "Subtype" => "XML",
+            "Filter" => vec!["Crypt".into(), "ASCIIHexDecode".into()],
+            "DecodeParms" => vec![
+                dictionary! { "Type" => "CryptFilterDecodeParms", "Name" => "Identity" }.into(),
+                Object::Null,
+            ],
+        },
+        format!("{hex}>").into_bytes(),
+    );
+    let metadata_id = doc.add_object(metadata);
+    doc.catalog_mut().unwrap().set("Metadata", metadata_id);
+    // Another stream is encrypted with a crypt filter other than the default.
+    let named = Stream::new(
+        dictionary! { "Filter" => "Crypt", "DecodeParms" => dictionary! { "Name" => "LegacyCF" } },
+        b"encrypted with RC4".to_vec(),
+    );
+    let named_id = doc.add_object(named);
+
+    let crypt_filters: BTreeMap<Vec<u8>, Arc<dyn CryptFilter>> = BTreeMap::from([
+        (b"StdCF".to_vec(), Arc::new(Aes128CryptFilter) as Arc<dyn CryptFilter>),
+        (b"LegacyCF".to_vec(), Arc::new(Rc4CryptFilter)),
+    ]);
+    let state = EncryptionState::try_from(EncryptionVersion::V4 {
+        document: &doc,
+        encrypt_metadata: true,
+        crypt_filters,
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: "owner",
+        user_password: "",
+        permissions: Permissions::all(),
+    })
+    .unwrap();
+    doc.encrypt(&state).unwrap();
+    // RC4 keeps the length, AES would add an initialization vector and padding.
+    let encrypted = doc.get_object(named_id).and_then(Object::as_stream).unwrap();
+    assert_eq!(encrypted.content.len(), b"encrypted with RC4".len());
+    assert_ne!(encrypted.content, b"encrypted with RC4");
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    assert!(buffer.windows(hex.len()).any(|window| window == hex.as_bytes()), "the metadata is in plain text");
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    let metadata = doc.get_object(metadata_id).and_then(Object::as_stream).unwrap();
+    assert_eq!(metadata.dict.get(b"Filter").unwrap(), &Object::Array(vec!["ASCIIHexDecode".into()]));
+    assert_eq!(metadata.get_plain_content().unwrap(), xmp);
+    let named = doc.get_object(named_id).and_then(Object::as_stream).unwrap();
+    assert!(!named.dict.has(b"Filter"));
+    assert!(!named.dict.has(b"DecodeParms"));
+    assert_eq!(named.content, b"encrypted with RC4");
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_remove_encryption_round_trip() {
+    use lopdf::{EncryptionOptions, FileIdPolicy, SaveOptions};
+
+    // Opened with the empty user password.
+    let mut doc = Document::load("assets/encrypted.pdf").unwrap();
+    let page_numbers: Vec<u32> = doc.get_pages().keys().cloned().collect();
+    let text = doc.extract_text(&page_numbers).unwrap();
+    assert!(text.contains("USCIS"));
+    let file_id = doc.trailer.get(b"ID").unwrap().clone();
+    doc.remove_encryption(FileIdPolicy::Preserve).unwrap();
+    assert!(!doc.was_encrypted());
+    let mut plain = Vec::new();
+    doc.save_to(&mut plain).unwrap();
+
+    let decrypted = Document::load_mem(&plain).unwrap();
+    assert!(!decrypted.was_encrypted());
+    assert!(!decrypted.trailer.has(b"Encrypt"));
+    assert_eq!(decrypted.trailer.get(b"ID").unwrap(), &file_id);
+    assert_eq!(decrypted.extract_text(&page_numbers).unwrap(), text);
+
+    // Encrypted again with a user password.
+    let mut doc = decrypted;
+    let state = EncryptionOptions::new("owner", "user").state(&doc).unwrap();
+    let mut encrypted = Vec::new();
+    doc.save_with_options(&mut encrypted, SaveOptions::builder().encryption(state).build().unwrap())
+        .unwrap();
+
+    let mut locked = Document::load_mem(&encrypted).unwrap();
+    assert!(locked.is_encrypted());
+    assert!(matches!(locked.remove_encryption(FileIdPolicy::Preserve), Err(Error::Decryption(_))));
+
+    let mut doc = Document::load_mem_with_password(&encrypted, "user").unwrap();
+    doc.remove_encryption(FileIdPolicy::Regenerate).unwrap();
+    let mut plain = Vec::new();
+    doc.save_to(&mut plain).unwrap();
+    let decrypted = Document::load_mem(&plain).unwrap();
+    assert!(!decrypted.was_encrypted());
+    assert!(!decrypted.trailer.has(b"Encrypt"));
+    assert_ne!(decrypted.trailer.get(b"ID").unwrap(), &file_id);
+    assert_eq!(decrypted.extract_text(&page_numbers).unwrap(), text);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn test_save_preserving_encryption() {
+    use lopdf::{Dictionary, PasswordKind, Permissions, SaveOptions, StringFormat};
+
+    let preserve = || SaveOptions::builder().preserve_encryption(true).build().unwrap();
+    let info_title = |doc: &Document| {
+        let info = doc.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+        doc.get_dictionary(info).unwrap().get(b"Title").unwrap().as_str().unwrap().to_vec()
+    };
+
+    // AES-256, revision 6, opened with the owner password.
+    let path = "assets/encrypted_aes256.pdf";
+    let user_password = "P\u{e4}ss\u{a0}w\u{f6}rd";
+    let mut doc = Document::load_with_password(path, "ownerIV").unwrap();
+    let page_numbers: Vec<u32> = doc.get_pages().keys().cloned().collect();
+    let text = doc.extract_text(&page_numbers).unwrap();
+    let permissions = doc.permissions().unwrap();
+    let file_id = doc.trailer.get(b"ID").unwrap().as_array().unwrap()[0].clone();
+    let mut info = Dictionary::new();
+    info.set("Title", Object::String(b"Quarterly review".to_vec(), StringFormat::Literal));
+    let info_id = doc.add_object(info);
+    doc.trailer.set("Info", info_id);
+    let mut bytes = Vec::new();
+    doc.save_with_options(&mut bytes, preserve()).unwrap();
+    assert!(!bytes.windows(b"Quarterly review".len()).any(|window| window == b"Quarterly review"));
+
+    let raw = Document::load_mem(&bytes).unwrap();
+    assert!(raw.is_encrypted(), "the user password is needed");
+    let encrypt = raw.get_encrypted().unwrap();
+    assert_eq!(encrypt.get(b"V").unwrap(), &Object::Integer(5));
+    assert_eq!(encrypt.get(b"R").unwrap(), &Object::Integer(6));
+    assert_eq!(raw.trailer.get(b"ID").unwrap().as_array().unwrap()[0], file_id);
+    for password in [user_password, "ownerIV"] {
+        let reloaded = Document::load_mem_with_password(&bytes, password).unwrap();
+        assert_eq!(reloaded.extract_text(&page_numbers).unwrap(), text, "opened with {password}");
+        assert_eq!(info_title(&reloaded), b"Quarterly review");
+        assert_eq!(reloaded.permissions(), Some(permissions));
+    }
+
+    // AES-128, revision 4, with the empty user password.
+    let mut doc = Document::load("assets/encrypted.pdf").unwrap();
+    let page_numbers: Vec<u32> = doc.get_pages().keys().cloned().collect();
+    let text = doc.extract_text(&page_numbers).unwrap();
+    let permissions = doc.permissions().unwrap();
+    let mut bytes = Vec::new();
+    doc.save_with_options(&mut bytes, preserve()).unwrap();
+    let reloaded = Document::load_mem(&bytes).unwrap();
+    assert!(reloaded.was_encrypted());
+    assert_eq!(reloaded.authentication_result(), Some(PasswordKind::Empty));
+    assert_eq!(reloaded.permissions(), Some(permissions));
+    assert_eq!(reloaded.extract_text(&page_numbers).unwrap(), text);
+    assert!(permissions.contains(Permissions::PRINTABLE));
+
+    // The keys of revision 4 are derived from the file identifier, which is put back if changed.
+    let file_id = doc.trailer.get(b"ID").unwrap().as_array().unwrap()[0].clone();
+    doc.trailer.remove(b"ID");
+    doc.ensure_file_id();
+    let mut bytes = Vec::new();
+    doc.save_with_options(&mut bytes, preserve()).unwrap();
+    let reloaded = Document::load_mem(&bytes).unwrap();
+    let reloaded_id = reloaded.trailer.get(b"ID").unwrap().as_array().unwrap()[0].clone();
+    assert_eq!(reloaded_id.as_str().unwrap(), file_id.as_str().unwrap());
+    assert_eq!(reloaded.extract_text(&page_numbers).unwrap(), text);
+    assert_ne!(doc.trailer.get(b"ID").unwrap().as_array().unwrap()[0], file_id);
+
+    // Saving without the option writes the document decrypted, as before.
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).unwrap();
+    assert!(!Document::load_mem(&bytes).unwrap().was_encrypted());
+}
@@ -1,5 +1,8 @@
 use lopdf::content::{Content, Operation};
-use lopdf::{Document, Object, Stream, StringFormat, dictionary};
+use lopdf::{
+    Dictionary, Document, Encoding, ExtractionOptions, MappingSource, Object, ObjectId, Stream, StringFormat,
+    dictionary,
+};
 
 // These ToUnicode CMaps are extracted from a real PDF which I have that have these
 // `0 beginbfrange ... endbfrange` sections. Historically (i.e version 0.38.0) this could trigger parse
@@ -104,8 +107,6 @@
 fn build_doc_with_tounicode(tounicode: &str, encoded_text: Vec<u8>) -> Document {
     let mut doc = Document::with_version("1.5");
 
-    let pages_id = doc.new_object_id();
-
     let tounicode_bytes = tounicode.as_bytes().to_vec();
     let tounicode_stream_id = doc.add_object(Stream::new(
         dictionary! { "Length" => tounicode_bytes.len() as i64 },
@@ -120,6 +121,13 @@ fn build_doc_with_tounicode(tounicode: &str, encoded_text: Vec<u8>) -> Document
         "ToUnicode" => Object::Reference(tounicode_stream_id),
     });
 
+    build_doc_with_font(doc, font_id, encoded_text)
+}
+
+/// Add a page showing `encoded_text` in the font `font_id` to `doc`.
+fn build_doc_with_font(mut doc: Document, font_id: ObjectId, encoded_text: Vec<u8>) -> Document {
+    let pages_id = doc.new_object_id();
+
     let resources_id = doc.add_object(dictionary! {
         "Font" => dictionary! {
             "F1" => font_id,
@@ -208,4 +216,170 @@ fn extract_text_does_not_error_with_empty_bfrange_font1() {
 
     let text = doc.extract_text(&[1]).expect("extract_text should not error");
     assert_eq!(text.trim_end(), " #%7a");
-}
\ No newline at end of file
+}
+
+#[test]
+fn extract_text_replaces_lone_surrogate_once() {
+    // <0001> maps to an unpaired high surrogate, <0002> to a surrogate pair.
+    let tounicode = FONT1_TOUNICODE.replace(
+        "1 beginbfchar\n<0033> <0050>\nendbfchar",
+        "3 beginbfchar\n<0001> <D800>\n<0002> <D83DDE00>\n<0033> <0050>\nendbfchar",
+    );
+    let encoded_text = vec![0x00, 0x01, 0x00, 0x02, 0x00, 0x33];
+    let doc = build_doc_with_tounicode(&tounicode, encoded_text.clone());
+
+    let text = doc.extract_text(&[1]).unwrap();
+    assert_eq!(text.trim_end(), "\u{FFFD}😀P");
+    assert_eq!(text.matches('\u{FFFD}').count(), 1);
+
+    let page_id = doc.get_pages()[&1];
+    let fonts = doc.get_page_fonts(page_id).unwrap();
+    let encoding = fonts[b"F1".as_slice()].get_font_encoding(&doc).unwrap();
+    let decoded = encoding.decode(&encoded_text).unwrap();
+    assert_eq!(decoded.text, "\u{FFFD}😀P");
+    assert_eq!(decoded.replacements, 1);
+}
+
+#[test]
+fn extract_text_resolves_subset_glyph_names() {
+    // A subset of Montserrat whose /Differences name glyphs by their index.
+    let font_file = std::fs::read("./tests/resources/fonts/Montserrat-Regular.ttf").unwrap();
+    let face = ttf_parser::Face::parse(&font_file, 0).unwrap();
+    let glyph = |ch| format!("g{}", face.glyph_index(ch).unwrap().0);
+    let differences: Vec<Object> = vec![
+        1.into(),
+        Object::Name(glyph('H').into_bytes()),
+        Object::Name(glyph('i').into_bytes()),
+        Object::Name(b"exclam".to_vec()),
+        65.into(),
+        Object::Name(b"opaque".to_vec()),
+    ];
+
+    let mut doc = Document::with_version("1.5");
+    let font_file_id = doc.add_object(Stream::new(dictionary! {}, font_file.clone()));
+    let descriptor_id = doc.add_object(dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => "ABCDEF+Montserrat-Regular",
+        "FontFile2" => font_file_id,
+    });
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "TrueType",
+        "BaseFont" => "ABCDEF+Montserrat-Regular",
+        "FontDescriptor" => descriptor_id,
+        "Encoding" => dictionary! {
+            "Type" => "Encoding",
+            "BaseEncoding" => "WinAnsiEncoding",
+            "Differences" => differences,
+        },
+    });
+    // The opaque name at code 65 keeps the "A" of the base encoding.
+    let doc = build_doc_with_font(doc, font_id, vec![1, 2, 3, 65]);
+
+    let text = doc.extract_text(&[1]).unwrap();
+    assert_eq!(text.trim_end(), "Hi!A");
+
+    let fonts = doc.get_page_fonts(doc.get_pages()[&1]).unwrap();
+    let encoding = fonts[b"F1".as_slice()].get_font_encoding(&doc).unwrap();
+    let Encoding::DifferencesEncoding(encoding) = &encoding else {
+        panic!("{encoding:?}");
+    };
+    assert_eq!(encoding.unresolved_names, 1);
+    assert_eq!(encoding.font_program_names, 2);
+    assert_eq!(encoding.encode("Hi!"), [1, 2, 3]);
+
+    let (_, traces) = doc.extract_text_with_trace(&[1], &ExtractionOptions::default()).unwrap();
+    assert_eq!(traces[0].fonts[0].source, MappingSource::FontProgram);
+    assert_eq!(traces[0].fonts[0].unmapped_glyphs, 0);
+}
+
+#[test]
+fn extraction_trace_names_mapping_sources() {
+    // <0024> <0025> are "AB" in the ToUnicode CMap of F1.
+    let mut doc = build_doc_with_tounicode(FONT2_TOUNICODE, vec![0x00, 0x24, 0x00, 0x25]);
+    let differences_font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => dictionary! {
+            "Type" => "Encoding",
+            "Differences" => vec![1.into(), "C".into(), "D".into()],
+        },
+    });
+    let form_content = b"BT /F2 12 Tf <0102> Tj /F9 12 Tf (lost) Tj ET".to_vec();
+    let form_id = doc.add_object(Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "Resources" => dictionary! { "Font" => dictionary! { "F2" => differences_font_id } },
+        },
+        form_content,
+    ));
+    let page_id = doc.get_pages()[&1];
+    let resources_id = doc.get_page_resources(page_id).unwrap().1[0];
+    doc.get_dictionary_mut(resources_id)
+        .unwrap()
+        .set("XObject", dictionary! { "Form" => form_id });
+    doc.add_page_contents(page_id, b"\n/Form Do".to_vec()).unwrap();
+
+    let (text, traces) = doc.extract_text_with_trace(&[1], &ExtractionOptions::default()).unwrap();
+    assert_eq!(text, "AB\nCD\n");
+    assert_eq!(text, doc.extract_text(&[1]).unwrap());
+    assert_eq!(traces.len(), 1);
+    let trace = &traces[0];
+    assert_eq!(trace.page_number, 1);
+    let sources: Vec<_> = trace.fonts.iter().map(|font| (font.name.as_slice(), &font.source)).collect();
+    assert_eq!(sources, [(b"F1".as_slice(), &MappingSource::ToUnicode), (b"F2", &MappingSource::Differences)]);
+    assert!(trace.fonts.iter().all(|font| font.unmapped_glyphs == 0));
+    assert_eq!(trace.fonts[1].font_id, Some(differences_font_id));
+    assert_eq!(trace.fonts[1].base_font.as_deref(), Some(b"Helvetica".as_slice()));
+    assert_eq!(trace.forms, [form_id]);
+    let skipped: Vec<_> = trace.skipped.iter().map(|skipped| (skipped.operator.as_str(), skipped.form_id)).collect();
+    assert_eq!(skipped, [("Tf", Some(form_id)), ("Tj", Some(form_id))]);
+    assert_eq!(trace.skipped[0].reason, "font /F9 not found");
+}
+
+#[test]
+fn extraction_descends_into_forms_as_deep_as_the_options_allow() {
+    // The page shows "AB", the form it draws shows "A" and the form that one draws shows "B".
+    let mut doc = build_doc_with_tounicode(FONT2_TOUNICODE, vec![0x00, 0x24, 0x00, 0x25]);
+    let page_id = doc.get_pages()[&1];
+    let resources_id = doc.get_page_resources(page_id).unwrap().1[0];
+    let font_id = doc.get_dictionary(resources_id).unwrap().get_deref(b"Font", &doc).unwrap().clone();
+    let mut add_form = |content: &[u8], xobjects: Dictionary| {
+        doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+                "Resources" => dictionary! { "Font" => font_id.clone(), "XObject" => xobjects },
+            },
+            content.to_vec(),
+        ))
+    };
+    let inner_id = add_form(b"BT /F1 12 Tf <0025> Tj ET", Dictionary::new());
+    let outer_id = add_form(b"BT /F1 12 Tf <0024> Tj ET /Inner Do", dictionary! { "Inner" => inner_id });
+    doc.get_dictionary_mut(resources_id)
+        .unwrap()
+        .set("XObject", dictionary! { "Outer" => outer_id });
+    doc.add_page_contents(page_id, b"\n/Outer Do".to_vec()).unwrap();
+
+    let text_at = |max_form_depth| {
+        let options = ExtractionOptions { max_form_depth, ..Default::default() };
+        doc.extract_text_with_options(&[1], &options).unwrap()
+    };
+    assert_eq!(text_at(Some(0)), "AB\n");
+    assert_eq!(text_at(Some(1)), "AB\nA\n");
+    assert_eq!(text_at(None), "AB\nA\nB\n");
+    assert_eq!(text_at(None), doc.extract_text(&[1]).unwrap());
+}
+
+#[test]
+fn extraction_trace_counts_unmapped_glyphs() {
+    // <0001> isn't in the ToUnicode CMap.
+    let doc = build_doc_with_tounicode(FONT2_TOUNICODE, vec![0x00, 0x24, 0x00, 0x25, 0x00, 0x01]);
+    let (text, traces) = doc.extract_text_with_trace(&[1], &ExtractionOptions::default()).unwrap();
+    assert_eq!(text.trim_end(), "AB\u{FFFD}");
+    assert_eq!(traces[0].fonts[0].unmapped_glyphs, 1);
+}
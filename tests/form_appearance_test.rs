@@ -0,0 +1,178 @@
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, SaveOptions, Stream};
+
+fn appearance_stream(doc: &mut Document, content: &[u8]) -> ObjectId {
+    doc.add_object(Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => vec![0.into(), 0.into(), 20.into(), 20.into()],
+        },
+        content.to_vec(),
+    ))
+}
+
+/// Build a one-page form with a merged checkbox field/widget and a text field with two kid widgets.
+fn create_form_document() -> (Document, Vec<ObjectId>) {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let page_id = doc.new_object_id();
+
+    let on = appearance_stream(&mut doc, b"0 0 20 20 re f");
+    let off = appearance_stream(&mut doc, b"0 0 20 20 re S");
+    let down = appearance_stream(&mut doc, b"1 0 0 rg 0 0 20 20 re f");
+    let checkbox_id = doc.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "FT" => "Btn",
+        "T" => Object::string_literal("agree"),
+        "Rect" => vec![10.into(), 10.into(), 30.into(), 30.into()],
+        "P" => page_id,
+        "AS" => "Off",
+        "AP" => dictionary! {
+            "N" => dictionary! { "Yes" => on, "Off" => off },
+            "D" => dictionary! { "Yes" => down, "Off" => off },
+        },
+    });
+
+    let text_id = doc.new_object_id();
+    let mut kids = vec![];
+    for y in [50, 80] {
+        let normal = appearance_stream(&mut doc, b"/Tx BMC EMC");
+        let kid_id = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "Parent" => text_id,
+            "Rect" => vec![10.into(), y.into(), 110.into(), (y + 20).into()],
+            "P" => page_id,
+            "AP" => dictionary! { "N" => normal },
+        });
+        kids.push(kid_id);
+    }
+    doc.objects.insert(
+        text_id,
+        Object::Dictionary(dictionary! {
+            "FT" => "Tx",
+            "T" => Object::string_literal("name"),
+            "Kids" => kids.iter().copied().map(Object::Reference).collect::<Vec<_>>(),
+        }),
+    );
+
+    let mut widgets = vec![checkbox_id];
+    widgets.extend(&kids);
+    doc.objects.insert(
+        page_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            "Annots" => widgets.iter().copied().map(Object::Reference).collect::<Vec<_>>(),
+        }),
+    );
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        "AcroForm" => dictionary! {
+            "Fields" => vec![checkbox_id.into(), text_id.into()],
+        },
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    (doc, widgets)
+}
+
+fn assert_appearance_resolves(doc: &Document, ap: &Dictionary, key: &[u8]) {
+    let entry = ap.get(key).unwrap();
+    let streams: Vec<&Object> = match doc.dereference(entry).unwrap().1 {
+        Object::Dictionary(states) => states.iter().map(|(_, state)| state).collect(),
+        stream => vec![stream],
+    };
+    assert!(!streams.is_empty());
+    for stream in streams {
+        let stream = doc.dereference(stream).unwrap().1.as_stream().unwrap();
+        let bbox = stream.dict.get(b"BBox").and_then(Object::as_array).unwrap();
+        assert_eq!(bbox.len(), 4);
+        assert_eq!(bbox[2].as_float().unwrap(), 20.0);
+    }
+}
+
+#[test]
+fn appearance_streams_survive_object_stream_save() {
+    let (mut doc, widgets) = create_form_document();
+
+    // Fill the form before saving.
+    let checkbox = doc.get_dictionary_mut(widgets[0]).unwrap();
+    checkbox.set("V", "Yes");
+    checkbox.set("AS", "Yes");
+
+    let mut buffer = Vec::new();
+    doc.save_with_options(&mut buffer, SaveOptions::builder().use_object_streams(true).build().unwrap())
+        .unwrap();
+
+    let mut modern = Vec::new();
+    Document::load_mem(&buffer).unwrap().save_modern(&mut modern).unwrap();
+
+    for buffer in [buffer, modern] {
+        let loaded = Document::load_mem(&buffer).unwrap();
+        let page_id = *loaded.get_pages().get(&1).unwrap();
+        let annotations = loaded.get_page_annotations(page_id).unwrap();
+        assert_eq!(annotations.len(), widgets.len());
+
+        for widget in annotations {
+            let ap = loaded.get_dict_in_dict(widget, b"AP").unwrap();
+            assert_appearance_resolves(&loaded, ap, b"N");
+
+            if let Ok(state) = widget.get(b"AS").and_then(Object::as_name) {
+                assert_eq!(state, b"Yes");
+                let normal = loaded.get_dict_in_dict(ap, b"N").unwrap();
+                assert!(normal.get(state).is_ok());
+                assert_appearance_resolves(&loaded, ap, b"D");
+            }
+        }
+    }
+}
+
+#[test]
+fn appearance_streams_with_ids_above_max_id_survive_object_stream_save() {
+    let (mut doc, widgets) = create_form_document();
+
+    // Form fillers often allocate ids by hand without bumping `max_id`.
+    let on_id = (doc.max_id + 1, 0);
+    let off_id = (doc.max_id + 2, 0);
+    for (id, content) in [(on_id, b"0 0 20 20 re f"), (off_id, b"0 0 20 20 re S")] {
+        let stream = Stream::new(
+            dictionary! {
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 20.into(), 20.into()],
+            },
+            content.to_vec(),
+        );
+        doc.objects.insert(id, Object::Stream(stream));
+    }
+    let checkbox = doc.get_dictionary_mut(widgets[0]).unwrap();
+    checkbox.set("AP", dictionary! { "N" => dictionary! { "Option A" => on_id, "Off" => off_id } });
+    checkbox.set("AS", "Option A");
+    let kid = doc.get_dictionary_mut(widgets[1]).unwrap();
+    kid.set("AP", dictionary! { "N" => on_id });
+
+    let mut buffer = Vec::new();
+    doc.save_modern(&mut buffer).unwrap();
+
+    let loaded = Document::load_mem(&buffer).unwrap();
+    for &widget_id in &widgets {
+        let widget = loaded.get_dictionary(widget_id).unwrap();
+        let ap = loaded.get_dict_in_dict(widget, b"AP").unwrap();
+        assert_appearance_resolves(&loaded, ap, b"N");
+    }
+    let checkbox = loaded.get_dictionary(widgets[0]).unwrap();
+    let state = checkbox.get(b"AS").and_then(Object::as_name).unwrap();
+    let normal = loaded.get_dict_in_dict(loaded.get_dict_in_dict(checkbox, b"AP").unwrap(), b"N").unwrap();
+    assert_eq!(normal.get(state).and_then(Object::as_reference).unwrap(), on_id);
+}
@@ -0,0 +1,58 @@
+#![cfg(target_os = "linux")]
+
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use lopdf::{Document, Object, Stream, dictionary};
+
+/// A field of `/proc/self/status` in bytes, such as `VmRSS` or `VmHWM`.
+fn memory_status(field: &str) -> usize {
+    let status = fs::read_to_string("/proc/self/status").unwrap();
+    let line = status.lines().find(|line| line.starts_with(field)).unwrap();
+    let kilobytes: usize = line.split_whitespace().nth(1).unwrap().parse().unwrap();
+    kilobytes * 1024
+}
+
+/// The growth of the peak resident set size while running `load`, or `None` if the peak can't be
+/// reset.
+fn peak_growth(load: impl FnOnce() -> Document) -> Option<(usize, Document)> {
+    fs::write("/proc/self/clear_refs", "5").ok()?;
+    let before = memory_status("VmRSS:");
+    let doc = load();
+    Some((memory_status("VmHWM:").saturating_sub(before), doc))
+}
+
+#[test]
+fn loading_from_a_seekable_source_keeps_the_file_out_of_memory() {
+    // A stream of 48 MiB, stored as is, makes up most of the file. Allocations that large are
+    // returned to the system when freed, so each load starts from the same resident set.
+    let data: Vec<u8> = (0..48 << 20)
+        .map(|i: u32| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect();
+    let mut doc = Document::with_version("1.5");
+    let image = doc.add_object(Stream::new(dictionary! {}, data));
+    let pages = doc.add_object(dictionary! { "Type" => "Pages", "Kids" => Vec::<Object>::new(), "Count" => 0 });
+    let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages, "Image" => image });
+    doc.trailer.set("Root", catalog);
+    let file = tempfile::NamedTempFile::new().unwrap();
+    doc.save(file.path()).unwrap();
+    drop(doc);
+
+    let Some((whole_growth, whole)) = peak_growth(|| Document::load_mem(&fs::read(file.path()).unwrap()).unwrap())
+    else {
+        eprintln!("the peak resident set size can't be reset, skipping");
+        return;
+    };
+    let whole_objects = whole.objects.clone();
+    drop(whole);
+    let (seekable_growth, seekable) =
+        peak_growth(|| Document::load_seekable(BufReader::new(File::open(file.path()).unwrap())).unwrap()).unwrap();
+
+    assert_eq!(seekable.objects, whole_objects);
+    assert!(matches!(seekable.get_object(image), Ok(Object::Stream(_))));
+    // The whole file and the stream read from it against the stream alone.
+    assert!(
+        seekable_growth * 3 < whole_growth * 2,
+        "peak grew by {seekable_growth} bytes loading object by object, {whole_growth} bytes loading the whole file"
+    );
+}
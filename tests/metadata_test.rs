@@ -1,4 +1,4 @@
-use lopdf::Document;
+use lopdf::{dictionary, Document, MetadataOptions, ObjectId, PageDimensions, RepairAction};
 
 #[test]
 fn test_metadata_extraction_basic() {
@@ -21,6 +21,69 @@ fn test_metadata_extraction_page_count() {
     assert_eq!(metadata.page_count, pages.len() as u32);
 }
 
+#[test]
+fn test_metadata_page_dimensions() {
+    let buffer = std::fs::read("assets/example.pdf").unwrap();
+    assert!(Document::load_metadata_mem(&buffer).unwrap().page_dimensions.is_empty());
+    let options = MetadataOptions {
+        page_dimensions: true,
+        ..MetadataOptions::default()
+    };
+    let metadata = Document::load_metadata_mem_with_options(&buffer, &options).unwrap();
+    let expected = PageDimensions {
+        width: 595.0,
+        height: 842.0,
+        rotation: 0,
+    };
+    assert_eq!(metadata.page_dimensions, [expected]);
+}
+
+#[test]
+fn test_metadata_page_dimensions_inherited() {
+    let mut doc = Document::with_version("1.5");
+    let root_id = doc.new_object_id();
+    let node_id = doc.new_object_id();
+    let page = |parent: ObjectId| dictionary! { "Type" => "Page", "Parent" => parent };
+    let first = doc.add_object(page(node_id));
+    let mut own_box = page(node_id);
+    own_box.set("MediaBox", vec![0.into(), 0.into(), 200.into(), 100.into()]);
+    own_box.set("Rotate", -90);
+    let second = doc.add_object(own_box);
+    let third = doc.add_object(page(root_id));
+    let node = dictionary! {
+        "Type" => "Pages",
+        "Parent" => root_id,
+        "Kids" => vec![first.into(), second.into()],
+        "Count" => 2,
+        "Rotate" => 90,
+    };
+    doc.objects.insert(node_id, node.into());
+    let root = dictionary! {
+        "Type" => "Pages",
+        "Kids" => vec![node_id.into(), third.into()],
+        "Count" => 3,
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+    };
+    doc.objects.insert(root_id, root.into());
+    let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => root_id });
+    doc.trailer.set("Root", catalog);
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    let options = MetadataOptions {
+        page_dimensions: true,
+        ..MetadataOptions::default()
+    };
+    let metadata = Document::load_metadata_mem_with_options(&buffer, &options).unwrap();
+    let dimensions = |width, height, rotation| PageDimensions { width, height, rotation };
+    let expected = [
+        dimensions(595.0, 842.0, 90),
+        dimensions(200.0, 100.0, 270),
+        dimensions(595.0, 842.0, 0),
+    ];
+    assert_eq!(metadata.page_dimensions, expected);
+}
+
 #[test]
 fn test_metadata_extraction_unicode() {
     let buffer = std::fs::read("assets/unicode.pdf").unwrap();
@@ -86,6 +149,8 @@ fn test_metadata_extraction_encrypted_empty_password() {
         "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()]
     };
     doc.objects.insert((3, 0), lopdf::Object::Dictionary(page_dict));
+    // Keep the info dictionary from taking the place of the page tree.
+    doc.max_id = 3;
 
     let info_dict = lopdf::dictionary! {
         "Title" => lopdf::Object::String(b"Test Encrypted PDF".to_vec(), lopdf::StringFormat::Literal),
@@ -152,6 +217,8 @@ fn test_metadata_extraction_encrypted_with_password() {
         "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()]
     };
     doc.objects.insert((3, 0), lopdf::Object::Dictionary(page_dict));
+    // Keep the info dictionary from taking the place of the page tree.
+    doc.max_id = 3;
 
     let info_dict = lopdf::dictionary! {
         "Title" => lopdf::Object::String(b"Password Protected PDF".to_vec(), lopdf::StringFormat::Literal),
@@ -242,3 +309,197 @@ fn test_metadata_extraction_encrypted_wrong_password() {
     assert!(result.is_err());
     assert!(matches!(result.unwrap_err(), lopdf::Error::InvalidPassword));
 }
+
+#[test]
+fn test_metadata_encryption_without_password() {
+    let mut doc = Document::with_version("1.5");
+    doc.trailer.set(
+        "ID",
+        vec![
+            lopdf::Object::string_literal(vec![1u8; 16]),
+            lopdf::Object::string_literal(vec![2u8; 16]),
+        ],
+    );
+    let pages_id = doc.new_object_id();
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()]
+    });
+    doc.objects.insert(
+        pages_id,
+        dictionary! { "Type" => "Pages", "Kids" => vec![page_id.into()], "Count" => 1 }.into(),
+    );
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+    let info_id = doc.add_object(dictionary! { "Title" => lopdf::Object::string_literal("Locked") });
+    doc.trailer.set("Info", info_id);
+
+    let permissions = lopdf::Permissions::PRINTABLE | lopdf::Permissions::COPYABLE;
+    let encryption_version = lopdf::EncryptionVersion::V2 {
+        document: &doc,
+        owner_password: "owner",
+        user_password: "user",
+        key_length: 128,
+        permissions,
+    };
+    let encryption_state = lopdf::EncryptionState::try_from(encryption_version).unwrap();
+    doc.encrypt(&encryption_state).unwrap();
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    let metadata = Document::load_metadata_mem(&buffer).unwrap();
+    assert!(metadata.is_encrypted);
+    assert_eq!(metadata.title, None);
+    assert_eq!(metadata.page_count, 1);
+    let encryption = metadata.encryption.unwrap();
+    assert_eq!(encryption.filter, "Standard");
+    assert_eq!((encryption.v, encryption.r, encryption.key_length), (2, 3, Some(128)));
+    assert!(encryption.permissions.contains(permissions));
+    assert!(!encryption.permissions.contains(lopdf::Permissions::MODIFIABLE));
+
+    let options = MetadataOptions {
+        require_decryption: true,
+        ..MetadataOptions::default()
+    };
+    assert!(Document::load_metadata_mem_with_options(&buffer, &options).is_err());
+    let metadata = Document::load_metadata_mem_with_password(&buffer, "user").unwrap();
+    assert!(metadata.is_encrypted);
+    assert_eq!(metadata.title.as_deref(), Some("Locked"));
+
+    let metadata = Document::load_metadata_mem(&std::fs::read("assets/example.pdf").unwrap()).unwrap();
+    assert!(!metadata.is_encrypted && metadata.encryption.is_none());
+}
+
+#[test]
+fn test_metadata_page_count_estimate_with_broken_page_tree() {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    for _ in 0..3 {
+        doc.add_object(lopdf::dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()]
+        });
+    }
+    // The catalog points at a Pages object that is never written.
+    let catalog_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    let metadata = Document::load_metadata_mem(&buffer).unwrap();
+    assert_eq!(metadata.page_count, 3);
+    assert!(metadata.page_count_is_estimate);
+
+    // Only the first cross-reference entries are scanned with a lower limit.
+    let options = MetadataOptions {
+        page_estimate_scan_limit: Some(2),
+        ..MetadataOptions::default()
+    };
+    let metadata = Document::load_metadata_mem_with_options(&buffer, &options).unwrap();
+    assert_eq!(metadata.page_count, 2);
+    assert!(metadata.page_count_is_estimate);
+
+    let loaded = Document::load_mem(&buffer).unwrap();
+    assert!(loaded.get_pages().is_empty());
+    assert_eq!(loaded.count_page_objects(), 3);
+}
+
+#[test]
+fn test_metadata_page_count_exact_for_valid_page_tree() {
+    let buffer = std::fs::read("assets/example.pdf").unwrap();
+    let metadata = Document::load_metadata_mem(&buffer).unwrap();
+    assert!(!metadata.page_count_is_estimate);
+
+    let doc = Document::load_mem(&buffer).unwrap();
+    assert_eq!(doc.count_page_objects(), metadata.page_count);
+}
+
+#[test]
+fn test_metadata_prefers_xmp_over_info() {
+    let xmp = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmlns:pdf="http://ns.adobe.com/pdf/1.3/"
+    xmp:CreateDate="2024-03-01T09:15:00Z" pdf:Producer="XMP Producer">
+  <dc:title><rdf:Alt><rdf:li xml:lang="x-default">XMP Title</rdf:li></rdf:Alt></dc:title>
+</rdf:Description>
+</rdf:RDF></x:xmpmeta>
+<?xpacket end="w"?>"#;
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.add_object(dictionary! { "Type" => "Pages", "Kids" => vec![], "Count" => 0 });
+    let mut metadata = lopdf::Stream::new(dictionary! { "Type" => "Metadata", "Subtype" => "XML" }, xmp.into());
+    metadata.compress().unwrap();
+    let metadata_id = doc.add_object(metadata);
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        "Metadata" => metadata_id,
+    });
+    let info_id = doc.add_object(dictionary! {
+        "Title" => lopdf::Object::string_literal("Info Title"),
+        "Author" => lopdf::Object::string_literal("Info Author"),
+        "Producer" => lopdf::Object::string_literal("Info Producer"),
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.trailer.set("Info", info_id);
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    let metadata = Document::load_metadata_mem(&buffer).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Info Title"));
+    assert_eq!(metadata.xmp, None);
+
+    let options = MetadataOptions {
+        xmp: true,
+        ..MetadataOptions::default()
+    };
+    let metadata = Document::load_metadata_mem_with_options(&buffer, &options).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("XMP Title"));
+    assert_eq!(metadata.author.as_deref(), Some("Info Author"));
+    assert_eq!(metadata.producer.as_deref(), Some("XMP Producer"));
+    assert_eq!(metadata.creation_date.as_deref(), Some("D:20240301091500Z"));
+    assert_eq!(metadata.xmp.unwrap().title.as_deref(), Some("XMP Title"));
+
+    let loaded = Document::load_mem(&buffer).unwrap();
+    assert_eq!(loaded.xmp_metadata().unwrap().unwrap().producer.as_deref(), Some("XMP Producer"));
+}
+
+#[test]
+fn test_metadata_page_count_ignores_overstated_count() {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let kids: Vec<lopdf::Object> = (0..3)
+        .map(|_| doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id }).into())
+        .collect();
+    let pages = dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => 1_000_000 };
+    doc.objects.insert(pages_id, pages.into());
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+
+    let metadata = Document::load_metadata_mem(&buffer).unwrap();
+    assert_eq!(metadata.page_count, 3);
+    assert!(metadata.page_count_mismatch && !metadata.page_count_is_estimate);
+
+    let loaded = Document::load_mem(&buffer).unwrap();
+    assert_eq!(
+        loaded.repairs,
+        [RepairAction::CorrectedPageCount {
+            node: pages_id,
+            declared: 1_000_000,
+            found: 3
+        }]
+    );
+    assert_eq!(loaded.get_pages().len(), 3);
+    assert_eq!(loaded.page_iter().size_hint(), (3, Some(3)));
+
+    let metadata = Document::load_metadata_mem(&std::fs::read("assets/example.pdf").unwrap()).unwrap();
+    assert!(!metadata.page_count_mismatch);
+}
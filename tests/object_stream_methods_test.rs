@@ -69,12 +69,11 @@ fn test_save_options_with_object_streams() {
     doc.trailer.set("Root", catalog_id);
     
     // Save with object streams enabled
-    let save_options = SaveOptions {
-        use_object_streams: true,
-        use_xref_streams: false,
-        linearize: false,
-        object_stream_config: Default::default(),
-    };
+    let save_options = SaveOptions::builder()
+        .use_object_streams(true)
+        .use_xref_streams(false)
+        .build()
+        .unwrap();
     
     let mut buffer = Vec::new();
     doc.save_with_options(&mut buffer, save_options).unwrap();
@@ -114,12 +113,11 @@ fn test_save_options_without_object_streams() {
     doc.trailer.set("Root", catalog_id);
     
     // Save without object streams
-    let save_options = SaveOptions {
-        use_object_streams: false,
-        use_xref_streams: false,
-        linearize: false,
-        object_stream_config: Default::default(),
-    };
+    let save_options = SaveOptions::builder()
+        .use_object_streams(false)
+        .use_xref_streams(false)
+        .build()
+        .unwrap();
     
     let mut buffer = Vec::new();
     doc.save_with_options(&mut buffer, save_options).unwrap();
@@ -92,7 +92,8 @@ fn test_save_performance_with_trailer_objects() {
     // Measure save performance
     let options = SaveOptions::builder()
         .use_object_streams(true)
-        .build();
+        .build()
+        .unwrap();
     
     let start = Instant::now();
     let mut output = Vec::new();
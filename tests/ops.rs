@@ -0,0 +1,134 @@
+use std::fs;
+
+use lopdf::ops::{self, Input, Output};
+use lopdf::{Document, Error, Object, PieceInfoTarget, SaveOptions, Stream, dictionary};
+
+#[test]
+fn merge_appends_pages_of_all_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("merged.pdf");
+    let report = ops::merge(["assets/example.pdf", "assets/unicode.pdf"], output.as_path()).unwrap();
+
+    let example = Document::load_mem(&fs::read("assets/example.pdf").unwrap()).unwrap();
+    let unicode = Document::load_mem(&fs::read("assets/unicode.pdf").unwrap()).unwrap();
+    let expected_pages = example.get_pages().len() + unicode.get_pages().len();
+    assert_eq!(report.pages, expected_pages);
+    assert_eq!(
+        report.bytes_in,
+        fs::metadata("assets/example.pdf").unwrap().len() + fs::metadata("assets/unicode.pdf").unwrap().len()
+    );
+    assert_eq!(report.bytes_out, fs::metadata(&output).unwrap().len());
+
+    let merged = Document::load_mem(&fs::read(&output).unwrap()).unwrap();
+    assert_eq!(merged.get_pages().len(), expected_pages);
+    let last_page = expected_pages as u32;
+    assert_eq!(
+        merged.extract_text(&[last_page]).unwrap(),
+        unicode.extract_text(&[unicode.get_pages().len() as u32]).unwrap()
+    );
+}
+
+/// `assets/example.pdf` with private data of `application` on its first page and its catalog.
+fn with_piece_info(application: &str) -> Vec<u8> {
+    let mut doc = Document::load_mem(&fs::read("assets/example.pdf").unwrap()).unwrap();
+    let blob_id = doc.add_object(Stream::new(dictionary! {}, format!("{application} page data").into_bytes()));
+    let page_id = doc.page_iter().next().unwrap();
+    let page_data = dictionary! { application => dictionary! { "Private" => blob_id } };
+    doc.get_dictionary_mut(page_id).unwrap().set("PieceInfo", page_data);
+    let catalog_data = dictionary! { application => dictionary! { "Private" => Object::string_literal(application) } };
+    doc.catalog_mut().unwrap().set("PieceInfo", catalog_data);
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn merge_carries_piece_info_along() {
+    let (illustrator, indesign) = (with_piece_info("Illustrator"), with_piece_info("InDesign"));
+    let inputs = [
+        Input::reader("illustrator", illustrator.as_slice()),
+        Input::reader("indesign", indesign.as_slice()),
+    ];
+    let mut output = Vec::new();
+    ops::merge(inputs, Output::writer(&mut output)).unwrap();
+
+    let merged = Document::load_mem(&output).unwrap();
+    let first_pages = Document::load_mem(&illustrator).unwrap().get_pages().len() as u32;
+    for (page_number, application) in [(1, "Illustrator"), (first_pages + 1, "InDesign")] {
+        let page_id = merged.get_pages()[&page_number];
+        let entries = merged.get_piece_info(PieceInfoTarget::Page(page_id)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].application, application.as_bytes());
+        let blob_id = entries[0].private.as_ref().unwrap().as_reference().unwrap();
+        let blob = merged.get_object(blob_id).and_then(Object::as_stream).unwrap();
+        assert_eq!(blob.content, format!("{application} page data").as_bytes());
+    }
+    let catalog = merged.get_piece_info(PieceInfoTarget::Catalog).unwrap();
+    let applications: Vec<&[u8]> = catalog.iter().map(|entry| entry.application.as_slice()).collect();
+    assert_eq!(applications, [b"Illustrator".as_slice(), b"InDesign"]);
+}
+
+#[test]
+fn split_writes_one_file_per_page() {
+    let dir = tempfile::tempdir().unwrap();
+    let report = ops::split("assets/example.pdf", dir.path()).unwrap();
+
+    let example = Document::load_mem(&fs::read("assets/example.pdf").unwrap()).unwrap();
+    assert_eq!(report.pages, example.get_pages().len());
+    let mut written = 0;
+    for page in 1..=report.pages {
+        let bytes = fs::read(dir.path().join(format!("example-{page}.pdf"))).unwrap();
+        written += bytes.len() as u64;
+        let single = Document::load_mem(&bytes).unwrap();
+        assert_eq!(single.get_pages().len(), 1);
+        assert_eq!(
+            single.extract_text(&[1]).unwrap(),
+            example.extract_text(&[page as u32]).unwrap()
+        );
+    }
+    assert_eq!(report.bytes_out, written);
+}
+
+#[test]
+fn extract_text_reads_all_pages_from_reader() {
+    let bytes = fs::read("assets/example.pdf").unwrap();
+    let (text, report) = ops::extract_text(Input::reader("example", bytes.as_slice())).unwrap();
+    let example = Document::load_mem(&bytes).unwrap();
+    let pages: Vec<u32> = example.get_pages().keys().copied().collect();
+    assert_eq!(text, example.extract_text(&pages).unwrap());
+    assert_eq!(report.bytes_in, bytes.len() as u64);
+    assert_eq!(report.bytes_out, 0);
+}
+
+#[test]
+fn decrypt_writes_plain_copy() {
+    let mut output = Vec::new();
+    let report = ops::decrypt("assets/encrypted.pdf", "", Output::writer(&mut output)).unwrap();
+    assert_eq!(report.pages, 1);
+    assert_eq!(report.bytes_out, output.len() as u64);
+
+    let decrypted = Document::load_mem(&output).unwrap();
+    assert!(decrypted.trailer.get(b"Encrypt").is_err());
+    assert!(decrypted.encryption_state.is_none());
+    assert!(decrypted.extract_text(&[1]).unwrap().contains("USCIS"));
+
+    let err = ops::decrypt("assets/example.pdf", "", Output::writer(Vec::new())).unwrap_err();
+    assert!(matches!(&err, Error::Ops { source, .. } if matches!(**source, Error::NotEncrypted)));
+}
+
+#[test]
+fn compress_saves_with_options() {
+    let mut output = Vec::new();
+    let options = SaveOptions::builder().use_object_streams(true).use_xref_streams(true).build().unwrap();
+    let report = ops::compress("assets/example.pdf", Output::writer(&mut output), options).unwrap();
+    assert_eq!(report.bytes_out, output.len() as u64);
+    assert_eq!(Document::load_mem(&output).unwrap().get_pages().len(), report.pages);
+}
+
+#[test]
+fn errors_name_the_failing_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = ops::merge(["assets/example.pdf", "assets/missing.pdf"], dir.path().join("out.pdf").as_path())
+        .unwrap_err();
+    assert!(err.to_string().starts_with("assets/missing.pdf: "), "{err}");
+}
@@ -0,0 +1,104 @@
+use lopdf::content::{Content, Operation};
+use lopdf::{ContentWalker, Document, Object, ObjectId, Stream, dictionary};
+
+/// A document with a page for each of `media_boxes`, in Helvetica.
+fn document_with_media_boxes(media_boxes: &[[i64; 4]]) -> Document {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let kids: Vec<Object> = media_boxes
+        .iter()
+        .map(|media_box| {
+            let content_id = doc.add_object(Stream::new(dictionary! {}, Vec::new()));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => media_box.iter().copied().map(Object::from).collect::<Vec<_>>(),
+                "Contents" => content_id,
+                "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            })
+            .into()
+        })
+        .collect();
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+    doc
+}
+
+/// Stamp `text` with its start 100 points left of and 30 points below the visual top-right corner.
+fn stamp_top_right(doc: &mut Document, page_id: ObjectId, text: &str) {
+    let [llx, lly, urx, ury] = doc.page_media_box(page_id).unwrap();
+    let (x, y) = doc
+        .page_to_user_coords(page_id, (urx - llx - 100.0, ury - lly - 30.0))
+        .unwrap();
+    let content = Content {
+        operations: vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 12.into()]),
+            Operation::new("Td", vec![x.into(), y.into()]),
+            Operation::new("Tj", vec![Object::string_literal(text)]),
+            Operation::new("ET", vec![]),
+        ],
+    };
+    doc.add_page_contents(page_id, content.encode().unwrap()).unwrap();
+}
+
+/// The user space origin of the first glyph shown of `text` on the page.
+fn glyph_origin(doc: &Document, page_id: ObjectId, text: &str) -> Option<(f32, f32)> {
+    let mut position = (0.0, 0.0);
+    let mut origin = None;
+    ContentWalker::new(doc)
+        .walk_page(page_id, |operation, context| match operation.operator.as_str() {
+            "BT" => position = (0.0, 0.0),
+            "Td" => {
+                let operands: Vec<f32> = operation
+                    .operands
+                    .iter()
+                    .filter_map(|operand| operand.as_float().ok())
+                    .collect();
+                position = (position.0 + operands[0], position.1 + operands[1]);
+            }
+            "Tj" if operation.operands[0].as_str().ok() == Some(text.as_bytes()) => {
+                let [a, b, c, d, e, f] = context.ctm;
+                let (x, y) = position;
+                origin.get_or_insert((a * x + c * y + e, b * x + d * y + f));
+            }
+            _ => {}
+        })
+        .unwrap();
+    origin
+}
+
+#[test]
+fn stamp_lands_in_visual_top_right_quadrant() {
+    let mut doc = document_with_media_boxes(&[[-306, -396, 306, 396], [0, 842, 595, 0], [100, 200, 712, 992]]);
+    let page_ids: Vec<ObjectId> = doc.page_iter().collect();
+    for &page_id in &page_ids {
+        stamp_top_right(&mut doc, page_id, "TOP RIGHT");
+    }
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    let doc = Document::load_mem(&buffer).unwrap();
+
+    for page_id in page_ids {
+        let [llx, lly, urx, ury] = doc.page_media_box(page_id).unwrap();
+        let (width, height) = (urx - llx, ury - lly);
+        let origin = glyph_origin(&doc, page_id, "TOP RIGHT").unwrap();
+        assert!(llx <= origin.0 && origin.0 <= urx && lly <= origin.1 && origin.1 <= ury);
+        let (x, y) = doc.user_to_page_coords(page_id, origin).unwrap();
+        assert!(x > width / 2.0 && y > height / 2.0, "{page_id:?}: stamped at {x}, {y}");
+        assert_eq!((x, y), (width - 100.0, height - 30.0));
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::panic;
+
+use lopdf::{Object, parse};
+
+const CORPUS: &str = "tests/resources/parse_corpus";
+
+/// Run every parser of `lopdf::parse` on `bytes`, none of which may panic.
+fn parse_all(bytes: &[u8]) {
+    let _ = parse::object(bytes);
+    let _ = parse::dictionary(bytes);
+    let _ = parse::content(bytes);
+    if let Ok((_, start)) = parse::object_id_header(bytes) {
+        assert!(start <= bytes.len());
+        let _ = parse::object(&bytes[start..]);
+    }
+}
+
+fn corpus() -> Vec<(String, Vec<u8>)> {
+    let mut corpus: Vec<_> = fs::read_dir(CORPUS)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            (
+                path.file_name().unwrap().to_string_lossy().into_owned(),
+                fs::read(&path).unwrap(),
+            )
+        })
+        .collect();
+    corpus.sort();
+    corpus
+}
+
+/// A xorshift generator, so that a failure can be reproduced.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+/// `seed` changed a few times: bytes replaced, removed or inserted, syntax inserted, or the end cut
+/// off.
+fn mutate(rng: &mut Rng, seed: &[u8]) -> Vec<u8> {
+    const TOKENS: &[&[u8]] = &[
+        b"<<",
+        b">>",
+        b"[",
+        b"]",
+        b"(",
+        b")",
+        b"<",
+        b">",
+        b"/",
+        b"#",
+        b"\\",
+        b"%",
+        b" R ",
+        b" obj ",
+        b"stream\n",
+        b"ID ",
+        b" EI",
+        b"BI ",
+        b"-",
+        b".",
+        b"e",
+        b"\r\n",
+        b"\x00",
+    ];
+    let mut bytes = seed.to_vec();
+    for _ in 0..1 + rng.below(4) {
+        let at = rng.below(bytes.len() + 1);
+        match rng.below(5) {
+            0 if at < bytes.len() => bytes[at] = rng.next() as u8,
+            1 if at < bytes.len() => {
+                bytes.remove(at);
+            }
+            2 => bytes.insert(at, rng.next() as u8),
+            3 => {
+                let token = TOKENS[rng.below(TOKENS.len())];
+                bytes.splice(at..at, token.iter().copied());
+            }
+            _ => bytes.truncate(at),
+        }
+    }
+    bytes
+}
+
+#[test]
+fn corpus_parses_without_panicking() {
+    for (name, bytes) in corpus() {
+        if panic::catch_unwind(|| parse_all(&bytes)).is_err() {
+            panic!("parsing {name} of the corpus panicked");
+        }
+    }
+}
+
+#[test]
+fn corpus_parses_as_expected() {
+    let corpus: Vec<_> = corpus();
+    let get = |name: &str| &corpus.iter().find(|(file, _)| file == name).unwrap().1;
+
+    let dict = parse::dictionary(get("dictionary.txt")).unwrap();
+    assert_eq!(dict.get(b"Parent").unwrap(), &Object::Reference((3, 0)));
+    let content = parse::content(get("content.txt")).unwrap();
+    assert_eq!(content.operations.len(), 11);
+    assert_eq!(content.operations[10].operator, "BI");
+    assert_eq!(parse::object_id_header(get("header.txt")).unwrap(), ((12, 0), 9));
+    for deep in ["deep_arrays.txt", "deep_dictionaries.txt", "deep_parentheses.txt"] {
+        assert!(parse::object(get(deep)).is_err(), "{deep}");
+    }
+    assert!(parse::object_id_header(get("glued_keyword.txt")).is_err());
+    assert!(parse::object(get("empty.txt")).is_err());
+    assert!(parse::content(get("empty.txt")).unwrap().operations.is_empty());
+}
+
+#[test]
+fn mutated_corpus_parses_without_panicking() {
+    let corpus = corpus();
+    let mut rng = Rng(0x5eed_1e55_0bad_cafe);
+    for _ in 0..20_000 {
+        let (name, seed) = &corpus[rng.below(corpus.len())];
+        let bytes = mutate(&mut rng, seed);
+        if panic::catch_unwind(|| parse_all(&bytes)).is_err() {
+            panic!("parsing {name} mutated to {bytes:?} panicked");
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use lopdf::{Stream, dictionary};
+
+/// Encode `pixels` as a PNG with `filter` and return the zlib stream of its IDAT chunks, which is
+/// what a Flate stream with a PNG predictor holds.
+fn png_image_data(
+    pixels: &[u8], width: u32, height: u32, color: png::ColorType, depth: png::BitDepth, filter: png::Filter,
+) -> Vec<u8> {
+    let mut file = Vec::new();
+    let mut encoder = png::Encoder::new(&mut file, width, height);
+    encoder.set_color(color);
+    encoder.set_depth(depth);
+    encoder.set_filter(filter);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(pixels).unwrap();
+    writer.finish().unwrap();
+
+    let mut data = Vec::new();
+    let mut chunks = &file[8..];
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes(chunks[..4].try_into().unwrap()) as usize;
+        if &chunks[4..8] == b"IDAT" {
+            data.extend_from_slice(&chunks[8..8 + length]);
+        }
+        chunks = &chunks[12 + length..];
+    }
+    data
+}
+
+#[test]
+fn png_predictors_match_png_encoder() {
+    use png::{BitDepth, ColorType, Filter};
+
+    let (width, height) = (13, 5);
+    for (color, colors) in [
+        (ColorType::Grayscale, 1),
+        (ColorType::GrayscaleAlpha, 2),
+        (ColorType::Rgb, 3),
+        (ColorType::Rgba, 4),
+    ] {
+        for (depth, bits) in [
+            (BitDepth::One, 1),
+            (BitDepth::Two, 2),
+            (BitDepth::Four, 4),
+            (BitDepth::Eight, 8),
+            (BitDepth::Sixteen, 16),
+        ] {
+            // PNG only packs grayscale components.
+            if bits < 8 && colors > 1 {
+                continue;
+            }
+            let bytes_per_row = (width as usize * colors * bits).div_ceil(8);
+            let pixels: Vec<u8> = (0..bytes_per_row * height as usize)
+                .map(|i| (i * 73 % 251 + i / 7) as u8)
+                .collect();
+            for filter in [Filter::NoFilter, Filter::Sub, Filter::Up, Filter::Avg, Filter::Paeth, Filter::Adaptive] {
+                let data = png_image_data(&pixels, width, height, color, depth, filter);
+                let stream = Stream::new(
+                    dictionary! {
+                        "Filter" => "FlateDecode",
+                        "DecodeParms" => dictionary! {
+                            "Predictor" => 15,
+                            "Colors" => colors as i64,
+                            "BitsPerComponent" => bits as i64,
+                            "Columns" => i64::from(width),
+                        },
+                    },
+                    data,
+                );
+                assert_eq!(
+                    stream.decompressed_content().unwrap(),
+                    pixels,
+                    "{colors} colors of {bits} bits, {filter:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn tiff_predictor_of_16_bit_rgb() {
+    use flate2::{Compression, write::ZlibEncoder};
+    use std::io::Write;
+
+    // Two rows of two pixels, each pixel the difference from the one to its left.
+    let samples: [u16; 12] = [1000, 2000, 65535, 10, 20, 1, 40000, 0, 5, 30000, 1, 65535];
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&samples.iter().flat_map(|sample| sample.to_be_bytes()).collect::<Vec<u8>>())
+        .unwrap();
+    let stream = Stream::new(
+        dictionary! {
+            "Filter" => "FlateDecode",
+            "DecodeParms" => dictionary! {
+                "Predictor" => 2,
+                "Colors" => 3,
+                "BitsPerComponent" => 16,
+                "Columns" => 2,
+            },
+        },
+        encoder.finish().unwrap(),
+    );
+    let content = stream.decompressed_content().unwrap();
+    let decoded: Vec<u16> = content.chunks(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+    assert_eq!(decoded, [1000, 2000, 65535, 1010, 2020, 0, 40000, 0, 5, 4464, 1, 4]);
+}
@@ -0,0 +1,46 @@
+#[cfg(not(feature = "async"))]
+use lopdf::Document;
+use lopdf::ReadOnlyDocument;
+
+fn assert_send_sync<T: Clone + Send + Sync>() {}
+
+#[test]
+fn read_only_document_is_shareable() {
+    assert_send_sync::<ReadOnlyDocument>();
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn read_only_document_reads_and_saves_copies() {
+    let doc = Document::load("assets/example.pdf").unwrap();
+    let text = doc.extract_text(&[1]).unwrap();
+    let view_pages = doc.as_readonly_view().get_pages();
+    let readonly = doc.into_readonly();
+    assert_eq!(readonly.get_pages(), view_pages);
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = readonly.clone();
+            std::thread::spawn(move || shared.extract_text(&[1]).unwrap())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), text);
+    }
+
+    let mut edited = readonly.to_mutable();
+    edited.delete_pages(&[1]);
+    assert!(edited.get_pages().is_empty());
+    assert_eq!(readonly.get_pages().len(), 1, "the shared document is untouched");
+
+    let mut saved = Vec::new();
+    readonly.save_to(&mut saved).unwrap();
+    let reloaded = Document::load_mem(&saved).unwrap();
+    assert_eq!(reloaded.extract_text(&[1]).unwrap(), text);
+}
+
+#[test]
+fn read_only_document_rejects_mutation() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/read_only_*.rs");
+}
@@ -0,0 +1,141 @@
+use std::time::{Duration, Instant};
+
+use lopdf::content::{Content, Operation};
+use lopdf::{Document, Object, SaveOptions, Stream, dictionary};
+
+const PAGES: usize = 32;
+
+/// Bytes which don't compress, as the entropy coded data of JPEG images.
+fn noise(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// A scanned document: each page a JPEG image of 256 KiB, drawn by an uncompressed content stream.
+fn scanned_document() -> Document {
+    let mut doc = Document::with_version("1.7");
+    let pages_id = doc.new_object_id();
+    let mut kids = Vec::new();
+    for page in 0..PAGES {
+        let image = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 1700,
+                "Height" => 2200,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+                "Filter" => "DCTDecode",
+            },
+            noise(page as u64 + 1, 256 * 1024),
+        );
+        let image_id = doc.add_object(image);
+        let content = Content {
+            operations: vec![
+                Operation::new("q", vec![]),
+                Operation::new(
+                    "cm",
+                    vec![612.into(), 0.into(), 0.into(), 792.into(), 0.into(), 0.into()],
+                ),
+                Operation::new("Do", vec![Object::Name(b"Im0".to_vec())]),
+                Operation::new("Q", vec![]),
+            ],
+        };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap().repeat(20)));
+        kids.push(
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+                "Resources" => dictionary! { "XObject" => dictionary! { "Im0" => image_id } },
+            })
+            .into(),
+        );
+    }
+    doc.objects.insert(
+        pages_id,
+        dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => PAGES as i64,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }
+        .into(),
+    );
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+    doc
+}
+
+/// The fastest of a few saves of the scanned document with `options`.
+fn save_time(options: impl Fn() -> SaveOptions) -> Duration {
+    (0..3)
+        .map(|_| {
+            let mut doc = scanned_document();
+            let start = Instant::now();
+            doc.save_with_options(&mut Vec::new(), options()).unwrap();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn saving_with_compression_leaves_jpeg_images_alone() {
+    let mut doc = scanned_document();
+    let mut saved = Vec::new();
+    let options = SaveOptions::builder().compress_streams(true).build().unwrap();
+    let report = doc.save_with_report(&mut saved, options).unwrap();
+
+    let summary = report.compression.unwrap();
+    assert_eq!(summary.compressed, PAGES);
+    assert_eq!(summary.skipped_by_filter.get("DCTDecode"), Some(&PAGES));
+    assert_eq!(summary.skipped_filtered(), PAGES);
+    assert_eq!(summary.skipped_incompressible, 0);
+    assert!(summary.bytes_saved > 0);
+
+    let saved = Document::load_mem(&saved).unwrap();
+    let original = scanned_document();
+    let mut images = 0;
+    for (id, object) in &original.objects {
+        let Ok(original) = object.as_stream() else { continue };
+        let stream = saved.get_object(*id).unwrap().as_stream().unwrap();
+        if stream.dict.has_type(b"XObject") {
+            images += 1;
+            assert_eq!(
+                stream.dict.get(b"Filter").unwrap(),
+                &Object::Name(b"DCTDecode".to_vec())
+            );
+            assert_eq!(stream.content, original.content);
+        } else {
+            assert_eq!(stream.filters().unwrap(), [b"FlateDecode"]);
+            assert_eq!(stream.decompressed_content().unwrap(), original.content);
+        }
+    }
+    assert_eq!(images, PAGES);
+}
+
+#[test]
+fn saving_scanned_pages_with_compression_takes_about_as_long_as_without() {
+    let without = save_time(SaveOptions::default);
+    let with = save_time(|| SaveOptions::builder().compress_streams(true).build().unwrap());
+    // Deflating the images instead would take many times as long as writing them.
+    assert!(
+        with < without * 2 + Duration::from_millis(50),
+        "saving took {with:?} compressing streams, {without:?} without"
+    );
+}
+
+#[test]
+fn saving_without_compression_reports_none() {
+    let mut doc = scanned_document();
+    let report = doc.save_with_report(&mut Vec::new(), SaveOptions::default()).unwrap();
+    assert_eq!(report.compression, None);
+}
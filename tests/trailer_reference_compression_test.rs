@@ -288,7 +288,8 @@ fn test_compression_with_save_integration() {
     // Save with object streams
     let options = SaveOptions::builder()
         .use_object_streams(true)
-        .build();
+        .build()
+        .unwrap();
     
     let mut output = Vec::new();
     doc.save_with_options(&mut output, options).unwrap();
@@ -0,0 +1,4 @@
+fn main() {
+    let doc = lopdf::Document::new().into_readonly();
+    doc.get_object_mut((1, 0)).unwrap();
+}
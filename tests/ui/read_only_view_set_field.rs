@@ -0,0 +1,5 @@
+fn main() {
+    let doc = lopdf::Document::new();
+    let view = doc.as_readonly_view();
+    view.trailer.set("Root", lopdf::Object::Null);
+}
@@ -120,10 +120,9 @@ fn unicode_can_be_written_to_pdf_and_read() {
 fn get_text_from_first_page(doc: &Document) -> String {
     let mut pages = doc.get_pages();
     let first_page = pages.first_entry().expect("Expected pages to be non empty");
-    let extracted_text = doc
+    doc
         .extract_text(&[*first_page.key()])
-        .expect("Expected to find text on the first page");
-    extracted_text
+        .expect("Expected to find text on the first page")
 }
 
 #[cfg(not(feature = "async"))]